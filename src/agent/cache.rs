@@ -0,0 +1,232 @@
+use crate::config::AgentConfig;
+use crate::providers::{ChatMessage, ChatResponse};
+use crate::tools::{ToolCall, ToolSpec};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// On-disk cache of provider responses, so repeating a prompt during
+/// development doesn't pay for the same answer twice.
+///
+/// Layout:
+///   workspace/cache/responses/<sha256>.json — one cached response
+///
+/// Entries are keyed on the full request (messages including the system
+/// prompt, tool specs, model and temperature) and expire after `ttl`.
+/// Calls with a temperature above zero are not cached unless
+/// `agent.cache_nondeterministic` is set. Read and write failures are
+/// logged and treated as misses; the cache never fails a turn.
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Duration,
+    nondeterministic: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Unix seconds when the response was stored
+    created_at: i64,
+    model: String,
+    text: String,
+    #[serde(default)]
+    tool_calls: Vec<CachedToolCall>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedToolCall {
+    name: String,
+    arguments: serde_json::Value,
+}
+
+#[derive(Serialize)]
+struct CacheKey<'a> {
+    model: &'a str,
+    temperature: f64,
+    messages: &'a [ChatMessage],
+    tools: &'a [ToolSpec],
+}
+
+impl ResponseCache {
+    pub fn new(workspace_dir: &Path, ttl: Duration, nondeterministic: bool) -> Self {
+        Self {
+            dir: workspace_dir.join("cache").join("responses"),
+            ttl,
+            nondeterministic,
+        }
+    }
+
+    /// The cache configured by `[agent]`, or `None` when `agent.cache` is off.
+    pub fn from_config(config: &AgentConfig, workspace_dir: &Path) -> Option<Self> {
+        config.cache.then(|| {
+            Self::new(
+                workspace_dir,
+                Duration::from_secs(config.cache_ttl_secs),
+                config.cache_nondeterministic,
+            )
+        })
+    }
+
+    /// Cache key for a request, or `None` if the request shouldn't be cached.
+    pub fn key(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> Option<String> {
+        if temperature > 0.0 && !self.nondeterministic {
+            return None;
+        }
+        let request = serde_json::to_vec(&CacheKey {
+            model,
+            temperature,
+            messages,
+            tools,
+        })
+        .ok()?;
+        Some(hex::encode(Sha256::digest(&request)))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The stored response for `key`, if there is one that hasn't expired.
+    /// Expired entries are removed.
+    pub fn get(&self, key: &str) -> Option<ChatResponse> {
+        let path = self.path(key);
+        let raw = match std::fs::read(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Failed to read cached response: {e}");
+                return None;
+            }
+        };
+        let entry: CacheEntry = match serde_json::from_slice(&raw) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Ignoring invalid cached response: {e}");
+                return None;
+            }
+        };
+
+        let age = chrono::Utc::now().timestamp() - entry.created_at;
+        if u64::try_from(age).map_or(true, |age| age >= self.ttl.as_secs()) {
+            let _ = std::fs::remove_file(&path);
+            return None;
+        }
+        tracing::debug!(key, model = entry.model, "Response cache hit");
+        Some(ChatResponse {
+            text: entry.text,
+            tool_calls: entry
+                .tool_calls
+                .into_iter()
+                .map(|call| ToolCall {
+                    name: call.name,
+                    arguments: call.arguments,
+                })
+                .collect(),
+            ..ChatResponse::default()
+        })
+    }
+
+    /// Store `response` under `key`.
+    pub fn put(&self, key: &str, model: &str, response: &ChatResponse) {
+        let entry = CacheEntry {
+            created_at: chrono::Utc::now().timestamp(),
+            model: model.to_string(),
+            text: response.text.clone(),
+            tool_calls: response
+                .tool_calls
+                .iter()
+                .map(|call| CachedToolCall {
+                    name: call.name.clone(),
+                    arguments: call.arguments.clone(),
+                })
+                .collect(),
+        };
+        let path = self.path(key);
+        let result = std::fs::create_dir_all(&self.dir)
+            .and_then(|()| std::fs::write(&path, serde_json::to_vec(&entry)?));
+        if let Err(e) = result {
+            tracing::warn!(path = %path.display(), "Failed to cache response: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn response(text: &str) -> ChatResponse {
+        ChatResponse {
+            text: text.into(),
+            tool_calls: vec![ToolCall {
+                name: "shell".into(),
+                arguments: serde_json::json!({"command": "ls"}),
+            }],
+            ..ChatResponse::default()
+        }
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ResponseCache::new(tmp.path(), Duration::from_secs(60), false);
+        let messages = [ChatMessage::system("sys"), ChatMessage::user("hi")];
+        let key = cache.key(&messages, &[], "model-a", 0.0).unwrap();
+
+        assert!(cache.get(&key).is_none());
+        cache.put(&key, "model-a", &response("hello"));
+        let hit = cache.get(&key).unwrap();
+        assert_eq!(hit.text, "hello");
+        assert_eq!(hit.tool_calls, response("hello").tool_calls);
+        assert!(hit.usage.is_none());
+        assert!(tmp
+            .path()
+            .join("cache/responses")
+            .join(format!("{key}.json"))
+            .exists());
+    }
+
+    #[test]
+    fn key_depends_on_model_and_messages() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ResponseCache::new(tmp.path(), Duration::from_secs(60), false);
+        let messages = [ChatMessage::user("hi")];
+        let key = cache.key(&messages, &[], "model-a", 0.0).unwrap();
+        assert_eq!(cache.key(&messages, &[], "model-a", 0.0).unwrap(), key);
+        assert_ne!(cache.key(&messages, &[], "model-b", 0.0).unwrap(), key);
+        assert_ne!(
+            cache
+                .key(&[ChatMessage::user("hello")], &[], "model-a", 0.0)
+                .unwrap(),
+            key
+        );
+    }
+
+    #[test]
+    fn nonzero_temperature_bypasses_cache_unless_allowed() {
+        let tmp = TempDir::new().unwrap();
+        let messages = [ChatMessage::user("hi")];
+        let strict = ResponseCache::new(tmp.path(), Duration::from_secs(60), false);
+        assert!(strict.key(&messages, &[], "m", 0.7).is_none());
+        let relaxed = ResponseCache::new(tmp.path(), Duration::from_secs(60), true);
+        assert!(relaxed.key(&messages, &[], "m", 0.7).is_some());
+    }
+
+    #[test]
+    fn expired_entries_are_removed() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ResponseCache::new(tmp.path(), Duration::ZERO, false);
+        let key = cache
+            .key(&[ChatMessage::user("hi")], &[], "m", 0.0)
+            .unwrap();
+        cache.put(&key, "m", &response("stale"));
+        assert!(cache.get(&key).is_none());
+        assert!(!cache.path(&key).exists());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::tools::ToolResult;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a recorded result answers a replayed call.
+const RECORD_TTL: Duration = Duration::from_mins(10);
+
+/// Short-lived, in-memory record of the results of side-effecting tool calls
+/// (those whose tool returns `true` from `Tool::idempotency_key_supported`),
+/// so a turn that is run again — by a retry or a fallback provider — gets the
+/// earlier result back instead of acting twice.
+///
+/// Entries are keyed by `key` and dropped after `ttl`. Each `run` and
+/// `AgentRunner` holds its own records; nothing is written to disk.
+pub struct IdempotencyRecords {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, ToolResult)>>,
+}
+
+impl IdempotencyRecords {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Key for a call identified by `call_key` (tool name and canonical
+    /// arguments), made on `iteration` of the turn `turn_id`.
+    pub fn key(turn_id: &str, iteration: usize, call_key: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(turn_id.as_bytes());
+        hasher.update(iteration.to_le_bytes());
+        hasher.update(call_key.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+
+    /// The result recorded under `key`, if it hasn't expired. Expired entries
+    /// are removed.
+    pub fn get(&self, key: &str) -> Option<ToolResult> {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let ttl = self.ttl;
+        entries.retain(|_, (recorded, _)| recorded.elapsed() < ttl);
+        entries.get(key).map(|(_, result)| result.clone())
+    }
+
+    /// Record `result` under `key`.
+    pub fn put(&self, key: String, result: ToolResult) {
+        self.entries
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(key, (Instant::now(), result));
+    }
+}
+
+impl Default for IdempotencyRecords {
+    fn default() -> Self {
+        Self::new(RECORD_TTL)
+    }
+}
+
+/// One turn's view of the records: a turn id made up when the turn starts.
+/// Running the turn again with the same `TurnKeys` replays its recorded
+/// results; a new turn, even with identical messages, gets a new id.
+pub struct TurnKeys<'a> {
+    records: &'a IdempotencyRecords,
+    turn_id: String,
+}
+
+impl<'a> TurnKeys<'a> {
+    /// Keys for a new turn.
+    pub fn start(records: &'a IdempotencyRecords) -> Self {
+        Self {
+            records,
+            turn_id: uuid::Uuid::new_v4().to_string(),
+        }
+    }
+
+    /// The result recorded for `call_key` on `iteration` of this turn.
+    pub fn get(&self, iteration: usize, call_key: &str) -> Option<ToolResult> {
+        self.records
+            .get(&IdempotencyRecords::key(&self.turn_id, iteration, call_key))
+    }
+
+    /// Record `result` for `call_key` on `iteration` of this turn.
+    pub fn put(&self, iteration: usize, call_key: &str, result: ToolResult) {
+        self.records.put(
+            IdempotencyRecords::key(&self.turn_id, iteration, call_key),
+            result,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(output: &str) -> ToolResult {
+        ToolResult {
+            success: true,
+            output: output.into(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn key_depends_on_turn_iteration_and_call() {
+        let key = IdempotencyRecords::key("turn-a", 0, "http_request\n{}");
+        assert_eq!(
+            IdempotencyRecords::key("turn-a", 0, "http_request\n{}"),
+            key
+        );
+        assert_ne!(
+            IdempotencyRecords::key("turn-b", 0, "http_request\n{}"),
+            key
+        );
+        assert_ne!(
+            IdempotencyRecords::key("turn-a", 1, "http_request\n{}"),
+            key
+        );
+        assert_ne!(IdempotencyRecords::key("turn-a", 0, "file_append\n{}"), key);
+    }
+
+    #[test]
+    fn put_then_get_round_trips() {
+        let records = IdempotencyRecords::new(Duration::from_secs(60));
+        assert!(records.get("k").is_none());
+        records.put("k".into(), result("sent"));
+        assert_eq!(records.get("k").unwrap().output, "sent");
+    }
+
+    #[test]
+    fn separate_turns_do_not_share_results() {
+        let records = IdempotencyRecords::default();
+        let first = TurnKeys::start(&records);
+        first.put(0, "http_request\n{}", result("sent"));
+        assert_eq!(first.get(0, "http_request\n{}").unwrap().output, "sent");
+        assert!(TurnKeys::start(&records)
+            .get(0, "http_request\n{}")
+            .is_none());
+    }
+
+    #[test]
+    fn expired_records_are_removed() {
+        let records = IdempotencyRecords::new(Duration::ZERO);
+        records.put("k".into(), result("sent"));
+        assert!(records.get("k").is_none());
+        assert!(records.entries.lock().unwrap().is_empty());
+    }
+}
@@ -1,62 +1,301 @@
-use crate::config::Config;
-use crate::memory::{self, Memory, MemoryCategory};
-use crate::observability::{self, Observer, ObserverEvent};
-use crate::providers::{self, Provider};
+use super::cache::ResponseCache;
+use super::idempotency::{IdempotencyRecords, TurnKeys};
+use super::report::{OutputMode, RunReport, ToolCallLog};
+use super::session::SessionStore;
+use super::structured::{InvalidJsonResponse, JsonResponse};
+use super::subagent::SubAgentTool;
+use crate::channels::cli::SlashCommand;
+use crate::channels::traits::ChannelMessage;
+use crate::channels::{Channel, CliChannel};
+use crate::config::{Config, ToolHistory};
+use crate::memory::save_summary::SaveSummary;
+use crate::memory::{self, Memory, MemoryCategory, MemoryEntry};
+use crate::observability::{self, Observer, ObserverEvent, ObserverMetric};
+use crate::providers::bedrock::GuardrailIntervened;
+use crate::providers::tokenizer::{self, Tokenizer};
+use crate::providers::traits::StreamEvent;
+use crate::providers::{self, ChatMessage, Provider};
 use crate::runtime;
+use crate::security::output_filter::{self, OutputBlocked};
 use crate::security::SecurityPolicy;
-use crate::tools::{self, Tool};
-use crate::util::truncate_with_ellipsis;
+use crate::tools::artifact::{self, Artifact};
+use crate::tools::{self, SessionContext, Tool, ToolCall, ToolSpec};
+use crate::util::{truncate_smart, truncate_with_ellipsis, ContentKind};
 use anyhow::Result;
+use futures_util::future::join_all;
+use futures_util::StreamExt;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt::Write;
-use std::sync::Arc;
-use std::time::Instant;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 /// Maximum tool-calling iterations per user message to prevent runaway loops.
-const MAX_TOOL_ITERATIONS: usize = 10;
+pub(super) const MAX_TOOL_ITERATIONS: usize = 10;
 
-/// A parsed tool invocation from the LLM response.
-#[derive(Debug, Clone)]
-pub struct ToolCall {
-    pub name: String,
-    pub arguments: Value,
+/// Parse tool calls from a response string.
+///
+/// `<tool_call>...</tool_call>` blocks are the primary format. Models that
+/// instead wrap the call in a ```` ```json ```` fence are also understood, but
+/// a fenced object only counts when it has both `name` and `arguments` and
+/// the name is one of `tool_names`, so ordinary JSON in an answer is left alone.
+/// Calls are returned in the order they appear.
+pub fn parse_tool_calls(response: &str, tool_names: &[&str]) -> Vec<ToolCall> {
+    parse_tool_call_blocks(response, tool_names).0
+}
+
+/// Like `parse_tool_calls`, but also returns why each unparseable
+/// `<tool_call>` block was rejected, so the model can be told to retry.
+fn parse_tool_call_blocks(response: &str, tool_names: &[&str]) -> (Vec<ToolCall>, Vec<String>) {
+    let mut blocks = tagged_tool_calls(response);
+    blocks.extend(
+        fenced_tool_calls(response, tool_names)
+            .into_iter()
+            .map(|(position, call)| (position, Ok(call))),
+    );
+    blocks.sort_by_key(|(position, _)| *position);
+
+    let mut calls = Vec::new();
+    let mut errors = Vec::new();
+    for (_, block) in blocks {
+        match block {
+            Ok(call) => calls.push(call),
+            Err(reason) => errors.push(reason),
+        }
+    }
+    (calls, errors)
+}
+
+/// Parse tool-call JSON, retrying once with `repair_json` if the strict parse
+/// fails. The strict parser's error is reported if both fail.
+fn parse_call_json(json_str: &str) -> std::result::Result<Value, String> {
+    serde_json::from_str(json_str).or_else(|strict| {
+        serde_json::from_str(&repair_json(json_str)).map_err(|_| strict.to_string())
+    })
+}
+
+/// Fix common model JSON slips: trailing commas before `}`/`]`, and raw
+/// newlines, carriage returns and tabs inside strings.
+fn repair_json(json_str: &str) -> String {
+    let mut out = String::with_capacity(json_str.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut chars = json_str.chars();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                _ if escaped => {
+                    escaped = false;
+                    out.push(c);
+                }
+                '\\' => {
+                    escaped = true;
+                    out.push(c);
+                }
+                '"' => {
+                    in_string = false;
+                    out.push(c);
+                }
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                _ => out.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}' | ']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Models sometimes send `arguments` as a JSON-encoded string; decode it.
+fn decode_double_encoded(arguments: Value) -> Value {
+    if let Value::String(encoded) = &arguments {
+        if encoded.trim_start().starts_with('{') {
+            if let Ok(decoded @ Value::Object(_)) = parse_call_json(encoded) {
+                return decoded;
+            }
+        }
+    }
+    arguments
+}
+
+/// Where a `<tool_call>` block ends, as found by `find_block_end`.
+enum BlockEnd {
+    /// Byte offset of the closing tag
+    Closed(usize),
+    /// Byte offset of another opening tag that came before any closing tag
+    Reopened(usize),
+}
+
+/// Find the end of the `<tool_call>` block whose content starts at `from`.
+///
+/// Tags inside JSON string literals (e.g. in a file's content) don't count.
+/// If tracking strings finds no closing tag, say after an unterminated
+/// string, the first closing tag anywhere is used so the block can still be
+/// reported as malformed.
+fn find_block_end(response: &str, from: usize) -> Option<BlockEnd> {
+    let bytes = response.as_bytes();
+    let mut in_string = false;
+    let mut escaped = false;
+    for i in from..bytes.len() {
+        let b = bytes[i];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else if b == b'"' {
+                in_string = false;
+            }
+        } else if b == b'"' {
+            in_string = true;
+        } else if bytes[i..].starts_with(b"</tool_call>") {
+            return Some(BlockEnd::Closed(i));
+        } else if bytes[i..].starts_with(b"<tool_call>") {
+            return Some(BlockEnd::Reopened(i));
+        }
+    }
+    response[from..]
+        .find("</tool_call>")
+        .map(|end| BlockEnd::Closed(from + end))
 }
 
-/// Parse all `<tool_call>...</tool_call>` blocks from a response string.
-pub fn parse_tool_calls(response: &str) -> Vec<ToolCall> {
+/// Calls in `<tool_call>` blocks, with their byte offsets. Blocks that cannot
+/// be parsed yield the reason instead; so does a block opened again before
+/// it was closed, after which parsing resumes at the second opening tag.
+fn tagged_tool_calls(response: &str) -> Vec<(usize, std::result::Result<ToolCall, String>)> {
     let mut calls = Vec::new();
     let mut search_from = 0;
 
-    loop {
-        let Some(start_tag) = response[search_from..].find("<tool_call>") else {
-            break;
+    while let Some(start_tag) = response[search_from..].find("<tool_call>") {
+        let block_start = search_from + start_tag;
+        let content_start = block_start + "<tool_call>".len();
+
+        let content_end = match find_block_end(response, content_start) {
+            None => break,
+            Some(BlockEnd::Reopened(next)) => {
+                calls.push((
+                    block_start,
+                    Err("<tool_call> was not closed before the next <tool_call>".to_string()),
+                ));
+                search_from = next;
+                continue;
+            }
+            Some(BlockEnd::Closed(end)) => end,
         };
-        let content_start = search_from + start_tag + "<tool_call>".len();
 
-        let Some(end_tag) = response[content_start..].find("</tool_call>") else {
+        let json_str = response[content_start..content_end].trim();
+        let call = parse_call_json(json_str).and_then(|parsed| {
+            let name = parsed
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or_else(|| "missing \"name\"".to_string())?;
+            let arguments = parsed
+                .get("arguments")
+                .cloned()
+                .map_or(Value::Object(Default::default()), decode_double_encoded);
+            Ok(ToolCall {
+                name: name.to_string(),
+                arguments,
+            })
+        });
+        calls.push((block_start, call));
+
+        search_from = content_end + "</tool_call>".len();
+    }
+
+    calls
+}
+
+/// Calls in ```` ```json ```` fences naming a registered tool, with their byte offsets.
+fn fenced_tool_calls(response: &str, tool_names: &[&str]) -> Vec<(usize, ToolCall)> {
+    const FENCE_OPEN: &str = "```json";
+    const FENCE_CLOSE: &str = "```";
+
+    let mut calls = Vec::new();
+    let mut search_from = 0;
+
+    while let Some(start) = response[search_from..].find(FENCE_OPEN) {
+        let fence_start = search_from + start;
+        let content_start = fence_start + FENCE_OPEN.len();
+        let Some(end) = response[content_start..].find(FENCE_CLOSE) else {
             break;
         };
-        let content_end = content_start + end_tag;
+        let content_end = content_start + end;
 
-        let json_str = response[content_start..content_end].trim();
-        if let Ok(parsed) = serde_json::from_str::<Value>(json_str) {
-            if let (Some(name), arguments) = (
-                parsed.get("name").and_then(|n| n.as_str()),
-                parsed.get("arguments").cloned().unwrap_or(Value::Object(Default::default())),
+        if let Ok(parsed) =
+            serde_json::from_str::<Value>(response[content_start..content_end].trim())
+        {
+            if let (Some(name), Some(arguments)) = (
+                parsed.get("name").and_then(Value::as_str),
+                parsed.get("arguments"),
             ) {
-                calls.push(ToolCall {
-                    name: name.to_string(),
-                    arguments,
-                });
+                if tool_names.contains(&name) {
+                    calls.push((
+                        fence_start,
+                        ToolCall {
+                            name: name.to_string(),
+                            arguments: arguments.clone(),
+                        },
+                    ));
+                }
             }
         }
 
-        search_from = content_end + "</tool_call>".len();
+        search_from = content_end + FENCE_CLOSE.len();
     }
 
     calls
 }
 
+/// Sent back when a response looks like a botched tool call.
+const MALFORMED_TOOL_CALL_PROMPT: &str =
+    "[Tool call error] Your previous tool_call was malformed. \
+Re-emit it as valid JSON: <tool_call>{\"name\": \"...\", \"arguments\": {...}}</tool_call>";
+
+/// Sent once the loop has used up its iterations while still calling tools.
+const ITERATION_LIMIT_PROMPT: &str = "[Iteration limit reached] You cannot call any more tools. \
+Answer now with your best response based on the tool results you already have.";
+
+/// Appended to the partial answer when even the final call asks for tools.
+const ITERATION_LIMIT_NOTE: &str = "[iteration limit reached]";
+
+/// Whether a response with no parseable calls still looks like the model
+/// meant to make one: a `<tool_call>` tag that is never closed, or a fenced
+/// block mentioning a registered tool by name.
+fn looks_like_intended_tool_call(response: &str, tool_names: &[&str]) -> bool {
+    if let Some(open) = response.rfind("<tool_call>") {
+        if !response[open..].contains("</tool_call>") {
+            return true;
+        }
+    }
+
+    response.split("```").skip(1).step_by(2).any(|block| {
+        tool_names
+            .iter()
+            .any(|name| block.contains(&format!("\"{name}\"")))
+    })
+}
+
 /// Extract the text portions of a response (everything outside `<tool_call>` blocks).
 fn extract_text_outside_tool_calls(response: &str) -> String {
     let mut text = String::new();
@@ -70,434 +309,5288 @@ fn extract_text_outside_tool_calls(response: &str) -> String {
         text.push_str(&response[search_from..search_from + start_tag]);
 
         let content_start = search_from + start_tag + "<tool_call>".len();
-        let Some(end_tag) = response[content_start..].find("</tool_call>") else {
-            break;
+        search_from = match find_block_end(response, content_start) {
+            None => break,
+            Some(BlockEnd::Reopened(next)) => next,
+            Some(BlockEnd::Closed(end)) => end + "</tool_call>".len(),
         };
-        search_from = content_start + end_tag + "</tool_call>".len();
     }
 
     let trimmed = text.trim();
     trimmed.to_string()
 }
 
-/// Execute parsed tool calls against the tool registry.
-async fn execute_tool_calls(
-    tools: &[Box<dyn Tool>],
-    calls: &[ToolCall],
-) -> Vec<(String, crate::tools::ToolResult)> {
-    let mut results = Vec::with_capacity(calls.len());
-
-    for call in calls {
-        let tool = tools.iter().find(|t| t.name() == call.name);
-        let result = match tool {
-            Some(t) => match t.execute(call.arguments.clone()).await {
-                Ok(r) => r,
-                Err(e) => crate::tools::ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!("Tool execution error: {e}")),
-                },
-            },
-            None => crate::tools::ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!("Unknown tool: {}", call.name)),
-            },
-        };
-        results.push((call.name.clone(), result));
-    }
-
-    results
-}
-
-/// Format tool results as XML blocks for feeding back to the LLM.
-pub fn format_tool_results(results: &[(String, crate::tools::ToolResult)]) -> String {
+/// Render tool calls in the `<tool_call>` tag format, so natively returned
+/// calls can be recorded in the text-only conversation history.
+fn format_tool_calls(calls: &[ToolCall]) -> String {
     let mut out = String::new();
-    for (name, result) in results {
+    for call in calls {
         let json = serde_json::json!({
-            "success": result.success,
-            "output": result.output,
-            "error": result.error,
+            "name": call.name,
+            "arguments": call.arguments,
         });
-        let _ = write!(
-            out,
-            "<tool_result name=\"{name}\">{}</tool_result>\n",
-            serde_json::to_string(&json).unwrap_or_else(|_| "{}".into())
-        );
+        let _ = write!(out, "\n<tool_call>{json}</tool_call>");
     }
     out
 }
 
-/// Run the tool-calling loop: call LLM, parse tool calls, execute, feed results back, repeat.
-///
-/// Returns the final text response (after all tool calls are resolved).
-async fn tool_calling_loop(
-    provider: &dyn Provider,
-    system_prompt: &str,
-    initial_message: &str,
-    model_name: &str,
-    temperature: f64,
-    tools: &[Box<dyn Tool>],
-) -> Result<String> {
-    // Build conversation as alternating user/assistant messages.
-    // The provider is stateless, so we pass the full conversation each iteration
-    // by concatenating into a single user message (since `chat_with_system` takes one string).
-    let mut conversation = initial_message.to_string();
-    let mut final_text = String::new();
-
-    for iteration in 0..MAX_TOOL_ITERATIONS {
-        let response = provider
-            .chat_with_system(Some(system_prompt), &conversation, model_name, temperature)
-            .await?;
+/// Whether `value` matches a JSON Schema type name. Unknown names pass, so
+/// schemas using keywords we don't check never block a call.
+fn matches_json_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
 
-        let calls = parse_tool_calls(&response);
+/// Check tool-call arguments against the tool's parameter schema: required
+/// arguments must be present and top-level values must have the declared
+/// type. The error names the offending argument so the model can fix its call.
+fn validate_tool_arguments(schema: &Value, args: &Value) -> Result<(), String> {
+    let empty = serde_json::Map::new();
+    let provided = match args {
+        Value::Object(map) => map,
+        Value::Null => &empty,
+        other => return Err(format!("arguments must be a JSON object, got {other}")),
+    };
 
-        // Extract and print any text the LLM produced alongside tool calls
-        let text = extract_text_outside_tool_calls(&response);
-        if !text.is_empty() {
-            if iteration > 0 || !calls.is_empty() {
-                // Print intermediate thinking
-                eprintln!("{text}");
-            }
+    let required = schema.get("required").and_then(Value::as_array);
+    for name in required.into_iter().flatten().filter_map(Value::as_str) {
+        if provided.get(name).is_none_or(Value::is_null) {
+            return Err(format!("missing required argument '{name}'"));
         }
+    }
 
-        if calls.is_empty() {
-            // No tool calls — this is the final response
-            final_text = response;
-            break;
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, value) in provided {
+        // Optional arguments sent as null are treated as omitted
+        if value.is_null() {
+            continue;
         }
-
-        tracing::debug!(
-            iteration,
-            num_calls = calls.len(),
-            "Executing tool calls"
-        );
-
-        let results = execute_tool_calls(tools, &calls).await;
-
-        // Log tool results
-        for (name, result) in &results {
-            if result.success {
-                tracing::debug!(tool = name, "Tool succeeded");
-            } else {
-                tracing::warn!(
-                    tool = name,
-                    error = result.error.as_deref().unwrap_or("unknown"),
-                    "Tool failed"
-                );
-            }
+        let Some(expected) = properties.and_then(|p| p.get(name)?.get("type")) else {
+            continue;
+        };
+        let ok = match expected {
+            Value::String(ty) => matches_json_type(value, ty),
+            Value::Array(types) => types
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|ty| matches_json_type(value, ty)),
+            _ => true,
+        };
+        if !ok {
+            return Err(format!(
+                "argument '{name}' must be of type {expected}, got {value}"
+            ));
         }
-
-        // Build the next conversation turn: original message + assistant response + tool results
-        let tool_results_text = format_tool_results(&results);
-        let _ = write!(
-            conversation,
-            "\n\n[Assistant]\n{response}\n\n[Tool Results]\n{tool_results_text}"
-        );
     }
-
-    Ok(final_text)
+    Ok(())
 }
 
-/// Build context preamble by searching memory for relevant entries
-async fn build_context(mem: &dyn Memory, user_msg: &str) -> String {
-    let mut context = String::new();
+/// Longest argument summary reported to observers.
+const TOOL_ARGS_SUMMARY_CHARS: usize = 200;
 
-    // Pull relevant memories for this message
-    if let Ok(entries) = mem.recall(user_msg, 5).await {
-        if !entries.is_empty() {
-            context.push_str("[Memory context]\n");
-            for entry in &entries {
-                let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
-            }
-            context.push('\n');
-        }
-    }
+/// Output reported for calls skipped in dry-run mode.
+const DRY_RUN_OUTPUT: &str = "[dry-run: not executed]";
 
-    context
+/// Compact, log-safe rendering of tool arguments: secret-looking fields are
+/// masked and the result is shortened, staying valid JSON.
+fn summarize_arguments(arguments: &Value) -> String {
+    let redacted = crate::security::audit::redact_secrets(
+        arguments,
+        crate::security::audit::DEFAULT_REDACT_FIELDS,
+    );
+    truncate_smart(
+        &redacted.to_string(),
+        TOOL_ARGS_SUMMARY_CHARS,
+        ContentKind::Json,
+    )
 }
 
-#[allow(clippy::too_many_lines)]
-pub async fn run(
-    config: Config,
-    message: Option<String>,
-    provider_override: Option<String>,
-    model_override: Option<String>,
-    temperature: f64,
-) -> Result<()> {
-    // ── Wire up agnostic subsystems ──────────────────────────────
-    let observer: Arc<dyn Observer> =
-        Arc::from(observability::create_observer(&config.observability));
-    let runtime: Arc<dyn runtime::RuntimeAdapter> =
-        Arc::from(runtime::create_runtime(&config.runtime)?);
-    let security = Arc::new(SecurityPolicy::from_config(
-        &config.autonomy,
-        &config.workspace_dir,
-    ));
-
-    // ── Memory (the brain) ────────────────────────────────────────
-    let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
-        &config.memory,
-        &config.workspace_dir,
-        config.api_key.as_deref(),
-    )?);
-    tracing::info!(backend = mem.name(), "Memory initialized");
+/// Execute a single tool call against the tool registry, reporting
+/// `ToolStart`/`ToolEnd` to `observer` around it.
+///
+/// Arguments are validated against the tool's schema first; a mismatch is
+/// reported as a failed result without running the tool. The call is
+/// cancelled (its future dropped) once it exceeds the tool's own timeout or
+/// `default_timeout`, and reported as a failed result. Failures the tool
+/// calls transient (`Tool::is_retryable`) are retried as `retry` allows.
+async fn execute_tool_call(
+    tools: &[Box<dyn Tool>],
+    call: &ToolCall,
+    default_timeout: Duration,
+    dry_run: bool,
+    retry: ToolRetry,
+    observer: &dyn Observer,
+) -> crate::tools::ToolResult {
+    observer.record_event(&ObserverEvent::ToolStart {
+        name: call.name.clone(),
+        arguments_summary: summarize_arguments(&call.arguments),
+    });
+    let started = Instant::now();
+    let result = run_tool_call(tools, call, default_timeout, dry_run, retry).await;
+    observer.record_event(&ObserverEvent::ToolEnd {
+        name: call.name.clone(),
+        success: result.success,
+        duration: started.elapsed(),
+    });
+    result
+}
 
-    // ── Tools (including memory tools) ────────────────────────────
-    let composio_key = if config.composio.enabled {
-        config.composio.api_key.as_deref()
-    } else {
-        None
+async fn run_tool_call(
+    tools: &[Box<dyn Tool>],
+    call: &ToolCall,
+    default_timeout: Duration,
+    dry_run: bool,
+    retry: ToolRetry,
+) -> crate::tools::ToolResult {
+    let Some(t) = tools.iter().find(|t| t.name() == call.name) else {
+        return crate::tools::ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!("Unknown tool: {}", call.name)),
+        };
     };
-    let agent_tools = tools::all_tools_with_runtime(
-        &security,
-        runtime,
-        mem.clone(),
-        composio_key,
-        &config.browser,
-    );
 
-    // ── Resolve provider ─────────────────────────────────────────
-    let provider_name = provider_override
-        .as_deref()
-        .or(config.default_provider.as_deref())
-        .unwrap_or("openrouter");
+    if let Err(e) = validate_tool_arguments(&t.parameters_schema(), &call.arguments) {
+        return crate::tools::ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!("Invalid arguments for {}: {e}", call.name)),
+        };
+    }
 
-    let model_name = model_override
-        .as_deref()
-        .or(config.default_model.as_deref())
-        .unwrap_or("anthropic/claude-sonnet-4-20250514");
+    if dry_run && !t.is_read_only() {
+        eprintln!("[dry-run] {} {}", call.name, call.arguments);
+        return crate::tools::ToolResult {
+            success: true,
+            output: DRY_RUN_OUTPUT.into(),
+            error: None,
+        };
+    }
 
-    let provider: Box<dyn Provider> = providers::create_routed_provider(
-        provider_name,
-        config.api_key.as_deref(),
-        &config.reliability,
-        &config.model_routes,
-        model_name,
-    )?;
+    let limit = t.timeout().unwrap_or(default_timeout);
+    let mut attempt = 0;
+    loop {
+        let result = execute_with_timeout(t.as_ref(), call, limit).await;
+        if result.success || attempt >= retry.max_retries || !t.is_retryable(&result) {
+            return result;
+        }
+        let delay = retry.delay(attempt);
+        attempt += 1;
+        tracing::warn!(
+            tool = call.name.as_str(),
+            attempt,
+            error = result.error.as_deref().unwrap_or_default(),
+            "Transient tool failure, retrying in {delay:?}"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
 
-    observer.record_event(&ObserverEvent::AgentStart {
-        provider: provider_name.to_string(),
-        model: model_name.to_string(),
-    });
+async fn execute_with_timeout(
+    tool: &dyn Tool,
+    call: &ToolCall,
+    limit: Duration,
+) -> crate::tools::ToolResult {
+    match tokio::time::timeout(limit, tool.execute(call.arguments.clone())).await {
+        Ok(Ok(r)) => r,
+        Ok(Err(e)) => crate::tools::ToolResult {
+            success: false,
+            output: String::new(),
+            error: Some(format!("Tool execution error: {e}")),
+        },
+        Err(_) => {
+            tracing::warn!(tool = call.name.as_str(), "Tool timed out");
+            crate::tools::ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("tool timed out after {}s", limit.as_secs_f64())),
+            }
+        }
+    }
+}
 
-    // ── Build system prompt from workspace MD files (OpenClaw framework) ──
-    let skills = crate::skills::load_skills(&config.workspace_dir);
-    let tool_specs: Vec<_> = agent_tools.iter().map(|t| t.spec()).collect();
-    let system_prompt = crate::channels::build_system_prompt(
-        &config.workspace_dir,
-        model_name,
-        &tool_specs,
-        &skills,
-        Some(&config.identity),
-    );
+/// Serialize JSON with object keys sorted at every level, so equal values
+/// always produce the same string.
+fn canonical_json(value: &Value) -> String {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    format!("{}:{}", Value::from(key.as_str()), canonical_json(value))
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
 
-    // ── Execute ──────────────────────────────────────────────────
-    let start = Instant::now();
+/// Identity of a call: equal for the same tool with the same arguments.
+fn call_key(call: &ToolCall) -> String {
+    format!("{}\n{}", call.name, canonical_json(&call.arguments))
+}
 
-    if let Some(msg) = message {
-        // Auto-save user message to memory
-        if config.memory.auto_save {
-            let _ = mem
-                .store("user_msg", &msg, MemoryCategory::Conversation)
-                .await;
+/// Per-turn gate for the tool calls `SecurityPolicy::requires_confirmation`
+/// flags. Interactively the proposed action is printed and the user answers
+/// through the channel; without one (single-message mode) flagged calls are
+/// denied. Decisions are remembered for the rest of the turn, so an identical
+/// action is never asked about twice.
+pub(super) struct ConfirmationGate<'a> {
+    security: &'a SecurityPolicy,
+    answers: Option<&'a mut mpsc::Receiver<ChannelMessage>>,
+    decisions: HashMap<String, bool>,
+}
+
+impl<'a> ConfirmationGate<'a> {
+    pub(super) fn new(
+        security: &'a SecurityPolicy,
+        answers: Option<&'a mut mpsc::Receiver<ChannelMessage>>,
+    ) -> Self {
+        Self {
+            security,
+            answers,
+            decisions: HashMap::new(),
+        }
+    }
+
+    /// The call to run, or the result reporting why it may not. A confirmed
+    /// shell command is marked `approved` so the shell tool's own risk gate
+    /// lets it through.
+    async fn check(&mut self, call: &ToolCall) -> Result<ToolCall, crate::tools::ToolResult> {
+        if !self
+            .security
+            .requires_confirmation(&call.name, &call.arguments)
+        {
+            return Ok(call.clone());
         }
 
-        // Inject memory context into user message
-        let context = build_context(mem.as_ref(), &msg).await;
-        let enriched = if context.is_empty() {
-            msg.clone()
+        let key = call_key(call);
+        let approved = if let Some(&approved) = self.decisions.get(&key) {
+            approved
         } else {
-            format!("{context}{msg}")
+            let Some(answers) = self.answers.as_deref_mut() else {
+                return Err(crate::tools::ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!(
+                        "{} needs the user's confirmation under supervised autonomy, which \
+                         isn't possible in single-message mode; run interactively or raise \
+                         the autonomy level",
+                        call.name
+                    )),
+                });
+            };
+            let approved = ask_confirmation(answers, call).await;
+            self.decisions.insert(key, approved);
+            approved
         };
 
-        let response = tool_calling_loop(
-            provider.as_ref(),
-            &system_prompt,
-            &enriched,
-            model_name,
-            temperature,
-            &agent_tools,
-        )
-        .await?;
-        println!("{response}");
-
-        // Auto-save assistant response to daily log
-        if config.memory.auto_save {
-            let summary = truncate_with_ellipsis(&response, 100);
-            let _ = mem
-                .store("assistant_resp", &summary, MemoryCategory::Daily)
-                .await;
+        if !approved {
+            return Err(crate::tools::ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("The user declined to run {}", call.name)),
+            });
         }
-    } else {
-        println!("🦀 ZeroClaw Interactive Mode");
-        println!("Type /quit to exit.\n");
+        let mut call = call.clone();
+        if call.name == "shell" {
+            if let Value::Object(args) = &mut call.arguments {
+                args.insert("approved".into(), Value::Bool(true));
+            }
+        }
+        Ok(call)
+    }
+
+    /// Split `calls` into those allowed to run and, by position, the denials.
+    async fn check_all(
+        &mut self,
+        calls: &[ToolCall],
+    ) -> (Vec<ToolCall>, Vec<(usize, crate::tools::ToolResult)>) {
+        let mut runnable = Vec::with_capacity(calls.len());
+        let mut denied = Vec::new();
+        for (index, call) in calls.iter().enumerate() {
+            match self.check(call).await {
+                Ok(call) => runnable.push(call),
+                Err(result) => denied.push((index, result)),
+            }
+        }
+        (runnable, denied)
+    }
+}
+
+/// Print the proposed action and wait for the user's answer; anything but
+/// "y"/"yes" (or the channel closing) declines.
+async fn ask_confirmation(answers: &mut mpsc::Receiver<ChannelMessage>, call: &ToolCall) -> bool {
+    let args = serde_json::to_string_pretty(&call.arguments).unwrap_or_default();
+    println!("\n⚠️  Confirm {}:\n{args}\nAllow? [y/N]", call.name);
+    let _ = std::io::stdout().flush();
+    answers.recv().await.is_some_and(|answer| {
+        matches!(
+            answer.content.trim().to_ascii_lowercase().as_str(),
+            "y" | "yes"
+        )
+    })
+}
+
+/// Execute parsed tool calls against the tool registry.
+///
+/// Identical calls (same name and arguments) within one batch run only once,
+/// and the result is reported at every position that requested it. Results
+/// are returned in the same order as `calls`.
+async fn execute_tool_calls(
+    tools: &[Box<dyn Tool>],
+    calls: &[ToolCall],
+    default_timeout: Duration,
+    dry_run: bool,
+    retry: ToolRetry,
+    observer: &dyn Observer,
+) -> Vec<(String, crate::tools::ToolResult)> {
+    let mut unique: Vec<ToolCall> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let slots: Vec<usize> = calls
+        .iter()
+        .map(|call| {
+            *seen.entry(call_key(call)).or_insert_with(|| {
+                unique.push(call.clone());
+                unique.len() - 1
+            })
+        })
+        .collect();
+    if unique.len() < calls.len() {
+        tracing::debug!(
+            duplicates = calls.len() - unique.len(),
+            "Collapsed duplicate tool calls"
+        );
+    }
+
+    let results =
+        execute_unique_tool_calls(tools, &unique, default_timeout, dry_run, retry, observer).await;
+    calls
+        .iter()
+        .zip(slots)
+        .map(|(call, slot)| (call.name.clone(), results[slot].clone()))
+        .collect()
+}
+
+/// `execute_tool_calls`, except that calls the tool opts in through
+/// `Tool::idempotency_key_supported` are first looked up in `turn`'s records
+/// for `iteration`. A call replayed by running the turn again gets the
+/// recorded result back instead of running again; fresh successful results
+/// are recorded. Dry runs neither read nor write records.
+async fn execute_tool_calls_once(
+    tools: &[Box<dyn Tool>],
+    calls: &[ToolCall],
+    turn: Option<&TurnKeys<'_>>,
+    iteration: usize,
+    settings: LoopSettings,
+    observer: &dyn Observer,
+) -> Vec<(String, crate::tools::ToolResult)> {
+    let mut fresh = Vec::with_capacity(calls.len());
+    let mut fresh_keys = Vec::with_capacity(calls.len());
+    let mut replayed = Vec::new();
+    for (index, call) in calls.iter().enumerate() {
+        let turn = turn.filter(|_| {
+            !settings.dry_run
+                && tools
+                    .iter()
+                    .any(|t| t.name() == call.name && t.idempotency_key_supported(&call.arguments))
+        });
+        let key = turn.map(|_| call_key(call));
+        if let Some(result) = turn
+            .zip(key.as_deref())
+            .and_then(|(turn, key)| turn.get(iteration, key))
+        {
+            tracing::debug!(
+                tool = call.name.as_str(),
+                "Replayed call, reusing its result"
+            );
+            replayed.push((index, (call.name.clone(), result)));
+        } else {
+            fresh.push(call.clone());
+            fresh_keys.push(key);
+        }
+    }
+
+    let mut results = execute_tool_calls(
+        tools,
+        &fresh,
+        settings.tool_timeout,
+        settings.dry_run,
+        settings.tool_retry,
+        observer,
+    )
+    .await;
+    for (key, (_, result)) in fresh_keys.into_iter().zip(&results) {
+        if let Some((turn, key)) = turn.zip(key).filter(|_| result.success) {
+            turn.put(iteration, &key, result.clone());
+        }
+    }
+    for (index, result) in replayed {
+        results.insert(index, result);
+    }
+    results
+}
+
+/// Consecutive calls to concurrency-safe tools run in parallel; a call to a
+/// tool that opts out runs on its own, after everything before it finished.
+async fn execute_unique_tool_calls(
+    tools: &[Box<dyn Tool>],
+    calls: &[ToolCall],
+    default_timeout: Duration,
+    dry_run: bool,
+    retry: ToolRetry,
+    observer: &dyn Observer,
+) -> Vec<crate::tools::ToolResult> {
+    let mut results = Vec::with_capacity(calls.len());
+
+    let is_safe = |call: &ToolCall| {
+        tools
+            .iter()
+            .find(|t| t.name() == call.name)
+            .is_none_or(|t| t.concurrency_safe())
+    };
+
+    let mut remaining = calls;
+    while let Some(first) = remaining.first() {
+        let batch_len = if is_safe(first) {
+            remaining.iter().take_while(|c| is_safe(c)).count()
+        } else {
+            1
+        };
+        let (batch, rest) = remaining.split_at(batch_len);
+        let batch_results =
+            join_all(batch.iter().map(|call| {
+                execute_tool_call(tools, call, default_timeout, dry_run, retry, observer)
+            }))
+            .await;
+        results.extend(batch_results);
+        remaining = rest;
+    }
+
+    results
+}
+
+/// Record each call and its result (`results[i]` answers `calls[i]`) in the
+/// security policy's audit log.
+fn audit_tool_calls(
+    security: &SecurityPolicy,
+    calls: &[ToolCall],
+    results: &[(String, crate::tools::ToolResult)],
+) {
+    for (call, (_, result)) in calls.iter().zip(results) {
+        security.audit_tool_call(
+            &call.name,
+            &call.arguments,
+            result.success,
+            result.error.as_deref(),
+        );
+    }
+}
+
+/// Format tool results as XML blocks for feeding back to the LLM.
+pub fn format_tool_results(results: &[(String, crate::tools::ToolResult)]) -> String {
+    results
+        .iter()
+        .map(|(name, result)| format_tool_result(name, result))
+        .collect()
+}
+
+/// One `<tool_result>` block of `format_tool_results`.
+fn format_tool_result(name: &str, result: &crate::tools::ToolResult) -> String {
+    let json = serde_json::json!({
+        "success": result.success,
+        "output": result.output,
+        "error": result.error,
+    });
+    format!(
+        "<tool_result name=\"{name}\">{}</tool_result>\n",
+        serde_json::to_string(&json).unwrap_or_else(|_| "{}".into())
+    )
+}
+
+/// Put in place of a tool result that a later call with the same arguments
+/// replaced, under `agent.tool_history = "latest"`.
+const SUPERSEDED: &str = "[superseded]";
+
+/// The newest result block of each tool call in a loop's history, so an
+/// older one can be replaced with `[superseded]` when the call is repeated.
+#[derive(Default)]
+struct LatestToolResults {
+    /// Block last sent for each tool name and canonical (key-sorted) arguments
+    latest: HashMap<(String, String), String>,
+}
+
+impl LatestToolResults {
+    /// Record this round's result blocks (`blocks[i]` answers `calls[i]`),
+    /// superseding earlier results of the same calls in `history` or earlier
+    /// in `blocks`.
+    fn supersede(
+        &mut self,
+        history: &mut [ChatMessage],
+        calls: &[ToolCall],
+        blocks: &mut [String],
+    ) {
+        for (index, call) in calls.iter().enumerate().take(blocks.len()) {
+            let key = (call.name.clone(), call.arguments.to_string());
+            let Some(old) = self.latest.insert(key, blocks[index].clone()) else {
+                continue;
+            };
+            let marker = format!(
+                "<tool_result name=\"{}\">{SUPERSEDED}</tool_result>\n",
+                call.name
+            );
+            if let Some(earlier) = blocks[..index].iter_mut().find(|block| **block == old) {
+                *earlier = marker;
+            } else if let Some(message) = history
+                .iter_mut()
+                .rev()
+                .find(|message| message.content.contains(&old))
+            {
+                message.content = message.content.replacen(&old, &marker, 1);
+            }
+        }
+    }
+}
+
+const TOOL_CALL_OPEN: &str = "<tool_call>";
+const TOOL_CALL_CLOSE: &str = "</tool_call>";
+
+/// Strips `<tool_call>` blocks from streamed text as it arrives, holding back
+/// only a trailing fragment that could be the start of a tag.
+#[derive(Default)]
+pub(crate) struct ToolCallFilter {
+    pending: String,
+    in_call: bool,
+}
+
+impl ToolCallFilter {
+    /// Feed a delta and return the text that is now safe to print.
+    pub(crate) fn push(&mut self, delta: &str) -> String {
+        self.pending.push_str(delta);
+        let mut out = String::new();
+        loop {
+            if self.in_call {
+                let Some(end) = self.pending.find(TOOL_CALL_CLOSE) else {
+                    break;
+                };
+                self.pending.drain(..end + TOOL_CALL_CLOSE.len());
+                self.in_call = false;
+            } else if let Some(start) = self.pending.find(TOOL_CALL_OPEN) {
+                out.push_str(&self.pending[..start]);
+                self.pending.drain(..start + TOOL_CALL_OPEN.len());
+                self.in_call = true;
+            } else {
+                let emit = self.pending.len() - partial_open_tag_len(&self.pending);
+                out.push_str(&self.pending[..emit]);
+                self.pending.drain(..emit);
+                break;
+            }
+        }
+        out
+    }
+
+    /// Flush what is left once the stream ends; an unterminated tool call is dropped.
+    pub(crate) fn finish(&mut self) -> String {
+        let rest = std::mem::take(&mut self.pending);
+        if self.in_call {
+            String::new()
+        } else {
+            rest
+        }
+    }
+}
+
+/// Length of the longest suffix of `text` that is a proper prefix of `<tool_call>`.
+fn partial_open_tag_len(text: &str) -> usize {
+    (1..TOOL_CALL_OPEN.len())
+        .rev()
+        .find(|&n| text.ends_with(&TOOL_CALL_OPEN[..n]))
+        .unwrap_or(0)
+}
+
+tokio::task_local! {
+    /// Takes streamed text in place of stdout, for `AgentRunner::respond_stream`
+    static STREAM_SINK: mpsc::UnboundedSender<String>;
+}
+
+/// Show streamed text through the CLI channel, or send it to `STREAM_SINK`
+/// when one is set.
+async fn emit_streamed(text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    let sent = STREAM_SINK.try_with(|sink| {
+        // A closed sink means the client went away; the turn still finishes
+        let _ = sink.send(text.to_string());
+    });
+    if sent.is_err() {
+        let _ = CliChannel::new().send_chunk(text, "user").await;
+    }
+}
+
+/// End a streamed reply started with `emit_streamed`.
+async fn end_streamed() {
+    if STREAM_SINK.try_with(|sink| sink.send("\n".into())).is_err() {
+        let _ = CliChannel::new().end_message("user").await;
+    }
+}
+
+/// Stream a reply to stdout (see `emit_streamed`) as it arrives, hiding
+/// `<tool_call>` blocks and applying `security.output_filters`, and return
+/// the full text for tool-call parsing along with the usage the provider
+/// reported at the end of the stream.
+async fn stream_response(
+    provider: &dyn Provider,
+    history: &[ChatMessage],
+    model_name: &str,
+    temperature: f64,
+    observer: &dyn Observer,
+) -> Result<providers::ChatResponse> {
+    let mut deltas = provider
+        .chat_stream(history, model_name, temperature)
+        .await?;
+    let mut filter = ToolCallFilter::default();
+    let output_filter = output_filter::active();
+    let mut screen = output_filter
+        .as_deref()
+        .map(output_filter::OutputFilter::stream);
+    let mut full = String::new();
+    let mut usage = None;
+    let mut printed = false;
+
+    while let Some(event) = deltas.next().await {
+        let delta = match event? {
+            StreamEvent::Text(delta) => delta,
+            StreamEvent::Usage(reported) => {
+                usage = Some(reported);
+                continue;
+            }
+        };
+        full.push_str(&delta);
+        let mut visible = filter.push(&delta);
+        if let Some(screen) = &mut screen {
+            visible = screen
+                .push(&visible)
+                .map_err(|blocked| report_blocked(blocked, observer))?;
+        }
+        printed |= !visible.is_empty();
+        emit_streamed(&visible).await;
+    }
+    let mut rest = filter.finish();
+    if let Some(screen) = &mut screen {
+        rest = screen
+            .push(&rest)
+            .and_then(|mut rest| {
+                rest.push_str(&screen.finish()?);
+                Ok(rest)
+            })
+            .map_err(|blocked| report_blocked(blocked, observer))?;
+    }
+    printed |= !rest.is_empty();
+    emit_streamed(&rest).await;
+    if printed {
+        end_streamed().await;
+    }
+
+    Ok(providers::ChatResponse {
+        text: full,
+        usage,
+        ..providers::ChatResponse::default()
+    })
+}
+
+/// Apply `security.output_filters` to text about to be shown to the user.
+fn screen_output(text: String, observer: &dyn Observer) -> Result<String> {
+    output_filter::screen(text).map_err(|blocked| report_blocked(blocked, observer))
+}
+
+/// Log a response withheld by `security.output_filters` and turn it into
+/// the error that ends the turn.
+fn report_blocked(blocked: OutputBlocked, observer: &dyn Observer) -> anyhow::Error {
+    tracing::warn!("{blocked}");
+    observer.record_event(&ObserverEvent::Error {
+        component: "output_filter".to_string(),
+        message: blocked.to_string(),
+    });
+    blocked.into()
+}
+
+/// How failed tool calls marked transient by `Tool::is_retryable` are
+/// retried: up to `max_retries` more attempts, waiting `backoff` before the
+/// first and twice as long before each one after.
+#[derive(Debug, Clone, Copy)]
+struct ToolRetry {
+    max_retries: u32,
+    backoff: Duration,
+}
+
+impl ToolRetry {
+    fn from_config(config: &crate::config::ToolsConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            backoff: Duration::from_millis(config.retry_backoff_ms),
+        }
+    }
+
+    /// Wait before retry number `attempt + 1`.
+    fn delay(self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1_u32.checked_shl(attempt).unwrap_or(u32::MAX))
+    }
+}
+
+/// Per-run settings for `tool_calling_loop`.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct LoopSettings {
+    /// Default tool timeout; tools may override it via `Tool::timeout`.
+    tool_timeout: Duration,
+    /// Retries for transient tool failures.
+    tool_retry: ToolRetry,
+    /// Print text to stdout as it streams in. Calls that offer tools are
+    /// batched and their text shown whole.
+    stream: bool,
+    /// Approximate token budget for the history sent each iteration.
+    context_budget: usize,
+    /// Limit on provider-reported tokens across the whole run.
+    max_total_tokens: Option<u64>,
+    /// Report calls to tools that aren't read-only instead of running them.
+    dry_run: bool,
+    /// Print reasoning returned by the provider to stderr.
+    show_reasoning: bool,
+    /// `--quiet` and `--json` hide the model's intermediate text and
+    /// reasoning.
+    output: OutputMode,
+    /// Which tool results stay in the history.
+    tool_history: ToolHistory,
+    /// Tool-calling iterations before the model is asked to wrap up.
+    pub(super) max_iterations: usize,
+    /// `agent.tool_phase_temperature`: used while tools are on offer.
+    tool_temperature: Option<f64>,
+    /// `agent.final_phase_temperature`: used for calls without tools.
+    final_temperature: Option<f64>,
+    /// Counts tokens against `context_budget` (`agent.tokenizers`).
+    pub(super) tokenizer: &'static dyn Tokenizer,
+}
+
+impl LoopSettings {
+    pub(super) fn from_config(config: &Config, stream: bool) -> Self {
+        Self {
+            tool_timeout: Duration::from_secs(config.tools.timeout_secs),
+            tool_retry: ToolRetry::from_config(&config.tools),
+            stream,
+            context_budget: config.agent.context_budget,
+            max_total_tokens: config.agent.max_total_tokens,
+            dry_run: config.agent.dry_run,
+            show_reasoning: config.agent.show_reasoning,
+            output: OutputMode::Normal,
+            tool_history: config.agent.tool_history,
+            max_iterations: MAX_TOOL_ITERATIONS,
+            tool_temperature: config.agent.tool_phase_temperature,
+            final_temperature: config.agent.final_phase_temperature,
+            tokenizer: tokenizer::for_model(
+                &config.agent,
+                config.default_model.as_deref().unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+/// Print reasoning kept apart from the answer and report it to observers,
+/// if `agent.show_reasoning` is on; otherwise it is dropped.
+fn report_reasoning(reasoning: Option<String>, settings: LoopSettings, observer: &dyn Observer) {
+    let Some(text) = reasoning.filter(|r| settings.show_reasoning && !r.trim().is_empty()) else {
+        return;
+    };
+    if settings.output.shows_progress() {
+        eprintln!("[reasoning]\n{}\n[/reasoning]", text.trim());
+    }
+    observer.record_event(&ObserverEvent::Reasoning { text });
+}
+
+/// Marker left in place of history dropped by `trim_history`.
+const OMITTED_TURNS_MARKER: &str = "[earlier turns omitted]";
+
+/// Tokens in `messages` as counted by `tokenizer`.
+fn estimate_tokens(messages: &[ChatMessage], tokenizer: &dyn Tokenizer) -> usize {
+    messages
+        .iter()
+        .map(|m| tokenizer.count_tokens(&m.content))
+        .sum()
+}
+
+/// Drop the oldest tool exchanges until the history fits `budget` tokens.
+///
+/// The first `preserved` entries (the system prompt and the conversation the
+/// turn started from) and the most recent exchange are always kept.
+/// Exchanges are removed in assistant/tool-result pairs so roles keep
+/// alternating, and a single `[earlier turns omitted]` marker is left where
+/// they were.
+fn trim_history(
+    history: &mut Vec<ChatMessage>,
+    preserved: usize,
+    budget: usize,
+    tokenizer: &dyn Tokenizer,
+) {
+    let mut trimmed = false;
+    while estimate_tokens(history, tokenizer) > budget {
+        let has_marker = history
+            .get(preserved)
+            .is_some_and(|m| m.content == OMITTED_TURNS_MARKER);
+        let first_turn = preserved + usize::from(has_marker);
+        // Keep at least the latest assistant/tool-result pair
+        if history.len() < first_turn + 4 {
+            break;
+        }
+        history.drain(first_turn..first_turn + 2);
+        if !has_marker {
+            history.insert(preserved, ChatMessage::user(OMITTED_TURNS_MARKER));
+        }
+        trimmed = true;
+    }
+
+    if trimmed {
+        tracing::debug!(
+            messages = history.len(),
+            estimated_tokens = estimate_tokens(history, tokenizer),
+            "Trimmed conversation history to fit the context budget"
+        );
+    }
+}
+
+/// The most recent turns of `conversation` that fit in `budget` tokens,
+/// starting at a user message so the replay reads as whole exchanges.
+fn recent_turns<'a>(
+    conversation: &'a [ChatMessage],
+    budget: usize,
+    tokenizer: &dyn Tokenizer,
+) -> &'a [ChatMessage] {
+    let mut start = conversation.len();
+    let mut tokens = 0;
+    for (index, message) in conversation.iter().enumerate().rev() {
+        tokens += tokenizer.count_tokens(&message.content);
+        if tokens > budget {
+            break;
+        }
+        if message.role == providers::traits::Role::User {
+            start = index;
+        }
+    }
+    &conversation[start..]
+}
+
+/// Returned (as an `anyhow` error) by `tool_calling_loop` when its turn is cancelled.
+#[derive(Debug)]
+struct TurnCancelled;
+
+impl std::fmt::Display for TurnCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("turn cancelled")
+    }
+}
+
+impl std::error::Error for TurnCancelled {}
+
+/// Await `future` unless `cancel` fires first, in which case the future is
+/// dropped (aborting in-flight requests and tool processes).
+async fn cancellable<T>(
+    cancel: &CancellationToken,
+    future: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::select! {
+        biased;
+        () = cancel.cancelled() => Err(TurnCancelled.into()),
+        result = future => result,
+    }
+}
+
+/// Returned (as an `anyhow` error) by `tool_calling_loop` once the run's total
+/// token usage exceeds `max_total_tokens`.
+#[derive(Debug)]
+struct BudgetExceeded {
+    tokens_used: u64,
+    limit: u64,
+    /// Text of the last response, outside any tool calls.
+    partial: String,
+}
+
+impl std::fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "token budget exceeded: used {} of {} tokens",
+            self.tokens_used, self.limit
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Print the partial answer carried by a `BudgetExceeded` error, if any
+/// (when streaming it is already on screen).
+fn print_partial_answer(err: &anyhow::Error, stream: bool) {
+    if let Some(exceeded) = err.downcast_ref::<BudgetExceeded>() {
+        if !stream && !exceeded.partial.is_empty() {
+            println!("{}", exceeded.partial);
+        }
+    }
+}
+
+/// Print the files returned during a turn, one `<artifact>` block each,
+/// after the answer.
+fn print_artifacts(artifacts: &[Artifact]) {
+    for artifact in artifacts {
+        println!("{}\n", artifact.render());
+    }
+}
+
+/// Add one call's token usage to the running total and report it, failing
+/// with `BudgetExceeded` once the total passes `limit`.
+fn add_usage(
+    tokens: u64,
+    tokens_used: &mut Option<u64>,
+    limit: Option<u64>,
+    observer: &dyn Observer,
+    response: &str,
+) -> Result<()> {
+    let total = tokens_used.unwrap_or(0).saturating_add(tokens);
+    *tokens_used = Some(total);
+    observer.record_event(&ObserverEvent::BudgetUpdate {
+        tokens_used: total,
+        limit,
+    });
+    match limit {
+        Some(limit) if total > limit => Err(BudgetExceeded {
+            tokens_used: total,
+            limit,
+            partial: extract_text_outside_tool_calls(response),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Check a final answer against `json`. A rejected answer is shown back to
+/// the model with the reason, and it is asked once more without tools (so
+/// providers in JSON mode constrain the reply); if that answer is rejected
+/// too, the turn fails with `InvalidJsonResponse`.
+#[allow(clippy::too_many_arguments)]
+async fn ensure_json_answer(
+    provider: &dyn Provider,
+    system_prompt: &str,
+    messages: &[ChatMessage],
+    answer: String,
+    model_name: &str,
+    temperature: f64,
+    json: &JsonResponse,
+    settings: LoopSettings,
+    observer: &dyn Observer,
+    tokens_used: &mut Option<u64>,
+) -> Result<String> {
+    let reason = match json.check(&answer) {
+        Ok(valid) => return Ok(valid),
+        Err(reason) => reason,
+    };
+    tracing::debug!(reason, "Final answer is not valid JSON, asking again");
+
+    let mut history = Vec::with_capacity(messages.len() + 3);
+    history.push(ChatMessage::system(system_prompt));
+    history.extend_from_slice(messages);
+    history.push(ChatMessage::assistant(answer));
+    history.push(ChatMessage::user(json.correction(&reason)));
+
+    let started = Instant::now();
+    let chat = provider
+        .chat_with_usage(&history, model_name, temperature)
+        .await?;
+    observer.record_metric(&ObserverMetric::RequestLatency(started.elapsed()));
+    if let Some(usage) = chat.usage {
+        add_usage(
+            usage.total(),
+            tokens_used,
+            settings.max_total_tokens,
+            observer,
+            &chat.text,
+        )?;
+    }
+    report_reasoning(chat.reasoning, settings, observer);
+    json.check(&chat.text).map_err(|reason| {
+        InvalidJsonResponse {
+            reason,
+            response: chat.text,
+        }
+        .into()
+    })
+}
+
+/// A second Ctrl-C within this window exits instead of cancelling the turn.
+const CTRL_C_EXIT_WINDOW: Duration = Duration::from_secs(2);
+
+/// How long a turn still running at shutdown may take to finish its tool
+/// calls before it is cancelled.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Handle Ctrl-C in interactive mode: the first press cancels the token
+/// currently in `turn`, a second press within `CTRL_C_EXIT_WINDOW` requests
+/// a graceful shutdown through `shutdown`. Pressing again once shutdown is
+/// under way exits immediately.
+fn spawn_ctrl_c_handler(
+    turn: Arc<Mutex<CancellationToken>>,
+    shutdown: CancellationToken,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last_press: Option<Instant> = None;
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if shutdown.is_cancelled() {
+                eprintln!();
+                std::process::exit(130);
+            }
+            if last_press.is_some_and(|at| at.elapsed() < CTRL_C_EXIT_WINDOW) {
+                eprintln!("\n[shutting down]");
+                shutdown.cancel();
+                continue;
+            }
+            last_press = Some(Instant::now());
+            if let Ok(token) = turn.lock() {
+                token.cancel();
+            }
+            eprintln!("\n^C (press Ctrl-C again within 2s to exit)");
+        }
+    })
+}
+
+/// Run the tool-calling loop: call LLM, parse tool calls, execute, feed results back, repeat.
+///
+/// `messages` is the conversation so far, ending with the new user message;
+/// it is sent after the system prompt and never trimmed.
+///
+/// Tool calls returned through the provider's native function-calling API are
+/// preferred; otherwise they are parsed from `<tool_call>` tags in the text.
+/// With `settings.stream` set, text is printed to stdout as it arrives (native
+/// tool calling needs the batched path). Before each call
+/// the tool exchanges are trimmed to `settings.context_budget`.
+/// Returns the final text response (after all tool calls are resolved), or a
+/// `TurnCancelled` error as soon as `cancel` fires.
+/// Token usage reported by the provider is added to `tokens_used` and
+/// recorded as a `BudgetUpdate`; once it exceeds `settings.max_total_tokens`
+/// the loop stops with a `BudgetExceeded` error.
+/// Calls flagged by the security policy go through `gate` first, and every
+/// call, denied or run, is recorded in the policy's audit log.
+/// A response that looks like a malformed tool call is answered with a
+/// correction once before it is accepted as final.
+/// With a `cache`, calls are answered from it when the identical request was
+/// seen before (shown whole in stream mode), and fresh responses, streamed
+/// ones included, are stored in it.
+/// Files returned through the `artifact` tool are added to `artifacts`; the
+/// model only sees a short confirmation for each.
+/// If every iteration still calls tools, the model is asked once more for an
+/// answer without tools; see `finish_at_iteration_limit`.
+/// The temperature is chosen before each call: calls that offer tools use
+/// `settings.tool_temperature`, and calls without tools (a loop given none,
+/// or the wrap-up at the iteration limit) `settings.final_temperature`, each
+/// falling back to `temperature`. An answer given while tools were on offer
+/// is kept as is.
+/// With `turn`, calls to tools that support idempotency keys are run once per
+/// turn, iteration and arguments; see `execute_tool_calls_once`.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+pub(super) async fn tool_calling_loop(
+    provider: &dyn Provider,
+    system_prompt: &str,
+    messages: &[ChatMessage],
+    model_name: &str,
+    temperature: f64,
+    tools: &[Box<dyn Tool>],
+    settings: LoopSettings,
+    cancel: &CancellationToken,
+    observer: &dyn Observer,
+    cache: Option<&ResponseCache>,
+    tokens_used: &mut Option<u64>,
+    artifacts: &mut Vec<Artifact>,
+    mut gate: ConfirmationGate<'_>,
+    turn: Option<&TurnKeys<'_>>,
+) -> Result<String> {
+    // The provider is stateless, so we pass the full history each iteration.
+    let mut history = Vec::with_capacity(messages.len() + 1);
+    history.push(ChatMessage::system(system_prompt));
+    history.extend_from_slice(messages);
+    let preserved = history.len();
+    let specs: Vec<ToolSpec> = tools.iter().map(|tool| tool.spec()).collect();
+    let tool_names: Vec<&str> = tools.iter().map(|tool| tool.name()).collect();
+    // Text the model wrote alongside its tool calls, kept in case it never
+    // gets to a final answer
+    let mut intermediate = Vec::new();
+    // Whether the last response was answered with `MALFORMED_TOOL_CALL_PROMPT`
+    let mut reprompted = false;
+    let mut latest_results = LatestToolResults::default();
+    let final_temperature = settings.final_temperature.unwrap_or(temperature);
+    // Without tools every call is the concluding one
+    let tool_temperature = if tools.is_empty() {
+        final_temperature
+    } else {
+        settings.tool_temperature.unwrap_or(temperature)
+    };
+
+    for iteration in 0..settings.max_iterations {
+        if cancel.is_cancelled() {
+            return Err(TurnCancelled.into());
+        }
+        trim_history(
+            &mut history,
+            preserved,
+            settings.context_budget,
+            settings.tokenizer,
+        );
+
+        let cache_key =
+            cache.and_then(|cache| cache.key(&history, &specs, model_name, tool_temperature));
+        let cached = cache
+            .zip(cache_key.as_deref())
+            .and_then(|(cache, key)| cache.get(key));
+        // Native tool calls only come back from batched calls, so a call that
+        // offers tools is never streamed
+        let streamed = settings.stream && specs.is_empty() && cached.is_none();
+        let (mut response, tool_calls) = if streamed {
+            let chat = cancellable(
+                cancel,
+                stream_response(provider, &history, model_name, tool_temperature, observer),
+            )
+            .await?;
+            if let Some(usage) = chat.usage {
+                add_usage(
+                    usage.total(),
+                    tokens_used,
+                    settings.max_total_tokens,
+                    observer,
+                    &chat.text,
+                )?;
+            }
+            if let Some((cache, key)) = cache.zip(cache_key.as_deref()) {
+                cache.put(key, model_name, &chat);
+            }
+            (chat.text, Vec::new())
+        } else {
+            let chat = if let Some(chat) = cached {
+                chat
+            } else {
+                let started = Instant::now();
+                let chat = cancellable(
+                    cancel,
+                    provider.chat_with_tools(&history, &specs, model_name, tool_temperature),
+                )
+                .await?;
+                observer.record_metric(&ObserverMetric::RequestLatency(started.elapsed()));
+                if let Some(usage) = chat.usage {
+                    add_usage(
+                        usage.total(),
+                        tokens_used,
+                        settings.max_total_tokens,
+                        observer,
+                        &chat.text,
+                    )?;
+                }
+                if let Some((cache, key)) = cache.zip(cache_key.as_deref()) {
+                    cache.put(key, model_name, &chat);
+                }
+                chat
+            };
+            report_reasoning(chat.reasoning, settings, observer);
+            (chat.text, chat.tool_calls)
+        };
+
+        let native_calls = !tool_calls.is_empty();
+        let (calls, parse_errors) = if native_calls {
+            (tool_calls, Vec::new())
+        } else {
+            parse_tool_call_blocks(&response, &tool_names)
+        };
+
+        // Extract and print any text the LLM produced alongside tool calls
+        // (already on screen when streamed)
+        let text = screen_output(extract_text_outside_tool_calls(&response), observer)?;
+        if settings.stream && !streamed && !text.is_empty() {
+            // Shown whole, where a streamed response would have appeared
+            emit_streamed(&text).await;
+            end_streamed().await;
+        } else if !settings.stream && settings.output.shows_progress() && !text.is_empty() {
+            if iteration > 0 || !calls.is_empty() {
+                // Print intermediate thinking
+                eprintln!("{text}");
+            }
+        }
+
+        if calls.is_empty() && parse_errors.is_empty() {
+            // Give a model that fumbled the call format one chance to fix it
+            if !reprompted && looks_like_intended_tool_call(&response, &tool_names) {
+                tracing::debug!(
+                    iteration,
+                    "Response looks like a malformed tool call, re-prompting"
+                );
+                reprompted = true;
+                history.push(ChatMessage::assistant(response));
+                history.push(ChatMessage::user(MALFORMED_TOOL_CALL_PROMPT));
+                continue;
+            }
+            // No tool calls — this is the final response
+            return screen_output(response, observer);
+        }
+        reprompted = false;
+        if !text.is_empty() {
+            intermediate.push(text);
+        }
+
+        tracing::debug!(
+            iteration,
+            num_calls = calls.len(),
+            "Executing tool calls"
+        );
+
+        let (runnable, denied) =
+            cancellable(cancel, async { Ok(gate.check_all(&calls).await) }).await?;
+        let mut results = cancellable(cancel, async {
+            Ok(
+                execute_tool_calls_once(tools, &runnable, turn, iteration, settings, observer)
+                    .await,
+            )
+        })
+        .await?;
+        for (index, result) in denied {
+            results.insert(index, (calls[index].name.clone(), result));
+        }
+        audit_tool_calls(gate.security, &calls, &results);
+        for (name, result) in &mut results {
+            if let Some(mut artifact) = artifact::take_artifact(name, result) {
+                if artifact.encoding == artifact::ArtifactEncoding::Utf8 {
+                    artifact.content = screen_output(artifact.content, observer)?;
+                }
+                artifacts.push(artifact);
+            }
+        }
+        // Report malformed calls back so the model can retry them
+        results.extend(parse_errors.into_iter().map(|reason| {
+            (
+                "tool_call".to_string(),
+                crate::tools::ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("could not parse tool_call: {reason}")),
+                },
+            )
+        }));
+
+        // Log tool results
+        for (name, result) in &results {
+            if result.success {
+                tracing::debug!(tool = name, "Tool succeeded");
+            } else {
+                tracing::warn!(
+                    tool = name,
+                    error = result.error.as_deref().unwrap_or("unknown"),
+                    "Tool failed"
+                );
+            }
+        }
+
+        // Feed the assistant turn and its tool results back as the next exchange
+        if native_calls {
+            response.push_str(&format_tool_calls(&calls));
+        }
+        let mut blocks: Vec<String> = results
+            .iter()
+            .map(|(name, result)| format_tool_result(name, result))
+            .collect();
+        if settings.tool_history == ToolHistory::Latest {
+            latest_results.supersede(&mut history, &calls, &mut blocks);
+        }
+        let tool_results_text = blocks.concat();
+        history.push(ChatMessage::assistant(response));
+        history.push(ChatMessage::user(format!(
+            "[Tool Results]\n{tool_results_text}"
+        )));
+    }
+
+    tracing::warn!(
+        max_iterations = settings.max_iterations,
+        "Tool iteration limit reached, asking for a final answer"
+    );
+    finish_at_iteration_limit(
+        provider,
+        &mut history,
+        preserved,
+        model_name,
+        final_temperature,
+        &tool_names,
+        settings,
+        cancel,
+        observer,
+        tokens_used,
+        intermediate,
+    )
+    .await
+    .and_then(|answer| screen_output(answer, observer))
+}
+
+/// Make one last call with no tools on offer, telling the model to answer
+/// from what it has. If it still only asks for tools, return the text it
+/// wrote along the way, marked with `ITERATION_LIMIT_NOTE`, so the turn
+/// never ends silently.
+#[allow(clippy::too_many_arguments)]
+async fn finish_at_iteration_limit(
+    provider: &dyn Provider,
+    history: &mut Vec<ChatMessage>,
+    preserved: usize,
+    model_name: &str,
+    temperature: f64,
+    tool_names: &[&str],
+    settings: LoopSettings,
+    cancel: &CancellationToken,
+    observer: &dyn Observer,
+    tokens_used: &mut Option<u64>,
+    mut intermediate: Vec<String>,
+) -> Result<String> {
+    if cancel.is_cancelled() {
+        return Err(TurnCancelled.into());
+    }
+    trim_history(
+        history,
+        preserved,
+        settings.context_budget,
+        settings.tokenizer,
+    );
+    history.push(ChatMessage::user(ITERATION_LIMIT_PROMPT));
+
+    let response = if settings.stream {
+        let chat = cancellable(
+            cancel,
+            stream_response(provider, history, model_name, temperature, observer),
+        )
+        .await?;
+        if let Some(usage) = chat.usage {
+            add_usage(
+                usage.total(),
+                tokens_used,
+                settings.max_total_tokens,
+                observer,
+                &chat.text,
+            )?;
+        }
+        chat.text
+    } else {
+        chat_without_tools(
+            provider,
+            history,
+            model_name,
+            temperature,
+            settings,
+            cancel,
+            observer,
+            tokens_used,
+        )
+        .await?
+    };
+
+    let (calls, parse_errors) = parse_tool_call_blocks(&response, tool_names);
+    if calls.is_empty() && parse_errors.is_empty() && !response.trim().is_empty() {
+        return Ok(response);
+    }
+    let text = extract_text_outside_tool_calls(&response);
+    if !text.is_empty() {
+        intermediate.push(text);
+    }
+    intermediate.push(ITERATION_LIMIT_NOTE.to_string());
+    Ok(intermediate.join("\n\n"))
+}
+
+/// One batched call without tools, with its latency, usage and reasoning
+/// reported like any other.
+#[allow(clippy::too_many_arguments)]
+async fn chat_without_tools(
+    provider: &dyn Provider,
+    history: &[ChatMessage],
+    model_name: &str,
+    temperature: f64,
+    settings: LoopSettings,
+    cancel: &CancellationToken,
+    observer: &dyn Observer,
+    tokens_used: &mut Option<u64>,
+) -> Result<String> {
+    let started = Instant::now();
+    let chat = cancellable(
+        cancel,
+        provider.chat_with_usage(history, model_name, temperature),
+    )
+    .await?;
+    observer.record_metric(&ObserverMetric::RequestLatency(started.elapsed()));
+    if let Some(usage) = chat.usage {
+        add_usage(
+            usage.total(),
+            tokens_used,
+            settings.max_total_tokens,
+            observer,
+            &chat.text,
+        )?;
+    }
+    report_reasoning(chat.reasoning, settings, observer);
+    Ok(chat.text)
+}
+
+/// Persona name that selects the default `[identity]`.
+const DEFAULT_PERSONA: &str = "default";
+
+/// Resolve a `/persona` argument against `config.identities`, returning the
+/// persona to use from now on and a note for the user. An empty name lists
+/// the personas; an unknown one falls back to the default identity.
+fn select_persona(config: &Config, current: Option<&str>, name: &str) -> (Option<String>, String) {
+    if name.is_empty() {
+        let mut names: Vec<&str> = config.identities.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        let note = format!(
+            "[persona: {}] available: {DEFAULT_PERSONA}{}{}",
+            current.unwrap_or(DEFAULT_PERSONA),
+            if names.is_empty() { "" } else { ", " },
+            names.join(", ")
+        );
+        return (current.map(ToString::to_string), note);
+    }
+    if config.identities.contains_key(name) {
+        return (Some(name.to_string()), format!("[persona: {name}]"));
+    }
+    if name == DEFAULT_PERSONA {
+        return (None, format!("[persona: {DEFAULT_PERSONA}]"));
+    }
+    tracing::warn!(
+        identity = name,
+        "Unknown persona, using the default identity"
+    );
+    (
+        None,
+        format!("[unknown persona '{name}', using {DEFAULT_PERSONA}]"),
+    )
+}
+
+/// How often a long interactive session purges expired memory entries.
+const MEMORY_PURGE_INTERVAL: Duration = Duration::from_hours(1);
+
+/// Await `turn`; if `shutdown` fires first, give it `grace` to finish its
+/// in-flight tool calls, then cancel it through `cancel`.
+async fn finish_turn<T>(
+    turn: impl std::future::Future<Output = T>,
+    shutdown: &CancellationToken,
+    cancel: &CancellationToken,
+    grace: Duration,
+) -> T {
+    tokio::pin!(turn);
+    tokio::select! {
+        out = &mut turn => return out,
+        () = shutdown.cancelled() => {}
+    }
+    if let Ok(out) = tokio::time::timeout(grace, &mut turn).await {
+        return out;
+    }
+    tracing::warn!("Turn still running at shutdown, cancelling it");
+    cancel.cancel();
+    turn.await
+}
+
+/// Wind down a run: record its end, flush buffered memory writes, then let
+/// the observers export what they still hold. Background tasks (listeners,
+/// watchers) are stopped only after this returns.
+async fn shut_down(
+    mem: &dyn Memory,
+    observer: &dyn Observer,
+    duration: Duration,
+    tokens_used: Option<u64>,
+) {
+    observer.record_event(&ObserverEvent::AgentEnd {
+        duration,
+        tokens_used,
+    });
+    if let Err(e) = mem.flush().await {
+        tracing::warn!("Flushing memory failed: {e}");
+    }
+    observer.shutdown();
+}
+
+/// Remove expired memory entries (best effort; reads skip them anyway).
+async fn purge_expired_memory(mem: &dyn Memory) {
+    match mem.purge_expired().await {
+        Ok(0) => {}
+        Ok(purged) => tracing::debug!(purged, "Purged expired memory entries"),
+        Err(e) => tracing::warn!("Purging expired memory failed: {e}"),
+    }
+}
+
+/// Keys `AgentRunner` stores a session's turns under, after `{session_id}_`.
+const SESSION_MEMORY_KEYS: [&str; 2] = ["user_msg", "assistant_resp"];
+
+/// How many more entries are recalled for a session, since entries from
+/// other sessions are dropped afterwards.
+const SESSION_RECALL_FACTOR: usize = 4;
+
+/// Whether a recalled entry holds one of `session`'s turns. Markdown memory
+/// keys its entries by file and line and keeps the stored key in the
+/// content, as `**key**: ...`.
+fn is_session_entry(entry: &MemoryEntry, session: &str) -> bool {
+    let key = entry
+        .content
+        .strip_prefix("**")
+        .and_then(|rest| rest.split_once("**:"))
+        .map_or(entry.key.as_str(), |(key, _)| key);
+    key.strip_prefix(session)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .is_some_and(|rest| SESSION_MEMORY_KEYS.contains(&rest))
+}
+
+/// Build context preamble by searching memory for relevant entries.
+///
+/// Durable facts and recent activity are recalled separately and labelled,
+/// so the model can tell long-term knowledge from recent chatter. With a
+/// `session`, only that session's own entries are recalled.
+async fn build_context(mem: &dyn Memory, user_msg: &str, session: Option<&str>) -> String {
+    let sections: [(&str, &[MemoryCategory], usize); 2] = [
+        (
+            "[Memory context: long-term facts]",
+            &[MemoryCategory::Core],
+            5,
+        ),
+        (
+            "[Memory context: recent activity]",
+            &[MemoryCategory::Daily, MemoryCategory::Conversation],
+            3,
+        ),
+    ];
+    let mut context = String::new();
+
+    // Pull relevant memories for this message
+    for (heading, categories, limit) in sections {
+        let fetch = if session.is_some() {
+            limit * SESSION_RECALL_FACTOR
+        } else {
+            limit
+        };
+        if let Ok(mut entries) = mem.recall_in(user_msg, fetch, categories).await {
+            if let Some(session) = session {
+                entries.retain(|entry| is_session_entry(entry, session));
+                entries.truncate(limit);
+            }
+            if !entries.is_empty() {
+                let _ = writeln!(context, "{heading}");
+                for entry in &entries {
+                    let _ = writeln!(context, "- {}: {}", entry.key, entry.content);
+                }
+                context.push('\n');
+            }
+        }
+    }
+
+    context
+}
+
+/// Memory entries for `/memory`: those matching `query`, or the newest ones
+/// when it is empty.
+async fn show_memory(mem: &dyn Memory, query: &str) -> String {
+    const SHOWN: usize = 10;
+    let entries = if query.is_empty() {
+        mem.list(None).await.map(|mut entries| {
+            entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+            entries.truncate(SHOWN);
+            entries
+        })
+    } else {
+        mem.recall(query, SHOWN).await
+    };
+    match entries {
+        Ok(entries) if entries.is_empty() => "[no memory entries]".to_string(),
+        Ok(entries) => entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "- [{}] {}: {}",
+                    entry.category,
+                    entry.key,
+                    truncate_with_ellipsis(&entry.content, 120)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(e) => format!("[could not read memory: {e:#}]"),
+    }
+}
+
+/// The system prompt of a `run`, built again only when something it depends
+/// on changed: the persona, the model, or (after `invalidate`) the skills.
+#[derive(Default)]
+struct PromptCache {
+    prompt: String,
+    /// Persona and model `prompt` was built for; `None` when it is stale
+    built_for: Option<(Option<String>, String)>,
+}
+
+impl PromptCache {
+    /// Build the prompt again on next use, e.g. after skills changed.
+    fn invalidate(&mut self) {
+        self.built_for = None;
+    }
+
+    /// The prompt for `persona` and `model`, calling `build` only if the
+    /// cached one doesn't fit.
+    fn get(
+        &mut self,
+        persona: Option<&str>,
+        model: &str,
+        build: impl FnOnce(Option<&str>, &str) -> String,
+    ) -> &str {
+        let fresh = self
+            .built_for
+            .as_ref()
+            .is_some_and(|(built_persona, built_model)| {
+                built_persona.as_deref() == persona && built_model == model
+            });
+        if !fresh {
+            self.prompt = build(persona, model);
+            self.built_for = Some((persona.map(str::to_string), model.to_string()));
+        }
+        &self.prompt
+    }
+}
+
+/// Conversation carried across the turns of one `run`, saved after every
+/// exchange when a session is active.
+struct Conversation {
+    sessions: SessionStore,
+    session_id: Option<String>,
+    turns: Vec<ChatMessage>,
+}
+
+impl Conversation {
+    /// Resume session `id` (or start it over if `fresh`). `fresh` without an
+    /// id starts a new session under a generated id.
+    fn open(workspace_dir: &std::path::Path, id: Option<String>, fresh: bool) -> Result<Self> {
+        let sessions = SessionStore::new(workspace_dir);
+        let session_id = match id {
+            Some(id) => {
+                if fresh {
+                    sessions.clear(&id)?;
+                }
+                Some(id)
+            }
+            None if fresh => Some(SessionStore::new_id()),
+            None => None,
+        };
+        let turns = match &session_id {
+            Some(id) => sessions.load(id)?,
+            None => Vec::new(),
+        };
+        Ok(Self {
+            sessions,
+            session_id,
+            turns,
+        })
+    }
+
+    /// Messages for the next turn: the recent history plus `message`.
+    fn next_turn(
+        &self,
+        message: String,
+        budget: usize,
+        tokenizer: &dyn Tokenizer,
+    ) -> Vec<ChatMessage> {
+        let mut messages = recent_turns(&self.turns, budget / 2, tokenizer).to_vec();
+        messages.push(ChatMessage::user(message));
+        messages
+    }
+
+    /// Forget the turns so far, in the saved session too.
+    fn reset(&mut self) {
+        self.turns.clear();
+        if let Some(id) = &self.session_id {
+            if let Err(e) = self.sessions.clear(id) {
+                tracing::warn!(session = id.as_str(), "Failed to clear session: {e:#}");
+            }
+        }
+    }
+
+    /// Save the turns so far, starting a session under a fresh id if none is
+    /// active; later turns are saved to it as well. Returns the session id.
+    fn save(&mut self) -> Result<String> {
+        let id = self
+            .session_id
+            .get_or_insert_with(SessionStore::new_id)
+            .clone();
+        self.sessions.save(&id, &self.turns)?;
+        Ok(id)
+    }
+
+    /// Remember a finished exchange and persist it if a session is active.
+    fn record(&mut self, user: &str, response: &str) {
+        self.turns.push(ChatMessage::user(user));
+        self.turns.push(ChatMessage::assistant(response));
+        if let Some(id) = &self.session_id {
+            if let Err(e) = self.sessions.save(id, &self.turns) {
+                tracing::warn!(session = id.as_str(), "Failed to save session: {e:#}");
+            }
+        }
+    }
+}
+
+/// An answer from `AgentRunner::respond`.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    /// The final text answer
+    pub text: String,
+    /// Files returned through the `artifact` tool, in call order
+    pub artifacts: Vec<Artifact>,
+}
+
+/// Long-lived agent for serving requests (e.g. the HTTP channel).
+///
+/// Provider, tools and system prompt are built once; every `respond` runs one
+/// `tool_calling_loop` turn with its own token count. There is nobody to ask
+/// for confirmation, so flagged tool calls are denied as in single-message
+/// mode.
+pub struct AgentRunner {
+    provider: Arc<dyn Provider>,
+    provider_name: String,
+    model_name: String,
+    temperature: f64,
+    system_prompt: String,
+    tools: Vec<Box<dyn Tool>>,
+    settings: LoopSettings,
+    security: Arc<SecurityPolicy>,
+    mem: Arc<dyn Memory>,
+    observer: Arc<dyn Observer>,
+    cache: Option<ResponseCache>,
+    /// Set when answers must be JSON (`agent.response_format`)
+    json_answer: Option<JsonResponse>,
+    auto_save: bool,
+    save_summary: SaveSummary,
+    /// Results of side-effecting calls, replayed if a turn is run again
+    idempotency: IdempotencyRecords,
+}
+
+impl AgentRunner {
+    /// Readiness probes for the provider and memory this runner uses.
+    pub fn probes(&self) -> crate::health::Probes {
+        crate::health::Probes::new(self.provider.clone(), self.mem.clone())
+    }
+
+    /// Build from config the same way `run` does.
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+        let model_name = config
+            .default_model
+            .as_deref()
+            .unwrap_or("anthropic/claude-sonnet-4-20250514");
+        let observer: Arc<dyn Observer> =
+            Arc::from(observability::create_observer(&config.observability));
+        let provider = providers::create_routed_provider(
+            provider_name,
+            config.api_key.as_deref(),
+            &config.reliability,
+            &config.model_routes,
+            model_name,
+            &providers::ProviderOptions::from_config(config),
+            Arc::clone(&observer),
+        )?;
+        let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
+            &config.memory,
+            &config.workspace_dir,
+            config.api_key.as_deref(),
+        )?);
+        Self::with_shared_provider(config, provider_name, Arc::from(provider), mem, observer)
+    }
+
+    /// Build around an existing provider and memory; everything else comes
+    /// from `config`.
+    pub fn with_provider(
+        config: &Config,
+        provider_name: &str,
+        provider: Box<dyn Provider>,
+        mem: Arc<dyn Memory>,
+    ) -> Result<Self> {
+        let observer: Arc<dyn Observer> =
+            Arc::from(observability::create_observer(&config.observability));
+        Self::with_shared_provider(config, provider_name, Arc::from(provider), mem, observer)
+    }
+
+    /// Build around a provider, memory and observer that are also used
+    /// elsewhere, so that e.g. every channel draws on the same rate limits.
+    pub fn with_shared_provider(
+        config: &Config,
+        provider_name: &str,
+        provider: Arc<dyn Provider>,
+        mem: Arc<dyn Memory>,
+        observer: Arc<dyn Observer>,
+    ) -> Result<Self> {
+        let runtime: Arc<dyn runtime::RuntimeAdapter> =
+            Arc::from(runtime::create_runtime(&config.runtime)?);
+        runtime.check_available()?;
+        let security = Arc::new(SecurityPolicy::from_config(
+            &config.autonomy,
+            &config.security,
+            &config.workspace_dir,
+        ));
+        let composio_key = if config.composio.enabled {
+            config.composio.api_key.as_deref()
+        } else {
+            None
+        };
+        let mut tools = tools::all_tools_with_runtime(
+            &security,
+            runtime.clone(),
+            mem.clone(),
+            composio_key,
+            &config.browser,
+            &config.search,
+        );
+        let model_name = config
+            .default_model
+            .clone()
+            .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+        if let Some(subagent) = SubAgentTool::from_config(
+            config,
+            Arc::clone(&provider),
+            &model_name,
+            Arc::clone(&security),
+            runtime,
+            mem.clone(),
+            Arc::clone(&observer),
+        ) {
+            tools.push(Box::new(subagent));
+        }
+        let skills = crate::skills::load_configured_skills(&config.workspace_dir, &config.skills);
+        let tool_specs: Vec<_> = tools.iter().map(|t| t.spec()).collect();
+        let mut system_prompt = crate::channels::build_system_prompt(
+            &config.workspace_dir,
+            &model_name,
+            &tool_specs,
+            &skills,
+            Some(config.active_identity_config()),
+        );
+        let json_answer = JsonResponse::from_config(&config.agent, &config.workspace_dir)?;
+        if let Some(json) = &json_answer {
+            system_prompt.push_str(&json.instructions());
+        }
+
+        Ok(Self {
+            provider,
+            provider_name: provider_name.to_string(),
+            model_name,
+            temperature: config.default_temperature,
+            system_prompt,
+            tools,
+            settings: LoopSettings::from_config(config, false),
+            security,
+            mem,
+            observer,
+            cache: ResponseCache::from_config(&config.agent, &config.workspace_dir),
+            json_answer,
+            auto_save: config.memory.auto_save,
+            save_summary: SaveSummary::from_config(&config.memory),
+            idempotency: IdempotencyRecords::default(),
+        })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model_name
+    }
+
+    /// Answer `message` in the conversation `session_id`, whose earlier
+    /// turns are `prior`. Memory entries written for the turn are keyed by
+    /// the session so concurrent conversations don't overwrite each other.
+    pub async fn respond(
+        &self,
+        session_id: &str,
+        prior: &[ChatMessage],
+        message: &str,
+    ) -> Result<Reply> {
+        self.respond_with(session_id, prior, message, self.settings)
+            .await
+    }
+
+    /// Like `respond`, but sends the text to `chunks` as it streams in,
+    /// exactly as `run` prints it. A JSON answer is checked before it is
+    /// shown, so with `agent.response_format` nothing is streamed.
+    pub async fn respond_stream(
+        &self,
+        session_id: &str,
+        prior: &[ChatMessage],
+        message: &str,
+        chunks: mpsc::UnboundedSender<String>,
+    ) -> Result<Reply> {
+        let settings = LoopSettings {
+            stream: self.json_answer.is_none(),
+            ..self.settings
+        };
+        STREAM_SINK
+            .scope(
+                chunks,
+                self.respond_with(session_id, prior, message, settings),
+            )
+            .await
+    }
+
+    async fn respond_with(
+        &self,
+        session_id: &str,
+        prior: &[ChatMessage],
+        message: &str,
+        settings: LoopSettings,
+    ) -> Result<Reply> {
+        if self.auto_save {
+            let _ = self
+                .mem
+                .store(
+                    &format!("{session_id}_user_msg"),
+                    message,
+                    MemoryCategory::Conversation,
+                )
+                .await;
+        }
+
+        let context = build_context(self.mem.as_ref(), message, Some(session_id)).await;
+        let mut messages =
+            recent_turns(prior, settings.context_budget / 2, settings.tokenizer).to_vec();
+        messages.push(ChatMessage::user(format!("{context}{message}")));
+
+        self.observer.record_event(&ObserverEvent::AgentStart {
+            provider: self.provider_name.clone(),
+            model: self.model_name.clone(),
+        });
+        let start = Instant::now();
+        let mut tokens_used: Option<u64> = None;
+        let mut artifacts = Vec::new();
+        // The tools are shared by every session; keep context_set values and
+        // shell working directories apart
+        let mut result = SessionContext::scope(
+            session_id,
+            tool_calling_loop(
+                self.provider.as_ref(),
+                &self.system_prompt,
+                &messages,
+                &self.model_name,
+                self.temperature,
+                &self.tools,
+                settings,
+                &CancellationToken::new(),
+                self.observer.as_ref(),
+                self.cache.as_ref(),
+                &mut tokens_used,
+                &mut artifacts,
+                ConfirmationGate::new(&self.security, None),
+                Some(&TurnKeys::start(&self.idempotency)),
+            ),
+        )
+        .await;
+        if let (Some(json), Ok(response)) = (&self.json_answer, &result) {
+            result = ensure_json_answer(
+                self.provider.as_ref(),
+                &self.system_prompt,
+                &messages,
+                response.clone(),
+                &self.model_name,
+                self.temperature,
+                json,
+                settings,
+                self.observer.as_ref(),
+                &mut tokens_used,
+            )
+            .await;
+        }
+        self.observer.record_event(&ObserverEvent::AgentEnd {
+            duration: start.elapsed(),
+            tokens_used,
+        });
+        self.observer.flush();
+        let response = result?;
+
+        if self.auto_save {
+            let summary = self
+                .save_summary
+                .summarize(self.provider.as_ref(), &self.model_name, &response)
+                .await;
+            let _ = self
+                .mem
+                .store(
+                    &format!("{session_id}_assistant_resp"),
+                    &summary,
+                    MemoryCategory::Daily,
+                )
+                .await;
+        }
+        Ok(Reply {
+            text: response,
+            artifacts,
+        })
+    }
+}
+
+/// Run the agent on `message`, or interactively when it is `None`.
+///
+/// With `session` the conversation is loaded from and saved to the session
+/// store so a later run can pick it up; `new_session` discards what was saved
+/// (or, without `session`, starts a session under a fresh id). `output`
+/// other than `Normal` needs a `message`.
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
+pub async fn run(
+    config: Config,
+    message: Option<String>,
+    provider_override: Option<String>,
+    model_override: Option<String>,
+    temperature: f64,
+    stream: bool,
+    session: Option<String>,
+    new_session: bool,
+    output: OutputMode,
+) -> Result<()> {
+    if message.is_none() && output != OutputMode::Normal {
+        anyhow::bail!("--quiet and --json need a message (-m or --template)");
+    }
+
+    // ── Wire up agnostic subsystems ──────────────────────────────
+    let observer: Arc<dyn Observer> =
+        Arc::from(observability::create_observer(&config.observability));
+    // `--json` lists the tool calls, so log them on the way to the observer
+    let tool_log =
+        (output == OutputMode::Json).then(|| Arc::new(ToolCallLog::new(Arc::clone(&observer))));
+    let observer: Arc<dyn Observer> = match &tool_log {
+        Some(log) => log.clone(),
+        None => observer,
+    };
+    let runtime: Arc<dyn runtime::RuntimeAdapter> =
+        Arc::from(runtime::create_runtime(&config.runtime)?);
+    runtime.check_available()?;
+    let security = Arc::new(SecurityPolicy::from_config(
+        &config.autonomy,
+        &config.security,
+        &config.workspace_dir,
+    ));
+
+    // ── Memory (the brain) ────────────────────────────────────────
+    let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?);
+    tracing::info!(backend = mem.name(), "Memory initialized");
+    purge_expired_memory(mem.as_ref()).await;
+    let mut last_purge = Instant::now();
+
+    // ── Tools (including memory tools) ────────────────────────────
+    let composio_key = if config.composio.enabled {
+        config.composio.api_key.as_deref()
+    } else {
+        None
+    };
+    let mut agent_tools = tools::all_tools_with_runtime(
+        &security,
+        runtime.clone(),
+        mem.clone(),
+        composio_key,
+        &config.browser,
+        &config.search,
+    );
+    let json_answer = JsonResponse::from_config(&config.agent, &config.workspace_dir)?;
+    // A JSON answer is checked before it is shown, so it can't be streamed
+    let stream = stream && json_answer.is_none() && output == OutputMode::Normal;
+    let mut settings = LoopSettings::from_config(&config, stream);
+    settings.output = output;
+    let cache = ResponseCache::from_config(&config.agent, &config.workspace_dir);
+
+    // ── Resolve provider ─────────────────────────────────────────
+    let provider_name = provider_override
+        .as_deref()
+        .or(config.default_provider.as_deref())
+        .unwrap_or("openrouter");
+
+    let model_name = model_override
+        .as_deref()
+        .or(config.default_model.as_deref())
+        .unwrap_or("anthropic/claude-sonnet-4-20250514");
+    settings.tokenizer = tokenizer::for_model(&config.agent, model_name);
+
+    let provider: Arc<dyn Provider> = Arc::from(providers::create_routed_provider(
+        provider_name,
+        config.api_key.as_deref(),
+        &config.reliability,
+        &config.model_routes,
+        model_name,
+        &providers::ProviderOptions::from_config(&config),
+        Arc::clone(&observer),
+    )?);
+    if let Some(subagent) = SubAgentTool::from_config(
+        &config,
+        Arc::clone(&provider),
+        model_name,
+        Arc::clone(&security),
+        runtime,
+        mem.clone(),
+        Arc::clone(&observer),
+    ) {
+        agent_tools.push(Box::new(subagent));
+    }
+
+    if config.agent.warmup {
+        providers::warm_up(provider.as_ref(), provider_name, observer.as_ref()).await;
+    }
+
+    // Roll an overgrown daily log up into summaries before the session starts
+    memory::compact::compact_if_needed(mem.as_ref(), provider.as_ref(), model_name, &config.memory)
+        .await;
+
+    observer.record_event(&ObserverEvent::AgentStart {
+        provider: provider_name.to_string(),
+        model: model_name.to_string(),
+    });
+
+    // ── Build system prompt from workspace MD files (OpenClaw framework) ──
+    let tool_specs: Vec<_> = agent_tools.iter().map(|t| t.spec()).collect();
+    let build_prompt = |persona: Option<&str>, model: &str| {
+        let mut prompt = crate::channels::build_system_prompt(
+            &config.workspace_dir,
+            model,
+            &tool_specs,
+            &crate::skills::load_configured_skills(&config.workspace_dir, &config.skills),
+            Some(config.identity_named(persona)),
+        );
+        if let Some(json) = &json_answer {
+            prompt.push_str(&json.instructions());
+        }
+        prompt
+    };
+    let mut persona = config.active_identity.clone();
+    let mut prompts = PromptCache::default();
+
+    // ── Conversation (resumed from a saved session if asked) ─────
+    let mut conversation = Conversation::open(&config.workspace_dir, session, new_session)?;
+    if let Some(id) = &conversation.session_id {
+        eprintln!(
+            "Session {id} ({} earlier messages); resume with --session {id}",
+            conversation.turns.len()
+        );
+    }
+
+    // ── Execute ──────────────────────────────────────────────────
+    let start = Instant::now();
+    let mut tokens_used: Option<u64> = None;
+    let idempotency = IdempotencyRecords::default();
+
+    // Memory is flushed and observers drained even when a turn fails
+    let outcome: Result<()> = if let Some(msg) = message {
+        async {
+            // Auto-save user message to memory
+            if config.memory.auto_save {
+                let _ = mem
+                    .store("user_msg", &msg, MemoryCategory::Conversation)
+                    .await;
+            }
+
+            // Inject memory context into user message
+            let context = build_context(mem.as_ref(), &msg, None).await;
+            let enriched = if context.is_empty() {
+                msg.clone()
+            } else {
+                format!("{context}{msg}")
+            };
+
+            let system_prompt = prompts.get(persona.as_deref(), model_name, build_prompt);
+            let turn_messages =
+                conversation.next_turn(enriched, settings.context_budget, settings.tokenizer);
+            let mut artifacts = Vec::new();
+            let mut response = tool_calling_loop(
+                provider.as_ref(),
+                system_prompt,
+                &turn_messages,
+                model_name,
+                temperature,
+                &agent_tools,
+                settings,
+                &CancellationToken::new(),
+                observer.as_ref(),
+                cache.as_ref(),
+                &mut tokens_used,
+                &mut artifacts,
+                ConfirmationGate::new(&security, None),
+                Some(&TurnKeys::start(&idempotency)),
+            )
+            .await
+            .inspect_err(|e| {
+                if output != OutputMode::Json {
+                    print_partial_answer(e, stream);
+                }
+            })?;
+            if let Some(json) = &json_answer {
+                response = ensure_json_answer(
+                    provider.as_ref(),
+                    system_prompt,
+                    &turn_messages,
+                    response,
+                    model_name,
+                    temperature,
+                    json,
+                    settings,
+                    observer.as_ref(),
+                    &mut tokens_used,
+                )
+                .await?;
+            }
+            conversation.record(&msg, &response);
+            if let Some(log) = &tool_log {
+                let report = RunReport::new(
+                    response.clone(),
+                    log.calls(),
+                    tokens_used,
+                    start.elapsed(),
+                    artifacts,
+                );
+                println!("{}", serde_json::to_string(&report)?);
+            } else {
+                if !stream {
+                    println!("{response}");
+                }
+                print_artifacts(&artifacts);
+            }
+
+            // Auto-save assistant response to daily log
+            if config.memory.auto_save {
+                let summary = SaveSummary::from_config(&config.memory)
+                    .summarize(provider.as_ref(), model_name, &response)
+                    .await;
+                let _ = mem
+                    .store("assistant_resp", &summary, MemoryCategory::Daily)
+                    .await;
+            }
+            Ok(())
+        }
+        .await
+    } else {
+        println!("🦀 ZeroClaw Interactive Mode");
+        println!("Type /help for commands, /quit to exit.\n");
+
+        // `/model` switches this for the following turns
+        let mut model = model_name.to_string();
 
         let (tx, mut rx) = tokio::sync::mpsc::channel(32);
         let cli = crate::channels::CliChannel::new();
 
-        // Spawn listener
-        let listen_handle = tokio::spawn(async move {
-            let _ = crate::channels::Channel::listen(&cli, tx).await;
-        });
+        // Deliver messages the agent scheduled for itself into the same queue
+        let schedule_handle = crate::cron::messages::spawn_dispatcher(
+            config.workspace_dir.clone(),
+            vec!["cli".to_string()],
+            &tx,
+            crate::cron::messages::DISPATCH_INTERVAL,
+        );
+
+        // `/quit`, closed stdin and a double Ctrl-C all end the session here
+        let shutdown = CancellationToken::new();
+
+        // Spawn listener
+        let listener_done = shutdown.clone();
+        let listen_handle = tokio::spawn(async move {
+            let _ = crate::channels::Channel::listen(&cli, tx).await;
+            listener_done.cancel();
+        });
+
+        // Ctrl-C cancels the running turn instead of killing the session
+        let turn = Arc::new(Mutex::new(CancellationToken::new()));
+        let ctrl_c_handle = spawn_ctrl_c_handler(Arc::clone(&turn), shutdown.clone());
+
+        // Pick up skill edits between turns; the watcher stops when dropped
+        let skills_changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&skills_changed);
+        let skills_watcher = crate::skills::watch::SkillsWatcher::start(
+            &config.workspace_dir,
+            crate::skills::watch::RELOAD_DEBOUNCE,
+            move || flag.store(true, Ordering::SeqCst),
+        )
+        .inspect_err(|e| tracing::warn!("Skill hot-reload disabled: {e}"))
+        .ok();
+
+        // Only armed while waiting for input, so it never fires mid-turn
+        let idle_after = (config.agent.idle_compact_minutes > 0)
+            .then(|| Duration::from_mins(config.agent.idle_compact_minutes));
+        let mut idle_done = false;
+        let mut failure = None;
+
+        loop {
+            let idle = async {
+                match idle_after {
+                    Some(after) if !idle_done => tokio::time::sleep(after).await,
+                    _ => std::future::pending().await,
+                }
+            };
+            let msg = tokio::select! {
+                biased;
+                () = shutdown.cancelled() => break,
+                msg = rx.recv() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                () = idle => {
+                    tracing::debug!("Session idle, compacting memory and warming up");
+                    purge_expired_memory(mem.as_ref()).await;
+                    last_purge = Instant::now();
+                    memory::compact::compact_if_needed(
+                        mem.as_ref(),
+                        provider.as_ref(),
+                        &model,
+                        &config.memory,
+                    )
+                    .await;
+                    if config.agent.warmup {
+                        providers::warm_up(provider.as_ref(), provider_name, observer.as_ref())
+                            .await;
+                    }
+                    idle_done = true;
+                    continue;
+                }
+            };
+            idle_done = false;
+            if skills_changed.swap(false, Ordering::SeqCst) {
+                prompts.invalidate();
+                eprintln!("[skills reloaded]");
+            }
+
+            if let Some(command) = SlashCommand::parse(&msg.content) {
+                let note = match command {
+                    SlashCommand::Persona(name) => {
+                        let (selected, note) = select_persona(&config, persona.as_deref(), &name);
+                        persona = selected;
+                        note
+                    }
+                    SlashCommand::Reset => {
+                        conversation.reset();
+                        "[conversation reset]".to_string()
+                    }
+                    SlashCommand::Model(name) => {
+                        if !name.is_empty() {
+                            model = name;
+                        }
+                        format!("[model: {model}]")
+                    }
+                    SlashCommand::Save => match conversation.save() {
+                        Ok(id) => format!("[saved session {id}; resume with --session {id}]"),
+                        Err(e) => format!("[could not save the session: {e:#}]"),
+                    },
+                    SlashCommand::Memory(query) => show_memory(mem.as_ref(), &query).await,
+                    SlashCommand::Quit | SlashCommand::Help | SlashCommand::Unknown(_) => {
+                        crate::channels::cli::HELP.to_string()
+                    }
+                };
+                println!("{note}\n");
+                continue;
+            }
+
+            if last_purge.elapsed() >= MEMORY_PURGE_INTERVAL {
+                purge_expired_memory(mem.as_ref()).await;
+                last_purge = Instant::now();
+            }
+
+            // Auto-save conversation turns
+            if config.memory.auto_save {
+                let _ = mem
+                    .store("user_msg", &msg.content, MemoryCategory::Conversation)
+                    .await;
+            }
+
+            // Inject memory context into user message
+            let context = build_context(mem.as_ref(), &msg.content, None).await;
+            let enriched = if context.is_empty() {
+                msg.content.clone()
+            } else {
+                format!("{context}{}", msg.content)
+            };
+
+            let cancel = CancellationToken::new();
+            if let Ok(mut current) = turn.lock() {
+                *current = cancel.clone();
+            }
+            let system_prompt = prompts.get(persona.as_deref(), &model, build_prompt);
+            let turn_messages =
+                conversation.next_turn(enriched, settings.context_budget, settings.tokenizer);
+            let mut artifacts = Vec::new();
+            let mut turn_result = finish_turn(
+                Box::pin(tool_calling_loop(
+                    provider.as_ref(),
+                    system_prompt,
+                    &turn_messages,
+                    &model,
+                    temperature,
+                    &agent_tools,
+                    settings,
+                    &cancel,
+                    observer.as_ref(),
+                    cache.as_ref(),
+                    &mut tokens_used,
+                    &mut artifacts,
+                    ConfirmationGate::new(&security, Some(&mut rx)),
+                    Some(&TurnKeys::start(&idempotency)),
+                )),
+                &shutdown,
+                &cancel,
+                SHUTDOWN_GRACE,
+            )
+            .await;
+            if let (Some(json), Ok(response)) = (&json_answer, &turn_result) {
+                turn_result = ensure_json_answer(
+                    provider.as_ref(),
+                    system_prompt,
+                    &turn_messages,
+                    response.clone(),
+                    &model,
+                    temperature,
+                    json,
+                    settings,
+                    observer.as_ref(),
+                    &mut tokens_used,
+                )
+                .await;
+            }
+            let response = match turn_result {
+                Ok(response) => response,
+                Err(e) if e.is::<TurnCancelled>() => {
+                    println!("\n[turn cancelled]\n");
+                    continue;
+                }
+                Err(e)
+                    if e.is::<InvalidJsonResponse>()
+                        || e.is::<OutputBlocked>()
+                        || e.is::<GuardrailIntervened>() =>
+                {
+                    println!("\n[{e}]\n");
+                    continue;
+                }
+                Err(e) => {
+                    print_partial_answer(&e, stream);
+                    failure = Some(e);
+                    break;
+                }
+            };
+            if stream {
+                println!();
+            } else {
+                println!("\n{response}\n");
+            }
+            print_artifacts(&artifacts);
+            conversation.record(&msg.content, &response);
+
+            if config.memory.auto_save {
+                let summary = SaveSummary::from_config(&config.memory)
+                    .summarize(provider.as_ref(), &model, &response)
+                    .await;
+                let _ = mem
+                    .store("assistant_resp", &summary, MemoryCategory::Daily)
+                    .await;
+            }
+        }
+
+        drop(skills_watcher);
+        listen_handle.abort();
+        ctrl_c_handle.abort();
+        schedule_handle.abort();
+        failure.map_or(Ok(()), Err)
+    };
+
+    shut_down(
+        mem.as_ref(),
+        observer.as_ref(),
+        start.elapsed(),
+        tokens_used,
+    )
+    .await;
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::NoopObserver;
+    use crate::providers::tokenizer::HeuristicTokenizer;
+
+    fn persona_config() -> Config {
+        let mut config = Config::default();
+        config.identities.insert(
+            "pirate".into(),
+            crate::config::IdentityConfig {
+                system_prompt_override: Some("Ye be a pirate.".into()),
+                ..crate::config::IdentityConfig::default()
+            },
+        );
+        config.identities.insert(
+            "butler".into(),
+            crate::config::IdentityConfig {
+                system_prompt_override: Some("You are a butler.".into()),
+                ..crate::config::IdentityConfig::default()
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn switching_persona_changes_system_prompt() {
+        let config = persona_config();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let prompt = |persona: Option<&str>| {
+            crate::channels::build_system_prompt(
+                tmp.path(),
+                "model",
+                &[],
+                &[],
+                Some(config.identity_named(persona)),
+            )
+        };
+
+        let (persona, note) = select_persona(&config, None, "pirate");
+        assert_eq!(persona.as_deref(), Some("pirate"));
+        assert_eq!(note, "[persona: pirate]");
+        let pirate = prompt(persona.as_deref());
+        assert!(pirate.starts_with("Ye be a pirate."), "{pirate}");
+
+        let (persona, _) = select_persona(&config, persona.as_deref(), "butler");
+        let butler = prompt(persona.as_deref());
+        assert!(butler.starts_with("You are a butler."), "{butler}");
+
+        let (persona, note) = select_persona(&config, persona.as_deref(), "default");
+        assert_eq!(persona, None);
+        assert_eq!(note, "[persona: default]");
+        assert_ne!(prompt(persona.as_deref()), butler);
+    }
+
+    #[test]
+    fn prompt_cache_rebuilds_only_when_invalidated() {
+        let builds = std::cell::Cell::new(0);
+        let build = |persona: Option<&str>, model: &str| {
+            builds.set(builds.get() + 1);
+            format!("{}@{model}", persona.unwrap_or("default"))
+        };
+        let mut prompts = PromptCache::default();
+
+        assert_eq!(prompts.get(None, "m1", build), "default@m1");
+        assert_eq!(prompts.get(None, "m1", build), "default@m1");
+        assert_eq!(builds.get(), 1);
+
+        // A persona or model switch, or changed skills, each need a rebuild
+        assert_eq!(prompts.get(Some("pirate"), "m1", build), "pirate@m1");
+        assert_eq!(prompts.get(Some("pirate"), "m2", build), "pirate@m2");
+        assert_eq!(builds.get(), 3);
+        prompts.invalidate();
+        assert_eq!(prompts.get(Some("pirate"), "m2", build), "pirate@m2");
+        assert_eq!(prompts.get(Some("pirate"), "m2", build), "pirate@m2");
+        assert_eq!(builds.get(), 4);
+    }
+
+    #[test]
+    fn unknown_persona_falls_back_to_default() {
+        let config = persona_config();
+        let (persona, note) = select_persona(&config, Some("pirate"), "ghost");
+        assert_eq!(persona, None);
+        assert!(note.contains("unknown persona 'ghost'"), "{note}");
+    }
+
+    #[test]
+    fn bare_persona_command_lists_personas() {
+        let config = persona_config();
+        let (persona, note) = select_persona(&config, Some("pirate"), "");
+        assert_eq!(persona.as_deref(), Some("pirate"));
+        assert_eq!(note, "[persona: pirate] available: default, butler, pirate");
+    }
+
+    #[test]
+    fn parse_single_tool_call() {
+        let response = r#"Let me check that. <tool_call>{"name": "shell", "arguments": {"command": "ls"}}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "shell");
+        assert_eq!(calls[0].arguments["command"], "ls");
+    }
+
+    #[test]
+    fn parse_multiple_tool_calls() {
+        let response = r#"<tool_call>{"name": "file_read", "arguments": {"path": "README.md"}}</tool_call>
+Also: <tool_call>{"name": "shell", "arguments": {"command": "pwd"}}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "file_read");
+        assert_eq!(calls[1].name, "shell");
+    }
+
+    #[test]
+    fn parse_no_tool_calls() {
+        let response = "Just a plain text response with no tools.";
+        let calls = parse_tool_calls(response, &[]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn parse_malformed_json_skipped() {
+        let response = r#"<tool_call>not valid json</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn parse_empty_tool_call_tag() {
+        let response = "<tool_call></tool_call>";
+        let calls = parse_tool_calls(response, &[]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn parse_missing_name_skipped() {
+        let response = r#"<tool_call>{"arguments": {"x": 1}}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn parse_missing_arguments_defaults_to_empty() {
+        let response = r#"<tool_call>{"name": "memory_recall"}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "memory_recall");
+        assert!(calls[0].arguments.is_object());
+    }
+
+    #[test]
+    fn parse_unclosed_tag_ignored() {
+        let response = r#"<tool_call>{"name": "shell", "arguments": {}}"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert!(calls.is_empty());
+    }
+
+    #[test]
+    fn parse_tool_call_with_trailing_comma() {
+        let response =
+            r#"<tool_call>{"name": "shell", "arguments": {"command": "ls",},}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arguments["command"], "ls");
+    }
+
+    #[test]
+    fn parse_tool_call_with_raw_newline_in_string() {
+        let response = "<tool_call>{\"name\": \"file_write\", \"arguments\": {\"content\": \"a\nb\"}}</tool_call>";
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arguments["content"], "a\nb");
+    }
+
+    #[test]
+    fn parse_tool_call_with_double_encoded_arguments() {
+        let response =
+            r#"<tool_call>{"name": "shell", "arguments": "{\"command\": \"ls\"}"}</tool_call>"#;
+        let calls = parse_tool_calls(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arguments, serde_json::json!({"command": "ls"}));
+    }
+
+    #[test]
+    fn repair_json_keeps_commas_inside_strings() {
+        assert_eq!(repair_json(r#"{"a": "x,}", }"#), r#"{"a": "x,}" }"#);
+    }
+
+    #[test]
+    fn parse_unrecoverable_tool_call_reports_error() {
+        let response = r#"<tool_call>{"name": "shell", "arguments": {"command": </tool_call>"#;
+        let (calls, errors) = parse_tool_call_blocks(response, &[]);
+        assert!(calls.is_empty());
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn parse_tool_call_with_tags_inside_arguments() {
+        let response = r#"Writing the docs.
+<tool_call>{"name": "file_write", "arguments": {"path": "a.md", "content": "Wrap calls in <tool_call> and \"</tool_call>\"."}}</tool_call>
+<tool_call>{"name": "file_read", "arguments": {"path": "a.md"}}</tool_call>"#;
+        let (calls, errors) = parse_tool_call_blocks(response, &[]);
+        assert!(errors.is_empty());
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].arguments["content"],
+            "Wrap calls in <tool_call> and \"</tool_call>\"."
+        );
+        assert_eq!(calls[1].name, "file_read");
+        assert_eq!(
+            extract_text_outside_tool_calls(response),
+            "Writing the docs."
+        );
+    }
+
+    #[test]
+    fn parse_recovers_after_second_open_before_close() {
+        let response = r#"<tool_call>{"name": "shell", "arguments": {"command":
+<tool_call>{"name": "shell", "arguments": {"command": "ls"}}</tool_call>
+Done."#;
+        let (calls, errors) = parse_tool_call_blocks(response, &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].arguments["command"], "ls");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("not closed"));
+        assert_eq!(extract_text_outside_tool_calls(response), "Done.");
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_reports_malformed_call_to_model() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "shell", "arguments": </tool_call>"#,
+                "done",
+            ]),
+            usage: None,
+        };
+        let mut tokens_used = None;
+
+        let text = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "done");
+        assert!(provider.responses.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn detects_unclosed_tool_call_tag() {
+        let response =
+            r#"Listing files. <tool_call>{"name": "shell", "arguments": {"command": "ls"}}"#;
+        assert!(looks_like_intended_tool_call(response, &["shell"]));
+        // Closed tags are handled by the parser, not the heuristic
+        assert!(!looks_like_intended_tool_call(
+            "<tool_call>{}</tool_call> done",
+            &["shell"]
+        ));
+    }
+
+    #[test]
+    fn detects_fenced_block_naming_a_tool() {
+        let response = "I'll run it:\n```\n{\"tool\": \"shell\", \"command\": \"ls\"}\n```";
+        assert!(looks_like_intended_tool_call(response, &["shell"]));
+        assert!(!looks_like_intended_tool_call(response, &["file_read"]));
+    }
+
+    #[test]
+    fn ignores_plain_answers() {
+        let tools = ["shell", "file_read"];
+        assert!(!looks_like_intended_tool_call("The shell is bash.", &tools));
+        assert!(!looks_like_intended_tool_call(
+            "Config:\n```json\n{\"port\": 8080}\n```\nThe shell tool is not needed.",
+            &tools
+        ));
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_reprompts_once_after_malformed_call() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "shell", "arguments": {"command": "ls"}"#,
+                "done",
+            ]),
+            usage: None,
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SleepTool {
+            name: "shell",
+            delay_ms: 0,
+            concurrency_safe: true,
+        })];
+        let text = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "done");
+
+        // A second malformed response in a row is accepted as the answer
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                "<tool_call>{\"name\": \"shell\"",
+                "<tool_call>still broken",
+                "unused",
+            ]),
+            usage: None,
+        };
+        let text = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "<tool_call>still broken");
+        assert_eq!(provider.responses.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_records_calls_in_audit_log() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        let security = SecurityPolicy {
+            audit: Some(Arc::new(crate::security::AuditLog::open(
+                path.clone(),
+                vec!["token".into()],
+            ))),
+            ..SecurityPolicy::default()
+        };
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "sleeper", "arguments": {"api_token": "sk-1"}}</tool_call>"#,
+                "done",
+            ]),
+            usage: None,
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SleepTool {
+            name: "sleeper",
+            delay_ms: 0,
+            concurrency_safe: true,
+        })];
+
+        tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&security, None),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let log = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains(r#""tool":"sleeper""#), "{log}");
+        assert!(!log.contains("sk-1"), "{log}");
+        assert_eq!(crate::security::audit::verify_chain(&path).unwrap(), 1);
+    }
+
+    #[test]
+    fn parse_fenced_json_tool_call() {
+        let response = "I'll list the files.\n```json\n{\"name\": \"shell\", \"arguments\": {\"command\": \"ls\"}}\n```";
+        let calls = parse_tool_calls(response, &["shell"]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].name, "shell");
+        assert_eq!(calls[0].arguments["command"], "ls");
+    }
+
+    #[test]
+    fn parse_fenced_json_without_call_shape_ignored() {
+        let response = "Here is the config:\n```json\n{\"name\": \"shell\", \"version\": 2}\n```";
+        assert!(parse_tool_calls(response, &["shell"]).is_empty());
+    }
+
+    #[test]
+    fn parse_fenced_json_with_unknown_tool_ignored() {
+        let response = "```json\n{\"name\": \"alice\", \"arguments\": {}}\n```";
+        assert!(parse_tool_calls(response, &["shell"]).is_empty());
+    }
+
+    #[test]
+    fn parse_mixed_tag_and_fenced_calls_in_order() {
+        let response = concat!(
+            "```json\n{\"name\": \"file_read\", \"arguments\": {\"path\": \"a.txt\"}}\n```\n",
+            r#"<tool_call>{"name": "shell", "arguments": {"command": "ls"}}</tool_call>"#,
+        );
+        let calls = parse_tool_calls(response, &["shell", "file_read"]);
+        let names: Vec<_> = calls.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["file_read", "shell"]);
+    }
+
+    #[test]
+    fn recent_turns_keeps_whole_exchanges_within_budget() {
+        let turns = vec![
+            ChatMessage::user("a".repeat(400)),
+            ChatMessage::assistant("b".repeat(400)),
+            ChatMessage::user("c".repeat(40)),
+            ChatMessage::assistant("d".repeat(40)),
+        ];
+        assert_eq!(
+            recent_turns(&turns, 10_000, &HeuristicTokenizer),
+            &turns[..]
+        );
+        assert_eq!(recent_turns(&turns, 100, &HeuristicTokenizer), &turns[2..]);
+        // Never starts on an assistant message
+        assert!(recent_turns(&turns, 15, &HeuristicTokenizer).is_empty());
+        assert!(recent_turns(&[], 100, &HeuristicTokenizer).is_empty());
+    }
+
+    #[test]
+    fn format_tool_results_output() {
+        let results = vec![(
+            "shell".to_string(),
+            crate::tools::ToolResult {
+                success: true,
+                output: "hello".into(),
+                error: None,
+            },
+        )];
+        let formatted = format_tool_results(&results);
+        assert!(formatted.contains(r#"<tool_result name="shell">"#));
+        assert!(formatted.contains(r#""success":true"#));
+        assert!(formatted.contains(r#""output":"hello""#));
+        assert!(formatted.contains("</tool_result>"));
+    }
+
+    #[test]
+    fn format_tool_results_with_error() {
+        let results = vec![(
+            "file_read".to_string(),
+            crate::tools::ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("permission denied".into()),
+            },
+        )];
+        let formatted = format_tool_results(&results);
+        assert!(formatted.contains(r#""success":false"#));
+        assert!(formatted.contains("permission denied"));
+    }
+
+    #[test]
+    fn extract_text_outside_calls() {
+        let response = r#"Before <tool_call>{"name":"x","arguments":{}}</tool_call> After"#;
+        let text = extract_text_outside_tool_calls(response);
+        assert_eq!(text, "Before  After");
+    }
+
+    #[test]
+    fn extract_text_no_calls() {
+        let response = "Just plain text.";
+        let text = extract_text_outside_tool_calls(response);
+        assert_eq!(text, "Just plain text.");
+    }
+
+    /// Never answers, so only cancellation can end the call.
+    struct HangingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for HangingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_returns_promptly_when_cancelled() {
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            trigger.cancel();
+        });
+        let mut tokens_used = None;
+
+        let start = Instant::now();
+        let err = tool_calling_loop(
+            &HangingProvider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &cancel,
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is::<TurnCancelled>());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_cancelled_before_start_skips_provider() {
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec!["unused"]),
+            usage: None,
+        };
+        let mut tokens_used = None;
+
+        let err = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &cancel,
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.is::<TurnCancelled>());
+        assert_eq!(provider.responses.lock().unwrap().len(), 1);
+    }
+
+    fn test_settings() -> LoopSettings {
+        LoopSettings::from_config(&Config::default(), false)
+    }
+
+    fn exchange(n: usize, size: usize) -> [ChatMessage; 2] {
+        [
+            ChatMessage::assistant(format!("call {n} {}", "a".repeat(size))),
+            ChatMessage::user(format!("[Tool Results]\n{n} {}", "r".repeat(size))),
+        ]
+    }
+
+    #[test]
+    fn trim_history_under_budget_is_untouched() {
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("task")];
+        history.extend(exchange(1, 10));
+        let before = history.clone();
+        trim_history(&mut history, 2, 1_000, &HeuristicTokenizer);
+        assert_eq!(history, before);
+    }
+
+    #[test]
+    fn trim_history_drops_oldest_turns_and_keeps_first_user_message() {
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("the task")];
+        for n in 0..20 {
+            history.extend(exchange(n, 400));
+        }
+        let budget = 1_000;
+        assert!(estimate_tokens(&history, &HeuristicTokenizer) > budget);
+
+        trim_history(&mut history, 2, budget, &HeuristicTokenizer);
+
+        assert!(estimate_tokens(&history, &HeuristicTokenizer) <= budget);
+        assert_eq!(history[0], ChatMessage::system("sys"));
+        assert_eq!(history[1], ChatMessage::user("the task"));
+        assert_eq!(history[2].content, OMITTED_TURNS_MARKER);
+        assert!(history
+            .last()
+            .unwrap()
+            .content
+            .starts_with("[Tool Results]\n19 "));
+        // Roles still alternate after the marker
+        assert_eq!(history[3].role, providers::traits::Role::Assistant);
+    }
+
+    #[test]
+    fn trim_history_keeps_latest_exchange_even_over_budget() {
+        let mut history = vec![ChatMessage::system("sys"), ChatMessage::user("task")];
+        history.extend(exchange(0, 4_000));
+        history.extend(exchange(1, 4_000));
+        trim_history(&mut history, 2, 10, &HeuristicTokenizer);
+        assert_eq!(history.len(), 5);
+        assert_eq!(
+            history
+                .iter()
+                .filter(|m| m.content == OMITTED_TURNS_MARKER)
+                .count(),
+            1
+        );
+        assert!(history[4].content.starts_with("[Tool Results]\n1 "));
+    }
+
+    /// Replays scripted responses, each reporting fixed token usage.
+    struct ScriptedProvider {
+        responses: std::sync::Mutex<Vec<&'static str>>,
+        usage: Option<providers::traits::ResponseUsage>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(self.responses.lock().unwrap().remove(0).to_string())
+        }
+
+        async fn chat_with_usage(
+            &self,
+            messages: &[ChatMessage],
+            model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            let text = self.chat(messages, model, temperature).await?;
+            Ok(providers::ChatResponse {
+                text,
+                usage: self.usage,
+                ..providers::ChatResponse::default()
+            })
+        }
+    }
+
+    /// Answers "ok" and records the history of every call.
+    #[derive(Default)]
+    struct RecordingProvider {
+        seen: std::sync::Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for RecordingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("ok".into())
+        }
+
+        async fn chat_with_usage(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            self.seen.lock().unwrap().push(messages.to_vec());
+            Ok(providers::ChatResponse {
+                text: format!("answer {}", self.seen.lock().unwrap().len()),
+                ..providers::ChatResponse::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_collects_artifacts_apart_from_answer() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "artifact", "arguments": {"filename": "report.csv", "content": "a,b\n1,2"}}</tool_call>"#,
+                "The report is attached.",
+            ]),
+            usage: None,
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(tools::ArtifactTool::new(security))];
+        let mut artifacts = Vec::new();
+
+        let text = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("export the numbers")],
+            "model",
+            0.0,
+            &tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut artifacts,
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(text, "The report is attached.");
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].filename, "report.csv");
+        assert_eq!(artifacts[0].content, "a,b\n1,2");
+    }
+
+    async fn cached_turn(provider: &dyn Provider, cache: &ResponseCache, model: &str) -> String {
+        tool_calling_loop(
+            provider,
+            "system",
+            &[ChatMessage::user("what is 2 + 2?")],
+            model,
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            Some(cache),
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_reuses_cached_response() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache = ResponseCache::new(tmp.path(), Duration::from_secs(60), false);
+        let provider = RecordingProvider::default();
+
+        assert_eq!(cached_turn(&provider, &cache, "model-a").await, "answer 1");
+        assert_eq!(cached_turn(&provider, &cache, "model-a").await, "answer 1");
+        assert_eq!(provider.seen.lock().unwrap().len(), 1);
+
+        // Another model is a different key
+        assert_eq!(cached_turn(&provider, &cache, "model-b").await, "answer 2");
+        assert_eq!(provider.seen.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn streamed_responses_are_cached_too() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cache = ResponseCache::new(tmp.path(), Duration::from_secs(60), false);
+        let provider =
+            providers::mock::MockProvider::new(vec!["four, I think".into(), "five".into()]);
+
+        for _ in 0..2 {
+            let (sink, mut shown) = mpsc::unbounded_channel();
+            let text = STREAM_SINK
+                .scope(
+                    sink,
+                    tool_calling_loop(
+                        &provider,
+                        "system",
+                        &[ChatMessage::user("what is 2 + 2?")],
+                        "model",
+                        0.0,
+                        &[],
+                        LoopSettings::from_config(&Config::default(), true),
+                        &CancellationToken::new(),
+                        &NoopObserver,
+                        Some(&cache),
+                        &mut None,
+                        &mut Vec::new(),
+                        ConfirmationGate::new(&SecurityPolicy::default(), None),
+                        None,
+                    ),
+                )
+                .await
+                .unwrap();
+            // The second turn is answered from the cache and shown whole
+            assert_eq!(text, "four, I think");
+            let mut chunks = Vec::new();
+            while let Ok(chunk) = shown.try_recv() {
+                chunks.push(chunk);
+            }
+            assert_eq!(chunks.concat(), "four, I think\n");
+        }
+    }
+
+    async fn session_turn(conversation: &mut Conversation, provider: &dyn Provider, text: &str) {
+        let messages = conversation.next_turn(text.to_string(), 10_000, &HeuristicTokenizer);
+        let response = tool_calling_loop(
+            provider,
+            "system",
+            &messages,
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        conversation.record(text, &response);
+    }
+
+    #[tokio::test]
+    async fn resumed_session_replays_prior_turns() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let provider = RecordingProvider::default();
+
+        let mut first = Conversation::open(tmp.path(), Some("demo".into()), false).unwrap();
+        session_turn(&mut first, &provider, "my name is Ada").await;
+        drop(first);
+
+        let mut resumed = Conversation::open(tmp.path(), Some("demo".into()), false).unwrap();
+        assert_eq!(resumed.turns.len(), 2);
+        session_turn(&mut resumed, &provider, "what is my name?").await;
+
+        let seen = provider.seen.lock().unwrap();
+        assert_eq!(
+            seen[1],
+            vec![
+                ChatMessage::system("system"),
+                ChatMessage::user("my name is Ada"),
+                ChatMessage::assistant("answer 1"),
+                ChatMessage::user("what is my name?"),
+            ]
+        );
+        drop(seen);
+
+        // Starting over discards the saved turns
+        let fresh = Conversation::open(tmp.path(), Some("demo".into()), true).unwrap();
+        assert!(fresh.turns.is_empty());
+        assert!(SessionStore::new(tmp.path())
+            .load("demo")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn conversation_without_session_is_not_persisted() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let provider = RecordingProvider::default();
+        let mut conversation = Conversation::open(tmp.path(), None, false).unwrap();
+        session_turn(&mut conversation, &provider, "one").await;
+        session_turn(&mut conversation, &provider, "two").await;
+
+        // Turns still carry over within the run
+        assert_eq!(provider.seen.lock().unwrap()[1].len(), 4);
+        assert!(!tmp.path().join("sessions").exists());
+        assert!(Conversation::open(tmp.path(), None, true)
+            .unwrap()
+            .session_id
+            .is_some());
+    }
+
+    async fn json_turn(provider: &dyn Provider, answer: &str) -> Result<String> {
+        ensure_json_answer(
+            provider,
+            "system",
+            &[ChatMessage::user("list two colours")],
+            answer.to_string(),
+            "model",
+            0.0,
+            &JsonResponse::new(None),
+            test_settings(),
+            &NoopObserver,
+            &mut None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn json_answer_that_parses_is_accepted_without_reprompt() {
+        let provider = RecordingProvider::default();
+        let answer = json_turn(&provider, "```json\n[\"red\", \"blue\"]\n```")
+            .await
+            .unwrap();
+        assert_eq!(answer, "[\"red\", \"blue\"]");
+        assert!(provider.seen.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn invalid_json_answer_is_reprompted_once() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![r#"{"colours": ["red", "blue"]}"#]),
+            usage: None,
+        };
+        let answer = json_turn(&provider, "Red and blue.").await.unwrap();
+        assert_eq!(answer, r#"{"colours": ["red", "blue"]}"#);
+        assert!(provider.responses.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn json_answer_still_invalid_after_reprompt_is_typed_error() {
+        let provider = RecordingProvider::default();
+        let err = json_turn(&provider, "Red and blue.").await.unwrap_err();
+        let invalid = err.downcast_ref::<InvalidJsonResponse>().unwrap();
+        assert_eq!(invalid.response, "answer 1");
+
+        let seen = provider.seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        let history = &seen[0];
+        assert_eq!(
+            history[history.len() - 2],
+            ChatMessage::assistant("Red and blue.")
+        );
+        assert!(history
+            .last()
+            .unwrap()
+            .content
+            .starts_with("Your answer was rejected: expected value"));
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_sums_token_usage() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "missing", "arguments": {}}</tool_call>"#,
+                "done",
+            ]),
+            usage: Some(providers::traits::ResponseUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+            }),
+        };
+        let mut tokens_used = None;
+
+        let response = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response, "done");
+        assert_eq!(tokens_used, Some(240));
+    }
+
+    /// Records the running totals reported through `BudgetUpdate`.
+    #[derive(Default)]
+    struct BudgetObserver {
+        totals: std::sync::Mutex<Vec<u64>>,
+    }
+
+    impl Observer for BudgetObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            if let ObserverEvent::BudgetUpdate { tokens_used, .. } = event {
+                self.totals.lock().unwrap().push(*tokens_used);
+            }
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "budget"
+        }
+    }
+
+    /// Records every event it sees.
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: std::sync::Mutex<Vec<ObserverEvent>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+
+        fn record_metric(&self, _metric: &crate::observability::traits::ObserverMetric) {}
+
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    /// Answers with reasoning kept apart from the text.
+    struct ThinkingProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ThinkingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("42".into())
+        }
+
+        async fn chat_with_usage(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            Ok(providers::ChatResponse {
+                text: "42".into(),
+                reasoning: Some("Six times seven.".into()),
+                ..providers::ChatResponse::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_reports_reasoning_only_when_enabled() {
+        for show_reasoning in [false, true] {
+            let observer = RecordingObserver::default();
+            let text = tool_calling_loop(
+                &ThinkingProvider,
+                "system",
+                &[ChatMessage::user("6 * 7?")],
+                "model",
+                0.0,
+                &[],
+                LoopSettings {
+                    show_reasoning,
+                    ..test_settings()
+                },
+                &CancellationToken::new(),
+                &observer,
+                None,
+                &mut None,
+                &mut Vec::new(),
+                ConfirmationGate::new(&SecurityPolicy::default(), None),
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(text, "42");
+
+            let reasoning: Vec<String> = observer
+                .events
+                .lock()
+                .unwrap()
+                .iter()
+                .filter_map(|event| match event {
+                    ObserverEvent::Reasoning { text } => Some(text.clone()),
+                    _ => None,
+                })
+                .collect();
+            if show_reasoning {
+                assert_eq!(reasoning, ["Six times seven."]);
+            } else {
+                assert!(reasoning.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_reports_tool_start_and_end() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                concat!(
+                    r#"<tool_call>{"name": "sleeper", "arguments": {"api_token": "sk-1"}}</tool_call>"#,
+                    r#"<tool_call>{"name": "read", "arguments": {"lines": 3}}</tool_call>"#,
+                ),
+                "done",
+            ]),
+            usage: None,
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(SleepTool {
+                name: "sleeper",
+                delay_ms: 0,
+                concurrency_safe: true,
+            }),
+            Box::new(SchemaTool {
+                executions: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            }),
+        ];
+        let observer = RecordingObserver::default();
+
+        tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &observer,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+
+        let events = observer.events.into_inner().unwrap();
+        let tool_events: Vec<_> = events
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e,
+                    ObserverEvent::ToolStart { .. } | ObserverEvent::ToolEnd { .. }
+                )
+            })
+            .collect();
+        assert_eq!(tool_events.len(), 4, "{events:?}");
+
+        let ObserverEvent::ToolStart {
+            name,
+            arguments_summary,
+        } = tool_events[0]
+        else {
+            panic!("expected ToolStart, got {:?}", tool_events[0]);
+        };
+        assert_eq!(name, "sleeper");
+        assert!(
+            arguments_summary.contains("[REDACTED]"),
+            "{arguments_summary}"
+        );
+        assert!(!arguments_summary.contains("sk-1"), "{arguments_summary}");
+
+        let ends: Vec<(&str, bool)> = tool_events
+            .iter()
+            .filter_map(|e| match e {
+                ObserverEvent::ToolEnd { name, success, .. } => Some((name.as_str(), *success)),
+                _ => None,
+            })
+            .collect();
+        assert!(ends.contains(&("sleeper", true)), "{ends:?}");
+        // Missing required `path` fails validation
+        assert!(ends.contains(&("read", false)), "{ends:?}");
+    }
+
+    #[test]
+    fn summarize_arguments_redacts_and_truncates() {
+        let summary = summarize_arguments(&serde_json::json!({
+            "password": "hunter2",
+            "text": "x".repeat(500),
+        }));
+        assert!(!summary.contains("hunter2"));
+        assert!(summary.contains(r#""password":"[REDACTED]""#), "{summary}");
+        let (document, _) = summary.rsplit_once(" [truncated JSON").unwrap();
+        assert!(
+            document.chars().count() <= TOOL_ARGS_SUMMARY_CHARS,
+            "{summary}"
+        );
+        serde_json::from_str::<Value>(document).unwrap();
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_stops_when_token_budget_exceeded() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"step one <tool_call>{"name": "missing", "arguments": {}}</tool_call>"#,
+                r#"step two <tool_call>{"name": "missing", "arguments": {}}</tool_call>"#,
+                r#"step three <tool_call>{"name": "missing", "arguments": {}}</tool_call>"#,
+                "done",
+            ]),
+            usage: Some(providers::traits::ResponseUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+            }),
+        };
+        let observer = BudgetObserver::default();
+        let settings = LoopSettings {
+            max_total_tokens: Some(300),
+            ..test_settings()
+        };
+        let mut tokens_used = None;
+
+        let err = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            settings,
+            &CancellationToken::new(),
+            &observer,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        let exceeded = err.downcast_ref::<BudgetExceeded>().unwrap();
+        assert_eq!(exceeded.tokens_used, 360);
+        assert_eq!(exceeded.limit, 300);
+        assert_eq!(exceeded.partial, "step three");
+        // Halted after the third call; the final response was never requested
+        assert_eq!(provider.responses.lock().unwrap().len(), 1);
+        assert_eq!(*observer.totals.lock().unwrap(), vec![120, 240, 360]);
+        assert_eq!(tokens_used, Some(360));
+    }
+
+    #[tokio::test]
+    async fn streamed_calls_count_toward_the_token_budget() {
+        let provider = providers::mock::MockProvider::new(vec!["a long streamed answer".into()]);
+        let observer = BudgetObserver::default();
+        let settings = LoopSettings {
+            stream: true,
+            max_total_tokens: Some(5),
+            ..test_settings()
+        };
+        let mut tokens_used = None;
+        let (sink, mut shown) = mpsc::unbounded_channel();
+
+        let err = STREAM_SINK
+            .scope(
+                sink,
+                tool_calling_loop(
+                    &provider,
+                    "system",
+                    &[ChatMessage::user("hi")],
+                    "model",
+                    0.0,
+                    &[],
+                    settings,
+                    &CancellationToken::new(),
+                    &observer,
+                    None,
+                    &mut tokens_used,
+                    &mut Vec::new(),
+                    ConfirmationGate::new(&SecurityPolicy::default(), None),
+                    None,
+                ),
+            )
+            .await
+            .unwrap_err();
+
+        let exceeded = err.downcast_ref::<BudgetExceeded>().unwrap();
+        assert!(exceeded.tokens_used > 5, "{exceeded}");
+        assert_eq!(exceeded.partial, "a long streamed answer");
+        assert_eq!(*observer.totals.lock().unwrap(), vec![exceeded.tokens_used]);
+        assert_eq!(tokens_used, Some(exceeded.tokens_used));
+        // The reply was streamed before the budget check stopped the run
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = shown.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.concat(), "a long streamed answer\n");
+    }
+
+    #[tokio::test]
+    async fn build_context_separates_facts_from_recent_activity() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mem = memory::MarkdownMemory::new(tmp.path());
+        mem.store("lang", "prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("chat", "asked about Rust traits", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let context = build_context(&mem, "Rust", None).await;
+        let facts = context.find("[Memory context: long-term facts]").unwrap();
+        let recent = context.find("[Memory context: recent activity]").unwrap();
+        let fact = context.find("prefers Rust").unwrap();
+        let chat = context.find("asked about Rust traits").unwrap();
+        assert!(facts < fact && fact < recent && recent < chat);
+
+        assert!(build_context(&mem, "python", None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_without_usage_leaves_tokens_none() {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec!["plain answer"]),
+            usage: None,
+        };
+        let mut tokens_used = None;
+
+        tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(tokens_used.is_none());
+    }
+
+    /// Sleeps for `delay_ms`, then echoes its name.
+    struct SleepTool {
+        name: &'static str,
+        delay_ms: u64,
+        concurrency_safe: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for SleepTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "sleeps"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn concurrency_safe(&self) -> bool {
+            self.concurrency_safe
+        }
+
+        async fn execute(&self, _args: Value) -> anyhow::Result<crate::tools::ToolResult> {
+            tokio::time::sleep(std::time::Duration::from_millis(self.delay_ms)).await;
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: self.name.to_string(),
+                error: None,
+            })
+        }
+    }
+
+    /// Counts how many times it was executed.
+    struct CountingTool {
+        executions: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for CountingTool {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn description(&self) -> &str {
+            "counts executions"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        async fn execute(&self, _args: Value) -> anyhow::Result<crate::tools::ToolResult> {
+            let n = self
+                .executions
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: format!("run {n}"),
+                error: None,
+            })
+        }
+    }
+
+    /// Replays scripted responses and records the history of every call.
+    struct ReplayingProvider {
+        responses: std::sync::Mutex<Vec<&'static str>>,
+        seen: std::sync::Mutex<Vec<Vec<ChatMessage>>>,
+        temperatures: std::sync::Mutex<Vec<f64>>,
+    }
+
+    impl ReplayingProvider {
+        fn new(responses: Vec<&'static str>) -> Self {
+            Self {
+                responses: std::sync::Mutex::new(responses),
+                seen: std::sync::Mutex::new(Vec::new()),
+                temperatures: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ReplayingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("the loop sends the whole history")
+        }
+
+        async fn chat_with_usage(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            self.seen.lock().unwrap().push(messages.to_vec());
+            self.temperatures.lock().unwrap().push(temperature);
+            Ok(providers::ChatResponse {
+                text: self.responses.lock().unwrap().remove(0).to_string(),
+                ..providers::ChatResponse::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn latest_tool_history_keeps_one_result_per_call() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "meeting at noon").unwrap();
+        std::fs::write(tmp.path().join("todo.txt"), "buy milk").unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(crate::tools::FileReadTool::new(security))];
+
+        for (tool_history, full_reads) in [(ToolHistory::All, 2), (ToolHistory::Latest, 1)] {
+            let provider = ReplayingProvider::new(vec![
+                r#"<tool_call>{"name": "file_read", "arguments": {"path": "notes.txt"}}</tool_call>"#,
+                r#"<tool_call>{"name": "file_read", "arguments": {"path": "notes.txt"}}</tool_call>
+<tool_call>{"name": "file_read", "arguments": {"path": "todo.txt"}}</tool_call>"#,
+                "Meeting at noon, then buy milk.",
+            ]);
+            tool_calling_loop(
+                &provider,
+                "system",
+                &[ChatMessage::user("what's on today?")],
+                "model",
+                0.0,
+                &tools,
+                LoopSettings {
+                    tool_history,
+                    ..test_settings()
+                },
+                &CancellationToken::new(),
+                &NoopObserver,
+                None,
+                &mut None,
+                &mut Vec::new(),
+                ConfirmationGate::new(&SecurityPolicy::default(), None),
+                None,
+            )
+            .await
+            .unwrap();
+
+            let seen = provider.seen.lock().unwrap();
+            let last: String = seen[2].iter().map(|m| m.content.as_str()).collect();
+            assert_eq!(last.matches("meeting at noon").count(), full_reads);
+            assert_eq!(last.matches("buy milk").count(), 1);
+            assert_eq!(last.matches(SUPERSEDED).count(), 2 - full_reads);
+            // Conversation structure is unchanged: system, task, two exchanges
+            assert_eq!(seen[2].len(), 6);
+        }
+    }
+
+    /// One token per character, four times the heuristic's count.
+    #[derive(Debug)]
+    struct CharTokenizer;
+
+    impl Tokenizer for CharTokenizer {
+        fn count_tokens(&self, text: &str) -> usize {
+            text.chars().count()
+        }
+    }
+
+    #[tokio::test]
+    async fn history_is_trimmed_by_the_configured_tokenizer() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "x".repeat(400)).unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(crate::tools::FileReadTool::new(security))];
+
+        // ~1,100 characters by the last call: within 600 tokens by the
+        // heuristic, over it one token per character
+        let tokenizers: [(&'static dyn Tokenizer, bool); 2] =
+            [(&HeuristicTokenizer, false), (&CharTokenizer, true)];
+        for (tokenizer, trimmed) in tokenizers {
+            let provider = ReplayingProvider::new(vec![
+                r#"<tool_call>{"name": "file_read", "arguments": {"path": "notes.txt"}}</tool_call>"#,
+                r#"<tool_call>{"name": "file_read", "arguments": {"path": "notes.txt"}}</tool_call>"#,
+                "Just x's.",
+            ]);
+            tool_calling_loop(
+                &provider,
+                "system",
+                &[ChatMessage::user("what's in my notes?")],
+                "model",
+                0.0,
+                &tools,
+                LoopSettings {
+                    context_budget: 600,
+                    tokenizer,
+                    ..test_settings()
+                },
+                &CancellationToken::new(),
+                &NoopObserver,
+                None,
+                &mut None,
+                &mut Vec::new(),
+                ConfirmationGate::new(&SecurityPolicy::default(), None),
+                None,
+            )
+            .await
+            .unwrap();
+
+            let seen = provider.seen.lock().unwrap();
+            let omitted = seen[2]
+                .iter()
+                .any(|message| message.content == OMITTED_TURNS_MARKER);
+            assert_eq!(omitted, trimmed, "{tokenizer:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn phase_temperatures_follow_whether_tools_are_offered() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "meeting at noon").unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(crate::tools::FileReadTool::new(security))];
+        let phased = LoopSettings {
+            tool_temperature: Some(0.1),
+            final_temperature: Some(0.9),
+            ..test_settings()
+        };
+
+        let no_tools: Vec<Box<dyn Tool>> = Vec::new();
+
+        // Unset, every call uses the single temperature. Set, calls that offer
+        // tools use the tool-phase one, with no second call for the answer;
+        // calls without tools, like the wrap-up at the iteration limit, use
+        // the final-phase one.
+        for (settings, tools, temperatures) in [
+            (test_settings(), &tools, vec![0.7, 0.7]),
+            (phased, &tools, vec![0.1, 0.1]),
+            (
+                LoopSettings {
+                    max_iterations: 1,
+                    ..phased
+                },
+                &tools,
+                vec![0.1, 0.9],
+            ),
+            (phased, &no_tools, vec![0.9]),
+        ] {
+            let provider = ReplayingProvider::new(if tools.is_empty() {
+                vec!["Meeting at noon."]
+            } else {
+                vec![
+                    r#"<tool_call>{"name": "file_read", "arguments": {"path": "notes.txt"}}</tool_call>"#,
+                    "Meeting at noon.",
+                ]
+            });
+            let response = tool_calling_loop(
+                &provider,
+                "system",
+                &[ChatMessage::user("what's on today?")],
+                "model",
+                0.7,
+                tools,
+                settings,
+                &CancellationToken::new(),
+                &NoopObserver,
+                None,
+                &mut None,
+                &mut Vec::new(),
+                ConfirmationGate::new(&SecurityPolicy::default(), None),
+                None,
+            )
+            .await
+            .unwrap();
+
+            assert_eq!(response, "Meeting at noon.");
+            assert_eq!(*provider.temperatures.lock().unwrap(), temperatures);
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_skips_writes_but_runs_reads() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "existing notes").unwrap();
+        let security = Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(crate::tools::FileReadTool::new(security.clone())),
+            Box::new(crate::tools::FileWriteTool::new(security)),
+        ];
+        let calls = [
+            ToolCall {
+                name: "file_write".into(),
+                arguments: serde_json::json!({"path": "out.txt", "content": "new"}),
+            },
+            ToolCall {
+                name: "file_read".into(),
+                arguments: serde_json::json!({"path": "notes.txt"}),
+            },
+        ];
+
+        let results = execute_tool_calls(
+            &tools,
+            &calls,
+            Duration::from_secs(5),
+            true,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+
+        assert!(results[0].1.success);
+        assert_eq!(results[0].1.output, DRY_RUN_OUTPUT);
+        assert!(!tmp.path().join("out.txt").exists());
+        assert!(results[1].1.success);
+        assert_eq!(results[1].1.output, "existing notes");
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_collapses_identical_calls() {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool {
+            executions: Arc::clone(&executions),
+        })];
+        let first = ToolCall {
+            name: "counter".into(),
+            arguments: serde_json::json!({"path": "a.txt", "mode": "w"}),
+        };
+        // Same arguments with keys in a different order
+        let duplicate = ToolCall {
+            name: "counter".into(),
+            arguments: serde_json::from_str(r#"{"mode": "w", "path": "a.txt"}"#).unwrap(),
+        };
+        let different = ToolCall {
+            name: "counter".into(),
+            arguments: serde_json::json!({"path": "b.txt", "mode": "w"}),
+        };
+
+        let results = execute_tool_calls(
+            &tools,
+            &[first, different, duplicate],
+            Duration::from_secs(5),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].1.output, results[2].1.output);
+        assert_ne!(results[0].1.output, results[1].1.output);
+    }
+
+    /// `CountingTool` that opts in to idempotency keys.
+    struct IdempotentCountingTool(CountingTool);
+
+    #[async_trait::async_trait]
+    impl Tool for IdempotentCountingTool {
+        fn name(&self) -> &str {
+            self.0.name()
+        }
+
+        fn description(&self) -> &str {
+            self.0.description()
+        }
+
+        fn parameters_schema(&self) -> Value {
+            self.0.parameters_schema()
+        }
+
+        fn idempotency_key_supported(&self, _args: &Value) -> bool {
+            true
+        }
+
+        async fn execute(&self, args: Value) -> anyhow::Result<crate::tools::ToolResult> {
+            self.0.execute(args).await
+        }
+    }
+
+    #[tokio::test]
+    async fn replayed_keyed_call_returns_recorded_result() {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(IdempotentCountingTool(CountingTool {
+            executions: Arc::clone(&executions),
+        }))];
+        let calls = [ToolCall {
+            name: "counter".into(),
+            arguments: serde_json::json!({"url": "https://example.com", "method": "POST"}),
+        }];
+        let records = IdempotencyRecords::new(Duration::from_secs(60));
+        let turn = TurnKeys::start(&records);
+
+        let first = execute_tool_calls_once(
+            &tools,
+            &calls,
+            Some(&turn),
+            0,
+            test_settings(),
+            &NoopObserver,
+        )
+        .await;
+        let replay = execute_tool_calls_once(
+            &tools,
+            &calls,
+            Some(&turn),
+            0,
+            test_settings(),
+            &NoopObserver,
+        )
+        .await;
+
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(first[0].1.output, "run 0");
+        assert_eq!(replay[0].1.output, "run 0");
+
+        // A later iteration is a new call, not a replay
+        let next = execute_tool_calls_once(
+            &tools,
+            &calls,
+            Some(&turn),
+            1,
+            test_settings(),
+            &NoopObserver,
+        )
+        .await;
+        assert_eq!(next[0].1.output, "run 1");
+    }
+
+    #[tokio::test]
+    async fn calls_to_tools_without_idempotency_keys_run_again() {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(CountingTool {
+            executions: Arc::clone(&executions),
+        })];
+        let calls = [ToolCall {
+            name: "counter".into(),
+            arguments: serde_json::json!({}),
+        }];
+        let records = IdempotencyRecords::new(Duration::from_secs(60));
+        let turn = TurnKeys::start(&records);
+
+        for _ in 0..2 {
+            execute_tool_calls_once(
+                &tools,
+                &calls,
+                Some(&turn),
+                0,
+                test_settings(),
+                &NoopObserver,
+            )
+            .await;
+        }
+
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// One turn of the loop in which the model makes one keyed call.
+    async fn keyed_call_turn(tools: &[Box<dyn Tool>], turn: &TurnKeys<'_>) -> String {
+        let provider = ScriptedProvider {
+            responses: std::sync::Mutex::new(vec![
+                r#"<tool_call>{"name": "counter", "arguments": {"method": "POST"}}</tool_call>"#,
+                "done",
+            ]),
+            usage: None,
+        };
+        tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("send it")],
+            "model",
+            0.0,
+            tools,
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            Some(turn),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn identical_separate_turns_both_execute_but_a_retry_does_not() {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(IdempotentCountingTool(CountingTool {
+            executions: Arc::clone(&executions),
+        }))];
+        let records = IdempotencyRecords::default();
+
+        keyed_call_turn(&tools, &TurnKeys::start(&records)).await;
+        let second = TurnKeys::start(&records);
+        keyed_call_turn(&tools, &second).await;
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // Running the second turn again replays its result
+        keyed_call_turn(&tools, &second).await;
+        assert_eq!(executions.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Sets a context value for "remember", reads it for "recall" and
+    /// answers with the tool results.
+    struct ContextProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for ContextProvider {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            let last = messages.last().map_or("", |m| m.content.as_str());
+            Ok(if last.starts_with("[Tool Results]") {
+                last.to_string()
+            } else if last.contains("recall") {
+                r#"<tool_call>{"name": "context_get", "arguments": {"key": "colour"}}</tool_call>"#
+                    .into()
+            } else {
+                r#"<tool_call>{"name": "context_set", "arguments": {"key": "colour", "value": "blue"}}</tool_call>"#
+                    .into()
+            })
+        }
+    }
+
+    #[test]
+    fn runner_uses_the_shared_provider() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        let provider: Arc<dyn Provider> = Arc::new(ContextProvider);
+        let runner = AgentRunner::with_shared_provider(
+            &config,
+            "context",
+            Arc::clone(&provider),
+            Arc::new(crate::memory::MarkdownMemory::new(tmp.path())),
+            Arc::new(NoopObserver),
+        )
+        .unwrap();
+        assert!(std::ptr::addr_eq(
+            Arc::as_ptr(&runner.provider),
+            Arc::as_ptr(&provider)
+        ));
+    }
+
+    #[tokio::test]
+    async fn respond_keeps_context_values_per_session() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        let runner = AgentRunner::with_provider(
+            &config,
+            "context",
+            Box::new(ContextProvider),
+            Arc::new(crate::memory::MarkdownMemory::new(tmp.path())),
+        )
+        .unwrap();
+
+        let set = runner.respond("a", &[], "remember blue").await.unwrap();
+        assert!(set.text.contains("Set context: colour"), "{}", set.text);
+
+        let other = runner.respond("b", &[], "recall it").await.unwrap();
+        assert!(
+            other.text.contains("No context value for 'colour'"),
+            "{}",
+            other.text
+        );
+        let same = runner.respond("a", &[], "recall it").await.unwrap();
+        assert!(same.text.contains("blue"), "{}", same.text);
+    }
+
+    #[test]
+    fn canonical_json_sorts_nested_keys() {
+        let a: Value =
+            serde_json::from_str(r#"{"b": {"y": 1, "x": [2, {"q": 1, "p": 0}]}, "a": "s"}"#)
+                .unwrap();
+        assert_eq!(
+            canonical_json(&a),
+            r#"{"a":"s","b":{"x":[2,{"p":0,"q":1}],"y":1}}"#
+        );
+    }
+
+    fn call(name: &str) -> ToolCall {
+        ToolCall {
+            name: name.into(),
+            arguments: serde_json::json!({}),
+        }
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_runs_safe_tools_in_parallel() {
+        let tools: Vec<Box<dyn Tool>> = vec![
+            Box::new(SleepTool {
+                name: "slow",
+                delay_ms: 300,
+                concurrency_safe: true,
+            }),
+            Box::new(SleepTool {
+                name: "fast",
+                delay_ms: 200,
+                concurrency_safe: true,
+            }),
+        ];
+
+        let start = Instant::now();
+        let results = execute_tool_calls(
+            &tools,
+            &[call("slow"), call("fast")],
+            Duration::from_secs(5),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+        let elapsed = start.elapsed();
+
+        assert!(
+            elapsed < std::time::Duration::from_millis(450),
+            "expected ~max(300, 200)ms, took {elapsed:?}"
+        );
+        let names: Vec<_> = results
+            .iter()
+            .map(|(n, r)| (n.as_str(), r.output.as_str()))
+            .collect();
+        assert_eq!(names, [("slow", "slow"), ("fast", "fast")]);
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_serializes_unsafe_tools() {
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SleepTool {
+            name: "exclusive",
+            delay_ms: 150,
+            concurrency_safe: false,
+        })];
+
+        let start = Instant::now();
+        let results = execute_tool_calls(
+            &tools,
+            &[
+                ToolCall {
+                    name: "exclusive".into(),
+                    arguments: serde_json::json!({"step": 1}),
+                },
+                ToolCall {
+                    name: "exclusive".into(),
+                    arguments: serde_json::json!({"step": 2}),
+                },
+            ],
+            Duration::from_secs(5),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+
+        assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+        assert_eq!(results.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_reports_unknown_tool_in_order() {
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SleepTool {
+            name: "known",
+            delay_ms: 0,
+            concurrency_safe: true,
+        })];
+
+        let results = execute_tool_calls(
+            &tools,
+            &[call("missing"), call("known")],
+            Duration::from_secs(5),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+        assert_eq!(results[0].0, "missing");
+        assert!(!results[0].1.success);
+        assert_eq!(results[1].0, "known");
+        assert!(results[1].1.success);
+    }
 
-        while let Some(msg) = rx.recv().await {
-            // Auto-save conversation turns
-            if config.memory.auto_save {
-                let _ = mem
-                    .store("user_msg", &msg.content, MemoryCategory::Conversation)
-                    .await;
-            }
+    #[tokio::test]
+    async fn execute_tool_calls_times_out_slow_tool() {
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SleepTool {
+            name: "hang",
+            delay_ms: 10_000,
+            concurrency_safe: true,
+        })];
 
-            // Inject memory context into user message
-            let context = build_context(mem.as_ref(), &msg.content).await;
-            let enriched = if context.is_empty() {
-                msg.content.clone()
-            } else {
-                format!("{context}{}", msg.content)
-            };
+        let start = Instant::now();
+        let results = execute_tool_calls(
+            &tools,
+            &[call("hang")],
+            Duration::from_millis(50),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
 
-            let response = tool_calling_loop(
-                provider.as_ref(),
-                &system_prompt,
-                &enriched,
-                model_name,
-                temperature,
-                &agent_tools,
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert!(!results[0].1.success);
+        assert_eq!(
+            results[0].1.error.as_deref(),
+            Some("tool timed out after 0.05s")
+        );
+    }
+
+    fn answer(content: &str) -> ChannelMessage {
+        ChannelMessage {
+            id: "1".into(),
+            sender: "user".into(),
+            content: content.into(),
+            channel: "cli".into(),
+            timestamp: 0,
+        }
+    }
+
+    fn shell_call(command: &str) -> ToolCall {
+        ToolCall {
+            name: "shell".into(),
+            arguments: serde_json::json!({ "command": command }),
+        }
+    }
+
+    #[tokio::test]
+    async fn confirmation_gate_passes_unflagged_calls() {
+        let security = SecurityPolicy::default();
+        let mut gate = ConfirmationGate::new(&security, None);
+        let call = shell_call("ls -la");
+        assert_eq!(gate.check(&call).await.unwrap(), call);
+    }
+
+    #[tokio::test]
+    async fn confirmation_gate_denies_without_channel() {
+        let security = SecurityPolicy::default();
+        let mut gate = ConfirmationGate::new(&security, None);
+        let denied = gate.check(&shell_call("git push")).await.unwrap_err();
+        assert!(!denied.success);
+        assert!(denied.error.unwrap().contains("single-message mode"));
+    }
+
+    #[tokio::test]
+    async fn confirmation_gate_asks_once_per_identical_action() {
+        let security = SecurityPolicy::default();
+        let (tx, mut rx) = mpsc::channel(4);
+        tx.send(answer("y")).await.unwrap();
+        tx.send(answer("n")).await.unwrap();
+        let mut gate = ConfirmationGate::new(&security, Some(&mut rx));
+
+        let approved = gate.check(&shell_call("git push")).await.unwrap();
+        assert_eq!(approved.arguments["approved"], true);
+        // The same action again reuses the "y" instead of reading the "n"
+        assert!(gate.check(&shell_call("git push")).await.is_ok());
+        // A different action consumes the next answer
+        let declined = gate.check(&shell_call("git commit")).await.unwrap_err();
+        assert_eq!(
+            declined.error.as_deref(),
+            Some("The user declined to run shell")
+        );
+        assert!(gate.check(&shell_call("git commit")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn confirmation_gate_keeps_denials_in_position() {
+        let security = SecurityPolicy::default();
+        let mut gate = ConfirmationGate::new(&security, None);
+        let calls = [shell_call("ls"), shell_call("git push"), shell_call("pwd")];
+        let (runnable, denied) = gate.check_all(&calls).await;
+        assert_eq!(runnable, [shell_call("ls"), shell_call("pwd")]);
+        assert_eq!(denied.len(), 1);
+        assert_eq!(denied[0].0, 1);
+    }
+
+    /// Declares a required string `path` and an optional integer `lines`,
+    /// and counts how many times it actually ran.
+    struct SchemaTool {
+        executions: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for SchemaTool {
+        fn name(&self) -> &str {
+            "read"
+        }
+
+        fn description(&self) -> &str {
+            "reads lines"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "path": {"type": "string"},
+                    "lines": {"type": "integer"}
+                },
+                "required": ["path"]
+            })
+        }
+
+        async fn execute(&self, _args: Value) -> anyhow::Result<crate::tools::ToolResult> {
+            self.executions
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(crate::tools::ToolResult {
+                success: true,
+                output: "ok".into(),
+                error: None,
+            })
+        }
+    }
+
+    async fn run_schema_tool(arguments: Value) -> (crate::tools::ToolResult, usize) {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(SchemaTool {
+            executions: Arc::clone(&executions),
+        })];
+        let call = ToolCall {
+            name: "read".into(),
+            arguments,
+        };
+        let mut results = execute_tool_calls(
+            &tools,
+            &[call],
+            Duration::from_secs(5),
+            false,
+            test_settings().tool_retry,
+            &NoopObserver,
+        )
+        .await;
+        let runs = executions.load(std::sync::atomic::Ordering::SeqCst);
+        (results.remove(0).1, runs)
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_rejects_missing_required_argument() {
+        let (result, runs) = run_schema_tool(serde_json::json!({"lines": 3})).await;
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("Invalid arguments for read: missing required argument 'path'")
+        );
+        assert_eq!(runs, 0);
+    }
+
+    #[tokio::test]
+    async fn execute_tool_calls_rejects_wrong_argument_type() {
+        let (result, runs) =
+            run_schema_tool(serde_json::json!({"path": "a.txt", "lines": "ten"})).await;
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some(
+                "Invalid arguments for read: argument 'lines' must be of type \"integer\", got \"ten\""
             )
-            .await?;
-            println!("\n{response}\n");
+        );
+        assert_eq!(runs, 0);
+    }
 
-            if config.memory.auto_save {
-                let summary = truncate_with_ellipsis(&response, 100);
-                let _ = mem
-                    .store("assistant_resp", &summary, MemoryCategory::Daily)
-                    .await;
-            }
+    #[tokio::test]
+    async fn execute_tool_calls_accepts_valid_arguments() {
+        let (result, runs) =
+            run_schema_tool(serde_json::json!({"path": "a.txt", "lines": null, "extra": 1})).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(runs, 1);
+    }
+
+    /// Fails with a transient error the first `failures` times it runs.
+    struct FlakyTool {
+        failures: usize,
+        retryable: bool,
+        executions: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Tool for FlakyTool {
+        fn name(&self) -> &str {
+            "flaky"
         }
 
-        listen_handle.abort();
+        fn description(&self) -> &str {
+            "fails, then works"
+        }
+
+        fn parameters_schema(&self) -> Value {
+            serde_json::json!({"type": "object"})
+        }
+
+        fn is_retryable(&self, _result: &crate::tools::ToolResult) -> bool {
+            self.retryable
+        }
+
+        async fn execute(&self, _args: Value) -> anyhow::Result<crate::tools::ToolResult> {
+            let run = self
+                .executions
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let failed = run < self.failures;
+            Ok(crate::tools::ToolResult {
+                success: !failed,
+                output: if failed { String::new() } else { "done".into() },
+                error: failed.then(|| "connection reset".to_string()),
+            })
+        }
     }
 
-    let duration = start.elapsed();
-    observer.record_event(&ObserverEvent::AgentEnd {
-        duration,
-        tokens_used: None,
-    });
+    /// Run `flaky` once through `execute_tool_calls`; returns the result and
+    /// how many times the tool ran.
+    async fn run_flaky(
+        failures: usize,
+        retryable: bool,
+        max_retries: u32,
+    ) -> (crate::tools::ToolResult, usize) {
+        let executions = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(FlakyTool {
+            failures,
+            retryable,
+            executions: Arc::clone(&executions),
+        })];
+        let call = ToolCall {
+            name: "flaky".into(),
+            arguments: serde_json::json!({}),
+        };
+        let retry = ToolRetry {
+            max_retries,
+            backoff: Duration::from_millis(1),
+        };
+        let mut results = execute_tool_calls(
+            &tools,
+            &[call],
+            Duration::from_secs(5),
+            false,
+            retry,
+            &NoopObserver,
+        )
+        .await;
+        assert_eq!(results.len(), 1);
+        let runs = executions.load(std::sync::atomic::Ordering::SeqCst);
+        (results.remove(0).1, runs)
+    }
 
-    Ok(())
-}
+    #[tokio::test]
+    async fn execute_tool_calls_retries_transient_failure() {
+        let (result, runs) = run_flaky(1, true, 2).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "done");
+        assert_eq!(runs, 2);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn execute_tool_calls_retry_is_bounded_and_opt_in() {
+        let (result, runs) = run_flaky(5, true, 2).await;
+        assert_eq!(result.error.as_deref(), Some("connection reset"));
+        assert_eq!(runs, 3);
 
-    #[test]
-    fn parse_single_tool_call() {
-        let response = r#"Let me check that. <tool_call>{"name": "shell", "arguments": {"command": "ls"}}</tool_call>"#;
-        let calls = parse_tool_calls(response);
-        assert_eq!(calls.len(), 1);
-        assert_eq!(calls[0].name, "shell");
-        assert_eq!(calls[0].arguments["command"], "ls");
+        let (result, runs) = run_flaky(1, false, 2).await;
+        assert!(!result.success);
+        assert_eq!(runs, 1);
     }
 
     #[test]
-    fn parse_multiple_tool_calls() {
-        let response = r#"<tool_call>{"name": "file_read", "arguments": {"path": "README.md"}}</tool_call>
-Also: <tool_call>{"name": "shell", "arguments": {"command": "pwd"}}</tool_call>"#;
-        let calls = parse_tool_calls(response);
-        assert_eq!(calls.len(), 2);
-        assert_eq!(calls[0].name, "file_read");
-        assert_eq!(calls[1].name, "shell");
+    fn tool_retry_backoff_doubles() {
+        let retry = ToolRetry {
+            max_retries: 3,
+            backoff: Duration::from_millis(500),
+        };
+        assert_eq!(retry.delay(0), Duration::from_millis(500));
+        assert_eq!(retry.delay(2), Duration::from_secs(2));
     }
 
     #[test]
-    fn parse_no_tool_calls() {
-        let response = "Just a plain text response with no tools.";
-        let calls = parse_tool_calls(response);
-        assert!(calls.is_empty());
+    fn validate_tool_arguments_checks_shape() {
+        let schema = serde_json::json!({
+            "properties": {"tags": {"type": "array"}, "n": {"type": ["integer", "null"]}},
+            "required": ["tags"]
+        });
+        assert!(validate_tool_arguments(&schema, &serde_json::json!({"tags": []})).is_ok());
+        assert!(
+            validate_tool_arguments(&schema, &serde_json::json!({"tags": [], "n": 1.5})).is_err()
+        );
+        assert!(validate_tool_arguments(&schema, &serde_json::json!("tags")).is_err());
+        assert!(validate_tool_arguments(&schema, &Value::Null).is_err());
+        assert!(validate_tool_arguments(&serde_json::json!({}), &Value::Null).is_ok());
     }
 
-    #[test]
-    fn parse_malformed_json_skipped() {
-        let response = r#"<tool_call>not valid json</tool_call>"#;
-        let calls = parse_tool_calls(response);
-        assert!(calls.is_empty());
+    fn filter_all(chunks: &[&str]) -> String {
+        let mut filter = ToolCallFilter::default();
+        let mut out: String = chunks.iter().map(|c| filter.push(c)).collect();
+        out.push_str(&filter.finish());
+        out
     }
 
     #[test]
-    fn parse_empty_tool_call_tag() {
-        let response = "<tool_call></tool_call>";
-        let calls = parse_tool_calls(response);
-        assert!(calls.is_empty());
+    fn tool_call_filter_passes_plain_text() {
+        let mut filter = ToolCallFilter::default();
+        assert_eq!(filter.push("Hello "), "Hello ");
+        assert_eq!(filter.push("world"), "world");
+        assert_eq!(filter.finish(), "");
     }
 
     #[test]
-    fn parse_missing_name_skipped() {
-        let response = r#"<tool_call>{"arguments": {"x": 1}}</tool_call>"#;
-        let calls = parse_tool_calls(response);
-        assert!(calls.is_empty());
+    fn tool_call_filter_flushes_text_before_tag_opening_mid_stream() {
+        let mut filter = ToolCallFilter::default();
+        assert_eq!(filter.push("Let me check.<tool"), "Let me check.");
+        assert_eq!(filter.push("_call>{\"name\": \"shell\"}</tool_"), "");
+        assert_eq!(filter.push("call> Done"), " Done");
     }
 
     #[test]
-    fn parse_missing_arguments_defaults_to_empty() {
-        let response = r#"<tool_call>{"name": "memory_recall"}</tool_call>"#;
-        let calls = parse_tool_calls(response);
-        assert_eq!(calls.len(), 1);
-        assert_eq!(calls[0].name, "memory_recall");
-        assert!(calls[0].arguments.is_object());
+    fn tool_call_filter_releases_false_partial_tag() {
+        assert_eq!(filter_all(&["a <to", "ast> b"]), "a <toast> b");
+        assert_eq!(filter_all(&["ends with <tool"]), "ends with <tool");
     }
 
     #[test]
-    fn parse_unclosed_tag_ignored() {
-        let response = r#"<tool_call>{"name": "shell", "arguments": {}}"#;
-        let calls = parse_tool_calls(response);
-        assert!(calls.is_empty());
+    fn tool_call_filter_drops_unterminated_call() {
+        assert_eq!(filter_all(&["x<tool_call>{\"name\""]), "x");
     }
 
     #[test]
-    fn format_tool_results_output() {
-        let results = vec![(
-            "shell".to_string(),
-            crate::tools::ToolResult {
-                success: true,
-                output: "hello".into(),
-                error: None,
-            },
-        )];
-        let formatted = format_tool_results(&results);
-        assert!(formatted.contains(r#"<tool_result name="shell">"#));
-        assert!(formatted.contains(r#""success":true"#));
-        assert!(formatted.contains(r#""output":"hello""#));
-        assert!(formatted.contains("</tool_result>"));
+    fn format_tool_calls_round_trips_through_parser() {
+        let calls = vec![ToolCall {
+            name: "shell".into(),
+            arguments: serde_json::json!({"command": "ls"}),
+        }];
+        assert_eq!(parse_tool_calls(&format_tool_calls(&calls), &[]), calls);
     }
 
-    #[test]
-    fn format_tool_results_with_error() {
-        let results = vec![(
-            "file_read".to_string(),
-            crate::tools::ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some("permission denied".into()),
-            },
-        )];
-        let formatted = format_tool_results(&results);
-        assert!(formatted.contains(r#""success":false"#));
-        assert!(formatted.contains("permission denied"));
+    /// Returns one native tool call, then a final answer, recording each history.
+    struct NativeToolProvider {
+        histories: std::sync::Mutex<Vec<Vec<ChatMessage>>>,
     }
 
-    #[test]
-    fn extract_text_outside_calls() {
-        let response = r#"Before <tool_call>{"name":"x","arguments":{}}</tool_call> After"#;
-        let text = extract_text_outside_tool_calls(response);
-        assert_eq!(text, "Before  After");
+    #[async_trait::async_trait]
+    impl Provider for NativeToolProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            anyhow::bail!("native path expected")
+        }
+
+        async fn chat_with_tools(
+            &self,
+            messages: &[ChatMessage],
+            _tools: &[ToolSpec],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            let mut histories = self.histories.lock().unwrap();
+            histories.push(messages.to_vec());
+            if histories.len() == 1 {
+                Ok(providers::ChatResponse {
+                    tool_calls: vec![ToolCall {
+                        name: "missing".into(),
+                        arguments: serde_json::json!({}),
+                    }],
+                    ..providers::ChatResponse::default()
+                })
+            } else {
+                Ok(providers::ChatResponse {
+                    text: "done".into(),
+                    ..providers::ChatResponse::default()
+                })
+            }
+        }
     }
 
-    #[test]
-    fn extract_text_no_calls() {
-        let response = "Just plain text.";
-        let text = extract_text_outside_tool_calls(response);
-        assert_eq!(text, "Just plain text.");
+    /// Calls a tool on every turn; with `answer_at_limit` it gives in once told
+    /// it cannot call tools any more.
+    struct ToolHungryProvider {
+        answer_at_limit: bool,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for ToolHungryProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            unreachable!("the loop sends full histories")
+        }
+
+        async fn chat_with_usage(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<providers::ChatResponse> {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let at_limit = messages.last().unwrap().content == ITERATION_LIMIT_PROMPT;
+            let text = if at_limit && self.answer_at_limit {
+                "Best effort: the file has 3 lines.".to_string()
+            } else {
+                format!(
+                    "Step {n}. <tool_call>{{\"name\": \"missing\", \"arguments\": {{}}}}</tool_call>"
+                )
+            };
+            Ok(providers::ChatResponse {
+                text,
+                ..providers::ChatResponse::default()
+            })
+        }
+    }
+
+    async fn run_tool_hungry(provider: &ToolHungryProvider) -> String {
+        tool_calling_loop(
+            provider,
+            "system",
+            &[ChatMessage::user("count the lines")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_asks_for_answer_at_iteration_limit() {
+        let provider = ToolHungryProvider {
+            answer_at_limit: true,
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        };
+        let text = run_tool_hungry(&provider).await;
+        assert_eq!(text, "Best effort: the file has 3 lines.");
+        assert_eq!(
+            provider.calls.load(std::sync::atomic::Ordering::SeqCst),
+            MAX_TOOL_ITERATIONS + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_marks_partial_result_at_iteration_limit() {
+        let provider = ToolHungryProvider {
+            answer_at_limit: false,
+            calls: std::sync::atomic::AtomicUsize::new(0),
+        };
+        let text = run_tool_hungry(&provider).await;
+        assert!(text.starts_with("Step 0."), "{text}");
+        assert!(
+            text.contains(&format!("Step {MAX_TOOL_ITERATIONS}.")),
+            "{text}"
+        );
+        assert!(text.ends_with(ITERATION_LIMIT_NOTE), "{text}");
+        assert!(!text.contains("<tool_call>"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn tool_calling_loop_prefers_native_tool_calls() {
+        let provider = NativeToolProvider {
+            histories: std::sync::Mutex::new(Vec::new()),
+        };
+        let mut tokens_used = None;
+
+        let text = tool_calling_loop(
+            &provider,
+            "system",
+            &[ChatMessage::user("hi")],
+            "model",
+            0.0,
+            &[],
+            test_settings(),
+            &CancellationToken::new(),
+            &NoopObserver,
+            None,
+            &mut tokens_used,
+            &mut Vec::new(),
+            ConfirmationGate::new(&SecurityPolicy::default(), None),
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(text, "done");
+
+        let histories = provider.histories.lock().unwrap();
+        let second = &histories[1];
+        assert_eq!(second.len(), 4);
+        assert!(second[2].content.contains("<tool_call>"));
+        assert!(second[2].content.contains("\"missing\""));
+        assert!(second[3].content.starts_with("[Tool Results]"));
+    }
+
+    #[tokio::test]
+    async fn stream_mode_keeps_native_tool_calls_when_tools_are_offered() {
+        let provider = NativeToolProvider {
+            histories: std::sync::Mutex::new(Vec::new()),
+        };
+        let tools: Vec<Box<dyn Tool>> = vec![Box::new(crate::tools::FileReadTool::new(Arc::new(
+            SecurityPolicy::default(),
+        )))];
+        let (sink, mut shown) = mpsc::unbounded_channel();
+
+        let text = STREAM_SINK
+            .scope(
+                sink,
+                tool_calling_loop(
+                    &provider,
+                    "system",
+                    &[ChatMessage::user("hi")],
+                    "model",
+                    0.0,
+                    &tools,
+                    LoopSettings::from_config(&Config::default(), true),
+                    &CancellationToken::new(),
+                    &NoopObserver,
+                    None,
+                    &mut None,
+                    &mut Vec::new(),
+                    ConfirmationGate::new(&SecurityPolicy::default(), None),
+                    None,
+                ),
+            )
+            .await
+            .unwrap();
+
+        // Both calls went through `chat_with_tools`; the answer is still shown
+        assert_eq!(text, "done");
+        assert_eq!(provider.histories.lock().unwrap().len(), 2);
+        let mut chunks = Vec::new();
+        while let Ok(chunk) = shown.try_recv() {
+            chunks.push(chunk);
+        }
+        assert_eq!(chunks.concat(), "done\n");
+    }
+
+    /// Holds nothing; counts `flush` calls.
+    #[derive(Default)]
+    struct FlushRecordingMemory {
+        flushes: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Memory for FlushRecordingMemory {
+        fn name(&self) -> &str {
+            "flush-recording"
+        }
+
+        async fn store(&self, _key: &str, _content: &str, _category: MemoryCategory) -> Result<()> {
+            Ok(())
+        }
+
+        async fn recall_in(
+            &self,
+            _query: &str,
+            _limit: usize,
+            _categories: &[MemoryCategory],
+        ) -> Result<Vec<memory::MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn get(&self, _key: &str) -> Result<Option<memory::MemoryEntry>> {
+            Ok(None)
+        }
+
+        async fn list(
+            &self,
+            _category: Option<&MemoryCategory>,
+        ) -> Result<Vec<memory::MemoryEntry>> {
+            Ok(Vec::new())
+        }
+
+        async fn forget(&self, _key: &str) -> Result<bool> {
+            Ok(false)
+        }
+
+        async fn count(&self) -> Result<usize> {
+            Ok(0)
+        }
+
+        async fn flush(&self) -> Result<()> {
+            self.flushes.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        async fn health_check(&self) -> bool {
+            true
+        }
+    }
+
+    /// Records events and counts `shutdown` calls.
+    #[derive(Default)]
+    struct ShutdownRecordingObserver {
+        events: std::sync::Mutex<Vec<ObserverEvent>>,
+        shutdowns: std::sync::atomic::AtomicUsize,
+    }
+
+    impl Observer for ShutdownRecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            self.events.lock().unwrap().push(event.clone());
+        }
+
+        fn record_metric(&self, _metric: &ObserverMetric) {}
+
+        fn shutdown(&self) {
+            self.shutdowns.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn name(&self) -> &str {
+            "shutdown-recording"
+        }
+    }
+
+    #[tokio::test]
+    async fn shut_down_flushes_memory_and_drains_observer() {
+        let mem = FlushRecordingMemory::default();
+        let observer = ShutdownRecordingObserver::default();
+
+        shut_down(&mem, &observer, Duration::from_secs(3), Some(42)).await;
+
+        assert_eq!(mem.flushes.load(Ordering::SeqCst), 1);
+        assert_eq!(observer.shutdowns.load(Ordering::SeqCst), 1);
+        let events = observer.events.lock().unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [ObserverEvent::AgentEnd {
+                tokens_used: Some(42),
+                ..
+            }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn failed_turn_still_shuts_down() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let log = tmp.path().join("events.jsonl");
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            config_path: tmp.path().join("config.toml"),
+            // Nothing listens on the discard port, so the turn fails
+            default_provider: Some("custom:http://127.0.0.1:9".into()),
+            ..Config::default()
+        };
+        config.reliability.provider_retries = 0;
+        config.agent.warmup = false;
+        config.observability.backend = "log".into();
+        config.observability.format = "json".into();
+        config.observability.log_path = Some(log.display().to_string());
+
+        let result = run(
+            config,
+            Some("hello".into()),
+            None,
+            None,
+            0.7,
+            false,
+            None,
+            false,
+            OutputMode::Normal,
+        )
+        .await;
+
+        assert!(result.is_err());
+        let events = std::fs::read_to_string(&log).unwrap();
+        assert!(events.contains("\"agent_end\""), "{events}");
+    }
+
+    #[tokio::test]
+    async fn finish_turn_lets_running_turn_complete_within_grace() {
+        let shutdown = CancellationToken::new();
+        let cancel = CancellationToken::new();
+        shutdown.cancel();
+
+        let turn = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            "done"
+        };
+        let out = finish_turn(turn, &shutdown, &cancel, Duration::from_secs(5)).await;
+        assert_eq!(out, "done");
+        assert!(!cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn finish_turn_cancels_turn_after_grace() {
+        let shutdown = CancellationToken::new();
+        let cancel = CancellationToken::new();
+        shutdown.cancel();
+
+        let turn = async {
+            cancel.cancelled().await;
+            "cancelled"
+        };
+        let out = tokio::time::timeout(
+            Duration::from_secs(5),
+            finish_turn(turn, &shutdown, &cancel, Duration::from_millis(20)),
+        )
+        .await
+        .expect("turn should be cancelled once the grace period ends");
+        assert_eq!(out, "cancelled");
+        assert!(cancel.is_cancelled());
     }
 }
@@ -1,3 +1,10 @@
+pub mod cache;
+pub mod idempotency;
 pub mod loop_;
+pub mod report;
+pub mod session;
+pub mod structured;
+pub mod subagent;
+pub mod template;
 
-pub use loop_::run;
+pub use loop_::{run, AgentRunner, Reply};
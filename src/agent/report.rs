@@ -0,0 +1,206 @@
+use crate::observability::{Observer, ObserverEvent, ObserverMetric};
+use crate::tools::artifact::Artifact;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How `zeroclaw agent -m` prints its result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The answer as it streams in, with the model's intermediate text on
+    /// stderr
+    #[default]
+    Normal,
+    /// Only the final answer (`--quiet`)
+    Quiet,
+    /// One JSON object with the answer, tool calls, usage and duration
+    /// (`--json`)
+    Json,
+}
+
+impl OutputMode {
+    /// Mode for the `--quiet` / `--json` flags.
+    pub fn from_flags(quiet: bool, json: bool) -> Self {
+        if json {
+            Self::Json
+        } else if quiet {
+            Self::Quiet
+        } else {
+            Self::Normal
+        }
+    }
+
+    /// Whether the model's intermediate text and reasoning are printed.
+    pub fn shows_progress(self) -> bool {
+        self == Self::Normal
+    }
+}
+
+/// A tool call made during the run, as reported under `--json`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ToolCallRecord {
+    pub name: String,
+    /// Redacted, truncated arguments, as sent to observers
+    pub arguments: String,
+    /// `None` while the call is still running
+    pub success: Option<bool>,
+    pub duration_ms: u64,
+}
+
+/// Observer that passes everything on to `inner` and keeps a log of the
+/// tool calls, for the `--json` report.
+pub struct ToolCallLog {
+    inner: Arc<dyn Observer>,
+    calls: Mutex<Vec<ToolCallRecord>>,
+}
+
+impl ToolCallLog {
+    pub fn new(inner: Arc<dyn Observer>) -> Self {
+        Self {
+            inner,
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The calls recorded so far, in the order they started.
+    pub fn calls(&self) -> Vec<ToolCallRecord> {
+        self.calls
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl Observer for ToolCallLog {
+    fn record_event(&self, event: &ObserverEvent) {
+        match event {
+            ObserverEvent::ToolStart {
+                name,
+                arguments_summary,
+            } => self
+                .calls
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push(ToolCallRecord {
+                    name: name.clone(),
+                    arguments: arguments_summary.clone(),
+                    success: None,
+                    duration_ms: 0,
+                }),
+            ObserverEvent::ToolEnd {
+                name,
+                success,
+                duration,
+            } => {
+                let mut calls = self
+                    .calls
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                // Concurrent calls may end out of order; match the oldest
+                // unfinished call of the same tool
+                if let Some(call) = calls
+                    .iter_mut()
+                    .find(|call| call.name == *name && call.success.is_none())
+                {
+                    call.success = Some(*success);
+                    call.duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+                }
+            }
+            _ => {}
+        }
+        self.inner.record_event(event);
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        self.inner.record_metric(metric);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// Token usage reported by the provider over the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Usage {
+    pub total_tokens: u64,
+}
+
+/// What `--json` prints: a single object, so scripts can parse stdout.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub response: String,
+    pub tool_calls: Vec<ToolCallRecord>,
+    /// `null` when the provider reported no usage
+    pub usage: Option<Usage>,
+    /// Wall-clock time of the run, in seconds
+    pub duration: f64,
+    pub artifacts: Vec<Artifact>,
+}
+
+impl RunReport {
+    pub fn new(
+        response: String,
+        tool_calls: Vec<ToolCallRecord>,
+        tokens_used: Option<u64>,
+        duration: Duration,
+        artifacts: Vec<Artifact>,
+    ) -> Self {
+        Self {
+            response,
+            tool_calls,
+            usage: tokens_used.map(|total_tokens| Usage { total_tokens }),
+            duration: duration.as_secs_f64(),
+            artifacts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::NoopObserver;
+
+    #[test]
+    fn tool_call_log_pairs_starts_and_ends() {
+        let log = ToolCallLog::new(Arc::new(NoopObserver));
+        for name in ["file_read", "shell"] {
+            log.record_event(&ObserverEvent::ToolStart {
+                name: name.into(),
+                arguments_summary: format!("{{\"tool\":\"{name}\"}}"),
+            });
+        }
+        log.record_event(&ObserverEvent::ToolEnd {
+            name: "shell".into(),
+            success: false,
+            duration: Duration::from_millis(40),
+        });
+
+        let calls = log.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].name, "file_read");
+        assert_eq!(calls[0].success, None);
+        assert_eq!(calls[1].success, Some(false));
+        assert_eq!(calls[1].duration_ms, 40);
+
+        let report = RunReport::new(
+            "done".into(),
+            calls,
+            Some(120),
+            Duration::from_millis(1500),
+            Vec::new(),
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["usage"]["total_tokens"], 120);
+        assert_eq!(json["duration"], 1.5);
+        assert_eq!(json["tool_calls"][1]["name"], "shell");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::providers::ChatMessage;
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Longest accepted session id.
+const MAX_SESSION_ID_LEN: usize = 64;
+
+/// Saved conversations that `zeroclaw agent --session <id>` can resume.
+///
+/// Layout:
+///   workspace/sessions/<id>.json — the session's user/assistant turns
+///
+/// Only the user's messages and the agent's final answers are kept; tool
+/// exchanges and injected memory context are rebuilt on every turn.
+pub struct SessionStore {
+    dir: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    id: String,
+    updated_at: String,
+    messages: Vec<ChatMessage>,
+}
+
+impl SessionStore {
+    pub fn new(workspace_dir: &Path) -> Self {
+        Self {
+            dir: workspace_dir.join("sessions"),
+        }
+    }
+
+    /// Short random id for a session the user didn't name.
+    pub fn new_id() -> String {
+        uuid::Uuid::new_v4().simple().to_string()[..12].to_string()
+    }
+
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        let valid = !id.is_empty()
+            && id.len() <= MAX_SESSION_ID_LEN
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            bail!(
+                "Invalid session id {id:?}: use up to {MAX_SESSION_ID_LEN} letters, digits, '-' or '_'"
+            );
+        }
+        Ok(self.dir.join(format!("{id}.json")))
+    }
+
+    /// Messages saved for `id`; empty if the session doesn't exist yet.
+    pub fn load(&self, id: &str) -> Result<Vec<ChatMessage>> {
+        let path = self.path(id)?;
+        let raw = match std::fs::read_to_string(&path) {
+            Ok(raw) => raw,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let file: SessionFile = serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid session file {}", path.display()))?;
+        Ok(file.messages)
+    }
+
+    /// Replace the saved messages for `id` (via a temp file, so a crash never
+    /// leaves a truncated session behind).
+    pub fn save(&self, id: &str, messages: &[ChatMessage]) -> Result<()> {
+        let path = self.path(id)?;
+        std::fs::create_dir_all(&self.dir)?;
+        let file = SessionFile {
+            id: id.to_string(),
+            updated_at: chrono::Utc::now().to_rfc3339(),
+            messages: messages.to_vec(),
+        };
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&file)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Forget `id`. Returns whether there was anything to remove.
+    pub fn clear(&self, id: &str) -> Result<bool> {
+        match std::fs::remove_file(self.path(id)?) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let tmp = TempDir::new().unwrap();
+        let store = SessionStore::new(tmp.path());
+        let messages = vec![
+            ChatMessage::user("what's in README?"),
+            ChatMessage::assistant("A Rust agent runtime."),
+        ];
+        store.save("work", &messages).unwrap();
+
+        assert_eq!(store.load("work").unwrap(), messages);
+        assert!(tmp.path().join("sessions/work.json").exists());
+        assert!(!tmp.path().join("sessions/work.json.tmp").exists());
+    }
+
+    #[test]
+    fn missing_session_loads_empty() {
+        let tmp = TempDir::new().unwrap();
+        let store = SessionStore::new(tmp.path());
+        assert!(store.load("nope").unwrap().is_empty());
+        assert!(!store.clear("nope").unwrap());
+    }
+
+    #[test]
+    fn clear_removes_session() {
+        let tmp = TempDir::new().unwrap();
+        let store = SessionStore::new(tmp.path());
+        store.save("s1", &[ChatMessage::user("hi")]).unwrap();
+        assert!(store.clear("s1").unwrap());
+        assert!(store.load("s1").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_ids_that_are_not_plain_names() {
+        let tmp = TempDir::new().unwrap();
+        let store = SessionStore::new(tmp.path());
+        for id in ["", "../escape", "a/b", "dot.json", &"x".repeat(65)] {
+            assert!(store.save(id, &[]).is_err(), "{id:?}");
+        }
+        assert!(SessionStore::new_id()
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric()));
+    }
+
+    #[test]
+    fn corrupt_session_file_is_an_error() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("sessions")).unwrap();
+        std::fs::write(tmp.path().join("sessions/bad.json"), "{not json").unwrap();
+        let err = SessionStore::new(tmp.path()).load("bad").unwrap_err();
+        assert!(err.to_string().contains("Invalid session file"), "{err}");
+    }
+}
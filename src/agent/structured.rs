@@ -0,0 +1,254 @@
+use crate::config::{AgentConfig, ResponseFormat};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::Path;
+
+/// Requirements for a JSON final answer (`agent.response_format = "json"`).
+///
+/// The answer must parse as JSON, optionally wrapped in a single
+/// ```` ```json ```` fence, and match `agent.response_schema` when one is
+/// configured. Only a subset of JSON Schema is checked: `type`, `enum`,
+/// `const`, `properties`, `required`, `additionalProperties: false` and
+/// `items`; other keywords are ignored.
+#[derive(Debug, Clone)]
+pub struct JsonResponse {
+    schema: Option<Value>,
+}
+
+/// Returned (as an `anyhow` error) when the answer is still not valid JSON
+/// after the model was asked once to correct it.
+#[derive(Debug)]
+pub struct InvalidJsonResponse {
+    /// Why the last answer was rejected
+    pub reason: String,
+    /// The rejected answer
+    pub response: String,
+}
+
+impl std::fmt::Display for InvalidJsonResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "response is not valid JSON: {}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidJsonResponse {}
+
+impl JsonResponse {
+    pub fn new(schema: Option<Value>) -> Self {
+        Self { schema }
+    }
+
+    /// The JSON requirements configured by `[agent]`, or `None` in text mode.
+    /// Fails if `response_schema` can't be read or isn't JSON.
+    pub fn from_config(config: &AgentConfig, workspace_dir: &Path) -> Result<Option<Self>> {
+        if config.response_format != ResponseFormat::Json {
+            return Ok(None);
+        }
+        let schema = match &config.response_schema {
+            Some(path) => {
+                let path = workspace_dir.join(path);
+                let raw = std::fs::read_to_string(&path).with_context(|| {
+                    format!("Failed to read agent.response_schema {}", path.display())
+                })?;
+                Some(serde_json::from_str(&raw).with_context(|| {
+                    format!("agent.response_schema {} is not JSON", path.display())
+                })?)
+            }
+            None => None,
+        };
+        Ok(Some(Self::new(schema)))
+    }
+
+    /// Appended to the system prompt so the model knows what to produce.
+    pub fn instructions(&self) -> String {
+        let mut text = String::from(
+            "\n\n## Response Format\n\n\
+             Your final answer must be a single JSON document and nothing else: \
+             no prose before or after it, no code fences. \
+             Tool calls work as usual until then.",
+        );
+        if let Some(schema) = &self.schema {
+            text.push_str("\nThe JSON must match this JSON Schema:\n");
+            text.push_str(&schema.to_string());
+        }
+        text
+    }
+
+    /// The answer as JSON text, or why it isn't acceptable.
+    pub fn check(&self, answer: &str) -> std::result::Result<String, String> {
+        let json = strip_fence(answer.trim());
+        let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if let Some(schema) = &self.schema {
+            validate(&value, schema, "$")?;
+        }
+        Ok(json.to_string())
+    }
+
+    /// Sent back to the model after a rejected answer.
+    pub fn correction(&self, reason: &str) -> String {
+        let mut text = format!(
+            "Your answer was rejected: {reason}. Reply again with only the corrected JSON document."
+        );
+        if self.schema.is_some() {
+            text.push_str(" It must match the JSON Schema from the system prompt.");
+        }
+        text
+    }
+}
+
+/// `json` from inside a lone ```` ``` ```` / ```` ```json ```` fence, else `text`.
+fn strip_fence(text: &str) -> &str {
+    let Some(body) = text.strip_prefix("```").and_then(|t| t.strip_suffix("```")) else {
+        return text;
+    };
+    let body = body.strip_prefix("json").unwrap_or(body);
+    if body.contains("```") {
+        return text;
+    }
+    body.trim()
+}
+
+fn type_matches(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Check `value` against the supported subset of `schema`; `path` names the
+/// value in the error.
+fn validate(value: &Value, schema: &Value, path: &str) -> std::result::Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+
+    if let Some(kind) = schema.get("type") {
+        let kinds: Vec<&str> = match kind {
+            Value::String(kind) => vec![kind.as_str()],
+            Value::Array(kinds) => kinds.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !kinds.is_empty() && !kinds.iter().any(|kind| type_matches(value, kind)) {
+            return Err(format!("{path} should be of type {}", kinds.join(" or ")));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{path} should be one of {}",
+                Value::from(allowed.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(format!("{path} should be {expected}"));
+        }
+    }
+
+    if let Value::Object(object) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(key) {
+                    return Err(format!("{path} is missing required property '{key}'"));
+                }
+            }
+        }
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (key, item) in object {
+            match properties.and_then(|p| p.get(key)) {
+                Some(property) => validate(item, property, &format!("{path}.{key}"))?,
+                None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                    return Err(format!("{path} has unexpected property '{key}'"));
+                }
+                None => {}
+            }
+        }
+    }
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (index, item) in items.iter().enumerate() {
+            validate(item, item_schema, &format!("{path}[{index}]"))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn person_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {"type": "string"},
+                "age": {"type": "integer"},
+                "tags": {"type": "array", "items": {"enum": ["a", "b"]}}
+            },
+            "required": ["name"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_plain_and_fenced_json() {
+        let json = JsonResponse::new(None);
+        assert_eq!(json.check(" {\"a\": 1} ").unwrap(), "{\"a\": 1}");
+        assert_eq!(json.check("```json\n[1, 2]\n```").unwrap(), "[1, 2]");
+        assert!(json.check("Here you go: {\"a\": 1}").is_err());
+    }
+
+    #[test]
+    fn checks_against_schema() {
+        let json = JsonResponse::new(Some(person_schema()));
+        assert!(json
+            .check(r#"{"name": "Ada", "age": 36, "tags": ["a"]}"#)
+            .is_ok());
+        assert_eq!(
+            json.check(r#"{"age": 36}"#).unwrap_err(),
+            "$ is missing required property 'name'"
+        );
+        assert_eq!(
+            json.check(r#"{"name": "Ada", "age": "36"}"#).unwrap_err(),
+            "$.age should be of type integer"
+        );
+        assert_eq!(
+            json.check(r#"{"name": "Ada", "tags": ["c"]}"#).unwrap_err(),
+            r#"$.tags[0] should be one of ["a","b"]"#
+        );
+        assert_eq!(
+            json.check(r#"{"name": "Ada", "email": "x"}"#).unwrap_err(),
+            "$ has unexpected property 'email'"
+        );
+    }
+
+    #[test]
+    fn from_config_loads_schema_only_in_json_mode() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("schema.json"), person_schema().to_string()).unwrap();
+        let mut config = AgentConfig {
+            response_schema: Some("schema.json".into()),
+            ..AgentConfig::default()
+        };
+        assert!(JsonResponse::from_config(&config, tmp.path())
+            .unwrap()
+            .is_none());
+
+        config.response_format = ResponseFormat::Json;
+        let json = JsonResponse::from_config(&config, tmp.path())
+            .unwrap()
+            .unwrap();
+        assert!(json.instructions().contains("\"required\":[\"name\"]"));
+
+        config.response_schema = Some("missing.json".into());
+        assert!(JsonResponse::from_config(&config, tmp.path()).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+use super::idempotency::{IdempotencyRecords, TurnKeys};
+use super::loop_::{tool_calling_loop, ConfirmationGate, LoopSettings, MAX_TOOL_ITERATIONS};
+use crate::config::{BrowserConfig, Config, SearchConfig};
+use crate::memory::Memory;
+use crate::observability::Observer;
+use crate::providers::tokenizer;
+use crate::providers::{ChatMessage, Provider};
+use crate::runtime::RuntimeAdapter;
+use crate::security::SecurityPolicy;
+use crate::tools::artifact::ARTIFACT_TOOL;
+use crate::tools::{self, Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// A sub-agent works through a whole tool loop, so it gets far longer than
+/// `[tools] timeout_secs`.
+const SUBAGENT_TIMEOUT: Duration = Duration::from_mins(10);
+
+/// Appended to the sub-agent's system prompt.
+const SUBAGENT_PROMPT: &str = "\n\n## Sub-agent\n\n\
+You are a sub-agent: another agent delegated one task to you. Complete it with the \
+tools available, then reply with the result only. The other agent sees nothing but \
+your final answer, so include everything it needs.";
+
+/// Delegate a focused task to a fresh agent.
+///
+/// The sub-agent starts with an empty conversation and runs its own
+/// `tool_calling_loop` with the same provider, tools, security policy and
+/// memory as the parent; its final answer becomes the tool output. Nobody
+/// can confirm its calls, so flagged ones are denied, and it is held to the
+/// same token limit and dry-run setting. Another `subagent` tool is offered
+/// to it only while `agent.max_subagent_depth` allows.
+pub struct SubAgentTool {
+    shared: Arc<Shared>,
+    /// Nesting level of the agent holding this tool (0 = the top-level agent)
+    depth: u32,
+}
+
+struct Shared {
+    provider: Arc<dyn Provider>,
+    model_name: String,
+    temperature: f64,
+    settings: LoopSettings,
+    max_depth: u32,
+    workspace_dir: PathBuf,
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+    mem: Arc<dyn Memory>,
+    observer: Arc<dyn Observer>,
+    composio_key: Option<String>,
+    browser: BrowserConfig,
+    search: SearchConfig,
+    idempotency: IdempotencyRecords,
+}
+
+impl SubAgentTool {
+    /// The tool for a top-level agent, or `None` when
+    /// `agent.max_subagent_depth` is 0.
+    pub fn from_config(
+        config: &Config,
+        provider: Arc<dyn Provider>,
+        model_name: &str,
+        security: Arc<SecurityPolicy>,
+        runtime: Arc<dyn RuntimeAdapter>,
+        mem: Arc<dyn Memory>,
+        observer: Arc<dyn Observer>,
+    ) -> Option<Self> {
+        if config.agent.max_subagent_depth == 0 {
+            return None;
+        }
+        let composio_key = config
+            .composio
+            .api_key
+            .clone()
+            .filter(|_| config.composio.enabled);
+        let mut settings = LoopSettings::from_config(config, false);
+        settings.tokenizer = tokenizer::for_model(&config.agent, model_name);
+        Some(Self {
+            shared: Arc::new(Shared {
+                provider,
+                model_name: model_name.to_string(),
+                temperature: config.default_temperature,
+                settings,
+                max_depth: config.agent.max_subagent_depth,
+                workspace_dir: config.workspace_dir.clone(),
+                security,
+                runtime,
+                mem,
+                observer,
+                composio_key,
+                browser: config.browser.clone(),
+                search: config.search.clone(),
+                idempotency: IdempotencyRecords::default(),
+            }),
+            depth: 0,
+        })
+    }
+
+    /// The sub-agent's tools: the usual registry, minus `artifact` (only the
+    /// top-level answer carries files), plus `subagent` one level down while
+    /// nesting is allowed.
+    fn sub_tools(&self) -> Vec<Box<dyn Tool>> {
+        let shared = &self.shared;
+        let mut tools = tools::all_tools_with_runtime(
+            &shared.security,
+            Arc::clone(&shared.runtime),
+            Arc::clone(&shared.mem),
+            shared.composio_key.as_deref(),
+            &shared.browser,
+            &shared.search,
+        );
+        tools.retain(|tool| tool.name() != ARTIFACT_TOOL);
+        let depth = self.depth + 1;
+        if depth < shared.max_depth {
+            tools.push(Box::new(Self {
+                shared: Arc::clone(shared),
+                depth,
+            }));
+        }
+        tools
+    }
+}
+
+#[async_trait]
+impl Tool for SubAgentTool {
+    fn name(&self) -> &str {
+        "subagent"
+    }
+
+    fn description(&self) -> &str {
+        "Delegate a self-contained task to a sub-agent with a fresh context and the same \
+         tools; returns its final answer"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "task": {
+                    "type": "string",
+                    "description": "What the sub-agent should do, with all the context it needs"
+                },
+                "model": {
+                    "type": "string",
+                    "description": "Model to use instead of the current one (also accepts hint:<name>)"
+                },
+                "max_iterations": {
+                    "type": "integer",
+                    "description": format!("Tool-calling rounds allowed (1-{MAX_TOOL_ITERATIONS}, default {MAX_TOOL_ITERATIONS})")
+                }
+            },
+            "required": ["task"]
+        })
+    }
+
+    /// Its tool calls are checked for dry-run one by one, so the sub-agent
+    /// itself may run.
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        Some(SUBAGENT_TIMEOUT)
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let task = args
+            .get("task")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|task| !task.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'task' parameter"))?;
+        let shared = &self.shared;
+        let model_name = args
+            .get("model")
+            .and_then(|v| v.as_str())
+            .filter(|model| !model.trim().is_empty())
+            .unwrap_or(&shared.model_name);
+        let mut settings = shared.settings;
+        if let Some(max) = args
+            .get("max_iterations")
+            .and_then(serde_json::Value::as_u64)
+        {
+            settings.max_iterations = usize::try_from(max)
+                .map_or(MAX_TOOL_ITERATIONS, |max| max.clamp(1, MAX_TOOL_ITERATIONS));
+        }
+
+        let tools = self.sub_tools();
+        let specs: Vec<_> = tools.iter().map(|tool| tool.spec()).collect();
+        let mut system_prompt = crate::channels::build_system_prompt(
+            &shared.workspace_dir,
+            model_name,
+            &specs,
+            &[],
+            None,
+        );
+        system_prompt.push_str(SUBAGENT_PROMPT);
+
+        tracing::debug!(
+            depth = self.depth + 1,
+            model = model_name,
+            "Starting sub-agent"
+        );
+        let result = Box::pin(tool_calling_loop(
+            shared.provider.as_ref(),
+            &system_prompt,
+            &[ChatMessage::user(task)],
+            model_name,
+            shared.temperature,
+            &tools,
+            settings,
+            &CancellationToken::new(),
+            shared.observer.as_ref(),
+            None,
+            &mut None,
+            &mut Vec::new(),
+            ConfirmationGate::new(&shared.security, None),
+            Some(&TurnKeys::start(&shared.idempotency)),
+        ))
+        .await;
+
+        Ok(match result {
+            Ok(answer) => ToolResult {
+                success: true,
+                output: answer,
+                error: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Sub-agent failed: {e:#}")),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MarkdownMemory;
+    use crate::observability::NoopObserver;
+    use crate::runtime::NativeRuntime;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+
+    /// Replays scripted responses and records the conversations it was sent.
+    struct ScriptedProvider {
+        responses: Mutex<Vec<&'static str>>,
+        seen: Mutex<Vec<Vec<ChatMessage>>>,
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.seen.lock().unwrap().push(messages.to_vec());
+            Ok(self.responses.lock().unwrap().remove(0).to_string())
+        }
+    }
+
+    fn subagent(
+        tmp: &TempDir,
+        provider: Arc<dyn Provider>,
+        max_depth: u32,
+    ) -> Option<SubAgentTool> {
+        let mut config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..Config::default()
+        };
+        config.agent.max_subagent_depth = max_depth;
+        SubAgentTool::from_config(
+            &config,
+            provider,
+            "model",
+            Arc::new(SecurityPolicy {
+                workspace_dir: tmp.path().to_path_buf(),
+                ..SecurityPolicy::default()
+            }),
+            Arc::new(NativeRuntime::new()),
+            Arc::new(MarkdownMemory::new(tmp.path())),
+            Arc::new(NoopObserver),
+        )
+    }
+
+    #[tokio::test]
+    async fn subagent_runs_a_task_and_returns_its_answer() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("version.txt"), "1.4.2").unwrap();
+        let provider = Arc::new(ScriptedProvider {
+            responses: Mutex::new(vec![
+                r#"<tool_call>{"name": "file_read", "arguments": {"path": "version.txt"}}</tool_call>"#,
+                "The version is 1.4.2.",
+            ]),
+            seen: Mutex::new(Vec::new()),
+        });
+        let tool = subagent(&tmp, provider.clone(), 1).unwrap();
+
+        let result = tool
+            .execute(json!({"task": "Find the version in version.txt"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "The version is 1.4.2.");
+
+        let seen = provider.seen.lock().unwrap();
+        // A fresh conversation: system prompt and the task only
+        assert_eq!(seen[0].len(), 2);
+        assert!(seen[0][0].content.contains(SUBAGENT_PROMPT.trim()));
+        assert_eq!(seen[0][1].content, "Find the version in version.txt");
+        assert!(seen[1].last().unwrap().content.contains("1.4.2"));
+    }
+
+    #[test]
+    fn nesting_stops_at_the_configured_depth() {
+        let tmp = TempDir::new().unwrap();
+        let provider: Arc<dyn Provider> = Arc::new(ScriptedProvider {
+            responses: Mutex::new(Vec::new()),
+            seen: Mutex::new(Vec::new()),
+        });
+        assert!(subagent(&tmp, Arc::clone(&provider), 0).is_none());
+
+        let names = |tools: &[Box<dyn Tool>]| -> Vec<String> {
+            tools.iter().map(|t| t.name().to_string()).collect()
+        };
+        let top = subagent(&tmp, Arc::clone(&provider), 1).unwrap();
+        assert!(!names(&top.sub_tools()).contains(&"subagent".to_string()));
+
+        let top = subagent(&tmp, provider, 2).unwrap();
+        let sub_tools = top.sub_tools();
+        assert!(names(&sub_tools).contains(&"subagent".to_string()));
+        assert!(!names(&sub_tools).contains(&ARTIFACT_TOOL.to_string()));
+    }
+}
@@ -0,0 +1,155 @@
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+
+/// Fill the `{{variable}}` placeholders in `template` from `vars`; a later
+/// pair wins over an earlier one with the same key.
+///
+/// `{{name:default}}` falls back to `default` when `name` isn't set.
+/// Whitespace inside the braces is ignored, and braces that don't enclose a
+/// valid name are left as they are. Fails listing every variable that has
+/// neither a value nor a default.
+pub fn render(template: &str, vars: &[(String, String)]) -> Result<String> {
+    let mut out = String::with_capacity(template.len());
+    let mut missing: Vec<&str> = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let inner = &rest[start + 2..start + 2 + len];
+        let (name, default) = match inner.split_once(':') {
+            Some((name, default)) => (name.trim(), Some(default.trim())),
+            None => (inner.trim(), None),
+        };
+        out.push_str(&rest[..start]);
+        if is_variable_name(name) {
+            let value = vars.iter().rev().find(|(key, _)| key == name);
+            match (value, default) {
+                (Some((_, value)), _) => out.push_str(value),
+                (None, Some(default)) => out.push_str(default),
+                (None, None) => {
+                    if !missing.contains(&name) {
+                        missing.push(name);
+                    }
+                }
+            }
+        } else {
+            out.push_str(&rest[start..start + 4 + len]);
+        }
+        rest = &rest[start + 4 + len..];
+    }
+    out.push_str(rest);
+
+    if !missing.is_empty() {
+        bail!(
+            "Missing template variables: {} (set them with --var name=value)",
+            missing.join(", ")
+        );
+    }
+    Ok(out)
+}
+
+/// Expand the template called `name` in `[prompts]`.
+pub fn expand(config: &Config, name: &str, vars: &[(String, String)]) -> Result<String> {
+    let Some(template) = config.prompts.get(name) else {
+        let mut known: Vec<&str> = config.prompts.keys().map(String::as_str).collect();
+        known.sort_unstable();
+        if known.is_empty() {
+            bail!("Unknown prompt template '{name}': no [prompts] are configured");
+        }
+        bail!(
+            "Unknown prompt template '{name}' (available: {})",
+            known.join(", ")
+        );
+    };
+    render(template, vars).with_context(|| format!("Prompt template '{name}'"))
+}
+
+/// Parse a `--var key=value` argument.
+pub fn parse_var(arg: &str) -> std::result::Result<(String, String), String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected key=value, got '{arg}'"))?;
+    let key = key.trim();
+    if !is_variable_name(key) {
+        return Err(format!("invalid variable name '{key}'"));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        let out = render(
+            "Review {{file}} for {{ focus }}; then {{file}} again. Keep {literal} and {{}}.",
+            &vars(&[("file", "src/main.rs"), ("focus", "bugs")]),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "Review src/main.rs for bugs; then src/main.rs again. Keep {literal} and {{}}."
+        );
+    }
+
+    #[test]
+    fn missing_variables_are_listed() {
+        let err = render("{{a}} {{b}} {{a}} {{c:x}}", &vars(&[])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Missing template variables: a, b (set them with --var name=value)"
+        );
+
+        let mut config = Config::default();
+        config
+            .prompts
+            .insert("greet".to_string(), "Hi {{who}}".to_string());
+        let err = expand(&config, "greet", &[]).unwrap_err();
+        assert!(format!("{err:#}")
+            .starts_with("Prompt template 'greet': Missing template variables: who"));
+        assert!(expand(&config, "nope", &[])
+            .unwrap_err()
+            .to_string()
+            .contains("available: greet"));
+    }
+
+    #[test]
+    fn defaults_apply_only_when_unset() {
+        let template = "Hello, {{name:World}}! Mood: {{mood: calm }}";
+        assert_eq!(
+            render(template, &vars(&[])).unwrap(),
+            "Hello, World! Mood: calm"
+        );
+        assert_eq!(
+            render(template, &vars(&[("name", "Ada")])).unwrap(),
+            "Hello, Ada! Mood: calm"
+        );
+    }
+
+    #[test]
+    fn parses_var_arguments() {
+        assert_eq!(
+            parse_var("url=https://x.dev/?a=b").unwrap(),
+            ("url".to_string(), "https://x.dev/?a=b".to_string())
+        );
+        assert!(parse_var("novalue").is_err());
+        assert!(parse_var("bad key=1").is_err());
+    }
+}
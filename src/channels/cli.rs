@@ -3,7 +3,112 @@ use async_trait::async_trait;
 use tokio::io::{self, AsyncBufReadExt, BufReader};
 use uuid::Uuid;
 
+/// Line that opens and closes a block of lines taken verbatim.
+const HEREDOC_FENCE: &str = "\"\"\"";
+
+/// Printed for `/help` and unknown commands.
+pub const HELP: &str = "Commands:
+  /help             show this list
+  /reset            forget the conversation so far
+  /model [name]     switch model for the next turns, or show the current one
+  /save             save the conversation as a session to resume later
+  /memory [query]   show memory entries matching query, or the newest ones
+  /persona [name]   switch persona, or list them
+  /quit, /exit      leave
+End a line with \\ to continue on the next one, or put text between two \"\"\" lines.";
+
+/// Interactive commands, handled without a model call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlashCommand {
+    /// `/quit` or `/exit`
+    Quit,
+    /// `/help`
+    Help,
+    /// `/reset`: forget the conversation so far
+    Reset,
+    /// `/model [name]`: switch model, or show the current one when empty
+    Model(String),
+    /// `/save`: keep the conversation as a session
+    Save,
+    /// `/memory [query]`: show matching memory entries, or the newest ones
+    Memory(String),
+    /// `/persona [name]`: switch persona, or list them when empty
+    Persona(String),
+    /// Any other `/word`
+    Unknown(String),
+}
+
+impl SlashCommand {
+    /// The command `input` is, or `None` for a message to the agent. Only a
+    /// single line starting with `/` and a plain word counts, so a message
+    /// like `/etc/hosts is empty` still goes to the agent.
+    pub fn parse(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if input.contains('\n') {
+            return None;
+        }
+        let rest = input.strip_prefix('/')?;
+        let (word, arg) = rest
+            .split_once(char::is_whitespace)
+            .map_or((rest, ""), |(word, arg)| (word, arg.trim()));
+        if word.is_empty() || !word.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some(match word {
+            "quit" | "exit" => Self::Quit,
+            "help" => Self::Help,
+            "reset" => Self::Reset,
+            "model" => Self::Model(arg.to_string()),
+            "save" => Self::Save,
+            "memory" => Self::Memory(arg.to_string()),
+            "persona" => Self::Persona(arg.to_string()),
+            _ => Self::Unknown(word.to_string()),
+        })
+    }
+}
+
+/// Joins input lines into messages: a line ending in `\` continues on the
+/// next one, and lines between two `"""` lines are kept verbatim.
+#[derive(Debug, Default)]
+struct MultiLineInput {
+    lines: Vec<String>,
+    in_heredoc: bool,
+}
+
+impl MultiLineInput {
+    /// Add one line; returns the message once it is complete.
+    fn push(&mut self, line: &str) -> Option<String> {
+        let line = line.trim_end_matches('\r');
+        if self.in_heredoc {
+            if line.trim() == HEREDOC_FENCE {
+                self.in_heredoc = false;
+                return Some(self.take());
+            }
+            self.lines.push(line.to_string());
+            return None;
+        }
+        if self.lines.is_empty() && line.trim() == HEREDOC_FENCE {
+            self.in_heredoc = true;
+            return None;
+        }
+        if let Some(head) = line.strip_suffix('\\') {
+            self.lines.push(head.to_string());
+            return None;
+        }
+        self.lines.push(line.to_string());
+        Some(self.take())
+    }
+
+    fn take(&mut self) -> String {
+        std::mem::take(&mut self.lines).join("\n")
+    }
+}
+
 /// CLI channel — stdin/stdout, always available, zero deps
+///
+/// Reads multi-line messages (see `MultiLineInput`) and answers `/help`,
+/// unknown commands and `/quit` itself; other slash commands are passed on
+/// for the agent loop to handle without a model call.
 pub struct CliChannel;
 
 impl CliChannel {
@@ -23,24 +128,53 @@ impl Channel for CliChannel {
         Ok(())
     }
 
+    fn streams_replies(&self) -> bool {
+        true
+    }
+
+    /// Printed inline as it arrives.
+    async fn send_chunk(&self, chunk: &str, _recipient: &str) -> anyhow::Result<()> {
+        print!("{chunk}");
+        std::io::Write::flush(&mut std::io::stdout())?;
+        Ok(())
+    }
+
+    async fn end_message(&self, _recipient: &str) -> anyhow::Result<()> {
+        println!();
+        Ok(())
+    }
+
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         let stdin = io::stdin();
         let reader = BufReader::new(stdin);
         let mut lines = reader.lines();
+        let mut input = MultiLineInput::default();
 
         while let Ok(Some(line)) = lines.next_line().await {
-            let line = line.trim().to_string();
-            if line.is_empty() {
+            let Some(message) = input.push(&line) else {
+                continue;
+            };
+            let message = message.trim();
+            if message.is_empty() {
                 continue;
             }
-            if line == "/quit" || line == "/exit" {
-                break;
+            match SlashCommand::parse(message) {
+                Some(SlashCommand::Quit) => break,
+                Some(SlashCommand::Help) => {
+                    println!("{HELP}\n");
+                    continue;
+                }
+                Some(SlashCommand::Unknown(word)) => {
+                    println!("Unknown command /{word}\n{HELP}\n");
+                    continue;
+                }
+                _ => {}
             }
 
             let msg = ChannelMessage {
                 id: Uuid::new_v4().to_string(),
                 sender: "user".to_string(),
-                content: line,
+                content: message.to_string(),
                 channel: "cli".to_string(),
                 timestamp: std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
@@ -60,6 +194,61 @@ impl Channel for CliChannel {
 mod tests {
     use super::*;
 
+    #[test]
+    fn slash_commands_are_parsed() {
+        assert_eq!(SlashCommand::parse(" /quit "), Some(SlashCommand::Quit));
+        assert_eq!(SlashCommand::parse("/exit"), Some(SlashCommand::Quit));
+        assert_eq!(SlashCommand::parse("/reset"), Some(SlashCommand::Reset));
+        assert_eq!(
+            SlashCommand::parse("/model  gpt-4o "),
+            Some(SlashCommand::Model("gpt-4o".into()))
+        );
+        assert_eq!(
+            SlashCommand::parse("/model"),
+            Some(SlashCommand::Model(String::new()))
+        );
+        assert_eq!(
+            SlashCommand::parse("/memory deploy keys"),
+            Some(SlashCommand::Memory("deploy keys".into()))
+        );
+        assert_eq!(
+            SlashCommand::parse("/persona pirate"),
+            Some(SlashCommand::Persona("pirate".into()))
+        );
+        assert_eq!(
+            SlashCommand::parse("  /persona  "),
+            Some(SlashCommand::Persona(String::new()))
+        );
+        assert_eq!(
+            SlashCommand::parse("/personality"),
+            Some(SlashCommand::Unknown("personality".into()))
+        );
+        assert_eq!(SlashCommand::parse("tell me about /persona"), None);
+        assert_eq!(SlashCommand::parse("/etc/hosts is empty"), None);
+        assert_eq!(SlashCommand::parse("/reset\nand more"), None);
+        assert_eq!(SlashCommand::parse("/"), None);
+    }
+
+    #[test]
+    fn multi_line_messages_are_assembled() {
+        let mut input = MultiLineInput::default();
+        assert_eq!(input.push("hello"), Some("hello".into()));
+
+        assert_eq!(input.push("fix this:\\"), None);
+        assert_eq!(input.push("  let x = 1;\\"), None);
+        assert_eq!(
+            input.push("please"),
+            Some("fix this:\n  let x = 1;\nplease".into())
+        );
+
+        assert_eq!(input.push("\"\"\""), None);
+        assert_eq!(input.push("fn main() {\\"), None);
+        assert_eq!(input.push(""), None);
+        assert_eq!(input.push("}"), None);
+        assert_eq!(input.push(" \"\"\" "), Some("fn main() {\\\n\n}".into()));
+        assert_eq!(input.push("next\r"), Some("next".into()));
+    }
+
     #[test]
     fn cli_channel_name() {
         assert_eq!(CliChannel::new().name(), "cli");
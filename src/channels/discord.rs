@@ -5,11 +5,19 @@ use serde_json::json;
 use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
-/// Discord channel — connects via Gateway WebSocket for real-time messages
+/// Discord's per-message content limit, in characters.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+/// Discord channel — connects via Gateway WebSocket for real-time messages.
+///
+/// Answers direct messages, messages that mention the bot, and everything in
+/// the configured `channel_ids`. Replies longer than Discord's limit are sent
+/// as several messages.
 pub struct DiscordChannel {
     bot_token: String,
     guild_id: Option<String>,
     allowed_users: Vec<String>,
+    channel_ids: Vec<String>,
     client: reqwest::Client,
 }
 
@@ -19,10 +27,71 @@ impl DiscordChannel {
             bot_token,
             guild_id,
             allowed_users,
+            channel_ids: Vec::new(),
             client: reqwest::Client::new(),
         }
     }
 
+    /// Answer every message in these channels, not just mentions.
+    #[must_use]
+    pub fn with_channel_ids(mut self, channel_ids: Vec<String>) -> Self {
+        self.channel_ids = channel_ids;
+        self
+    }
+
+    /// Content to hand to the agent for a `MESSAGE_CREATE` payload, with the
+    /// bot's mention stripped, or `None` if the message should be ignored.
+    fn message_to_answer(&self, d: &serde_json::Value, bot_user_id: &str) -> Option<String> {
+        let author = d.get("author")?;
+        let author_id = author.get("id").and_then(|i| i.as_str()).unwrap_or("");
+        // Skip the bot's own messages and other bots
+        if author_id == bot_user_id
+            || author
+                .get("bot")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false)
+        {
+            return None;
+        }
+
+        if !self.is_user_allowed(author_id) {
+            tracing::warn!("Discord: ignoring message from unauthorized user: {author_id}");
+            return None;
+        }
+
+        let guild = d.get("guild_id").and_then(serde_json::Value::as_str);
+        if let Some(ref gid) = self.guild_id {
+            if guild != Some(gid.as_str()) {
+                return None;
+            }
+        }
+
+        let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("");
+        let mentioned = !bot_user_id.is_empty()
+            && d.get("mentions")
+                .and_then(serde_json::Value::as_array)
+                .is_some_and(|mentions| {
+                    mentions
+                        .iter()
+                        .any(|m| m.get("id").and_then(|i| i.as_str()) == Some(bot_user_id))
+                });
+        let is_dm = guild.is_none();
+        if !(is_dm || mentioned || self.channel_ids.iter().any(|c| c == channel_id)) {
+            return None;
+        }
+
+        let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
+        let content = if bot_user_id.is_empty() {
+            content.to_string()
+        } else {
+            content
+                .replace(&format!("<@{bot_user_id}>"), "")
+                .replace(&format!("<@!{bot_user_id}>"), "")
+        };
+        let content = content.trim();
+        (!content.is_empty()).then(|| content.to_string())
+    }
+
     /// Check if a Discord user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -77,6 +146,55 @@ fn base64_decode(input: &str) -> Option<String> {
     String::from_utf8(bytes).ok()
 }
 
+/// Split `text` into messages of at most `limit` characters, preferring
+/// paragraph, then line, then word boundaries. A code block cut in two is
+/// closed at the end of one chunk and reopened at the start of the next.
+fn chunk_message(text: &str, limit: usize) -> Vec<String> {
+    const FENCE: &str = "```";
+    // Room for a closing and a reopening fence
+    let budget = limit.saturating_sub(2 * (FENCE.len() + 1)).max(1);
+
+    let mut pieces = Vec::new();
+    let mut rest = text.trim();
+    while rest.chars().count() > budget {
+        let window_end = rest
+            .char_indices()
+            .nth(budget)
+            .map_or(rest.len(), |(i, _)| i);
+        let window = &rest[..window_end];
+        let split = ["\n\n", "\n", " "]
+            .iter()
+            .filter_map(|sep| window.rfind(sep))
+            .find(|&i| i >= window.len() / 2)
+            .unwrap_or(window_end);
+        pieces.push(rest[..split].trim_end());
+        rest = rest[split..].trim_start_matches(['\n', ' ']);
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+
+    let mut chunks = Vec::with_capacity(pieces.len());
+    let mut in_code_block = false;
+    for piece in pieces {
+        let mut chunk = String::new();
+        if in_code_block {
+            chunk.push_str(FENCE);
+            chunk.push('\n');
+        }
+        chunk.push_str(piece);
+        if piece.matches(FENCE).count() % 2 == 1 {
+            in_code_block = !in_code_block;
+        }
+        if in_code_block {
+            chunk.push('\n');
+            chunk.push_str(FENCE);
+        }
+        chunks.push(chunk);
+    }
+    chunks
+}
+
 #[async_trait]
 impl Channel for DiscordChannel {
     fn name(&self) -> &str {
@@ -85,23 +203,25 @@ impl Channel for DiscordChannel {
 
     async fn send(&self, message: &str, channel_id: &str) -> anyhow::Result<()> {
         let url = format!("https://discord.com/api/v10/channels/{channel_id}/messages");
-        let body = json!({ "content": message });
-
-        let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bot {}", self.bot_token))
-            .json(&body)
-            .send()
-            .await?;
 
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let err = resp
-                .text()
-                .await
-                .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
-            anyhow::bail!("Discord send message failed ({status}): {err}");
+        for chunk in chunk_message(message, MAX_MESSAGE_CHARS) {
+            let body = json!({ "content": chunk });
+            let resp = self
+                .client
+                .post(&url)
+                .header("Authorization", format!("Bot {}", self.bot_token))
+                .json(&body)
+                .send()
+                .await?;
+
+            if !resp.status().is_success() {
+                let status = resp.status();
+                let err = resp
+                    .text()
+                    .await
+                    .unwrap_or_else(|e| format!("<failed to read response body: {e}>"));
+                anyhow::bail!("Discord send message failed ({status}): {err}");
+            }
         }
 
         Ok(())
@@ -176,8 +296,6 @@ impl Channel for DiscordChannel {
             }
         });
 
-        let guild_filter = self.guild_id.clone();
-
         loop {
             tokio::select! {
                 _ = hb_rx.recv() => {
@@ -239,42 +357,15 @@ impl Channel for DiscordChannel {
                         continue;
                     };
 
-                    // Skip messages from the bot itself
-                    let author_id = d.get("author").and_then(|a| a.get("id")).and_then(|i| i.as_str()).unwrap_or("");
-                    if author_id == bot_user_id {
+                    let Some(content) = self.message_to_answer(d, &bot_user_id) else {
                         continue;
-                    }
-
-                    // Skip bot messages
-                    if d.get("author").and_then(|a| a.get("bot")).and_then(serde_json::Value::as_bool).unwrap_or(false) {
-                        continue;
-                    }
-
-                    // Sender validation
-                    if !self.is_user_allowed(author_id) {
-                        tracing::warn!("Discord: ignoring message from unauthorized user: {author_id}");
-                        continue;
-                    }
-
-                    // Guild filter
-                    if let Some(ref gid) = guild_filter {
-                        let msg_guild = d.get("guild_id").and_then(serde_json::Value::as_str).unwrap_or("");
-                        if msg_guild != gid {
-                            continue;
-                        }
-                    }
-
-                    let content = d.get("content").and_then(|c| c.as_str()).unwrap_or("");
-                    if content.is_empty() {
-                        continue;
-                    }
-
+                    };
                     let channel_id = d.get("channel_id").and_then(|c| c.as_str()).unwrap_or("").to_string();
 
                     let channel_msg = ChannelMessage {
                         id: Uuid::new_v4().to_string(),
                         sender: channel_id,
-                        content: content.to_string(),
+                        content,
                         channel: "discord".to_string(),
                         timestamp: std::time::SystemTime::now()
                             .duration_since(std::time::UNIX_EPOCH)
@@ -397,4 +488,124 @@ mod tests {
         let id = DiscordChannel::bot_user_id_from_token("");
         assert_eq!(id, Some(String::new()));
     }
+
+    fn message(
+        author: &str,
+        content: &str,
+        guild: Option<&str>,
+        channel: &str,
+    ) -> serde_json::Value {
+        let mut d = json!({
+            "author": {"id": author},
+            "content": content,
+            "channel_id": channel,
+            "mentions": [],
+        });
+        if let Some(guild) = guild {
+            d["guild_id"] = json!(guild);
+        }
+        d
+    }
+
+    #[test]
+    fn answers_mentions_and_strips_them() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["*".into()]);
+        let mut d = message("42", "<@999> what's up?", Some("g1"), "c1");
+        d["mentions"] = json!([{"id": "999"}]);
+        assert_eq!(
+            ch.message_to_answer(&d, "999").as_deref(),
+            Some("what's up?")
+        );
+
+        let mut d = message("42", "<@!999> nickname mention", Some("g1"), "c1");
+        d["mentions"] = json!([{"id": "999"}]);
+        assert_eq!(
+            ch.message_to_answer(&d, "999").as_deref(),
+            Some("nickname mention")
+        );
+    }
+
+    #[test]
+    fn answers_dms_and_configured_channels() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["*".into()])
+            .with_channel_ids(vec!["c-bot".into()]);
+        let dm = message("42", "hello", None, "dm1");
+        assert_eq!(ch.message_to_answer(&dm, "999").as_deref(), Some("hello"));
+
+        let configured = message("42", "hi all", Some("g1"), "c-bot");
+        assert_eq!(
+            ch.message_to_answer(&configured, "999").as_deref(),
+            Some("hi all")
+        );
+    }
+
+    #[test]
+    fn ignores_unrelated_guild_messages() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["*".into()])
+            .with_channel_ids(vec!["c-bot".into()]);
+        let d = message("42", "just chatting", Some("g1"), "c-general");
+        assert!(ch.message_to_answer(&d, "999").is_none());
+    }
+
+    #[test]
+    fn ignores_self_bots_and_unauthorized_users() {
+        let ch = DiscordChannel::new("fake".into(), None, vec!["42".into()]);
+        assert!(ch
+            .message_to_answer(&message("999", "echo", None, "dm1"), "999")
+            .is_none());
+
+        let mut from_bot = message("7", "beep", None, "dm1");
+        from_bot["author"]["bot"] = json!(true);
+        assert!(ch.message_to_answer(&from_bot, "999").is_none());
+
+        assert!(ch
+            .message_to_answer(&message("13", "hi", None, "dm1"), "999")
+            .is_none());
+        assert!(ch
+            .message_to_answer(&message("42", "   ", None, "dm1"), "999")
+            .is_none());
+    }
+
+    #[test]
+    fn short_message_is_single_chunk() {
+        assert_eq!(chunk_message("hello", MAX_MESSAGE_CHARS), vec!["hello"]);
+    }
+
+    #[test]
+    fn long_message_splits_on_paragraphs() {
+        let para = "word ".repeat(300);
+        let text = format!("{}\n\n{}", para.trim(), para.trim());
+        let chunks = chunk_message(&text, MAX_MESSAGE_CHARS);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], para.trim());
+        assert_eq!(chunks[1], para.trim());
+        assert!(chunks
+            .iter()
+            .all(|c| c.chars().count() <= MAX_MESSAGE_CHARS));
+    }
+
+    #[test]
+    fn hard_split_respects_char_boundaries() {
+        let text = "é".repeat(4500);
+        let chunks = chunk_message(&text, MAX_MESSAGE_CHARS);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks
+            .iter()
+            .all(|c| c.chars().count() <= MAX_MESSAGE_CHARS));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn code_blocks_stay_balanced_across_chunks() {
+        let code: String = (0..400).map(|i| format!("let x{i} = {i};\n")).collect();
+        let text = format!("Here you go:\n```rust\n{code}```\nDone.");
+        let chunks = chunk_message(&text, MAX_MESSAGE_CHARS);
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.chars().count() <= MAX_MESSAGE_CHARS);
+            assert_eq!(chunk.matches("```").count() % 2, 0, "{chunk}");
+        }
+        assert!(chunks[1].starts_with("```\n"));
+        assert!(chunks.last().unwrap().ends_with("Done."));
+    }
 }
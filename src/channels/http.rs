@@ -0,0 +1,671 @@
+use super::traits::{Channel, ChannelMessage};
+use crate::agent::{AgentRunner, Reply};
+use crate::config::HttpChannelConfig;
+use crate::gateway::MAX_BODY_SIZE;
+use crate::providers::ChatMessage;
+use crate::security::pairing::{constant_time_eq, is_public_bind};
+use async_trait::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::timeout::TimeoutLayer;
+
+/// Generous per-request timeout — one request is a whole agent turn,
+/// tool calls included.
+const REQUEST_TIMEOUT_SECS: u64 = 300;
+/// Messages (user + assistant) remembered per session.
+const MAX_SESSION_MESSAGES: usize = 20;
+/// Sessions kept in memory; the least recently used one is dropped beyond this.
+const MAX_SESSIONS: usize = 256;
+const MAX_SESSION_ID_LEN: usize = 128;
+const DEFAULT_SESSION_ID: &str = "default";
+
+/// HTTP API channel — `POST /chat` with `{"message", "session_id"}` runs one
+/// agent turn and returns the final response as JSON, with any files from
+/// the `artifact` tool under `artifacts`.
+///
+/// `GET /chat/stream?message=...&session_id=...` runs the same turn as
+/// server-sent events: `chunk` events carry `{"text"}` as the reply streams
+/// in, then one `done` event carries the `POST /chat` body (or `error`).
+///
+/// Each `session_id` is its own conversation: earlier turns are replayed to
+/// the agent and memory entries are keyed by the session. Unlike the
+/// messaging channels, replies go back in the HTTP response rather than
+/// through `send`. `GET /healthz` and `GET /readyz` are served for
+/// orchestrators, without the auth token; see `health::Probes`.
+pub struct HttpChannel {
+    listen: String,
+    state: HttpState,
+}
+
+#[derive(Clone)]
+struct HttpState {
+    runner: Arc<AgentRunner>,
+    auth_token: Option<Arc<str>>,
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+}
+
+struct Session {
+    messages: Vec<ChatMessage>,
+    last_used: Instant,
+}
+
+/// `POST /chat` request body, or `GET /chat/stream` query
+#[derive(serde::Deserialize)]
+pub struct ChatRequest {
+    pub message: String,
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+impl HttpChannel {
+    pub fn new(config: &HttpChannelConfig, runner: Arc<AgentRunner>) -> Self {
+        Self {
+            listen: config.listen.clone(),
+            state: HttpState {
+                runner,
+                auth_token: config
+                    .auth_token
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                    .map(Arc::from),
+                sessions: Arc::new(Mutex::new(HashMap::new())),
+            },
+        }
+    }
+
+    fn router(&self) -> Router {
+        Router::new()
+            .route("/chat", post(handle_chat))
+            .route("/chat/stream", get(handle_chat_stream))
+            .with_state(self.state.clone())
+            .merge(self.state.runner.probes().router())
+            .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
+            .layer(TimeoutLayer::with_status_code(
+                StatusCode::REQUEST_TIMEOUT,
+                Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            ))
+    }
+
+    /// Serve on an already-bound listener until the server stops.
+    pub async fn serve(&self, listener: tokio::net::TcpListener) -> anyhow::Result<()> {
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+
+    /// Parse the listen address, refusing a public bind without a token.
+    fn listen_addr(&self) -> anyhow::Result<SocketAddr> {
+        let addr: SocketAddr = self.listen.parse().map_err(|e| {
+            anyhow::anyhow!("Invalid channels_config.http.listen '{}': {e}", self.listen)
+        })?;
+        if is_public_bind(&addr.ip().to_string()) && self.state.auth_token.is_none() {
+            anyhow::bail!(
+                "Refusing to serve the HTTP channel on {addr} without \
+                 channels_config.http.auth_token"
+            );
+        }
+        Ok(addr)
+    }
+}
+
+impl HttpState {
+    fn is_authorized(&self, headers: &HeaderMap) -> bool {
+        let Some(ref expected) = self.auth_token else {
+            return true;
+        };
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|auth| auth.strip_prefix("Bearer "))
+            .unwrap_or("");
+        constant_time_eq(token, expected)
+    }
+
+    fn history(&self, session_id: &str) -> Vec<ChatMessage> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(session_id)
+            .map(|session| session.messages.clone())
+            .unwrap_or_default()
+    }
+
+    fn unauthorized() -> ErrorResponse {
+        error_response(
+            StatusCode::UNAUTHORIZED,
+            "Unauthorized — send Authorization: Bearer <token>",
+        )
+    }
+
+    /// The `POST /chat` body for a finished turn
+    fn reply_body(&self, session_id: &str, reply: &Reply) -> serde_json::Value {
+        serde_json::json!({
+            "response": reply.text,
+            "artifacts": reply.artifacts,
+            "session_id": session_id,
+            "model": self.runner.model(),
+        })
+    }
+
+    fn log_agent_error(session_id: &str, e: &anyhow::Error) {
+        tracing::error!(
+            session_id,
+            "HTTP channel agent error: {}",
+            crate::providers::sanitize_api_error(&e.to_string())
+        );
+    }
+
+    /// Append a completed turn, keeping the session and session count bounded.
+    fn record_turn(&self, session_id: &str, message: &str, response: &str) {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if !sessions.contains_key(session_id) && sessions.len() >= MAX_SESSIONS {
+            if let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, session)| session.last_used)
+                .map(|(id, _)| id.clone())
+            {
+                sessions.remove(&oldest);
+            }
+        }
+        let session = sessions
+            .entry(session_id.to_string())
+            .or_insert_with(|| Session {
+                messages: Vec::new(),
+                last_used: Instant::now(),
+            });
+        session.messages.push(ChatMessage::user(message));
+        session.messages.push(ChatMessage::assistant(response));
+        let excess = session.messages.len().saturating_sub(MAX_SESSION_MESSAGES);
+        session.messages.drain(..excess);
+        session.last_used = Instant::now();
+    }
+}
+
+type ErrorResponse = (StatusCode, Json<serde_json::Value>);
+
+fn error_response(status: StatusCode, error: &str) -> ErrorResponse {
+    (status, Json(serde_json::json!({ "error": error })))
+}
+
+impl ChatRequest {
+    /// The trimmed message and session id, or why the request is rejected.
+    fn validate(&self) -> Result<(&str, &str), ErrorResponse> {
+        let message = self.message.trim();
+        if message.is_empty() {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                "message must not be empty",
+            ));
+        }
+        let session_id = self
+            .session_id
+            .as_deref()
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .unwrap_or(DEFAULT_SESSION_ID);
+        if session_id.len() > MAX_SESSION_ID_LEN {
+            return Err(error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("session_id is longer than {MAX_SESSION_ID_LEN} bytes"),
+            ));
+        }
+        Ok((message, session_id))
+    }
+}
+
+/// POST /chat — run one agent turn in the request's session
+async fn handle_chat(
+    State(state): State<HttpState>,
+    headers: HeaderMap,
+    body: Result<Json<ChatRequest>, axum::extract::rejection::JsonRejection>,
+) -> impl IntoResponse {
+    if !state.is_authorized(&headers) {
+        return HttpState::unauthorized();
+    }
+
+    let Json(request) = match body {
+        Ok(b) => b,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("Invalid JSON: {e}. Expected: {{\"message\": \"...\", \"session_id\": \"...\"}}"),
+            );
+        }
+    };
+    let (message, session_id) = match request.validate() {
+        Ok(valid) => valid,
+        Err(err) => return err,
+    };
+
+    let history = state.history(session_id);
+    match state
+        .runner
+        .respond(&format!("http_{session_id}"), &history, message)
+        .await
+    {
+        Ok(reply) => {
+            state.record_turn(session_id, message, &reply.text);
+            (StatusCode::OK, Json(state.reply_body(session_id, &reply)))
+        }
+        Err(e) => {
+            HttpState::log_agent_error(session_id, &e);
+            error_response(StatusCode::INTERNAL_SERVER_ERROR, "Agent request failed")
+        }
+    }
+}
+
+/// GET /chat/stream — run one agent turn, streaming the reply as SSE
+async fn handle_chat_stream(
+    State(state): State<HttpState>,
+    headers: HeaderMap,
+    query: Result<Query<ChatRequest>, axum::extract::rejection::QueryRejection>,
+) -> Response {
+    if !state.is_authorized(&headers) {
+        return HttpState::unauthorized().into_response();
+    }
+    let Query(request) = match query {
+        Ok(q) => q,
+        Err(e) => {
+            return error_response(
+                StatusCode::BAD_REQUEST,
+                &format!("Invalid query: {e}. Expected: ?message=...&session_id=..."),
+            )
+            .into_response();
+        }
+    };
+    let (message, session_id) = match request.validate() {
+        Ok((message, session_id)) => (message.to_string(), session_id.to_string()),
+        Err(err) => return err.into_response(),
+    };
+
+    let (chunk_tx, chunk_rx) = tokio::sync::mpsc::unbounded_channel();
+    // Runs to completion even if the client disconnects, like `POST /chat`
+    let turn = tokio::spawn(async move {
+        let history = state.history(&session_id);
+        let result = state
+            .runner
+            .respond_stream(&format!("http_{session_id}"), &history, &message, chunk_tx)
+            .await;
+        match result {
+            Ok(reply) => {
+                state.record_turn(&session_id, &message, &reply.text);
+                ("done", state.reply_body(&session_id, &reply))
+            }
+            Err(e) => {
+                HttpState::log_agent_error(&session_id, &e);
+                (
+                    "error",
+                    serde_json::json!({"error": "Agent request failed"}),
+                )
+            }
+        }
+    });
+
+    let chunks = futures_util::stream::unfold(chunk_rx, |mut rx| async move {
+        let text = rx.recv().await?;
+        Some((sse_event("chunk", &serde_json::json!({ "text": text })), rx))
+    });
+    let end = futures_util::stream::once(async move {
+        let (name, body) = turn.await.unwrap_or_else(|e| {
+            tracing::error!("HTTP channel stream task failed: {e}");
+            (
+                "error",
+                serde_json::json!({"error": "Agent request failed"}),
+            )
+        });
+        sse_event(name, &body)
+    });
+    Sse::new(chunks.chain(end).map(Ok::<_, Infallible>))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_event(name: &str, data: &serde_json::Value) -> Event {
+    Event::default()
+        .event(name)
+        .json_data(data)
+        .unwrap_or_default()
+}
+
+#[async_trait]
+impl Channel for HttpChannel {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    /// Replies are returned in the HTTP response; there is nothing to push.
+    async fn send(&self, _message: &str, recipient: &str) -> anyhow::Result<()> {
+        tracing::debug!(recipient, "HTTP channel ignores out-of-band sends");
+        Ok(())
+    }
+
+    /// Serve the API. Requests are answered directly, so `tx` is unused.
+    async fn listen(&self, _tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        let addr = self.listen_addr()?;
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        tracing::info!(
+            "HTTP channel listening on http://{}",
+            listener.local_addr()?
+        );
+        self.serve(listener).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::memory::MarkdownMemory;
+    use crate::providers::traits::StreamEvent;
+    use crate::providers::Provider;
+    use tempfile::TempDir;
+
+    /// Replies with the last user message it was sent.
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(format!(
+                "echo: {} ({} messages)",
+                messages.last().map_or("", |m| m.content.as_str()),
+                messages.len()
+            ))
+        }
+
+        /// The echo, one word at a time
+        async fn chat_stream(
+            &self,
+            messages: &[ChatMessage],
+            model: &str,
+            temperature: f64,
+        ) -> anyhow::Result<crate::providers::traits::ChatStream> {
+            let text = self.chat(messages, model, temperature).await?;
+            let words: Vec<anyhow::Result<StreamEvent>> = text
+                .split_inclusive(' ')
+                .map(|word| Ok(StreamEvent::Text(word.to_string())))
+                .collect();
+            Ok(futures_util::stream::iter(words).boxed())
+        }
+    }
+
+    fn channel(tmp: &TempDir, auth_token: Option<&str>) -> HttpChannel {
+        let config = Config {
+            workspace_dir: tmp.path().to_path_buf(),
+            config_path: tmp.path().join("config.toml"),
+            ..Config::default()
+        };
+        let runner = AgentRunner::with_provider(
+            &config,
+            "echo",
+            Box::new(EchoProvider),
+            Arc::new(MarkdownMemory::new(tmp.path())),
+        )
+        .unwrap();
+        HttpChannel::new(
+            &HttpChannelConfig {
+                listen: "127.0.0.1:0".into(),
+                auth_token: auth_token.map(String::from),
+            },
+            Arc::new(runner),
+        )
+    }
+
+    /// Serve `channel` on an ephemeral port and return its base URL.
+    async fn start(channel: HttpChannel) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { channel.serve(listener).await });
+        format!("http://{addr}")
+    }
+
+    async fn chat(base: &str, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        let resp = reqwest::Client::new()
+            .post(format!("{base}/chat"))
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        let status = StatusCode::from_u16(resp.status().as_u16()).unwrap();
+        (status, resp.json().await.unwrap())
+    }
+
+    #[test]
+    fn http_channel_name() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(channel(&tmp, None).name(), "http");
+    }
+
+    #[tokio::test]
+    async fn chat_round_trips_through_the_agent_loop() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, None)).await;
+
+        let (status, body) = chat(
+            &base,
+            serde_json::json!({"message": "hello there", "session_id": "s1"}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["session_id"], "s1");
+        let response = body["response"].as_str().unwrap();
+        assert!(response.starts_with("echo: "), "{response}");
+        assert!(response.ends_with("hello there (2 messages)"), "{response}");
+    }
+
+    /// `(event, data)` pairs of a server-sent event stream.
+    fn sse_events(body: &str) -> Vec<(String, serde_json::Value)> {
+        body.split("\n\n")
+            .filter_map(|block| {
+                let mut name = None;
+                let mut data = None;
+                for line in block.lines() {
+                    if let Some(value) = line.strip_prefix("event: ") {
+                        name = Some(value.to_string());
+                    } else if let Some(value) = line.strip_prefix("data: ") {
+                        data = serde_json::from_str(value).ok();
+                    }
+                }
+                Some((name?, data?))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn chat_stream_sends_chunks_then_the_reply() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, None)).await;
+
+        let resp = reqwest::Client::new()
+            .get(format!("{base}/chat/stream"))
+            .query(&[("message", "hello there"), ("session_id", "s1")])
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+        assert_eq!(
+            resp.headers()[reqwest::header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let events = sse_events(&resp.text().await.unwrap());
+
+        let (last, body) = events.last().unwrap();
+        assert_eq!(last, "done");
+        assert_eq!(body["session_id"], "s1");
+        let response = body["response"].as_str().unwrap();
+        assert!(response.ends_with("hello there (2 messages)"), "{response}");
+        let chunks: Vec<&str> = events
+            .iter()
+            .filter(|(name, _)| name == "chunk")
+            .map(|(_, data)| data["text"].as_str().unwrap())
+            .collect();
+        assert!(chunks.len() > 1, "{chunks:?}");
+        assert_eq!(chunks.concat().trim_end(), response);
+
+        // The streamed turn is part of the session
+        let (_, next) = chat(
+            &base,
+            serde_json::json!({"message": "again", "session_id": "s1"}),
+        )
+        .await;
+        let next = next["response"].as_str().unwrap();
+        assert!(next.ends_with("(4 messages)"), "{next}");
+    }
+
+    #[tokio::test]
+    async fn chat_stream_checks_token_and_query() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, Some("s3cret"))).await;
+        let client = reqwest::Client::new();
+
+        let resp = client
+            .get(format!("{base}/chat/stream?message=hi"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 401);
+        let resp = client
+            .get(format!("{base}/chat/stream"))
+            .bearer_auth("s3cret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 400);
+    }
+
+    #[tokio::test]
+    async fn sessions_keep_separate_conversations() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, None)).await;
+
+        chat(
+            &base,
+            serde_json::json!({"message": "first", "session_id": "a"}),
+        )
+        .await;
+        let (_, same) = chat(
+            &base,
+            serde_json::json!({"message": "second", "session_id": "a"}),
+        )
+        .await;
+        let (_, other) = chat(
+            &base,
+            serde_json::json!({"message": "third", "session_id": "b"}),
+        )
+        .await;
+
+        // System prompt, the earlier exchange, then the new message
+        let same = same["response"].as_str().unwrap();
+        assert!(same.ends_with("(4 messages)"), "{same}");
+        let other = other["response"].as_str().unwrap();
+        assert!(other.ends_with("(2 messages)"), "{other}");
+    }
+
+    #[tokio::test]
+    async fn sessions_never_recall_each_others_messages() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, None)).await;
+
+        chat(
+            &base,
+            serde_json::json!({"message": "my locker code is 4711", "session_id": "a"}),
+        )
+        .await;
+        let (_, other) = chat(
+            &base,
+            serde_json::json!({"message": "what is my locker code", "session_id": "b"}),
+        )
+        .await;
+
+        let other = other["response"].as_str().unwrap();
+        assert!(!other.contains("4711"), "{other}");
+
+        let (_, same) = chat(
+            &base,
+            serde_json::json!({"message": "what is my locker code", "session_id": "a"}),
+        )
+        .await;
+        let same = same["response"].as_str().unwrap();
+        assert!(same.contains("4711"), "{same}");
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_requests_and_missing_token() {
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, None)).await;
+        let (status, _) = chat(&base, serde_json::json!({"message": "  "})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = chat(&base, serde_json::json!({"text": "hi"})).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let tmp = TempDir::new().unwrap();
+        let base = start(channel(&tmp, Some("s3cret"))).await;
+        let (status, _) = chat(&base, serde_json::json!({"message": "hi"})).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let resp = reqwest::Client::new()
+            .post(format!("{base}/chat"))
+            .bearer_auth("s3cret")
+            .json(&serde_json::json!({"message": "hi"}))
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    #[test]
+    fn public_bind_requires_auth_token() {
+        let tmp = TempDir::new().unwrap();
+        let mut ch = channel(&tmp, None);
+        ch.listen = "0.0.0.0:8090".into();
+        assert!(ch.listen_addr().is_err());
+        ch.listen = "127.0.0.1:8090".into();
+        assert!(ch.listen_addr().is_ok());
+    }
+
+    #[test]
+    fn session_history_is_bounded() {
+        let tmp = TempDir::new().unwrap();
+        let ch = channel(&tmp, None);
+        for i in 0..MAX_SESSION_MESSAGES {
+            ch.state
+                .record_turn("s", &format!("q{i}"), &format!("a{i}"));
+        }
+        let history = ch.state.history("s");
+        assert_eq!(history.len(), MAX_SESSION_MESSAGES);
+        assert_eq!(
+            history.last().unwrap().content,
+            format!("a{}", MAX_SESSION_MESSAGES - 1)
+        );
+
+        for i in 0..=MAX_SESSIONS {
+            ch.state.record_turn(&format!("session-{i}"), "q", "a");
+        }
+        assert_eq!(ch.state.sessions.lock().unwrap().len(), MAX_SESSIONS);
+        assert!(
+            ch.state.history("s").is_empty(),
+            "least recently used session is dropped"
+        );
+    }
+}
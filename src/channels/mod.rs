@@ -1,6 +1,7 @@
 pub mod cli;
 pub mod discord;
 pub mod email_channel;
+pub mod http;
 pub mod imessage;
 pub mod irc;
 pub mod matrix;
@@ -12,19 +13,23 @@ pub mod whatsapp;
 pub use cli::CliChannel;
 pub use discord::DiscordChannel;
 pub use email_channel::EmailChannel;
+pub use http::HttpChannel;
 pub use imessage::IMessageChannel;
 pub use irc::IrcChannel;
 pub use matrix::MatrixChannel;
 pub use slack::SlackChannel;
 pub use telegram::TelegramChannel;
-pub use traits::Channel;
+pub use traits::{Channel, ReplyStream};
 pub use whatsapp::WhatsAppChannel;
 
+use crate::agent::loop_::ToolCallFilter;
 use crate::config::Config;
 use crate::memory::{self, Memory};
 use crate::providers::{self, Provider};
+use crate::security::output_filter::{self, OutputBlocked, OutputFilter, StreamFilter};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
+use futures_util::StreamExt;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -34,6 +39,9 @@ const BOOTSTRAP_MAX_CHARS: usize = 20_000;
 const DEFAULT_CHANNEL_INITIAL_BACKOFF_SECS: u64 = 2;
 const DEFAULT_CHANNEL_MAX_BACKOFF_SECS: u64 = 60;
 
+/// Chat command that clears the sender's conversation memory.
+const RESET_COMMAND: &str = "/reset";
+
 fn spawn_supervised_listener(
     ch: Arc<dyn Channel>,
     tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
@@ -118,6 +126,12 @@ fn load_openclaw_bootstrap_files(prompt: &mut String, workspace_dir: &std::path:
 ///
 /// Daily memory files (`memory/*.md`) are NOT injected — they are accessed
 /// on-demand via `memory_recall` / `memory_search` tools.
+///
+/// The identity config can adjust the result, in this order of precedence:
+/// 1. `system_prompt_override` replaces everything above except the tool
+///    list, which is still appended so tool calling keeps working.
+/// 2. `system_prompt_prepend` / `system_prompt_append` are then placed
+///    before / after that body, whether it was generated or overridden.
 pub fn build_system_prompt(
     workspace_dir: &std::path::Path,
     model_name: &str,
@@ -125,10 +139,49 @@ pub fn build_system_prompt(
     skills: &[crate::skills::Skill],
     identity_config: Option<&crate::config::IdentityConfig>,
 ) -> String {
+    let configured = |pick: fn(&crate::config::IdentityConfig) -> Option<&String>| {
+        identity_config
+            .and_then(pick)
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+    };
+    let override_text = configured(|c| c.system_prompt_override.as_ref());
+    let prepend = configured(|c| c.system_prompt_prepend.as_ref());
+    let append = configured(|c| c.system_prompt_append.as_ref());
+
+    let mut prompt = String::new();
+    if let Some(prepend) = prepend {
+        prompt.push_str(prepend);
+        prompt.push_str("\n\n");
+    }
+    match override_text {
+        Some(text) => {
+            prompt.push_str(text);
+            prompt.push_str("\n\n");
+            push_tools_section(&mut prompt, tools);
+        }
+        None => prompt.push_str(&generated_system_prompt(
+            workspace_dir,
+            model_name,
+            tools,
+            skills,
+            identity_config,
+        )),
+    }
+    if let Some(append) = append {
+        if !prompt.ends_with("\n\n") {
+            prompt.push_str(if prompt.ends_with('\n') { "\n" } else { "\n\n" });
+        }
+        prompt.push_str(append);
+        prompt.push('\n');
+    }
+    prompt
+}
+
+/// Tool-calling instructions and the list of available tools.
+fn push_tools_section(prompt: &mut String, tools: &[crate::tools::ToolSpec]) {
     use std::fmt::Write;
-    let mut prompt = String::with_capacity(8192);
 
-    // ── 1. Tooling ──────────────────────────────────────────────
     if !tools.is_empty() {
         prompt.push_str("## Tools\n\n");
         prompt.push_str("You have access to the following tools. To use a tool, emit a `<tool_call>` XML block with a JSON body:\n\n");
@@ -148,6 +201,22 @@ pub fn build_system_prompt(
             );
         }
     }
+}
+
+/// The prompt assembled from tools, skills and workspace files, before any
+/// configured override or prepend/append text is applied.
+fn generated_system_prompt(
+    workspace_dir: &std::path::Path,
+    model_name: &str,
+    tools: &[crate::tools::ToolSpec],
+    skills: &[crate::skills::Skill],
+    identity_config: Option<&crate::config::IdentityConfig>,
+) -> String {
+    use std::fmt::Write;
+    let mut prompt = String::with_capacity(8192);
+
+    // ── 1. Tooling ──────────────────────────────────────────────
+    push_tools_section(&mut prompt, tools);
 
     // ── 2. Safety ───────────────────────────────────────────────
     prompt.push_str("## Safety\n\n");
@@ -310,6 +379,7 @@ pub fn handle_command(command: crate::ChannelCommands, config: &Config) -> Resul
                 ("WhatsApp", config.channels_config.whatsapp.is_some()),
                 ("Email", config.channels_config.email.is_some()),
                 ("IRC", config.channels_config.irc.is_some()),
+                ("HTTP API", config.channels_config.http.is_some()),
             ] {
                 println!("  {} {name}", if configured { "✅" } else { "❌" });
             }
@@ -366,22 +436,28 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     if let Some(ref dc) = config.channels_config.discord {
         channels.push((
             "Discord",
-            Arc::new(DiscordChannel::new(
-                dc.bot_token.clone(),
-                dc.guild_id.clone(),
-                dc.allowed_users.clone(),
-            )),
+            Arc::new(
+                DiscordChannel::new(
+                    dc.bot_token.clone(),
+                    dc.guild_id.clone(),
+                    dc.allowed_users.clone(),
+                )
+                .with_channel_ids(dc.channel_ids.clone()),
+            ),
         ));
     }
 
     if let Some(ref sl) = config.channels_config.slack {
         channels.push((
             "Slack",
-            Arc::new(SlackChannel::new(
-                sl.bot_token.clone(),
-                sl.channel_id.clone(),
-                sl.allowed_users.clone(),
-            )),
+            Arc::new(
+                SlackChannel::new(
+                    sl.bot_token.clone(),
+                    sl.channel_id.clone(),
+                    sl.allowed_users.clone(),
+                )
+                .with_app_token(sl.app_token.clone()),
+            ),
         ));
     }
 
@@ -479,25 +555,97 @@ pub async fn doctor_channels(config: Config) -> Result<()> {
     Ok(())
 }
 
+/// Stream the model's answer to `message` out through `channel` (see
+/// `ReplyStream`) with `<tool_call>` blocks hidden and `output_filter`
+/// applied, and return the text that was sent. A reply the filter blocks
+/// is ended where it stands and the turn fails with `OutputBlocked`.
+#[allow(clippy::too_many_arguments)]
+async fn stream_reply(
+    provider: &dyn Provider,
+    channel: &dyn Channel,
+    recipient: &str,
+    system_prompt: &str,
+    message: &str,
+    model: &str,
+    temperature: f64,
+    output_filter: Option<&OutputFilter>,
+) -> Result<String> {
+    let mut deltas = provider
+        .chat_stream_with_system(Some(system_prompt), message, model, temperature)
+        .await?;
+    let mut reply = ReplyStream::new(channel, recipient);
+    let mut tool_calls = ToolCallFilter::default();
+    let mut screen = output_filter.map(OutputFilter::stream);
+    let streamed: Result<()> = async {
+        while let Some(event) = deltas.next().await {
+            // Channels have no token budget, so usage is dropped
+            let Some(delta) = event?.into_text() else {
+                continue;
+            };
+            let visible = tool_calls.push(&delta);
+            reply
+                .push(&screen_chunk(screen.as_mut(), &visible)?)
+                .await?;
+        }
+        let mut rest = screen_chunk(screen.as_mut(), &tool_calls.finish())?;
+        if let Some(screen) = &mut screen {
+            rest.push_str(&screen.finish().map_err(log_blocked)?);
+        }
+        reply.push(&rest).await
+    }
+    .await;
+    if let Err(e) = streamed {
+        // Close what a streaming channel already showed
+        if channel.streams_replies() {
+            let _ = channel.end_message(recipient).await;
+        }
+        return Err(e);
+    }
+    reply.finish().await
+}
+
+/// `chunk` as `screen` lets it through; unchanged without a filter.
+fn screen_chunk(screen: Option<&mut StreamFilter<'_>>, chunk: &str) -> Result<String> {
+    match screen {
+        Some(screen) => screen.push(chunk).map_err(log_blocked),
+        None => Ok(chunk.to_string()),
+    }
+}
+
+/// Log a reply withheld by `security.output_filters`.
+fn log_blocked(blocked: OutputBlocked) -> anyhow::Error {
+    tracing::warn!("{blocked}");
+    blocked.into()
+}
+
 /// Start all configured channels and route messages to the agent
 #[allow(clippy::too_many_lines)]
 pub async fn start_channels(config: Config) -> Result<()> {
-    let provider: Arc<dyn Provider> = Arc::from(providers::create_resilient_provider(
-        config.default_provider.as_deref().unwrap_or("openrouter"),
+    let provider_name = config.default_provider.as_deref().unwrap_or("openrouter");
+    let observer: Arc<dyn crate::observability::Observer> =
+        Arc::from(crate::observability::create_observer(&config.observability));
+    let model = config
+        .default_model
+        .clone()
+        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
+    // Routed like `AgentRunner::from_config`, since the HTTP channel's runner
+    // shares this provider (and with it the rate limits)
+    let provider: Arc<dyn Provider> = Arc::from(providers::create_routed_provider(
+        provider_name,
         config.api_key.as_deref(),
         &config.reliability,
+        &config.model_routes,
+        &model,
+        &providers::ProviderOptions::from_config(&config),
+        Arc::clone(&observer),
     )?);
 
     // Warm up the provider connection pool (TLS handshake, DNS, HTTP/2 setup)
     // so the first real message doesn't hit a cold-start timeout.
-    if let Err(e) = provider.warmup().await {
-        tracing::warn!("Provider warmup failed (non-fatal): {e}");
+    if config.agent.warmup {
+        providers::warm_up(provider.as_ref(), provider_name, observer.as_ref()).await;
     }
 
-    let model = config
-        .default_model
-        .clone()
-        .unwrap_or_else(|| "anthropic/claude-sonnet-4-20250514".into());
     let temperature = config.default_temperature;
     let mem: Arc<dyn Memory> = Arc::from(memory::create_memory(
         &config.memory,
@@ -507,43 +655,16 @@ pub async fn start_channels(config: Config) -> Result<()> {
 
     // Build system prompt from workspace identity files + skills
     let workspace = config.workspace_dir.clone();
-    let skills = crate::skills::load_skills(&workspace);
-
-    // Collect tool specs for the prompt (channels don't execute tools yet, just describe them)
-    let tool_specs: Vec<crate::tools::ToolSpec> = vec![
-        crate::tools::ToolSpec {
-            name: "shell".into(),
-            description: "Execute terminal commands.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"command": {"type": "string", "description": "The shell command to execute"}}, "required": ["command"]}),
-        },
-        crate::tools::ToolSpec {
-            name: "file_read".into(),
-            description: "Read file contents.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"path": {"type": "string", "description": "Path to file"}}, "required": ["path"]}),
-        },
-        crate::tools::ToolSpec {
-            name: "file_write".into(),
-            description: "Write file contents.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"path": {"type": "string"}, "content": {"type": "string"}}, "required": ["path", "content"]}),
-        },
-        crate::tools::ToolSpec {
-            name: "memory_store".into(),
-            description: "Save to memory.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"key": {"type": "string"}, "content": {"type": "string"}}, "required": ["key", "content"]}),
-        },
-        crate::tools::ToolSpec {
-            name: "memory_recall".into(),
-            description: "Search memory.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"query": {"type": "string"}}, "required": ["query"]}),
-        },
-        crate::tools::ToolSpec {
-            name: "memory_forget".into(),
-            description: "Delete a memory entry.".into(),
-            parameters: serde_json::json!({"type": "object", "properties": {"key": {"type": "string"}}, "required": ["key"]}),
-        },
-    ];
-
-    let system_prompt = build_system_prompt(&workspace, &model, &tool_specs, &skills, Some(&config.identity));
+    let skills = crate::skills::load_configured_skills(&workspace, &config.skills);
+
+    // Channels reply without running tools, so none are offered in the prompt
+    let system_prompt = build_system_prompt(
+        &workspace,
+        &model,
+        &[],
+        &skills,
+        Some(config.active_identity_config()),
+    );
 
     if !skills.is_empty() {
         println!(
@@ -567,19 +688,25 @@ pub async fn start_channels(config: Config) -> Result<()> {
     }
 
     if let Some(ref dc) = config.channels_config.discord {
-        channels.push(Arc::new(DiscordChannel::new(
-            dc.bot_token.clone(),
-            dc.guild_id.clone(),
-            dc.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            DiscordChannel::new(
+                dc.bot_token.clone(),
+                dc.guild_id.clone(),
+                dc.allowed_users.clone(),
+            )
+            .with_channel_ids(dc.channel_ids.clone()),
+        ));
     }
 
     if let Some(ref sl) = config.channels_config.slack {
-        channels.push(Arc::new(SlackChannel::new(
-            sl.bot_token.clone(),
-            sl.channel_id.clone(),
-            sl.allowed_users.clone(),
-        )));
+        channels.push(Arc::new(
+            SlackChannel::new(
+                sl.bot_token.clone(),
+                sl.channel_id.clone(),
+                sl.allowed_users.clone(),
+            )
+            .with_app_token(sl.app_token.clone()),
+        ));
     }
 
     if let Some(ref im) = config.channels_config.imessage {
@@ -623,6 +750,17 @@ pub async fn start_channels(config: Config) -> Result<()> {
         )));
     }
 
+    if let Some(ref http) = config.channels_config.http {
+        let runner = Arc::new(crate::agent::AgentRunner::with_shared_provider(
+            &config,
+            provider_name,
+            Arc::clone(&provider),
+            Arc::clone(&mem),
+            Arc::clone(&observer),
+        )?);
+        channels.push(Arc::new(HttpChannel::new(http, runner)));
+    }
+
     if channels.is_empty() {
         println!("No channels configured. Run `zeroclaw onboard` to set up channels.");
         return Ok(());
@@ -671,8 +809,17 @@ pub async fn start_channels(config: Config) -> Result<()> {
             max_backoff_secs,
         ));
     }
+    // Scheduled self-messages arrive on the bus like any other message
+    let schedule_handle = crate::cron::messages::spawn_dispatcher(
+        config.workspace_dir.clone(),
+        channels.iter().map(|ch| ch.name().to_string()).collect(),
+        &tx,
+        crate::cron::messages::DISPATCH_INTERVAL,
+    );
     drop(tx); // Drop our copy so rx closes when all channels stop
 
+    let output_filter = output_filter::active();
+
     // Process incoming messages — call the LLM and reply
     while let Some(msg) = rx.recv().await {
         println!(
@@ -682,50 +829,66 @@ pub async fn start_channels(config: Config) -> Result<()> {
             truncate_with_ellipsis(&msg.content, 80)
         );
 
+        let conversation_key = format!("{}_{}", msg.channel, msg.sender);
+
+        // `/reset` drops this chat's conversation context instead of asking the LLM
+        if msg.content.trim() == RESET_COMMAND {
+            let reply = match mem.forget(&conversation_key).await {
+                Ok(_) => "🔄 Conversation reset.".to_string(),
+                Err(e) => format!("⚠️ Error: failed to reset conversation: {e}"),
+            };
+            if let Some(ch) = channels.iter().find(|ch| ch.name() == msg.channel) {
+                if let Err(e) = ch.send(&reply, &msg.sender).await {
+                    eprintln!("  ❌ Failed to reply on {}: {e}", ch.name());
+                }
+            }
+            continue;
+        }
+
         // Auto-save to memory
         if config.memory.auto_save {
             let _ = mem
                 .store(
-                    &format!("{}_{}", msg.channel, msg.sender),
+                    &conversation_key,
                     &msg.content,
                     crate::memory::MemoryCategory::Conversation,
                 )
                 .await;
         }
 
+        // Find the channel that sent this message to reply on
+        let Some(ch) = channels.iter().find(|ch| ch.name() == msg.channel) else {
+            continue;
+        };
+
         // Call the LLM with system prompt (identity + soul + tools)
-        match provider
-            .chat_with_system(Some(&system_prompt), &msg.content, &model, temperature)
-            .await
+        match stream_reply(
+            provider.as_ref(),
+            ch.as_ref(),
+            &msg.sender,
+            &system_prompt,
+            &msg.content,
+            &model,
+            temperature,
+            output_filter.as_deref(),
+        )
+        .await
         {
             Ok(response) => {
                 println!(
                     "  🤖 Reply: {}",
                     truncate_with_ellipsis(&response, 80)
                 );
-                // Find the channel that sent this message and reply
-                for ch in &channels {
-                    if ch.name() == msg.channel {
-                        if let Err(e) = ch.send(&response, &msg.sender).await {
-                            eprintln!("  ❌ Failed to reply on {}: {e}", ch.name());
-                        }
-                        break;
-                    }
-                }
             }
             Err(e) => {
                 eprintln!("  ❌ LLM error: {e}");
-                for ch in &channels {
-                    if ch.name() == msg.channel {
-                        let _ = ch.send(&format!("⚠️ Error: {e}"), &msg.sender).await;
-                        break;
-                    }
-                }
+                let _ = ch.send(&format!("⚠️ Error: {e}"), &msg.sender).await;
             }
         }
     }
 
     // Wait for all channel tasks
+    schedule_handle.abort();
     for h in handles {
         let _ = h.await;
     }
@@ -984,6 +1147,60 @@ mod tests {
         assert!(prompt.contains(&format!("Working directory: `{}`", ws.path().display())));
     }
 
+    #[test]
+    fn prompt_prepend_and_append_wrap_generated_prompt() {
+        let ws = make_workspace();
+        let config = crate::config::IdentityConfig {
+            system_prompt_prepend: Some("Always answer in French.".into()),
+            system_prompt_append: Some("Sign every answer with 🦀.".into()),
+            ..crate::config::IdentityConfig::default()
+        };
+        let prompt =
+            build_system_prompt(ws.path(), "model", &make_tool_specs(), &[], Some(&config));
+
+        assert!(
+            prompt.starts_with("Always answer in French.\n\n## Tools"),
+            "{prompt}"
+        );
+        assert!(prompt.ends_with("\n\nSign every answer with 🦀.\n"));
+        let soul = prompt.find("Be helpful.").unwrap();
+        assert!(soul > prompt.find("French").unwrap());
+        assert!(soul < prompt.find("Sign every answer").unwrap());
+    }
+
+    #[test]
+    fn prompt_override_discards_generated_body_but_keeps_tools() {
+        let ws = make_workspace();
+        let config = crate::config::IdentityConfig {
+            system_prompt_override: Some("You are a terse build bot.".into()),
+            system_prompt_prepend: Some("PREPEND".into()),
+            system_prompt_append: Some("APPEND".into()),
+            ..crate::config::IdentityConfig::default()
+        };
+        let prompt =
+            build_system_prompt(ws.path(), "model", &make_tool_specs(), &[], Some(&config));
+
+        assert!(prompt.starts_with("PREPEND\n\nYou are a terse build bot.\n\n## Tools"));
+        assert!(prompt.contains("**shell**"));
+        assert!(prompt.ends_with("APPEND\n"));
+        assert!(!prompt.contains("## Safety"));
+        assert!(!prompt.contains("## Project Context"));
+        assert!(!prompt.contains("Be helpful."));
+    }
+
+    #[test]
+    fn prompt_blank_hooks_are_ignored() {
+        let ws = make_workspace();
+        let config = crate::config::IdentityConfig {
+            system_prompt_override: Some("   ".into()),
+            system_prompt_prepend: Some(String::new()),
+            ..crate::config::IdentityConfig::default()
+        };
+        let with_blanks = build_system_prompt(ws.path(), "model", &[], &[], Some(&config));
+        assert!(with_blanks.starts_with("## Safety"));
+        assert!(with_blanks.contains("Be helpful."));
+    }
+
     // ── AIEOS Identity Tests (Issue #168) ─────────────────────────
 
     #[test]
@@ -1017,6 +1234,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("aieos_identity.json".into()),
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
 
         let prompt = build_system_prompt(tmp.path(), "model", &[], &[], Some(&config));
@@ -1051,6 +1269,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: Some(r#"{"identity":{"names":{"first":"Claw"}}}"#.into()),
+            ..IdentityConfig::default()
         };
 
         let prompt = build_system_prompt(
@@ -1073,6 +1292,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("nonexistent.json".into()),
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
 
         let ws = make_workspace();
@@ -1091,6 +1311,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
 
         let ws = make_workspace();
@@ -1109,6 +1330,7 @@ mod tests {
             format: "openclaw".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
 
         let ws = make_workspace();
@@ -1154,6 +1376,126 @@ mod tests {
         assert_eq!(state, ChannelHealthState::Timeout);
     }
 
+    /// Streams replies, recording each chunk and the end of each reply.
+    #[derive(Default)]
+    struct ChunkRecordingChannel {
+        chunks: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Channel for ChunkRecordingChannel {
+        fn name(&self) -> &str {
+            "chunks"
+        }
+
+        async fn send(&self, _message: &str, _recipient: &str) -> anyhow::Result<()> {
+            anyhow::bail!("a streaming channel is not sent whole replies")
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<traits::ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn streams_replies(&self) -> bool {
+            true
+        }
+
+        async fn send_chunk(&self, chunk: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.chunks.lock().unwrap().push(chunk.to_string());
+            Ok(())
+        }
+
+        async fn end_message(&self, _recipient: &str) -> anyhow::Result<()> {
+            self.chunks.lock().unwrap().push("<end>".into());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_reply_pushes_deltas_to_streaming_channels() {
+        let provider = providers::mock::MockProvider::new(vec!["Hello there, Alice".into()]);
+        let channel = ChunkRecordingChannel::default();
+        let reply = stream_reply(&provider, &channel, "alice", "system", "hi", "m", 0.0, None)
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello there, Alice");
+        assert_eq!(
+            *channel.chunks.lock().unwrap(),
+            vec!["Hello ", "there, ", "Alice", "<end>"]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_reply_hides_tool_call_blocks() {
+        let provider = providers::mock::MockProvider::new(vec![
+            "Let me check. <tool_call>{\"name\": \"shell\", \"arguments\": {}}</tool_call> Done."
+                .into(),
+        ]);
+        let channel = ChunkRecordingChannel::default();
+        let reply = stream_reply(&provider, &channel, "alice", "system", "hi", "m", 0.0, None)
+            .await
+            .unwrap();
+        assert_eq!(reply, "Let me check.  Done.");
+        let chunks = channel.chunks.lock().unwrap();
+        assert!(chunks.iter().all(|chunk| !chunk.contains("tool_call")));
+        assert_eq!(chunks.last().map(String::as_str), Some("<end>"));
+    }
+
+    #[tokio::test]
+    async fn stream_reply_applies_output_filters() {
+        let filter = OutputFilter::new(
+            &[r"db-internal\.corp".into()],
+            crate::config::OutputFilterAction::Redact,
+        )
+        .unwrap();
+        let provider =
+            providers::mock::MockProvider::new(vec!["Connect to db-internal.corp now".into()]);
+        let channel = ChunkRecordingChannel::default();
+        let reply = stream_reply(
+            &provider,
+            &channel,
+            "alice",
+            "system",
+            "hi",
+            "m",
+            0.0,
+            Some(&filter),
+        )
+        .await
+        .unwrap();
+        assert_eq!(reply, "Connect to [FILTERED] now");
+        assert_eq!(
+            channel.chunks.lock().unwrap().concat(),
+            "Connect to [FILTERED] now<end>"
+        );
+
+        let filter = OutputFilter::new(
+            &[r"db-internal\.corp".into()],
+            crate::config::OutputFilterAction::Block,
+        )
+        .unwrap();
+        let provider =
+            providers::mock::MockProvider::new(vec!["Connect to db-internal.corp now".into()]);
+        let channel = ChunkRecordingChannel::default();
+        let err = stream_reply(
+            &provider,
+            &channel,
+            "alice",
+            "system",
+            "hi",
+            "m",
+            0.0,
+            Some(&filter),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.downcast_ref::<OutputBlocked>().is_some());
+        assert_eq!(*channel.chunks.lock().unwrap(), vec!["<end>"]);
+    }
+
     struct AlwaysFailChannel {
         name: &'static str,
         calls: Arc<AtomicUsize>,
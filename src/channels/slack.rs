@@ -1,25 +1,111 @@
 use super::traits::{Channel, ChannelMessage};
 use async_trait::async_trait;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
-/// Slack channel — polls conversations.history via Web API
+/// Slack channel — Socket Mode when an app token is configured, otherwise
+/// polls conversations.history via Web API.
+///
+/// Socket Mode replies are posted in the thread of the message that
+/// triggered them; the recipient is encoded as `channel:thread_ts`.
 pub struct SlackChannel {
     bot_token: String,
+    app_token: Option<String>,
     channel_id: Option<String>,
     allowed_users: Vec<String>,
     client: reqwest::Client,
 }
 
+/// A user message received over Socket Mode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SlackMessageEvent {
+    user: String,
+    text: String,
+    channel: String,
+    ts: String,
+    thread_ts: Option<String>,
+}
+
+impl SlackMessageEvent {
+    /// Recipient for the reply: the message's thread, or a new thread under
+    /// the message itself.
+    fn reply_target(&self) -> String {
+        let thread = self.thread_ts.as_deref().unwrap_or(&self.ts);
+        format!("{}:{thread}", self.channel)
+    }
+}
+
+/// `envelope_id` of a Socket Mode envelope that must be acknowledged.
+fn envelope_id(envelope: &Value) -> Option<&str> {
+    envelope.get("envelope_id").and_then(Value::as_str)
+}
+
+/// Extract a plain user message from a Socket Mode envelope. Edits, joins,
+/// bot posts and every other event type yield `None`.
+fn parse_message_event(envelope: &Value) -> Option<SlackMessageEvent> {
+    if envelope.get("type").and_then(Value::as_str) != Some("events_api") {
+        return None;
+    }
+    let event = envelope.get("payload")?.get("event")?;
+    if event.get("type").and_then(Value::as_str) != Some("message")
+        || event.get("subtype").is_some()
+        || event.get("bot_id").is_some()
+    {
+        return None;
+    }
+    let field = |name: &str| event.get(name).and_then(Value::as_str).map(String::from);
+    let text = field("text").filter(|t| !t.trim().is_empty())?;
+    Some(SlackMessageEvent {
+        user: field("user")?,
+        text,
+        channel: field("channel")?,
+        ts: field("ts")?,
+        thread_ts: field("thread_ts"),
+    })
+}
+
+/// `chat.postMessage` body for `recipient` (`channel` or `channel:thread_ts`).
+fn post_message_body(message: &str, recipient: &str) -> Value {
+    match recipient.split_once(':') {
+        Some((channel, thread_ts)) if !thread_ts.is_empty() => json!({
+            "channel": channel,
+            "text": message,
+            "thread_ts": thread_ts,
+        }),
+        _ => json!({
+            "channel": recipient,
+            "text": message,
+        }),
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
 impl SlackChannel {
     pub fn new(bot_token: String, channel_id: Option<String>, allowed_users: Vec<String>) -> Self {
         Self {
             bot_token,
+            app_token: None,
             channel_id,
             allowed_users,
             client: reqwest::Client::new(),
         }
     }
 
+    /// Receive events over Socket Mode with this app-level (`xapp-`) token.
+    #[must_use]
+    pub fn with_app_token(mut self, app_token: Option<String>) -> Self {
+        self.app_token = app_token.filter(|t| !t.trim().is_empty());
+        self
+    }
+
     /// Check if a Slack user ID is in the allowlist.
     /// Empty list means deny everyone until explicitly configured.
     /// `"*"` means allow everyone.
@@ -44,6 +130,110 @@ impl SlackChannel {
             .and_then(|u| u.as_str())
             .map(String::from)
     }
+
+    /// Ask Slack for a Socket Mode WebSocket URL.
+    async fn open_socket_url(&self, app_token: &str) -> anyhow::Result<String> {
+        let resp: Value = self
+            .client
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if resp.get("ok") != Some(&Value::Bool(true)) {
+            let err = resp
+                .get("error")
+                .and_then(|e| e.as_str())
+                .unwrap_or("unknown");
+            anyhow::bail!("Slack apps.connections.open failed: {err}");
+        }
+        resp.get("url")
+            .and_then(|u| u.as_str())
+            .map(String::from)
+            .ok_or_else(|| anyhow::anyhow!("Slack apps.connections.open returned no url"))
+    }
+
+    /// Whether a parsed event should reach the agent.
+    fn accepts(&self, event: &SlackMessageEvent, bot_user_id: &str) -> bool {
+        if event.user == bot_user_id {
+            return false;
+        }
+        if let Some(ref channel_id) = self.channel_id {
+            if &event.channel != channel_id {
+                return false;
+            }
+        }
+        if !self.is_user_allowed(&event.user) {
+            tracing::warn!(
+                "Slack: ignoring message from unauthorized user: {}",
+                event.user
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Socket Mode: every envelope is acked as soon as it arrives (Slack
+    /// expects that within 3 seconds); the agent's reply is posted later via
+    /// `send` when the message loop gets to it.
+    async fn listen_socket_mode(
+        &self,
+        app_token: &str,
+        tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+    ) -> anyhow::Result<()> {
+        let bot_user_id = self.get_bot_user_id().await.unwrap_or_default();
+        let url = self.open_socket_url(app_token).await?;
+        let (ws_stream, _) = tokio_tungstenite::connect_async(&url).await?;
+        let (mut write, mut read) = ws_stream.split();
+
+        tracing::info!("Slack: connected via Socket Mode");
+
+        while let Some(frame) = read.next().await {
+            let text = match frame? {
+                Message::Text(t) => t,
+                Message::Ping(payload) => {
+                    write.send(Message::Pong(payload)).await?;
+                    continue;
+                }
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+
+            if let Some(id) = envelope_id(&envelope) {
+                let ack = json!({ "envelope_id": id });
+                write.send(Message::Text(ack.to_string())).await?;
+            }
+
+            if envelope.get("type").and_then(Value::as_str) == Some("disconnect") {
+                tracing::info!("Slack: server requested reconnect");
+                break;
+            }
+
+            let Some(event) = parse_message_event(&envelope) else {
+                continue;
+            };
+            if !self.accepts(&event, &bot_user_id) {
+                continue;
+            }
+
+            let channel_msg = ChannelMessage {
+                id: Uuid::new_v4().to_string(),
+                sender: event.reply_target(),
+                content: event.text,
+                channel: "slack".to_string(),
+                timestamp: now_secs(),
+            };
+            if tx.send(channel_msg).await.is_err() {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -53,10 +243,7 @@ impl Channel for SlackChannel {
     }
 
     async fn send(&self, message: &str, channel: &str) -> anyhow::Result<()> {
-        let body = serde_json::json!({
-            "channel": channel,
-            "text": message
-        });
+        let body = post_message_body(message, channel);
 
         let resp = self
             .client
@@ -90,6 +277,10 @@ impl Channel for SlackChannel {
     }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
+        if let Some(ref app_token) = self.app_token {
+            return self.listen_socket_mode(app_token, tx).await;
+        }
+
         let channel_id = self
             .channel_id
             .clone()
@@ -164,10 +355,7 @@ impl Channel for SlackChannel {
                         sender: channel_id.clone(),
                         content: text.to_string(),
                         channel: "slack".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
+                        timestamp: now_secs(),
                     };
 
                     if tx.send(channel_msg).await.is_err() {
@@ -252,4 +440,125 @@ mod tests {
         assert!(ch.is_user_allowed("U111"));
         assert!(ch.is_user_allowed("anyone"));
     }
+
+    fn envelope(event: &Value) -> Value {
+        json!({
+            "envelope_id": "env-1",
+            "type": "events_api",
+            "accepts_response_payload": false,
+            "payload": { "type": "event_callback", "event": event },
+        })
+    }
+
+    #[test]
+    fn with_app_token_ignores_blank_tokens() {
+        let ch =
+            SlackChannel::new("xoxb-fake".into(), None, vec![]).with_app_token(Some("  ".into()));
+        assert!(ch.app_token.is_none());
+        let ch = ch.with_app_token(Some("xapp-1".into()));
+        assert_eq!(ch.app_token.as_deref(), Some("xapp-1"));
+    }
+
+    #[test]
+    fn parses_user_message_event() {
+        let env = envelope(&json!({
+            "type": "message",
+            "user": "U111",
+            "text": "deploy status?",
+            "channel": "C123",
+            "ts": "1700000000.000100",
+        }));
+        assert_eq!(envelope_id(&env), Some("env-1"));
+        assert_eq!(
+            parse_message_event(&env),
+            Some(SlackMessageEvent {
+                user: "U111".into(),
+                text: "deploy status?".into(),
+                channel: "C123".into(),
+                ts: "1700000000.000100".into(),
+                thread_ts: None,
+            })
+        );
+    }
+
+    #[test]
+    fn ignores_non_message_events() {
+        let cases = [
+            json!({"type": "hello", "num_connections": 1}),
+            json!({"type": "disconnect", "reason": "refresh_requested"}),
+            envelope(&json!({"type": "reaction_added", "user": "U111", "reaction": "eyes"})),
+            envelope(&json!({
+                "type": "message", "subtype": "message_changed",
+                "channel": "C123", "ts": "1.0",
+            })),
+            envelope(&json!({
+                "type": "message", "bot_id": "B1", "user": "U999",
+                "text": "from a bot", "channel": "C123", "ts": "1.0",
+            })),
+            envelope(&json!({
+                "type": "message", "user": "U111", "text": "  ",
+                "channel": "C123", "ts": "1.0",
+            })),
+        ];
+        for case in &cases {
+            assert_eq!(parse_message_event(case), None, "{case}");
+        }
+    }
+
+    #[test]
+    fn reply_target_uses_thread_ts_when_present() {
+        let threaded = parse_message_event(&envelope(&json!({
+            "type": "message", "user": "U111", "text": "and now?",
+            "channel": "C123", "ts": "1700000050.000200",
+            "thread_ts": "1700000000.000100",
+        })))
+        .unwrap();
+        assert_eq!(threaded.reply_target(), "C123:1700000000.000100");
+
+        let top_level = SlackMessageEvent {
+            thread_ts: None,
+            ..threaded
+        };
+        assert_eq!(top_level.reply_target(), "C123:1700000050.000200");
+    }
+
+    #[test]
+    fn post_message_body_maps_thread_ts() {
+        let body = post_message_body("done", "C123:1700000000.000100");
+        assert_eq!(body["channel"], "C123");
+        assert_eq!(body["thread_ts"], "1700000000.000100");
+        assert_eq!(body["text"], "done");
+
+        let body = post_message_body("done", "C123");
+        assert_eq!(body["channel"], "C123");
+        assert!(body.get("thread_ts").is_none());
+    }
+
+    #[test]
+    fn accepts_filters_bot_channel_and_users() {
+        let ch = SlackChannel::new("xoxb-fake".into(), Some("C123".into()), vec!["U111".into()]);
+        let event = SlackMessageEvent {
+            user: "U111".into(),
+            text: "hi".into(),
+            channel: "C123".into(),
+            ts: "1.0".into(),
+            thread_ts: None,
+        };
+        assert!(ch.accepts(&event, "UBOT"));
+        assert!(!ch.accepts(&event, "U111"));
+        assert!(!ch.accepts(
+            &SlackMessageEvent {
+                channel: "C999".into(),
+                ..event.clone()
+            },
+            "UBOT"
+        ));
+        assert!(!ch.accepts(
+            &SlackMessageEvent {
+                user: "U222".into(),
+                ..event
+            },
+            "UBOT"
+        ));
+    }
 }
@@ -361,20 +361,66 @@ impl TelegramChannel {
         tracing::info!("Telegram photo (URL) sent to {chat_id}: {url}");
         Ok(())
     }
-}
 
-#[async_trait]
-impl Channel for TelegramChannel {
-    fn name(&self) -> &str {
-        "telegram"
-    }
+    /// Turn one `getUpdates` entry into a channel message. Returns `None` for
+    /// non-text updates and for senders outside the allowlist.
+    fn parse_update(&self, update: &serde_json::Value) -> Option<ChannelMessage> {
+        let message = update.get("message")?;
+        let text = message.get("text").and_then(serde_json::Value::as_str)?;
+
+        let username_opt = message
+            .get("from")
+            .and_then(|f| f.get("username"))
+            .and_then(|u| u.as_str());
+        let username = username_opt.unwrap_or("unknown");
+
+        let user_id = message
+            .get("from")
+            .and_then(|f| f.get("id"))
+            .and_then(serde_json::Value::as_i64);
+        let user_id_str = user_id.map(|id| id.to_string());
+
+        let mut identities = vec![username];
+        if let Some(ref id) = user_id_str {
+            identities.push(id.as_str());
+        }
 
-    async fn send(&self, message: &str, chat_id: &str) -> anyhow::Result<()> {
-        let body = serde_json::json!({
+        if !self.is_any_user_allowed(identities.iter().copied()) {
+            tracing::warn!(
+                "Telegram: ignoring message from unauthorized user: username={username}, user_id={}. \
+Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --channels-only`.",
+                user_id_str.as_deref().unwrap_or("unknown")
+            );
+            return None;
+        }
+
+        let chat_id = message
+            .get("chat")
+            .and_then(|c| c.get("id"))
+            .and_then(serde_json::Value::as_i64)
+            .map(|id| id.to_string())
+            .unwrap_or_default();
+
+        Some(ChannelMessage {
+            id: Uuid::new_v4().to_string(),
+            sender: chat_id,
+            content: normalize_command(text),
+            channel: "telegram".to_string(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        })
+    }
+
+    async fn send_message(&self, chat_id: &str, text: &str, markdown: bool) -> anyhow::Result<()> {
+        let mut body = serde_json::json!({
             "chat_id": chat_id,
-            "text": message,
-            "parse_mode": "Markdown"
+            "text": text,
         });
+        if markdown {
+            body["parse_mode"] = serde_json::Value::String("Markdown".into());
+        }
 
         let resp = self
             .client
@@ -394,6 +440,77 @@ impl Channel for TelegramChannel {
 
         Ok(())
     }
+}
+
+/// Reply to `/start`.
+const START_MESSAGE: &str =
+    "👋 Hi! Send me a message and I'll answer. Use /reset to start a fresh conversation.";
+
+/// Strip the `@botname` suffix Telegram adds to commands in group chats, so
+/// `/reset@my_bot` reads as `/reset`.
+fn normalize_command(text: &str) -> String {
+    let text = text.trim();
+    if !text.starts_with('/') {
+        return text.to_string();
+    }
+    let (command, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let command = command.split_once('@').map_or(command, |(name, _)| name);
+    if rest.is_empty() {
+        command.to_string()
+    } else {
+        format!("{command} {}", rest.trim_start())
+    }
+}
+
+/// Escape agent output for Telegram's legacy `Markdown` parse mode.
+///
+/// `_`, `*`, `[` and stray backticks are escaped so they show up literally
+/// instead of opening an entity that never closes (which Telegram rejects).
+/// Closed inline code spans and fenced code blocks are kept as they are.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            let fence = if rest.starts_with("```") { "```" } else { "`" };
+            if let Some(end) = rest[fence.len()..].find(fence) {
+                let span_end = fence.len() + end + fence.len();
+                out.push_str(&rest[..span_end]);
+                rest = &rest[span_end..];
+            } else {
+                for _ in 0..fence.len() {
+                    out.push_str("\\`");
+                }
+                rest = &rest[fence.len()..];
+            }
+            continue;
+        }
+        if matches!(c, '_' | '*' | '[') {
+            out.push('\\');
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+#[async_trait]
+impl Channel for TelegramChannel {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn send(&self, message: &str, chat_id: &str) -> anyhow::Result<()> {
+        if let Err(e) = self
+            .send_message(chat_id, &escape_markdown(message), true)
+            .await
+        {
+            // Formatting Telegram still refuses to parse: send it unformatted
+            tracing::warn!("Telegram: Markdown reply rejected, resending as plain text: {e}");
+            self.send_message(chat_id, message, false).await?;
+        }
+        Ok(())
+    }
 
     async fn listen(&self, tx: tokio::sync::mpsc::Sender<ChannelMessage>) -> anyhow::Result<()> {
         let mut offset: i64 = 0;
@@ -433,58 +550,17 @@ impl Channel for TelegramChannel {
                         offset = uid + 1;
                     }
 
-                    let Some(message) = update.get("message") else {
+                    let Some(msg) = self.parse_update(update) else {
                         continue;
                     };
 
-                    let Some(text) = message.get("text").and_then(serde_json::Value::as_str) else {
-                        continue;
-                    };
-
-                    let username_opt = message
-                        .get("from")
-                        .and_then(|f| f.get("username"))
-                        .and_then(|u| u.as_str());
-                    let username = username_opt.unwrap_or("unknown");
-
-                    let user_id = message
-                        .get("from")
-                        .and_then(|f| f.get("id"))
-                        .and_then(serde_json::Value::as_i64);
-                    let user_id_str = user_id.map(|id| id.to_string());
-
-                    let mut identities = vec![username];
-                    if let Some(ref id) = user_id_str {
-                        identities.push(id.as_str());
-                    }
-
-                    if !self.is_any_user_allowed(identities.iter().copied()) {
-                        tracing::warn!(
-                            "Telegram: ignoring message from unauthorized user: username={username}, user_id={}. \
-Allowlist Telegram @username or numeric user ID, then run `zeroclaw onboard --channels-only`.",
-                            user_id_str.as_deref().unwrap_or("unknown")
-                        );
+                    if msg.content == "/start" {
+                        if let Err(e) = self.send_message(&msg.sender, START_MESSAGE, false).await {
+                            tracing::warn!("Telegram: failed to answer /start: {e}");
+                        }
                         continue;
                     }
 
-                    let chat_id = message
-                        .get("chat")
-                        .and_then(|c| c.get("id"))
-                        .and_then(serde_json::Value::as_i64)
-                        .map(|id| id.to_string())
-                        .unwrap_or_default();
-
-                    let msg = ChannelMessage {
-                        id: Uuid::new_v4().to_string(),
-                        sender: chat_id,
-                        content: text.to_string(),
-                        channel: "telegram".to_string(),
-                        timestamp: std::time::SystemTime::now()
-                            .duration_since(std::time::UNIX_EPOCH)
-                            .unwrap_or_default()
-                            .as_secs(),
-                    };
-
                     if tx.send(msg).await.is_err() {
                         return Ok(());
                     }
@@ -828,4 +904,112 @@ mod tests {
         // Should not panic
         assert!(result.is_err());
     }
+
+    fn update(from: serde_json::Value, text: &str) -> serde_json::Value {
+        serde_json::json!({
+            "update_id": 1,
+            "message": {
+                "message_id": 10,
+                "from": from,
+                "chat": {"id": -100_123, "type": "group"},
+                "text": text,
+            }
+        })
+    }
+
+    #[test]
+    fn telegram_parse_update_text_message() {
+        let ch = TelegramChannel::new("t".into(), vec!["alice".into()]);
+        let msg = ch
+            .parse_update(&update(
+                serde_json::json!({"id": 42, "username": "alice"}),
+                "  hello bot ",
+            ))
+            .unwrap();
+        assert_eq!(msg.sender, "-100123");
+        assert_eq!(msg.content, "hello bot");
+        assert_eq!(msg.channel, "telegram");
+    }
+
+    #[test]
+    fn telegram_parse_update_allows_numeric_id() {
+        let ch = TelegramChannel::new("t".into(), vec!["42".into()]);
+        let msg = ch.parse_update(&update(serde_json::json!({"id": 42}), "hi"));
+        assert!(msg.is_some());
+    }
+
+    #[test]
+    fn telegram_parse_update_skips_unauthorized_and_non_text() {
+        let ch = TelegramChannel::new("t".into(), vec!["alice".into()]);
+        assert!(ch
+            .parse_update(&update(
+                serde_json::json!({"id": 7, "username": "eve"}),
+                "hi"
+            ))
+            .is_none());
+
+        let sticker = serde_json::json!({
+            "update_id": 2,
+            "message": {"from": {"username": "alice"}, "chat": {"id": 1}, "sticker": {}}
+        });
+        assert!(ch.parse_update(&sticker).is_none());
+        assert!(ch
+            .parse_update(&serde_json::json!({"update_id": 3, "edited_message": {}}))
+            .is_none());
+    }
+
+    #[test]
+    fn telegram_parse_update_normalizes_group_commands() {
+        let ch = TelegramChannel::new("t".into(), vec!["*".into()]);
+        let msg = ch
+            .parse_update(&update(
+                serde_json::json!({"username": "alice"}),
+                "/reset@zeroclaw_bot",
+            ))
+            .unwrap();
+        assert_eq!(msg.content, "/reset");
+    }
+
+    #[test]
+    fn telegram_normalize_command() {
+        assert_eq!(normalize_command("/start"), "/start");
+        assert_eq!(
+            normalize_command("/ask@my_bot  what time"),
+            "/ask what time"
+        );
+        assert_eq!(
+            normalize_command("mail me@example.com"),
+            "mail me@example.com"
+        );
+    }
+
+    #[test]
+    fn telegram_escape_markdown_specials() {
+        assert_eq!(escape_markdown("snake_case * 2"), r"snake\_case \* 2");
+        assert_eq!(escape_markdown("see [docs]"), r"see \[docs]");
+        assert_eq!(escape_markdown("plain text"), "plain text");
+    }
+
+    #[test]
+    fn telegram_escape_markdown_keeps_code() {
+        assert_eq!(
+            escape_markdown("run `my_tool --x *`"),
+            "run `my_tool --x *`"
+        );
+        let block = "```\nfn a_b() {}\n```";
+        assert_eq!(
+            escape_markdown(&format!("x_y\n{block}")),
+            format!("x\\_y\n{block}")
+        );
+    }
+
+    #[test]
+    fn telegram_escape_markdown_unbalanced_backticks() {
+        assert_eq!(escape_markdown("it`s `odd"), r"it`s `odd");
+        assert_eq!(escape_markdown("a ` b"), r"a \` b");
+        assert_eq!(
+            escape_markdown("```unclosed_fence"),
+            r"\`\`\`unclosed\_fence"
+        );
+    }
 }
@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
 /// A message received from or sent to a channel
 #[derive(Debug, Clone)]
@@ -26,4 +28,228 @@ pub trait Channel: Send + Sync {
     async fn health_check(&self) -> bool {
         true
     }
+
+    /// Whether `send_chunk` shows a reply while it is being written.
+    /// Otherwise `ReplyStream` collects the chunks and sends the reply once.
+    fn streams_replies(&self) -> bool {
+        false
+    }
+
+    /// Push the next piece of a reply that is still being written.
+    /// `ReplyStream` only calls it when `streams_replies` is set; the default
+    /// buffers the chunk until `end_message`.
+    async fn send_chunk(&self, chunk: &str, recipient: &str) -> anyhow::Result<()> {
+        UNSENT_CHUNKS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry((self.name().to_string(), recipient.to_string()))
+            .or_default()
+            .push_str(chunk);
+        Ok(())
+    }
+
+    /// Finish a reply pushed with `send_chunk`. The default sends what the
+    /// default `send_chunk` buffered as one message.
+    async fn end_message(&self, recipient: &str) -> anyhow::Result<()> {
+        let text = UNSENT_CHUNKS
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&(self.name().to_string(), recipient.to_string()));
+        match text {
+            Some(text) if !text.is_empty() => self.send(&text, recipient).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Replies buffered by the default `Channel::send_chunk`, by channel name
+/// and recipient.
+static UNSENT_CHUNKS: LazyLock<Mutex<HashMap<(String, String), String>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// One reply streamed out through a channel: chunks go straight to channels
+/// that stream replies, and are otherwise sent as one message by `finish`.
+pub struct ReplyStream<'a> {
+    channel: &'a dyn Channel,
+    recipient: &'a str,
+    text: String,
+}
+
+impl<'a> ReplyStream<'a> {
+    pub fn new(channel: &'a dyn Channel, recipient: &'a str) -> Self {
+        Self {
+            channel,
+            recipient,
+            text: String::new(),
+        }
+    }
+
+    pub async fn push(&mut self, chunk: &str) -> anyhow::Result<()> {
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        self.text.push_str(chunk);
+        if self.channel.streams_replies() {
+            self.channel.send_chunk(chunk, self.recipient).await?;
+        }
+        Ok(())
+    }
+
+    /// End the reply and return its full text.
+    pub async fn finish(self) -> anyhow::Result<String> {
+        if self.text.is_empty() {
+            return Ok(self.text);
+        }
+        if self.channel.streams_replies() {
+            self.channel.end_message(self.recipient).await?;
+        } else {
+            self.channel.send(&self.text, self.recipient).await?;
+        }
+        Ok(self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Sent(String, String),
+        Chunk(String),
+        End,
+    }
+
+    /// Records what it sends; streams replies when `streams` is set.
+    struct RecordingChannel {
+        streams: bool,
+        events: Mutex<Vec<Event>>,
+    }
+
+    impl RecordingChannel {
+        fn new(streams: bool) -> Self {
+            Self {
+                streams,
+                events: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn record(&self, event: Event) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[async_trait]
+    impl Channel for RecordingChannel {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        async fn send(&self, message: &str, recipient: &str) -> anyhow::Result<()> {
+            self.record(Event::Sent(recipient.to_string(), message.to_string()));
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn streams_replies(&self) -> bool {
+            self.streams
+        }
+
+        async fn send_chunk(&self, chunk: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.record(Event::Chunk(chunk.to_string()));
+            Ok(())
+        }
+
+        async fn end_message(&self, _recipient: &str) -> anyhow::Result<()> {
+            self.record(Event::End);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn streaming_channel_gets_chunks_in_order_then_one_end() {
+        let channel = RecordingChannel::new(true);
+        let mut reply = ReplyStream::new(&channel, "alice");
+        for chunk in ["Hel", "lo, ", "world"] {
+            reply.push(chunk).await.unwrap();
+        }
+        assert_eq!(reply.finish().await.unwrap(), "Hello, world");
+        assert_eq!(
+            *channel.events.lock().unwrap(),
+            vec![
+                Event::Chunk("Hel".into()),
+                Event::Chunk("lo, ".into()),
+                Event::Chunk("world".into()),
+                Event::End,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn other_channels_get_each_reply_once() {
+        let channel = RecordingChannel::new(false);
+        let mut to_alice = ReplyStream::new(&channel, "alice");
+        let mut to_bob = ReplyStream::new(&channel, "bob");
+        to_alice.push("Hel").await.unwrap();
+        to_bob.push("Hi ").await.unwrap();
+        to_alice.push("lo").await.unwrap();
+        to_bob.push("Bob").await.unwrap();
+        to_bob.finish().await.unwrap();
+        to_alice.finish().await.unwrap();
+        // A reply with no text sends nothing
+        ReplyStream::new(&channel, "carol").finish().await.unwrap();
+        assert_eq!(
+            *channel.events.lock().unwrap(),
+            vec![
+                Event::Sent("bob".into(), "Hi Bob".into()),
+                Event::Sent("alice".into(), "Hello".into()),
+            ]
+        );
+    }
+
+    /// Keeps the default `send_chunk` and `end_message`.
+    struct PlainChannel {
+        sent: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl Channel for PlainChannel {
+        fn name(&self) -> &str {
+            "plain"
+        }
+
+        async fn send(&self, message: &str, _recipient: &str) -> anyhow::Result<()> {
+            self.sent.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        async fn listen(
+            &self,
+            _tx: tokio::sync::mpsc::Sender<ChannelMessage>,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn default_send_chunk_buffers_until_end_message() {
+        let channel = PlainChannel {
+            sent: Mutex::new(Vec::new()),
+        };
+        for chunk in ["Hel", "lo, ", "world"] {
+            channel.send_chunk(chunk, "dave").await.unwrap();
+        }
+        assert!(channel.sent.lock().unwrap().is_empty());
+        channel.end_message("dave").await.unwrap();
+        // Nothing left over for a second end
+        channel.end_message("dave").await.unwrap();
+        assert_eq!(*channel.sent.lock().unwrap(), vec!["Hello, world"]);
+    }
 }
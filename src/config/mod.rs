@@ -1,8 +1,12 @@
 pub mod schema;
+pub mod validate;
 
 pub use schema::{
-    AutonomyConfig, BrowserConfig, ChannelsConfig, ComposioConfig, Config, DiscordConfig,
-    DockerRuntimeConfig, GatewayConfig, HeartbeatConfig, IMessageConfig, IdentityConfig,
-    MatrixConfig, MemoryConfig, ModelRouteConfig, ObservabilityConfig, ReliabilityConfig,
-    RuntimeConfig, SecretsConfig, SlackConfig, TelegramConfig, TunnelConfig, WebhookConfig,
+    AgentConfig, AutonomyConfig, BedrockConfig, BrowserConfig, ChannelsConfig, ComposioConfig,
+    Config, DiscordConfig, DockerRuntimeConfig, GatewayConfig, HeartbeatConfig, HttpChannelConfig,
+    IMessageConfig, IdentityConfig, MatrixConfig, MemoryConfig, MemoryTtlConfig,
+    MockProviderConfig, ModelRouteConfig, ObservabilityConfig, OllamaConfig, OpenAiCompatConfig,
+    OpenRouterConfig, OutputFilterAction, ReliabilityConfig, ResponseFormat, RuntimeConfig,
+    SearchConfig, SecretsConfig, SecurityConfig, SkillsConfig, SlackConfig, SshRuntimeConfig,
+    TelegramConfig, TiktokenEncoding, ToolHistory, ToolsConfig, TunnelConfig, WebhookConfig,
 };
@@ -2,6 +2,7 @@ use crate::security::AutonomyLevel;
 use anyhow::{Context, Result};
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -58,11 +59,55 @@ pub struct Config {
     #[serde(default)]
     pub secrets: SecretsConfig,
 
+    #[serde(default)]
+    pub security: SecurityConfig,
+
     #[serde(default)]
     pub browser: BrowserConfig,
 
+    #[serde(default)]
+    pub tools: ToolsConfig,
+
+    #[serde(default)]
+    pub search: SearchConfig,
+
+    #[serde(default)]
+    pub agent: AgentConfig,
+
+    #[serde(default)]
+    pub skills: SkillsConfig,
+
+    /// Named prompt templates (`[prompts]`) with `{{variable}}` placeholders,
+    /// expanded by `zeroclaw agent --template <name> --var key=value`
+    #[serde(default)]
+    pub prompts: HashMap<String, String>,
+
     #[serde(default)]
     pub identity: IdentityConfig,
+
+    /// Named personas (`[identities.<name>]`), picked with `active_identity`,
+    /// `zeroclaw agent --identity <name>` or `/persona <name>`
+    #[serde(default)]
+    pub identities: HashMap<String, IdentityConfig>,
+
+    /// Persona from `identities` to use instead of `[identity]`
+    #[serde(default)]
+    pub active_identity: Option<String>,
+
+    #[serde(default)]
+    pub bedrock: BedrockConfig,
+
+    #[serde(default)]
+    pub openrouter: OpenRouterConfig,
+
+    #[serde(default)]
+    pub ollama: OllamaConfig,
+
+    #[serde(default)]
+    pub openai_compat: OpenAiCompatConfig,
+
+    #[serde(default)]
+    pub mock: MockProviderConfig,
 }
 
 // ── Identity (AIEOS / OpenClaw format) ──────────────────────────
@@ -78,6 +123,15 @@ pub struct IdentityConfig {
     /// Inline AIEOS JSON (alternative to file path)
     #[serde(default)]
     pub aieos_inline: Option<String>,
+    /// Fixed instructions placed before the system prompt
+    #[serde(default)]
+    pub system_prompt_prepend: Option<String>,
+    /// Fixed instructions placed after the system prompt
+    #[serde(default)]
+    pub system_prompt_append: Option<String>,
+    /// Replaces the generated system prompt (the tool list is still appended)
+    #[serde(default)]
+    pub system_prompt_override: Option<String>,
 }
 
 fn default_identity_format() -> String {
@@ -90,6 +144,9 @@ impl Default for IdentityConfig {
             format: default_identity_format(),
             aieos_path: None,
             aieos_inline: None,
+            system_prompt_prepend: None,
+            system_prompt_append: None,
+            system_prompt_override: None,
         }
     }
 }
@@ -210,6 +267,86 @@ impl Default for SecretsConfig {
     }
 }
 
+// ── Security (tool policy, secret redaction) ────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Programs the shell tool may run, matched on each pipeline segment's
+    /// program name. Replaces `autonomy.allowed_commands` when set.
+    #[serde(default)]
+    pub allowed_commands: Option<Vec<String>>,
+    /// Programs that are always refused, even if also allowlisted.
+    #[serde(default)]
+    pub denied_commands: Vec<String>,
+    /// Directories outside the workspace that file tools may also use.
+    #[serde(default)]
+    pub extra_allowed_dirs: Vec<String>,
+    /// Hosts network tools may reach (`example.com`, `*.example.com`,
+    /// `host:8080`). Empty allows any public host; listing a private host
+    /// here is the only way to reach it.
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Hosts network tools may never reach, even if also allowlisted.
+    #[serde(default)]
+    pub denied_hosts: Vec<String>,
+    /// Hash-chained JSON-lines log of every tool call (relative paths are
+    /// under the workspace). Unset disables auditing.
+    #[serde(default)]
+    pub audit_log: Option<String>,
+    /// Argument keys containing any of these (case-insensitive) are masked
+    /// in the audit log.
+    #[serde(default = "default_audit_redact_fields")]
+    pub audit_redact_fields: Vec<String>,
+    /// Extra regular expressions whose matches are masked in logs, observer
+    /// events and the audit log, on top of the built-in key formats
+    /// (AWS access keys, `sk-` API keys, bearer tokens, ...).
+    #[serde(default)]
+    pub redaction_patterns: Vec<String>,
+    /// Regular expressions for text the agent must never show the user,
+    /// checked in answers (streamed ones too) and returned files before
+    /// they are printed or sent.
+    #[serde(default)]
+    pub output_filters: Vec<String>,
+    /// What a match of `output_filters` does.
+    #[serde(default)]
+    pub output_filter_action: OutputFilterAction,
+}
+
+fn default_audit_redact_fields() -> Vec<String> {
+    crate::security::audit::DEFAULT_REDACT_FIELDS
+        .iter()
+        .map(|field| (*field).to_string())
+        .collect()
+}
+
+impl Default for SecurityConfig {
+    fn default() -> Self {
+        Self {
+            allowed_commands: None,
+            denied_commands: Vec::new(),
+            extra_allowed_dirs: Vec::new(),
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            audit_log: None,
+            audit_redact_fields: default_audit_redact_fields(),
+            redaction_patterns: Vec::new(),
+            output_filters: Vec::new(),
+            output_filter_action: OutputFilterAction::default(),
+        }
+    }
+}
+
+/// How `security.output_filters` treat a match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFilterAction {
+    /// Replace the matched text with `[FILTERED]`
+    #[default]
+    Redact,
+    /// Withhold the whole response and end the turn
+    Block,
+}
+
 // ── Browser (friendly-service browsing only) ───────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -225,11 +362,233 @@ pub struct BrowserConfig {
     pub session_name: Option<String>,
 }
 
+// ── Web search ───────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SearchConfig {
+    /// Backend for the `web_search` tool: "brave" | "serpapi" | "tavily"
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// API key for the search provider
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Override the provider's API endpoint (proxies, testing)
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+// ── Tools ────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolsConfig {
+    /// Seconds a tool call may run before it is cancelled and reported as
+    /// failed. Tools can override this via `Tool::timeout`.
+    #[serde(default = "default_tool_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Extra attempts for a failed call to a tool that reports the failure
+    /// as transient (`Tool::is_retryable`), before the model sees it.
+    #[serde(default = "default_tool_max_retries")]
+    pub max_retries: u32,
+    /// Wait before the first retry, in milliseconds; doubled for each
+    /// further attempt.
+    #[serde(default = "default_tool_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+}
+
+fn default_tool_timeout_secs() -> u64 {
+    60
+}
+
+fn default_tool_max_retries() -> u32 {
+    2
+}
+
+fn default_tool_retry_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for ToolsConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_tool_timeout_secs(),
+            max_retries: default_tool_max_retries(),
+            retry_backoff_ms: default_tool_retry_backoff_ms(),
+        }
+    }
+}
+
+// ── Agent loop ───────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // independent on/off switches
+pub struct AgentConfig {
+    /// Approximate token budget (~4 characters per token) for the conversation
+    /// sent on each tool-loop iteration. Oldest tool exchanges are dropped
+    /// once it is exceeded; the system prompt and user message are always kept.
+    #[serde(default = "default_context_budget")]
+    pub context_budget: usize,
+    /// Stop the run once the provider-reported token usage, summed across all
+    /// calls, exceeds this total. Unset means no limit.
+    #[serde(default)]
+    pub max_total_tokens: Option<u64>,
+    /// Plan without side effects: calls to tools that aren't read-only are
+    /// printed and answered with a placeholder instead of being executed.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Ask reasoning-capable models for their thinking and print it to stderr
+    /// (and report it to observers), apart from the answer. When off, any
+    /// reasoning a provider returns is dropped.
+    #[serde(default)]
+    pub show_reasoning: bool,
+    /// Cache provider responses under `workspace/cache/responses` and reuse
+    /// them for identical requests. Meant for development, where the same
+    /// prompt is sent over and over.
+    #[serde(default)]
+    pub cache: bool,
+    /// How long a cached response stays valid, in seconds.
+    #[serde(default = "default_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+    /// Also cache requests with a temperature above zero, whose answers
+    /// would otherwise vary from call to call.
+    #[serde(default)]
+    pub cache_nondeterministic: bool,
+    /// `json` makes the final answer a JSON document: providers that can
+    /// are asked for JSON output, and a reply that doesn't parse (or doesn't
+    /// match `response_schema`) is sent back once for correction.
+    #[serde(default)]
+    pub response_format: ResponseFormat,
+    /// JSON Schema file (relative to the workspace) that JSON answers must
+    /// match. Only used when `response_format = "json"`.
+    #[serde(default)]
+    pub response_schema: Option<PathBuf>,
+    /// How deep `subagent` calls may nest: 1 lets the agent delegate to
+    /// sub-agents that can't delegate further, 0 removes the tool.
+    #[serde(default = "default_max_subagent_depth")]
+    pub max_subagent_depth: u32,
+    /// After this many minutes without input, an interactive session
+    /// compacts memory and warms the provider up so the next turn is fast.
+    /// 0 disables it.
+    #[serde(default = "default_idle_compact_minutes")]
+    pub idle_compact_minutes: u64,
+    /// Warm the provider up before the first turn (and when an idle session
+    /// is refreshed), checking that it is reachable with the configured
+    /// credentials. The result is reported as an observer event.
+    #[serde(default)]
+    pub warmup: bool,
+    /// `latest` keeps only the newest result of each tool call (same tool,
+    /// same arguments) in the conversation sent to the provider; older ones
+    /// are replaced with `[superseded]`. `all` keeps every result.
+    #[serde(default)]
+    pub tool_history: ToolHistory,
+    /// Temperature for calls that offer tools, where a low value keeps tool
+    /// selection deterministic. Unset uses `default_temperature`.
+    #[serde(default)]
+    pub tool_phase_temperature: Option<f64>,
+    /// Temperature for calls made without tools on offer: a run with no
+    /// tools, or the wrap-up once the tool iteration limit is reached. Unset
+    /// uses `default_temperature`.
+    #[serde(default)]
+    pub final_phase_temperature: Option<f64>,
+    /// Tokenizers used to count tokens against `context_budget`, per model
+    /// family: keys are model-name prefixes (`gpt-4o`, `openai/`), values
+    /// tiktoken encodings. The longest matching prefix wins; other models
+    /// are estimated at ~4 characters per token.
+    #[serde(default)]
+    pub tokenizers: HashMap<String, TiktokenEncoding>,
+}
+
+/// A tiktoken vocabulary, bundled with the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TiktokenEncoding {
+    /// GPT-4o, o1 and later models
+    O200kBase,
+    /// GPT-4 and GPT-3.5
+    Cl100kBase,
+    /// Codex and `text-davinci-002/003`
+    P50kBase,
+    /// GPT-3 (`davinci`) and GPT-2
+    R50kBase,
+}
+
+/// Shape of the agent's final answer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    /// Free-form text
+    #[default]
+    Text,
+    /// A single JSON document
+    Json,
+}
+
+/// Which tool results stay in the conversation during a tool loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolHistory {
+    /// Every result, as returned
+    #[default]
+    All,
+    /// Only the most recent result per tool and arguments
+    Latest,
+}
+
+fn default_context_budget() -> usize {
+    64_000
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    86_400
+}
+
+fn default_max_subagent_depth() -> u32 {
+    1
+}
+
+fn default_idle_compact_minutes() -> u64 {
+    30
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            context_budget: default_context_budget(),
+            max_total_tokens: None,
+            dry_run: false,
+            show_reasoning: false,
+            cache: false,
+            cache_ttl_secs: default_cache_ttl_secs(),
+            cache_nondeterministic: false,
+            response_format: ResponseFormat::Text,
+            response_schema: None,
+            max_subagent_depth: default_max_subagent_depth(),
+            idle_compact_minutes: default_idle_compact_minutes(),
+            warmup: false,
+            tool_history: ToolHistory::All,
+            tool_phase_temperature: None,
+            final_phase_temperature: None,
+            tokenizers: HashMap::new(),
+        }
+    }
+}
+
+// ── Skills ───────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SkillsConfig {
+    /// Only load these skills (by name or directory/file name). Empty loads all.
+    #[serde(default)]
+    pub enabled: Vec<String>,
+    /// Never load these skills, even if they are also listed in `enabled`
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
 // ── Memory ───────────────────────────────────────────────────
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryConfig {
-    /// "sqlite" | "markdown" | "none"
+    /// "sqlite" | "vector" | "markdown" | "none"
     pub backend: String,
     /// Auto-save conversation context to memory
     pub auto_save: bool,
@@ -245,7 +604,7 @@ pub struct MemoryConfig {
     /// For sqlite backend: prune conversation rows older than this many days
     #[serde(default = "default_conversation_retention_days")]
     pub conversation_retention_days: u32,
-    /// Embedding provider: "none" | "openai" | "custom:URL"
+    /// Embedding provider: "none" | "openai" | "custom:URL" | "provider:NAME"
     #[serde(default = "default_embedding_provider")]
     pub embedding_provider: String,
     /// Embedding model name (e.g. "text-embedding-3-small")
@@ -266,6 +625,52 @@ pub struct MemoryConfig {
     /// Max tokens per chunk for document splitting
     #[serde(default = "default_chunk_size")]
     pub chunk_max_tokens: usize,
+    /// Expiry for stored entries (unset keeps everything forever)
+    #[serde(default)]
+    pub ttl: MemoryTtlConfig,
+    /// Summarize old Daily entries at startup once there are more than this
+    /// many (0 disables compaction)
+    #[serde(default = "default_daily_compact_threshold")]
+    pub daily_compact_threshold: usize,
+    /// Only Daily entries older than this many days are compacted
+    #[serde(default = "default_daily_compact_after_days")]
+    pub daily_compact_after_days: u32,
+    /// Encrypt entry content at rest with this 256-bit key (64 hex chars)
+    #[serde(default)]
+    pub encryption_key: Option<String>,
+    /// Encrypt entry content at rest with a key derived from this passphrase
+    /// (Argon2id); also settable via `ZEROCLAW_MEMORY_PASSPHRASE`
+    #[serde(default)]
+    pub encryption_passphrase: Option<String>,
+    /// Summarize auto-saved assistant responses with a one-line LLM summary
+    /// instead of their first sentence
+    #[serde(default)]
+    pub summarize_saves: bool,
+    /// Maximum length in characters of an auto-saved response summary
+    #[serde(default = "default_save_summary_chars")]
+    pub save_summary_chars: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryTtlConfig {
+    /// Days until an entry expires, for categories without an override
+    #[serde(default)]
+    pub default_days: Option<u32>,
+    /// Per-category lifetimes in days, keyed by category name
+    /// ("core", "daily", "conversation" or a custom name); 0 never expires
+    #[serde(default)]
+    pub categories: HashMap<String, u32>,
+}
+
+impl MemoryTtlConfig {
+    /// Lifetime in days for entries stored under `category`, if they expire
+    pub fn days_for(&self, category: &str) -> Option<u32> {
+        self.categories
+            .get(category)
+            .copied()
+            .or(self.default_days)
+            .filter(|&days| days > 0)
+    }
 }
 
 fn default_embedding_provider() -> String {
@@ -301,6 +706,15 @@ fn default_cache_size() -> usize {
 fn default_chunk_size() -> usize {
     512
 }
+fn default_daily_compact_threshold() -> usize {
+    100
+}
+fn default_daily_compact_after_days() -> u32 {
+    3
+}
+fn default_save_summary_chars() -> usize {
+    100
+}
 
 impl Default for MemoryConfig {
     fn default() -> Self {
@@ -318,6 +732,13 @@ impl Default for MemoryConfig {
             keyword_weight: default_keyword_weight(),
             embedding_cache_size: default_cache_size(),
             chunk_max_tokens: default_chunk_size(),
+            ttl: MemoryTtlConfig::default(),
+            daily_compact_threshold: default_daily_compact_threshold(),
+            daily_compact_after_days: default_daily_compact_after_days(),
+            encryption_key: None,
+            encryption_passphrase: None,
+            summarize_saves: false,
+            save_summary_chars: default_save_summary_chars(),
         }
     }
 }
@@ -328,12 +749,48 @@ impl Default for MemoryConfig {
 pub struct ObservabilityConfig {
     /// "none" | "log" | "prometheus" | "otel"
     pub backend: String,
+    /// Several backends at once (e.g. `["log", "otel"]`); overrides
+    /// `backend` when non-empty.
+    #[serde(default)]
+    pub backends: Vec<String>,
+    /// Output of the `log` backend: "text" (tracing lines) or "json" (one
+    /// structured JSON object per event).
+    #[serde(default = "default_observability_format")]
+    pub format: String,
+    /// File the JSON log is appended to; unset writes to stdout.
+    #[serde(default)]
+    pub log_path: Option<String>,
+    /// Address the `prometheus` backend serves `GET /metrics` on.
+    #[serde(default = "default_metrics_addr")]
+    pub metrics_addr: String,
+    /// OTLP/HTTP collector URL for the `otel` backend (`/v1/traces` and
+    /// `/v1/metrics` are appended). Unset uses `OTEL_EXPORTER_OTLP_ENDPOINT`
+    /// or `http://localhost:4318`.
+    #[serde(default)]
+    pub otel_endpoint: Option<String>,
+    /// Extra headers sent with every OTLP export (e.g. collector auth).
+    #[serde(default)]
+    pub otel_headers: HashMap<String, String>,
+}
+
+fn default_observability_format() -> String {
+    "text".into()
+}
+
+fn default_metrics_addr() -> String {
+    "127.0.0.1:9464".into()
 }
 
 impl Default for ObservabilityConfig {
     fn default() -> Self {
         Self {
             backend: "none".into(),
+            backends: Vec::new(),
+            format: default_observability_format(),
+            log_path: None,
+            metrics_addr: default_metrics_addr(),
+            otel_endpoint: None,
+            otel_headers: HashMap::new(),
         }
     }
 }
@@ -409,13 +866,17 @@ impl Default for AutonomyConfig {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuntimeConfig {
-    /// Runtime kind (`native` | `docker`).
+    /// Runtime kind (`native` | `docker` | `ssh`).
     #[serde(default = "default_runtime_kind")]
     pub kind: String,
 
     /// Docker runtime settings (used when `kind = "docker"`).
     #[serde(default)]
     pub docker: DockerRuntimeConfig,
+
+    /// Remote host settings (used when `kind = "ssh"`).
+    #[serde(default)]
+    pub ssh: SshRuntimeConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -449,6 +910,29 @@ pub struct DockerRuntimeConfig {
     pub allowed_workspace_roots: Vec<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshRuntimeConfig {
+    /// Remote host name or address.
+    #[serde(default)]
+    pub host: String,
+
+    /// Login user (empty = ssh default / `~/.ssh/config`).
+    #[serde(default)]
+    pub user: String,
+
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+
+    /// Private key used for authentication. Supports `~`.
+    #[serde(default = "default_ssh_key_path")]
+    pub key_path: String,
+
+    /// Workspace directory on the remote host (`None` = same path as the
+    /// local workspace).
+    #[serde(default)]
+    pub remote_workspace: Option<String>,
+}
+
 fn default_runtime_kind() -> String {
     "native".into()
 }
@@ -483,11 +967,32 @@ impl Default for DockerRuntimeConfig {
     }
 }
 
+fn default_ssh_port() -> u16 {
+    22
+}
+
+fn default_ssh_key_path() -> String {
+    "~/.ssh/id_ed25519".into()
+}
+
+impl Default for SshRuntimeConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            user: String::new(),
+            port: default_ssh_port(),
+            key_path: default_ssh_key_path(),
+            remote_workspace: None,
+        }
+    }
+}
+
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             kind: default_runtime_kind(),
             docker: DockerRuntimeConfig::default(),
+            ssh: SshRuntimeConfig::default(),
         }
     }
 }
@@ -505,6 +1010,23 @@ pub struct ReliabilityConfig {
     /// Fallback provider chain (e.g. `["anthropic", "openai"]`).
     #[serde(default)]
     pub fallback_providers: Vec<String>,
+    /// Consecutive provider failures that open its circuit breaker (0 = off).
+    #[serde(default = "default_circuit_failure_threshold")]
+    pub circuit_failure_threshold: u32,
+    /// Window (seconds) the consecutive failures must fall within.
+    #[serde(default = "default_circuit_window_secs")]
+    pub circuit_window_secs: u64,
+    /// Seconds an open circuit fails fast before a trial call is allowed.
+    #[serde(default = "default_circuit_cooldown_secs")]
+    pub circuit_cooldown_secs: u64,
+    /// Provider requests allowed in flight at once; further calls queue.
+    /// Shared by every channel using the same provider (0 = unlimited).
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Provider requests allowed per minute, paced with short bursts
+    /// (0 = unlimited).
+    #[serde(default)]
+    pub requests_per_minute: u32,
     /// Initial backoff for channel/daemon restarts.
     #[serde(default = "default_channel_backoff_secs")]
     pub channel_initial_backoff_secs: u64,
@@ -527,6 +1049,22 @@ fn default_provider_backoff_ms() -> u64 {
     500
 }
 
+fn default_circuit_failure_threshold() -> u32 {
+    5
+}
+
+fn default_circuit_window_secs() -> u64 {
+    60
+}
+
+fn default_circuit_cooldown_secs() -> u64 {
+    30
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
+}
+
 fn default_channel_backoff_secs() -> u64 {
     2
 }
@@ -549,6 +1087,11 @@ impl Default for ReliabilityConfig {
             provider_retries: default_provider_retries(),
             provider_backoff_ms: default_provider_backoff_ms(),
             fallback_providers: Vec::new(),
+            circuit_failure_threshold: default_circuit_failure_threshold(),
+            circuit_window_secs: default_circuit_window_secs(),
+            circuit_cooldown_secs: default_circuit_cooldown_secs(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            requests_per_minute: 0,
             channel_initial_backoff_secs: default_channel_backoff_secs(),
             channel_max_backoff_secs: default_channel_backoff_max_secs(),
             scheduler_poll_secs: default_scheduler_poll_secs(),
@@ -557,6 +1100,143 @@ impl Default for ReliabilityConfig {
     }
 }
 
+// ── AWS Bedrock ──────────────────────────────────────────────────
+
+/// Bedrock-specific settings.
+///
+/// Credentials resolve in order: the static keys below, then the standard
+/// `AWS_*` environment variables, then the shared credentials file profile,
+/// and finally the ECS/EC2 instance role (see `instance_metadata`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    /// AWS region (overrides `AWS_REGION` and the profile region)
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Named profile in `~/.aws/credentials` (default: `AWS_PROFILE`, then "default")
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Static access key ID (prefer env vars or a profile)
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    /// Static secret access key
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// Optional session token for temporary static credentials
+    #[serde(default)]
+    pub session_token: Option<String>,
+    /// IAM role to assume via STS with the resolved credentials
+    #[serde(default)]
+    pub assume_role_arn: Option<String>,
+    /// External ID required by the role's trust policy, if any
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Role session name (default: "zeroclaw-bedrock")
+    #[serde(default)]
+    pub session_name: Option<String>,
+    /// Runtime endpoint override, e.g. a `PrivateLink` VPC endpoint hostname
+    #[serde(default)]
+    pub endpoint_url: Option<String>,
+    /// Maximum tokens generated per response
+    #[serde(default = "default_bedrock_max_tokens")]
+    pub max_tokens: u32,
+    /// Nucleus sampling probability (`topP`); omitted from requests when unset
+    #[serde(default)]
+    pub top_p: Option<f64>,
+    /// Sequences that stop generation when produced
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    /// Fall back to ECS/EC2 instance-role credentials when no keys are found
+    #[serde(default = "default_true")]
+    pub instance_metadata: bool,
+    /// Bedrock Guardrail (ID or ARN) applied to every Converse request
+    #[serde(default)]
+    pub guardrail_id: Option<String>,
+    /// Version of `guardrail_id` to apply (default: "DRAFT")
+    #[serde(default)]
+    pub guardrail_version: Option<String>,
+}
+
+fn default_bedrock_max_tokens() -> u32 {
+    4096
+}
+
+impl Default for BedrockConfig {
+    fn default() -> Self {
+        Self {
+            region: None,
+            profile: None,
+            access_key_id: None,
+            secret_access_key: None,
+            session_token: None,
+            assume_role_arn: None,
+            external_id: None,
+            session_name: None,
+            endpoint_url: None,
+            max_tokens: default_bedrock_max_tokens(),
+            top_p: None,
+            stop_sequences: Vec::new(),
+            instance_metadata: true,
+            guardrail_id: None,
+            guardrail_version: None,
+        }
+    }
+}
+
+// ── OpenRouter ───────────────────────────────────────────────────
+
+/// App attribution for the `OpenRouter` provider, sent as `HTTP-Referer` and
+/// `X-Title` on every request so traffic is credited to a named app.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenRouterConfig {
+    /// App URL sent as `HTTP-Referer` (default: the project repository)
+    #[serde(default)]
+    pub app_url: Option<String>,
+    /// App name sent as `X-Title` (default: "`ZeroClaw`")
+    #[serde(default)]
+    pub app_name: Option<String>,
+}
+
+// ── Ollama ───────────────────────────────────────────────────────
+
+/// Settings for a local or self-hosted Ollama server.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    /// Server URL (default: "<http://localhost:11434>")
+    #[serde(default)]
+    pub base_url: Option<String>,
+}
+
+// ── OpenAI-compatible endpoint ───────────────────────────────────
+
+/// Settings for `provider = "openai-compat"`: any server that speaks the
+/// chat-completions API (Azure, vLLM, `LocalAI`, ...).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenAiCompatConfig {
+    /// Endpoint base URL; `/chat/completions` is appended unless present
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Header that carries the API key instead of `Authorization: Bearer`
+    /// (e.g. `api-key` for Azure)
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Extra headers sent on every request; also applied to `custom:<url>`
+    /// providers
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+// ── Mock provider ────────────────────────────────────────────────
+
+/// Scripted replies for `provider = "mock"`, an offline provider for tests
+/// and demos. Replies are used in order; once they run out the provider
+/// echoes the last user message.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MockProviderConfig {
+    /// Responses returned in turn (may contain `<tool_call>` blocks)
+    #[serde(default)]
+    pub responses: Vec<String>,
+}
+
 // ── Model routing ────────────────────────────────────────────────
 
 /// Route a task hint to a specific provider + model.
@@ -574,6 +1254,9 @@ impl Default for ReliabilityConfig {
 /// ```
 ///
 /// Usage: pass `hint:reasoning` as the model parameter to route the request.
+///
+/// Repeating a hint builds a fallback chain: if the first route fails with a
+/// retryable error (rate limit, outage), the next one is tried with its model.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelRouteConfig {
     /// Task hint name (e.g. "reasoning", "fast", "code", "summarize")
@@ -694,6 +1377,7 @@ pub struct ChannelsConfig {
     pub whatsapp: Option<WhatsAppConfig>,
     pub email: Option<crate::channels::email_channel::EmailConfig>,
     pub irc: Option<IrcConfig>,
+    pub http: Option<HttpChannelConfig>,
 }
 
 impl Default for ChannelsConfig {
@@ -709,6 +1393,7 @@ impl Default for ChannelsConfig {
             whatsapp: None,
             email: None,
             irc: None,
+            http: None,
         }
     }
 }
@@ -725,6 +1410,10 @@ pub struct DiscordConfig {
     pub guild_id: Option<String>,
     #[serde(default)]
     pub allowed_users: Vec<String>,
+    /// Channels where every message is answered; elsewhere the bot only
+    /// answers mentions and DMs.
+    #[serde(default)]
+    pub channel_ids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -742,6 +1431,30 @@ pub struct WebhookConfig {
     pub secret: Option<String>,
 }
 
+/// HTTP API channel: `POST /chat` runs one agent turn per request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpChannelConfig {
+    /// Address to listen on (default: `127.0.0.1:8090`).
+    #[serde(default = "default_http_channel_listen")]
+    pub listen: String,
+    /// Bearer token required on every request. Mandatory for a public bind.
+    #[serde(default)]
+    pub auth_token: Option<String>,
+}
+
+fn default_http_channel_listen() -> String {
+    "127.0.0.1:8090".into()
+}
+
+impl Default for HttpChannelConfig {
+    fn default() -> Self {
+        Self {
+            listen: default_http_channel_listen(),
+            auth_token: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IMessageConfig {
     pub allowed_contacts: Vec<String>,
@@ -829,13 +1542,153 @@ impl Default for Config {
             gateway: GatewayConfig::default(),
             composio: ComposioConfig::default(),
             secrets: SecretsConfig::default(),
+            security: SecurityConfig::default(),
             browser: BrowserConfig::default(),
+            tools: ToolsConfig::default(),
+            search: SearchConfig::default(),
+            agent: AgentConfig::default(),
+            skills: SkillsConfig::default(),
+            prompts: HashMap::new(),
             identity: IdentityConfig::default(),
+            identities: HashMap::new(),
+            active_identity: None,
+            bedrock: BedrockConfig::default(),
+            openrouter: OpenRouterConfig::default(),
+            ollama: OllamaConfig::default(),
+            openai_compat: OpenAiCompatConfig::default(),
+            mock: MockProviderConfig::default(),
         }
     }
 }
 
+const ENV_PREFIX: &str = "ZEROCLAW_";
+const ENV_NESTING_SEPARATOR: &str = "__";
+
+/// Set the field `name` (an env var name) points at in the serialized config.
+fn apply_env_override(root: &mut toml::Value, name: &str, raw: &str) -> Result<()> {
+    let path: Vec<String> = name[ENV_PREFIX.len()..]
+        .split(ENV_NESTING_SEPARATOR)
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if path.iter().any(String::is_empty) {
+        anyhow::bail!("{name}: empty field name (use `__` only between names)");
+    }
+    let (field, sections) = path.split_last().context("empty override path")?;
+
+    let candidates = match env_override_table(root, name, sections)?.get(field) {
+        Some(current) => vec![coerce_env_value(name, raw, field, current)?],
+        // Unset optional field: try the value as a TOML literal, then as text
+        None => toml::from_str::<toml::Table>(&format!("v = {raw}"))
+            .ok()
+            .and_then(|mut t| t.remove("v"))
+            .filter(|v| !v.is_str())
+            .into_iter()
+            .chain([toml::Value::String(raw.to_string())])
+            .collect(),
+    };
+
+    let mut last_err = None;
+    for value in candidates {
+        env_override_table(root, name, sections)?.insert(field.clone(), value);
+        match root.clone().try_into::<Config>() {
+            Ok(_) => return Ok(()),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    match last_err {
+        Some(e) => anyhow::bail!("{name}: {}", e.message()),
+        None => Ok(()),
+    }
+}
+
+/// The table holding the overridden field, creating missing sections.
+fn env_override_table<'a>(
+    root: &'a mut toml::Value,
+    name: &str,
+    sections: &[String],
+) -> Result<&'a mut toml::Table> {
+    let mut table = root
+        .as_table_mut()
+        .context("Config did not serialize to a table")?;
+    for section in sections {
+        let entry = table
+            .entry(section.clone())
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        table = match entry {
+            toml::Value::Table(inner) => inner,
+            _ => anyhow::bail!("{name}: `{section}` is a value, not a section"),
+        };
+    }
+    Ok(table)
+}
+
+/// Parse `raw` as the same type as the field's current value.
+fn coerce_env_value(
+    name: &str,
+    raw: &str,
+    field: &str,
+    current: &toml::Value,
+) -> Result<toml::Value> {
+    let value = match current {
+        toml::Value::String(_) => toml::Value::String(raw.to_string()),
+        toml::Value::Boolean(_) => match raw.trim().to_ascii_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => toml::Value::Boolean(true),
+            "false" | "0" | "no" | "off" => toml::Value::Boolean(false),
+            _ => anyhow::bail!("{name}: expected true or false, got {raw:?}"),
+        },
+        toml::Value::Integer(_) => toml::Value::Integer(
+            raw.trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("{name}: expected an integer, got {raw:?}"))?,
+        ),
+        toml::Value::Float(_) => toml::Value::Float(
+            raw.trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("{name}: expected a number, got {raw:?}"))?,
+        ),
+        // `[1, 2]` is parsed as TOML; anything else is a comma-separated list
+        toml::Value::Array(_) if raw.trim_start().starts_with('[') => {
+            toml::from_str::<toml::Table>(&format!("v = {raw}"))
+                .ok()
+                .and_then(|mut t| t.remove("v"))
+                .with_context(|| format!("{name}: invalid list {raw:?}"))?
+        }
+        toml::Value::Array(_) => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| toml::Value::String(item.to_string()))
+                .collect(),
+        ),
+        toml::Value::Table(_) => {
+            anyhow::bail!("{name}: `{field}` is a section; set its fields with {name}__<FIELD>")
+        }
+        toml::Value::Datetime(_) => anyhow::bail!("{name}: date fields can't be overridden"),
+    };
+    Ok(value)
+}
+
 impl Config {
+    /// The persona called `name` in `[identities]`, or `[identity]` for
+    /// `None`. An unknown name logs a warning and falls back to `[identity]`.
+    pub fn identity_named(&self, name: Option<&str>) -> &IdentityConfig {
+        let Some(name) = name.map(str::trim).filter(|n| !n.is_empty()) else {
+            return &self.identity;
+        };
+        self.identities.get(name).unwrap_or_else(|| {
+            tracing::warn!(
+                identity = name,
+                "Unknown identity, using the default [identity]"
+            );
+            &self.identity
+        })
+    }
+
+    /// The identity selected by `active_identity`.
+    pub fn active_identity_config(&self) -> &IdentityConfig {
+        self.identity_named(self.active_identity.as_deref())
+    }
+
     pub fn load_or_init() -> Result<Self> {
         let home = UserDirs::new()
             .map(|u| u.home_dir().to_path_buf())
@@ -924,6 +1777,51 @@ impl Config {
                 }
             }
         }
+
+        // Memory encryption passphrase: ZEROCLAW_MEMORY_PASSPHRASE
+        if let Ok(passphrase) = std::env::var("ZEROCLAW_MEMORY_PASSPHRASE") {
+            if !passphrase.is_empty() {
+                self.memory.encryption_passphrase = Some(passphrase);
+            }
+        }
+    }
+
+    /// Override any config field from `ZEROCLAW_<SECTION>__<FIELD>` variables,
+    /// e.g. `ZEROCLAW_MEMORY__BACKEND=sqlite` or `ZEROCLAW_DEFAULT_MODEL=gpt-4o`.
+    ///
+    /// `__` separates nesting levels. Values are coerced to the type the field
+    /// already has (bool, integer, float, string, or a comma-separated list);
+    /// a value that doesn't fit is an error naming the variable. Variables that
+    /// don't name a config field (`ZEROCLAW_WORKSPACE`, ...) are ignored.
+    pub fn apply_layered_env_overrides(&mut self) -> Result<()> {
+        self.apply_layered_overrides(std::env::vars())
+    }
+
+    fn apply_layered_overrides(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<()> {
+        let mut overrides: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, value)| name.starts_with(ENV_PREFIX) && !value.is_empty())
+            .collect();
+        if overrides.is_empty() {
+            return Ok(());
+        }
+        overrides.sort();
+
+        let mut root = toml::Value::try_from(&*self).context("Failed to serialize config")?;
+        for (name, raw) in &overrides {
+            apply_env_override(&mut root, name, raw)?;
+        }
+
+        let mut config: Config = root
+            .try_into()
+            .context("Environment overrides produced an invalid config")?;
+        config.workspace_dir = std::mem::take(&mut self.workspace_dir);
+        config.config_path = std::mem::take(&mut self.config_path);
+        *self = config;
+        Ok(())
     }
 
     pub fn save(&self) -> Result<()> {
@@ -1097,6 +1995,7 @@ mod tests {
             default_temperature: 0.5,
             observability: ObservabilityConfig {
                 backend: "log".into(),
+                ..ObservabilityConfig::default()
             },
             autonomy: AutonomyConfig {
                 level: AutonomyLevel::Full,
@@ -1134,14 +2033,37 @@ mod tests {
                 whatsapp: None,
                 email: None,
                 irc: None,
+                http: None,
             },
             memory: MemoryConfig::default(),
             tunnel: TunnelConfig::default(),
             gateway: GatewayConfig::default(),
             composio: ComposioConfig::default(),
             secrets: SecretsConfig::default(),
+            security: SecurityConfig {
+                allowed_commands: Some(vec!["git".into()]),
+                denied_commands: vec!["rm".into()],
+                extra_allowed_dirs: vec!["/srv/shared".into()],
+                allowed_hosts: vec!["api.github.com".into()],
+                denied_hosts: vec!["*.internal".into()],
+                audit_log: Some("~/.zeroclaw/audit.jsonl".into()),
+                audit_redact_fields: vec!["password".into()],
+                ..SecurityConfig::default()
+            },
             browser: BrowserConfig::default(),
+            tools: ToolsConfig::default(),
+            search: SearchConfig::default(),
+            agent: AgentConfig::default(),
+            skills: SkillsConfig::default(),
+            prompts: HashMap::new(),
             identity: IdentityConfig::default(),
+            identities: HashMap::new(),
+            active_identity: None,
+            bedrock: BedrockConfig::default(),
+            openrouter: OpenRouterConfig::default(),
+            ollama: OllamaConfig::default(),
+            openai_compat: OpenAiCompatConfig::default(),
+            mock: MockProviderConfig::default(),
         };
 
         let toml_str = toml::to_string_pretty(&config).unwrap();
@@ -1154,6 +2076,11 @@ mod tests {
         assert_eq!(parsed.observability.backend, "log");
         assert_eq!(parsed.autonomy.level, AutonomyLevel::Full);
         assert!(!parsed.autonomy.workspace_only);
+        assert_eq!(parsed.security.denied_commands, ["rm"]);
+        assert_eq!(
+            parsed.security.audit_log.as_deref(),
+            Some("~/.zeroclaw/audit.jsonl")
+        );
         assert_eq!(parsed.runtime.kind, "docker");
         assert!(parsed.heartbeat.enabled);
         assert_eq!(parsed.heartbeat.interval_minutes, 15);
@@ -1185,6 +2112,166 @@ default_temperature = 0.7
         assert_eq!(parsed.memory.conversation_retention_days, 30);
     }
 
+    #[test]
+    fn config_tools_timeout_defaults_and_parses() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.tools.timeout_secs, 60);
+        assert_eq!(parsed.tools.max_retries, 2);
+        assert_eq!(parsed.tools.retry_backoff_ms, 500);
+
+        let parsed: Config = toml::from_str(&format!(
+            "{toml_str}\n[tools]\ntimeout_secs = 5\nmax_retries = 0\n"
+        ))
+        .unwrap();
+        assert_eq!(parsed.tools.timeout_secs, 5);
+        assert_eq!(parsed.tools.max_retries, 0);
+    }
+
+    #[test]
+    fn config_memory_ttl_parses_with_category_overrides() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[memory]
+backend = "sqlite"
+auto_save = true
+
+[memory.ttl]
+default_days = 90
+categories = { conversation = 7, core = 0 }
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        let ttl = &parsed.memory.ttl;
+        assert_eq!(ttl.days_for("conversation"), Some(7));
+        assert_eq!(ttl.days_for("daily"), Some(90));
+        assert_eq!(ttl.days_for("core"), None);
+        assert_eq!(MemoryTtlConfig::default().days_for("daily"), None);
+    }
+
+    #[test]
+    fn config_agent_context_budget_parses() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[agent]
+context_budget = 8000
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.agent.context_budget, 8000);
+        assert_eq!(AgentConfig::default().context_budget, 64_000);
+        assert!(parsed.agent.max_total_tokens.is_none());
+
+        let parsed: Config =
+            toml::from_str(&format!("{toml_str}max_total_tokens = 50000\n")).unwrap();
+        assert_eq!(parsed.agent.max_total_tokens, Some(50_000));
+    }
+
+    #[test]
+    fn config_bedrock_section_parses() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[bedrock]
+region = "eu-central-1"
+profile = "work"
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.bedrock.region.as_deref(), Some("eu-central-1"));
+        assert_eq!(parsed.bedrock.profile.as_deref(), Some("work"));
+        assert!(parsed.bedrock.access_key_id.is_none());
+        assert_eq!(parsed.bedrock.max_tokens, 4096);
+        assert!(parsed.bedrock.top_p.is_none());
+        assert!(parsed.bedrock.stop_sequences.is_empty());
+        assert!(parsed.bedrock.instance_metadata);
+        assert!(parsed.bedrock.assume_role_arn.is_none());
+    }
+
+    #[test]
+    fn config_bedrock_inference_params_parse() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[bedrock]
+max_tokens = 16000
+top_p = 0.9
+stop_sequences = ["</answer>"]
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.bedrock.max_tokens, 16000);
+        assert_eq!(parsed.bedrock.top_p, Some(0.9));
+        assert_eq!(parsed.bedrock.stop_sequences, vec!["</answer>".to_string()]);
+    }
+
+    #[test]
+    fn named_identities_parse_and_resolve() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+active_identity = "pirate"
+
+[identity]
+system_prompt_prepend = "Be formal."
+
+[identities.pirate]
+system_prompt_prepend = "Talk like a pirate."
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(parsed.identities.len(), 1);
+        assert_eq!(
+            parsed
+                .active_identity_config()
+                .system_prompt_prepend
+                .as_deref(),
+            Some("Talk like a pirate.")
+        );
+        assert_eq!(
+            parsed.identity_named(None).system_prompt_prepend.as_deref(),
+            Some("Be formal.")
+        );
+        // Unknown names fall back to [identity] instead of failing
+        assert_eq!(
+            parsed
+                .identity_named(Some("ghost"))
+                .system_prompt_prepend
+                .as_deref(),
+            Some("Be formal.")
+        );
+    }
+
+    #[test]
+    fn config_openrouter_attribution_parses() {
+        let toml_str = r#"
+workspace_dir = "/tmp/ws"
+config_path = "/tmp/config.toml"
+default_temperature = 0.7
+
+[openrouter]
+app_url = "https://example.com/bot"
+app_name = "Bot"
+"#;
+        let parsed: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            parsed.openrouter.app_url.as_deref(),
+            Some("https://example.com/bot")
+        );
+        assert_eq!(parsed.openrouter.app_name.as_deref(), Some("Bot"));
+        assert!(Config::default().openrouter.app_url.is_none());
+    }
+
     #[test]
     fn config_save_and_load_tmpdir() {
         let dir = std::env::temp_dir().join("zeroclaw_test_config");
@@ -1211,8 +2298,21 @@ default_temperature = 0.7
             gateway: GatewayConfig::default(),
             composio: ComposioConfig::default(),
             secrets: SecretsConfig::default(),
+            security: SecurityConfig::default(),
             browser: BrowserConfig::default(),
+            tools: ToolsConfig::default(),
+            search: SearchConfig::default(),
+            agent: AgentConfig::default(),
+            skills: SkillsConfig::default(),
+            prompts: HashMap::new(),
             identity: IdentityConfig::default(),
+            identities: HashMap::new(),
+            active_identity: None,
+            bedrock: BedrockConfig::default(),
+            openrouter: OpenRouterConfig::default(),
+            ollama: OllamaConfig::default(),
+            openai_compat: OpenAiCompatConfig::default(),
+            mock: MockProviderConfig::default(),
         };
 
         config.save().unwrap();
@@ -1279,6 +2379,7 @@ default_temperature = 0.7
             bot_token: "discord-token".into(),
             guild_id: Some("12345".into()),
             allowed_users: vec![],
+            channel_ids: vec![],
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
@@ -1292,12 +2393,19 @@ default_temperature = 0.7
             bot_token: "tok".into(),
             guild_id: None,
             allowed_users: vec![],
+            channel_ids: vec![],
         };
         let json = serde_json::to_string(&dc).unwrap();
         let parsed: DiscordConfig = serde_json::from_str(&json).unwrap();
         assert!(parsed.guild_id.is_none());
     }
 
+    #[test]
+    fn discord_config_channel_ids_default_empty() {
+        let parsed: DiscordConfig = toml::from_str(r#"bot_token = "tok""#).unwrap();
+        assert!(parsed.channel_ids.is_empty());
+    }
+
     // ── iMessage / Matrix config ────────────────────────────
 
     #[test]
@@ -1381,6 +2489,7 @@ default_temperature = 0.7
             whatsapp: None,
             email: None,
             irc: None,
+            http: None,
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1539,6 +2648,7 @@ channel_id = "C123"
             }),
             email: None,
             irc: None,
+            http: None,
         };
         let toml_str = toml::to_string_pretty(&c).unwrap();
         let parsed: ChannelsConfig = toml::from_str(&toml_str).unwrap();
@@ -1928,6 +3038,21 @@ default_temperature = 0.7
         std::env::remove_var("ZEROCLAW_TEMPERATURE");
     }
 
+    #[test]
+    fn env_override_memory_passphrase() {
+        let mut config = Config::default();
+        assert!(config.memory.encryption_passphrase.is_none());
+
+        std::env::set_var("ZEROCLAW_MEMORY_PASSPHRASE", "correct horse");
+        config.apply_env_overrides();
+        assert_eq!(
+            config.memory.encryption_passphrase.as_deref(),
+            Some("correct horse")
+        );
+
+        std::env::remove_var("ZEROCLAW_MEMORY_PASSPHRASE");
+    }
+
     #[test]
     fn env_override_temperature_out_of_range_ignored() {
         // Clean up any leftover env vars from other tests
@@ -1959,6 +3084,99 @@ default_temperature = 0.7
         std::env::remove_var("PORT");
     }
 
+    // ── Layered `ZEROCLAW_<SECTION>__<FIELD>` overrides ──────────
+
+    fn layered(vars: &[(&str, &str)]) -> Result<Config> {
+        let base = r#"
+default_temperature = 0.7
+
+[memory]
+backend = "markdown"
+auto_save = true
+"#;
+        let mut config: Config = toml::from_str(base).unwrap();
+        config.workspace_dir = PathBuf::from("/tmp/ws");
+        config.apply_layered_overrides(
+            vars.iter()
+                .map(|(k, v)| ((*k).to_string(), (*v).to_string())),
+        )?;
+        Ok(config)
+    }
+
+    #[test]
+    fn layered_env_overrides_set_nested_fields() {
+        let key = "ZEROCLAW_AGENT__CONTEXT_BUDGET";
+        std::env::set_var(key, "12000");
+        std::env::set_var("ZEROCLAW_MEMORY__BACKEND", "sqlite");
+        let mut config: Config = toml::from_str("default_temperature = 0.7").unwrap();
+        config.apply_layered_env_overrides().unwrap();
+        std::env::remove_var(key);
+        std::env::remove_var("ZEROCLAW_MEMORY__BACKEND");
+
+        assert_eq!(config.agent.context_budget, 12_000);
+        assert_eq!(config.memory.backend, "sqlite");
+    }
+
+    #[test]
+    fn layered_env_overrides_coerce_to_field_types() {
+        let config = layered(&[
+            ("ZEROCLAW_MEMORY__AUTO_SAVE", "false"),
+            ("ZEROCLAW_DEFAULT_TEMPERATURE", "0.2"),
+            ("ZEROCLAW_GATEWAY__PORT", "8080"),
+            ("ZEROCLAW_BROWSER__ALLOWED_DOMAINS", "docs.rs, github.com"),
+            ("ZEROCLAW_AGENT__MAX_TOTAL_TOKENS", "5000"),
+            ("ZEROCLAW_DEFAULT_MODEL", "gpt-4o"),
+            ("ZEROCLAW_API_KEY", "12345"),
+        ])
+        .unwrap();
+
+        assert!(!config.memory.auto_save);
+        assert_eq!(config.memory.backend, "markdown", "siblings untouched");
+        assert!((config.default_temperature - 0.2).abs() < f64::EPSILON);
+        assert_eq!(config.gateway.port, 8080);
+        assert_eq!(config.browser.allowed_domains, ["docs.rs", "github.com"]);
+        assert_eq!(config.agent.max_total_tokens, Some(5000));
+        assert_eq!(config.default_model.as_deref(), Some("gpt-4o"));
+        assert_eq!(config.api_key.as_deref(), Some("12345"));
+        assert_eq!(config.workspace_dir, PathBuf::from("/tmp/ws"));
+    }
+
+    #[test]
+    fn layered_env_overrides_reject_type_mismatches() {
+        let err = layered(&[("ZEROCLAW_MEMORY__AUTO_SAVE", "maybe")]).unwrap_err();
+        assert!(
+            err.to_string().contains("ZEROCLAW_MEMORY__AUTO_SAVE"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("true or false"), "{err}");
+
+        let err = layered(&[("ZEROCLAW_GATEWAY__PORT", "http")]).unwrap_err();
+        assert!(err.to_string().contains("expected an integer"), "{err}");
+
+        let err = layered(&[("ZEROCLAW_MEMORY", "sqlite")]).unwrap_err();
+        assert!(err.to_string().contains("is a section"), "{err}");
+
+        let err = layered(&[("ZEROCLAW_AGENT__MAX_TOTAL_TOKENS", "lots")]).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("ZEROCLAW_AGENT__MAX_TOTAL_TOKENS"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn layered_env_overrides_ignore_unrelated_vars() {
+        let config = layered(&[
+            ("ZEROCLAW_WORKSPACE", "/elsewhere"),
+            ("ZEROCLAW_AUTOSTART_CHANNELS", "1"),
+            ("ZEROCLAW_MEMORY__BACKEND", ""),
+            ("HOME", "/root"),
+        ])
+        .unwrap();
+        assert_eq!(config.memory.backend, "markdown");
+        assert_eq!(config.workspace_dir, PathBuf::from("/tmp/ws"));
+    }
+
     #[test]
     fn gateway_config_default_values() {
         let g = GatewayConfig::default();
@@ -0,0 +1,469 @@
+use super::schema::Config;
+use std::fmt;
+
+const MEMORY_BACKENDS: &[&str] = &["sqlite", "vector", "markdown", "none"];
+const OBSERVABILITY_BACKENDS: &[&str] = &["none", "noop", "log", "prometheus", "otel"];
+const RUNTIME_KINDS: &[&str] = &["native", "docker", "ssh"];
+const SEARCH_PROVIDERS: &[&str] = &["brave", "serpapi", "tavily"];
+const TUNNEL_PROVIDERS: &[&str] = &["none", "", "cloudflare", "tailscale", "ngrok", "custom"];
+
+/// One problem found by [`Config::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigError {
+    /// Dotted path of the offending field, e.g. `memory.embedding_provider`
+    pub field: String,
+    /// What is wrong with it
+    pub message: String,
+    /// How to fix it
+    pub hint: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (fix: {})", self.field, self.message, self.hint)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Render every problem on its own line under one heading.
+pub fn format_errors(errors: &[ConfigError]) -> String {
+    let mut out = format!(
+        "Invalid configuration ({} problem{}):",
+        errors.len(),
+        if errors.len() == 1 { "" } else { "s" }
+    );
+    for error in errors {
+        out.push_str("\n  - ");
+        out.push_str(&error.to_string());
+    }
+    out
+}
+
+#[derive(Default)]
+struct Problems(Vec<ConfigError>);
+
+impl Problems {
+    fn push(&mut self, field: &str, message: impl Into<String>, hint: impl Into<String>) {
+        self.0.push(ConfigError {
+            field: field.to_string(),
+            message: message.into(),
+            hint: hint.into(),
+        });
+    }
+
+    fn one_of(&mut self, field: &str, value: &str, allowed: &[&str]) {
+        if !allowed.contains(&value) {
+            let expected: Vec<&str> = allowed.iter().copied().filter(|v| !v.is_empty()).collect();
+            self.push(
+                field,
+                format!("unknown value \"{value}\""),
+                format!("use one of: {}", expected.join(", ")),
+            );
+        }
+    }
+}
+
+fn is_blank(value: Option<&String>) -> bool {
+    value.is_none_or(|v| v.trim().is_empty())
+}
+
+impl Config {
+    /// Check for invalid values and combinations that would otherwise only
+    /// fail deep inside a subsystem. Every problem is collected, so one run
+    /// reports them all.
+    pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+        let mut problems = Problems::default();
+
+        if !(0.0..=2.0).contains(&self.default_temperature) {
+            problems.push(
+                "default_temperature",
+                format!("{} is out of range", self.default_temperature),
+                "use a value between 0.0 and 2.0",
+            );
+        }
+
+        self.validate_agent(&mut problems);
+        self.validate_memory(&mut problems);
+        self.validate_observability(&mut problems);
+
+        problems.one_of("runtime.kind", &self.runtime.kind, RUNTIME_KINDS);
+        if self.runtime.kind == "ssh" && self.runtime.ssh.host.trim().is_empty() {
+            problems.push(
+                "runtime.ssh.host",
+                "runtime.kind = \"ssh\" but no host is set",
+                "set runtime.ssh.host to the remote host name or address",
+            );
+        }
+
+        if self.composio.enabled && is_blank(self.composio.api_key.as_ref()) {
+            problems.push(
+                "composio.api_key",
+                "composio.enabled = true but no API key is set",
+                "set composio.api_key, or set composio.enabled = false",
+            );
+        }
+
+        if let Some(provider) = self.search.provider.as_deref().map(str::trim) {
+            if !provider.is_empty() {
+                problems.one_of(
+                    "search.provider",
+                    &provider.to_ascii_lowercase(),
+                    SEARCH_PROVIDERS,
+                );
+                if is_blank(self.search.api_key.as_ref()) {
+                    problems.push(
+                        "search.api_key",
+                        format!("search.provider = \"{provider}\" but no API key is set"),
+                        "set search.api_key, or remove search.provider",
+                    );
+                }
+            }
+        }
+
+        self.validate_tunnel(&mut problems);
+
+        self.validate_providers(&mut problems);
+
+        problems.one_of(
+            "identity.format",
+            &self.identity.format,
+            &["openclaw", "aieos"],
+        );
+        for (name, identity) in &self.identities {
+            problems.one_of(
+                &format!("identities.{name}.format"),
+                &identity.format,
+                &["openclaw", "aieos"],
+            );
+        }
+
+        if self.reliability.channel_initial_backoff_secs > self.reliability.channel_max_backoff_secs
+        {
+            problems.push(
+                "reliability.channel_initial_backoff_secs",
+                "is larger than reliability.channel_max_backoff_secs",
+                "lower the initial backoff or raise the maximum",
+            );
+        }
+
+        if self.tools.timeout_secs == 0 {
+            problems.push(
+                "tools.timeout_secs",
+                "0 would time out every tool call",
+                "use a positive number of seconds",
+            );
+        }
+
+        for (index, pattern) in self.security.redaction_patterns.iter().enumerate() {
+            if let Err(e) = regex_automata::meta::Regex::new(pattern) {
+                problems.push(
+                    &format!("security.redaction_patterns[{index}]"),
+                    format!("invalid regular expression \"{pattern}\": {e}"),
+                    "fix or remove the pattern",
+                );
+            }
+        }
+        for (index, pattern) in self.security.output_filters.iter().enumerate() {
+            if let Err(e) = regex_automata::meta::Regex::new(pattern) {
+                problems.push(
+                    &format!("security.output_filters[{index}]"),
+                    format!("invalid regular expression \"{pattern}\": {e}"),
+                    "fix or remove the pattern",
+                );
+            }
+        }
+
+        if problems.0.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.0)
+        }
+    }
+
+    fn validate_agent(&self, problems: &mut Problems) {
+        let agent = &self.agent;
+        for (field, temperature) in [
+            ("agent.tool_phase_temperature", agent.tool_phase_temperature),
+            (
+                "agent.final_phase_temperature",
+                agent.final_phase_temperature,
+            ),
+        ] {
+            if let Some(t) = temperature.filter(|t| !(0.0..=2.0).contains(t)) {
+                problems.push(
+                    field,
+                    format!("{t} is out of range"),
+                    "use a value between 0.0 and 2.0, or remove it to use default_temperature",
+                );
+            }
+        }
+    }
+
+    fn validate_memory(&self, problems: &mut Problems) {
+        let memory = &self.memory;
+        problems.one_of("memory.backend", &memory.backend, MEMORY_BACKENDS);
+
+        let embedder = memory.embedding_provider.trim();
+        let known_embedder = matches!(embedder, "none" | "" | "openai")
+            || embedder.starts_with("custom:")
+            || embedder.starts_with("provider:");
+        if !known_embedder {
+            problems.push(
+                "memory.embedding_provider",
+                format!("unknown embedding provider \"{embedder}\""),
+                "use \"none\", \"openai\", \"custom:URL\" or \"provider:NAME\"",
+            );
+        } else if memory.backend == "vector" && matches!(embedder, "none" | "") {
+            problems.push(
+                "memory.embedding_provider",
+                "memory.backend = \"vector\" needs an embedding provider, but none is set",
+                "set memory.embedding_provider (e.g. \"openai\"), or use memory.backend = \"sqlite\"",
+            );
+        }
+
+        for (field, weight) in [
+            ("memory.vector_weight", memory.vector_weight),
+            ("memory.keyword_weight", memory.keyword_weight),
+        ] {
+            if !(0.0..=1.0).contains(&weight) {
+                problems.push(
+                    field,
+                    format!("{weight} is out of range"),
+                    "use a value between 0.0 and 1.0",
+                );
+            }
+        }
+
+        if memory.encryption_key.is_some() && memory.encryption_passphrase.is_some() {
+            problems.push(
+                "memory.encryption_key",
+                "both encryption_key and encryption_passphrase are set",
+                "keep only one of them",
+            );
+        }
+        if let Some(key) = &memory.encryption_key {
+            let key = key.trim();
+            if key.len() != 64 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+                problems.push(
+                    "memory.encryption_key",
+                    "is not a 256-bit hex key",
+                    "use 64 hex characters (e.g. from `openssl rand -hex 32`)",
+                );
+            }
+        }
+    }
+
+    fn validate_observability(&self, problems: &mut Problems) {
+        let observability = &self.observability;
+        let backends: Vec<&str> = if observability.backends.is_empty() {
+            vec![observability.backend.as_str()]
+        } else {
+            observability.backends.iter().map(String::as_str).collect()
+        };
+        let field = if observability.backends.is_empty() {
+            "observability.backend"
+        } else {
+            "observability.backends"
+        };
+        for backend in &backends {
+            problems.one_of(field, backend, OBSERVABILITY_BACKENDS);
+        }
+
+        problems.one_of(
+            "observability.format",
+            &observability.format,
+            &["text", "json"],
+        );
+        if backends.contains(&"prometheus")
+            && observability
+                .metrics_addr
+                .parse::<std::net::SocketAddr>()
+                .is_err()
+        {
+            problems.push(
+                "observability.metrics_addr",
+                format!("\"{}\" is not a socket address", observability.metrics_addr),
+                "use host:port, e.g. \"127.0.0.1:9464\"",
+            );
+        }
+    }
+
+    fn validate_providers(&self, problems: &mut Problems) {
+        for (i, route) in self.model_routes.iter().enumerate() {
+            for (name, value) in [
+                ("hint", &route.hint),
+                ("provider", &route.provider),
+                ("model", &route.model),
+            ] {
+                if value.trim().is_empty() {
+                    problems.push(
+                        &format!("model_routes[{i}].{name}"),
+                        "is empty",
+                        "every [[model_routes]] entry needs hint, provider and model",
+                    );
+                }
+            }
+        }
+
+        if self.bedrock.guardrail_version.is_some() && self.bedrock.guardrail_id.is_none() {
+            problems.push(
+                "bedrock.guardrail_version",
+                "is set without bedrock.guardrail_id",
+                "set guardrail_id to the guardrail to apply, or remove guardrail_version",
+            );
+        }
+
+        if let Some(url) = &self.ollama.base_url {
+            if reqwest::Url::parse(url).is_err() {
+                problems.push(
+                    "ollama.base_url",
+                    format!("\"{url}\" is not a URL"),
+                    "use the server address, e.g. \"http://localhost:11434\"",
+                );
+            }
+        }
+    }
+
+    fn validate_tunnel(&self, problems: &mut Problems) {
+        let tunnel = &self.tunnel;
+        problems.one_of("tunnel.provider", &tunnel.provider, TUNNEL_PROVIDERS);
+        let missing = match tunnel.provider.as_str() {
+            "cloudflare" => tunnel.cloudflare.is_none(),
+            "ngrok" => tunnel.ngrok.is_none(),
+            "custom" => tunnel.custom.is_none(),
+            _ => false,
+        };
+        if missing {
+            let provider = &tunnel.provider;
+            problems.push(
+                &format!("tunnel.{provider}"),
+                format!("tunnel.provider = \"{provider}\" but [tunnel.{provider}] is missing"),
+                format!("add a [tunnel.{provider}] section, or set tunnel.provider = \"none\""),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::schema::CloudflareTunnelConfig;
+    use crate::config::ModelRouteConfig;
+
+    fn fields(config: &Config) -> Vec<String> {
+        config
+            .validate()
+            .unwrap_err()
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(Config::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn vector_memory_without_embedder_is_reported() {
+        let mut config = Config::default();
+        config.memory.backend = "vector".into();
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "memory.embedding_provider");
+        assert!(errors[0].hint.contains("openai"), "{}", errors[0]);
+
+        config.memory.embedding_provider = "openai".into();
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn composio_without_key_is_reported() {
+        let mut config = Config::default();
+        config.composio.enabled = true;
+        assert_eq!(fields(&config), ["composio.api_key"]);
+
+        config.composio.api_key = Some("ck-123".into());
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn all_problems_are_reported_together() {
+        let mut config = Config::default();
+        config.default_temperature = 3.5;
+        config.memory.backend = "postgres".into();
+        config.runtime.kind = "ssh".into();
+        config.search.provider = Some("bing".into());
+        config.tunnel.provider = "cloudflare".into();
+        config.model_routes.push(ModelRouteConfig {
+            hint: "fast".into(),
+            provider: String::new(),
+            model: "m".into(),
+            api_key: None,
+        });
+        config.ollama.base_url = Some("not a url".into());
+
+        assert_eq!(
+            fields(&config),
+            [
+                "default_temperature",
+                "memory.backend",
+                "runtime.ssh.host",
+                "search.provider",
+                "search.api_key",
+                "tunnel.cloudflare",
+                "model_routes[0].provider",
+                "ollama.base_url",
+            ]
+        );
+
+        config.tunnel.cloudflare = Some(CloudflareTunnelConfig { token: "t".into() });
+        assert!(!fields(&config).contains(&"tunnel.cloudflare".to_string()));
+    }
+
+    #[test]
+    fn observability_and_memory_values_are_checked() {
+        let mut config = Config::default();
+        config.observability.backends = vec!["log".into(), "datadog".into()];
+        config.observability.format = "xml".into();
+        config.memory.vector_weight = 1.5;
+        config.memory.encryption_key = Some("abc".into());
+        assert_eq!(
+            fields(&config),
+            [
+                "memory.vector_weight",
+                "memory.encryption_key",
+                "observability.backends",
+                "observability.format",
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_render_with_field_and_hint() {
+        let mut config = Config::default();
+        config.runtime.kind = "wasm".into();
+        config.tools.timeout_secs = 0;
+        let rendered = format_errors(&config.validate().unwrap_err());
+        assert!(
+            rendered.starts_with("Invalid configuration (2 problems):"),
+            "{rendered}"
+        );
+        assert!(rendered.contains(
+            "\n  - runtime.kind: unknown value \"wasm\" (fix: use one of: native, docker, ssh)"
+        ));
+        assert!(rendered.contains("\n  - tools.timeout_secs:"));
+    }
+
+    #[test]
+    fn invalid_redaction_pattern_is_reported() {
+        let mut config = Config::default();
+        config.security.redaction_patterns = vec!["internal-[0-9]{4}".into(), "(unclosed".into()];
+        assert_eq!(fields(&config), ["security.redaction_patterns[1]"]);
+
+        let mut config = Config::default();
+        config.security.output_filters = vec!["[".into()];
+        assert_eq!(fields(&config), ["security.output_filters[0]"]);
+    }
+}
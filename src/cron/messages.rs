@@ -0,0 +1,465 @@
+use super::{next_run_for, parse_rfc3339, with_workspace_connection};
+use crate::channels::traits::ChannelMessage;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+use std::fmt::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// How often the dispatcher checks for due messages.
+pub const DISPATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest accepted delay.
+const MAX_DELAY: Duration = Duration::from_hours(366 * 24);
+
+/// Prefix on delivered messages so the agent knows nobody just typed them.
+const DELIVERY_PREFIX: &str = "[Scheduled message]";
+
+/// When a scheduled message fires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSchedule {
+    /// Once, this long after it was scheduled
+    Delay(Duration),
+    /// Every time a cron expression (5, 6 or 7 fields) matches
+    Cron(String),
+}
+
+impl MessageSchedule {
+    /// Build from the tool arguments: exactly one of `delay` (e.g. `"1h30m"`)
+    /// or `cron` (e.g. `"0 9 * * *"`).
+    pub fn parse(delay: Option<&str>, cron: Option<&str>) -> Result<Self> {
+        match (delay, cron) {
+            (Some(delay), None) => Ok(Self::Delay(parse_delay(delay)?)),
+            (None, Some(expression)) => {
+                let expression = expression.trim().to_string();
+                next_run_for(&expression, Utc::now())?;
+                Ok(Self::Cron(expression))
+            }
+            (Some(_), Some(_)) => anyhow::bail!("Pass either 'delay' or 'cron', not both"),
+            (None, None) => {
+                anyhow::bail!("Pass 'delay' (e.g. \"1h\") or 'cron' (e.g. \"0 9 * * *\")")
+            }
+        }
+    }
+
+    /// The next time the message is due after `from`.
+    pub fn next_run(&self, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
+        match self {
+            Self::Delay(delay) => Ok(from + chrono::Duration::from_std(*delay)?),
+            Self::Cron(expression) => next_run_for(expression, from),
+        }
+    }
+}
+
+impl fmt::Display for MessageSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Delay(delay) => write!(f, "once, after {}", format_delay(*delay)),
+            Self::Cron(expression) => write!(f, "cron '{expression}'"),
+        }
+    }
+}
+
+/// Parse a delay such as `"90s"`, `"10m"`, `"1h30m"` or `"2d"`.
+pub fn parse_delay(raw: &str) -> Result<Duration> {
+    let raw = raw.trim();
+    let invalid = || anyhow::anyhow!("Invalid delay '{raw}': use e.g. 30s, 10m, 1h30m or 2d");
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        total = amount
+            .checked_mul(unit)
+            .and_then(|secs| total.checked_add(secs))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+    if !digits.is_empty() || total == 0 {
+        return Err(invalid());
+    }
+    let delay = Duration::from_secs(total);
+    anyhow::ensure!(
+        delay <= MAX_DELAY,
+        "Delay '{raw}' is too long (max {})",
+        format_delay(MAX_DELAY)
+    );
+    Ok(delay)
+}
+
+fn format_delay(delay: Duration) -> String {
+    let mut secs = delay.as_secs();
+    let mut out = String::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        if secs >= size {
+            let _ = write!(out, "{}{unit}", secs / size);
+            secs %= size;
+        }
+    }
+    out
+}
+
+/// A message the agent scheduled for itself, delivered back through the
+/// channel it came from.
+#[derive(Debug, Clone)]
+pub struct ScheduledMessage {
+    pub id: String,
+    pub schedule: MessageSchedule,
+    pub message: String,
+    pub channel: String,
+    pub recipient: String,
+    pub next_run: DateTime<Utc>,
+}
+
+impl ScheduledMessage {
+    /// The message as it is fed back to the agent.
+    pub fn to_channel_message(&self, now: DateTime<Utc>) -> ChannelMessage {
+        ChannelMessage {
+            id: format!("schedule-{}-{}", self.id, now.timestamp()),
+            sender: self.recipient.clone(),
+            content: format!("{DELIVERY_PREFIX} {}", self.message),
+            channel: self.channel.clone(),
+            timestamp: u64::try_from(now.timestamp()).unwrap_or_default(),
+        }
+    }
+}
+
+/// Persist a new scheduled message in `workspace/cron/jobs.db`.
+pub fn add_message(
+    workspace_dir: &Path,
+    schedule: MessageSchedule,
+    message: &str,
+    channel: &str,
+    recipient: &str,
+) -> Result<ScheduledMessage> {
+    let now = Utc::now();
+    let next_run = schedule.next_run(now)?;
+    let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    let (delay_secs, expression) = match &schedule {
+        MessageSchedule::Delay(delay) => (Some(i64::try_from(delay.as_secs())?), None),
+        MessageSchedule::Cron(expression) => (None, Some(expression.as_str())),
+    };
+
+    with_workspace_connection(workspace_dir, |conn| {
+        conn.execute(
+            "INSERT INTO scheduled_messages
+             (id, delay_secs, expression, message, channel, recipient, created_at, next_run)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                id,
+                delay_secs,
+                expression,
+                message,
+                channel,
+                recipient,
+                now.to_rfc3339(),
+                next_run.to_rfc3339()
+            ],
+        )
+        .context("Failed to insert scheduled message")?;
+        Ok(())
+    })?;
+
+    Ok(ScheduledMessage {
+        id,
+        schedule,
+        message: message.to_string(),
+        channel: channel.to_string(),
+        recipient: recipient.to_string(),
+        next_run,
+    })
+}
+
+/// All scheduled messages, soonest first.
+pub fn list_messages(workspace_dir: &Path) -> Result<Vec<ScheduledMessage>> {
+    with_workspace_connection(workspace_dir, |conn| {
+        query_messages(conn, "1 = 1", &Utc::now().to_rfc3339())
+    })
+}
+
+/// Messages due at `now`.
+pub fn due_messages(workspace_dir: &Path, now: DateTime<Utc>) -> Result<Vec<ScheduledMessage>> {
+    with_workspace_connection(workspace_dir, |conn| {
+        query_messages(conn, "next_run <= ?1", &now.to_rfc3339())
+    })
+}
+
+/// Remove a scheduled message. Returns whether it existed.
+pub fn cancel_message(workspace_dir: &Path, id: &str) -> Result<bool> {
+    let changed = with_workspace_connection(workspace_dir, |conn| {
+        conn.execute("DELETE FROM scheduled_messages WHERE id = ?1", params![id])
+            .context("Failed to delete scheduled message")
+    })?;
+    Ok(changed > 0)
+}
+
+/// After delivery, drop a one-shot message or move a recurring one to its
+/// next occurrence.
+pub fn mark_delivered(
+    workspace_dir: &Path,
+    message: &ScheduledMessage,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if let MessageSchedule::Cron(_) = message.schedule {
+        let next_run = message.schedule.next_run(now)?;
+        with_workspace_connection(workspace_dir, |conn| {
+            conn.execute(
+                "UPDATE scheduled_messages SET next_run = ?1 WHERE id = ?2",
+                params![next_run.to_rfc3339(), message.id],
+            )
+            .context("Failed to reschedule message")?;
+            Ok(())
+        })
+    } else {
+        cancel_message(workspace_dir, &message.id).map(|_| ())
+    }
+}
+
+/// `filter` is a SQL condition that may use `?1`, bound to `param`.
+fn query_messages(conn: &Connection, filter: &str, param: &str) -> Result<Vec<ScheduledMessage>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, delay_secs, expression, message, channel, recipient, next_run
+         FROM scheduled_messages WHERE {filter} ORDER BY next_run ASC"
+    ))?;
+    let param_count = stmt.parameter_count();
+    let rows = if param_count == 0 {
+        stmt.query_map([], read_row)?.collect::<Vec<_>>()
+    } else {
+        stmt.query_map(params![param], read_row)?
+            .collect::<Vec<_>>()
+    };
+
+    let mut messages = Vec::new();
+    for row in rows {
+        let (id, delay_secs, expression, message, channel, recipient, next_run_raw) = row?;
+        let schedule = match (delay_secs, expression) {
+            (_, Some(expression)) => MessageSchedule::Cron(expression),
+            (Some(secs), None) => {
+                MessageSchedule::Delay(Duration::from_secs(u64::try_from(secs).unwrap_or(0)))
+            }
+            (None, None) => anyhow::bail!("Scheduled message '{id}' has no schedule"),
+        };
+        messages.push(ScheduledMessage {
+            id,
+            schedule,
+            message,
+            channel,
+            recipient,
+            next_run: parse_rfc3339(&next_run_raw)?,
+        });
+    }
+    Ok(messages)
+}
+
+type MessageRow = (
+    String,
+    Option<i64>,
+    Option<String>,
+    String,
+    String,
+    String,
+    String,
+);
+
+fn read_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MessageRow> {
+    Ok((
+        row.get(0)?,
+        row.get(1)?,
+        row.get(2)?,
+        row.get(3)?,
+        row.get(4)?,
+        row.get(5)?,
+        row.get(6)?,
+    ))
+}
+
+/// Deliver due messages for `channels` into `tx` every `interval`, alongside
+/// the channel listeners. Messages for other channels stay queued until a
+/// process listening on them picks them up. Only a weak handle to `tx` is
+/// kept, so the bus still closes once every listener is gone; the task then
+/// exits.
+pub fn spawn_dispatcher(
+    workspace_dir: PathBuf,
+    channels: Vec<String>,
+    tx: &mpsc::Sender<ChannelMessage>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    let tx = tx.downgrade();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let Some(tx) = tx.upgrade() else {
+                break;
+            };
+            let now = Utc::now();
+            let due = match due_messages(&workspace_dir, now) {
+                Ok(due) => due,
+                Err(e) => {
+                    tracing::warn!("Scheduled message query failed: {e}");
+                    continue;
+                }
+            };
+            for message in due.into_iter().filter(|m| channels.contains(&m.channel)) {
+                if tx.send(message.to_channel_message(now)).await.is_err() {
+                    return;
+                }
+                if let Err(e) = mark_delivered(&workspace_dir, &message, now) {
+                    tracing::warn!(id = message.id, "Failed to update scheduled message: {e}");
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn parse_delay_accepts_units_and_combinations() {
+        assert_eq!(parse_delay("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_delay("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_delay(" 1h30m ").unwrap(), Duration::from_secs(5_400));
+        assert_eq!(parse_delay("2D").unwrap(), Duration::from_secs(172_800));
+    }
+
+    #[test]
+    fn parse_delay_rejects_bad_input() {
+        for raw in ["", "10", "0s", "h", "5 minutes", "-1h", "400d"] {
+            assert!(parse_delay(raw).is_err(), "{raw:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn schedule_parses_delay_or_cron() {
+        assert_eq!(
+            MessageSchedule::parse(Some("1h"), None).unwrap(),
+            MessageSchedule::Delay(Duration::from_secs(3_600))
+        );
+        assert_eq!(
+            MessageSchedule::parse(None, Some("0 9 * * 1-5")).unwrap(),
+            MessageSchedule::Cron("0 9 * * 1-5".into())
+        );
+        assert!(MessageSchedule::parse(None, Some("every day")).is_err());
+        assert!(MessageSchedule::parse(Some("1h"), Some("0 9 * * *")).is_err());
+        assert!(MessageSchedule::parse(None, None).is_err());
+    }
+
+    #[test]
+    fn next_run_follows_schedule() {
+        let from = DateTime::parse_from_rfc3339("2026-03-02T08:15:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let delay = MessageSchedule::Delay(Duration::from_secs(5_400));
+        assert_eq!(
+            delay.next_run(from).unwrap().to_rfc3339(),
+            "2026-03-02T09:45:00+00:00"
+        );
+        let cron = MessageSchedule::Cron("0 9 * * *".into());
+        assert_eq!(
+            cron.next_run(from).unwrap().to_rfc3339(),
+            "2026-03-02T09:00:00+00:00"
+        );
+        assert_eq!(delay.to_string(), "once, after 1h30m");
+    }
+
+    #[test]
+    fn messages_persist_and_recurring_ones_reschedule() {
+        let tmp = TempDir::new().unwrap();
+        let once = add_message(
+            tmp.path(),
+            MessageSchedule::Delay(Duration::from_secs(60)),
+            "stretch",
+            "cli",
+            "user",
+        )
+        .unwrap();
+        let daily = add_message(
+            tmp.path(),
+            MessageSchedule::Cron("0 9 * * *".into()),
+            "check the build",
+            "slack",
+            "U123",
+        )
+        .unwrap();
+
+        let listed = list_messages(tmp.path()).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].id, once.id);
+
+        let later = Utc::now() + chrono::Duration::days(2);
+        let due = due_messages(tmp.path(), later).unwrap();
+        assert_eq!(due.len(), 2);
+        for message in &due {
+            mark_delivered(tmp.path(), message, later).unwrap();
+        }
+
+        let listed = list_messages(tmp.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, daily.id);
+        assert!(listed[0].next_run > later);
+
+        assert!(cancel_message(tmp.path(), &daily.id).unwrap());
+        assert!(!cancel_message(tmp.path(), &daily.id).unwrap());
+    }
+
+    #[tokio::test]
+    async fn short_delay_fires_into_channel() {
+        let tmp = TempDir::new().unwrap();
+        add_message(
+            tmp.path(),
+            MessageSchedule::Delay(Duration::from_secs(1)),
+            "time to stretch",
+            "telegram",
+            "alice",
+        )
+        .unwrap();
+        let elsewhere = add_message(
+            tmp.path(),
+            MessageSchedule::Delay(Duration::from_secs(1)),
+            "not for this process",
+            "slack",
+            "bob",
+        )
+        .unwrap();
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let dispatcher = spawn_dispatcher(
+            tmp.path().to_path_buf(),
+            vec!["telegram".into()],
+            &tx,
+            Duration::from_millis(50),
+        );
+        let msg = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("scheduled message should fire")
+            .unwrap();
+        assert_eq!(msg.channel, "telegram");
+        assert_eq!(msg.sender, "alice");
+        assert_eq!(msg.content, "[Scheduled message] time to stretch");
+        let pending = list_messages(tmp.path()).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, elsewhere.id);
+
+        // The dispatcher only holds a weak sender and stops with the bus
+        drop(tx);
+        tokio::time::timeout(Duration::from_secs(5), dispatcher)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
@@ -6,6 +6,7 @@ use rusqlite::{params, Connection};
 use std::str::FromStr;
 use uuid::Uuid;
 
+pub mod messages;
 pub mod scheduler;
 
 #[derive(Debug, Clone)]
@@ -246,7 +247,16 @@ fn parse_rfc3339(raw: &str) -> Result<DateTime<Utc>> {
 }
 
 fn with_connection<T>(config: &Config, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
-    let db_path = config.workspace_dir.join("cron").join("jobs.db");
+    with_workspace_connection(&config.workspace_dir, f)
+}
+
+/// Open (and if needed create) `workspace/cron/jobs.db`, which holds both
+/// shell jobs and scheduled agent messages.
+fn with_workspace_connection<T>(
+    workspace_dir: &std::path::Path,
+    f: impl FnOnce(&Connection) -> Result<T>,
+) -> Result<T> {
+    let db_path = workspace_dir.join("cron").join("jobs.db");
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create cron directory: {}", parent.display()))?;
@@ -266,7 +276,19 @@ fn with_connection<T>(config: &Config, f: impl FnOnce(&Connection) -> Result<T>)
             last_status TEXT,
             last_output TEXT
         );
-        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);",
+        CREATE INDEX IF NOT EXISTS idx_cron_jobs_next_run ON cron_jobs(next_run);
+        CREATE TABLE IF NOT EXISTS scheduled_messages (
+            id          TEXT PRIMARY KEY,
+            delay_secs  INTEGER,
+            expression  TEXT,
+            message     TEXT NOT NULL,
+            channel     TEXT NOT NULL,
+            recipient   TEXT NOT NULL,
+            created_at  TEXT NOT NULL,
+            next_run    TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_scheduled_messages_next_run
+            ON scheduled_messages(next_run);",
     )
     .context("Failed to initialize cron schema")?;
 
@@ -11,7 +11,8 @@ const MIN_POLL_SECONDS: u64 = 5;
 pub async fn run(config: Config) -> Result<()> {
     let poll_secs = config.reliability.scheduler_poll_secs.max(MIN_POLL_SECONDS);
     let mut interval = time::interval(Duration::from_secs(poll_secs));
-    let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+    let security =
+        SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
     crate::health::mark_component_ok("scheduler");
 
@@ -211,7 +212,8 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let config = test_config(&tmp);
         let job = test_job("echo scheduler-ok");
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         let (success, output) = run_job_command(&config, &security, &job).await;
         assert!(success);
@@ -224,7 +226,8 @@ mod tests {
         let tmp = TempDir::new().unwrap();
         let config = test_config(&tmp);
         let job = test_job("ls definitely_missing_file_for_scheduler_test");
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         let (success, output) = run_job_command(&config, &security, &job).await;
         assert!(!success);
@@ -238,7 +241,8 @@ mod tests {
         let mut config = test_config(&tmp);
         config.autonomy.allowed_commands = vec!["echo".into()];
         let job = test_job("curl https://evil.example");
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         let (success, output) = run_job_command(&config, &security, &job).await;
         assert!(!success);
@@ -252,7 +256,8 @@ mod tests {
         let mut config = test_config(&tmp);
         config.autonomy.allowed_commands = vec!["cat".into()];
         let job = test_job("cat /etc/passwd");
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         let (success, output) = run_job_command(&config, &security, &job).await;
         assert!(!success);
@@ -268,7 +273,8 @@ mod tests {
         config.reliability.scheduler_retries = 1;
         config.reliability.provider_backoff_ms = 1;
         config.autonomy.allowed_commands = vec!["sh".into()];
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         std::fs::write(
             config.workspace_dir.join("retry-once.sh"),
@@ -288,7 +294,8 @@ mod tests {
         let mut config = test_config(&tmp);
         config.reliability.scheduler_retries = 1;
         config.reliability.provider_backoff_ms = 1;
-        let security = SecurityPolicy::from_config(&config.autonomy, &config.workspace_dir);
+        let security =
+            SecurityPolicy::from_config(&config.autonomy, &config.security, &config.workspace_dir);
 
         let job = test_job("ls always_missing_for_retry_test");
 
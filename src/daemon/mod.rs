@@ -66,7 +66,7 @@ pub async fn run(config: Config, host: String, port: u16) -> Result<()> {
             max_backoff,
             move || {
                 let cfg = heartbeat_cfg.clone();
-                async move { run_heartbeat_worker(cfg).await }
+                async move { Box::pin(run_heartbeat_worker(cfg)).await }
             },
         ));
     }
@@ -201,6 +201,10 @@ async fn run_heartbeat_worker(config: Config) -> Result<()> {
                 provider_override,
                 model_override,
                 temp,
+                false,
+                None,
+                false,
+                crate::agent::report::OutputMode::Normal,
             )
             .await
             {
@@ -221,6 +225,7 @@ fn has_supervised_channels(config: &Config) -> bool {
         || config.channels_config.matrix.is_some()
         || config.channels_config.whatsapp.is_some()
         || config.channels_config.email.is_some()
+        || config.channels_config.http.is_some()
 }
 
 #[cfg(test)]
@@ -11,6 +11,7 @@ use crate::channels::{Channel, WhatsAppChannel};
 use crate::config::Config;
 use crate::memory::{self, Memory, MemoryCategory};
 use crate::providers::{self, Provider};
+use crate::security::output_filter;
 use crate::security::pairing::{constant_time_eq, is_public_bind, PairingGuard};
 use crate::util::truncate_with_ellipsis;
 use anyhow::Result;
@@ -189,6 +190,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         config.default_provider.as_deref().unwrap_or("openrouter"),
         config.api_key.as_deref(),
         &config.reliability,
+        &providers::ProviderOptions::from_config(&config),
+        &Arc::from(crate::observability::create_observer(&config.observability)),
     )?);
     let model = config
         .default_model
@@ -282,6 +285,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
     }
     println!("  GET  /          — web UI (pair + chat)");
     println!("  GET  /health    — health check");
+    println!("  GET  /healthz   — liveness probe");
+    println!("  GET  /readyz    — readiness probe (provider + memory)");
     if let Some(code) = pairing.pairing_code() {
         println!();
         println!("  🔐 PAIRING REQUIRED — use this one-time code:");
@@ -301,6 +306,8 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
 
     crate::health::mark_component_ok("gateway");
 
+    let probes = crate::health::Probes::new(provider.clone(), mem.clone());
+
     // Build shared state
     let state = AppState {
         provider,
@@ -325,6 +332,7 @@ pub async fn run_gateway(host: &str, port: u16, config: Config) -> Result<()> {
         .route("/whatsapp", post(handle_whatsapp_message))
         .fallback(serve_ui)
         .with_state(state)
+        .merge(probes.router())
         .layer(RequestBodyLimitLayer::new(MAX_BODY_SIZE))
         .layer(TimeoutLayer::with_status_code(
             StatusCode::REQUEST_TIMEOUT,
@@ -497,13 +505,20 @@ async fn handle_webhook(
 
     match state
         .provider
-        .chat(message, &state.model, state.temperature)
+        .chat_with_system(None, message, &state.model, state.temperature)
         .await
     {
-        Ok(response) => {
-            let body = serde_json::json!({"response": response, "model": state.model});
-            (StatusCode::OK, Json(body))
-        }
+        Ok(response) => match output_filter::screen(response) {
+            Ok(response) => {
+                let body = serde_json::json!({"response": response, "model": state.model});
+                (StatusCode::OK, Json(body))
+            }
+            Err(blocked) => {
+                tracing::warn!("Webhook: {blocked}");
+                let err = serde_json::json!({"error": blocked.to_string()});
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(err))
+            }
+        },
         Err(e) => {
             tracing::error!(
                 "Webhook provider error: {}",
@@ -654,8 +669,9 @@ async fn handle_whatsapp_message(
         // Call the LLM
         match state
             .provider
-            .chat(&msg.content, &state.model, state.temperature)
+            .chat_with_system(None, &msg.content, &state.model, state.temperature)
             .await
+            .and_then(|response| Ok(output_filter::screen(response)?))
         {
             Ok(response) => {
                 // Send reply via WhatsApp
@@ -762,7 +778,12 @@ mod tests {
             Ok(())
         }
 
-        async fn recall(&self, _query: &str, _limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+        async fn recall_in(
+            &self,
+            _query: &str,
+            _limit: usize,
+            _categories: &[MemoryCategory],
+        ) -> anyhow::Result<Vec<MemoryEntry>> {
             Ok(Vec::new())
         }
 
@@ -1,3 +1,7 @@
+mod readiness;
+
+pub use readiness::Probes;
+
 use chrono::Utc;
 use serde::Serialize;
 use std::collections::BTreeMap;
@@ -0,0 +1,109 @@
+use crate::memory::Memory;
+use crate::providers::Provider;
+use axum::{extract::State, http::StatusCode, response::Json, routing::get, Router};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Result of one subsystem check behind `GET /readyz`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadinessCheck {
+    pub name: &'static str,
+    pub ready: bool,
+    /// What was checked, or why it failed
+    pub detail: String,
+}
+
+/// Body of `GET /readyz`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+    /// Names of the checks that failed
+    pub failed: Vec<&'static str>,
+}
+
+impl Readiness {
+    fn from_checks(checks: Vec<ReadinessCheck>) -> Self {
+        let failed: Vec<_> = checks
+            .iter()
+            .filter(|check| !check.ready)
+            .map(|check| check.name)
+            .collect();
+        Self {
+            ready: failed.is_empty(),
+            checks,
+            failed,
+        }
+    }
+}
+
+/// Liveness and readiness probes for orchestrators, served by the gateway
+/// and the HTTP channel.
+///
+/// - `GET /healthz`: 200 whenever the process can answer.
+/// - `GET /readyz`: warms the provider up and checks the memory backend;
+///   200 when both are ready, 503 otherwise, with a `Readiness` body either
+///   way.
+///
+/// Both are public, like `/health`: they report reachability, not secrets.
+#[derive(Clone)]
+pub struct Probes {
+    provider: Arc<dyn Provider>,
+    memory: Arc<dyn Memory>,
+}
+
+impl Probes {
+    pub fn new(provider: Arc<dyn Provider>, memory: Arc<dyn Memory>) -> Self {
+        Self { provider, memory }
+    }
+
+    /// Run every readiness check.
+    pub async fn readiness(&self) -> Readiness {
+        let (warmup, memory_ok) = tokio::join!(self.provider.warmup(), self.memory.health_check());
+        Readiness::from_checks(vec![
+            ReadinessCheck {
+                name: "provider",
+                ready: warmup.reachable,
+                detail: warmup.detail,
+            },
+            ReadinessCheck {
+                name: "memory",
+                ready: memory_ok,
+                detail: format!(
+                    "{} backend {}",
+                    self.memory.name(),
+                    if memory_ok {
+                        "reachable"
+                    } else {
+                        "unreachable"
+                    }
+                ),
+            },
+        ])
+    }
+
+    /// Routes for `/healthz` and `/readyz`, to merge into a server's router.
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/healthz", get(handle_healthz))
+            .route("/readyz", get(handle_readyz))
+            .with_state(self)
+    }
+}
+
+/// GET /healthz — liveness
+async fn handle_healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({"status": "ok"}))
+}
+
+/// GET /readyz — readiness
+async fn handle_readyz(State(probes): State<Probes>) -> (StatusCode, Json<Readiness>) {
+    let readiness = probes.readiness().await;
+    let status = if readiness.ready {
+        StatusCode::OK
+    } else {
+        tracing::warn!(failed = ?readiness.failed, "Readiness check failed");
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(readiness))
+}
@@ -733,6 +733,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
         assert!(is_aieos_configured(&config));
     }
@@ -743,6 +744,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: Some("{\"identity\":{}}".into()),
+            ..IdentityConfig::default()
         };
         assert!(is_aieos_configured(&config));
     }
@@ -753,6 +755,7 @@ mod tests {
             format: "openclaw".into(),
             aieos_path: Some("identity.json".into()),
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
         assert!(!is_aieos_configured(&config));
     }
@@ -763,6 +766,7 @@ mod tests {
             format: "aieos".into(),
             aieos_path: None,
             aieos_inline: None,
+            ..IdentityConfig::default()
         };
         assert!(!is_aieos_configured(&config));
     }
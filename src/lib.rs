@@ -110,6 +110,25 @@ pub enum MigrateCommands {
     },
 }
 
+/// Memory backup subcommands
+#[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MemoryCommands {
+    /// Export all memory entries to a JSON or JSONL file
+    Export {
+        /// Output file (`.jsonl` writes one entry per line, otherwise a JSON array)
+        path: std::path::PathBuf,
+    },
+    /// Import memory entries from a JSON or JSONL export
+    Import {
+        /// File written by `memory export`
+        path: std::path::PathBuf,
+
+        /// Keep existing memory and upsert entries by key (default replaces it)
+        #[arg(long)]
+        merge: bool,
+    },
+}
+
 /// Cron subcommands
 #[derive(Subcommand, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum CronCommands {
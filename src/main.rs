@@ -96,6 +96,19 @@ enum Commands {
         #[arg(short, long)]
         message: Option<String>,
 
+        /// Send the [prompts] template with this name as the message
+        #[arg(long, value_name = "NAME", conflicts_with = "message")]
+        template: Option<String>,
+
+        /// Template variable as key=value (repeatable)
+        #[arg(
+            long = "var",
+            value_name = "KEY=VALUE",
+            requires = "template",
+            value_parser = agent::template::parse_var
+        )]
+        vars: Vec<(String, String)>,
+
         /// Provider to use (openrouter, anthropic, openai)
         #[arg(short, long)]
         provider: Option<String>,
@@ -107,6 +120,38 @@ enum Commands {
         /// Temperature (0.0 - 2.0)
         #[arg(short, long, default_value = "0.7")]
         temperature: f64,
+
+        /// Print each response once it is complete instead of streaming it
+        #[arg(long)]
+        no_stream: bool,
+
+        /// Resume (or start) the saved conversation with this id
+        #[arg(long)]
+        session: Option<String>,
+
+        /// Start the session over instead of resuming it (a new id is generated without --session)
+        #[arg(long)]
+        new_session: bool,
+
+        /// Print the tool calls that would change something instead of running them
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Only load this skill (repeatable; replaces skills.enabled from config)
+        #[arg(long = "skill", value_name = "NAME")]
+        skills: Vec<String>,
+
+        /// Use this persona from [identities] instead of the default identity
+        #[arg(long, value_name = "NAME")]
+        identity: Option<String>,
+
+        /// Print only the final answer (logs go to stderr)
+        #[arg(short, long, conflicts_with = "json")]
+        quiet: bool,
+
+        /// Print one JSON object with the answer, tool calls, usage and duration
+        #[arg(long)]
+        json: bool,
     },
 
     /// Start the gateway server (webhooks, websockets)
@@ -172,6 +217,12 @@ enum Commands {
         #[command(subcommand)]
         migrate_command: MigrateCommands,
     },
+
+    /// Back up or restore agent memory
+    Memory {
+        #[command(subcommand)]
+        memory_command: MemoryCommands,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -188,6 +239,24 @@ enum MigrateCommands {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum MemoryCommands {
+    /// Export all memory entries to a JSON or JSONL file
+    Export {
+        /// Output file (`.jsonl` writes one entry per line, otherwise a JSON array)
+        path: std::path::PathBuf,
+    },
+    /// Import memory entries from a JSON or JSONL export
+    Import {
+        /// File written by `memory export`
+        path: std::path::PathBuf,
+
+        /// Keep existing memory and upsert entries by key (default replaces it)
+        #[arg(long)]
+        merge: bool,
+    },
+}
+
 #[derive(Subcommand, Debug)]
 enum CronCommands {
     /// List all scheduled tasks
@@ -265,12 +334,25 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Initialize logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
-
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    // Initialize logging; `agent --quiet/--json` keep stdout for the answer
+    let builder = FmtSubscriber::builder().with_max_level(Level::INFO);
+    let subscriber = if matches!(
+        cli.command,
+        Commands::Agent { quiet: true, .. } | Commands::Agent { json: true, .. }
+    ) {
+        tracing::subscriber::set_global_default(
+            builder
+                .with_writer(security::redact::RedactingWriter::stderr)
+                .finish(),
+        )
+    } else {
+        tracing::subscriber::set_global_default(
+            builder
+                .with_writer(security::redact::RedactingWriter::stdout)
+                .finish(),
+        )
+    };
+    subscriber.expect("setting default subscriber failed");
 
     // Onboard runs quick setup by default, or the interactive wizard with --interactive
     if let Commands::Onboard {
@@ -303,17 +385,67 @@ async fn main() -> Result<()> {
     }
 
     // All other commands need config loaded first
-    let config = Config::load_or_init()?;
+    let mut config = Config::load_or_init()?;
+    config.apply_layered_env_overrides()?;
+    // Doctor still runs on a broken config so it can be used to diagnose it
+    if !matches!(cli.command, Commands::Doctor) {
+        if let Err(errors) = config.validate() {
+            bail!("{}", config::validate::format_errors(&errors));
+        }
+    }
+    match security::redact::Redactor::from_config(&config.security) {
+        Ok(redactor) => security::redact::install(redactor),
+        Err(e) => tracing::warn!("Ignoring security.redaction_patterns: {e:#}"),
+    }
+    match security::output_filter::OutputFilter::from_config(&config.security) {
+        Ok(Some(filter)) => security::output_filter::install(filter),
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Ignoring security.output_filters: {e:#}"),
+    }
 
     match cli.command {
         Commands::Onboard { .. } => unreachable!(),
 
         Commands::Agent {
             message,
+            template,
+            vars,
             provider,
             model,
             temperature,
-        } => agent::run(config, message, provider, model, temperature).await,
+            no_stream,
+            session,
+            new_session,
+            dry_run,
+            skills,
+            identity,
+            quiet,
+            json,
+        } => {
+            config.agent.dry_run |= dry_run;
+            if !skills.is_empty() {
+                config.skills.enabled = skills;
+            }
+            if identity.is_some() {
+                config.active_identity = identity;
+            }
+            let message = match template {
+                Some(name) => Some(agent::template::expand(&config, &name, &vars)?),
+                None => message,
+            };
+            agent::run(
+                config,
+                message,
+                provider,
+                model,
+                temperature,
+                !no_stream,
+                session,
+                new_session,
+                agent::report::OutputMode::from_flags(quiet, json),
+            )
+            .await
+        }
 
         Commands::Gateway { port, host } => {
             if port == 0 {
@@ -425,6 +557,10 @@ async fn main() -> Result<()> {
         Commands::Migrate { migrate_command } => {
             migration::handle_command(migrate_command, &config).await
         }
+
+        Commands::Memory { memory_command } => {
+            memory::backup::handle_command(memory_command, &config).await
+        }
     }
 }
 
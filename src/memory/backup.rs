@@ -0,0 +1,145 @@
+use super::traits::MemoryEntry;
+use crate::config::Config;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Handle `zeroclaw memory export/import`
+pub async fn handle_command(command: crate::MemoryCommands, config: &Config) -> Result<()> {
+    let mem = super::create_memory(
+        &config.memory,
+        &config.workspace_dir,
+        config.api_key.as_deref(),
+    )?;
+
+    match command {
+        crate::MemoryCommands::Export { path } => {
+            let entries = mem.export().await?;
+            write_entries(&path, &entries)?;
+            println!(
+                "Exported {} memories from the {} backend to {}",
+                entries.len(),
+                mem.name(),
+                path.display()
+            );
+        }
+        crate::MemoryCommands::Import { path, merge } => {
+            let entries = read_entries(&path)?;
+            let imported = mem.import(entries, merge).await?;
+            let mode = if merge { "merged into" } else { "replaced" };
+            println!(
+                "Imported {imported} memories from {} ({mode} the {} backend)",
+                path.display(),
+                mem.name()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn is_jsonl(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("jsonl"))
+}
+
+/// Write entries as JSONL (one per line) for `.jsonl` paths, else as a JSON array
+pub fn write_entries(path: &Path, entries: &[MemoryEntry]) -> Result<()> {
+    let body = if is_jsonl(path) {
+        let mut body = String::new();
+        for entry in entries {
+            body.push_str(&serde_json::to_string(entry)?);
+            body.push('\n');
+        }
+        body
+    } else {
+        serde_json::to_string_pretty(entries)?
+    };
+    std::fs::write(path, body).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Read entries from a JSON array or JSONL export
+pub fn read_entries(path: &Path) -> Result<Vec<MemoryEntry>> {
+    let raw = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    if raw.trim_start().starts_with('[') {
+        return serde_json::from_str(&raw)
+            .with_context(|| format!("Invalid memory export {}", path.display()));
+    }
+
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("Invalid memory entry at {}:{}", path.display(), i + 1))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryCategory;
+    use tempfile::TempDir;
+
+    fn entries() -> Vec<MemoryEntry> {
+        vec![
+            MemoryEntry {
+                id: "1".into(),
+                key: "lang".into(),
+                content: "Prefers Rust".into(),
+                category: MemoryCategory::Core,
+                timestamp: "2025-01-01T00:00:00+00:00".into(),
+                session_id: None,
+                score: None,
+                expires_at: None,
+            },
+            MemoryEntry {
+                id: "2".into(),
+                key: "chat".into(),
+                content: "line one\nline two".into(),
+                category: MemoryCategory::Custom("notes".into()),
+                timestamp: "2025-01-02T00:00:00+00:00".into(),
+                session_id: None,
+                score: None,
+                expires_at: Some("2025-02-01T00:00:00Z".into()),
+            },
+        ]
+    }
+
+    fn assert_same(a: &[MemoryEntry], b: &[MemoryEntry]) {
+        assert_eq!(
+            serde_json::to_value(a).unwrap(),
+            serde_json::to_value(b).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("memory.json");
+        write_entries(&path, &entries()).unwrap();
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with('['));
+        assert_same(&read_entries(&path).unwrap(), &entries());
+    }
+
+    #[test]
+    fn jsonl_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("memory.jsonl");
+        write_entries(&path, &entries()).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert_same(&read_entries(&path).unwrap(), &entries());
+    }
+
+    #[test]
+    fn read_reports_bad_jsonl_line() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("memory.jsonl");
+        std::fs::write(&path, "\n{\"not\": \"an entry\"}\n").unwrap();
+        let err = read_entries(&path).unwrap_err().to_string();
+        assert!(err.contains("memory.jsonl:2"), "{err}");
+    }
+}
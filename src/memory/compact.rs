@@ -0,0 +1,276 @@
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use crate::config::MemoryConfig;
+use crate::providers::Provider;
+use chrono::{Duration, Local, NaiveDate};
+use std::fmt::Write;
+
+const SUMMARY_PROMPT: &str = "You condense an AI assistant's daily memory log. \
+Summarize the entries into a short list of the durable facts, decisions and open \
+tasks they contain, dropping chatter and repetition. Reply with the summary only.";
+
+/// Date an entry was written: RFC 3339 timestamps and daily-log names both
+/// start with `YYYY-MM-DD`
+pub fn entry_date(entry: &MemoryEntry) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(entry.timestamp.get(..10)?, "%Y-%m-%d").ok()
+}
+
+/// Last date whose entries count as older than `days`
+pub fn cutoff_date(days: u32) -> NaiveDate {
+    Local::now().date_naive() - Duration::days(i64::from(days))
+}
+
+/// Daily entries written before `cutoff`, oldest first
+pub fn stale_daily_entries(entries: Vec<MemoryEntry>, cutoff: NaiveDate) -> Vec<MemoryEntry> {
+    let mut stale: Vec<MemoryEntry> = entries
+        .into_iter()
+        .filter(|e| e.category == MemoryCategory::Daily)
+        .filter(|e| entry_date(e).is_some_and(|date| date < cutoff))
+        .collect();
+    stale.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    stale
+}
+
+/// Key for the rolled-up entry covering `entries` (sorted oldest first)
+pub fn summary_key(entries: &[MemoryEntry]) -> String {
+    let date = |entry: Option<&MemoryEntry>| {
+        entry
+            .and_then(entry_date)
+            .map_or_else(|| "unknown".to_string(), |d| d.to_string())
+    };
+    format!(
+        "daily_summary_{}_{}",
+        date(entries.first()),
+        date(entries.last())
+    )
+}
+
+/// Ask the provider to roll `entries` up into one summary entry's content
+pub async fn summarize(
+    provider: &dyn Provider,
+    model: &str,
+    entries: &[MemoryEntry],
+) -> anyhow::Result<String> {
+    let mut log = String::new();
+    for entry in entries {
+        let _ = writeln!(
+            log,
+            "- [{}] {}: {}",
+            entry.timestamp, entry.key, entry.content
+        );
+    }
+
+    let summary = provider
+        .chat_with_system(Some(SUMMARY_PROMPT), &log, model, 0.2)
+        .await?;
+    let summary = summary.trim();
+    if summary.is_empty() {
+        anyhow::bail!("provider returned an empty summary");
+    }
+
+    let key = summary_key(entries);
+    Ok(format!(
+        "Summary of {} daily entries ({}):\n{summary}",
+        entries.len(),
+        key.trim_start_matches("daily_summary_")
+            .replace('_', " to ")
+    ))
+}
+
+/// Compact the Daily log at startup once it grows past
+/// `daily_compact_threshold` entries (best effort; failures are logged)
+pub async fn compact_if_needed(
+    mem: &dyn Memory,
+    provider: &dyn Provider,
+    model: &str,
+    config: &MemoryConfig,
+) {
+    if config.daily_compact_threshold == 0 {
+        return;
+    }
+    let daily = match mem.list(Some(&MemoryCategory::Daily)).await {
+        Ok(entries) => entries.len(),
+        Err(e) => {
+            tracing::warn!("Daily memory compaction skipped: {e}");
+            return;
+        }
+    };
+    if daily <= config.daily_compact_threshold {
+        return;
+    }
+
+    match mem
+        .compact(provider, model, config.daily_compact_after_days)
+        .await
+    {
+        Ok(0) => {}
+        Ok(replaced) => tracing::info!(replaced, "Compacted daily memory log"),
+        Err(e) => tracing::warn!("Daily memory compaction failed, originals kept: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::{MarkdownMemory, SqliteMemory};
+    use async_trait::async_trait;
+    use tempfile::TempDir;
+
+    /// Returns a canned summary, or fails when `fail` is set.
+    struct SummaryProvider {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            if self.fail {
+                anyhow::bail!("provider unavailable");
+            }
+            assert_eq!(system_prompt, Some(SUMMARY_PROMPT));
+            assert!(message.contains("fixed the login bug"));
+            Ok("- Login bug fixed\n- Release planned for Friday".into())
+        }
+    }
+
+    fn daily(key: &str, content: &str, days_ago: i64) -> MemoryEntry {
+        let at = Local::now() - Duration::days(days_ago);
+        MemoryEntry {
+            id: String::new(),
+            key: key.into(),
+            content: content.into(),
+            category: MemoryCategory::Daily,
+            timestamp: at.to_rfc3339(),
+            session_id: None,
+            score: None,
+            expires_at: None,
+        }
+    }
+
+    async fn seeded_sqlite(tmp: &TempDir) -> SqliteMemory {
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        mem.import(
+            vec![
+                daily("resp_1", "fixed the login bug", 10),
+                daily("resp_2", "planned release for Friday", 9),
+                daily("resp_3", "release planned for Friday", 8),
+                daily("resp_today", "reviewing PRs", 0),
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+        mem.store("lang", "prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem
+    }
+
+    #[test]
+    fn stale_entries_are_daily_and_older_than_cutoff() {
+        let entries = vec![
+            daily("new", "x", 1),
+            daily("old", "y", 10),
+            MemoryEntry {
+                category: MemoryCategory::Core,
+                ..daily("core", "z", 10)
+            },
+        ];
+        let stale = stale_daily_entries(entries, cutoff_date(7));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].key, "old");
+    }
+
+    #[tokio::test]
+    async fn compact_replaces_old_daily_entries_with_summary() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded_sqlite(&tmp).await;
+
+        let replaced = mem
+            .compact(&SummaryProvider { fail: false }, "model", 7)
+            .await
+            .unwrap();
+        assert_eq!(replaced, 3);
+        assert_eq!(mem.count().await.unwrap(), 3);
+
+        let daily = mem.list(Some(&MemoryCategory::Daily)).await.unwrap();
+        assert_eq!(daily.len(), 2);
+        let summary = daily
+            .iter()
+            .find(|e| e.key.starts_with("daily_summary_"))
+            .unwrap();
+        assert!(summary.content.starts_with("Summary of 3 daily entries"));
+        assert!(summary.content.contains("Login bug fixed"));
+        assert!(mem.get("resp_today").await.unwrap().is_some());
+        assert!(mem.get("resp_1").await.unwrap().is_none());
+        assert!(mem.get("lang").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn compact_keeps_originals_when_provider_fails() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded_sqlite(&tmp).await;
+
+        assert!(mem
+            .compact(&SummaryProvider { fail: true }, "model", 7)
+            .await
+            .is_err());
+        assert_eq!(mem.count().await.unwrap(), 5);
+        assert!(mem.get("resp_1").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn compact_if_needed_respects_threshold() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded_sqlite(&tmp).await;
+        let provider = SummaryProvider { fail: false };
+        let mut config = MemoryConfig {
+            daily_compact_threshold: 4,
+            daily_compact_after_days: 7,
+            ..MemoryConfig::default()
+        };
+
+        compact_if_needed(&mem, &provider, "model", &config).await;
+        assert_eq!(mem.count().await.unwrap(), 5);
+
+        config.daily_compact_threshold = 3;
+        compact_if_needed(&mem, &provider, "model", &config).await;
+        assert_eq!(mem.count().await.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn markdown_compact_replaces_old_daily_logs() {
+        let tmp = TempDir::new().unwrap();
+        let mem = MarkdownMemory::new(tmp.path());
+        mem.import(
+            vec![
+                daily("resp_1", "fixed the login bug", 10),
+                daily("resp_2", "planned release for Friday", 9),
+                daily("resp_today", "reviewing PRs", 0),
+            ],
+            false,
+        )
+        .await
+        .unwrap();
+
+        let replaced = mem
+            .compact(&SummaryProvider { fail: false }, "model", 7)
+            .await
+            .unwrap();
+        assert_eq!(replaced, 2);
+
+        let entries = mem.list(None).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .any(|e| e.content.contains("Login bug fixed")));
+        assert!(entries
+            .iter()
+            .all(|e| !e.content.contains("fixed the login bug")));
+    }
+}
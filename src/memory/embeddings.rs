@@ -123,6 +123,44 @@ impl EmbeddingProvider for OpenAiEmbedding {
     }
 }
 
+// ── Chat provider embeddings (Provider::embed) ───────────────
+
+/// Embeds through a chat provider's own embeddings API (e.g. Bedrock Titan)
+pub struct ProviderEmbedding {
+    provider: Box<dyn crate::providers::Provider>,
+    model: String,
+    dims: usize,
+}
+
+impl ProviderEmbedding {
+    pub fn new(provider: Box<dyn crate::providers::Provider>, model: &str, dims: usize) -> Self {
+        Self {
+            provider,
+            model: model.to_string(),
+            dims,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for ProviderEmbedding {
+    fn name(&self) -> &str {
+        "provider"
+    }
+
+    fn dimensions(&self) -> usize {
+        self.dims
+    }
+
+    async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let texts: Vec<String> = texts.iter().map(|t| (*t).to_string()).collect();
+        self.provider.embed(&texts, &self.model).await
+    }
+}
+
 // ── Factory ──────────────────────────────────────────────────
 
 pub fn create_embedding_provider(
@@ -146,6 +184,16 @@ pub fn create_embedding_provider(
             let key = api_key.unwrap_or("");
             Box::new(OpenAiEmbedding::new(base_url, key, model, dims))
         }
+        name if name.starts_with("provider:") => {
+            let provider_name = name.strip_prefix("provider:").unwrap_or("");
+            match crate::providers::create_provider(provider_name, api_key) {
+                Ok(provider) => Box::new(ProviderEmbedding::new(provider, model, dims)),
+                Err(e) => {
+                    tracing::warn!("Embedding provider '{name}' unavailable, using noop: {e}");
+                    Box::new(NoopEmbedding)
+                }
+            }
+        }
         _ => Box::new(NoopEmbedding),
     }
 }
@@ -238,6 +286,55 @@ mod tests {
         assert_eq!(p.dimensions(), 1536);
     }
 
+    #[test]
+    fn factory_chat_provider() {
+        let p = create_embedding_provider(
+            "provider:bedrock",
+            None,
+            "amazon.titan-embed-text-v2:0",
+            1024,
+        );
+        assert_eq!(p.name(), "provider");
+        assert_eq!(p.dimensions(), 1024);
+    }
+
+    #[test]
+    fn factory_unknown_chat_provider_returns_noop() {
+        let p = create_embedding_provider("provider:nonexistent", None, "model", 1536);
+        assert_eq!(p.name(), "none");
+    }
+
+    #[tokio::test]
+    async fn chat_provider_embedding_forwards_to_provider() {
+        struct Embedder;
+
+        #[async_trait]
+        impl crate::providers::Provider for Embedder {
+            async fn chat_with_system(
+                &self,
+                _system_prompt: Option<&str>,
+                _message: &str,
+                _model: &str,
+                _temperature: f64,
+            ) -> anyhow::Result<String> {
+                Ok(String::new())
+            }
+
+            async fn embed(&self, texts: &[String], model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+                #[allow(clippy::cast_precision_loss)]
+                Ok(texts
+                    .iter()
+                    .map(|t| vec![t.len() as f32, model.len() as f32])
+                    .collect())
+            }
+        }
+
+        let p = ProviderEmbedding::new(Box::new(Embedder), "m1", 2);
+        let result = p.embed(&["abc", "de"]).await.unwrap();
+        assert_eq!(result, vec![vec![3.0, 2.0], vec![2.0, 2.0]]);
+        assert!(p.embed(&[]).await.unwrap().is_empty());
+    }
+
     #[test]
     fn openai_trailing_slash_stripped() {
         let p = OpenAiEmbedding::new("https://api.openai.com/", "key", "model", 1536);
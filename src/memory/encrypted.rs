@@ -0,0 +1,369 @@
+use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use crate::config::MemoryConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng};
+use chacha20poly1305::{AeadCore, ChaCha20Poly1305, Key, Nonce};
+use std::path::Path;
+
+/// Prefix of encrypted content: `enc2:<hex(nonce ‖ ciphertext ‖ tag)>`,
+/// the same format the secret store uses
+const PREFIX: &str = "enc2:";
+
+/// ChaCha20-Poly1305 nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// Salt for passphrase-derived keys, kept next to the memory it protects
+const SALT_FILE: &str = ".encryption_salt";
+
+/// Encryption at rest for any memory backend
+///
+/// Content is sealed with ChaCha20-Poly1305 before it reaches the inner
+/// backend and opened again on the way out; keys, categories and timestamps
+/// stay in the clear. The inner backend only ever sees ciphertext, so its
+/// full-text and vector indexes are unusable and `recall` falls back to
+/// keyword matching over the decrypted entries.
+///
+/// Content stored before encryption was enabled (no `enc2:` prefix) is
+/// returned as-is.
+pub struct EncryptedMemory {
+    inner: Box<dyn Memory>,
+    cipher: ChaCha20Poly1305,
+}
+
+/// Whether `config` turns on memory encryption
+pub fn is_configured(config: &MemoryConfig) -> bool {
+    config.encryption_key.is_some() || config.encryption_passphrase.is_some()
+}
+
+/// Wrap `inner` in [`EncryptedMemory`] when `config` sets a key or passphrase
+pub fn wrap_if_configured(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    inner: Box<dyn Memory>,
+) -> Result<Box<dyn Memory>> {
+    let key = match (&config.encryption_key, &config.encryption_passphrase) {
+        (Some(_), Some(_)) => {
+            anyhow::bail!("Set only one of memory.encryption_key and memory.encryption_passphrase")
+        }
+        (Some(hex_key), None) => key_from_hex(hex_key)?,
+        (None, Some(passphrase)) => key_from_passphrase(passphrase, workspace_dir)?,
+        (None, None) => return Ok(inner),
+    };
+    // Markdown memory is meant to be read and edited by hand
+    anyhow::ensure!(
+        inner.name() != "markdown",
+        "Memory encryption is not supported by the markdown backend; use sqlite or vector"
+    );
+    Ok(Box::new(EncryptedMemory::new(inner, &key)))
+}
+
+/// Parse a 256-bit key given as 64 hex characters
+pub fn key_from_hex(hex_key: &str) -> Result<[u8; 32]> {
+    let bytes =
+        hex::decode(hex_key.trim()).context("memory.encryption_key must be 64 hex characters")?;
+    bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("memory.encryption_key must be 64 hex characters"))
+}
+
+/// Derive a 256-bit key from a passphrase with Argon2id, using a random salt
+/// created in `workspace/memory/` on first use
+pub fn key_from_passphrase(passphrase: &str, workspace_dir: &Path) -> Result<[u8; 32]> {
+    let memory_dir = workspace_dir.join("memory");
+    let salt_path = memory_dir.join(SALT_FILE);
+    let salt = if salt_path.exists() {
+        std::fs::read(&salt_path)
+            .with_context(|| format!("Failed to read {}", salt_path.display()))?
+    } else {
+        // 256 random bits from the OS CSPRNG
+        let salt = ChaCha20Poly1305::generate_key(&mut OsRng).to_vec();
+        std::fs::create_dir_all(&memory_dir)?;
+        std::fs::write(&salt_path, &salt)
+            .with_context(|| format!("Failed to write {}", salt_path.display()))?;
+        salt
+    };
+
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| anyhow::anyhow!("Failed to derive memory encryption key: {e}"))?;
+    Ok(key)
+}
+
+impl EncryptedMemory {
+    pub fn new(inner: Box<dyn Memory>, key: &[u8; 32]) -> Self {
+        Self {
+            inner,
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+
+    fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Memory encryption failed: {e}"))?;
+
+        let mut blob = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(format!("{PREFIX}{}", hex::encode(blob)))
+    }
+
+    fn decrypt(&self, value: &str) -> Result<String> {
+        let Some(hex_str) = value.strip_prefix(PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let blob = hex::decode(hex_str).context("corrupt hex")?;
+        anyhow::ensure!(blob.len() > NONCE_LEN, "ciphertext too short");
+
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow::anyhow!("wrong memory encryption key or tampered data"))?;
+        String::from_utf8(plaintext).context("decrypted content is not valid UTF-8")
+    }
+
+    fn open(&self, mut entry: MemoryEntry) -> Result<MemoryEntry> {
+        entry.content = self
+            .decrypt(&entry.content)
+            .with_context(|| format!("Failed to decrypt memory '{}'", entry.key))?;
+        Ok(entry)
+    }
+
+    fn open_all(&self, entries: Vec<MemoryEntry>) -> Result<Vec<MemoryEntry>> {
+        entries.into_iter().map(|e| self.open(e)).collect()
+    }
+}
+
+#[async_trait]
+impl Memory for EncryptedMemory {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn store(&self, key: &str, content: &str, category: MemoryCategory) -> Result<()> {
+        let sealed = self.encrypt(content)?;
+        self.inner.store(key, &sealed, category).await
+    }
+
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> Result<Vec<MemoryEntry>> {
+        let mut all = self.open_all(self.inner.list(None).await?)?;
+        if !categories.is_empty() {
+            all.retain(|entry| categories.contains(&entry.category));
+        }
+        let query_lower = query.to_lowercase();
+        let keywords: Vec<&str> = query_lower.split_whitespace().collect();
+
+        let mut scored: Vec<MemoryEntry> = all
+            .into_iter()
+            .filter_map(|mut entry| {
+                let content_lower = entry.content.to_lowercase();
+                let matched = keywords
+                    .iter()
+                    .filter(|kw| content_lower.contains(**kw) || entry.key.contains(**kw))
+                    .count();
+                if matched > 0 {
+                    #[allow(clippy::cast_precision_loss)]
+                    let score = matched as f64 / keywords.len() as f64;
+                    entry.score = Some(score);
+                    Some(entry)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scored.truncate(limit);
+        Ok(scored)
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<MemoryEntry>> {
+        self.inner.get(key).await?.map(|e| self.open(e)).transpose()
+    }
+
+    async fn list(&self, category: Option<&MemoryCategory>) -> Result<Vec<MemoryEntry>> {
+        self.open_all(self.inner.list(category).await?)
+    }
+
+    async fn forget(&self, key: &str) -> Result<bool> {
+        self.inner.forget(key).await
+    }
+
+    async fn count(&self) -> Result<usize> {
+        self.inner.count().await
+    }
+
+    async fn export(&self) -> Result<Vec<MemoryEntry>> {
+        self.open_all(self.inner.export().await?)
+    }
+
+    async fn import(&self, entries: Vec<MemoryEntry>, merge: bool) -> Result<usize> {
+        let sealed = entries
+            .into_iter()
+            .map(|mut entry| {
+                entry.content = self.encrypt(&entry.content)?;
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.inner.import(sealed, merge).await
+    }
+
+    async fn purge_expired(&self) -> Result<usize> {
+        self.inner.purge_expired().await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn health_check(&self) -> bool {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    const KEY: [u8; 32] = [7; 32];
+
+    fn encrypted(tmp: &TempDir, key: &[u8; 32]) -> EncryptedMemory {
+        EncryptedMemory::new(Box::new(SqliteMemory::new(tmp.path()).unwrap()), key)
+    }
+
+    #[tokio::test]
+    async fn round_trip_keeps_ciphertext_on_disk() {
+        let tmp = TempDir::new().unwrap();
+        let mem = encrypted(&tmp, &KEY);
+        mem.store(
+            "token",
+            "the deploy password is hunter2",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+
+        let entry = mem.get("token").await.unwrap().unwrap();
+        assert_eq!(entry.content, "the deploy password is hunter2");
+        let recalled = mem.recall("deploy password", 5).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].content, "the deploy password is hunter2");
+        assert_eq!(
+            mem.export().await.unwrap()[0].content,
+            "the deploy password is hunter2"
+        );
+
+        // The backend underneath only ever sees ciphertext
+        let raw = SqliteMemory::new(tmp.path()).unwrap();
+        let stored = raw.get("token").await.unwrap().unwrap();
+        assert!(stored.content.starts_with(PREFIX));
+        assert!(!stored.content.contains("hunter2"));
+        let db = std::fs::read(tmp.path().join("memory").join("brain.db")).unwrap();
+        assert!(!db.windows(7).any(|w| w == b"hunter2"));
+    }
+
+    #[tokio::test]
+    async fn wrong_key_is_a_clear_error() {
+        let tmp = TempDir::new().unwrap();
+        encrypted(&tmp, &KEY)
+            .store("token", "hunter2", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let wrong = encrypted(&tmp, &[8; 32]);
+        let err = wrong.get("token").await.unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains("Failed to decrypt memory 'token'"),
+            "{message}"
+        );
+        assert!(message.contains("wrong memory encryption key"), "{message}");
+        assert!(wrong.recall("hunter2", 5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn import_encrypts_and_plaintext_passes_through() {
+        let tmp = TempDir::new().unwrap();
+        SqliteMemory::new(tmp.path())
+            .unwrap()
+            .store("old", "stored before encryption", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let mem = encrypted(&tmp, &KEY);
+        assert_eq!(
+            mem.get("old").await.unwrap().unwrap().content,
+            "stored before encryption"
+        );
+
+        let entries = mem.export().await.unwrap();
+        assert_eq!(mem.import(entries, false).await.unwrap(), 1);
+        let raw = SqliteMemory::new(tmp.path()).unwrap();
+        assert!(raw
+            .get("old")
+            .await
+            .unwrap()
+            .unwrap()
+            .content
+            .starts_with(PREFIX));
+        assert_eq!(
+            mem.get("old").await.unwrap().unwrap().content,
+            "stored before encryption"
+        );
+    }
+
+    #[test]
+    fn passphrase_key_is_stable_per_workspace() {
+        let tmp = TempDir::new().unwrap();
+        let first = key_from_passphrase("correct horse", tmp.path()).unwrap();
+        assert!(tmp.path().join("memory").join(SALT_FILE).exists());
+        assert_eq!(
+            first,
+            key_from_passphrase("correct horse", tmp.path()).unwrap()
+        );
+        assert_ne!(
+            first,
+            key_from_passphrase("battery staple", tmp.path()).unwrap()
+        );
+
+        let other = TempDir::new().unwrap();
+        assert_ne!(
+            first,
+            key_from_passphrase("correct horse", other.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn hex_key_must_be_256_bits() {
+        assert_eq!(key_from_hex(&"07".repeat(32)).unwrap(), KEY);
+        assert!(key_from_hex("abcd").is_err());
+        assert!(key_from_hex("not hex").is_err());
+    }
+
+    #[test]
+    fn markdown_backend_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let config = MemoryConfig {
+            encryption_key: Some("07".repeat(32)),
+            ..MemoryConfig::default()
+        };
+        let inner = Box::new(crate::memory::MarkdownMemory::new(tmp.path()));
+        assert!(wrap_if_configured(&config, tmp.path(), inner).is_err());
+    }
+}
@@ -1,6 +1,9 @@
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::compact;
+use super::traits::{self, Memory, MemoryCategory, MemoryEntry};
+use crate::config::MemoryTtlConfig;
+use crate::providers::Provider;
 use async_trait::async_trait;
-use chrono::Local;
+use chrono::{Local, NaiveDate};
 use std::path::{Path, PathBuf};
 use tokio::fs;
 
@@ -9,17 +12,61 @@ use tokio::fs;
 /// Layout:
 ///   workspace/MEMORY.md          — curated long-term memory (core)
 ///   workspace/memory/YYYY-MM-DD.md — daily logs (append-only)
+///
+/// Entries with an expiry carry it in a trailing
+/// `<!-- expires_at: ... -->` comment.
 pub struct MarkdownMemory {
     workspace_dir: PathBuf,
+    ttl: MemoryTtlConfig,
 }
 
+/// Prefix of the trailing comment holding an entry's expiry
+const EXPIRES_MARKER: &str = "<!-- expires_at: ";
+
 impl MarkdownMemory {
     pub fn new(workspace_dir: &Path) -> Self {
         Self {
             workspace_dir: workspace_dir.to_path_buf(),
+            ttl: MemoryTtlConfig::default(),
         }
     }
 
+    /// Stamp newly stored entries with an expiry per `ttl`
+    pub fn with_ttl(mut self, ttl: MemoryTtlConfig) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Split a trailing expiry comment off an entry line
+    fn split_expiry(line: &str) -> (&str, Option<&str>) {
+        line.rsplit_once(EXPIRES_MARKER)
+            .and_then(|(text, rest)| {
+                let expires = rest.strip_suffix("-->")?.trim();
+                Some((text.trim_end(), Some(expires)))
+            })
+            .unwrap_or((line, None))
+    }
+
+    /// All markdown files that hold entries: MEMORY.md plus the daily logs
+    async fn memory_files(&self) -> anyhow::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let core_path = self.core_path();
+        if core_path.exists() {
+            files.push(core_path);
+        }
+        let mem_dir = self.memory_dir();
+        if mem_dir.exists() {
+            let mut dir = fs::read_dir(&mem_dir).await?;
+            while let Some(entry) = dir.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) == Some("md") {
+                    files.push(path);
+                }
+            }
+        }
+        Ok(files)
+    }
+
     fn memory_dir(&self) -> PathBuf {
         self.workspace_dir.join("memory")
     }
@@ -51,7 +98,7 @@ impl MarkdownMemory {
             let header = if path == self.core_path() {
                 "# Long-Term Memory\n\n"
             } else {
-                let date = Local::now().format("%Y-%m-%d").to_string();
+                let date = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
                 &format!("# Daily Log — {date}\n\n")
             };
             format!("{header}{content}\n")
@@ -83,6 +130,7 @@ impl MarkdownMemory {
             .map(|(i, line)| {
                 let trimmed = line.trim();
                 let clean = trimmed.strip_prefix("- ").unwrap_or(trimmed);
+                let (clean, expires_at) = Self::split_expiry(clean);
                 MemoryEntry {
                     id: format!("{filename}:{i}"),
                     key: format!("{filename}:{i}"),
@@ -91,6 +139,7 @@ impl MarkdownMemory {
                     timestamp: filename.to_string(),
                     session_id: None,
                     score: None,
+                    expires_at: expires_at.map(str::to_string),
                 }
             })
             .collect()
@@ -98,35 +147,20 @@ impl MarkdownMemory {
 
     async fn read_all_entries(&self) -> anyhow::Result<Vec<MemoryEntry>> {
         let mut entries = Vec::new();
-
-        // Read MEMORY.md (core)
         let core_path = self.core_path();
-        if core_path.exists() {
-            let content = fs::read_to_string(&core_path).await?;
-            entries.extend(Self::parse_entries_from_file(
-                &core_path,
-                &content,
-                &MemoryCategory::Core,
-            ));
-        }
 
-        // Read daily logs
-        let mem_dir = self.memory_dir();
-        if mem_dir.exists() {
-            let mut dir = fs::read_dir(&mem_dir).await?;
-            while let Some(entry) = dir.next_entry().await? {
-                let path = entry.path();
-                if path.extension().and_then(|e| e.to_str()) == Some("md") {
-                    let content = fs::read_to_string(&path).await?;
-                    entries.extend(Self::parse_entries_from_file(
-                        &path,
-                        &content,
-                        &MemoryCategory::Daily,
-                    ));
-                }
-            }
+        // MEMORY.md is core; everything under memory/ is a daily log
+        for path in self.memory_files().await? {
+            let category = if path == core_path {
+                MemoryCategory::Core
+            } else {
+                MemoryCategory::Daily
+            };
+            let content = fs::read_to_string(&path).await?;
+            entries.extend(Self::parse_entries_from_file(&path, &content, &category));
         }
 
+        entries.retain(|entry| !entry.is_expired());
         entries.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
         Ok(entries)
     }
@@ -144,7 +178,10 @@ impl Memory for MarkdownMemory {
         content: &str,
         category: MemoryCategory,
     ) -> anyhow::Result<()> {
-        let entry = format!("- **{key}**: {content}");
+        let expiry = traits::expiry_for(&self.ttl, &category)
+            .map(|expires_at| format!(" {EXPIRES_MARKER}{expires_at} -->"))
+            .unwrap_or_default();
+        let entry = format!("- **{key}**: {content}{expiry}");
         let path = match category {
             MemoryCategory::Core => self.core_path(),
             _ => self.daily_path(),
@@ -152,8 +189,16 @@ impl Memory for MarkdownMemory {
         self.append_to_file(&path, &entry).await
     }
 
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
-        let all = self.read_all_entries().await?;
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        let mut all = self.read_all_entries().await?;
+        if !categories.is_empty() {
+            all.retain(|entry| categories.contains(&entry.category));
+        }
         let query_lower = query.to_lowercase();
         let keywords: Vec<&str> = query_lower.split_whitespace().collect();
 
@@ -211,6 +256,104 @@ impl Memory for MarkdownMemory {
         Ok(all.len())
     }
 
+    /// Rewrites the markdown files: core entries go to MEMORY.md, everything
+    /// else to the daily log of the entry's date. Markdown memory has no
+    /// stable keys, so `merge` appends instead of upserting.
+    async fn import(&self, entries: Vec<MemoryEntry>, merge: bool) -> anyhow::Result<usize> {
+        if !merge {
+            for path in self.memory_files().await? {
+                fs::remove_file(path).await?;
+            }
+        }
+
+        for entry in &entries {
+            let path = if entry.category == MemoryCategory::Core {
+                self.core_path()
+            } else {
+                // RFC 3339 timestamps and daily-log names both start with the date
+                let date = entry
+                    .timestamp
+                    .get(..10)
+                    .filter(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok())
+                    .map_or_else(
+                        || Local::now().format("%Y-%m-%d").to_string(),
+                        str::to_string,
+                    );
+                self.memory_dir().join(format!("{date}.md"))
+            };
+            // Entries exported from markdown already carry their "**key**:" prefix
+            let mut line = if entry.content.starts_with("**") {
+                format!("- {}", entry.content)
+            } else {
+                format!("- **{}**: {}", entry.key, entry.content)
+            };
+            if let Some(expires_at) = &entry.expires_at {
+                line = format!("{line} {EXPIRES_MARKER}{expires_at} -->");
+            }
+            self.append_to_file(&path, &line).await?;
+        }
+        Ok(entries.len())
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        let mut purged = 0;
+        for path in self.memory_files().await? {
+            let content = fs::read_to_string(&path).await?;
+            let kept: Vec<&str> = content
+                .lines()
+                .filter(|line| {
+                    let (_, expires_at) = Self::split_expiry(line.trim());
+                    !expires_at.is_some_and(traits::is_past)
+                })
+                .collect();
+            let removed = content.lines().count() - kept.len();
+            if removed > 0 {
+                fs::write(&path, format!("{}\n", kept.join("\n"))).await?;
+                purged += removed;
+            }
+        }
+        Ok(purged)
+    }
+
+    /// Entries can't be forgotten one by one here, so the daily log files
+    /// older than the cutoff are deleted once their summary is stored.
+    async fn compact(
+        &self,
+        provider: &dyn Provider,
+        model: &str,
+        older_than_days: u32,
+    ) -> anyhow::Result<usize> {
+        let cutoff = compact::cutoff_date(older_than_days);
+        let daily = self.list(Some(&MemoryCategory::Daily)).await?;
+        let stale = compact::stale_daily_entries(daily, cutoff);
+        if stale.len() < 2 {
+            return Ok(0);
+        }
+
+        let summary = compact::summarize(provider, model, &stale).await?;
+        // One entry per line: fold the summary onto a single line
+        let summary = summary
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let key = compact::summary_key(&stale);
+        self.store(&key, &summary, MemoryCategory::Daily).await?;
+
+        for path in self.memory_files().await? {
+            let old = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").ok())
+                .is_some_and(|date| date < cutoff);
+            if old && path != self.core_path() {
+                fs::remove_file(path).await?;
+            }
+        }
+        Ok(stale.len())
+    }
+
     async fn health_check(&self) -> bool {
         self.workspace_dir.exists()
     }
@@ -281,6 +424,33 @@ mod tests {
             .all(|r| r.content.to_lowercase().contains("rust")));
     }
 
+    #[tokio::test]
+    async fn markdown_recall_in_filters_categories() {
+        let (_tmp, mem) = temp_workspace();
+        mem.store("a", "Rust core fact", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "Rust daily note", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let core = mem
+            .recall_in("Rust", 10, &[MemoryCategory::Core])
+            .await
+            .unwrap();
+        assert_eq!(core.len(), 1);
+        assert_eq!(core[0].category, MemoryCategory::Core);
+
+        let daily = mem
+            .recall_in("Rust", 10, &[MemoryCategory::Daily])
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert!(daily[0].content.contains("daily note"));
+
+        assert_eq!(mem.recall("Rust", 10).await.unwrap().len(), 2);
+    }
+
     #[tokio::test]
     async fn markdown_recall_no_match() {
         let (_tmp, mem) = temp_workspace();
@@ -329,6 +499,97 @@ mod tests {
         assert!(!removed, "Markdown memory is append-only");
     }
 
+    #[tokio::test]
+    async fn markdown_store_stamps_expiry_from_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let mut ttl = MemoryTtlConfig::default();
+        ttl.default_days = Some(30);
+        let mem = MarkdownMemory::new(tmp.path()).with_ttl(ttl);
+        mem.store("note", "Finished tests", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let content = sync_fs::read_to_string(mem.daily_path()).unwrap();
+        assert!(content.contains(EXPIRES_MARKER));
+        let entries = mem.list(None).await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].content, "**note**: Finished tests");
+        assert!(entries[0].expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn markdown_expired_entries_are_skipped_and_purged() {
+        let (_tmp, mem) = temp_workspace();
+        sync_fs::write(
+            mem.core_path(),
+            "# Long-Term Memory\n\n\
+             - **old**: stale Rust fact <!-- expires_at: 2000-01-01T00:00:00Z -->\n\
+             - **new**: fresh Rust fact\n",
+        )
+        .unwrap();
+
+        let results = mem.recall("Rust", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].content.contains("fresh"));
+        assert_eq!(mem.count().await.unwrap(), 1);
+
+        assert_eq!(mem.purge_expired().await.unwrap(), 1);
+        let content = sync_fs::read_to_string(mem.core_path()).unwrap();
+        assert!(!content.contains("stale"));
+        assert!(content.contains("fresh Rust fact"));
+        assert_eq!(mem.purge_expired().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn markdown_export_import_round_trip() {
+        let (_tmp, source) = temp_workspace();
+        source
+            .store("pref", "User likes Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        source
+            .store("note", "Finished tests", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        let mut exported = source.export().await.unwrap();
+
+        let (_tmp2, target) = temp_workspace();
+        target
+            .store("stale", "replaced on import", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert_eq!(target.import(exported.clone(), false).await.unwrap(), 2);
+
+        let mut imported = target.export().await.unwrap();
+        exported.sort_by(|a, b| a.key.cmp(&b.key));
+        imported.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            serde_json::to_value(&imported).unwrap(),
+            serde_json::to_value(&exported).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn markdown_import_files_daily_entries_by_date() {
+        let (_tmp, mem) = temp_workspace();
+        let entry = MemoryEntry {
+            id: "x".into(),
+            key: "standup".into(),
+            content: "Moved to 10am".into(),
+            category: MemoryCategory::Conversation,
+            timestamp: "2025-03-04T09:00:00+00:00".into(),
+            session_id: None,
+            score: None,
+            expires_at: None,
+        };
+        mem.import(vec![entry], true).await.unwrap();
+
+        let path = mem.memory_dir().join("2025-03-04.md");
+        let content = sync_fs::read_to_string(path).unwrap();
+        assert!(content.starts_with("# Daily Log — 2025-03-04"));
+        assert!(content.contains("- **standup**: Moved to 10am"));
+    }
+
     #[tokio::test]
     async fn markdown_empty_recall() {
         let (_tmp, mem) = temp_workspace();
@@ -1,12 +1,18 @@
+pub mod backup;
 pub mod chunker;
+pub mod compact;
 pub mod embeddings;
+pub mod encrypted;
 pub mod hygiene;
 pub mod markdown;
+pub mod save_summary;
+pub mod semantic;
 pub mod sqlite;
 pub mod traits;
 pub mod vector;
 
 pub use markdown::MarkdownMemory;
+pub use semantic::VectorMemory;
 pub use sqlite::SqliteMemory;
 pub use traits::Memory;
 #[allow(unused_imports)]
@@ -27,6 +33,24 @@ pub fn create_memory(
         tracing::warn!("memory hygiene skipped: {e}");
     }
 
+    if !encrypted::is_configured(config) {
+        return create_backend(config, workspace_dir, api_key);
+    }
+    // Embedding plaintext would leak it to the index (and the embedding
+    // provider), so encrypted memory recalls by keyword only
+    let inner_config = MemoryConfig {
+        embedding_provider: "none".into(),
+        ..config.clone()
+    };
+    let inner = create_backend(&inner_config, workspace_dir, api_key)?;
+    encrypted::wrap_if_configured(config, workspace_dir, inner)
+}
+
+fn create_backend(
+    config: &MemoryConfig,
+    workspace_dir: &Path,
+    api_key: Option<&str>,
+) -> anyhow::Result<Box<dyn Memory>> {
     match config.backend.as_str() {
         "sqlite" => {
             let embedder: Arc<dyn embeddings::EmbeddingProvider> =
@@ -44,13 +68,30 @@ pub fn create_memory(
                 config.vector_weight as f32,
                 config.keyword_weight as f32,
                 config.embedding_cache_size,
-            )?;
+            )?
+            .with_ttl(config.ttl.clone());
             Ok(Box::new(mem))
         }
-        "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(workspace_dir))),
+        "vector" => {
+            let embedder: Arc<dyn embeddings::EmbeddingProvider> =
+                Arc::from(embeddings::create_embedding_provider(
+                    &config.embedding_provider,
+                    api_key,
+                    &config.embedding_model,
+                    config.embedding_dimensions,
+                ));
+            Ok(Box::new(
+                VectorMemory::new(workspace_dir, embedder)?.with_ttl(config.ttl.clone()),
+            ))
+        }
+        "markdown" | "none" => Ok(Box::new(
+            MarkdownMemory::new(workspace_dir).with_ttl(config.ttl.clone()),
+        )),
         other => {
             tracing::warn!("Unknown memory backend '{other}', falling back to markdown");
-            Ok(Box::new(MarkdownMemory::new(workspace_dir)))
+            Ok(Box::new(
+                MarkdownMemory::new(workspace_dir).with_ttl(config.ttl.clone()),
+            ))
         }
     }
 }
@@ -82,6 +123,35 @@ mod tests {
         assert_eq!(mem.name(), "markdown");
     }
 
+    #[test]
+    fn factory_vector() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            backend: "vector".into(),
+            ..MemoryConfig::default()
+        };
+        let mem = create_memory(&cfg, tmp.path(), None).unwrap();
+        assert_eq!(mem.name(), "vector");
+    }
+
+    #[tokio::test]
+    async fn factory_encrypts_when_key_configured() {
+        let tmp = TempDir::new().unwrap();
+        let cfg = MemoryConfig {
+            encryption_key: Some("ab".repeat(32)),
+            ..MemoryConfig::default()
+        };
+        let mem = create_memory(&cfg, tmp.path(), None).unwrap();
+        assert_eq!(mem.name(), "sqlite");
+        mem.store("k", "secret", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert_eq!(mem.get("k").await.unwrap().unwrap().content, "secret");
+
+        let plain = create_memory(&MemoryConfig::default(), tmp.path(), None).unwrap();
+        assert_ne!(plain.get("k").await.unwrap().unwrap().content, "secret");
+    }
+
     #[test]
     fn factory_none_falls_back_to_markdown() {
         let tmp = TempDir::new().unwrap();
@@ -0,0 +1,157 @@
+use crate::config::MemoryConfig;
+use crate::providers::Provider;
+use crate::util::truncate_with_ellipsis;
+
+const SUMMARY_PROMPT: &str = "You summarize an AI assistant's reply for its daily memory \
+log. Reply with a single line stating what the reply said or did, nothing else.";
+
+/// How an assistant response is condensed before it is auto-saved to the
+/// Daily log (`memory.summarize_saves`, `memory.save_summary_chars`)
+#[derive(Debug, Clone, Copy)]
+pub struct SaveSummary {
+    /// Ask the provider for a one-line summary instead of extracting one
+    llm: bool,
+    max_chars: usize,
+}
+
+impl SaveSummary {
+    pub fn from_config(config: &MemoryConfig) -> Self {
+        Self {
+            llm: config.summarize_saves,
+            max_chars: config.save_summary_chars,
+        }
+    }
+
+    /// The summary to store for `response`. If the provider fails to
+    /// summarize it, the response is truncated to `max_chars` instead.
+    pub async fn summarize(&self, provider: &dyn Provider, model: &str, response: &str) -> String {
+        if !self.llm {
+            return first_sentence(response, self.max_chars);
+        }
+        match summarize_with_provider(provider, model, response).await {
+            Ok(summary) => truncate_with_ellipsis(&summary, self.max_chars),
+            Err(e) => {
+                tracing::warn!("Response summary failed, saving it truncated: {e}");
+                truncate_with_ellipsis(response, self.max_chars)
+            }
+        }
+    }
+}
+
+/// The first sentence (or first line, if that ends sooner) of `response`,
+/// cut to `max_chars`
+pub fn first_sentence(response: &str, max_chars: usize) -> String {
+    let text = response.trim();
+    let mut end = text.len();
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\n' {
+            end = i;
+            break;
+        }
+        if matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|(_, next)| next.is_whitespace())
+        {
+            end = i + c.len_utf8();
+            break;
+        }
+    }
+    truncate_with_ellipsis(text[..end].trim_end(), max_chars)
+}
+
+/// Ask the provider for a one-line summary of `response`
+async fn summarize_with_provider(
+    provider: &dyn Provider,
+    model: &str,
+    response: &str,
+) -> anyhow::Result<String> {
+    let summary = provider
+        .chat_with_system(Some(SUMMARY_PROMPT), response, model, 0.2)
+        .await?;
+    let line = summary.trim().lines().next().unwrap_or_default().trim();
+    if line.is_empty() {
+        anyhow::bail!("provider returned an empty summary");
+    }
+    Ok(line.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    /// Returns a canned summary, or fails when `fail` is set.
+    struct SummaryProvider {
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Provider for SummaryProvider {
+        async fn chat_with_system(
+            &self,
+            system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            if self.fail {
+                anyhow::bail!("provider unavailable");
+            }
+            assert_eq!(system_prompt, Some(SUMMARY_PROMPT));
+            Ok("Explained how to rotate the API key.\nSecond line".into())
+        }
+    }
+
+    const RESPONSE: &str = "You can rotate the key in the dashboard. Open Settings, then \
+        choose API keys! Old keys stop working after an hour.";
+
+    fn summary(llm: bool, max_chars: usize) -> SaveSummary {
+        SaveSummary { llm, max_chars }
+    }
+
+    #[test]
+    fn first_sentence_of_multi_sentence_response() {
+        assert_eq!(
+            first_sentence(RESPONSE, 100),
+            "You can rotate the key in the dashboard."
+        );
+        assert_eq!(first_sentence("  Done!  Anything else?", 100), "Done!");
+        // A period inside a number or name doesn't end the sentence
+        assert_eq!(
+            first_sentence("Version 1.2 ships Friday. Tell the team.", 100),
+            "Version 1.2 ships Friday."
+        );
+    }
+
+    #[test]
+    fn first_sentence_stops_at_line_break_and_length() {
+        assert_eq!(
+            first_sentence("Here is the plan:\n1. Build\n2. Ship", 100),
+            "Here is the plan:"
+        );
+        assert_eq!(first_sentence(RESPONSE, 12), "You can rota...");
+    }
+
+    #[tokio::test]
+    async fn llm_summary_keeps_first_line() {
+        let saved = summary(true, 100)
+            .summarize(&SummaryProvider { fail: false }, "m", RESPONSE)
+            .await;
+        assert_eq!(saved, "Explained how to rotate the API key.");
+    }
+
+    #[tokio::test]
+    async fn failed_llm_summary_falls_back_to_truncation() {
+        let saved = summary(true, 20)
+            .summarize(&SummaryProvider { fail: true }, "m", RESPONSE)
+            .await;
+        assert_eq!(saved, truncate_with_ellipsis(RESPONSE, 20));
+    }
+
+    #[tokio::test]
+    async fn extractive_summary_skips_the_provider() {
+        let saved = summary(false, 100)
+            .summarize(&SummaryProvider { fail: true }, "m", RESPONSE)
+            .await;
+        assert_eq!(saved, "You can rotate the key in the dashboard.");
+    }
+}
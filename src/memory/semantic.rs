@@ -0,0 +1,566 @@
+use super::embeddings::EmbeddingProvider;
+use super::traits::{self, Memory, MemoryCategory, MemoryEntry};
+use super::vector;
+use crate::config::MemoryTtlConfig;
+use async_trait::async_trait;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Vector-embedding memory — recall ranks entries by meaning, not keywords
+///
+/// Every stored entry is embedded and recall returns the entries whose
+/// embeddings are closest (cosine similarity) to the query's, so "my dog's
+/// name" finds "pet: Rex".
+///
+/// Layout:
+///   workspace/memory/vectors.json — entries with their embeddings
+///
+/// Entries without an embedding (stored while the embedder was unavailable,
+/// or written before this backend existed) are embedded on the next recall.
+pub struct VectorMemory {
+    path: PathBuf,
+    workspace_dir: PathBuf,
+    embedder: Arc<dyn EmbeddingProvider>,
+    entries: Mutex<Vec<StoredEntry>>,
+    ttl: MemoryTtlConfig,
+}
+
+/// On-disk record: the entry plus its embedding, if computed yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StoredEntry {
+    #[serde(flatten)]
+    entry: MemoryEntry,
+    #[serde(default)]
+    embedding: Option<Vec<f32>>,
+}
+
+impl VectorMemory {
+    pub fn new(workspace_dir: &Path, embedder: Arc<dyn EmbeddingProvider>) -> anyhow::Result<Self> {
+        let path = workspace_dir.join("memory").join("vectors.json");
+        let entries = if path.exists() {
+            let raw = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&raw).map_err(|e| {
+                anyhow::anyhow!("Invalid vector memory file {}: {e}", path.display())
+            })?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            path,
+            workspace_dir: workspace_dir.to_path_buf(),
+            embedder,
+            entries: Mutex::new(entries),
+            ttl: MemoryTtlConfig::default(),
+        })
+    }
+
+    /// Stamp newly stored entries with an expiry per `ttl`
+    pub fn with_ttl(mut self, ttl: MemoryTtlConfig) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn lock(&self) -> anyhow::Result<std::sync::MutexGuard<'_, Vec<StoredEntry>>> {
+        self.entries
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))
+    }
+
+    /// Write all entries to disk (via a temp file, so a crash never leaves
+    /// a truncated store behind)
+    fn persist(&self, entries: &[StoredEntry]) -> anyhow::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(entries)?)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Embed entries that have no vector yet and save the result
+    async fn embed_missing(&self) -> anyhow::Result<()> {
+        let missing: Vec<(String, String)> = self
+            .lock()?
+            .iter()
+            .filter(|stored| stored.embedding.is_none() && !stored.entry.is_expired())
+            .map(|stored| (stored.entry.id.clone(), stored.entry.content.clone()))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let texts: Vec<&str> = missing
+            .iter()
+            .map(|(_, content)| content.as_str())
+            .collect();
+        let embeddings = self.embedder.embed(&texts).await?;
+        if embeddings.len() != missing.len() {
+            anyhow::bail!(
+                "Embedding provider returned {} vectors for {} entries",
+                embeddings.len(),
+                missing.len()
+            );
+        }
+
+        let mut entries = self.lock()?;
+        for ((id, _), embedding) in missing.iter().zip(embeddings) {
+            // The entry may have been replaced or forgotten while embedding
+            if let Some(stored) = entries
+                .iter_mut()
+                .find(|stored| &stored.entry.id == id && stored.embedding.is_none())
+            {
+                stored.embedding = Some(embedding);
+            }
+        }
+        tracing::debug!(
+            count = missing.len(),
+            "Embedded memory entries missing vectors"
+        );
+        self.persist(&entries)
+    }
+
+    /// Rank entries in `categories` (all if empty) by cosine similarity to
+    /// `query`, best first, dropping entries with no similarity at all
+    fn rank(
+        entries: &[StoredEntry],
+        query: &[f32],
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> Vec<MemoryEntry> {
+        let mut scored: Vec<MemoryEntry> = entries
+            .iter()
+            .filter(|stored| !stored.entry.is_expired())
+            .filter(|stored| categories.is_empty() || categories.contains(&stored.entry.category))
+            .filter_map(|stored| {
+                let similarity = vector::cosine_similarity(stored.embedding.as_deref()?, query);
+                (similarity > 0.0).then(|| MemoryEntry {
+                    score: Some(f64::from(similarity)),
+                    ..stored.entry.clone()
+                })
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scored.truncate(limit);
+        scored
+    }
+}
+
+#[async_trait]
+impl Memory for VectorMemory {
+    fn name(&self) -> &str {
+        "vector"
+    }
+
+    async fn store(
+        &self,
+        key: &str,
+        content: &str,
+        category: MemoryCategory,
+    ) -> anyhow::Result<()> {
+        // Compute embedding (async, before lock); retried lazily on recall
+        let embedding = match self.embedder.embed_one(content).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                tracing::warn!("Embedding memory entry '{key}' failed, deferring: {e}");
+                None
+            }
+        };
+
+        let mut entries = self.lock()?;
+        let entry = MemoryEntry {
+            id: Uuid::new_v4().to_string(),
+            key: key.to_string(),
+            content: content.to_string(),
+            expires_at: traits::expiry_for(&self.ttl, &category),
+            category,
+            timestamp: Local::now().to_rfc3339(),
+            session_id: None,
+            score: None,
+        };
+        match entries.iter_mut().find(|stored| stored.entry.key == key) {
+            Some(stored) => {
+                stored.entry = MemoryEntry {
+                    id: stored.entry.id.clone(),
+                    ..entry
+                };
+                stored.embedding = embedding;
+            }
+            None => entries.push(StoredEntry { entry, embedding }),
+        }
+        self.persist(&entries)
+    }
+
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
+        if query.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        if let Err(e) = self.embed_missing().await {
+            tracing::warn!("Embedding pending memory entries failed: {e}");
+        }
+        let query_embedding = self.embedder.embed_one(query).await?;
+
+        let entries = self.lock()?;
+        Ok(Self::rank(&entries, &query_embedding, limit, categories))
+    }
+
+    async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>> {
+        Ok(self
+            .lock()?
+            .iter()
+            .find(|stored| stored.entry.key == key && !stored.entry.is_expired())
+            .map(|stored| stored.entry.clone()))
+    }
+
+    async fn list(&self, category: Option<&MemoryCategory>) -> anyhow::Result<Vec<MemoryEntry>> {
+        Ok(self
+            .lock()?
+            .iter()
+            .filter(|stored| !stored.entry.is_expired())
+            .filter(|stored| category.is_none_or(|cat| &stored.entry.category == cat))
+            .map(|stored| stored.entry.clone())
+            .collect())
+    }
+
+    async fn forget(&self, key: &str) -> anyhow::Result<bool> {
+        let mut entries = self.lock()?;
+        let before = entries.len();
+        entries.retain(|stored| stored.entry.key != key);
+        if entries.len() == before {
+            return Ok(false);
+        }
+        self.persist(&entries)?;
+        Ok(true)
+    }
+
+    async fn count(&self) -> anyhow::Result<usize> {
+        Ok(self
+            .lock()?
+            .iter()
+            .filter(|stored| !stored.entry.is_expired())
+            .count())
+    }
+
+    async fn import(&self, entries: Vec<MemoryEntry>, merge: bool) -> anyhow::Result<usize> {
+        let imported = entries.len();
+        let mut stored_entries = self.lock()?;
+        if !merge {
+            stored_entries.clear();
+        }
+        // Vectors are computed lazily on the next recall
+        for entry in entries {
+            let entry = MemoryEntry {
+                score: None,
+                ..entry
+            };
+            match stored_entries
+                .iter_mut()
+                .find(|stored| stored.entry.key == entry.key)
+            {
+                Some(stored) => {
+                    stored.entry = entry;
+                    stored.embedding = None;
+                }
+                None => stored_entries.push(StoredEntry {
+                    entry,
+                    embedding: None,
+                }),
+            }
+        }
+        self.persist(&stored_entries)?;
+        Ok(imported)
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        let mut entries = self.lock()?;
+        let before = entries.len();
+        entries.retain(|stored| !stored.entry.is_expired());
+        let purged = before - entries.len();
+        if purged > 0 {
+            self.persist(&entries)?;
+        }
+        Ok(purged)
+    }
+
+    async fn health_check(&self) -> bool {
+        self.workspace_dir.exists()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Maps known texts to fixed vectors; anything else is an error.
+    struct FixedEmbedding {
+        vectors: Vec<(&'static str, Vec<f32>)>,
+        calls: Mutex<usize>,
+    }
+
+    impl FixedEmbedding {
+        fn new() -> Self {
+            Self {
+                vectors: vec![
+                    ("pet: Rex", vec![0.9, 0.1, 0.0]),
+                    ("favorite color: blue", vec![0.0, 1.0, 0.1]),
+                    ("works at Acme", vec![0.1, 0.2, 0.9]),
+                    ("my dog's name", vec![1.0, 0.0, 0.1]),
+                    ("where do I work", vec![0.0, 0.3, 1.0]),
+                ],
+                calls: Mutex::new(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for FixedEmbedding {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        async fn embed(&self, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+            *self.calls.lock().unwrap() += 1;
+            texts
+                .iter()
+                .map(|text| {
+                    self.vectors
+                        .iter()
+                        .find(|(known, _)| known == text)
+                        .map(|(_, v)| v.clone())
+                        .ok_or_else(|| anyhow::anyhow!("no vector for '{text}'"))
+                })
+                .collect()
+        }
+    }
+
+    async fn seeded(tmp: &TempDir) -> VectorMemory {
+        let mem = VectorMemory::new(tmp.path(), Arc::new(FixedEmbedding::new())).unwrap();
+        mem.store("pet", "pet: Rex", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("color", "favorite color: blue", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("job", "works at Acme", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem
+    }
+
+    #[test]
+    fn rank_orders_by_cosine_similarity() {
+        let entry = |key: &str, embedding: Vec<f32>| StoredEntry {
+            entry: MemoryEntry {
+                id: key.into(),
+                key: key.into(),
+                content: key.into(),
+                category: MemoryCategory::Core,
+                timestamp: String::new(),
+                session_id: None,
+                score: None,
+                expires_at: None,
+            },
+            embedding: Some(embedding),
+        };
+        let entries = vec![
+            entry("far", vec![0.0, 1.0, 0.0]),
+            entry("exact", vec![1.0, 0.0, 0.0]),
+            entry("near", vec![0.8, 0.6, 0.0]),
+            entry("opposite", vec![-1.0, 0.0, 0.0]),
+        ];
+
+        let ranked = VectorMemory::rank(&entries, &[1.0, 0.0, 0.0], 10, &[]);
+        let keys: Vec<&str> = ranked.iter().map(|e| e.key.as_str()).collect();
+        // Orthogonal and opposite vectors have no similarity and are dropped
+        assert_eq!(keys, ["exact", "near"]);
+        assert!((ranked[0].score.unwrap() - 1.0).abs() < 1e-6);
+        assert!((ranked[1].score.unwrap() - 0.8).abs() < 1e-6);
+
+        let top = VectorMemory::rank(&entries, &[1.0, 0.0, 0.0], 1, &[]);
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].key, "exact");
+    }
+
+    #[tokio::test]
+    async fn recall_finds_entries_by_meaning() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+
+        let results = mem.recall("my dog's name", 1).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].content, "pet: Rex");
+
+        let results = mem.recall("where do I work", 3).await.unwrap();
+        assert_eq!(results[0].key, "job");
+    }
+
+    #[tokio::test]
+    async fn recall_in_returns_only_requested_categories() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+
+        let results = mem
+            .recall_in("where do I work", 5, &[MemoryCategory::Core])
+            .await
+            .unwrap();
+        assert!(!results.is_empty());
+        assert!(results.iter().all(|e| e.category == MemoryCategory::Core));
+
+        let daily = mem
+            .recall_in("where do I work", 5, &[MemoryCategory::Daily])
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].key, "job");
+    }
+
+    #[tokio::test]
+    async fn export_import_round_trip() {
+        let tmp = TempDir::new().unwrap();
+        let source = seeded(&tmp).await;
+        let mut exported = source.export().await.unwrap();
+
+        let tmp2 = TempDir::new().unwrap();
+        let target = VectorMemory::new(tmp2.path(), Arc::new(FixedEmbedding::new())).unwrap();
+        target
+            .store("stale", "favorite color: blue", MemoryCategory::Core)
+            .await
+            .unwrap();
+        assert_eq!(target.import(exported.clone(), false).await.unwrap(), 3);
+
+        let mut imported = target.export().await.unwrap();
+        exported.sort_by(|a, b| a.key.cmp(&b.key));
+        imported.sort_by(|a, b| a.key.cmp(&b.key));
+        assert_eq!(
+            serde_json::to_value(&imported).unwrap(),
+            serde_json::to_value(&exported).unwrap()
+        );
+        // Imported entries are embedded on demand
+        let results = target.recall("my dog's name", 1).await.unwrap();
+        assert_eq!(results[0].key, "pet");
+    }
+
+    #[tokio::test]
+    async fn store_replaces_existing_key() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+        mem.store("pet", "works at Acme", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        assert_eq!(mem.count().await.unwrap(), 3);
+        assert_eq!(
+            mem.get("pet").await.unwrap().unwrap().content,
+            "works at Acme"
+        );
+    }
+
+    #[tokio::test]
+    async fn entries_and_vectors_survive_reopen() {
+        let tmp = TempDir::new().unwrap();
+        drop(seeded(&tmp).await);
+
+        let embedder = Arc::new(FixedEmbedding::new());
+        let mem = VectorMemory::new(tmp.path(), embedder.clone()).unwrap();
+        assert_eq!(mem.count().await.unwrap(), 3);
+        assert_eq!(
+            mem.list(Some(&MemoryCategory::Daily)).await.unwrap().len(),
+            1
+        );
+
+        let results = mem.recall("my dog's name", 1).await.unwrap();
+        assert_eq!(results[0].key, "pet");
+        // Only the query was embedded; stored vectors came from disk
+        assert_eq!(*embedder.calls.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn entries_without_vectors_are_embedded_on_recall() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("memory")).unwrap();
+        std::fs::write(
+            tmp.path().join("memory").join("vectors.json"),
+            r#"[{"id": "1", "key": "pet", "content": "pet: Rex", "category": "core",
+                 "timestamp": "2025-01-01T00:00:00Z", "session_id": null, "score": null}]"#,
+        )
+        .unwrap();
+
+        let mem = VectorMemory::new(tmp.path(), Arc::new(FixedEmbedding::new())).unwrap();
+        let results = mem.recall("my dog's name", 5).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "pet");
+
+        let raw = std::fs::read_to_string(tmp.path().join("memory").join("vectors.json")).unwrap();
+        assert!(raw.contains("\"embedding\":[0.9"));
+    }
+
+    #[tokio::test]
+    async fn store_defers_embedding_when_embedder_fails() {
+        let tmp = TempDir::new().unwrap();
+        let mem = VectorMemory::new(tmp.path(), Arc::new(FixedEmbedding::new())).unwrap();
+        mem.store("note", "unknown text", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert!(mem.lock().unwrap()[0].embedding.is_none());
+    }
+
+    #[tokio::test]
+    async fn forget_removes_entry() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+        assert!(mem.forget("pet").await.unwrap());
+        assert!(!mem.forget("pet").await.unwrap());
+        assert_eq!(mem.count().await.unwrap(), 2);
+        assert!(mem
+            .recall("my dog's name", 5)
+            .await
+            .unwrap()
+            .iter()
+            .all(|e| e.key != "pet"));
+    }
+
+    #[tokio::test]
+    async fn expired_entries_are_skipped_and_purged() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+        mem.lock().unwrap()[0].entry.expires_at = Some("2000-01-01T00:00:00Z".into());
+
+        let results = mem.recall("my dog's name", 5).await.unwrap();
+        assert!(results.iter().all(|e| e.key != "pet"));
+        assert!(mem.get("pet").await.unwrap().is_none());
+        assert_eq!(mem.count().await.unwrap(), 2);
+
+        assert_eq!(mem.purge_expired().await.unwrap(), 1);
+        let reopened = VectorMemory::new(tmp.path(), Arc::new(FixedEmbedding::new())).unwrap();
+        assert_eq!(reopened.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn recall_empty_query_returns_nothing() {
+        let tmp = TempDir::new().unwrap();
+        let mem = seeded(&tmp).await;
+        assert!(mem.recall("  ", 5).await.unwrap().is_empty());
+    }
+}
@@ -1,6 +1,7 @@
 use super::embeddings::EmbeddingProvider;
-use super::traits::{Memory, MemoryCategory, MemoryEntry};
+use super::traits::{self, Memory, MemoryCategory, MemoryEntry};
 use super::vector;
+use crate::config::MemoryTtlConfig;
 use async_trait::async_trait;
 use chrono::Local;
 use rusqlite::{params, Connection};
@@ -8,6 +9,17 @@ use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
+/// SQL condition keeping rows whose `expires_at` has not passed
+/// (timestamps are stored as second-precision UTC, so they compare as text)
+const NOT_EXPIRED: &str =
+    "(expires_at IS NULL OR expires_at > strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))";
+
+/// SQL condition limiting rows to the JSON array of category names bound at
+/// parameter `index` (NULL matches every category)
+fn in_categories(index: usize) -> String {
+    format!("(?{index} IS NULL OR category IN (SELECT value FROM json_each(?{index})))")
+}
+
 /// SQLite-backed persistent memory — the brain
 ///
 /// Full-stack search engine:
@@ -23,6 +35,7 @@ pub struct SqliteMemory {
     vector_weight: f32,
     keyword_weight: f32,
     cache_max: usize,
+    ttl: MemoryTtlConfig,
 }
 
 impl SqliteMemory {
@@ -59,9 +72,16 @@ impl SqliteMemory {
             vector_weight,
             keyword_weight,
             cache_max,
+            ttl: MemoryTtlConfig::default(),
         })
     }
 
+    /// Stamp newly stored entries with an expiry per `ttl`
+    pub fn with_ttl(mut self, ttl: MemoryTtlConfig) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
     /// Initialize all tables: memories, FTS5, `embedding_cache`
     fn init_schema(conn: &Connection) -> anyhow::Result<()> {
         conn.execute_batch(
@@ -73,7 +93,8 @@ impl SqliteMemory {
                 category    TEXT NOT NULL DEFAULT 'core',
                 embedding   BLOB,
                 created_at  TEXT NOT NULL,
-                updated_at  TEXT NOT NULL
+                updated_at  TEXT NOT NULL,
+                expires_at  TEXT
             );
             CREATE INDEX IF NOT EXISTS idx_memories_category ON memories(category);
             CREATE INDEX IF NOT EXISTS idx_memories_key ON memories(key);
@@ -108,6 +129,19 @@ impl SqliteMemory {
             );
             CREATE INDEX IF NOT EXISTS idx_cache_accessed ON embedding_cache(accessed_at);",
         )?;
+
+        // Databases created before entry expiry lack the column
+        let has_expires_at: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('memories') WHERE name = 'expires_at'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_expires_at {
+            conn.execute_batch("ALTER TABLE memories ADD COLUMN expires_at TEXT;")?;
+        }
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_memories_expires ON memories(expires_at);",
+        )?;
         Ok(())
     }
 
@@ -205,11 +239,21 @@ impl SqliteMemory {
         Ok(Some(embedding))
     }
 
+    /// Category names as a JSON array for `in_categories`, or `None` for all
+    fn category_filter(categories: &[MemoryCategory]) -> Option<String> {
+        if categories.is_empty() {
+            return None;
+        }
+        let names: Vec<String> = categories.iter().map(Self::category_to_str).collect();
+        serde_json::to_string(&names).ok()
+    }
+
     /// FTS5 BM25 keyword search
     fn fts5_search(
         conn: &Connection,
         query: &str,
         limit: usize,
+        categories: Option<&str>,
     ) -> anyhow::Result<Vec<(String, f32)>> {
         // Escape FTS5 special chars and build query
         let fts_query: String = query
@@ -222,18 +266,21 @@ impl SqliteMemory {
             return Ok(Vec::new());
         }
 
-        let sql = "SELECT m.id, bm25(memories_fts) as score
-                   FROM memories_fts f
-                   JOIN memories m ON m.rowid = f.rowid
-                   WHERE memories_fts MATCH ?1
-                   ORDER BY score
-                   LIMIT ?2";
+        let sql = format!(
+            "SELECT m.id, bm25(memories_fts) as score
+             FROM memories_fts f
+             JOIN memories m ON m.rowid = f.rowid
+             WHERE memories_fts MATCH ?1 AND {NOT_EXPIRED} AND {}
+             ORDER BY score
+             LIMIT ?2",
+            in_categories(3)
+        );
 
-        let mut stmt = conn.prepare(sql)?;
+        let mut stmt = conn.prepare(&sql)?;
         #[allow(clippy::cast_possible_wrap)]
         let limit_i64 = limit as i64;
 
-        let rows = stmt.query_map(params![fts_query, limit_i64], |row| {
+        let rows = stmt.query_map(params![fts_query, limit_i64, categories], |row| {
             let id: String = row.get(0)?;
             let score: f64 = row.get(1)?;
             // BM25 returns negative scores (lower = better), negate for ranking
@@ -253,11 +300,15 @@ impl SqliteMemory {
         conn: &Connection,
         query_embedding: &[f32],
         limit: usize,
+        categories: Option<&str>,
     ) -> anyhow::Result<Vec<(String, f32)>> {
-        let mut stmt =
-            conn.prepare("SELECT id, embedding FROM memories WHERE embedding IS NOT NULL")?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, embedding FROM memories
+             WHERE embedding IS NOT NULL AND {NOT_EXPIRED} AND {}",
+            in_categories(1)
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(params![categories], |row| {
             let id: String = row.get(0)?;
             let blob: Vec<u8> = row.get(1)?;
             Ok((id, blob))
@@ -353,27 +404,36 @@ impl Memory for SqliteMemory {
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
         let now = Local::now().to_rfc3339();
+        let expires_at = traits::expiry_for(&self.ttl, &category);
         let cat = Self::category_to_str(&category);
         let id = Uuid::new_v4().to_string();
 
         conn.execute(
-            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
              ON CONFLICT(key) DO UPDATE SET
                 content = excluded.content,
                 category = excluded.category,
                 embedding = excluded.embedding,
-                updated_at = excluded.updated_at",
-            params![id, key, content, cat, embedding_bytes, now, now],
+                updated_at = excluded.updated_at,
+                expires_at = excluded.expires_at",
+            params![id, key, content, cat, embedding_bytes, now, now, expires_at],
         )?;
 
         Ok(())
     }
 
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+    #[allow(clippy::too_many_lines)]
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> anyhow::Result<Vec<MemoryEntry>> {
         if query.trim().is_empty() {
             return Ok(Vec::new());
         }
+        let category_filter = Self::category_filter(categories);
 
         // Compute query embedding (async, before lock)
         let query_embedding = self.get_or_compute_embedding(query).await?;
@@ -384,11 +444,14 @@ impl Memory for SqliteMemory {
             .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
 
         // FTS5 BM25 keyword search
-        let keyword_results = Self::fts5_search(&conn, query, limit * 2).unwrap_or_default();
+        let keyword_results =
+            Self::fts5_search(&conn, query, limit * 2, category_filter.as_deref())
+                .unwrap_or_default();
 
         // Vector similarity search (if embeddings available)
         let vector_results = if let Some(ref qe) = query_embedding {
-            Self::vector_search(&conn, qe, limit * 2).unwrap_or_default()
+            Self::vector_search(&conn, qe, limit * 2, category_filter.as_deref())
+                .unwrap_or_default()
         } else {
             Vec::new()
         };
@@ -418,9 +481,10 @@ impl Memory for SqliteMemory {
         // Fetch full entries for merged results
         let mut results = Vec::new();
         for scored in &merged {
-            let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories WHERE id = ?1",
-            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, key, content, category, created_at, expires_at FROM memories
+                 WHERE id = ?1 AND {NOT_EXPIRED}"
+            ))?;
             if let Ok(entry) = stmt.query_row(params![scored.id], |row| {
                 Ok(MemoryEntry {
                     id: row.get(0)?,
@@ -430,6 +494,7 @@ impl Memory for SqliteMemory {
                     timestamp: row.get(4)?,
                     session_id: None,
                     score: Some(f64::from(scored.final_score)),
+                    expires_at: row.get(5)?,
                 })
             }) {
                 results.push(entry);
@@ -450,10 +515,11 @@ impl Memory for SqliteMemory {
                     .collect();
                 let where_clause = conditions.join(" OR ");
                 let sql = format!(
-                    "SELECT id, key, content, category, created_at FROM memories
-                     WHERE {where_clause}
+                    "SELECT id, key, content, category, created_at, expires_at FROM memories
+                     WHERE ({where_clause}) AND {NOT_EXPIRED} AND {}
                      ORDER BY updated_at DESC
                      LIMIT ?{}",
+                    in_categories(keywords.len() * 2 + 2),
                     keywords.len() * 2 + 1
                 );
                 let mut stmt = conn.prepare(&sql)?;
@@ -464,6 +530,7 @@ impl Memory for SqliteMemory {
                 }
                 #[allow(clippy::cast_possible_wrap)]
                 param_values.push(Box::new(limit as i64));
+                param_values.push(Box::new(category_filter.clone()));
                 let params_ref: Vec<&dyn rusqlite::types::ToSql> =
                     param_values.iter().map(AsRef::as_ref).collect();
                 let rows = stmt.query_map(params_ref.as_slice(), |row| {
@@ -475,6 +542,7 @@ impl Memory for SqliteMemory {
                         timestamp: row.get(4)?,
                         session_id: None,
                         score: Some(1.0),
+                        expires_at: row.get(5)?,
                     })
                 })?;
                 for row in rows {
@@ -493,9 +561,10 @@ impl Memory for SqliteMemory {
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
 
-        let mut stmt = conn.prepare(
-            "SELECT id, key, content, category, created_at FROM memories WHERE key = ?1",
-        )?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, key, content, category, created_at, expires_at FROM memories
+             WHERE key = ?1 AND {NOT_EXPIRED}"
+        ))?;
 
         let mut rows = stmt.query_map(params![key], |row| {
             Ok(MemoryEntry {
@@ -506,6 +575,7 @@ impl Memory for SqliteMemory {
                 timestamp: row.get(4)?,
                 session_id: None,
                 score: None,
+                expires_at: row.get(5)?,
             })
         })?;
 
@@ -532,24 +602,25 @@ impl Memory for SqliteMemory {
                 timestamp: row.get(4)?,
                 session_id: None,
                 score: None,
+                expires_at: row.get(5)?,
             })
         };
 
         if let Some(cat) = category {
             let cat_str = Self::category_to_str(cat);
-            let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories
-                 WHERE category = ?1 ORDER BY updated_at DESC",
-            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, key, content, category, created_at, expires_at FROM memories
+                 WHERE category = ?1 AND {NOT_EXPIRED} ORDER BY updated_at DESC"
+            ))?;
             let rows = stmt.query_map(params![cat_str], row_mapper)?;
             for row in rows {
                 results.push(row?);
             }
         } else {
-            let mut stmt = conn.prepare(
-                "SELECT id, key, content, category, created_at FROM memories
-                 ORDER BY updated_at DESC",
-            )?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT id, key, content, category, created_at, expires_at FROM memories
+                 WHERE {NOT_EXPIRED} ORDER BY updated_at DESC"
+            ))?;
             let rows = stmt.query_map([], row_mapper)?;
             for row in rows {
                 results.push(row?);
@@ -573,11 +644,77 @@ impl Memory for SqliteMemory {
             .conn
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
-        let count: i64 = conn.query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))?;
+        let count: i64 = conn.query_row(
+            &format!("SELECT COUNT(*) FROM memories WHERE {NOT_EXPIRED}"),
+            [],
+            |row| row.get(0),
+        )?;
         #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
         Ok(count as usize)
     }
 
+    async fn import(&self, entries: Vec<MemoryEntry>, merge: bool) -> anyhow::Result<usize> {
+        // Compute embeddings (async, before lock); a failure leaves the entry
+        // for `reindex` instead of aborting the import
+        let mut embeddings = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            let embedding = self
+                .get_or_compute_embedding(&entry.content)
+                .await
+                .ok()
+                .flatten()
+                .map(|emb| vector::vec_to_bytes(&emb));
+            embeddings.push(embedding);
+        }
+
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let tx = conn.transaction()?;
+        if !merge {
+            tx.execute("DELETE FROM memories", [])?;
+        }
+        for (entry, embedding) in entries.iter().zip(embeddings) {
+            let id = if entry.id.is_empty() {
+                Uuid::new_v4().to_string()
+            } else {
+                entry.id.clone()
+            };
+            tx.execute(
+                "INSERT INTO memories (id, key, content, category, embedding, created_at, updated_at, expires_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6, ?7)
+                 ON CONFLICT(key) DO UPDATE SET
+                    content = excluded.content,
+                    category = excluded.category,
+                    embedding = excluded.embedding,
+                    created_at = excluded.created_at,
+                    updated_at = excluded.updated_at,
+                    expires_at = excluded.expires_at",
+                params![
+                    id,
+                    entry.key,
+                    entry.content,
+                    Self::category_to_str(&entry.category),
+                    embedding,
+                    entry.timestamp,
+                    entry.expires_at
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(entries.len())
+    }
+
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {e}"))?;
+        let purged = conn.execute(&format!("DELETE FROM memories WHERE NOT {NOT_EXPIRED}"), [])?;
+        Ok(purged)
+    }
+
     async fn health_check(&self) -> bool {
         self.conn
             .lock()
@@ -624,6 +761,184 @@ mod tests {
         assert_eq!(entry.category, MemoryCategory::Core);
     }
 
+    fn expire(mem: &SqliteMemory, key: &str) {
+        mem.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE memories SET expires_at = '2000-01-01T00:00:00Z' WHERE key = ?1",
+                params![key],
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sqlite_store_stamps_expiry_from_ttl() {
+        let tmp = TempDir::new().unwrap();
+        let mut ttl = MemoryTtlConfig::default();
+        ttl.categories.insert("conversation".into(), 7);
+        let mem = SqliteMemory::new(tmp.path()).unwrap().with_ttl(ttl);
+
+        mem.store("msg", "hello", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("fact", "likes Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let msg = mem.get("msg").await.unwrap().unwrap();
+        assert!(msg.expires_at.is_some());
+        assert!(!msg.is_expired());
+        assert!(mem.get("fact").await.unwrap().unwrap().expires_at.is_none());
+    }
+
+    #[tokio::test]
+    async fn sqlite_expired_entries_are_skipped_and_purged() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("old", "stale Rust note", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        mem.store("new", "fresh Rust note", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+        expire(&mem, "old");
+
+        let results = mem.recall("Rust note", 10).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "new");
+        assert!(mem.get("old").await.unwrap().is_none());
+        assert_eq!(mem.list(None).await.unwrap().len(), 1);
+        assert_eq!(mem.count().await.unwrap(), 1);
+
+        assert_eq!(mem.purge_expired().await.unwrap(), 1);
+        assert_eq!(mem.purge_expired().await.unwrap(), 0);
+        let total: i64 = mem
+            .conn
+            .lock()
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM memories", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(total, 1);
+    }
+
+    #[test]
+    fn sqlite_adds_expires_at_to_existing_database() {
+        let tmp = TempDir::new().unwrap();
+        let db_path = tmp.path().join("memory").join("brain.db");
+        std::fs::create_dir_all(db_path.parent().unwrap()).unwrap();
+        Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE memories (
+                    id TEXT PRIMARY KEY, key TEXT NOT NULL UNIQUE, content TEXT NOT NULL,
+                    category TEXT NOT NULL DEFAULT 'core', embedding BLOB,
+                    created_at TEXT NOT NULL, updated_at TEXT NOT NULL
+                );",
+            )
+            .unwrap();
+
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        let has_column: bool = mem
+            .conn
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('memories') WHERE name = 'expires_at'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(has_column);
+    }
+
+    fn sorted(
+        mut entries: Vec<MemoryEntry>,
+    ) -> Vec<(
+        String,
+        String,
+        String,
+        String,
+        MemoryCategory,
+        Option<String>,
+    )> {
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        entries
+            .into_iter()
+            .map(|e| {
+                (
+                    e.id,
+                    e.key,
+                    e.content,
+                    e.timestamp,
+                    e.category,
+                    e.expires_at,
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn sqlite_export_import_round_trip() {
+        let (_tmp, source) = temp_sqlite();
+        source
+            .store("lang", "Prefers Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        source
+            .store("standup", "Moved to 10am", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        source
+            .store(
+                "recipe",
+                "Lasagna",
+                MemoryCategory::Custom("recipes".into()),
+            )
+            .await
+            .unwrap();
+        let exported = source.export().await.unwrap();
+        assert_eq!(exported.len(), 3);
+
+        let (_tmp2, target) = temp_sqlite();
+        assert_eq!(target.import(exported.clone(), false).await.unwrap(), 3);
+
+        assert_eq!(sorted(target.export().await.unwrap()), sorted(exported));
+        let results = target.recall("Rust", 5).await.unwrap();
+        assert_eq!(results[0].key, "lang");
+    }
+
+    #[tokio::test]
+    async fn sqlite_import_replace_clears_and_merge_upserts() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("old", "to be replaced", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("shared", "local version", MemoryCategory::Core)
+            .await
+            .unwrap();
+        let incoming = vec![MemoryEntry {
+            id: String::new(),
+            key: "shared".into(),
+            content: "imported version".into(),
+            category: MemoryCategory::Daily,
+            timestamp: "2025-01-02T03:04:05+00:00".into(),
+            session_id: None,
+            score: None,
+            expires_at: None,
+        }];
+
+        mem.import(incoming.clone(), true).await.unwrap();
+        assert_eq!(mem.count().await.unwrap(), 2);
+        let shared = mem.get("shared").await.unwrap().unwrap();
+        assert_eq!(shared.content, "imported version");
+        assert_eq!(shared.category, MemoryCategory::Daily);
+        assert_eq!(shared.timestamp, "2025-01-02T03:04:05+00:00");
+
+        mem.import(incoming, false).await.unwrap();
+        assert_eq!(mem.count().await.unwrap(), 1);
+        assert!(mem.get("old").await.unwrap().is_none());
+    }
+
     #[tokio::test]
     async fn sqlite_store_upsert() {
         let (_tmp, mem) = temp_sqlite();
@@ -840,6 +1155,84 @@ mod tests {
         assert!(results[0].content.contains("quick"));
     }
 
+    #[tokio::test]
+    async fn recall_in_returns_only_requested_categories() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store(
+            "fact",
+            "Rust is the user's main language",
+            MemoryCategory::Core,
+        )
+        .await
+        .unwrap();
+        mem.store(
+            "chat",
+            "user asked about Rust macros",
+            MemoryCategory::Conversation,
+        )
+        .await
+        .unwrap();
+        mem.store("log", "reviewed Rust PR today", MemoryCategory::Daily)
+            .await
+            .unwrap();
+        mem.store(
+            "tip",
+            "Rust clippy tip",
+            MemoryCategory::Custom("tips".into()),
+        )
+        .await
+        .unwrap();
+
+        let core = mem
+            .recall_in("Rust", 10, &[MemoryCategory::Core])
+            .await
+            .unwrap();
+        assert_eq!(core.len(), 1);
+        assert_eq!(core[0].key, "fact");
+
+        let recent = mem
+            .recall_in(
+                "Rust",
+                10,
+                &[MemoryCategory::Daily, MemoryCategory::Conversation],
+            )
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+        assert!(recent
+            .iter()
+            .all(|e| e.category == MemoryCategory::Daily
+                || e.category == MemoryCategory::Conversation));
+
+        let custom = mem
+            .recall_in("Rust", 10, &[MemoryCategory::Custom("tips".into())])
+            .await
+            .unwrap();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].key, "tip");
+
+        assert_eq!(mem.recall("Rust", 10).await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn recall_in_filters_like_fallback() {
+        let (_tmp, mem) = temp_sqlite();
+        mem.store("a", "rustacean", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("b", "rustacean too", MemoryCategory::Conversation)
+            .await
+            .unwrap();
+
+        // "rusta" only matches via the LIKE fallback, not FTS5 tokens
+        let results = mem
+            .recall_in("rusta", 10, &[MemoryCategory::Conversation])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, "b");
+    }
+
     #[tokio::test]
     async fn recall_empty_query_returns_empty() {
         let (_tmp, mem) = temp_sqlite();
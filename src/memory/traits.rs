@@ -1,4 +1,8 @@
+use super::compact;
+use crate::config::MemoryTtlConfig;
+use crate::providers::Provider;
 use async_trait::async_trait;
+use chrono::{DateTime, SecondsFormat, Utc};
 use serde::{Deserialize, Serialize};
 
 /// A single memory entry
@@ -11,6 +15,28 @@ pub struct MemoryEntry {
     pub timestamp: String,
     pub session_id: Option<String>,
     pub score: Option<f64>,
+    /// When the entry stops being recalled (UTC RFC 3339); `None` never expires
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+impl MemoryEntry {
+    /// Whether the entry's expiry has passed
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.as_deref().is_some_and(is_past)
+    }
+}
+
+/// Expiry timestamp for an entry stored now under `category`, per `ttl`
+pub fn expiry_for(ttl: &MemoryTtlConfig, category: &MemoryCategory) -> Option<String> {
+    let days = ttl.days_for(&category.to_string())?;
+    let expires = Utc::now() + chrono::Duration::days(i64::from(days));
+    Some(expires.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Whether an RFC 3339 timestamp lies in the past (unparseable ones never do)
+pub fn is_past(timestamp: &str) -> bool {
+    DateTime::parse_from_rfc3339(timestamp).is_ok_and(|at| at <= Utc::now())
 }
 
 /// Memory categories for organization
@@ -27,6 +53,18 @@ pub enum MemoryCategory {
     Custom(String),
 }
 
+impl MemoryCategory {
+    /// Parse a category name as shown by `Display`; unknown names are custom.
+    pub fn parse(name: &str) -> Self {
+        match name.trim() {
+            "core" => Self::Core,
+            "daily" => Self::Daily,
+            "conversation" => Self::Conversation,
+            other => Self::Custom(other.to_string()),
+        }
+    }
+}
+
 impl std::fmt::Display for MemoryCategory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -48,8 +86,19 @@ pub trait Memory: Send + Sync {
     async fn store(&self, key: &str, content: &str, category: MemoryCategory)
         -> anyhow::Result<()>;
 
-    /// Recall memories matching a query (keyword search)
-    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>>;
+    /// Recall memories matching a query, across all categories
+    async fn recall(&self, query: &str, limit: usize) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.recall_in(query, limit, &[]).await
+    }
+
+    /// Recall memories matching a query from the given categories only
+    /// (an empty slice searches every category)
+    async fn recall_in(
+        &self,
+        query: &str,
+        limit: usize,
+        categories: &[MemoryCategory],
+    ) -> anyhow::Result<Vec<MemoryEntry>>;
 
     /// Get a specific memory by key
     async fn get(&self, key: &str) -> anyhow::Result<Option<MemoryEntry>>;
@@ -63,6 +112,59 @@ pub trait Memory: Send + Sync {
     /// Count total memories
     async fn count(&self) -> anyhow::Result<usize>;
 
+    /// Every live entry, for backup or moving memory to another machine
+    async fn export(&self) -> anyhow::Result<Vec<MemoryEntry>> {
+        self.list(None).await
+    }
+
+    /// Load exported entries, keeping their category and timestamp. Without
+    /// `merge` existing memory is cleared first; with it, entries upsert by
+    /// key. Returns the number of entries imported.
+    async fn import(&self, entries: Vec<MemoryEntry>, merge: bool) -> anyhow::Result<usize> {
+        let _ = (entries, merge);
+        anyhow::bail!("memory import not supported by the {} backend", self.name())
+    }
+
+    /// Physically remove expired entries, returning how many were removed.
+    /// Reads already skip them; this reclaims the space.
+    async fn purge_expired(&self) -> anyhow::Result<usize> {
+        Ok(0)
+    }
+
+    /// Roll Daily entries older than `older_than_days` up into one summary
+    /// written by `provider`, returning how many entries it replaced. On any
+    /// error the originals are left in place.
+    async fn compact(
+        &self,
+        provider: &dyn Provider,
+        model: &str,
+        older_than_days: u32,
+    ) -> anyhow::Result<usize> {
+        let daily = self.list(Some(&MemoryCategory::Daily)).await?;
+        let stale = compact::stale_daily_entries(daily, compact::cutoff_date(older_than_days));
+        if stale.len() < 2 {
+            return Ok(0);
+        }
+
+        let summary = compact::summarize(provider, model, &stale).await?;
+        let key = compact::summary_key(&stale);
+        self.store(&key, &summary, MemoryCategory::Daily).await?;
+
+        let mut replaced = 0;
+        for entry in stale.iter().filter(|e| e.key != key) {
+            if self.forget(&entry.key).await? {
+                replaced += 1;
+            }
+        }
+        Ok(replaced)
+    }
+
+    /// Persist anything still buffered. Called once on shutdown; backends
+    /// that write through need not override it.
+    async fn flush(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
     /// Health check
     async fn health_check(&self) -> bool;
 }
@@ -1,11 +1,15 @@
 use crate::config::Config;
-use crate::memory::{MarkdownMemory, Memory, MemoryCategory, SqliteMemory};
+use crate::memory::embeddings::create_embedding_provider;
+use crate::memory::{
+    encrypted, MarkdownMemory, Memory, MemoryCategory, SqliteMemory, VectorMemory,
+};
 use anyhow::{bail, Context, Result};
 use directories::UserDirs;
 use rusqlite::{Connection, OpenFlags, OptionalExtension};
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 struct SourceEntry {
@@ -112,8 +116,25 @@ async fn migrate_openclaw_memory(
 }
 
 fn target_memory_backend(config: &Config) -> Result<Box<dyn Memory>> {
+    let backend = unencrypted_target_backend(config)?;
+    encrypted::wrap_if_configured(&config.memory, &config.workspace_dir, backend)
+}
+
+fn unencrypted_target_backend(config: &Config) -> Result<Box<dyn Memory>> {
     match config.memory.backend.as_str() {
         "sqlite" => Ok(Box::new(SqliteMemory::new(&config.workspace_dir)?)),
+        "vector" => {
+            let embedder = create_embedding_provider(
+                &config.memory.embedding_provider,
+                config.api_key.as_deref(),
+                &config.memory.embedding_model,
+                config.memory.embedding_dimensions,
+            );
+            Ok(Box::new(VectorMemory::new(
+                &config.workspace_dir,
+                Arc::from(embedder),
+            )?))
+        }
         "markdown" | "none" => Ok(Box::new(MarkdownMemory::new(&config.workspace_dir))),
         other => {
             tracing::warn!(
@@ -0,0 +1,420 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use serde_json::{json, Map, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// JSON-lines observer — one structured object per event, for log shippers.
+///
+/// Every line has an `event` discriminator, an ISO-8601 `timestamp` and the
+/// run's `trace_id`. A new trace id is rolled when an agent starts after the
+/// previous run ended, so everything from one run shares an id.
+pub struct JsonObserver {
+    out: Mutex<Box<dyn Write + Send>>,
+    trace: Mutex<TraceState>,
+}
+
+struct TraceState {
+    trace_id: String,
+    run_ended: bool,
+}
+
+impl JsonObserver {
+    /// Write to stdout.
+    pub fn stdout() -> Self {
+        Self::with_writer(Box::new(std::io::stdout()))
+    }
+
+    /// Append to the file at `path`, creating it if needed.
+    pub fn to_file(path: &Path) -> anyhow::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::with_writer(Box::new(file)))
+    }
+
+    pub fn with_writer(out: Box<dyn Write + Send>) -> Self {
+        Self {
+            out: Mutex::new(out),
+            trace: Mutex::new(TraceState {
+                trace_id: new_trace_id(),
+                run_ended: false,
+            }),
+        }
+    }
+
+    /// Trace id for `event`, rolling a new one when a fresh run starts.
+    fn trace_id(&self, event: Option<&ObserverEvent>) -> String {
+        let mut trace = self
+            .trace
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match event {
+            Some(ObserverEvent::AgentStart { .. }) if trace.run_ended => {
+                trace.trace_id = new_trace_id();
+                trace.run_ended = false;
+            }
+            Some(ObserverEvent::AgentEnd { .. }) => trace.run_ended = true,
+            _ => {}
+        }
+        trace.trace_id.clone()
+    }
+
+    fn write_line(&self, event: &str, trace_id: String, fields: Value) {
+        let mut line = Map::new();
+        line.insert("event".into(), event.into());
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
+                .into(),
+        );
+        line.insert("trace_id".into(), trace_id.into());
+        if let Value::Object(fields) = fields {
+            line.extend(fields);
+        }
+
+        let mut out = self
+            .out
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let written = serde_json::to_writer(&mut *out, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|()| out.write_all(b"\n"));
+        if let Err(e) = written {
+            tracing::warn!("Failed to write JSON observer line: {e}");
+        }
+    }
+}
+
+fn new_trace_id() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+fn duration_ms(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+/// Stable `event` name and payload fields for an event.
+fn event_fields(event: &ObserverEvent) -> (&'static str, Value) {
+    match event {
+        ObserverEvent::AgentStart { provider, model } => (
+            "agent_start",
+            json!({ "provider": provider, "model": model }),
+        ),
+        ObserverEvent::AgentEnd {
+            duration,
+            tokens_used,
+        } => (
+            "agent_end",
+            json!({ "duration_ms": duration_ms(*duration), "tokens_used": tokens_used }),
+        ),
+        ObserverEvent::ToolStart {
+            name,
+            arguments_summary,
+        } => (
+            "tool_start",
+            json!({ "tool": name, "arguments": arguments_summary }),
+        ),
+        ObserverEvent::ToolEnd {
+            name,
+            success,
+            duration,
+        } => (
+            "tool_end",
+            json!({ "tool": name, "duration_ms": duration_ms(*duration), "success": success }),
+        ),
+        ObserverEvent::ChannelMessage { channel, direction } => (
+            "channel_message",
+            json!({ "channel": channel, "direction": direction }),
+        ),
+        ObserverEvent::BudgetUpdate { tokens_used, limit } => (
+            "budget_update",
+            json!({ "tokens_used": tokens_used, "limit": limit }),
+        ),
+        ObserverEvent::HeartbeatTick => ("heartbeat_tick", json!({})),
+        ObserverEvent::Reasoning { text } => ("reasoning", json!({ "text": text })),
+        ObserverEvent::ProviderFallback {
+            from_provider,
+            to_provider,
+            model,
+            error,
+        } => (
+            "provider_fallback",
+            json!({ "from": from_provider, "to": to_provider, "model": model, "error": error }),
+        ),
+        ObserverEvent::CircuitStateChanged { provider, state } => (
+            "circuit_state_changed",
+            json!({ "provider": provider, "state": state }),
+        ),
+        ObserverEvent::Warmup {
+            provider,
+            reachable,
+            latency,
+            detail,
+        } => (
+            "warmup",
+            json!({
+                "provider": provider,
+                "reachable": reachable,
+                "latency_ms": duration_ms(*latency),
+                "detail": detail
+            }),
+        ),
+        ObserverEvent::Error { component, message } => (
+            "error",
+            json!({ "component": component, "message": message }),
+        ),
+    }
+}
+
+fn metric_fields(metric: &ObserverMetric) -> Value {
+    let (name, value) = match metric {
+        ObserverMetric::RequestLatency(d) => ("request_latency_ms", duration_ms(*d)),
+        ObserverMetric::TokensUsed(t) => ("tokens_used", *t),
+        ObserverMetric::ActiveSessions(s) => ("active_sessions", *s),
+        ObserverMetric::QueueDepth(d) => ("queue_depth", *d),
+    };
+    json!({ "metric": name, "value": value })
+}
+
+impl Observer for JsonObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        let trace_id = self.trace_id(Some(event));
+        let (name, fields) = event_fields(event);
+        self.write_line(name, trace_id, fields);
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        let trace_id = self.trace_id(None);
+        self.write_line("metric", trace_id, metric_fields(metric));
+    }
+
+    fn flush(&self) {
+        let _ = self
+            .out
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .flush();
+    }
+
+    fn shutdown(&self) {
+        self.flush();
+    }
+
+    fn name(&self) -> &str {
+        "json"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Writer the test keeps a handle to.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SharedBuf {
+        fn lines(&self) -> Vec<Value> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    fn observer() -> (JsonObserver, SharedBuf) {
+        let buf = SharedBuf::default();
+        (JsonObserver::with_writer(Box::new(buf.clone())), buf)
+    }
+
+    fn keys(line: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = line
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn json_observer_name() {
+        assert_eq!(observer().0.name(), "json");
+    }
+
+    #[test]
+    fn each_event_serializes_with_expected_keys() {
+        let (obs, buf) = observer();
+        let cases = [
+            (
+                ObserverEvent::AgentStart {
+                    provider: "openrouter".into(),
+                    model: "claude-sonnet".into(),
+                },
+                "agent_start",
+                vec!["model", "provider"],
+            ),
+            (
+                ObserverEvent::ToolStart {
+                    name: "shell".into(),
+                    arguments_summary: r#"{"command":"ls"}"#.into(),
+                },
+                "tool_start",
+                vec!["arguments", "tool"],
+            ),
+            (
+                ObserverEvent::ToolEnd {
+                    name: "shell".into(),
+                    duration: Duration::from_millis(10),
+                    success: false,
+                },
+                "tool_end",
+                vec!["duration_ms", "success", "tool"],
+            ),
+            (
+                ObserverEvent::ChannelMessage {
+                    channel: "telegram".into(),
+                    direction: "outbound".into(),
+                },
+                "channel_message",
+                vec!["channel", "direction"],
+            ),
+            (
+                ObserverEvent::BudgetUpdate {
+                    tokens_used: 1200,
+                    limit: Some(5000),
+                },
+                "budget_update",
+                vec!["limit", "tokens_used"],
+            ),
+            (ObserverEvent::HeartbeatTick, "heartbeat_tick", vec![]),
+            (
+                ObserverEvent::ProviderFallback {
+                    from_provider: "bedrock".into(),
+                    to_provider: "openrouter".into(),
+                    model: "anthropic/claude-sonnet-4".into(),
+                    error: "503".into(),
+                },
+                "provider_fallback",
+                vec!["error", "from", "model", "to"],
+            ),
+            (
+                ObserverEvent::CircuitStateChanged {
+                    provider: "bedrock".into(),
+                    state: "open".into(),
+                },
+                "circuit_state_changed",
+                vec!["provider", "state"],
+            ),
+            (
+                ObserverEvent::Error {
+                    component: "provider".into(),
+                    message: "timeout".into(),
+                },
+                "error",
+                vec!["component", "message"],
+            ),
+            (
+                ObserverEvent::AgentEnd {
+                    duration: Duration::from_millis(500),
+                    tokens_used: Some(100),
+                },
+                "agent_end",
+                vec!["duration_ms", "tokens_used"],
+            ),
+        ];
+        for (event, _, _) in &cases {
+            obs.record_event(event);
+        }
+
+        let lines = buf.lines();
+        assert_eq!(lines.len(), cases.len());
+        for (line, (_, name, fields)) in lines.iter().zip(&cases) {
+            let mut expected = vec!["event", "timestamp", "trace_id"];
+            expected.extend(fields);
+            expected.sort_unstable();
+            assert_eq!(line["event"], *name);
+            assert_eq!(keys(line), expected, "{line}");
+            let timestamp = line["timestamp"].as_str().unwrap();
+            assert!(chrono::DateTime::parse_from_rfc3339(timestamp).is_ok());
+        }
+        assert_eq!(lines[2]["duration_ms"], 10);
+        assert_eq!(lines[9]["tokens_used"], 100);
+    }
+
+    #[test]
+    fn metrics_serialize_with_name_and_value() {
+        let (obs, buf) = observer();
+        obs.record_metric(&ObserverMetric::RequestLatency(Duration::from_secs(2)));
+        obs.record_metric(&ObserverMetric::QueueDepth(3));
+
+        let lines = buf.lines();
+        assert_eq!(lines[0]["event"], "metric");
+        assert_eq!(lines[0]["metric"], "request_latency_ms");
+        assert_eq!(lines[0]["value"], 2000);
+        assert_eq!(lines[1]["metric"], "queue_depth");
+        assert_eq!(
+            keys(&lines[1]),
+            ["event", "metric", "timestamp", "trace_id", "value"]
+        );
+    }
+
+    #[test]
+    fn trace_id_is_shared_within_a_run_and_rolls_between_runs() {
+        let (obs, buf) = observer();
+        let start = ObserverEvent::AgentStart {
+            provider: "p".into(),
+            model: "m".into(),
+        };
+        let end = ObserverEvent::AgentEnd {
+            duration: Duration::ZERO,
+            tokens_used: None,
+        };
+        obs.record_event(&start);
+        obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_metric(&ObserverMetric::TokensUsed(1));
+        obs.record_event(&end);
+        obs.record_event(&start);
+        obs.record_event(&end);
+
+        let lines = buf.lines();
+        let ids: Vec<&str> = lines
+            .iter()
+            .map(|line| line["trace_id"].as_str().unwrap())
+            .collect();
+        assert!(ids[..4].iter().all(|id| *id == ids[0]), "{ids:?}");
+        assert_eq!(ids[0].len(), 32);
+        assert_ne!(ids[4], ids[0]);
+        assert_eq!(ids[5], ids[4]);
+    }
+
+    #[test]
+    fn to_file_appends_lines() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("logs/events.jsonl");
+        let obs = JsonObserver::to_file(&path).unwrap();
+        obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.flush();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+    }
+}
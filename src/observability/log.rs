@@ -23,20 +23,64 @@ impl Observer for LogObserver {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
                 info!(duration_ms = ms, tokens = ?tokens_used, "agent.end");
             }
-            ObserverEvent::ToolCall {
-                tool,
-                duration,
+            ObserverEvent::ToolStart {
+                name,
+                arguments_summary,
+            } => {
+                info!(tool = %name, args = %arguments_summary, "tool.start");
+            }
+            ObserverEvent::ToolEnd {
+                name,
                 success,
+                duration,
             } => {
                 let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
-                info!(tool = %tool, duration_ms = ms, success = success, "tool.call");
+                info!(tool = %name, duration_ms = ms, success = success, "tool.end");
             }
             ObserverEvent::ChannelMessage { channel, direction } => {
                 info!(channel = %channel, direction = %direction, "channel.message");
             }
+            ObserverEvent::BudgetUpdate { tokens_used, limit } => {
+                info!(tokens = tokens_used, limit = ?limit, "budget.update");
+            }
             ObserverEvent::HeartbeatTick => {
                 info!("heartbeat.tick");
             }
+            ObserverEvent::Reasoning { text } => {
+                info!(chars = text.chars().count(), "agent.reasoning");
+            }
+            ObserverEvent::ProviderFallback {
+                from_provider,
+                to_provider,
+                model,
+                error,
+            } => {
+                info!(
+                    from = %from_provider,
+                    to = %to_provider,
+                    model = %model,
+                    error = %error,
+                    "provider.fallback"
+                );
+            }
+            ObserverEvent::CircuitStateChanged { provider, state } => {
+                info!(provider = %provider, state = %state, "provider.circuit");
+            }
+            ObserverEvent::Warmup {
+                provider,
+                reachable,
+                latency,
+                detail,
+            } => {
+                let ms = u64::try_from(latency.as_millis()).unwrap_or(u64::MAX);
+                info!(
+                    provider = %provider,
+                    reachable = reachable,
+                    latency_ms = ms,
+                    detail = %detail,
+                    "provider.warmup"
+                );
+            }
             ObserverEvent::Error { component, message } => {
                 info!(component = %component, error = %message, "error");
             }
@@ -91,8 +135,12 @@ mod tests {
             duration: Duration::ZERO,
             tokens_used: None,
         });
-        obs.record_event(&ObserverEvent::ToolCall {
-            tool: "shell".into(),
+        obs.record_event(&ObserverEvent::ToolStart {
+            name: "shell".into(),
+            arguments_summary: r#"{"command":"ls"}"#.into(),
+        });
+        obs.record_event(&ObserverEvent::ToolEnd {
+            name: "shell".into(),
             duration: Duration::from_millis(10),
             success: false,
         });
@@ -100,7 +148,21 @@ mod tests {
             channel: "telegram".into(),
             direction: "outbound".into(),
         });
+        obs.record_event(&ObserverEvent::BudgetUpdate {
+            tokens_used: 1200,
+            limit: Some(5000),
+        });
         obs.record_event(&ObserverEvent::HeartbeatTick);
+        obs.record_event(&ObserverEvent::ProviderFallback {
+            from_provider: "bedrock".into(),
+            to_provider: "openrouter".into(),
+            model: "anthropic/claude-sonnet-4".into(),
+            error: "503 Service Unavailable".into(),
+        });
+        obs.record_event(&ObserverEvent::CircuitStateChanged {
+            provider: "bedrock".into(),
+            state: "open".into(),
+        });
         obs.record_event(&ObserverEvent::Error {
             component: "provider".into(),
             message: "timeout".into(),
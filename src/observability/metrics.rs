@@ -0,0 +1,268 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use axum::{http::header, routing::get, Router};
+use prometheus::{
+    Encoder, Histogram, HistogramOpts, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Prometheus observer — keeps run, tool, latency and token metrics and
+/// serves them in the text exposition format on `GET /metrics`.
+pub struct MetricsObserver {
+    metrics: Arc<Metrics>,
+}
+
+struct Metrics {
+    registry: Registry,
+    agent_runs: IntCounter,
+    agent_duration: Histogram,
+    tool_calls: IntCounterVec,
+    tool_errors: IntCounterVec,
+    provider_latency: Histogram,
+    tokens_used: IntCounter,
+    errors: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("zeroclaw".into()), None)?;
+        let metrics = Self {
+            agent_runs: IntCounter::new("agent_runs_total", "Agent runs started")?,
+            agent_duration: Histogram::with_opts(HistogramOpts::new(
+                "agent_duration_seconds",
+                "Wall-clock duration of agent runs",
+            ))?,
+            tool_calls: IntCounterVec::new(
+                Opts::new("tool_calls_total", "Tool calls executed"),
+                &["tool"],
+            )?,
+            tool_errors: IntCounterVec::new(
+                Opts::new("tool_errors_total", "Tool calls that failed"),
+                &["tool"],
+            )?,
+            provider_latency: Histogram::with_opts(HistogramOpts::new(
+                "provider_request_duration_seconds",
+                "Latency of LLM provider requests",
+            ))?,
+            tokens_used: IntCounter::new("tokens_used_total", "Tokens consumed")?,
+            errors: IntCounterVec::new(
+                Opts::new("errors_total", "Errors reported by component"),
+                &["component"],
+            )?,
+            registry,
+        };
+        metrics
+            .registry
+            .register(Box::new(metrics.agent_runs.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.agent_duration.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.tool_calls.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.tool_errors.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.provider_latency.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.tokens_used.clone()))?;
+        metrics
+            .registry
+            .register(Box::new(metrics.errors.clone()))?;
+        Ok(metrics)
+    }
+
+    fn render(&self) -> String {
+        let mut buf = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buf) {
+            tracing::warn!("Failed to encode Prometheus metrics: {e}");
+        }
+        String::from_utf8(buf).unwrap_or_default()
+    }
+}
+
+impl MetricsObserver {
+    pub fn new() -> Self {
+        Self {
+            metrics: Arc::new(Metrics::new().expect("metric names and labels are valid")),
+        }
+    }
+
+    /// Current metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        self.metrics.render()
+    }
+
+    /// Serve `GET /metrics` on `addr` from a background task. The address
+    /// is bound before returning so a port conflict surfaces immediately.
+    /// Returns the bound address (useful with port 0).
+    pub fn serve(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|_| anyhow::anyhow!("metrics endpoint needs a tokio runtime"))?;
+        let listener = std::net::TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let local_addr = listener.local_addr()?;
+
+        let metrics = Arc::clone(&self.metrics);
+        let app = Router::new().route(
+            "/metrics",
+            get(move || {
+                let metrics = Arc::clone(&metrics);
+                async move {
+                    (
+                        [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+                        metrics.render(),
+                    )
+                }
+            }),
+        );
+        runtime.spawn(async move {
+            let served = match tokio::net::TcpListener::from_std(listener) {
+                Ok(listener) => axum::serve(listener, app).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = served {
+                tracing::warn!("Metrics endpoint stopped: {e}");
+            }
+        });
+        Ok(local_addr)
+    }
+}
+
+impl Observer for MetricsObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        let m = &self.metrics;
+        match event {
+            ObserverEvent::AgentStart { .. } => m.agent_runs.inc(),
+            ObserverEvent::AgentEnd {
+                duration,
+                tokens_used,
+            } => {
+                m.agent_duration.observe(duration.as_secs_f64());
+                if let Some(tokens) = tokens_used {
+                    m.tokens_used.inc_by(*tokens);
+                }
+            }
+            ObserverEvent::ToolEnd {
+                name: tool,
+                success,
+                ..
+            } => {
+                m.tool_calls.with_label_values(&[tool]).inc();
+                if !success {
+                    m.tool_errors.with_label_values(&[tool]).inc();
+                }
+            }
+            ObserverEvent::Error { component, .. } => {
+                m.errors.with_label_values(&[component]).inc();
+            }
+            ObserverEvent::ToolStart { .. }
+            | ObserverEvent::ChannelMessage { .. }
+            | ObserverEvent::BudgetUpdate { .. }
+            | ObserverEvent::ProviderFallback { .. }
+            | ObserverEvent::CircuitStateChanged { .. }
+            | ObserverEvent::Warmup { .. }
+            | ObserverEvent::Reasoning { .. }
+            | ObserverEvent::HeartbeatTick => {}
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        match metric {
+            ObserverMetric::RequestLatency(d) => {
+                self.metrics.provider_latency.observe(d.as_secs_f64());
+            }
+            ObserverMetric::TokensUsed(t) => self.metrics.tokens_used.inc_by(*t),
+            ObserverMetric::ActiveSessions(_) | ObserverMetric::QueueDepth(_) => {}
+        }
+    }
+
+    fn name(&self) -> &str {
+        "prometheus"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn record_run(obs: &MetricsObserver) {
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+        });
+        obs.record_metric(&ObserverMetric::RequestLatency(Duration::from_millis(250)));
+        for success in [true, true, false] {
+            obs.record_event(&ObserverEvent::ToolEnd {
+                name: "shell".into(),
+                duration: Duration::from_millis(10),
+                success,
+            });
+        }
+        obs.record_event(&ObserverEvent::ToolEnd {
+            name: "file_read".into(),
+            duration: Duration::from_millis(1),
+            success: true,
+        });
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::from_secs(2),
+            tokens_used: Some(1500),
+        });
+    }
+
+    #[test]
+    fn metrics_observer_name() {
+        assert_eq!(MetricsObserver::new().name(), "prometheus");
+    }
+
+    #[test]
+    fn rendered_counters_reflect_recorded_events() {
+        let obs = MetricsObserver::new();
+        record_run(&obs);
+        record_run(&obs);
+
+        let text = obs.render();
+        for line in [
+            "zeroclaw_agent_runs_total 2",
+            r#"zeroclaw_tool_calls_total{tool="shell"} 6"#,
+            r#"zeroclaw_tool_calls_total{tool="file_read"} 2"#,
+            r#"zeroclaw_tool_errors_total{tool="shell"} 2"#,
+            "zeroclaw_tokens_used_total 3000",
+            "zeroclaw_provider_request_duration_seconds_count 2",
+            "zeroclaw_provider_request_duration_seconds_sum 0.5",
+            "zeroclaw_agent_duration_seconds_count 2",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {line:?} in\n{text}"
+            );
+        }
+        assert!(!text.contains(r#"tool_errors_total{tool="file_read"}"#));
+    }
+
+    #[tokio::test]
+    async fn serves_metrics_endpoint() {
+        let obs = MetricsObserver::new();
+        let addr = obs.serve("127.0.0.1:0".parse().unwrap()).unwrap();
+        record_run(&obs);
+
+        let response = reqwest::get(format!("http://{addr}/metrics"))
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let content_type = response.headers()[reqwest::header::CONTENT_TYPE].clone();
+        assert_eq!(content_type, prometheus::TEXT_FORMAT);
+        let body = response.text().await.unwrap();
+        assert!(body.contains("zeroclaw_agent_runs_total 1"), "{body}");
+    }
+
+    #[test]
+    fn serve_without_runtime_fails() {
+        let obs = MetricsObserver::new();
+        assert!(obs.serve("127.0.0.1:0".parse().unwrap()).is_err());
+    }
+}
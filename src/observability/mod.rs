@@ -1,24 +1,84 @@
+pub mod json;
 pub mod log;
+pub mod metrics;
 pub mod multi;
 pub mod noop;
+pub mod otel;
+pub mod redacting;
 pub mod traits;
 
+pub use self::json::JsonObserver;
 pub use self::log::LogObserver;
+pub use metrics::MetricsObserver;
+pub use multi::MultiObserver;
 pub use noop::NoopObserver;
-pub use traits::{Observer, ObserverEvent};
+pub use otel::OtelObserver;
+pub use redacting::RedactingObserver;
+pub use traits::{Observer, ObserverEvent, ObserverMetric};
 
 use crate::config::ObservabilityConfig;
+use std::path::Path;
 
-/// Factory: create the right observer from config
+/// Factory: create the right observer from config. Several `backends` are
+/// combined into a [`MultiObserver`]; otherwise `backend` picks one. Either
+/// way events are redacted first (see [`RedactingObserver`]).
 pub fn create_observer(config: &ObservabilityConfig) -> Box<dyn Observer> {
-    match config.backend.as_str() {
+    let observer = match config.backends.as_slice() {
+        [] => create_backend(&config.backend, config),
+        [backend] => create_backend(backend, config),
+        backends => Box::new(MultiObserver::new(
+            backends
+                .iter()
+                .map(|backend| create_backend(backend, config))
+                .collect(),
+        )),
+    };
+    Box::new(RedactingObserver::new(observer))
+}
+
+/// Build a single backend by name.
+fn create_backend(backend: &str, config: &ObservabilityConfig) -> Box<dyn Observer> {
+    match backend {
+        "log" if config.format == "json" => match config.log_path.as_deref() {
+            Some(path) => {
+                let path = shellexpand::tilde(path);
+                match JsonObserver::to_file(Path::new(path.as_ref())) {
+                    Ok(observer) => Box::new(observer),
+                    Err(e) => {
+                        tracing::warn!("Failed to open JSON log {path}, writing to stdout: {e}");
+                        Box::new(JsonObserver::stdout())
+                    }
+                }
+            }
+            None => Box::new(JsonObserver::stdout()),
+        },
         "log" => Box::new(LogObserver::new()),
         "none" | "noop" => Box::new(NoopObserver),
+        "prometheus" => {
+            let observer = MetricsObserver::new();
+            match config.metrics_addr.parse() {
+                Ok(addr) => match observer.serve(addr) {
+                    Ok(addr) => tracing::info!("Prometheus metrics on http://{addr}/metrics"),
+                    Err(e) => tracing::warn!("Failed to serve metrics on {addr}: {e}"),
+                },
+                Err(e) => tracing::warn!(
+                    "Invalid observability.metrics_addr '{}': {e}",
+                    config.metrics_addr
+                ),
+            }
+            Box::new(observer)
+        }
+        "otel" | "otlp" => {
+            match OtelObserver::new(config.otel_endpoint.as_deref(), &config.otel_headers) {
+                Ok(observer) => Box::new(observer),
+                Err(e) => {
+                    tracing::warn!("Failed to set up OTLP exporter, falling back to noop: {e}");
+                    Box::new(NoopObserver)
+                }
+            }
+        }
         _ => {
-            tracing::warn!(
-                "Unknown observability backend '{}', falling back to noop",
-                config.backend
-            );
+            tracing::warn!("Unknown observability backend '{backend}', falling back to noop");
             Box::new(NoopObserver)
         }
     }
@@ -32,6 +92,7 @@ mod tests {
     fn factory_none_returns_noop() {
         let cfg = ObservabilityConfig {
             backend: "none".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -40,6 +101,7 @@ mod tests {
     fn factory_noop_returns_noop() {
         let cfg = ObservabilityConfig {
             backend: "noop".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -48,14 +110,67 @@ mod tests {
     fn factory_log_returns_log() {
         let cfg = ObservabilityConfig {
             backend: "log".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "log");
     }
 
     #[test]
-    fn factory_unknown_falls_back_to_noop() {
+    fn factory_log_with_json_format_returns_json() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let cfg = ObservabilityConfig {
+            backend: "log".into(),
+            format: "json".into(),
+            log_path: Some(tmp.path().join("events.jsonl").display().to_string()),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "json");
+    }
+
+    #[tokio::test]
+    async fn factory_prometheus_returns_metrics_observer() {
         let cfg = ObservabilityConfig {
             backend: "prometheus".into(),
+            metrics_addr: "127.0.0.1:0".into(),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "prometheus");
+    }
+
+    #[test]
+    fn factory_otel_returns_otel() {
+        let cfg = ObservabilityConfig {
+            backend: "otel".into(),
+            otel_endpoint: Some("http://127.0.0.1:4318".into()),
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "otel");
+    }
+
+    #[test]
+    fn factory_multiple_backends_returns_multi() {
+        let cfg = ObservabilityConfig {
+            backends: vec!["log".into(), "noop".into()],
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "multi");
+    }
+
+    #[test]
+    fn factory_single_entry_backends_overrides_backend() {
+        let cfg = ObservabilityConfig {
+            backend: "none".into(),
+            backends: vec!["log".into()],
+            ..ObservabilityConfig::default()
+        };
+        assert_eq!(create_observer(&cfg).name(), "log");
+    }
+
+    #[test]
+    fn factory_unknown_falls_back_to_noop() {
+        let cfg = ObservabilityConfig {
+            backend: "statsd".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -64,6 +179,7 @@ mod tests {
     fn factory_empty_string_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
             backend: String::new(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
@@ -72,6 +188,7 @@ mod tests {
     fn factory_garbage_falls_back_to_noop() {
         let cfg = ObservabilityConfig {
             backend: "xyzzy_garbage_123".into(),
+            ..ObservabilityConfig::default()
         };
         assert_eq!(create_observer(&cfg).name(), "noop");
     }
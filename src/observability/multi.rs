@@ -1,6 +1,10 @@
 use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
 /// Combine multiple observers — fan-out events to all backends
+///
+/// Observers are called in order. One that panics is skipped for that call
+/// (with a warning) so the rest still receive the event.
 pub struct MultiObserver {
     observers: Vec<Box<dyn Observer>>,
 }
@@ -9,25 +13,31 @@ impl MultiObserver {
     pub fn new(observers: Vec<Box<dyn Observer>>) -> Self {
         Self { observers }
     }
+
+    fn each(&self, what: &str, f: impl Fn(&dyn Observer)) {
+        for obs in &self.observers {
+            if catch_unwind(AssertUnwindSafe(|| f(obs.as_ref()))).is_err() {
+                tracing::warn!(observer = obs.name(), "Observer panicked during {what}");
+            }
+        }
+    }
 }
 
 impl Observer for MultiObserver {
     fn record_event(&self, event: &ObserverEvent) {
-        for obs in &self.observers {
-            obs.record_event(event);
-        }
+        self.each("record_event", |obs| obs.record_event(event));
     }
 
     fn record_metric(&self, metric: &ObserverMetric) {
-        for obs in &self.observers {
-            obs.record_metric(metric);
-        }
+        self.each("record_metric", |obs| obs.record_metric(metric));
     }
 
     fn flush(&self) {
-        for obs in &self.observers {
-            obs.flush();
-        }
+        self.each("flush", |obs| obs.flush());
+    }
+
+    fn shutdown(&self) {
+        self.each("shutdown", |obs| obs.shutdown());
     }
 
     fn name(&self) -> &str {
@@ -39,7 +49,7 @@ impl Observer for MultiObserver {
 mod tests {
     use super::*;
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     /// Test observer that counts calls
@@ -151,4 +161,86 @@ mod tests {
         assert_eq!(fc1.load(Ordering::SeqCst), 1);
         assert_eq!(fc2.load(Ordering::SeqCst), 1);
     }
+
+    /// Test observer that appends what it saw to a shared log
+    struct RecordingObserver {
+        id: &'static str,
+        seen: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(format!("{}: {event:?}", self.id));
+        }
+        fn record_metric(&self, _metric: &ObserverMetric) {}
+        fn name(&self) -> &str {
+            self.id
+        }
+    }
+
+    struct PanickingObserver;
+
+    impl Observer for PanickingObserver {
+        fn record_event(&self, _event: &ObserverEvent) {
+            panic!("observer backend failed");
+        }
+        fn record_metric(&self, _metric: &ObserverMetric) {
+            panic!("observer backend failed");
+        }
+        fn name(&self) -> &str {
+            "panicking"
+        }
+    }
+
+    #[test]
+    fn multi_delivers_same_events_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let m = MultiObserver::new(vec![
+            Box::new(RecordingObserver {
+                id: "a",
+                seen: seen.clone(),
+            }),
+            Box::new(RecordingObserver {
+                id: "b",
+                seen: seen.clone(),
+            }),
+        ]);
+
+        let start = ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+        };
+        m.record_event(&start);
+        m.record_event(&ObserverEvent::HeartbeatTick);
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                format!("a: {start:?}"),
+                format!("b: {start:?}"),
+                "a: HeartbeatTick".to_string(),
+                "b: HeartbeatTick".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn multi_isolates_panicking_observer() {
+        let ec = Arc::new(AtomicUsize::new(0));
+        let mc = Arc::new(AtomicUsize::new(0));
+        let fc = Arc::new(AtomicUsize::new(0));
+        let m = MultiObserver::new(vec![
+            Box::new(PanickingObserver),
+            Box::new(CountingObserver::new(ec.clone(), mc.clone(), fc.clone())),
+        ]);
+
+        m.record_event(&ObserverEvent::HeartbeatTick);
+        m.record_metric(&ObserverMetric::TokensUsed(1));
+
+        assert_eq!(ec.load(Ordering::SeqCst), 1);
+        assert_eq!(mc.load(Ordering::SeqCst), 1);
+    }
 }
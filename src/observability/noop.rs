@@ -41,8 +41,12 @@ mod tests {
             duration: Duration::ZERO,
             tokens_used: None,
         });
-        obs.record_event(&ObserverEvent::ToolCall {
-            tool: "shell".into(),
+        obs.record_event(&ObserverEvent::ToolStart {
+            name: "shell".into(),
+            arguments_summary: String::new(),
+        });
+        obs.record_event(&ObserverEvent::ToolEnd {
+            name: "shell".into(),
             duration: Duration::from_secs(1),
             success: true,
         });
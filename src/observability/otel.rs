@@ -0,0 +1,395 @@
+use super::traits::{Observer, ObserverEvent, ObserverMetric};
+use opentelemetry::context::Context;
+use opentelemetry::metrics::{Counter, Histogram, MeterProvider as _};
+use opentelemetry::trace::{Span as _, Status, TraceContextExt, Tracer as _, TracerProvider as _};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+const SERVICE_NAME: &str = "zeroclaw";
+
+/// OpenTelemetry observer — ships agent runs to an OTLP collector.
+///
+/// `AgentStart`/`AgentEnd` bracket an `agent.run` span carrying the provider,
+/// model, duration and token count; tool calls become child spans. Token
+/// usage, run and tool durations are also exported as metrics. Buffered data
+/// is flushed by [`Observer::flush`], [`Observer::shutdown`] (which also ends
+/// a run that never saw `AgentEnd`) and when the observer is dropped.
+pub struct OtelObserver {
+    tracer_provider: SdkTracerProvider,
+    meter_provider: SdkMeterProvider,
+    tracer: SdkTracer,
+    /// Context holding the open `agent.run` span, between start and end.
+    run: Mutex<Option<Context>>,
+    tokens_used: Counter<u64>,
+    agent_duration: Histogram<f64>,
+    tool_duration: Histogram<f64>,
+    request_latency: Histogram<f64>,
+}
+
+impl OtelObserver {
+    /// Export over OTLP/HTTP to `endpoint` (`/v1/traces` and `/v1/metrics` are
+    /// appended). Without an endpoint the standard `OTEL_EXPORTER_OTLP_*`
+    /// variables apply, falling back to `http://localhost:4318`.
+    pub fn new(endpoint: Option<&str>, headers: &HashMap<String, String>) -> anyhow::Result<Self> {
+        let endpoint = endpoint.map(|e| e.trim_end_matches('/'));
+
+        let mut spans = opentelemetry_otlp::SpanExporter::builder()
+            .with_http()
+            .with_headers(headers.clone());
+        if let Some(endpoint) = endpoint {
+            spans = spans.with_endpoint(format!("{endpoint}/v1/traces"));
+        }
+        let mut metrics = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_headers(headers.clone());
+        if let Some(endpoint) = endpoint {
+            metrics = metrics.with_endpoint(format!("{endpoint}/v1/metrics"));
+        }
+
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(spans.build()?)
+            .with_resource(resource())
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metrics.build()?)
+            .with_resource(resource())
+            .build();
+        Ok(Self::from_providers(tracer_provider, meter_provider))
+    }
+
+    /// Build on already-configured providers (custom exporters, tests).
+    pub fn from_providers(
+        tracer_provider: SdkTracerProvider,
+        meter_provider: SdkMeterProvider,
+    ) -> Self {
+        let tracer = tracer_provider.tracer(SERVICE_NAME);
+        let meter = meter_provider.meter(SERVICE_NAME);
+        Self {
+            tracer_provider,
+            tracer,
+            run: Mutex::new(None),
+            tokens_used: meter
+                .u64_counter("zeroclaw.tokens.used")
+                .with_description("Tokens consumed by agent runs")
+                .build(),
+            agent_duration: meter
+                .f64_histogram("zeroclaw.agent.duration")
+                .with_unit("s")
+                .build(),
+            tool_duration: meter
+                .f64_histogram("zeroclaw.tool.duration")
+                .with_unit("s")
+                .build(),
+            request_latency: meter
+                .f64_histogram("zeroclaw.request.latency")
+                .with_unit("s")
+                .build(),
+            meter_provider,
+        }
+    }
+
+    fn run_context(&self) -> Option<Context> {
+        self.run
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+fn resource() -> Resource {
+    Resource::builder().with_service_name(SERVICE_NAME).build()
+}
+
+impl Observer for OtelObserver {
+    fn record_event(&self, event: &ObserverEvent) {
+        match event {
+            ObserverEvent::AgentStart { provider, model } => {
+                let span = self
+                    .tracer
+                    .span_builder("agent.run")
+                    .with_attributes(vec![
+                        KeyValue::new("provider", provider.clone()),
+                        KeyValue::new("model", model.clone()),
+                    ])
+                    .start(&self.tracer);
+                let previous = self
+                    .run
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .replace(Context::current_with_span(span));
+                if let Some(previous) = previous {
+                    previous.span().end();
+                }
+            }
+            ObserverEvent::AgentEnd {
+                duration,
+                tokens_used,
+            } => {
+                self.agent_duration.record(duration.as_secs_f64(), &[]);
+                if let Some(tokens) = tokens_used {
+                    self.tokens_used.add(*tokens, &[]);
+                }
+                let run = self
+                    .run
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .take();
+                if let Some(run) = run {
+                    let span = run.span();
+                    span.set_attribute(KeyValue::new("duration_ms", duration_ms(*duration)));
+                    if let Some(tokens) = tokens_used {
+                        span.set_attribute(KeyValue::new(
+                            "tokens_used",
+                            i64::try_from(*tokens).unwrap_or(i64::MAX),
+                        ));
+                    }
+                    span.end();
+                }
+            }
+            ObserverEvent::ToolEnd {
+                name: tool,
+                success,
+                duration,
+            } => {
+                self.tool_duration.record(
+                    duration.as_secs_f64(),
+                    &[
+                        KeyValue::new("tool", tool.clone()),
+                        KeyValue::new("success", *success),
+                    ],
+                );
+                // ToolEnd arrives once the call finished; backdate the span
+                let start = SystemTime::now()
+                    .checked_sub(*duration)
+                    .unwrap_or_else(SystemTime::now);
+                let builder = self
+                    .tracer
+                    .span_builder("tool.call")
+                    .with_start_time(start)
+                    .with_attributes(vec![
+                        KeyValue::new("tool", tool.clone()),
+                        KeyValue::new("success", *success),
+                    ]);
+                let mut span = match self.run_context() {
+                    Some(run) => builder.start_with_context(&self.tracer, &run),
+                    None => builder.start(&self.tracer),
+                };
+                if !success {
+                    span.set_status(Status::error("tool call failed"));
+                }
+                span.end();
+            }
+            ObserverEvent::Error { component, message } => {
+                if let Some(run) = self.run_context() {
+                    run.span().add_event(
+                        "error",
+                        vec![
+                            KeyValue::new("component", component.clone()),
+                            KeyValue::new("message", message.clone()),
+                        ],
+                    );
+                }
+            }
+            ObserverEvent::ToolStart { .. }
+            | ObserverEvent::ChannelMessage { .. }
+            | ObserverEvent::BudgetUpdate { .. }
+            | ObserverEvent::ProviderFallback { .. }
+            | ObserverEvent::CircuitStateChanged { .. }
+            | ObserverEvent::Warmup { .. }
+            | ObserverEvent::Reasoning { .. }
+            | ObserverEvent::HeartbeatTick => {}
+        }
+    }
+
+    fn record_metric(&self, metric: &ObserverMetric) {
+        match metric {
+            ObserverMetric::RequestLatency(d) => self.request_latency.record(d.as_secs_f64(), &[]),
+            ObserverMetric::TokensUsed(t) => self.tokens_used.add(*t, &[]),
+            ObserverMetric::ActiveSessions(_) | ObserverMetric::QueueDepth(_) => {}
+        }
+    }
+
+    fn flush(&self) {
+        if let Err(e) = self.tracer_provider.force_flush() {
+            tracing::warn!("Failed to flush OTLP spans: {e}");
+        }
+        if let Err(e) = self.meter_provider.force_flush() {
+            tracing::warn!("Failed to flush OTLP metrics: {e}");
+        }
+    }
+
+    fn shutdown(&self) {
+        if let Some(run) = self
+            .run
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+        {
+            run.span().end();
+        }
+        self.flush();
+    }
+
+    fn name(&self) -> &str {
+        "otel"
+    }
+}
+
+impl Drop for OtelObserver {
+    fn drop(&mut self) {
+        // Shutdown exports whatever is still buffered
+        if let Some(run) = self
+            .run
+            .get_mut()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+        {
+            run.span().end();
+        }
+        let _ = self.tracer_provider.shutdown();
+        let _ = self.meter_provider.shutdown();
+    }
+}
+
+fn duration_ms(duration: std::time::Duration) -> i64 {
+    i64::try_from(duration.as_millis()).unwrap_or(i64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::SpanKind;
+    use opentelemetry::Value;
+    use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData};
+    use opentelemetry_sdk::metrics::{InMemoryMetricExporter, PeriodicReader};
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SpanData};
+    use std::time::Duration;
+
+    fn observer() -> (OtelObserver, InMemorySpanExporter, InMemoryMetricExporter) {
+        let spans = InMemorySpanExporter::default();
+        let metrics = InMemoryMetricExporter::default();
+        let observer = OtelObserver::from_providers(
+            SdkTracerProvider::builder()
+                .with_simple_exporter(spans.clone())
+                .build(),
+            SdkMeterProvider::builder()
+                .with_reader(PeriodicReader::builder(metrics.clone()).build())
+                .build(),
+        );
+        (observer, spans, metrics)
+    }
+
+    fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key.as_str() == key)
+            .map(|kv| &kv.value)
+    }
+
+    #[test]
+    fn otel_observer_name() {
+        assert_eq!(observer().0.name(), "otel");
+    }
+
+    #[test]
+    fn start_end_pair_produces_agent_span() {
+        let (obs, spans, _) = observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+        });
+        obs.record_event(&ObserverEvent::ToolEnd {
+            name: "shell".into(),
+            duration: Duration::from_millis(5),
+            success: false,
+        });
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::from_millis(1500),
+            tokens_used: Some(420),
+        });
+
+        let finished = spans.get_finished_spans().unwrap();
+        assert_eq!(finished.len(), 2);
+        let tool = &finished[0];
+        let run = &finished[1];
+
+        assert_eq!(run.name, "agent.run");
+        assert_eq!(run.span_kind, SpanKind::Internal);
+        assert_eq!(attribute(run, "provider"), Some(&Value::from("openrouter")));
+        assert_eq!(attribute(run, "model"), Some(&Value::from("claude-sonnet")));
+        assert_eq!(attribute(run, "duration_ms"), Some(&Value::I64(1500)));
+        assert_eq!(attribute(run, "tokens_used"), Some(&Value::I64(420)));
+
+        assert_eq!(tool.name, "tool.call");
+        assert_eq!(tool.parent_span_id, run.span_context.span_id());
+        assert_eq!(tool.status, Status::error("tool call failed"));
+    }
+
+    #[test]
+    fn shutdown_exports_unfinished_run() {
+        let (obs, spans, _) = observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "openrouter".into(),
+            model: "claude-sonnet".into(),
+        });
+        assert!(spans.get_finished_spans().unwrap().is_empty());
+
+        obs.shutdown();
+        let finished = spans.get_finished_spans().unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].name, "agent.run");
+    }
+
+    #[test]
+    fn end_without_start_records_no_span() {
+        let (obs, spans, _) = observer();
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::ZERO,
+            tokens_used: None,
+        });
+        assert!(spans.get_finished_spans().unwrap().is_empty());
+    }
+
+    #[test]
+    fn token_counts_are_exported_as_metrics() {
+        let (obs, _, metrics) = observer();
+        obs.record_event(&ObserverEvent::AgentEnd {
+            duration: Duration::from_secs(1),
+            tokens_used: Some(100),
+        });
+        obs.record_metric(&ObserverMetric::TokensUsed(20));
+        obs.flush();
+
+        let exported = metrics.get_finished_metrics().unwrap();
+        let total: u64 = exported
+            .iter()
+            .flat_map(|rm| rm.scope_metrics())
+            .flat_map(|sm| sm.metrics())
+            .filter(|m| m.name() == "zeroclaw.tokens.used")
+            .filter_map(|m| match m.data() {
+                AggregatedMetrics::U64(MetricData::Sum(sum)) => {
+                    Some(sum.data_points().map(|p| p.value()).sum::<u64>())
+                }
+                _ => None,
+            })
+            .sum();
+        assert_eq!(total, 120);
+    }
+
+    #[test]
+    fn drop_shuts_down_exporters() {
+        let (obs, spans, _) = observer();
+        obs.record_event(&ObserverEvent::AgentStart {
+            provider: "p".into(),
+            model: "m".into(),
+        });
+        drop(obs);
+        assert!(spans.is_shutdown_called());
+    }
+}
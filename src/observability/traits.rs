@@ -11,16 +11,55 @@ pub enum ObserverEvent {
         duration: Duration,
         tokens_used: Option<u64>,
     },
-    ToolCall {
-        tool: String,
-        duration: Duration,
+    /// A tool call is about to run; `arguments_summary` is redacted and
+    /// truncated, safe to log
+    ToolStart {
+        name: String,
+        arguments_summary: String,
+    },
+    /// A tool call finished (or was refused before running)
+    ToolEnd {
+        name: String,
         success: bool,
+        duration: Duration,
     },
     ChannelMessage {
         channel: String,
         direction: String,
     },
+    /// Running token total after a provider call, against the configured limit
+    BudgetUpdate {
+        tokens_used: u64,
+        limit: Option<u64>,
+    },
     HeartbeatTick,
+    /// Reasoning the model produced alongside its answer
+    /// (`agent.show_reasoning`)
+    Reasoning {
+        text: String,
+    },
+    /// A routed provider failed with a retryable error and the request moved
+    /// on to the next route for the same hint; `model` is the fallback's model
+    ProviderFallback {
+        from_provider: String,
+        to_provider: String,
+        model: String,
+        error: String,
+    },
+    /// A provider's circuit breaker moved to `state`: `open` (calls fail
+    /// fast), `half_open` (one trial call) or `closed`
+    CircuitStateChanged {
+        provider: String,
+        state: String,
+    },
+    /// Result of warming up the provider before the first turn
+    /// (`agent.warmup`)
+    Warmup {
+        provider: String,
+        reachable: bool,
+        latency: Duration,
+        detail: String,
+    },
     Error {
         component: String,
         message: String,
@@ -47,11 +86,18 @@ pub trait Observer: Send + Sync {
     /// Flush any buffered data (no-op for most backends)
     fn flush(&self) {}
 
+    /// Drain everything still buffered before the process exits. Called
+    /// once, after the last event (no-op for most backends)
+    fn shutdown(&self) {}
+
     /// Human-readable name of this observer
     fn name(&self) -> &str;
 
     /// Downcast to `Any` for backend-specific operations
-    fn as_any(&self) -> &dyn std::any::Any where Self: Sized {
+    fn as_any(&self) -> &dyn std::any::Any
+    where
+        Self: Sized,
+    {
         // Default implementation returns a placeholder that will fail on downcast.
         // Implementors should override this to return `self`.
         static PLACEHOLDER: () = ();
@@ -105,7 +105,13 @@ pub fn run_wizard() -> Result<Config> {
         composio: composio_config,
         secrets: secrets_config,
         browser: BrowserConfig::default(),
+        tools: crate::config::ToolsConfig::default(),
+        search: crate::config::SearchConfig::default(),
+        agent: crate::config::AgentConfig::default(),
+        skills: crate::config::SkillsConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        bedrock: crate::config::BedrockConfig::default(),
+        ..Config::default()
     };
 
     println!(
@@ -274,6 +280,13 @@ pub fn run_quick_setup(
             0
         },
         chunk_max_tokens: 512,
+        ttl: crate::config::MemoryTtlConfig::default(),
+        daily_compact_threshold: 100,
+        daily_compact_after_days: 3,
+        encryption_key: None,
+        encryption_passphrase: None,
+        summarize_saves: false,
+        save_summary_chars: 100,
     };
 
     let config = Config {
@@ -296,7 +309,13 @@ pub fn run_quick_setup(
         composio: ComposioConfig::default(),
         secrets: SecretsConfig::default(),
         browser: BrowserConfig::default(),
+        tools: crate::config::ToolsConfig::default(),
+        search: crate::config::SearchConfig::default(),
+        agent: crate::config::AgentConfig::default(),
+        skills: crate::config::SkillsConfig::default(),
         identity: crate::config::IdentityConfig::default(),
+        bedrock: crate::config::BedrockConfig::default(),
+        ..Config::default()
     };
 
     config.save()?;
@@ -1098,6 +1117,13 @@ fn setup_memory() -> Result<MemoryConfig> {
         keyword_weight: 0.3,
         embedding_cache_size: if backend == "sqlite" { 10000 } else { 0 },
         chunk_max_tokens: 512,
+        ttl: crate::config::MemoryTtlConfig::default(),
+        daily_compact_threshold: 100,
+        daily_compact_after_days: 3,
+        encryption_key: None,
+        encryption_passphrase: None,
+        summarize_saves: false,
+        save_summary_chars: 100,
     })
 }
 
@@ -1120,6 +1146,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
         whatsapp: None,
         email: None,
         irc: None,
+        http: None,
     };
 
     loop {
@@ -1371,6 +1398,7 @@ fn setup_channels() -> Result<ChannelsConfig> {
                     bot_token: token,
                     guild_id: if guild.is_empty() { None } else { Some(guild) },
                     allowed_users,
+                    channel_ids: vec![],
                 });
             }
             2 => {
@@ -2233,8 +2261,29 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
            - Use when: inspecting project files, configs, or logs.\n\
            - Don't use when: you only need a quick string search (prefer targeted search first).\n\
          - **file_write** — Write file contents\n\
-           - Use when: applying focused edits, scaffolding files, or updating docs/code.\n\
+           - Use when: scaffolding new files or rewriting a file wholesale.\n\
            - Don't use when: unsure about side effects or when the file should remain user-owned.\n\
+         - **file_edit** — Replace an exact snippet in a file (old_string → new_string)\n\
+           - Use when: applying focused edits to an existing file.\n\
+           - Don't use when: the snippet isn't unique; add surrounding lines or set replace_all.\n\
+         - **file_append** — Add content to the end of a file, creating it if missing\n\
+           - Use when: keeping a log, journal or running list up to date.\n\
+           - Don't use when: changing existing content (use file_edit).\n\
+         - **make_directory** — Create a directory and any missing parents\n\
+           - Use when: preparing a folder layout before writing files into it.\n\
+           - Don't use when: you're about to write a file there anyway (file_write creates parents).\n\
+         - **search** — Find files by glob or lines by regex (respects .gitignore)\n\
+           - Use when: locating files, definitions or usages before reading them.\n\
+           - Don't use when: you already know the exact file (read it directly).\n\
+         - **artifact** — Hand a file back to the user with your answer (content, or a workspace path)\n\
+           - Use when: the result is a file the user should keep (report, script, export).\n\
+           - Don't use when: a short answer in text is enough.\n\
+         - **http_request** — Call an HTTP API (method, url, headers, body)\n\
+           - Use when: talking to a REST API or fetching a raw resource.\n\
+           - Don't use when: a dedicated tool or integration covers the service.\n\
+         - **web_search** — Search the web (needs [search] in config.toml)\n\
+           - Use when: researching something that isn't in the workspace or memory.\n\
+           - Don't use when: you already know the URL (use http_request).\n\
          - **memory_store** — Save to memory\n\
            - Use when: preserving durable preferences, decisions, or key context.\n\
            - Don't use when: info is transient, noisy, or sensitive without explicit need.\n\
@@ -2243,7 +2292,22 @@ fn scaffold_workspace(workspace_dir: &Path, ctx: &ProjectContext) -> Result<()>
            - Don't use when: the answer is already in current files/conversation.\n\
          - **memory_forget** — Delete a memory entry\n\
            - Use when: memory is incorrect, stale, or explicitly requested to be removed.\n\
-           - Don't use when: uncertain about impact; verify before deleting.\n\n\
+           - Don't use when: uncertain about impact; verify before deleting.\n\
+         - **memory_list** — List memory keys with category and timestamp (paginated)\n\
+           - Use when: reviewing what you know or looking for stale entries.\n\
+           - Don't use when: searching for a specific fact (use memory_recall).\n\
+         - **memory_delete** — Delete a memory entry, optionally only from one category\n\
+           - Use when: pruning a stale or wrong entry found with memory_list.\n\
+           - Don't use when: unsure the key is right; a missing key is reported as a failure.\n\
+         - **schedule** — Send yourself a message later, once (delay) or repeatedly (cron)\n\
+           - Use when: a reminder or follow-up is due at a later time.\n\
+           - Don't use when: the work can be done now in this turn.\n\
+         - **schedule_list** — List pending scheduled messages with their ids\n\
+           - Use when: checking what reminders are already set.\n\
+           - Don't use when: you just need to set a new one.\n\
+         - **schedule_cancel** — Cancel a scheduled message by id\n\
+           - Use when: a reminder is no longer wanted or was set by mistake.\n\
+           - Don't use when: unsure of the id; look it up with schedule_list first.\n\n\
          ---\n\
          *Add whatever helps you do your job. This is your cheat sheet.*\n";
 
@@ -2916,9 +2980,20 @@ mod tests {
             "shell",
             "file_read",
             "file_write",
+            "file_edit",
+            "file_append",
+            "make_directory",
+            "search",
+            "artifact",
+            "http_request",
             "memory_store",
             "memory_recall",
             "memory_forget",
+            "memory_list",
+            "memory_delete",
+            "schedule",
+            "schedule_list",
+            "schedule_cancel",
         ] {
             assert!(
                 tools.contains(tool),
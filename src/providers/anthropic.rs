@@ -1,8 +1,16 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    system_prompt, ChatMessage, ChatResponse as ProviderResponse, ChatStream, Provider,
+    ResponseUsage, Role, StreamEvent,
+};
+use crate::tools::{ToolCall, ToolSpec};
 use async_trait::async_trait;
+use futures_util::stream::{self, Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+const API_VERSION: &str = "2023-06-01";
+const MAX_TOKENS: u32 = 4096;
+
 pub struct AnthropicProvider {
     credential: Option<String>,
     base_url: String,
@@ -17,6 +25,10 @@ struct ChatRequest {
     system: Option<String>,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tools: Vec<ToolDefinition>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -25,14 +37,108 @@ struct Message {
     content: String,
 }
 
+/// A tool offered to the model, in the Messages API's format.
+#[derive(Debug, Serialize)]
+struct ToolDefinition {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+}
+
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     content: Vec<ContentBlock>,
+    #[serde(default)]
+    usage: Option<Usage>,
 }
 
+/// One block of the response: `text`, `tool_use` or a kind we skip
+/// (e.g. `thinking`).
 #[derive(Debug, Deserialize)]
 struct ContentBlock {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
     text: String,
+    /// Tool name, on `tool_use` blocks
+    #[serde(default)]
+    name: Option<String>,
+    /// Tool arguments, on `tool_use` blocks
+    #[serde(default)]
+    input: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+/// One `data:` payload of a streamed response. Only text deltas, usage and
+/// errors matter here; every other event is skipped.
+#[derive(Debug, Deserialize)]
+struct SseEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    delta: Option<StreamDelta>,
+    #[serde(default)]
+    error: Option<StreamError>,
+    /// Input token count, on `message_start`
+    #[serde(default)]
+    message: Option<StreamMessage>,
+    /// Final output token count, on `message_delta`
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    message: String,
+}
+
+impl ChatResponse {
+    /// Text blocks joined, `tool_use` blocks as native tool calls.
+    fn into_provider_response(self) -> anyhow::Result<ProviderResponse> {
+        if self.content.is_empty() {
+            anyhow::bail!("No response from Anthropic");
+        }
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in self.content {
+            match block.kind.as_str() {
+                "tool_use" => tool_calls.push(ToolCall {
+                    name: block.name.unwrap_or_default(),
+                    arguments: block.input.unwrap_or_else(|| serde_json::json!({})),
+                }),
+                "text" | "" => text.push_str(&block.text),
+                _ => {}
+            }
+        }
+        Ok(ProviderResponse {
+            text,
+            usage: self.usage.map(|usage| ResponseUsage {
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+            }),
+            tool_calls,
+            ..ProviderResponse::default()
+        })
+    }
 }
 
 impl AnthropicProvider {
@@ -62,61 +168,210 @@ impl AnthropicProvider {
     fn is_setup_token(token: &str) -> bool {
         token.starts_with("sk-ant-oat01-")
     }
-}
 
-#[async_trait]
-impl Provider for AnthropicProvider {
-    async fn chat_with_system(
-        &self,
-        system_prompt: Option<&str>,
-        message: &str,
+    /// System messages go to the top-level `system` field, the rest to
+    /// `messages`.
+    fn build_request(
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<String> {
+        stream: bool,
+    ) -> ChatRequest {
+        ChatRequest {
+            model: model.to_string(),
+            max_tokens: MAX_TOKENS,
+            system: system_prompt(messages),
+            messages: messages
+                .iter()
+                .filter(|m| m.role != Role::System)
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content.clone(),
+                })
+                .collect(),
+            temperature,
+            tools: tools
+                .iter()
+                .map(|tool| ToolDefinition {
+                    name: tool.name.clone(),
+                    description: tool.description.clone(),
+                    input_schema: tool.parameters.clone(),
+                })
+                .collect(),
+            stream,
+        }
+    }
+
+    /// POST to `/v1/messages` with the auth and version headers.
+    async fn send(&self, request: &ChatRequest) -> anyhow::Result<reqwest::Response> {
         let credential = self.credential.as_ref().ok_or_else(|| {
             anyhow::anyhow!(
                 "Anthropic credentials not set. Set ANTHROPIC_API_KEY or ANTHROPIC_OAUTH_TOKEN (setup-token)."
             )
         })?;
 
-        let request = ChatRequest {
-            model: model.to_string(),
-            max_tokens: 4096,
-            system: system_prompt.map(ToString::to_string),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: message.to_string(),
-            }],
-            temperature,
-        };
-
-        let mut request = self
+        let mut builder = self
             .client
             .post(format!("{}/v1/messages", self.base_url))
-            .header("anthropic-version", "2023-06-01")
+            .header("anthropic-version", API_VERSION)
             .header("content-type", "application/json")
-            .json(&request);
+            .json(request);
 
         if Self::is_setup_token(credential) {
-            request = request.header("Authorization", format!("Bearer {credential}"));
+            builder = builder.header("Authorization", format!("Bearer {credential}"));
         } else {
-            request = request.header("x-api-key", credential);
+            builder = builder.header("x-api-key", credential);
         }
 
-        let response = request.send().await?;
-
+        let response = builder.send().await?;
         if !response.status().is_success() {
             return Err(super::api_error("Anthropic", response).await);
         }
+        Ok(response)
+    }
+}
 
-        let chat_response: ChatResponse = response.json().await?;
+/// Turn the Messages API's server-sent events into text deltas. Only
+/// `content_block_delta` text is yielded, plus the usage once `message_delta`
+/// reports the output token count; an `error` event ends the stream with an
+/// error.
+fn sse_deltas<S, B, E>(bytes: S) -> ChatStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    // (bytes, unparsed buffer, finished, input tokens from `message_start`)
+    let state = (Box::pin(bytes), Vec::<u8>::new(), false, 0);
+    stream::unfold(
+        state,
+        |(mut bytes, mut buffer, finished, mut input_tokens)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                let line = if let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                    buffer.drain(..=end).collect::<Vec<u8>>()
+                } else {
+                    match bytes.next().await {
+                        Some(Ok(chunk)) => {
+                            buffer.extend_from_slice(chunk.as_ref());
+                            continue;
+                        }
+                        Some(Err(e)) => {
+                            return Some((Err(e.into()), (bytes, buffer, true, input_tokens)))
+                        }
+                        None if !buffer.is_empty() => std::mem::take(&mut buffer),
+                        None => return None,
+                    }
+                };
+                let Some(data) = line.trim_ascii().strip_prefix(b"data:") else {
+                    continue;
+                };
+                let event = match serde_json::from_slice::<SseEvent>(data.trim_ascii()) {
+                    Ok(event) => event,
+                    Err(e) => return Some((Err(e.into()), (bytes, buffer, true, input_tokens))),
+                };
+                if let Some(error) = event.error {
+                    let err = anyhow::anyhow!("Anthropic stream error: {}", error.message);
+                    return Some((Err(err), (bytes, buffer, true, input_tokens)));
+                }
+                match event.kind.as_str() {
+                    "message_start" => {
+                        if let Some(usage) = event.message.and_then(|message| message.usage) {
+                            input_tokens = usage.input_tokens;
+                        }
+                    }
+                    "content_block_delta" => {
+                        if let Some(text) = event
+                            .delta
+                            .and_then(|delta| delta.text)
+                            .filter(|text| !text.is_empty())
+                        {
+                            let state = (bytes, buffer, false, input_tokens);
+                            return Some((Ok(StreamEvent::Text(text)), state));
+                        }
+                    }
+                    "message_delta" => {
+                        if let Some(usage) = event.usage {
+                            let usage = ResponseUsage {
+                                input_tokens,
+                                output_tokens: usage.output_tokens,
+                            };
+                            let state = (bytes, buffer, false, input_tokens);
+                            return Some((Ok(StreamEvent::Usage(usage)), state));
+                        }
+                    }
+                    "message_stop" => return None,
+                    _ => {}
+                }
+            }
+        },
+    )
+    .boxed()
+}
 
-        chat_response
-            .content
-            .into_iter()
-            .next()
-            .map(|c| c.text)
-            .ok_or_else(|| anyhow::anyhow!("No response from Anthropic"))
+#[async_trait]
+impl Provider for AnthropicProvider {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .chat_with_usage(messages, model, temperature)
+            .await?
+            .text)
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderResponse> {
+        self.chat_with_tools(messages, &[], model, temperature)
+            .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderResponse> {
+        let request = Self::build_request(messages, tools, model, temperature, false);
+        let chat_response: ChatResponse = self.send(&request).await?.json().await?;
+        chat_response.into_provider_response()
+    }
+
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(ChatMessage::system(system_prompt));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_stream(&messages, model, temperature).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let request = Self::build_request(messages, &[], model, temperature, true);
+        let response = self.send(&request).await?;
+        Ok(sse_deltas(response.bytes_stream()))
     }
 }
 
@@ -211,6 +466,8 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.7,
+            tools: Vec::new(),
+            stream: false,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(
@@ -232,6 +489,8 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.7,
+            tools: Vec::new(),
+            stream: false,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"system\":\"You are ZeroClaw\""));
@@ -271,9 +530,160 @@ mod tests {
                 system: None,
                 messages: vec![],
                 temperature: temp,
+                tools: Vec::new(),
+                stream: false,
             };
             let json = serde_json::to_string(&req).unwrap();
             assert!(json.contains(&format!("{temp}")));
         }
     }
+
+    #[test]
+    fn chat_response_maps_text_and_usage() {
+        let json = r#"{
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": "Hello there!"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 12, "output_tokens": 4}
+        }"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        let response = resp.into_provider_response().unwrap();
+        assert_eq!(response.text, "Hello there!");
+        assert!(response.tool_calls.is_empty());
+        assert_eq!(
+            response.usage,
+            Some(ResponseUsage {
+                input_tokens: 12,
+                output_tokens: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn tool_use_response_maps_tool_calls() {
+        let json = r#"{
+            "content": [
+                {"type": "text", "text": "Let me check."},
+                {"type": "tool_use", "id": "toolu_01", "name": "shell", "input": {"command": "ls"}}
+            ],
+            "stop_reason": "tool_use",
+            "usage": {"input_tokens": 30, "output_tokens": 9}
+        }"#;
+        let resp: ChatResponse = serde_json::from_str(json).unwrap();
+        let response = resp.into_provider_response().unwrap();
+        assert_eq!(response.text, "Let me check.");
+        assert_eq!(
+            response.tool_calls,
+            vec![ToolCall {
+                name: "shell".into(),
+                arguments: serde_json::json!({"command": "ls"}),
+            }]
+        );
+    }
+
+    #[test]
+    fn empty_content_is_an_error() {
+        let resp: ChatResponse = serde_json::from_str(r#"{"content":[]}"#).unwrap();
+        let err = resp.into_provider_response().unwrap_err();
+        assert!(err.to_string().contains("No response from Anthropic"));
+    }
+
+    #[test]
+    fn request_maps_system_and_tools() {
+        let tools = [ToolSpec {
+            name: "shell".into(),
+            description: "Run a command".into(),
+            parameters: serde_json::json!({"type": "object"}),
+        }];
+        let request = AnthropicProvider::build_request(
+            &[ChatMessage::system("be brief"), ChatMessage::user("hi")],
+            &tools,
+            "claude-sonnet-4",
+            0.2,
+            false,
+        );
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["system"], "be brief");
+        assert_eq!(
+            json["messages"],
+            serde_json::json!([{"role": "user", "content": "hi"}])
+        );
+        assert_eq!(
+            json["tools"],
+            serde_json::json!([{
+                "name": "shell",
+                "description": "Run a command",
+                "input_schema": {"type": "object"}
+            }])
+        );
+        assert!(json.get("stream").is_none());
+    }
+
+    #[tokio::test]
+    async fn sse_stream_yields_text_deltas() {
+        let events = concat!(
+            "event: message_start\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_01\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hel\"}}\n\n",
+            "event: ping\n",
+            "data: {\"type\":\"ping\"}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"lo\"}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        // Split mid-event to exercise buffering
+        let (first, second) = events.split_at(90);
+        let bytes = stream::iter(vec![
+            Ok::<_, std::io::Error>(first.as_bytes().to_vec()),
+            Ok(second.as_bytes().to_vec()),
+        ]);
+        let chunks: Vec<StreamEvent> = sse_deltas(bytes).map(Result::unwrap).collect().await;
+        assert_eq!(
+            chunks,
+            [
+                StreamEvent::Text("Hel".into()),
+                StreamEvent::Text("lo".into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sse_stream_reports_usage_from_message_events() {
+        let bytes = stream::iter(vec![Ok::<_, std::io::Error>(
+            concat!(
+                "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":25,\"output_tokens\":1}}}\n\n",
+                "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hi\"}}\n\n",
+                "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"end_turn\"},\"usage\":{\"output_tokens\":15}}\n\n",
+                "data: {\"type\":\"message_stop\"}\n\n",
+            )
+            .as_bytes()
+            .to_vec(),
+        )]);
+        let chunks: Vec<StreamEvent> = sse_deltas(bytes).map(Result::unwrap).collect().await;
+        assert_eq!(
+            chunks,
+            [
+                StreamEvent::Text("Hi".into()),
+                StreamEvent::Usage(ResponseUsage {
+                    input_tokens: 25,
+                    output_tokens: 15,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn sse_error_event_ends_stream() {
+        let bytes = stream::iter(vec![Ok::<_, std::io::Error>(
+            b"event: error\ndata: {\"type\":\"error\",\"error\":{\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}}\n\n".to_vec(),
+        )]);
+        let chunks: Vec<anyhow::Result<StreamEvent>> = sse_deltas(bytes).collect().await;
+        assert_eq!(chunks.len(), 1);
+        let err = chunks.into_iter().next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Anthropic stream error: Overloaded");
+    }
 }
@@ -0,0 +1,266 @@
+use crate::config::ReliabilityConfig;
+use crate::observability::{Observer, ObserverEvent};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// When a provider's circuit opens and how long it stays open.
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitSettings {
+    /// Consecutive failures that open the circuit; 0 disables the breaker.
+    pub failure_threshold: u32,
+    /// The failures must all fall within this window.
+    pub window: Duration,
+    /// Time spent open before a trial call is let through.
+    pub cooldown: Duration,
+}
+
+impl CircuitSettings {
+    pub fn from_config(config: &ReliabilityConfig) -> Self {
+        Self {
+            failure_threshold: config.circuit_failure_threshold,
+            window: Duration::from_secs(config.circuit_window_secs),
+            cooldown: Duration::from_secs(config.circuit_cooldown_secs),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Calls go through; `failures` consecutive failures since `since`.
+    Closed {
+        failures: u32,
+        since: Option<Instant>,
+    },
+    /// Calls fail fast until `until`.
+    Open { until: Instant },
+    /// One trial call is in flight, started at `started`.
+    HalfOpen { started: Instant },
+}
+
+impl State {
+    fn name(self) -> &'static str {
+        match self {
+            State::Closed { .. } => "closed",
+            State::Open { .. } => "open",
+            State::HalfOpen { .. } => "half_open",
+        }
+    }
+}
+
+const CLOSED: State = State::Closed {
+    failures: 0,
+    since: None,
+};
+
+/// Per-provider circuit breaker.
+///
+/// Closed → open after `failure_threshold` consecutive failures within
+/// `window`; open → half-open once `cooldown` passes, letting one trial call
+/// through; the trial's outcome closes the circuit or reopens it. Every
+/// transition is reported as `ObserverEvent::CircuitStateChanged`.
+pub struct CircuitBreaker {
+    provider: String,
+    settings: CircuitSettings,
+    state: Mutex<State>,
+    observer: Arc<dyn Observer>,
+}
+
+impl CircuitBreaker {
+    pub fn new(provider: &str, settings: CircuitSettings, observer: Arc<dyn Observer>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            settings,
+            state: Mutex::new(CLOSED),
+            observer,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.settings.failure_threshold > 0
+    }
+
+    /// Whether a call may go out now. `Err` carries how long until the next
+    /// trial call is allowed.
+    pub fn try_acquire(&self) -> Result<(), Duration> {
+        if !self.enabled() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        match *state {
+            State::Closed { .. } => Ok(()),
+            State::Open { until } if now < until => Err(until - now),
+            // A trial that never reported back (e.g. its future was dropped)
+            // doesn't block the provider for more than one cooldown
+            State::HalfOpen { started } if now < started + self.settings.cooldown => {
+                Err(started + self.settings.cooldown - now)
+            }
+            State::Open { .. } | State::HalfOpen { .. } => {
+                self.transition(&mut state, State::HalfOpen { started: now });
+                Ok(())
+            }
+        }
+    }
+
+    /// The provider answered (or rejected the request itself, which still
+    /// shows it is up).
+    pub fn record_success(&self) {
+        if !self.enabled() {
+            return;
+        }
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.transition(&mut state, CLOSED);
+    }
+
+    /// The provider failed in a way that suggests it is unhealthy.
+    pub fn record_failure(&self) {
+        if !self.enabled() {
+            return;
+        }
+        let now = Instant::now();
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let next = match *state {
+            State::Closed { failures, since } => {
+                let (failures, since) = match since {
+                    Some(since) if now.duration_since(since) <= self.settings.window => {
+                        (failures + 1, since)
+                    }
+                    _ => (1, now),
+                };
+                if failures >= self.settings.failure_threshold {
+                    State::Open {
+                        until: now + self.settings.cooldown,
+                    }
+                } else {
+                    State::Closed {
+                        failures,
+                        since: Some(since),
+                    }
+                }
+            }
+            State::Open { .. } | State::HalfOpen { .. } => State::Open {
+                until: now + self.settings.cooldown,
+            },
+        };
+        self.transition(&mut state, next);
+    }
+
+    pub fn is_open(&self) -> bool {
+        matches!(
+            *self
+                .state
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner),
+            State::Open { .. }
+        )
+    }
+
+    fn transition(&self, state: &mut State, next: State) {
+        let changed = state.name() != next.name();
+        *state = next;
+        if changed {
+            tracing::warn!(
+                provider = self.provider.as_str(),
+                state = next.name(),
+                "Provider circuit state changed"
+            );
+            self.observer
+                .record_event(&ObserverEvent::CircuitStateChanged {
+                    provider: self.provider.clone(),
+                    state: next.name().to_string(),
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::{NoopObserver, ObserverMetric};
+
+    #[derive(Default)]
+    struct StateLog(Mutex<Vec<String>>);
+
+    impl Observer for StateLog {
+        fn record_event(&self, event: &ObserverEvent) {
+            if let ObserverEvent::CircuitStateChanged { state, .. } = event {
+                self.0.lock().unwrap().push(state.clone());
+            }
+        }
+        fn record_metric(&self, _metric: &ObserverMetric) {}
+        fn name(&self) -> &str {
+            "state-log"
+        }
+    }
+
+    fn settings(threshold: u32, window_ms: u64, cooldown_ms: u64) -> CircuitSettings {
+        CircuitSettings {
+            failure_threshold: threshold,
+            window: Duration::from_millis(window_ms),
+            cooldown: Duration::from_millis(cooldown_ms),
+        }
+    }
+
+    #[test]
+    fn opens_after_threshold_then_half_opens_and_closes() {
+        let log = Arc::new(StateLog::default());
+        let breaker = CircuitBreaker::new("p", settings(2, 10_000, 40), log.clone());
+
+        breaker.record_failure();
+        assert!(breaker.try_acquire().is_ok());
+        breaker.record_failure();
+        assert!(breaker.try_acquire().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.try_acquire().is_ok(), "trial call after cooldown");
+        assert!(breaker.try_acquire().is_err(), "only one trial at a time");
+        breaker.record_success();
+        assert!(breaker.try_acquire().is_ok());
+
+        assert_eq!(*log.0.lock().unwrap(), ["open", "half_open", "closed"]);
+    }
+
+    #[test]
+    fn failed_trial_reopens() {
+        let breaker = CircuitBreaker::new("p", settings(1, 10_000, 30), Arc::new(NoopObserver));
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(breaker.try_acquire().is_ok());
+        breaker.record_failure();
+        assert!(breaker.is_open());
+        assert!(breaker.try_acquire().is_err());
+    }
+
+    #[test]
+    fn success_or_stale_failures_reset_the_count() {
+        let breaker = CircuitBreaker::new("p", settings(2, 30, 10_000), Arc::new(NoopObserver));
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        assert!(!breaker.is_open());
+
+        std::thread::sleep(Duration::from_millis(50));
+        breaker.record_failure();
+        assert!(!breaker.is_open(), "first failure fell outside the window");
+        breaker.record_failure();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn zero_threshold_disables_breaker() {
+        let breaker = CircuitBreaker::new("p", settings(0, 10, 10_000), Arc::new(NoopObserver));
+        for _ in 0..10 {
+            breaker.record_failure();
+        }
+        assert!(breaker.try_acquire().is_ok());
+    }
+}
@@ -2,10 +2,15 @@
 //! Most LLM APIs follow the same `/v1/chat/completions` format.
 //! This module provides a single implementation that works for all of them.
 
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    system_prompt, ChatMessage, ChatResponse as ProviderResponse, ChatStream, Provider,
+    ResponseUsage, Role, StreamEvent,
+};
 use async_trait::async_trait;
+use futures_util::stream::{self, Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// A provider that speaks the OpenAI-compatible chat completions API.
 /// Used by: Venice, Vercel AI Gateway, Cloudflare AI Gateway, Moonshot,
@@ -15,6 +20,10 @@ pub struct OpenAiCompatibleProvider {
     pub(crate) base_url: String,
     pub(crate) api_key: Option<String>,
     pub(crate) auth_header: AuthStyle,
+    /// Sent on every request, e.g. `api-version` for Azure
+    pub(crate) extra_headers: BTreeMap<String, String>,
+    /// Ask for `response_format: json_object` on chat completions
+    json_mode: bool,
     client: Client,
 }
 
@@ -36,6 +45,8 @@ impl OpenAiCompatibleProvider {
             base_url: base_url.trim_end_matches('/').to_string(),
             api_key: api_key.map(ToString::to_string),
             auth_header: auth_style,
+            extra_headers: BTreeMap::new(),
+            json_mode: false,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(120))
                 .connect_timeout(std::time::Duration::from_secs(10))
@@ -44,6 +55,21 @@ impl OpenAiCompatibleProvider {
         }
     }
 
+    /// Send `headers` on every request, alongside the auth header.
+    #[must_use]
+    pub fn with_headers(mut self, headers: &BTreeMap<String, String>) -> Self {
+        self.extra_headers.clone_from(headers);
+        self
+    }
+
+    /// Constrain chat completions to a JSON object (`agent.response_format`).
+    /// The endpoint must support `response_format`; most do.
+    #[must_use]
+    pub fn with_json_mode(mut self, enabled: bool) -> Self {
+        self.json_mode = enabled;
+        self
+    }
+
     /// Build the full URL for chat completions, detecting if base_url already includes the path.
     /// This allows custom providers with non-standard endpoints (e.g., VolcEngine ARK uses
     /// `/api/coding/v3/chat/completions` instead of `/v1/chat/completions`).
@@ -72,6 +98,24 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    /// Asks for a final usage chunk when streaming
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -83,6 +127,44 @@ struct Message {
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    prompt_tokens: u64,
+    #[serde(default)]
+    completion_tokens: u64,
+}
+
+/// One `data:` payload of a streamed completion.
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    #[serde(default)]
+    error: Option<StreamError>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: Option<StreamDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    message: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -169,12 +251,90 @@ fn extract_responses_text(response: ResponsesResponse) -> Option<String> {
     None
 }
 
+/// Turn a chat-completions SSE stream (`data: {...}` lines ending with
+/// `data: [DONE]`) into text deltas. Usage may come on several chunks (some
+/// servers repeat running totals), so only the last one is yielded, at the end.
+fn sse_deltas<S, B, E>(bytes: S) -> ChatStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    // (bytes, unparsed buffer, finished, latest usage)
+    let state = (Box::pin(bytes), Vec::<u8>::new(), false, None);
+    stream::unfold(
+        state,
+        |(mut bytes, mut buffer, finished, mut usage)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                let line = if let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                    buffer.drain(..=end).collect::<Vec<u8>>()
+                } else {
+                    match bytes.next().await {
+                        Some(Ok(chunk)) => {
+                            buffer.extend_from_slice(chunk.as_ref());
+                            continue;
+                        }
+                        Some(Err(e)) => return Some((Err(e.into()), (bytes, buffer, true, usage))),
+                        None if !buffer.is_empty() => std::mem::take(&mut buffer),
+                        None => {
+                            let state = (bytes, buffer, true, None);
+                            return Some((Ok(StreamEvent::Usage(usage?)), state));
+                        }
+                    }
+                };
+                let Some(data) = line.trim_ascii().strip_prefix(b"data:") else {
+                    continue;
+                };
+                let data = data.trim_ascii();
+                if data == b"[DONE]" {
+                    let state = (bytes, buffer, true, None);
+                    return Some((Ok(StreamEvent::Usage(usage?)), state));
+                }
+                match serde_json::from_slice::<StreamChunk>(data) {
+                    Ok(StreamChunk {
+                        error: Some(error), ..
+                    }) => {
+                        let err = anyhow::anyhow!("Stream error: {}", error.message);
+                        return Some((Err(err), (bytes, buffer, true, usage)));
+                    }
+                    Ok(chunk) => {
+                        if let Some(reported) = chunk.usage {
+                            usage = Some(ResponseUsage {
+                                input_tokens: reported.prompt_tokens,
+                                output_tokens: reported.completion_tokens,
+                            });
+                        }
+                        let text: String = chunk
+                            .choices
+                            .into_iter()
+                            .filter_map(|choice| choice.delta.and_then(|delta| delta.content))
+                            .collect();
+                        if !text.is_empty() {
+                            let state = (bytes, buffer, false, usage);
+                            return Some((Ok(StreamEvent::Text(text)), state));
+                        }
+                    }
+                    Err(e) => return Some((Err(e.into()), (bytes, buffer, true, usage))),
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
 impl OpenAiCompatibleProvider {
     fn apply_auth_header(
         &self,
         req: reqwest::RequestBuilder,
         api_key: &str,
     ) -> reqwest::RequestBuilder {
+        let req = self
+            .extra_headers
+            .iter()
+            .fold(req, |req, (name, value)| req.header(name, value));
         match &self.auth_header {
             AuthStyle::Bearer => req.header("Authorization", format!("Bearer {api_key}")),
             AuthStyle::XApiKey => req.header("x-api-key", api_key),
@@ -182,20 +342,59 @@ impl OpenAiCompatibleProvider {
         }
     }
 
+    fn api_key(&self) -> anyhow::Result<&str> {
+        self.api_key.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
+                self.name
+            )
+        })
+    }
+
+    fn chat_request(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        stream: bool,
+    ) -> ChatRequest {
+        ChatRequest {
+            model: model.to_string(),
+            messages: messages
+                .iter()
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content.clone(),
+                })
+                .collect(),
+            temperature,
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+            response_format: self.json_mode.then_some(ResponseFormat {
+                kind: "json_object",
+            }),
+        }
+    }
+
     async fn chat_via_responses(
         &self,
         api_key: &str,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
     ) -> anyhow::Result<String> {
         let request = ResponsesRequest {
             model: model.to_string(),
-            input: vec![ResponsesInput {
-                role: "user".to_string(),
-                content: message.to_string(),
-            }],
-            instructions: system_prompt.map(str::to_string),
+            input: messages
+                .iter()
+                .filter(|m| m.role != Role::System)
+                .map(|m| ResponsesInput {
+                    role: m.role.as_str().to_string(),
+                    content: m.content.clone(),
+                })
+                .collect(),
+            instructions: system_prompt(messages),
             stream: Some(false),
         };
 
@@ -220,39 +419,26 @@ impl OpenAiCompatibleProvider {
 
 #[async_trait]
 impl Provider for OpenAiCompatibleProvider {
-    async fn chat_with_system(
+    async fn chat(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let api_key = self.api_key.as_ref().ok_or_else(|| {
-            anyhow::anyhow!(
-                "{} API key not set. Run `zeroclaw onboard` or set the appropriate env var.",
-                self.name
-            )
-        })?;
-
-        let mut messages = Vec::new();
-
-        if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
-        }
-
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
+        Ok(self
+            .chat_with_usage(messages, model, temperature)
+            .await?
+            .text)
+    }
 
-        let request = ChatRequest {
-            model: model.to_string(),
-            messages,
-            temperature,
-        };
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderResponse> {
+        let api_key = self.api_key()?;
+        let request = self.chat_request(messages, model, temperature, false);
 
         let url = self.chat_completions_url();
 
@@ -267,8 +453,12 @@ impl Provider for OpenAiCompatibleProvider {
 
             if status == reqwest::StatusCode::NOT_FOUND {
                 return self
-                    .chat_via_responses(api_key, system_prompt, message, model)
+                    .chat_via_responses(api_key, messages, model)
                     .await
+                    .map(|text| ProviderResponse {
+                        text,
+                        ..ProviderResponse::default()
+                    })
                     .map_err(|responses_err| {
                         anyhow::anyhow!(
                             "{} API error: {error} (chat completions unavailable; responses fallback failed: {responses_err})",
@@ -281,19 +471,69 @@ impl Provider for OpenAiCompatibleProvider {
         }
 
         let chat_response: ChatResponse = response.json().await?;
+        let usage = chat_response.usage.map(|usage| ResponseUsage {
+            input_tokens: usage.prompt_tokens,
+            output_tokens: usage.completion_tokens,
+        });
 
         chat_response
             .choices
             .into_iter()
             .next()
-            .map(|c| c.message.content)
+            .map(|c| ProviderResponse {
+                text: c.message.content,
+                usage,
+                ..ProviderResponse::default()
+            })
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))
     }
+
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(ChatMessage::system(system_prompt));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_stream(&messages, model, temperature).await
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let api_key = self.api_key()?;
+        let request = self.chat_request(messages, model, temperature, true);
+        let response = self
+            .apply_auth_header(
+                self.client.post(self.chat_completions_url()).json(&request),
+                api_key,
+            )
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error = response.text().await?;
+            anyhow::bail!("{} API error: {error}", self.name);
+        }
+        Ok(sse_deltas(response.bytes_stream()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::http::HeaderMap;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::sync::{Arc, Mutex};
 
     fn make_provider(name: &str, url: &str, key: Option<&str>) -> OpenAiCompatibleProvider {
         OpenAiCompatibleProvider::new(name, url, key, AuthStyle::Bearer)
@@ -347,13 +587,28 @@ mod tests {
                 },
             ],
             temperature: 0.7,
+            stream: false,
+            stream_options: None,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("llama-3.3-70b"));
+        assert!(!json.contains("response_format"));
         assert!(json.contains("system"));
         assert!(json.contains("user"));
     }
 
+    #[test]
+    fn json_mode_requests_json_object() {
+        let p = make_provider("Venice", "https://api.venice.ai", None).with_json_mode(true);
+        let req = p.chat_request(&[ChatMessage::user("hi")], "m", 0.0, false);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(
+            json["response_format"],
+            serde_json::json!({"type": "json_object"})
+        );
+    }
+
     #[test]
     fn response_deserializes() {
         let json = r#"{"choices":[{"message":{"content":"Hello from Venice!"}}]}"#;
@@ -558,4 +813,117 @@ mod tests {
             "https://opencode.ai/zen/v1/chat/completions"
         );
     }
+
+    type Seen = Arc<Mutex<Vec<(HeaderMap, serde_json::Value)>>>;
+
+    /// Serve `/v1/chat/completions` with `body` as `content_type`, recording
+    /// each request's headers and JSON.
+    async fn serve(body: &'static str, content_type: &'static str) -> (String, Seen) {
+        let seen = Seen::default();
+        let log = Arc::clone(&seen);
+        let handler = move |headers: HeaderMap, Json(request): Json<serde_json::Value>| async move {
+            log.lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .push((headers, request));
+            ([("content-type", content_type)], body)
+        };
+        let router = Router::new().route("/v1/chat/completions", post(handler));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (format!("http://{addr}/v1"), seen)
+    }
+
+    #[tokio::test]
+    async fn completion_against_mock_server() {
+        let (url, seen) = serve(
+            r#"{"id":"chatcmpl-1","object":"chat.completion","choices":[{"index":0,"message":{"role":"assistant","content":"Hi there"},"finish_reason":"stop"}],"usage":{"prompt_tokens":9,"completion_tokens":2,"total_tokens":11}}"#,
+            "application/json",
+        )
+        .await;
+        let headers = BTreeMap::from([("api-version".to_string(), "2024-06-01".to_string())]);
+        let provider =
+            OpenAiCompatibleProvider::new("vLLM", &url, Some("sk-test"), AuthStyle::Bearer)
+                .with_headers(&headers);
+
+        let response = provider
+            .chat_with_usage(
+                &[ChatMessage::system("be brief"), ChatMessage::user("hello")],
+                "llama-3.1-8b",
+                0.3,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text, "Hi there");
+        assert_eq!(
+            response.usage,
+            Some(ResponseUsage {
+                input_tokens: 9,
+                output_tokens: 2,
+            })
+        );
+
+        let (headers, request) = seen.lock().unwrap()[0].clone();
+        assert_eq!(headers["authorization"], "Bearer sk-test");
+        assert_eq!(headers["api-version"], "2024-06-01");
+        assert_eq!(request["model"], "llama-3.1-8b");
+        assert!(request.get("stream").is_none());
+        assert_eq!(
+            request["messages"],
+            serde_json::json!([
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "hello"},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_against_mock_server() {
+        let (url, seen) = serve(
+            concat!(
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hel\"}}]}\n\n",
+                ": keep-alive\n\n",
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"lo\"}}]}\n\n",
+                "data: {\"choices\":[{\"index\":0,\"delta\":{},\"finish_reason\":\"stop\"}]}\n\n",
+                "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":9,\"completion_tokens\":2}}\n\n",
+                "data: [DONE]\n\n",
+            ),
+            "text/event-stream",
+        )
+        .await;
+        let provider = make_provider("vLLM", &url, Some("sk-test"));
+        let chunks: Vec<StreamEvent> = provider
+            .chat_stream(&[ChatMessage::user("hi")], "llama-3.1-8b", 0.7)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            [
+                StreamEvent::Text("Hel".into()),
+                StreamEvent::Text("lo".into()),
+                StreamEvent::Usage(ResponseUsage {
+                    input_tokens: 9,
+                    output_tokens: 2,
+                }),
+            ]
+        );
+        let request = &seen.lock().unwrap()[0].1;
+        assert_eq!(request["stream"], true);
+        assert_eq!(request["stream_options"]["include_usage"], true);
+    }
+
+    #[tokio::test]
+    async fn sse_error_payload_ends_stream() {
+        let bytes = stream::iter(vec![Ok::<_, std::io::Error>(
+            b"data: {\"error\":{\"message\":\"model overloaded\"}}\n\n".to_vec(),
+        )]);
+        let chunks: Vec<anyhow::Result<StreamEvent>> = sse_deltas(bytes).collect().await;
+        assert_eq!(chunks.len(), 1);
+        let err = chunks.into_iter().next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Stream error: model overloaded");
+    }
 }
@@ -3,7 +3,7 @@
 //! - Gemini CLI OAuth tokens (reuse existing ~/.gemini/ authentication)
 //! - Google Cloud ADC (`GOOGLE_APPLICATION_CREDENTIALS`)
 
-use crate::providers::traits::Provider;
+use crate::providers::traits::{system_prompt, ChatMessage, Provider, Role};
 use async_trait::async_trait;
 use directories::UserDirs;
 use reqwest::Client;
@@ -181,10 +181,9 @@ impl GeminiProvider {
 
 #[async_trait]
 impl Provider for GeminiProvider {
-    async fn chat_with_system(
+    async fn chat(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
@@ -199,20 +198,31 @@ impl Provider for GeminiProvider {
         })?;
 
         // Build request
-        let system_instruction = system_prompt.map(|sys| Content {
+        let system_instruction = system_prompt(messages).map(|sys| Content {
             role: None,
-            parts: vec![Part {
-                text: sys.to_string(),
-            }],
+            parts: vec![Part { text: sys }],
         });
 
+        // Gemini calls the assistant role "model"
+        let contents = messages
+            .iter()
+            .filter_map(|m| {
+                let role = match m.role {
+                    Role::System => return None,
+                    Role::User => "user",
+                    Role::Assistant => "model",
+                };
+                Some(Content {
+                    role: Some(role.to_string()),
+                    parts: vec![Part {
+                        text: m.content.clone(),
+                    }],
+                })
+            })
+            .collect();
+
         let request = GenerateContentRequest {
-            contents: vec![Content {
-                role: Some("user".to_string()),
-                parts: vec![Part {
-                    text: message.to_string(),
-                }],
-            }],
+            contents,
             system_instruction,
             generation_config: GenerationConfig {
                 temperature,
@@ -0,0 +1,320 @@
+use super::traits::{
+    ChatMessage, ChatResponse, ChatStream, ContentPart, InferenceParams, Provider, WarmupStatus,
+};
+use crate::config::ReliabilityConfig;
+use crate::tools::ToolSpec;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Token bucket that refills continuously at `per_second` tokens per second
+/// and holds at most one second's worth, so bursts stay short.
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    per_second: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn per_minute(requests_per_minute: u32, now: Instant) -> Self {
+        let per_second = f64::from(requests_per_minute) / 60.0;
+        let capacity = per_second.ceil().max(1.0);
+        Self {
+            capacity,
+            per_second,
+            tokens: capacity,
+            refilled_at: now,
+        }
+    }
+
+    /// Take a token, or return how long until one is available.
+    fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+/// Provider wrapper that caps in-flight requests and, optionally, their rate.
+///
+/// Callers over the limit wait their turn instead of failing. The limits live
+/// in the wrapper, so every channel sharing the `Arc`'d provider shares them.
+/// Streaming calls keep their slot until the stream is dropped.
+pub struct LimitedProvider {
+    inner: Box<dyn Provider>,
+    permits: Option<Arc<Semaphore>>,
+    bucket: Option<Mutex<TokenBucket>>,
+}
+
+impl LimitedProvider {
+    /// `max_concurrent` of 0 and `requests_per_minute` of 0 mean no limit.
+    pub fn new(inner: Box<dyn Provider>, max_concurrent: usize, requests_per_minute: u32) -> Self {
+        Self {
+            inner,
+            permits: (max_concurrent > 0).then(|| Arc::new(Semaphore::new(max_concurrent))),
+            bucket: (requests_per_minute > 0)
+                .then(|| Mutex::new(TokenBucket::per_minute(requests_per_minute, Instant::now()))),
+        }
+    }
+
+    /// Wrap `inner` with the `[reliability]` limits, or return it as is when
+    /// both are off.
+    pub fn wrap(inner: Box<dyn Provider>, config: &ReliabilityConfig) -> Box<dyn Provider> {
+        if config.max_concurrent_requests == 0 && config.requests_per_minute == 0 {
+            return inner;
+        }
+        Box::new(Self::new(
+            inner,
+            config.max_concurrent_requests,
+            config.requests_per_minute,
+        ))
+    }
+
+    /// Wait for a free slot, then for the rate limit.
+    async fn acquire(&self) -> anyhow::Result<Option<OwnedSemaphorePermit>> {
+        let permit = match &self.permits {
+            Some(permits) => Some(Arc::clone(permits).acquire_owned().await?),
+            None => None,
+        };
+        if let Some(bucket) = &self.bucket {
+            loop {
+                let wait = bucket
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .try_take(Instant::now());
+                match wait {
+                    Ok(()) => break,
+                    Err(delay) => {
+                        tracing::debug!(
+                            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                            "Provider rate limit reached, waiting"
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                }
+            }
+        }
+        Ok(permit)
+    }
+}
+
+#[async_trait]
+impl Provider for LimitedProvider {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let _permit = self.acquire().await?;
+        self.inner.chat(messages, model, temperature).await
+    }
+
+    async fn chat_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .chat_with_usage(messages, model, temperature)
+            .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .chat_with_tools(messages, tools, model, temperature)
+            .await
+    }
+
+    async fn chat_with_params(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        params: &InferenceParams,
+    ) -> anyhow::Result<String> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .chat_with_params(messages, model, temperature, params)
+            .await
+    }
+
+    async fn embed(&self, texts: &[String], model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        let _permit = self.acquire().await?;
+        self.inner.embed(texts, model).await
+    }
+
+    async fn chat_with_parts(
+        &self,
+        system_prompt: Option<&str>,
+        parts: Vec<ContentPart>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let _permit = self.acquire().await?;
+        self.inner
+            .chat_with_parts(system_prompt, parts, model, temperature)
+            .await
+    }
+
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let permit = self.acquire().await?;
+        let stream = self
+            .inner
+            .chat_stream_with_system(system_prompt, message, model, temperature)
+            .await?;
+        Ok(hold_permit(stream, permit))
+    }
+
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let permit = self.acquire().await?;
+        let stream = self.inner.chat_stream(messages, model, temperature).await?;
+        Ok(hold_permit(stream, permit))
+    }
+
+    async fn warmup(&self) -> WarmupStatus {
+        self.inner.warmup().await
+    }
+}
+
+/// Keep the concurrency slot until the caller drops the stream.
+fn hold_permit(stream: ChatStream, permit: Option<OwnedSemaphorePermit>) -> ChatStream {
+    stream
+        .map(move |chunk| {
+            let _held = &permit;
+            chunk
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Sleeps on every call and records the highest concurrency it saw.
+    #[derive(Default)]
+    struct ConcurrencyProbe {
+        in_flight: AtomicUsize,
+        max_seen: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Provider for ConcurrencyProbe {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_seen.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(message.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_calls_never_exceed_permit_count() {
+        let probe = ConcurrencyProbe::default();
+        let max_seen = Arc::clone(&probe.max_seen);
+        let provider: Arc<dyn Provider> = Arc::new(LimitedProvider::new(Box::new(probe), 3, 0));
+
+        let calls: Vec<_> = (0..12)
+            .map(|i| {
+                let provider = Arc::clone(&provider);
+                tokio::spawn(async move {
+                    provider
+                        .chat_with_system(None, &i.to_string(), "m", 0.0)
+                        .await
+                })
+            })
+            .collect();
+        for (i, call) in calls.into_iter().enumerate() {
+            assert_eq!(call.await.unwrap().unwrap(), i.to_string());
+        }
+
+        assert_eq!(max_seen.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_holds_its_slot_until_dropped() {
+        let provider = LimitedProvider::new(Box::new(ConcurrencyProbe::default()), 1, 0);
+        let stream = provider
+            .chat_stream_with_system(None, "hi", "m", 0.0)
+            .await
+            .unwrap();
+        let permits = provider.permits.as_ref().unwrap();
+        assert_eq!(permits.available_permits(), 0);
+        drop(stream);
+        assert_eq!(permits.available_permits(), 1);
+    }
+
+    #[test]
+    fn token_bucket_allows_a_burst_then_paces() {
+        let start = Instant::now();
+        // 120/min = 2 per second, burst of 2
+        let mut bucket = TokenBucket::per_minute(120, start);
+        assert!(bucket.try_take(start).is_ok());
+        assert!(bucket.try_take(start).is_ok());
+        let wait = bucket.try_take(start).unwrap_err();
+        assert_eq!(wait, Duration::from_millis(500));
+
+        assert!(bucket.try_take(start + Duration::from_millis(500)).is_ok());
+        assert!(bucket.try_take(start + Duration::from_millis(500)).is_err());
+        // Idle time never banks more than one burst
+        let later = start + Duration::from_secs(60);
+        assert!(bucket.try_take(later).is_ok());
+        assert!(bucket.try_take(later).is_ok());
+        assert!(bucket.try_take(later).is_err());
+    }
+}
@@ -0,0 +1,293 @@
+use crate::providers::tokenizer::{HeuristicTokenizer, Tokenizer};
+use crate::providers::traits::{
+    ChatMessage, ChatResponse, ChatStream, ContentPart, Provider, ResponseUsage, Role, StreamEvent,
+};
+use crate::tools::{ToolCall, ToolSpec};
+use async_trait::async_trait;
+use futures_util::stream::{self, StreamExt};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Dimensions of the deterministic vectors returned by `embed`.
+const EMBEDDING_DIMENSIONS: usize = 16;
+
+/// Offline provider for tests and demos: answers with scripted responses in
+/// order, then echoes the last user message once the script runs out.
+///
+/// A scripted response is returned as text, so `<tool_call>` tags in it are
+/// picked up by the agent loop. A response that is a JSON object with a
+/// `tool_calls` array (`{"text": "...", "tool_calls": [{"name": ..,
+/// "arguments": {..}}]}`) is instead returned through `chat_with_tools` as
+/// native tool calls.
+pub struct MockProvider {
+    script: Mutex<VecDeque<String>>,
+}
+
+/// One parsed reply: text plus any native tool calls.
+struct Reply {
+    text: String,
+    tool_calls: Vec<ToolCall>,
+}
+
+impl MockProvider {
+    pub fn new(responses: Vec<String>) -> Self {
+        Self {
+            script: Mutex::new(responses.into()),
+        }
+    }
+
+    /// Take the next scripted response, or echo the last user message.
+    fn next_reply(&self, messages: &[ChatMessage]) -> Reply {
+        let scripted = self
+            .script
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .pop_front();
+        match scripted {
+            Some(response) => parse_reply(response),
+            None => Reply {
+                text: messages
+                    .iter()
+                    .rev()
+                    .find(|m| m.role == Role::User)
+                    .map(|m| m.content.clone())
+                    .unwrap_or_default(),
+                tool_calls: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Read a `{"text", "tool_calls"}` object; anything else is plain text.
+fn parse_reply(response: String) -> Reply {
+    let Ok(serde_json::Value::Object(object)) = serde_json::from_str(&response) else {
+        return Reply {
+            text: response,
+            tool_calls: Vec::new(),
+        };
+    };
+    let Some(calls) = object.get("tool_calls").and_then(|v| v.as_array()) else {
+        return Reply {
+            text: response,
+            tool_calls: Vec::new(),
+        };
+    };
+    Reply {
+        text: object
+            .get("text")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        tool_calls: calls
+            .iter()
+            .filter_map(|call| {
+                Some(ToolCall {
+                    name: call.get("name")?.as_str()?.to_string(),
+                    arguments: call
+                        .get("arguments")
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!({})),
+                })
+            })
+            .collect(),
+    }
+}
+
+/// Rough token count (4 characters per token), so usage-based budgets can
+/// be exercised without a real tokenizer.
+fn estimate_tokens(text: &str) -> u64 {
+    HeuristicTokenizer.count_tokens(text) as u64
+}
+
+#[async_trait]
+impl Provider for MockProvider {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .chat_with_usage(messages, model, temperature)
+            .await?
+            .text)
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let mut response = self
+            .chat_with_tools(messages, &[], model, temperature)
+            .await?;
+        // Without tools on offer, native calls are folded back into the text
+        for call in std::mem::take(&mut response.tool_calls) {
+            let _ = write!(
+                response.text,
+                "\n<tool_call>{}</tool_call>",
+                serde_json::json!({"name": call.name, "arguments": call.arguments})
+            );
+        }
+        Ok(response)
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        _tools: &[ToolSpec],
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let reply = self.next_reply(messages);
+        let input_tokens = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+        Ok(ChatResponse {
+            usage: Some(ResponseUsage {
+                input_tokens,
+                output_tokens: estimate_tokens(&reply.text),
+            }),
+            text: reply.text,
+            tool_calls: reply.tool_calls,
+            reasoning: None,
+        })
+    }
+
+    /// Deterministic bag-of-bytes vectors: equal texts embed identically and
+    /// texts sharing characters score closer than unrelated ones.
+    async fn embed(&self, texts: &[String], _model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        Ok(texts
+            .iter()
+            .map(|text| {
+                let mut vector = vec![0.0_f32; EMBEDDING_DIMENSIONS];
+                for byte in text.to_lowercase().bytes() {
+                    vector[usize::from(byte) % EMBEDDING_DIMENSIONS] += 1.0;
+                }
+                let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+                if norm > 0.0 {
+                    for v in &mut vector {
+                        *v /= norm;
+                    }
+                }
+                vector
+            })
+            .collect())
+    }
+
+    /// Images are accepted and stand in as `[image: N bytes]` placeholders.
+    async fn chat_with_parts(
+        &self,
+        system_prompt: Option<&str>,
+        parts: Vec<ContentPart>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let texts: Vec<String> = parts
+            .into_iter()
+            .map(|part| match part {
+                ContentPart::Text(text) => text,
+                ContentPart::Image { data, .. } => format!("[image: {} bytes]", data.len()),
+            })
+            .collect();
+        self.chat_with_system(system_prompt, &texts.join("\n\n"), model, temperature)
+            .await
+    }
+
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(ChatMessage::system(system_prompt));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_stream(&messages, model, temperature).await
+    }
+
+    /// Streams the reply word by word, so consumers see several deltas, then
+    /// the usage.
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self.chat_with_usage(messages, model, temperature).await?;
+        let chunks: Vec<anyhow::Result<StreamEvent>> = response
+            .text
+            .split_inclusive(' ')
+            .map(|chunk| StreamEvent::Text(chunk.to_string()))
+            .chain(response.usage.map(StreamEvent::Usage))
+            .map(Ok)
+            .collect();
+        Ok(stream::iter(chunks).boxed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn scripted_responses_then_echo() {
+        let provider = MockProvider::new(vec!["one".into(), "two".into()]);
+        let messages = [ChatMessage::user("ping")];
+        assert_eq!(provider.chat(&messages, "m", 0.0).await.unwrap(), "one");
+        assert_eq!(provider.chat(&messages, "m", 0.0).await.unwrap(), "two");
+        assert_eq!(provider.chat(&messages, "m", 0.0).await.unwrap(), "ping");
+    }
+
+    #[tokio::test]
+    async fn json_script_entries_become_native_tool_calls() {
+        let provider = MockProvider::new(vec![
+            r#"{"text": "checking", "tool_calls": [{"name": "shell", "arguments": {"command": "ls"}}]}"#
+                .into(),
+        ]);
+        let response = provider
+            .chat_with_tools(&[ChatMessage::user("go")], &[], "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(response.text, "checking");
+        assert_eq!(
+            response.tool_calls,
+            vec![ToolCall {
+                name: "shell".into(),
+                arguments: serde_json::json!({"command": "ls"}),
+            }]
+        );
+        assert!(response.usage.unwrap().output_tokens > 0);
+    }
+
+    #[tokio::test]
+    async fn stream_yields_word_chunks() {
+        let provider = MockProvider::new(vec!["hello streaming world".into()]);
+        let mut chunks: Vec<StreamEvent> = provider
+            .chat_stream(&[ChatMessage::user("x")], "m", 0.0)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert!(matches!(chunks.pop(), Some(StreamEvent::Usage(_))));
+        let text: Vec<String> = chunks
+            .into_iter()
+            .filter_map(StreamEvent::into_text)
+            .collect();
+        assert_eq!(text, ["hello ", "streaming ", "world"]);
+    }
+
+    #[tokio::test]
+    async fn embeddings_are_deterministic() {
+        let provider = MockProvider::new(Vec::new());
+        let texts = ["rust".to_string(), "rust".to_string(), "zzz".to_string()];
+        let vectors = provider.embed(&texts, "m").await.unwrap();
+        assert_eq!(vectors[0], vectors[1]);
+        assert_ne!(vectors[0], vectors[2]);
+        assert_eq!(vectors[0].len(), EMBEDDING_DIMENSIONS);
+    }
+}
@@ -1,18 +1,25 @@
 pub mod anthropic;
 pub mod bedrock;
+pub mod circuit;
 pub mod compatible;
 pub mod gemini;
+pub mod limiter;
+pub mod mock;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
 pub mod reliable;
+mod retry;
 pub mod router;
+pub mod tokenizer;
 pub mod traits;
 
-pub use traits::Provider;
+pub use traits::{ChatMessage, ChatResponse, Provider, WarmupStatus};
 
+use circuit::CircuitSettings;
 use compatible::{AuthStyle, OpenAiCompatibleProvider};
 use reliable::ReliableProvider;
+use std::sync::Arc;
 
 const MAX_API_ERROR_CHARS: usize = 200;
 
@@ -157,21 +164,80 @@ fn resolve_api_key(name: &str, api_key: Option<&str>) -> Option<String> {
     None
 }
 
+/// Provider-specific settings that don't fit the shared `name` + `api_key` shape.
+#[derive(Debug, Clone, Default)]
+pub struct ProviderOptions {
+    pub bedrock: crate::config::BedrockConfig,
+    pub openrouter: crate::config::OpenRouterConfig,
+    pub ollama: crate::config::OllamaConfig,
+    pub openai_compat: crate::config::OpenAiCompatConfig,
+    /// Scripted replies for the offline `mock` provider
+    pub mock: crate::config::MockProviderConfig,
+    /// Retry budget for providers that retry transient HTTP errors themselves
+    pub reliability: crate::config::ReliabilityConfig,
+    /// Request reasoning from models that can return it (`agent.show_reasoning`)
+    pub show_reasoning: bool,
+    /// Ask providers that support it for JSON output (`agent.response_format`)
+    pub json_mode: bool,
+}
+
+impl ProviderOptions {
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            bedrock: config.bedrock.clone(),
+            openrouter: config.openrouter.clone(),
+            ollama: config.ollama.clone(),
+            openai_compat: config.openai_compat.clone(),
+            mock: config.mock.clone(),
+            reliability: config.reliability.clone(),
+            show_reasoning: config.agent.show_reasoning,
+            json_mode: config.agent.response_format == crate::config::ResponseFormat::Json,
+        }
+    }
+}
+
 /// Factory: create the right provider from config
-#[allow(clippy::too_many_lines)]
 pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<dyn Provider>> {
+    create_provider_with_options(name, api_key, &ProviderOptions::default())
+}
+
+/// Factory: create the right provider, honoring provider-specific options
+#[allow(clippy::too_many_lines)]
+pub fn create_provider_with_options(
+    name: &str,
+    api_key: Option<&str>,
+    options: &ProviderOptions,
+) -> anyhow::Result<Box<dyn Provider>> {
     let _resolved_key = resolve_api_key(name, api_key);
     match name {
         // ── Primary providers (custom implementations) ───────
-        "openrouter" => Ok(Box::new(openrouter::OpenRouterProvider::new(api_key))),
+        "openrouter" => Ok(Box::new(
+            openrouter::OpenRouterProvider::new(api_key)
+                .with_retries(
+                    options.reliability.provider_retries,
+                    options.reliability.provider_backoff_ms,
+                )
+                .with_attribution(
+                    options.openrouter.app_url.as_deref(),
+                    options.openrouter.app_name.as_deref(),
+                ),
+        )),
         "anthropic" => Ok(Box::new(anthropic::AnthropicProvider::new(api_key))),
-        "openai" => Ok(Box::new(openai::OpenAiProvider::new(api_key))),
+        "openai" => Ok(Box::new(
+            openai::OpenAiProvider::new(api_key).with_json_mode(options.json_mode),
+        )),
         // Ollama is a local service that doesn't use API keys.
-        // The api_key parameter is ignored to avoid it being misinterpreted as a base_url.
-        "ollama" => Ok(Box::new(ollama::OllamaProvider::new(None))),
+        // The api_key parameter is ignored to avoid it being misinterpreted as a base_url;
+        // the server URL comes from `[ollama] base_url`.
+        "ollama" => Ok(Box::new(
+            ollama::OllamaProvider::new(options.ollama.base_url.as_deref())
+                .with_json_mode(options.json_mode),
+        )),
         "gemini" | "google" | "google-gemini" => {
             Ok(Box::new(gemini::GeminiProvider::new(api_key)))
         }
+        // Offline scripted provider for tests and demos; needs no credentials.
+        "mock" => Ok(Box::new(mock::MockProvider::new(options.mock.responses.clone()))),
 
         // ── OpenAI-compatible providers ──────────────────────
         "venice" => Ok(Box::new(OpenAiCompatibleProvider::new(
@@ -204,7 +270,15 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
         "minimax" => Ok(Box::new(OpenAiCompatibleProvider::new(
             "MiniMax", "https://api.minimax.chat", api_key, AuthStyle::Bearer,
         ))),
-        "bedrock" | "aws-bedrock" => Ok(Box::new(bedrock::BedrockProvider::new())),
+        "bedrock" | "aws-bedrock" => Ok(Box::new(
+            bedrock::BedrockProvider::new(&options.bedrock)
+                .with_retries(
+                    options.reliability.provider_retries,
+                    options.reliability.provider_backoff_ms,
+                )
+                .with_reasoning(options.show_reasoning)
+                .with_json_mode(options.json_mode),
+        )),
         "qianfan" | "baidu" => Ok(Box::new(OpenAiCompatibleProvider::new(
             "Qianfan", "https://aip.baidubce.com", api_key, AuthStyle::Bearer,
         ))),
@@ -245,12 +319,21 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
             if base_url.is_empty() {
                 anyhow::bail!("Custom provider requires a URL. Format: custom:https://your-api.com");
             }
-            Ok(Box::new(OpenAiCompatibleProvider::new(
-                "Custom",
-                base_url,
-                api_key,
-                AuthStyle::Bearer,
-            )))
+            Ok(Box::new(
+                openai_compat_provider("Custom", base_url, api_key, &options.openai_compat)
+                    .with_json_mode(options.json_mode),
+            ))
+        }
+        // Same, with the URL taken from `[openai_compat] base_url`
+        "openai-compat" | "openai-compatible" => {
+            let base_url = options.openai_compat.base_url.as_deref().unwrap_or("");
+            if base_url.is_empty() {
+                anyhow::bail!("openai-compat provider requires [openai_compat] base_url in config.toml");
+            }
+            Ok(Box::new(
+                openai_compat_provider("OpenAI-compatible", base_url, api_key, &options.openai_compat)
+                    .with_json_mode(options.json_mode),
+            ))
         }
 
         // ── Anthropic-compatible custom endpoints ───────────
@@ -273,17 +356,49 @@ pub fn create_provider(name: &str, api_key: Option<&str>) -> anyhow::Result<Box<
     }
 }
 
-/// Create provider chain with retry and fallback behavior.
+/// An OpenAI-compatible provider with the `[openai_compat]` auth header and
+/// extra headers applied.
+fn openai_compat_provider(
+    name: &str,
+    base_url: &str,
+    api_key: Option<&str>,
+    config: &crate::config::OpenAiCompatConfig,
+) -> OpenAiCompatibleProvider {
+    let auth_style = match config.auth_header.as_deref().filter(|h| !h.is_empty()) {
+        Some(header) => AuthStyle::Custom(header.to_string()),
+        None => AuthStyle::Bearer,
+    };
+    OpenAiCompatibleProvider::new(name, base_url, api_key, auth_style).with_headers(&config.headers)
+}
+
+/// Create provider chain with retry, fallback and circuit-breaker behavior;
+/// circuit state changes are reported to `observer`. Calls through it are
+/// held to the `[reliability]` concurrency and rate limits.
 pub fn create_resilient_provider(
     primary_name: &str,
     api_key: Option<&str>,
     reliability: &crate::config::ReliabilityConfig,
+    options: &ProviderOptions,
+    observer: &Arc<dyn crate::observability::Observer>,
+) -> anyhow::Result<Box<dyn Provider>> {
+    let provider = build_resilient_provider(primary_name, api_key, reliability, options, observer)?;
+    Ok(limiter::LimitedProvider::wrap(provider, reliability))
+}
+
+/// The retry/fallback chain behind `create_resilient_provider`, without the
+/// rate limits (the router applies those once, around all of its routes).
+fn build_resilient_provider(
+    primary_name: &str,
+    api_key: Option<&str>,
+    reliability: &crate::config::ReliabilityConfig,
+    options: &ProviderOptions,
+    observer: &Arc<dyn crate::observability::Observer>,
 ) -> anyhow::Result<Box<dyn Provider>> {
     let mut providers: Vec<(String, Box<dyn Provider>)> = Vec::new();
 
     providers.push((
         primary_name.to_string(),
-        create_provider(primary_name, api_key)?,
+        create_provider_with_options(primary_name, api_key, options)?,
     ));
 
     for fallback in &reliability.fallback_providers {
@@ -300,7 +415,7 @@ pub fn create_resilient_provider(
             );
         }
 
-        match create_provider(fallback, api_key) {
+        match create_provider_with_options(fallback, api_key, options) {
             Ok(provider) => providers.push((fallback.clone(), provider)),
             Err(e) => {
                 tracing::warn!(
@@ -311,25 +426,32 @@ pub fn create_resilient_provider(
         }
     }
 
-    Ok(Box::new(ReliableProvider::new(
-        providers,
-        reliability.provider_retries,
-        reliability.provider_backoff_ms,
-    )))
+    Ok(Box::new(
+        ReliableProvider::new(
+            providers,
+            reliability.provider_retries,
+            reliability.provider_backoff_ms,
+        )
+        .with_circuit_breaker(CircuitSettings::from_config(reliability), observer),
+    ))
 }
 
 /// Create a RouterProvider if model routes are configured, otherwise return a
 /// standard resilient provider. The router wraps individual providers per route,
-/// each with its own retry/fallback chain.
+/// each with its own retry/fallback chain. Routes sharing a hint fall back to
+/// one another in config order, reported to `observer`. One set of
+/// `[reliability]` concurrency and rate limits covers all routes.
 pub fn create_routed_provider(
     primary_name: &str,
     api_key: Option<&str>,
     reliability: &crate::config::ReliabilityConfig,
     model_routes: &[crate::config::ModelRouteConfig],
     default_model: &str,
+    options: &ProviderOptions,
+    observer: Arc<dyn crate::observability::Observer>,
 ) -> anyhow::Result<Box<dyn Provider>> {
     if model_routes.is_empty() {
-        return create_resilient_provider(primary_name, api_key, reliability);
+        return create_resilient_provider(primary_name, api_key, reliability, options, &observer);
     }
 
     // Collect unique provider names needed
@@ -348,7 +470,7 @@ pub fn create_routed_provider(
             .find(|r| &r.provider == name)
             .and_then(|r| r.api_key.as_deref())
             .or(api_key);
-        match create_resilient_provider(name, key, reliability) {
+        match build_resilient_provider(name, key, reliability, options, &observer) {
             Ok(provider) => providers.push((name.clone(), provider)),
             Err(e) => {
                 if name == primary_name {
@@ -376,16 +498,51 @@ pub fn create_routed_provider(
         })
         .collect();
 
-    Ok(Box::new(router::RouterProvider::new(
-        providers,
-        routes,
-        default_model.to_string(),
-    )))
+    Ok(limiter::LimitedProvider::wrap(
+        Box::new(
+            router::RouterProvider::new(providers, routes, default_model.to_string())
+                .with_observer(observer),
+        ),
+        reliability,
+    ))
+}
+
+/// Warm `provider` up and report the outcome to `observer` as
+/// `ObserverEvent::Warmup`. An unreachable provider is only logged: the
+/// first real request will surface the error again.
+pub async fn warm_up(
+    provider: &dyn Provider,
+    name: &str,
+    observer: &dyn crate::observability::Observer,
+) -> WarmupStatus {
+    let status = provider.warmup().await;
+    if status.reachable {
+        tracing::info!(
+            provider = name,
+            latency_ms = u64::try_from(status.latency.as_millis()).unwrap_or(u64::MAX),
+            "Provider warmed up: {}",
+            status.detail
+        );
+    } else {
+        tracing::warn!(
+            provider = name,
+            "Provider warmup failed (non-fatal): {}",
+            status.detail
+        );
+    }
+    observer.record_event(&crate::observability::ObserverEvent::Warmup {
+        provider: name.to_string(),
+        reachable: status.reachable,
+        latency: status.latency,
+        detail: status.detail.clone(),
+    });
+    status
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use traits::{ContentPart, InferenceParams};
 
     // ── Primary providers ────────────────────────────────────
 
@@ -569,6 +726,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn factory_openai_compat_requires_base_url() {
+        match create_provider("openai-compat", Some("key")) {
+            Err(e) => assert!(e.to_string().contains("[openai_compat] base_url")),
+            Ok(_) => panic!("Expected error without [openai_compat] base_url"),
+        }
+    }
+
+    #[test]
+    fn factory_openai_compat_uses_configured_url() {
+        let options = ProviderOptions {
+            openai_compat: crate::config::OpenAiCompatConfig {
+                base_url: Some("http://localhost:8000/v1".into()),
+                auth_header: Some("api-key".into()),
+                headers: std::collections::BTreeMap::from([(
+                    "api-version".to_string(),
+                    "2024-06-01".to_string(),
+                )]),
+            },
+            ..ProviderOptions::default()
+        };
+        assert!(create_provider_with_options("openai-compat", Some("key"), &options).is_ok());
+    }
+
     // ── Anthropic-compatible custom endpoints ─────────────────
 
     #[test]
@@ -616,6 +797,10 @@ mod tests {
         assert!(create_provider("", None).is_err());
     }
 
+    fn noop_observer() -> Arc<dyn crate::observability::Observer> {
+        Arc::new(crate::observability::NoopObserver)
+    }
+
     #[test]
     fn resilient_provider_ignores_duplicate_and_invalid_fallbacks() {
         let reliability = crate::config::ReliabilityConfig {
@@ -631,19 +816,176 @@ mod tests {
             channel_max_backoff_secs: 60,
             scheduler_poll_secs: 15,
             scheduler_retries: 2,
+            ..crate::config::ReliabilityConfig::default()
         };
 
-        let provider = create_resilient_provider("openrouter", Some("sk-test"), &reliability);
+        let provider = create_resilient_provider(
+            "openrouter",
+            Some("sk-test"),
+            &reliability,
+            &ProviderOptions::default(),
+            &noop_observer(),
+        );
         assert!(provider.is_ok());
     }
 
     #[test]
     fn resilient_provider_errors_for_invalid_primary() {
         let reliability = crate::config::ReliabilityConfig::default();
-        let provider = create_resilient_provider("totally-invalid", Some("sk-test"), &reliability);
+        let provider = create_resilient_provider(
+            "totally-invalid",
+            Some("sk-test"),
+            &reliability,
+            &ProviderOptions::default(),
+            &noop_observer(),
+        );
         assert!(provider.is_err());
     }
 
+    #[tokio::test]
+    async fn routed_provider_passes_images_through() {
+        let routes = [crate::config::ModelRouteConfig {
+            hint: "vision".into(),
+            provider: "mock".into(),
+            model: "mock-vision".into(),
+            api_key: None,
+        }];
+        let provider = create_routed_provider(
+            "mock",
+            None,
+            &crate::config::ReliabilityConfig::default(),
+            &routes,
+            "mock-default",
+            &ProviderOptions::default(),
+            noop_observer(),
+        )
+        .unwrap();
+        let parts = vec![
+            ContentPart::Text("What is this?".into()),
+            ContentPart::Image {
+                data: vec![0; 4],
+                format: None,
+            },
+        ];
+
+        for model in ["hint:vision", "mock-default"] {
+            let reply = provider
+                .chat_with_parts(None, parts.clone(), model, 0.0)
+                .await
+                .unwrap();
+            assert_eq!(reply, "What is this?\n\n[image: 4 bytes]");
+        }
+    }
+
+    #[tokio::test]
+    async fn bedrock_retries_come_from_reliability_config() {
+        let hits = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let router = axum::Router::new().fallback(move || {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async { (axum::http::StatusCode::TOO_MANY_REQUESTS, "slow down") }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let options = ProviderOptions {
+            bedrock: crate::config::BedrockConfig {
+                region: Some("us-east-1".into()),
+                access_key_id: Some("AKIATEST".into()),
+                secret_access_key: Some("secret".into()),
+                endpoint_url: Some(format!("http://{addr}")),
+                ..crate::config::BedrockConfig::default()
+            },
+            reliability: crate::config::ReliabilityConfig {
+                provider_retries: 1,
+                provider_backoff_ms: 1,
+                ..crate::config::ReliabilityConfig::default()
+            },
+            ..ProviderOptions::default()
+        };
+        let provider = create_provider_with_options("bedrock", None, &options).unwrap();
+        assert!(provider
+            .chat_with_system(None, "hi", "model", 0.0)
+            .await
+            .is_err());
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn inference_params_reach_bedrock_through_the_routed_stack() {
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let requests = Arc::clone(&seen);
+        let router = axum::Router::new().fallback(move |body: axum::body::Bytes| {
+            let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            requests
+                .lock()
+                .unwrap()
+                .push(request["inferenceConfig"].clone());
+            async {
+                axum::Json(serde_json::json!({
+                    "output": {"message": {"role": "assistant", "content": [{"text": "ok"}]}}
+                }))
+            }
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+
+        let routes = [crate::config::ModelRouteConfig {
+            hint: "short".into(),
+            provider: "bedrock".into(),
+            model: "amazon.nova-lite-v1:0".into(),
+            api_key: None,
+        }];
+        let options = ProviderOptions {
+            bedrock: crate::config::BedrockConfig {
+                region: Some("us-east-1".into()),
+                access_key_id: Some("AKIATEST".into()),
+                secret_access_key: Some("secret".into()),
+                endpoint_url: Some(format!("http://{addr}")),
+                top_p: Some(0.9),
+                ..crate::config::BedrockConfig::default()
+            },
+            ..ProviderOptions::default()
+        };
+        let provider = create_routed_provider(
+            "bedrock",
+            None,
+            &crate::config::ReliabilityConfig::default(),
+            &routes,
+            "amazon.nova-pro-v1:0",
+            &options,
+            noop_observer(),
+        )
+        .unwrap();
+        let params = InferenceParams {
+            max_tokens: Some(256),
+            stop_sequences: Some(vec!["</answer>".into()]),
+            ..InferenceParams::default()
+        };
+        let messages = [ChatMessage::user("hi")];
+
+        for model in ["hint:short", "amazon.nova-pro-v1:0"] {
+            let reply = provider
+                .chat_with_params(&messages, model, 0.5, &params)
+                .await
+                .unwrap();
+            assert_eq!(reply, "ok");
+        }
+        provider.chat(&messages, "hint:short", 0.5).await.unwrap();
+
+        let seen = seen.lock().unwrap();
+        let overridden = serde_json::json!({
+            "maxTokens": 256, "temperature": 0.5, "topP": 0.9, "stopSequences": ["</answer>"]
+        });
+        assert_eq!(seen[..2], [overridden.clone(), overridden]);
+        assert_eq!(
+            seen[2],
+            serde_json::json!({"maxTokens": 4096, "temperature": 0.5, "topP": 0.9})
+        );
+    }
+
     #[test]
     fn factory_all_providers_create_successfully() {
         let providers = [
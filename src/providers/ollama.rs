@@ -1,10 +1,17 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{
+    ChatMessage, ChatResponse as ProviderResponse, ChatStream, Provider, ResponseUsage, StreamEvent,
+};
 use async_trait::async_trait;
+use futures_util::stream::{self, Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
 pub struct OllamaProvider {
     base_url: String,
+    /// Send `format: "json"` so replies are constrained to JSON
+    json_mode: bool,
     client: Client,
 }
 
@@ -14,6 +21,8 @@ struct ChatRequest {
     messages: Vec<Message>,
     stream: bool,
     options: Options,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'static str>,
 }
 
 #[derive(Debug, Serialize)]
@@ -30,6 +39,12 @@ struct Options {
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     message: ResponseMessage,
+    /// Tokens in the prompt (absent when the prompt was cached)
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    /// Tokens generated
+    #[serde(default)]
+    eval_count: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,13 +52,28 @@ struct ResponseMessage {
     content: String,
 }
 
+/// One line of a streamed `/api/chat` response.
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    message: Option<ResponseMessage>,
+    #[serde(default)]
+    error: Option<String>,
+    /// Set on the final (`done`) line, like `ChatResponse`'s
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
 impl OllamaProvider {
     pub fn new(base_url: Option<&str>) -> Self {
         Self {
             base_url: base_url
-                .unwrap_or("http://localhost:11434")
+                .unwrap_or(DEFAULT_BASE_URL)
                 .trim_end_matches('/')
                 .to_string(),
+            json_mode: false,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(300)) // Ollama runs locally, may be slow
                 .connect_timeout(std::time::Duration::from_secs(10))
@@ -51,60 +81,329 @@ impl OllamaProvider {
                 .unwrap_or_else(|_| Client::new()),
         }
     }
-}
 
-#[async_trait]
-impl Provider for OllamaProvider {
-    async fn chat_with_system(
+    /// Constrain replies to JSON (`agent.response_format`).
+    #[must_use]
+    pub fn with_json_mode(mut self, enabled: bool) -> Self {
+        self.json_mode = enabled;
+        self
+    }
+
+    fn request(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<String> {
-        let mut messages = Vec::new();
-
-        if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
-        }
-
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
-
-        let request = ChatRequest {
+        stream: bool,
+    ) -> ChatRequest {
+        ChatRequest {
             model: model.to_string(),
-            messages,
-            stream: false,
+            messages: messages
+                .iter()
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content.clone(),
+                })
+                .collect(),
+            stream,
             options: Options { temperature },
-        };
+            format: self.json_mode.then_some("json"),
+        }
+    }
 
+    /// POST to `/api/chat`, turning connection failures and error statuses
+    /// into errors that say what to check.
+    async fn send(&self, request: &ChatRequest) -> anyhow::Result<reqwest::Response> {
         let url = format!("{}/api/chat", self.base_url);
+        let response = match self.client.post(&url).json(request).send().await {
+            Ok(response) => response,
+            Err(e) if e.is_connect() => anyhow::bail!(
+                "Could not connect to Ollama at {}. Is Ollama running? (start it with `ollama serve`)",
+                self.base_url
+            ),
+            Err(e) => return Err(e.into()),
+        };
 
-        let response = self.client.post(&url).json(&request).send().await?;
-
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            let err = super::api_error("Ollama", response).await;
+            anyhow::bail!(
+                "{err}. Pull the model first: `ollama pull {}`",
+                request.model
+            );
+        }
         if !response.status().is_success() {
             let err = super::api_error("Ollama", response).await;
             anyhow::bail!("{err}. Is Ollama running? (brew install ollama && ollama serve)");
         }
+        Ok(response)
+    }
+}
+
+/// Turn Ollama's NDJSON stream (one JSON object per line) into text deltas,
+/// followed by the usage from the final line.
+fn ndjson_deltas<S, B, E>(bytes: S) -> ChatStream
+where
+    S: Stream<Item = Result<B, E>> + Send + 'static,
+    B: AsRef<[u8]> + Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    // (bytes, unparsed buffer, finished, usage from the final line)
+    let state = (Box::pin(bytes), Vec::<u8>::new(), false, None);
+    stream::unfold(
+        state,
+        |(mut bytes, mut buffer, finished, mut usage)| async move {
+            if finished {
+                return None;
+            }
+            loop {
+                let line = if let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=end).collect();
+                    Some(line)
+                } else {
+                    match bytes.next().await {
+                        Some(Ok(chunk)) => {
+                            buffer.extend_from_slice(chunk.as_ref());
+                            continue;
+                        }
+                        Some(Err(e)) => return Some((Err(e.into()), (bytes, buffer, true, usage))),
+                        // A final line without a trailing newline still counts
+                        None if !buffer.is_empty() => Some(std::mem::take(&mut buffer)),
+                        None => {
+                            let state = (bytes, buffer, true, None);
+                            return Some((Ok(StreamEvent::Usage(usage?)), state));
+                        }
+                    }
+                };
+                let Some(line) = line.filter(|l| !l.trim_ascii().is_empty()) else {
+                    continue;
+                };
+                match serde_json::from_slice::<StreamChunk>(&line) {
+                    Ok(StreamChunk {
+                        error: Some(error), ..
+                    }) => {
+                        let err = anyhow::anyhow!("Ollama stream error: {error}");
+                        return Some((Err(err), (bytes, buffer, true, usage)));
+                    }
+                    Ok(chunk) => {
+                        if chunk.prompt_eval_count.is_some() || chunk.eval_count.is_some() {
+                            usage = Some(ResponseUsage {
+                                input_tokens: chunk.prompt_eval_count.unwrap_or(0),
+                                output_tokens: chunk.eval_count.unwrap_or(0),
+                            });
+                        }
+                        if let Some(message) = chunk.message.filter(|m| !m.content.is_empty()) {
+                            let state = (bytes, buffer, false, usage);
+                            return Some((Ok(StreamEvent::Text(message.content)), state));
+                        }
+                    }
+                    Err(e) => return Some((Err(e.into()), (bytes, buffer, true, usage))),
+                }
+            }
+        },
+    )
+    .boxed()
+}
+
+#[async_trait]
+impl Provider for OllamaProvider {
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .chat_with_usage(messages, model, temperature)
+            .await?
+            .text)
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ProviderResponse> {
+        let request = self.request(messages, model, temperature, false);
+        let chat_response: ChatResponse = self.send(&request).await?.json().await?;
+        let usage = (chat_response.prompt_eval_count.is_some()
+            || chat_response.eval_count.is_some())
+        .then(|| ResponseUsage {
+            input_tokens: chat_response.prompt_eval_count.unwrap_or(0),
+            output_tokens: chat_response.eval_count.unwrap_or(0),
+        });
+        Ok(ProviderResponse {
+            text: chat_response.message.content,
+            usage,
+            ..ProviderResponse::default()
+        })
+    }
+
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(ChatMessage::system(system_prompt));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat_stream(&messages, model, temperature).await
+    }
 
-        let chat_response: ChatResponse = response.json().await?;
-        Ok(chat_response.message.content)
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let request = self.request(messages, model, temperature, true);
+        let response = self.send(&request).await?;
+        Ok(ndjson_deltas(response.bytes_stream()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::sync::{Arc, Mutex};
+
+    /// Serve `/api/chat` with `body`, recording each request's JSON.
+    async fn serve(body: &'static str) -> (String, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let router = Router::new().route(
+            "/api/chat",
+            post(move |Json(request): Json<serde_json::Value>| async move {
+                log.lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .push(request);
+                body
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (format!("http://{addr}"), seen)
+    }
+
+    #[tokio::test]
+    async fn chat_against_mock_server() {
+        let (url, seen) = serve(
+            r#"{"model":"llama3","message":{"role":"assistant","content":"Hi there"},"done":true,"prompt_eval_count":12,"eval_count":3}"#,
+        )
+        .await;
+        let provider = OllamaProvider::new(Some(&url));
+        let response = provider
+            .chat_with_usage(
+                &[ChatMessage::system("be brief"), ChatMessage::user("hello")],
+                "llama3",
+                0.2,
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text, "Hi there");
+        assert_eq!(
+            response.usage,
+            Some(ResponseUsage {
+                input_tokens: 12,
+                output_tokens: 3,
+            })
+        );
+
+        let request = seen.lock().unwrap()[0].clone();
+        assert_eq!(request["model"], "llama3");
+        assert_eq!(request["stream"], false);
+        assert_eq!(request["options"]["temperature"], 0.2);
+        assert_eq!(
+            request["messages"],
+            serde_json::json!([
+                {"role": "system", "content": "be brief"},
+                {"role": "user", "content": "hello"},
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_against_mock_server() {
+        let (url, seen) = serve(concat!(
+            r#"{"message":{"role":"assistant","content":"Hel"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":"lo"},"done":false}"#,
+            "\n",
+            r#"{"message":{"role":"assistant","content":""},"done":true,"eval_count":2}"#,
+            "\n",
+        ))
+        .await;
+        let chunks: Vec<StreamEvent> = OllamaProvider::new(Some(&url))
+            .chat_stream(&[ChatMessage::user("hi")], "llama3", 0.7)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            [
+                StreamEvent::Text("Hel".into()),
+                StreamEvent::Text("lo".into()),
+                StreamEvent::Usage(ResponseUsage {
+                    input_tokens: 0,
+                    output_tokens: 2,
+                }),
+            ]
+        );
+        assert_eq!(seen.lock().unwrap()[0]["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn ndjson_lines_split_across_chunks() {
+        let bytes = stream::iter(vec![
+            Ok::<_, std::io::Error>(br#"{"message":{"content":"a"}}"#.to_vec()),
+            Ok(b"\n{\"message\":{\"con".to_vec()),
+            Ok(br#"tent":"b"}}"#.to_vec()),
+        ]);
+        let chunks: Vec<String> = ndjson_deltas(bytes)
+            .filter_map(|chunk| async move { chunk.unwrap().into_text() })
+            .collect()
+            .await;
+        assert_eq!(chunks, ["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn ndjson_error_line_ends_stream() {
+        let bytes = stream::iter(vec![Ok::<_, std::io::Error>(
+            b"{\"error\":\"model 'nope' not found\"}\n".to_vec(),
+        )]);
+        let results: Vec<anyhow::Result<StreamEvent>> = ndjson_deltas(bytes).collect().await;
+        assert_eq!(results.len(), 1);
+        let err = results[0].as_ref().unwrap_err().to_string();
+        assert!(err.contains("model 'nope' not found"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn connection_refused_asks_if_ollama_is_running() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let err = OllamaProvider::new(Some(&url))
+            .chat(&[ChatMessage::user("hi")], "llama3", 0.7)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Is Ollama running?"), "{err}");
+        assert!(err.contains(&url), "{err}");
+    }
 
     #[test]
     fn default_url() {
         let p = OllamaProvider::new(None);
-        assert_eq!(p.base_url, "http://localhost:11434");
+        assert_eq!(p.base_url, DEFAULT_BASE_URL);
     }
 
     #[test]
@@ -141,6 +440,7 @@ mod tests {
             ],
             stream: false,
             options: Options { temperature: 0.7 },
+            format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"stream\":false"));
@@ -159,10 +459,20 @@ mod tests {
             }],
             stream: false,
             options: Options { temperature: 0.0 },
+            format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("\"role\":\"system\""));
         assert!(json.contains("mistral"));
+        assert!(!json.contains("format"));
+    }
+
+    #[test]
+    fn json_mode_sets_format() {
+        let p = OllamaProvider::new(None).with_json_mode(true);
+        let req = p.request(&[ChatMessage::user("hi")], "llama3", 0.0, false);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["format"], "json");
     }
 
     #[test]
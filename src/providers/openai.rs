@@ -1,10 +1,12 @@
-use crate::providers::traits::Provider;
+use crate::providers::traits::{ChatMessage, Provider};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
 pub struct OpenAiProvider {
     api_key: Option<String>,
+    /// Ask for `response_format: json_object` (`agent.response_format`)
+    json_mode: bool,
     client: Client,
 }
 
@@ -13,6 +15,14 @@ struct ChatRequest {
     model: String,
     messages: Vec<Message>,
     temperature: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<ResponseFormat>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseFormat {
+    #[serde(rename = "type")]
+    kind: &'static str,
 }
 
 #[derive(Debug, Serialize)]
@@ -40,6 +50,7 @@ impl OpenAiProvider {
     pub fn new(api_key: Option<&str>) -> Self {
         Self {
             api_key: api_key.map(ToString::to_string),
+            json_mode: false,
             client: Client::builder()
                 .timeout(std::time::Duration::from_secs(120))
                 .connect_timeout(std::time::Duration::from_secs(10))
@@ -47,14 +58,37 @@ impl OpenAiProvider {
                 .unwrap_or_else(|_| Client::new()),
         }
     }
+
+    /// Constrain replies to a JSON object.
+    #[must_use]
+    pub fn with_json_mode(mut self, enabled: bool) -> Self {
+        self.json_mode = enabled;
+        self
+    }
+
+    fn request(&self, messages: &[ChatMessage], model: &str, temperature: f64) -> ChatRequest {
+        ChatRequest {
+            model: model.to_string(),
+            messages: messages
+                .iter()
+                .map(|m| Message {
+                    role: m.role.as_str().to_string(),
+                    content: m.content.clone(),
+                })
+                .collect(),
+            temperature,
+            response_format: self.json_mode.then_some(ResponseFormat {
+                kind: "json_object",
+            }),
+        }
+    }
 }
 
 #[async_trait]
 impl Provider for OpenAiProvider {
-    async fn chat_with_system(
+    async fn chat(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
@@ -62,25 +96,7 @@ impl Provider for OpenAiProvider {
             anyhow::anyhow!("OpenAI API key not set. Set OPENAI_API_KEY or edit config.toml.")
         })?;
 
-        let mut messages = Vec::new();
-
-        if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
-        }
-
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
-
-        let request = ChatRequest {
-            model: model.to_string(),
-            messages,
-            temperature,
-        };
+        let request = self.request(messages, model, temperature);
 
         let response = self
             .client
@@ -159,6 +175,7 @@ mod tests {
                 },
             ],
             temperature: 0.7,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"role\":\"system\""));
@@ -175,10 +192,20 @@ mod tests {
                 content: "hello".to_string(),
             }],
             temperature: 0.0,
+            response_format: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(!json.contains("system"));
         assert!(json.contains("\"temperature\":0.0"));
+        assert!(!json.contains("response_format"));
+    }
+
+    #[test]
+    fn json_mode_sets_response_format() {
+        let p = OpenAiProvider::new(Some("key")).with_json_mode(true);
+        let req = p.request(&[ChatMessage::user("hi")], "gpt-4o", 0.0);
+        let json = serde_json::to_value(&req).unwrap();
+        assert_eq!(json["response_format"]["type"], "json_object");
     }
 
     #[test]
@@ -1,11 +1,20 @@
-use crate::providers::traits::Provider;
+use crate::providers::retry::{send_with_retry, RetryPolicy};
+use crate::providers::traits::{ChatMessage, Provider, WarmupStatus};
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, Method, RequestBuilder};
 use serde::{Deserialize, Serialize};
 
+const BASE_URL: &str = "https://openrouter.ai/api/v1";
+const DEFAULT_APP_URL: &str = "https://github.com/theonlyhennygod/zeroclaw";
+const DEFAULT_APP_NAME: &str = "ZeroClaw";
+
 pub struct OpenRouterProvider {
     api_key: Option<String>,
     client: Client,
+    base_url: String,
+    retry: RetryPolicy,
+    app_url: String,
+    app_name: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -45,49 +54,96 @@ impl OpenRouterProvider {
                 .connect_timeout(std::time::Duration::from_secs(10))
                 .build()
                 .unwrap_or_else(|_| Client::new()),
+            base_url: BASE_URL.to_string(),
+            retry: RetryPolicy::default(),
+            app_url: DEFAULT_APP_URL.to_string(),
+            app_name: DEFAULT_APP_NAME.to_string(),
+        }
+    }
+
+    /// Attribute requests to this app (`HTTP-Referer` / `X-Title`). Unset or
+    /// blank values keep the built-in defaults.
+    #[must_use]
+    pub fn with_attribution(mut self, app_url: Option<&str>, app_name: Option<&str>) -> Self {
+        if let Some(url) = app_url.map(str::trim).filter(|v| !v.is_empty()) {
+            self.app_url = url.to_string();
+        }
+        if let Some(name) = app_name.map(str::trim).filter(|v| !v.is_empty()) {
+            self.app_name = name.to_string();
         }
+        self
+    }
+
+    /// Override how throttled or temporarily unavailable requests are retried.
+    #[must_use]
+    pub fn with_retries(mut self, max_retries: u32, base_delay_ms: u64) -> Self {
+        self.retry = RetryPolicy {
+            max_retries,
+            base_delay_ms,
+        };
+        self
+    }
+
+    /// Authenticated request carrying the app attribution headers.
+    fn request(&self, method: Method, url: &str, api_key: &str) -> RequestBuilder {
+        self.client
+            .request(method, url)
+            .header("Authorization", format!("Bearer {api_key}"))
+            .header("HTTP-Referer", &self.app_url)
+            .header("X-Title", &self.app_name)
     }
+
+    #[cfg(test)]
+    fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.to_string();
+        self
+    }
+}
+
+/// `OpenRouter`'s own rejections (400 bad request, 401 bad key, 402 out of
+/// credits, 403 moderation) fail fast; 429 (usually an upstream model
+/// throttling us), 408 and 5xx (model down, no provider available) are
+/// transient.
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504)
 }
 
 #[async_trait]
 impl Provider for OpenRouterProvider {
-    async fn warmup(&self) -> anyhow::Result<()> {
+    async fn warmup(&self) -> WarmupStatus {
         // Hit a lightweight endpoint to establish TLS + HTTP/2 connection pool.
-        // This prevents the first real chat request from timing out on cold start.
-        if let Some(api_key) = self.api_key.as_ref() {
-            self.client
-                .get("https://openrouter.ai/api/v1/auth/key")
-                .header("Authorization", format!("Bearer {api_key}"))
+        // This prevents the first real chat request from timing out on cold start,
+        // and checks the API key on the way.
+        WarmupStatus::check(async {
+            let api_key = self
+                .api_key
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set"))?;
+            self.request(Method::GET, &format!("{}/auth/key", self.base_url), api_key)
                 .send()
                 .await?
                 .error_for_status()?;
-        }
-        Ok(())
+            Ok("API key accepted".to_string())
+        })
+        .await
     }
 
-    async fn chat_with_system(
+    async fn chat(
         &self,
-        system_prompt: Option<&str>,
-        message: &str,
+        messages: &[ChatMessage],
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
         let api_key = self.api_key.as_ref()
             .ok_or_else(|| anyhow::anyhow!("OpenRouter API key not set. Run `zeroclaw onboard` or set OPENROUTER_API_KEY env var."))?;
 
-        let mut messages = Vec::new();
-
-        if let Some(sys) = system_prompt {
-            messages.push(Message {
-                role: "system".to_string(),
-                content: sys.to_string(),
-            });
-        }
-
-        messages.push(Message {
-            role: "user".to_string(),
-            content: message.to_string(),
-        });
+        let messages = messages
+            .iter()
+            .map(|m| Message {
+                role: m.role.as_str().to_string(),
+                content: m.content.clone(),
+            })
+            .collect();
 
         let request = ChatRequest {
             model: model.to_string(),
@@ -95,22 +151,15 @@ impl Provider for OpenRouterProvider {
             temperature,
         };
 
-        let response = self
-            .client
-            .post("https://openrouter.ai/api/v1/chat/completions")
-            .header("Authorization", format!("Bearer {api_key}"))
-            .header(
-                "HTTP-Referer",
-                "https://github.com/theonlyhennygod/zeroclaw",
-            )
-            .header("X-Title", "ZeroClaw")
-            .json(&request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(super::api_error("OpenRouter", response).await);
-        }
+        let url = format!("{}/chat/completions", self.base_url);
+        let response = send_with_retry("OpenRouter", self.retry, is_retryable_status, || async {
+            Ok(self
+                .request(Method::POST, &url, api_key)
+                .json(&request)
+                .send()
+                .await?)
+        })
+        .await?;
 
         let chat_response: ChatResponse = response.json().await?;
 
@@ -122,3 +171,159 @@ impl Provider for OpenRouterProvider {
             .ok_or_else(|| anyhow::anyhow!("No response from OpenRouter"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::{Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serve chat completions that answer with `statuses` in turn (the last
+    /// one repeats), returning the base URL and a hit counter.
+    async fn serve(statuses: &'static [u16]) -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let router = Router::new().route(
+            "/chat/completions",
+            post(move || {
+                let n = counter.fetch_add(1, Ordering::SeqCst);
+                let status = StatusCode::from_u16(statuses[n.min(statuses.len() - 1)]).unwrap();
+                async move {
+                    let body = if status.is_success() {
+                        serde_json::json!({"choices": [{"message": {"content": "hello"}}]})
+                    } else {
+                        serde_json::json!({"error": {"code": status.as_u16(), "message": "nope"}})
+                    };
+                    (status, [("retry-after", "0")], Json(body))
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (format!("http://{addr}"), hits)
+    }
+
+    fn provider(base_url: &str) -> OpenRouterProvider {
+        OpenRouterProvider::new(Some("sk-or-test"))
+            .with_base_url(base_url)
+            .with_retries(2, 1)
+    }
+
+    #[tokio::test]
+    async fn upstream_throttling_is_retried() {
+        let (url, hits) = serve(&[429, 503, 200]).await;
+        let reply = provider(&url)
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .unwrap();
+        assert_eq!(reply, "hello");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn insufficient_credits_fails_fast() {
+        let (url, hits) = serve(&[402]).await;
+        let err = provider(&url)
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(hits.load(Ordering::SeqCst), 1, "402 must not be retried");
+        assert!(err.starts_with("OpenRouter API error (402"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn exhausted_retries_surface_last_error() {
+        let (url, hits) = serve(&[429]).await;
+        let err = provider(&url)
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            hits.load(Ordering::SeqCst),
+            3,
+            "initial attempt + 2 retries"
+        );
+        assert!(err.contains("OpenRouter API error (429"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn persistent_throttling_is_retried_in_one_layer() {
+        let (url, hits) = serve(&[429]).await;
+        let reliable = crate::providers::reliable::ReliableProvider::new(
+            vec![("openrouter".into(), Box::new(provider(&url)))],
+            2,
+            1,
+        );
+        assert!(reliable
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 3, "not 3 x 3 attempts");
+    }
+
+    /// Serve one chat completion and hand back the headers it arrived with.
+    async fn serve_capturing_headers() -> (String, tokio::sync::oneshot::Receiver<HeaderMap>) {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
+        let router = Router::new().route(
+            "/chat/completions",
+            post(move |headers: HeaderMap| async move {
+                if let Some(tx) = tx
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .take()
+                {
+                    let _ = tx.send(headers);
+                }
+                Json(serde_json::json!({"choices": [{"message": {"content": "hello"}}]}))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        (format!("http://{addr}"), rx)
+    }
+
+    #[tokio::test]
+    async fn attribution_headers_use_configured_values() {
+        let (url, headers) = serve_capturing_headers().await;
+        provider(&url)
+            .with_attribution(Some("https://example.com/my-bot"), Some("My Bot"))
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .unwrap();
+        let headers = headers.await.unwrap();
+        assert_eq!(headers["http-referer"], "https://example.com/my-bot");
+        assert_eq!(headers["x-title"], "My Bot");
+        assert_eq!(headers["authorization"], "Bearer sk-or-test");
+    }
+
+    #[tokio::test]
+    async fn attribution_headers_default_to_zeroclaw() {
+        let (url, headers) = serve_capturing_headers().await;
+        provider(&url)
+            .with_attribution(None, Some("  "))
+            .chat(&[ChatMessage::user("hi")], "m", 0.7)
+            .await
+            .unwrap();
+        let headers = headers.await.unwrap();
+        assert_eq!(headers["http-referer"], DEFAULT_APP_URL);
+        assert_eq!(headers["x-title"], DEFAULT_APP_NAME);
+    }
+
+    #[test]
+    fn credit_and_auth_errors_are_not_retryable() {
+        for code in [400, 401, 402, 403] {
+            assert!(!is_retryable_status(StatusCode::from_u16(code).unwrap()));
+        }
+        for code in [408, 429, 502, 503] {
+            assert!(is_retryable_status(StatusCode::from_u16(code).unwrap()));
+        }
+    }
+}
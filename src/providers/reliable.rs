@@ -1,9 +1,21 @@
-use super::Provider;
+use super::bedrock::GuardrailIntervened;
+use super::circuit::{CircuitBreaker, CircuitSettings};
+use super::retry::RetriesExhausted;
+use super::traits::{
+    ChatMessage, ChatResponse, ChatStream, ContentPart, InferenceParams, Provider, WarmupStatus,
+};
+use crate::observability::{NoopObserver, Observer};
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Check if an error is non-retryable (client errors that won't resolve with retries).
-fn is_non_retryable(err: &anyhow::Error) -> bool {
+pub(super) fn is_non_retryable(err: &anyhow::Error) -> bool {
+    if err.is::<GuardrailIntervened>() {
+        return true;
+    }
     // Check for reqwest status errors (returned by .error_for_status())
     if let Some(reqwest_err) = err.downcast_ref::<reqwest::Error>() {
         if let Some(status) = reqwest_err.status() {
@@ -27,8 +39,12 @@ fn is_non_retryable(err: &anyhow::Error) -> bool {
 }
 
 /// Provider wrapper with retry + fallback behavior.
+///
+/// With a circuit breaker configured, a provider that keeps failing is
+/// skipped (its calls fail fast) until its cooldown ends.
 pub struct ReliableProvider {
     providers: Vec<(String, Box<dyn Provider>)>,
+    breakers: Vec<CircuitBreaker>,
     max_retries: u32,
     base_backoff_ms: u64,
 }
@@ -39,44 +55,64 @@ impl ReliableProvider {
         max_retries: u32,
         base_backoff_ms: u64,
     ) -> Self {
+        let disabled = CircuitSettings {
+            failure_threshold: 0,
+            window: Duration::ZERO,
+            cooldown: Duration::ZERO,
+        };
+        let observer: Arc<dyn Observer> = Arc::new(NoopObserver);
         Self {
+            breakers: Self::breakers(&providers, disabled, &observer),
             providers,
             max_retries,
             base_backoff_ms: base_backoff_ms.max(50),
         }
     }
-}
 
-#[async_trait]
-impl Provider for ReliableProvider {
-    async fn warmup(&self) -> anyhow::Result<()> {
-        for (name, provider) in &self.providers {
-            tracing::info!(provider = name, "Warming up provider connection pool");
-            if let Err(e) = provider.warmup().await {
-                tracing::warn!(provider = name, "Warmup failed (non-fatal): {e}");
-            }
-        }
-        Ok(())
+    /// Give every provider its own circuit breaker, reporting state changes
+    /// to `observer`.
+    #[must_use]
+    pub fn with_circuit_breaker(
+        mut self,
+        settings: CircuitSettings,
+        observer: &Arc<dyn Observer>,
+    ) -> Self {
+        self.breakers = Self::breakers(&self.providers, settings, observer);
+        self
     }
 
-    async fn chat_with_system(
-        &self,
-        system_prompt: Option<&str>,
-        message: &str,
-        model: &str,
-        temperature: f64,
-    ) -> anyhow::Result<String> {
+    fn breakers(
+        providers: &[(String, Box<dyn Provider>)],
+        settings: CircuitSettings,
+        observer: &Arc<dyn Observer>,
+    ) -> Vec<CircuitBreaker> {
+        providers
+            .iter()
+            .map(|(name, _)| CircuitBreaker::new(name, settings, Arc::clone(observer)))
+            .collect()
+    }
+
+    /// Run `call` against each provider in turn, retrying with backoff,
+    /// until one succeeds.
+    async fn call_with_retries<'a, T, F, Fut>(&'a self, call: F) -> anyhow::Result<T>
+    where
+        F: Fn(&'a dyn Provider) -> Fut + Send,
+        Fut: Future<Output = anyhow::Result<T>> + Send,
+    {
         let mut failures = Vec::new();
 
-        for (provider_name, provider) in &self.providers {
+        for ((provider_name, provider), breaker) in self.providers.iter().zip(&self.breakers) {
+            if breaker.try_acquire().is_err() {
+                tracing::warn!(provider = provider_name, "Circuit open, skipping provider");
+                failures.push(format!("{provider_name}: circuit open, skipped"));
+                continue;
+            }
             let mut backoff_ms = self.base_backoff_ms;
 
             for attempt in 0..=self.max_retries {
-                match provider
-                    .chat_with_system(system_prompt, message, model, temperature)
-                    .await
-                {
+                match call(provider.as_ref()).await {
                     Ok(resp) => {
+                        breaker.record_success();
                         if attempt > 0 {
                             tracing::info!(
                                 provider = provider_name,
@@ -86,8 +122,14 @@ impl Provider for ReliableProvider {
                         }
                         return Ok(resp);
                     }
+                    Err(e) if e.is::<GuardrailIntervened>() => {
+                        // A fallback would answer without the guardrail
+                        breaker.record_success();
+                        return Err(e);
+                    }
                     Err(e) => {
                         let non_retryable = is_non_retryable(&e);
+                        let already_retried = e.is::<RetriesExhausted>();
                         failures.push(format!(
                             "{provider_name} attempt {}/{}: {e}",
                             attempt + 1,
@@ -95,12 +137,26 @@ impl Provider for ReliableProvider {
                         ));
 
                         if non_retryable {
+                            // The provider is up; the request itself was rejected
+                            breaker.record_success();
                             tracing::warn!(
                                 provider = provider_name,
                                 "Non-retryable error, switching provider"
                             );
                             break;
                         }
+                        breaker.record_failure();
+                        if breaker.is_open() {
+                            break;
+                        }
+                        if already_retried {
+                            // The provider backed off and retried this itself
+                            tracing::warn!(
+                                provider = provider_name,
+                                "Provider retries exhausted, switching provider"
+                            );
+                            break;
+                        }
 
                         if attempt < self.max_retries {
                             tracing::warn!(
@@ -123,6 +179,143 @@ impl Provider for ReliableProvider {
     }
 }
 
+#[async_trait]
+impl Provider for ReliableProvider {
+    /// Reachable when any provider in the chain is, since requests fall back.
+    async fn warmup(&self) -> WarmupStatus {
+        let mut statuses = Vec::with_capacity(self.providers.len());
+        for (name, provider) in &self.providers {
+            tracing::info!(provider = name, "Warming up provider connection pool");
+            let status = provider.warmup().await;
+            if !status.reachable {
+                tracing::warn!(
+                    provider = name,
+                    "Warmup failed (non-fatal): {}",
+                    status.detail
+                );
+            }
+            statuses.push((name.clone(), status));
+        }
+        let reachable = statuses.iter().any(|(_, status)| status.reachable);
+        WarmupStatus::combine(&statuses, reachable)
+    }
+
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.chat_with_usage(messages, model, temperature)
+            .await
+            .map(|response| response.text)
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.chat_with_tools(messages, &[], model, temperature)
+            .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.call_with_retries(|provider| {
+            provider.chat_with_tools(messages, tools, model, temperature)
+        })
+        .await
+    }
+
+    async fn chat_with_params(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        params: &InferenceParams,
+    ) -> anyhow::Result<String> {
+        self.call_with_retries(|provider| {
+            provider.chat_with_params(messages, model, temperature, params)
+        })
+        .await
+    }
+
+    async fn chat_with_parts(
+        &self,
+        system_prompt: Option<&str>,
+        parts: Vec<ContentPart>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.call_with_retries(|provider| {
+            provider.chat_with_parts(system_prompt, parts.clone(), model, temperature)
+        })
+        .await
+    }
+
+    /// Falls back across providers if a stream fails to start. Errors after
+    /// the first chunk surface to the caller, since text was already emitted.
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let mut failures = Vec::new();
+        for ((provider_name, provider), breaker) in self.providers.iter().zip(&self.breakers) {
+            if breaker.try_acquire().is_err() {
+                failures.push(format!("{provider_name}: circuit open, skipped"));
+                continue;
+            }
+            match provider.chat_stream(messages, model, temperature).await {
+                Ok(stream) => {
+                    breaker.record_success();
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    if is_non_retryable(&e) {
+                        breaker.record_success();
+                    } else {
+                        breaker.record_failure();
+                    }
+                    tracing::warn!(
+                        provider = provider_name,
+                        "Stream failed to start, switching provider"
+                    );
+                    failures.push(format!("{provider_name}: {e}"));
+                }
+            }
+        }
+        anyhow::bail!(
+            "All providers failed to start a stream. Attempts:\n{}",
+            failures.join("\n")
+        )
+    }
+
+    /// Embeddings fall back across providers without retrying: the common
+    /// failure is a provider with no embeddings API, which retries won't fix.
+    async fn embed(&self, texts: &[String], model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        let mut failures = Vec::new();
+        for (provider_name, provider) in &self.providers {
+            match provider.embed(texts, model).await {
+                Ok(vectors) => return Ok(vectors),
+                Err(e) => failures.push(format!("{provider_name}: {e}")),
+            }
+        }
+        anyhow::bail!(
+            "All providers failed to embed. Attempts:\n{}",
+            failures.join("\n")
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -170,7 +363,10 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
+        let result = provider
+            .chat_with_system(None, "hello", "test", 0.0)
+            .await
+            .unwrap();
         assert_eq!(result, "ok");
         assert_eq!(calls.load(Ordering::SeqCst), 1);
     }
@@ -192,7 +388,10 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
+        let result = provider
+            .chat_with_system(None, "hello", "test", 0.0)
+            .await
+            .unwrap();
         assert_eq!(result, "recovered");
         assert_eq!(calls.load(Ordering::SeqCst), 2);
     }
@@ -227,12 +426,63 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
+        let result = provider
+            .chat_with_system(None, "hello", "test", 0.0)
+            .await
+            .unwrap();
         assert_eq!(result, "from fallback");
         assert_eq!(primary_calls.load(Ordering::SeqCst), 2);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
 
+    struct GuardrailProvider;
+
+    #[async_trait]
+    impl Provider for GuardrailProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Err(GuardrailIntervened {
+                message: "Blocked.".into(),
+                policies: vec!["Investment advice".into()],
+            }
+            .into())
+        }
+    }
+
+    #[tokio::test]
+    async fn guardrail_intervention_does_not_fall_back() {
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                ("bedrock".into(), Box::new(GuardrailProvider)),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "unguarded answer",
+                        error: "fallback down",
+                    }),
+                ),
+            ],
+            2,
+            1,
+        );
+
+        let err = provider
+            .chat_with_system(None, "hello", "test", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.is::<GuardrailIntervened>());
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 0);
+        assert!(is_non_retryable(&err));
+    }
+
     #[tokio::test]
     async fn returns_aggregated_error_when_all_providers_fail() {
         let provider = ReliableProvider::new(
@@ -261,7 +511,7 @@ mod tests {
         );
 
         let err = provider
-            .chat("hello", "test", 0.0)
+            .chat_with_system(None, "hello", "test", 0.0)
             .await
             .expect_err("all providers should fail");
         let msg = err.to_string();
@@ -326,10 +576,273 @@ mod tests {
             1,
         );
 
-        let result = provider.chat("hello", "test", 0.0).await.unwrap();
+        let result = provider
+            .chat_with_system(None, "hello", "test", 0.0)
+            .await
+            .unwrap();
         assert_eq!(result, "from fallback");
         // Primary should have been called only once (no retries)
         assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
         assert_eq!(fallback_calls.load(Ordering::SeqCst), 1);
     }
+
+    #[derive(Default)]
+    struct CircuitLog(std::sync::Mutex<Vec<String>>);
+
+    impl Observer for CircuitLog {
+        fn record_event(&self, event: &crate::observability::ObserverEvent) {
+            if let crate::observability::ObserverEvent::CircuitStateChanged { provider, state } =
+                event
+            {
+                self.0.lock().unwrap().push(format!("{provider}:{state}"));
+            }
+        }
+        fn record_metric(&self, _metric: &crate::observability::ObserverMetric) {}
+        fn name(&self) -> &str {
+            "circuit-log"
+        }
+    }
+
+    fn noop() -> Arc<dyn Observer> {
+        Arc::new(NoopObserver)
+    }
+
+    fn breaker_settings(cooldown_ms: u64) -> CircuitSettings {
+        CircuitSettings {
+            failure_threshold: 3,
+            window: Duration::from_secs(60),
+            cooldown: Duration::from_millis(cooldown_ms),
+        }
+    }
+
+    #[tokio::test]
+    async fn circuit_opens_short_circuits_and_recovers() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let log = Arc::new(CircuitLog::default());
+        let observer: Arc<dyn Observer> = log.clone();
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: 3,
+                    response: "recovered",
+                    error: "503 Service Unavailable",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_breaker(breaker_settings(60), &observer);
+
+        for _ in 0..3 {
+            assert!(provider
+                .chat_with_system(None, "hi", "m", 0.0)
+                .await
+                .is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Open: fails fast without touching the provider
+        let err = provider
+            .chat_with_system(None, "hi", "m", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("circuit open"), "{err}");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // After the cooldown one trial call goes through and closes it
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let result = provider
+            .chat_with_system(None, "hi", "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(result, "recovered");
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(
+            *log.0.lock().unwrap(),
+            ["primary:open", "primary:half_open", "primary:closed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn open_circuit_routes_straight_to_fallback() {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback_calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&primary_calls),
+                        fail_until_attempt: usize::MAX,
+                        response: "never",
+                        error: "connection refused",
+                    }),
+                ),
+                (
+                    "fallback".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::clone(&fallback_calls),
+                        fail_until_attempt: 0,
+                        response: "from fallback",
+                        error: "fallback down",
+                    }),
+                ),
+            ],
+            5,
+            1,
+        )
+        .with_circuit_breaker(breaker_settings(60_000), &noop());
+
+        // Retries stop as soon as the circuit opens
+        let first = provider.chat_with_system(None, "hi", "m", 0.0).await;
+        assert_eq!(first.unwrap(), "from fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 3);
+
+        let second = provider.chat_with_system(None, "hi", "m", 0.0).await;
+        assert_eq!(second.unwrap(), "from fallback");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 3);
+        assert_eq!(fallback_calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn client_errors_do_not_open_the_circuit() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: usize::MAX,
+                    response: "never",
+                    error: "400 Bad Request",
+                }),
+            )],
+            0,
+            1,
+        )
+        .with_circuit_breaker(breaker_settings(60_000), &noop());
+
+        for _ in 0..5 {
+            assert!(provider
+                .chat_with_system(None, "hi", "m", 0.0)
+                .await
+                .is_err());
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+
+    struct UsageProvider;
+
+    #[async_trait]
+    impl Provider for UsageProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("counted".into())
+        }
+
+        async fn chat_with_usage(
+            &self,
+            _messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<ChatResponse> {
+            Ok(ChatResponse {
+                text: "counted".into(),
+                usage: Some(crate::providers::traits::ResponseUsage {
+                    input_tokens: 12,
+                    output_tokens: 3,
+                }),
+                ..ChatResponse::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn forwards_usage_from_inner_provider() {
+        let provider = ReliableProvider::new(vec![("usage".into(), Box::new(UsageProvider))], 0, 1);
+
+        let response = provider
+            .chat_with_usage(&[ChatMessage::user("hello")], "test", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(response.text, "counted");
+        assert_eq!(response.usage.map(|u| u.total()), Some(15));
+    }
+
+    #[tokio::test]
+    async fn default_usage_is_none() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = ReliableProvider::new(
+            vec![(
+                "primary".into(),
+                Box::new(MockProvider {
+                    calls: Arc::clone(&calls),
+                    fail_until_attempt: 0,
+                    response: "ok",
+                    error: "boom",
+                }),
+            )],
+            0,
+            1,
+        );
+
+        let response = provider
+            .chat_with_usage(&[ChatMessage::user("hello")], "test", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(response.text, "ok");
+        assert!(response.usage.is_none());
+    }
+
+    struct EmbedProvider;
+
+    #[async_trait]
+    impl Provider for EmbedProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok("ok".into())
+        }
+
+        async fn embed(&self, texts: &[String], _model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|t| vec![t.len() as f32]).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn embed_falls_back_to_provider_with_embeddings() {
+        let provider = ReliableProvider::new(
+            vec![
+                (
+                    "primary".into(),
+                    Box::new(MockProvider {
+                        calls: Arc::new(AtomicUsize::new(0)),
+                        fail_until_attempt: 0,
+                        response: "ok",
+                        error: "boom",
+                    }),
+                ),
+                ("embedder".into(), Box::new(EmbedProvider)),
+            ],
+            2,
+            1,
+        );
+
+        let vectors = provider
+            .embed(&["a".into(), "abc".into()], "embed-model")
+            .await
+            .unwrap();
+        assert_eq!(vectors, vec![vec![1.0], vec![3.0]]);
+    }
 }
@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const DEFAULT_BASE_DELAY_MS: u64 = 500;
+/// Upper bound for a single backoff sleep, including `Retry-After` hints.
+pub(super) const MAX_RETRY_DELAY_MS: u64 = 20_000;
+
+/// A transient failure `send_with_retry` has already retried with backoff.
+/// `ReliableProvider` falls back on it instead of retrying the provider
+/// again, so HTTP-level retries happen in one layer only.
+#[derive(Debug)]
+pub(super) struct RetriesExhausted(anyhow::Error);
+
+impl std::fmt::Display for RetriesExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for RetriesExhausted {}
+
+/// Retry policy for transient HTTP failures (full-jitter exponential backoff).
+#[derive(Debug, Clone, Copy)]
+pub(super) struct RetryPolicy {
+    pub(super) max_retries: u32,
+    pub(super) base_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay_ms: DEFAULT_BASE_DELAY_MS,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Full jitter: a random delay in `[0, min(cap, base * 2^attempt)]`.
+    pub(super) fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay_ms
+            .saturating_mul(1_u64.checked_shl(attempt).unwrap_or(u64::MAX))
+            .min(MAX_RETRY_DELAY_MS);
+        let random = u64::from_le_bytes(
+            uuid::Uuid::new_v4().as_bytes()[..8]
+                .try_into()
+                .unwrap_or_default(),
+        );
+        Duration::from_millis(random % (ceiling + 1))
+    }
+}
+
+/// Parse a `Retry-After` header expressed in seconds.
+pub(super) fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    let secs: u64 = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(Duration::from_millis(
+        secs.saturating_mul(1000).min(MAX_RETRY_DELAY_MS),
+    ))
+}
+
+/// Run `send` until it succeeds, fails with a status `is_retryable` rejects,
+/// or retries run out. Failures are reported via `api_error` under `provider`,
+/// wrapped in `RetriesExhausted` when they were retried.
+pub(super) async fn send_with_retry<F, Fut>(
+    provider: &str,
+    policy: RetryPolicy,
+    is_retryable: fn(reqwest::StatusCode) -> bool,
+    mut send: F,
+) -> anyhow::Result<reqwest::Response>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = anyhow::Result<reqwest::Response>>,
+{
+    let mut attempt = 0;
+    loop {
+        let response = send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if !is_retryable(status) {
+            return Err(super::api_error(provider, response).await);
+        }
+        if attempt >= policy.max_retries {
+            let err = super::api_error(provider, response).await;
+            return Err(if attempt > 0 {
+                RetriesExhausted(err).into()
+            } else {
+                err
+            });
+        }
+
+        let delay = retry_after(&response).unwrap_or_else(|| policy.backoff(attempt));
+        attempt += 1;
+        tracing::warn!(
+            provider,
+            status = status.as_u16(),
+            attempt,
+            max_retries = policy.max_retries,
+            delay_ms = u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+            "Provider request throttled, retrying"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_response(status: u16, retry_after_secs: Option<&str>) -> reqwest::Response {
+        let mut builder = axum::http::Response::builder().status(status);
+        if let Some(secs) = retry_after_secs {
+            builder = builder.header("retry-after", secs);
+        }
+        reqwest::Response::from(builder.body(r#"{"message":"boom"}"#).unwrap())
+    }
+
+    fn throttled(status: reqwest::StatusCode) -> bool {
+        matches!(status.as_u16(), 429 | 503)
+    }
+
+    async fn run_with_statuses(
+        policy: RetryPolicy,
+        statuses: &[u16],
+    ) -> (anyhow::Result<reqwest::Response>, usize) {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let result = send_with_retry("Test", policy, throttled, || {
+            let n = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let status = statuses[n.min(statuses.len() - 1)];
+            async move { Ok(status_response(status, None)) }
+        })
+        .await;
+        (result, calls.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    #[test]
+    fn backoff_stays_within_full_jitter_bounds() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay_ms: 100,
+        };
+        for attempt in 0..5 {
+            let ceiling = 100 * 2_u64.pow(attempt);
+            for _ in 0..20 {
+                assert!(policy.backoff(attempt) <= Duration::from_millis(ceiling));
+            }
+        }
+        // Large attempt counts are capped instead of overflowing.
+        assert!(policy.backoff(200) <= Duration::from_millis(MAX_RETRY_DELAY_MS));
+    }
+
+    #[test]
+    fn retry_after_header_parsed_and_capped() {
+        assert_eq!(
+            retry_after(&status_response(429, Some("2"))),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            retry_after(&status_response(429, Some("99999"))),
+            Some(Duration::from_millis(MAX_RETRY_DELAY_MS))
+        );
+        assert_eq!(retry_after(&status_response(429, Some("soon"))), None);
+        assert_eq!(retry_after(&status_response(429, None)), None);
+    }
+
+    #[tokio::test]
+    async fn throttled_requests_use_all_configured_attempts() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+        };
+        let (result, calls) = run_with_statuses(policy, &[429]).await;
+        assert_eq!(calls, 4, "initial attempt + 3 retries");
+        let err = result.unwrap_err();
+        assert!(err.is::<RetriesExhausted>());
+        let err = err.to_string();
+        assert!(
+            err.contains("Test API error (429"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn throttled_request_recovers() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+        };
+        let (result, calls) = run_with_statuses(policy, &[503, 429, 200]).await;
+        assert!(result.unwrap().status().is_success());
+        assert_eq!(calls, 3);
+    }
+
+    #[tokio::test]
+    async fn validation_error_fails_fast() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay_ms: 1,
+        };
+        let (result, calls) = run_with_statuses(policy, &[400]).await;
+        assert_eq!(calls, 1, "400 must not be retried");
+        let err = result.unwrap_err();
+        assert!(!err.is::<RetriesExhausted>());
+        assert!(err.to_string().contains("400"));
+    }
+}
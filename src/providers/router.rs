@@ -1,6 +1,12 @@
-use super::Provider;
+use super::reliable::is_non_retryable;
+use super::traits::{
+    ChatMessage, ChatResponse, ChatStream, ContentPart, InferenceParams, Provider, WarmupStatus,
+};
+use crate::observability::{NoopObserver, Observer, ObserverEvent};
+use crate::tools::ToolSpec;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// A single route: maps a task hint to a provider + model combo.
 #[derive(Debug, Clone)]
@@ -17,18 +23,24 @@ pub struct Route {
 /// - A hint-prefixed string (e.g. "hint:reasoning") → resolves via route table
 ///
 /// This wraps multiple pre-created providers and selects the right one per request.
+///
+/// Several routes may share a hint: they form an ordered fallback chain. When
+/// a route fails with a retryable error (rate limit, outage) the request moves
+/// on to the next one; client errors such as a bad request are returned as-is.
 pub struct RouterProvider {
-    routes: HashMap<String, (usize, String)>, // hint → (provider_index, model)
+    routes: HashMap<String, Vec<(usize, String)>>, // hint → [(provider_index, model)]
     providers: Vec<(String, Box<dyn Provider>)>,
     default_index: usize,
     default_model: String,
+    observer: Arc<dyn Observer>,
 }
 
 impl RouterProvider {
     /// Create a new router with a default provider and optional routes.
     ///
     /// `providers` is a list of (name, provider) pairs. The first one is the default.
-    /// `routes` maps hint names to Route structs containing provider_name and model.
+    /// `routes` maps hint names to Route structs containing provider_name and model;
+    /// repeated hints are tried in the order given.
     pub fn new(
         providers: Vec<(String, Box<dyn Provider>)>,
         routes: Vec<(String, Route)>,
@@ -41,42 +53,54 @@ impl RouterProvider {
             .map(|(i, (name, _))| (name.as_str(), i))
             .collect();
 
-        // Resolve routes to provider indices
-        let resolved_routes: HashMap<String, (usize, String)> = routes
-            .into_iter()
-            .filter_map(|(hint, route)| {
-                let index = name_to_index.get(route.provider_name.as_str()).copied();
-                match index {
-                    Some(i) => Some((hint, (i, route.model))),
-                    None => {
-                        tracing::warn!(
-                            hint = hint,
-                            provider = route.provider_name,
-                            "Route references unknown provider, skipping"
-                        );
-                        None
-                    }
+        // Resolve routes to provider indices, keeping the order of each chain
+        let mut resolved_routes: HashMap<String, Vec<(usize, String)>> = HashMap::new();
+        for (hint, route) in routes {
+            match name_to_index.get(route.provider_name.as_str()).copied() {
+                Some(i) => resolved_routes
+                    .entry(hint)
+                    .or_default()
+                    .push((i, route.model)),
+                None => {
+                    tracing::warn!(
+                        hint = hint,
+                        provider = route.provider_name,
+                        "Route references unknown provider, skipping"
+                    );
                 }
-            })
-            .collect();
+            }
+        }
 
         Self {
             routes: resolved_routes,
             providers,
             default_index: 0,
             default_model,
+            observer: Arc::new(NoopObserver),
         }
     }
 
+    /// Report fallbacks between routes to `observer`.
+    #[must_use]
+    pub fn with_observer(mut self, observer: Arc<dyn Observer>) -> Self {
+        self.observer = observer;
+        self
+    }
+
     /// Resolve a model parameter to a (provider, actual_model) pair.
     ///
     /// If the model starts with "hint:", look up the hint in the route table.
     /// Otherwise, use the default provider with the given model name.
     /// Resolve a model parameter to a (provider_index, actual_model) pair.
     fn resolve(&self, model: &str) -> (usize, String) {
+        self.resolve_chain(model).swap_remove(0)
+    }
+
+    /// Every `(provider_index, model)` to try for `model`, primary first.
+    fn resolve_chain(&self, model: &str) -> Vec<(usize, String)> {
         if let Some(hint) = model.strip_prefix("hint:") {
-            if let Some((idx, resolved_model)) = self.routes.get(hint) {
-                return (*idx, resolved_model.clone());
+            if let Some(chain) = self.routes.get(hint) {
+                return chain.clone();
             }
             tracing::warn!(
                 hint = hint,
@@ -85,41 +109,184 @@ impl RouterProvider {
         }
 
         // Not a hint or hint not found — use default provider with the model as-is
-        (self.default_index, model.to_string())
+        vec![(self.default_index, model.to_string())]
+    }
+
+    /// Whether a failed route should hand the request to the next one.
+    fn should_fall_back(
+        &self,
+        err: &anyhow::Error,
+        from_idx: usize,
+        next: Option<&(usize, String)>,
+    ) -> bool {
+        let Some((to_idx, to_model)) = next else {
+            return false;
+        };
+        if is_non_retryable(err) {
+            return false;
+        }
+        let from = &self.providers[from_idx].0;
+        let to = &self.providers[*to_idx].0;
+        tracing::warn!(
+            from = from.as_str(),
+            to = to.as_str(),
+            model = to_model.as_str(),
+            "Routed provider failed, trying fallback route"
+        );
+        self.observer
+            .record_event(&ObserverEvent::ProviderFallback {
+                from_provider: from.clone(),
+                to_provider: to.clone(),
+                model: to_model.clone(),
+                error: super::sanitize_api_error(&err.to_string()),
+            });
+        true
     }
 }
 
 #[async_trait]
 impl Provider for RouterProvider {
-    async fn chat_with_system(
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        self.chat_with_usage(messages, model, temperature)
+            .await
+            .map(|response| response.text)
+    }
+
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.chat_with_tools(messages, &[], model, temperature)
+            .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let chain = self.resolve_chain(model);
+        let mut routes = chain.iter().peekable();
+        while let Some((provider_idx, resolved_model)) = routes.next() {
+            let (provider_name, provider) = &self.providers[*provider_idx];
+            tracing::info!(
+                provider = provider_name.as_str(),
+                model = resolved_model.as_str(),
+                "Router dispatching request"
+            );
+
+            match provider
+                .chat_with_tools(messages, tools, resolved_model, temperature)
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(e) if self.should_fall_back(&e, *provider_idx, routes.peek().copied()) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        anyhow::bail!("No route for model {model}")
+    }
+
+    async fn chat_with_params(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        params: &InferenceParams,
+    ) -> anyhow::Result<String> {
+        let chain = self.resolve_chain(model);
+        let mut routes = chain.iter().peekable();
+        while let Some((provider_idx, resolved_model)) = routes.next() {
+            let (_, provider) = &self.providers[*provider_idx];
+            match provider
+                .chat_with_params(messages, resolved_model, temperature, params)
+                .await
+            {
+                Ok(text) => return Ok(text),
+                Err(e) if self.should_fall_back(&e, *provider_idx, routes.peek().copied()) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        anyhow::bail!("No route for model {model}")
+    }
+
+    async fn chat_with_parts(
         &self,
         system_prompt: Option<&str>,
-        message: &str,
+        parts: Vec<ContentPart>,
         model: &str,
         temperature: f64,
     ) -> anyhow::Result<String> {
-        let (provider_idx, resolved_model) = self.resolve(model);
+        let chain = self.resolve_chain(model);
+        let mut routes = chain.iter().peekable();
+        while let Some((provider_idx, resolved_model)) = routes.next() {
+            let (_, provider) = &self.providers[*provider_idx];
+            match provider
+                .chat_with_parts(system_prompt, parts.clone(), resolved_model, temperature)
+                .await
+            {
+                Ok(text) => return Ok(text),
+                Err(e) if self.should_fall_back(&e, *provider_idx, routes.peek().copied()) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        anyhow::bail!("No route for model {model}")
+    }
 
-        let (provider_name, provider) = &self.providers[provider_idx];
-        tracing::info!(
-            provider = provider_name.as_str(),
-            model = resolved_model.as_str(),
-            "Router dispatching request"
-        );
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let chain = self.resolve_chain(model);
+        let mut routes = chain.iter().peekable();
+        while let Some((provider_idx, resolved_model)) = routes.next() {
+            let (_, provider) = &self.providers[*provider_idx];
+            match provider
+                .chat_stream(messages, resolved_model, temperature)
+                .await
+            {
+                Ok(stream) => return Ok(stream),
+                Err(e) if self.should_fall_back(&e, *provider_idx, routes.peek().copied()) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        anyhow::bail!("No route for model {model}")
+    }
 
-        provider
-            .chat_with_system(system_prompt, message, &resolved_model, temperature)
-            .await
+    async fn embed(&self, texts: &[String], model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        let (provider_idx, resolved_model) = self.resolve(model);
+        let (_, provider) = &self.providers[provider_idx];
+        provider.embed(texts, &resolved_model).await
     }
 
-    async fn warmup(&self) -> anyhow::Result<()> {
+    /// Every route may be used, so all of them have to be reachable.
+    async fn warmup(&self) -> WarmupStatus {
+        let mut statuses = Vec::with_capacity(self.providers.len());
         for (name, provider) in &self.providers {
             tracing::info!(provider = name, "Warming up routed provider");
-            if let Err(e) = provider.warmup().await {
-                tracing::warn!(provider = name, "Warmup failed (non-fatal): {e}");
+            let status = provider.warmup().await;
+            if !status.reachable {
+                tracing::warn!(
+                    provider = name,
+                    "Warmup failed (non-fatal): {}",
+                    status.detail
+                );
             }
+            statuses.push((name.clone(), status));
         }
-        Ok(())
+        let reachable = statuses.iter().all(|(_, status)| status.reachable);
+        WarmupStatus::combine(&statuses, reachable)
     }
 }
 
@@ -233,7 +400,10 @@ mod tests {
             ],
         );
 
-        let result = router.chat("hello", "hint:reasoning", 0.5).await.unwrap();
+        let result = router
+            .chat_with_system(None, "hello", "hint:reasoning", 0.5)
+            .await
+            .unwrap();
         assert_eq!(result, "smart-response");
         assert_eq!(mocks[1].call_count(), 1);
         assert_eq!(mocks[1].last_model(), "claude-opus");
@@ -247,7 +417,10 @@ mod tests {
             vec![("fast", "fast", "llama-3-70b")],
         );
 
-        let result = router.chat("hello", "hint:fast", 0.5).await.unwrap();
+        let result = router
+            .chat_with_system(None, "hello", "hint:fast", 0.5)
+            .await
+            .unwrap();
         assert_eq!(result, "fast-response");
         assert_eq!(mocks[0].call_count(), 1);
         assert_eq!(mocks[0].last_model(), "llama-3-70b");
@@ -260,7 +433,10 @@ mod tests {
             vec![],
         );
 
-        let result = router.chat("hello", "hint:nonexistent", 0.5).await.unwrap();
+        let result = router
+            .chat_with_system(None, "hello", "hint:nonexistent", 0.5)
+            .await
+            .unwrap();
         assert_eq!(result, "default-response");
         assert_eq!(mocks[0].call_count(), 1);
         // Falls back to default with the hint as model name
@@ -275,7 +451,7 @@ mod tests {
         );
 
         let result = router
-            .chat("hello", "anthropic/claude-sonnet-4-20250514", 0.5)
+            .chat_with_system(None, "hello", "anthropic/claude-sonnet-4-20250514", 0.5)
             .await
             .unwrap();
         assert_eq!(result, "primary-response");
@@ -325,8 +501,155 @@ mod tests {
             vec![],
         );
 
-        // Warmup should not error
-        assert!(router.warmup().await.is_ok());
+        let status = router.warmup().await;
+        assert!(status.reachable);
+        assert_eq!(status.detail, "a: no warmup needed; b: no warmup needed");
+    }
+
+    /// Always fails with `error`, counting calls.
+    struct FailingProvider {
+        calls: Arc<AtomicUsize>,
+        error: &'static str,
+    }
+
+    #[async_trait]
+    impl Provider for FailingProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            _message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!(self.error)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        fallbacks: std::sync::Mutex<Vec<(String, String, String)>>,
+    }
+
+    impl Observer for RecordingObserver {
+        fn record_event(&self, event: &ObserverEvent) {
+            if let ObserverEvent::ProviderFallback {
+                from_provider,
+                to_provider,
+                model,
+                ..
+            } = event
+            {
+                self.fallbacks.lock().unwrap().push((
+                    from_provider.clone(),
+                    to_provider.clone(),
+                    model.clone(),
+                ));
+            }
+        }
+        fn record_metric(&self, _metric: &crate::observability::ObserverMetric) {}
+        fn name(&self) -> &str {
+            "recording"
+        }
+    }
+
+    fn fallback_router(
+        error: &'static str,
+    ) -> (
+        RouterProvider,
+        Arc<AtomicUsize>,
+        Arc<MockProvider>,
+        Arc<RecordingObserver>,
+    ) {
+        let primary_calls = Arc::new(AtomicUsize::new(0));
+        let fallback = Arc::new(MockProvider::new("fallback-response"));
+        let observer = Arc::new(RecordingObserver::default());
+        let route = |provider: &str, model: &str| {
+            (
+                "smart".to_string(),
+                Route {
+                    provider_name: provider.into(),
+                    model: model.into(),
+                },
+            )
+        };
+        let router = RouterProvider::new(
+            vec![
+                (
+                    "bedrock".into(),
+                    Box::new(FailingProvider {
+                        calls: Arc::clone(&primary_calls),
+                        error,
+                    }) as Box<dyn Provider>,
+                ),
+                ("openrouter".into(), Box::new(Arc::clone(&fallback))),
+            ],
+            vec![
+                route("bedrock", "anthropic.claude-sonnet-4"),
+                route("openrouter", "anthropic/claude-sonnet-4"),
+            ],
+            "default-model".into(),
+        )
+        .with_observer(Arc::clone(&observer) as Arc<dyn Observer>);
+        (router, primary_calls, fallback, observer)
+    }
+
+    #[tokio::test]
+    async fn retryable_failure_falls_back_to_next_route() {
+        let (router, primary_calls, fallback, observer) =
+            fallback_router("Bedrock API error (503 Service Unavailable)");
+
+        let result = router
+            .chat_with_system(None, "hello", "hint:smart", 0.5)
+            .await
+            .unwrap();
+        assert_eq!(result, "fallback-response");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.last_model(), "anthropic/claude-sonnet-4");
+        assert_eq!(
+            *observer.fallbacks.lock().unwrap(),
+            [(
+                "bedrock".to_string(),
+                "openrouter".to_string(),
+                "anthropic/claude-sonnet-4".to_string()
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn bad_request_does_not_fall_back() {
+        let (router, primary_calls, fallback, observer) =
+            fallback_router("Bedrock API error (400 Bad Request): invalid tool schema");
+
+        let err = router
+            .chat_with_system(None, "hello", "hint:smart", 0.5)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("400"), "{err}");
+        assert_eq!(primary_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.call_count(), 0);
+        assert!(observer.fallbacks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn last_route_failure_is_returned() {
+        let router = RouterProvider::new(
+            vec![(
+                "only".into(),
+                Box::new(FailingProvider {
+                    calls: Arc::new(AtomicUsize::new(0)),
+                    error: "503 Service Unavailable",
+                }) as Box<dyn Provider>,
+            )],
+            vec![],
+            "model".into(),
+        );
+
+        let err = router
+            .chat_with_system(None, "hello", "model", 0.5)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("503"), "{err}");
     }
 
     #[tokio::test]
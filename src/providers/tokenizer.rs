@@ -0,0 +1,157 @@
+use crate::config::{AgentConfig, TiktokenEncoding};
+use std::fmt;
+use tiktoken_rs::CoreBPE;
+
+/// Counts tokens the way a model family does, for context budgeting and
+/// usage estimates.
+pub trait Tokenizer: Send + Sync + fmt::Debug {
+    fn count_tokens(&self, text: &str) -> usize;
+}
+
+/// ~4 characters per token. Free and close enough for English prose, but
+/// it undercounts code and non-Latin text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.chars().count().div_ceil(4)
+    }
+}
+
+/// Exact counts for GPT models, from tiktoken's vocabularies. Each
+/// vocabulary is loaded on first use and kept for the life of the
+/// process.
+#[derive(Debug, Clone, Copy)]
+pub struct TiktokenTokenizer {
+    encoding: TiktokenEncoding,
+}
+
+impl TiktokenTokenizer {
+    pub fn for_encoding(encoding: TiktokenEncoding) -> &'static Self {
+        match encoding {
+            TiktokenEncoding::O200kBase => &Self {
+                encoding: TiktokenEncoding::O200kBase,
+            },
+            TiktokenEncoding::Cl100kBase => &Self {
+                encoding: TiktokenEncoding::Cl100kBase,
+            },
+            TiktokenEncoding::P50kBase => &Self {
+                encoding: TiktokenEncoding::P50kBase,
+            },
+            TiktokenEncoding::R50kBase => &Self {
+                encoding: TiktokenEncoding::R50kBase,
+            },
+        }
+    }
+
+    fn bpe(self) -> &'static CoreBPE {
+        match self.encoding {
+            TiktokenEncoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            TiktokenEncoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+            TiktokenEncoding::P50kBase => tiktoken_rs::p50k_base_singleton(),
+            TiktokenEncoding::R50kBase => tiktoken_rs::r50k_base_singleton(),
+        }
+    }
+}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        self.bpe().encode_ordinary(text).len()
+    }
+}
+
+/// The tokenizer for `model`: the `agent.tokenizers` entry whose key is the
+/// longest prefix of the model name, or the heuristic without one.
+pub fn for_model(config: &AgentConfig, model: &str) -> &'static dyn Tokenizer {
+    config
+        .tokenizers
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map_or(&HeuristicTokenizer, |(_, encoding)| {
+            TiktokenTokenizer::for_encoding(*encoding)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROSE: &str = "The agent trims the oldest tool exchanges once the conversation \
+                         grows past its context budget, keeping the system prompt and the \
+                         latest exchange so the model never loses the task at hand.";
+
+    #[test]
+    fn tiktoken_counts_match_the_reference_encodings() {
+        let cl100k = TiktokenTokenizer::for_encoding(TiktokenEncoding::Cl100kBase);
+        let o200k = TiktokenTokenizer::for_encoding(TiktokenEncoding::O200kBase);
+        assert_eq!(cl100k.count_tokens("hello world"), 2);
+        assert_eq!(o200k.count_tokens("hello world"), 2);
+        assert_eq!(cl100k.count_tokens(""), 0);
+        // Special-token text is counted as ordinary text
+        assert!(cl100k.count_tokens("<|endoftext|>") > 1);
+    }
+
+    /// On English prose the heuristic errs high, which keeps the context
+    /// budget safe, and by less than half.
+    #[test]
+    fn heuristic_error_on_prose_is_bounded() {
+        for encoding in [TiktokenEncoding::Cl100kBase, TiktokenEncoding::O200kBase] {
+            let exact = TiktokenTokenizer::for_encoding(encoding).count_tokens(PROSE);
+            let estimate = HeuristicTokenizer.count_tokens(PROSE);
+            assert!(
+                estimate >= exact && estimate - exact < exact / 2,
+                "{encoding:?}: heuristic {estimate} vs {exact} tokens"
+            );
+        }
+    }
+
+    #[test]
+    fn heuristic_undercounts_non_latin_text() {
+        let cjk = "上下文预算超出时，代理会先删除最早的工具调用记录。";
+        let exact = TiktokenTokenizer::for_encoding(TiktokenEncoding::Cl100kBase).count_tokens(cjk);
+        let estimate = HeuristicTokenizer.count_tokens(cjk);
+        assert!(
+            exact > 2 * estimate,
+            "heuristic {estimate} vs {exact} tokens"
+        );
+    }
+
+    #[test]
+    fn for_model_picks_the_longest_prefix() {
+        let mut config = AgentConfig::default();
+        config
+            .tokenizers
+            .insert("gpt-".into(), TiktokenEncoding::Cl100kBase);
+        config
+            .tokenizers
+            .insert("gpt-4o".into(), TiktokenEncoding::O200kBase);
+
+        let tokenizer = for_model(&config, "gpt-4o-mini");
+        assert!(format!("{tokenizer:?}").contains("O200kBase"));
+        let tokenizer = for_model(&config, "gpt-3.5-turbo");
+        assert!(format!("{tokenizer:?}").contains("Cl100kBase"));
+        let tokenizer = for_model(&config, "claude-sonnet-4");
+        assert!(format!("{tokenizer:?}").contains("HeuristicTokenizer"));
+    }
+
+    #[test]
+    fn tokenizers_are_configured_by_encoding_name() {
+        let config: AgentConfig = toml::from_str(
+            "[tokenizers]\n\"gpt-4o\" = \"o200k_base\"\n\"gpt-4\" = \"cl100k_base\"",
+        )
+        .unwrap();
+        assert_eq!(
+            config.tokenizers.get("gpt-4o"),
+            Some(&TiktokenEncoding::O200kBase)
+        );
+        assert_eq!(
+            config.tokenizers.get("gpt-4"),
+            Some(&TiktokenEncoding::Cl100kBase)
+        );
+        assert!(
+            toml::from_str::<AgentConfig>("[tokenizers]\n\"gpt-4o\" = \"o200k.tiktoken\"").is_err()
+        );
+    }
+}
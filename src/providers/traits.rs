@@ -1,23 +1,676 @@
+use crate::tools::{ToolCall, ToolSpec};
 use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 
+/// Stream of incremental text deltas produced by a streaming chat call,
+/// followed by the call's token usage when the provider reports it.
+pub type ChatStream = BoxStream<'static, anyhow::Result<StreamEvent>>;
+
+/// One item of a `ChatStream`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEvent {
+    /// The next piece of the reply
+    Text(String),
+    /// Token counts for the whole call
+    Usage(ResponseUsage),
+}
+
+impl StreamEvent {
+    /// The text of a `Text` event; `None` for usage.
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Usage(_) => None,
+        }
+    }
+}
+
+/// Outcome of `Provider::warmup`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WarmupStatus {
+    /// The provider answered, and accepted the credentials where they were checked
+    pub reachable: bool,
+    /// How long the check took
+    pub latency: Duration,
+    /// What was checked, or why it failed
+    pub detail: String,
+}
+
+impl WarmupStatus {
+    /// Status of a provider with nothing to warm up.
+    pub fn not_needed() -> Self {
+        Self {
+            reachable: true,
+            latency: Duration::ZERO,
+            detail: "no warmup needed".to_string(),
+        }
+    }
+
+    /// Run `check` and time it; `Ok` carries the detail, `Err` marks the
+    /// provider unreachable.
+    pub async fn check(check: impl std::future::Future<Output = anyhow::Result<String>>) -> Self {
+        let start = Instant::now();
+        let result = check.await;
+        let latency = start.elapsed();
+        match result {
+            Ok(detail) => Self {
+                reachable: true,
+                latency,
+                detail,
+            },
+            Err(e) => Self {
+                reachable: false,
+                latency,
+                detail: format!("{e:#}"),
+            },
+        }
+    }
+
+    /// One status for several named providers warmed up in turn: `reachable`
+    /// is decided by the caller, latencies add up and details are listed.
+    pub fn combine(statuses: &[(String, Self)], reachable: bool) -> Self {
+        Self {
+            reachable,
+            latency: statuses.iter().map(|(_, status)| status.latency).sum(),
+            detail: statuses
+                .iter()
+                .map(|(name, status)| format!("{name}: {}", status.detail))
+                .collect::<Vec<_>>()
+                .join("; "),
+        }
+    }
+}
+
+/// Token counts reported by a provider for a single call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl ResponseUsage {
+    pub fn total(&self) -> u64 {
+        self.input_tokens.saturating_add(self.output_tokens)
+    }
+}
+
+/// Author of a message in a conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    System,
+    User,
+    Assistant,
+}
+
+impl Role {
+    /// Role name as used by OpenAI-style chat APIs.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::User => "user",
+            Self::Assistant => "assistant",
+        }
+    }
+}
+
+/// One turn of a conversation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::System,
+            content: content.into(),
+        }
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::Assistant,
+            content: content.into(),
+        }
+    }
+}
+
+/// Join all system messages into one prompt (`None` if there are none).
+pub fn system_prompt(messages: &[ChatMessage]) -> Option<String> {
+    let parts: Vec<&str> = messages
+        .iter()
+        .filter(|m| m.role == Role::System)
+        .map(|m| m.content.as_str())
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
+
+/// Flatten a conversation into a system prompt plus one user message, for
+/// providers that only implement `chat_with_system`.
+///
+/// A single turn is passed through unchanged; longer histories are rendered
+/// as `[User]` / `[Assistant]` labelled blocks.
+pub fn flatten_history(messages: &[ChatMessage]) -> (Option<String>, String) {
+    let turns: Vec<&ChatMessage> = messages.iter().filter(|m| m.role != Role::System).collect();
+    let message = match turns.as_slice() {
+        [only] => only.content.clone(),
+        _ => turns
+            .iter()
+            .map(|m| match m.role {
+                Role::Assistant => format!("[Assistant]\n{}", m.content),
+                _ => format!("[User]\n{}", m.content),
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+    (system_prompt(messages), message)
+}
+
+/// Image encodings accepted in multimodal requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Webp,
+}
+
+impl ImageFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Jpeg => "jpeg",
+            Self::Gif => "gif",
+            Self::Webp => "webp",
+        }
+    }
+
+    /// Parse a declared format name (case-insensitive; `jpg` is accepted).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "png" => Some(Self::Png),
+            "jpeg" | "jpg" => Some(Self::Jpeg),
+            "gif" => Some(Self::Gif),
+            "webp" => Some(Self::Webp),
+            _ => None,
+        }
+    }
+
+    /// Detect the format from the file's magic bytes.
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some(Self::Jpeg)
+        } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else {
+            None
+        }
+    }
+
+    /// Resolve the format of an image, preferring the declared name over detection.
+    ///
+    /// Errors when the declared format is unsupported or detection fails.
+    pub fn resolve(declared: Option<&str>, bytes: &[u8]) -> anyhow::Result<Self> {
+        match declared {
+            Some(name) => Self::parse(name).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unsupported image format '{name}' (expected png, jpeg, gif or webp)"
+                )
+            }),
+            None => Self::detect(bytes).ok_or_else(|| {
+                anyhow::anyhow!("Could not detect image format (expected png, jpeg, gif or webp)")
+            }),
+        }
+    }
+}
+
+/// One piece of a multimodal user message.
+#[derive(Debug, Clone)]
+pub enum ContentPart {
+    Text(String),
+    /// Raw image bytes. `format` is the declared format name; when `None`
+    /// it is detected from the bytes.
+    Image {
+        format: Option<String>,
+        data: Vec<u8>,
+    },
+}
+
+/// Chat response text plus token usage, when the provider reports it.
+#[derive(Debug, Clone, Default)]
+pub struct ChatResponse {
+    pub text: String,
+    pub usage: Option<ResponseUsage>,
+    /// Tool calls returned through the provider's native function-calling
+    /// API. Empty when the provider has none (or no native support).
+    pub tool_calls: Vec<ToolCall>,
+    /// The model's separate thinking output, for providers that return one
+    /// (kept out of `text`).
+    pub reasoning: Option<String>,
+}
+
+/// Per-call overrides of a provider's configured inference parameters.
+/// Unset fields keep the configured value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InferenceParams {
+    pub max_tokens: Option<u32>,
+    pub top_p: Option<f64>,
+    pub stop_sequences: Option<Vec<String>>,
+}
+
+/// An LLM backend.
+///
+/// Implementors must override at least one of `chat` or `chat_with_system`;
+/// each has a default implementation in terms of the other.
 #[async_trait]
 pub trait Provider: Send + Sync {
-    async fn chat(&self, message: &str, model: &str, temperature: f64) -> anyhow::Result<String> {
-        self.chat_with_system(None, message, model, temperature)
+    /// Send a full conversation and return the assistant's reply.
+    ///
+    /// Default implementation flattens the history (see `flatten_history`) and
+    /// calls `chat_with_system`; providers with a native messages API should override.
+    async fn chat(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let (system_prompt, message) = flatten_history(messages);
+        self.chat_with_system(system_prompt.as_deref(), &message, model, temperature)
             .await
     }
 
+    /// Single-turn shim over `chat`.
     async fn chat_with_system(
         &self,
         system_prompt: Option<&str>,
         message: &str,
         model: &str,
         temperature: f64,
-    ) -> anyhow::Result<String>;
+    ) -> anyhow::Result<String> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system_prompt) = system_prompt {
+            messages.push(ChatMessage::system(system_prompt));
+        }
+        messages.push(ChatMessage::user(message));
+        self.chat(&messages, model, temperature).await
+    }
+
+    /// Like `chat`, but also returns token usage.
+    ///
+    /// Default implementation reports no usage; providers whose API returns
+    /// token counts should override.
+    async fn chat_with_usage(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        let text = self.chat(messages, model, temperature).await?;
+        Ok(ChatResponse {
+            text,
+            ..ChatResponse::default()
+        })
+    }
+
+    /// Like `chat_with_usage`, but offers `tools` to the model through the
+    /// provider's native function-calling API.
+    ///
+    /// Default implementation ignores the tools and returns no calls, so the
+    /// caller falls back to parsing `<tool_call>` tags out of the text.
+    async fn chat_with_tools(
+        &self,
+        messages: &[ChatMessage],
+        _tools: &[ToolSpec],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatResponse> {
+        self.chat_with_usage(messages, model, temperature).await
+    }
 
-    /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup).
-    /// Default implementation is a no-op; providers with HTTP clients should override.
-    async fn warmup(&self) -> anyhow::Result<()> {
-        Ok(())
+    /// Like `chat`, with `params` overriding the provider's configured
+    /// inference parameters for this call.
+    ///
+    /// Default implementation ignores `params`; providers whose API takes
+    /// these parameters should override.
+    async fn chat_with_params(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+        _params: &InferenceParams,
+    ) -> anyhow::Result<String> {
+        self.chat(messages, model, temperature).await
+    }
+
+    /// Embed each text into a vector, returning one vector per input in order.
+    ///
+    /// Default implementation fails for providers without an embeddings API.
+    async fn embed(&self, _texts: &[String], _model: &str) -> anyhow::Result<Vec<Vec<f32>>> {
+        anyhow::bail!("embeddings not supported by this provider")
+    }
+
+    /// Send a user message made of text and image parts.
+    ///
+    /// Default implementation accepts text-only messages (parts are joined with
+    /// blank lines) and rejects images; vision-capable providers should override.
+    async fn chat_with_parts(
+        &self,
+        system_prompt: Option<&str>,
+        parts: Vec<ContentPart>,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<String> {
+        let mut texts = Vec::with_capacity(parts.len());
+        for part in parts {
+            match part {
+                ContentPart::Text(text) => texts.push(text),
+                ContentPart::Image { .. } => {
+                    anyhow::bail!("This provider does not support image input")
+                }
+            }
+        }
+        self.chat_with_system(system_prompt, &texts.join("\n\n"), model, temperature)
+            .await
+    }
+
+    /// Stream the response as text deltas while it is being generated.
+    ///
+    /// Default implementation waits for `chat_with_system` and yields the full
+    /// response as a single chunk; providers with a native streaming API should override.
+    async fn chat_stream_with_system(
+        &self,
+        system_prompt: Option<&str>,
+        message: &str,
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self
+            .chat_with_system(system_prompt, message, model, temperature)
+            .await?;
+        Ok(stream::once(async move { Ok(StreamEvent::Text(response)) }).boxed())
+    }
+
+    /// Stream the reply to a full conversation as text deltas.
+    ///
+    /// Default implementation waits for `chat_with_usage` and yields the full
+    /// response as a single chunk, then its usage if any; providers with a
+    /// native streaming API should override.
+    async fn chat_stream(
+        &self,
+        messages: &[ChatMessage],
+        model: &str,
+        temperature: f64,
+    ) -> anyhow::Result<ChatStream> {
+        let response = self.chat_with_usage(messages, model, temperature).await?;
+        let events = std::iter::once(StreamEvent::Text(response.text))
+            .chain(response.usage.map(StreamEvent::Usage))
+            .map(Ok);
+        Ok(stream::iter(events).boxed())
+    }
+
+    /// Warm up the HTTP connection pool (TLS handshake, DNS, HTTP/2 setup)
+    /// and report whether the provider is reachable with the configured
+    /// credentials. Default implementation is a no-op; providers with HTTP
+    /// clients should override.
+    async fn warmup(&self) -> WarmupStatus {
+        WarmupStatus::not_needed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEADER: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        async fn chat_with_system(
+            &self,
+            _system_prompt: Option<&str>,
+            message: &str,
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(message.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn default_chat_stream_yields_single_chunk() {
+        let chunks: Vec<StreamEvent> = EchoProvider
+            .chat_stream(&[ChatMessage::user("ping")], "m", 0.0)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(chunks, vec![StreamEvent::Text("ping".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn default_chat_stream_ends_with_reported_usage() {
+        struct CountingProvider;
+
+        #[async_trait]
+        impl Provider for CountingProvider {
+            async fn chat_with_system(
+                &self,
+                _system_prompt: Option<&str>,
+                message: &str,
+                _model: &str,
+                _temperature: f64,
+            ) -> anyhow::Result<String> {
+                Ok(message.to_string())
+            }
+
+            async fn chat_with_usage(
+                &self,
+                _messages: &[ChatMessage],
+                _model: &str,
+                _temperature: f64,
+            ) -> anyhow::Result<ChatResponse> {
+                Ok(ChatResponse {
+                    text: "pong".into(),
+                    usage: Some(ResponseUsage {
+                        input_tokens: 3,
+                        output_tokens: 1,
+                    }),
+                    ..ChatResponse::default()
+                })
+            }
+        }
+
+        let chunks: Vec<StreamEvent> = CountingProvider
+            .chat_stream(&[ChatMessage::user("ping")], "m", 0.0)
+            .await
+            .unwrap()
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            vec![
+                StreamEvent::Text("pong".to_string()),
+                StreamEvent::Usage(ResponseUsage {
+                    input_tokens: 3,
+                    output_tokens: 1,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn default_chat_stream_with_system_yields_single_chunk() {
+        let chunks: Vec<String> = EchoProvider
+            .chat_stream_with_system(Some("be brief"), "whole answer", "m", 0.0)
+            .await
+            .unwrap()
+            .filter_map(|chunk| async move { chunk.unwrap().into_text() })
+            .collect()
+            .await;
+        assert_eq!(chunks, vec!["whole answer".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn default_embed_is_unsupported() {
+        let err = EchoProvider
+            .embed(&["x".to_string()], "m")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("embeddings not supported"));
+    }
+
+    #[test]
+    fn flatten_single_turn_passes_message_through() {
+        let messages = vec![ChatMessage::system("be brief"), ChatMessage::user("hello")];
+        let (system, message) = flatten_history(&messages);
+        assert_eq!(system.as_deref(), Some("be brief"));
+        assert_eq!(message, "hello");
+    }
+
+    #[test]
+    fn flatten_multi_turn_labels_roles() {
+        let messages = vec![
+            ChatMessage::user("hi"),
+            ChatMessage::assistant("hello"),
+            ChatMessage::user("bye"),
+        ];
+        let (system, message) = flatten_history(&messages);
+        assert!(system.is_none());
+        assert_eq!(message, "[User]\nhi\n\n[Assistant]\nhello\n\n[User]\nbye");
+    }
+
+    #[test]
+    fn system_prompt_joins_all_system_messages() {
+        let messages = vec![
+            ChatMessage::system("one"),
+            ChatMessage::user("x"),
+            ChatMessage::system("two"),
+        ];
+        assert_eq!(system_prompt(&messages).as_deref(), Some("one\n\ntwo"));
+    }
+
+    #[tokio::test]
+    async fn default_chat_uses_chat_with_system() {
+        let reply = EchoProvider
+            .chat(&[ChatMessage::user("ping")], "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(reply, "ping");
+    }
+
+    struct HistoryProvider;
+
+    #[async_trait]
+    impl Provider for HistoryProvider {
+        async fn chat(
+            &self,
+            messages: &[ChatMessage],
+            _model: &str,
+            _temperature: f64,
+        ) -> anyhow::Result<String> {
+            Ok(messages
+                .iter()
+                .map(|m| format!("{}:{}", m.role.as_str(), m.content))
+                .collect::<Vec<_>>()
+                .join("|"))
+        }
+    }
+
+    #[tokio::test]
+    async fn default_chat_with_system_builds_messages() {
+        let reply = HistoryProvider
+            .chat_with_system(Some("sys"), "hi", "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(reply, "system:sys|user:hi");
+    }
+
+    #[test]
+    fn image_format_parse_accepts_known_names() {
+        assert_eq!(ImageFormat::parse("PNG"), Some(ImageFormat::Png));
+        assert_eq!(ImageFormat::parse("jpg"), Some(ImageFormat::Jpeg));
+        assert_eq!(ImageFormat::parse("webp"), Some(ImageFormat::Webp));
+        assert_eq!(ImageFormat::parse("bmp"), None);
+    }
+
+    #[test]
+    fn image_format_detects_magic_bytes() {
+        assert_eq!(ImageFormat::detect(PNG_HEADER), Some(ImageFormat::Png));
+        assert_eq!(
+            ImageFormat::detect(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(ImageFormat::detect(b"GIF89a...."), Some(ImageFormat::Gif));
+        assert_eq!(
+            ImageFormat::detect(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some(ImageFormat::Webp)
+        );
+        assert_eq!(ImageFormat::detect(b"BM"), None);
+    }
+
+    #[test]
+    fn image_format_resolve_rejects_unsupported_format() {
+        let err = ImageFormat::resolve(Some("tiff"), &[1, 2, 3])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("tiff"));
+        assert!(ImageFormat::resolve(None, b"plain text").is_err());
+    }
+
+    #[test]
+    fn image_format_resolve_prefers_declared_format() {
+        assert_eq!(
+            ImageFormat::resolve(Some("jpeg"), PNG_HEADER).unwrap(),
+            ImageFormat::Jpeg
+        );
+        assert_eq!(
+            ImageFormat::resolve(None, PNG_HEADER).unwrap(),
+            ImageFormat::Png
+        );
+    }
+
+    #[tokio::test]
+    async fn default_chat_with_parts_joins_text() {
+        let parts = vec![
+            ContentPart::Text("one".into()),
+            ContentPart::Text("two".into()),
+        ];
+        let reply = EchoProvider
+            .chat_with_parts(None, parts, "m", 0.0)
+            .await
+            .unwrap();
+        assert_eq!(reply, "one\n\ntwo");
+    }
+
+    #[tokio::test]
+    async fn default_chat_with_parts_rejects_images() {
+        let parts = vec![ContentPart::Image {
+            format: None,
+            data: PNG_HEADER.to_vec(),
+        }];
+        let err = EchoProvider
+            .chat_with_parts(None, parts, "m", 0.0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not support image"));
     }
 }
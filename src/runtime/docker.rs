@@ -52,6 +52,26 @@ impl DockerRuntime {
     }
 }
 
+/// Turn the result of `docker version` into a startup error a user can act on.
+fn docker_availability(output: std::io::Result<std::process::Output>) -> Result<()> {
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+            "runtime.kind = \"docker\" but the `docker` CLI was not found on PATH. \
+             Install Docker or set runtime.kind = \"native\"."
+        ),
+        Err(e) => return Err(e).context("Failed to run `docker version`"),
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "runtime.kind = \"docker\" but the Docker daemon is not reachable: {}",
+            stderr.trim()
+        );
+    }
+    Ok(())
+}
+
 impl RuntimeAdapter for DockerRuntime {
     fn name(&self) -> &str {
         "docker"
@@ -83,6 +103,14 @@ impl RuntimeAdapter for DockerRuntime {
             .map_or(0, |mb| mb.saturating_mul(1024 * 1024))
     }
 
+    fn check_available(&self) -> Result<()> {
+        docker_availability(
+            std::process::Command::new("docker")
+                .args(["version", "--format", "{{.Server.Version}}"])
+                .output(),
+        )
+    }
+
     fn build_shell_command(
         &self,
         command: &str,
@@ -183,6 +211,69 @@ mod tests {
         assert!(debug.contains("echo hello"));
     }
 
+    #[test]
+    fn docker_default_config_has_no_network_and_limits() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        let command = runtime
+            .build_shell_command("true", &std::env::temp_dir())
+            .unwrap();
+        let args: Vec<_> = command
+            .as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+
+        let network = args.iter().position(|a| a == "--network").unwrap();
+        assert_eq!(args[network + 1], "none");
+        assert!(args.iter().any(|a| a == "--memory"));
+        assert!(args.iter().any(|a| a == "--cpus"));
+        assert!(args.iter().any(|a| a == "--read-only"));
+    }
+
+    #[test]
+    fn docker_missing_cli_is_a_clear_error() {
+        let missing = Err(std::io::Error::from(std::io::ErrorKind::NotFound));
+        let err = docker_availability(missing).unwrap_err().to_string();
+        assert!(err.contains("`docker` CLI was not found"), "{err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn docker_unreachable_daemon_is_a_clear_error() {
+        use std::os::unix::process::ExitStatusExt;
+        let output = std::process::Output {
+            status: std::process::ExitStatus::from_raw(1 << 8),
+            stdout: Vec::new(),
+            stderr: b"Cannot connect to the Docker daemon\n".to_vec(),
+        };
+        let err = docker_availability(Ok(output)).unwrap_err().to_string();
+        assert!(err.contains("daemon is not reachable"), "{err}");
+        assert!(err.contains("Cannot connect to the Docker daemon"), "{err}");
+    }
+
+    /// Runs a real container; skipped when Docker isn't available.
+    #[tokio::test]
+    async fn docker_runs_echo_in_container() {
+        let runtime = DockerRuntime::new(DockerRuntimeConfig::default());
+        if let Err(e) = runtime.check_available() {
+            eprintln!("skipping docker_runs_echo_in_container: {e}");
+            return;
+        }
+        let workspace = tempfile::TempDir::new().unwrap();
+        let output = runtime
+            .build_shell_command("echo hello", workspace.path())
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+    }
+
     #[test]
     fn docker_workspace_allowlist_blocks_outside_paths() {
         let cfg = DockerRuntimeConfig {
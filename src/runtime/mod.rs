@@ -1,9 +1,11 @@
 pub mod docker;
 pub mod native;
+pub mod ssh;
 pub mod traits;
 
 pub use docker::DockerRuntime;
 pub use native::NativeRuntime;
+pub use ssh::SshRuntime;
 pub use traits::RuntimeAdapter;
 
 use crate::config::RuntimeConfig;
@@ -13,13 +15,16 @@ pub fn create_runtime(config: &RuntimeConfig) -> anyhow::Result<Box<dyn RuntimeA
     match config.kind.as_str() {
         "native" => Ok(Box::new(NativeRuntime::new())),
         "docker" => Ok(Box::new(DockerRuntime::new(config.docker.clone()))),
+        "ssh" => Ok(Box::new(SshRuntime::new(config.ssh.clone()))),
         "cloudflare" => anyhow::bail!(
             "runtime.kind='cloudflare' is not implemented yet. Use runtime.kind='native' for now."
         ),
         other if other.trim().is_empty() => {
-            anyhow::bail!("runtime.kind cannot be empty. Supported values: native, docker")
+            anyhow::bail!("runtime.kind cannot be empty. Supported values: native, docker, ssh")
+        }
+        other => {
+            anyhow::bail!("Unknown runtime kind '{other}'. Supported values: native, docker, ssh")
         }
-        other => anyhow::bail!("Unknown runtime kind '{other}'. Supported values: native, docker"),
     }
 }
 
@@ -49,6 +54,17 @@ mod tests {
         assert!(rt.has_shell_access());
     }
 
+    #[test]
+    fn factory_ssh() {
+        let cfg = RuntimeConfig {
+            kind: "ssh".into(),
+            ..RuntimeConfig::default()
+        };
+        let rt = create_runtime(&cfg).unwrap();
+        assert_eq!(rt.name(), "ssh");
+        assert!(rt.remote_files().is_some());
+    }
+
     #[test]
     fn factory_cloudflare_errors() {
         let cfg = RuntimeConfig {
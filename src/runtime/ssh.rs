@@ -0,0 +1,599 @@
+use super::traits::{RemoteFiles, RuntimeAdapter};
+use crate::config::SshRuntimeConfig;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use tokio::io::AsyncWriteExt;
+
+/// How long an idle master connection is kept open for reuse.
+const CONTROL_PERSIST_SECS: u32 = 600;
+
+/// Remote runtime — shell commands run on another host over `ssh`, file
+/// tools go through `sftp`.
+///
+/// All invocations share one OpenSSH master connection (`ControlMaster`), so
+/// the handshake happens once and later commands reuse it. If the master dies
+/// the next command transparently opens a new one; file transfers that fail
+/// on a dropped connection are retried once.
+#[derive(Debug, Clone)]
+pub struct SshRuntime {
+    config: SshRuntimeConfig,
+    ssh_program: OsString,
+    sftp_program: OsString,
+}
+
+impl SshRuntime {
+    pub fn new(config: SshRuntimeConfig) -> Self {
+        Self {
+            config,
+            ssh_program: "ssh".into(),
+            sftp_program: "sftp".into(),
+        }
+    }
+
+    /// `user@host`, or just `host` when no user is configured.
+    fn destination(&self) -> Result<String> {
+        let host = self.config.host.trim();
+        if host.is_empty() {
+            anyhow::bail!("runtime.kind = \"ssh\" but runtime.ssh.host is not set");
+        }
+        if host.starts_with('-') {
+            anyhow::bail!("Invalid runtime.ssh.host: {host}");
+        }
+        let user = self.config.user.trim();
+        Ok(if user.is_empty() {
+            host.to_string()
+        } else {
+            format!("{user}@{host}")
+        })
+    }
+
+    fn key_path(&self) -> PathBuf {
+        PathBuf::from(shellexpand::tilde(self.config.key_path.trim()).as_ref())
+    }
+
+    fn check_key(&self) -> Result<PathBuf> {
+        let key = self.key_path();
+        if !key.is_file() {
+            anyhow::bail!(
+                "SSH key not found at {} (runtime.ssh.key_path)",
+                key.display()
+            );
+        }
+        Ok(key)
+    }
+
+    /// Workspace as seen on the remote host. `~/` paths are left relative so
+    /// they resolve against the remote user's home directory.
+    fn remote_workspace(&self, workspace_dir: &Path) -> PathBuf {
+        match self.config.remote_workspace.as_deref().map(str::trim) {
+            Some("~") => PathBuf::from("."),
+            Some(dir) if dir.starts_with("~/") => PathBuf::from(&dir[2..]),
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => workspace_dir.to_path_buf(),
+        }
+    }
+
+    /// Options shared by `ssh` and `sftp`: key auth only, never prompt, and
+    /// multiplex over one persistent master connection.
+    fn common_options(&self, key: &Path) -> Vec<OsString> {
+        let control_path = std::env::temp_dir().join("zeroclaw-ssh-%C");
+        let options = [
+            format!("IdentityFile={}", key.display()),
+            "IdentitiesOnly=yes".into(),
+            "BatchMode=yes".into(),
+            format!("Port={}", self.config.port),
+            "ConnectTimeout=10".into(),
+            "ServerAliveInterval=15".into(),
+            "ServerAliveCountMax=3".into(),
+            "ControlMaster=auto".into(),
+            format!("ControlPath={}", control_path.display()),
+            format!("ControlPersist={CONTROL_PERSIST_SECS}"),
+        ];
+        options
+            .into_iter()
+            .flat_map(|option| [OsString::from("-o"), OsString::from(option)])
+            .collect()
+    }
+
+    fn ssh_command(&self, key: &Path) -> std::process::Command {
+        let mut process = std::process::Command::new(&self.ssh_program);
+        process.args(self.common_options(key));
+        process
+    }
+
+    /// Ask the master connection to exit so the next call reconnects.
+    async fn drop_master(&self, key: &Path, destination: &str) {
+        let mut process = tokio::process::Command::from(self.ssh_command(key));
+        process
+            .args(["-O", "exit", destination])
+            .stdin(std::process::Stdio::null());
+        let _ = process.output().await;
+    }
+
+    async fn sftp_once(&self, key: &Path, destination: &str, batch: &str) -> Result<Output> {
+        let mut process = tokio::process::Command::new(&self.sftp_program);
+        process
+            .args(self.common_options(key))
+            .args(["-b", "-", destination])
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        let mut child = process.spawn().context("Failed to start `sftp`")?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(batch.as_bytes()).await?;
+        }
+        Ok(child.wait_with_output().await?)
+    }
+
+    /// Run an sftp batch, reconnecting once if the connection dropped.
+    async fn sftp(&self, batch: &str) -> Result<()> {
+        let key = self.check_key()?;
+        let destination = self.destination()?;
+        let mut output = self.sftp_once(&key, &destination, batch).await?;
+        if !output.status.success() && is_connection_failure(&output) {
+            tracing::warn!(destination, "SSH connection dropped, reconnecting");
+            self.drop_master(&key, &destination).await;
+            output = self.sftp_once(&key, &destination, batch).await?;
+        }
+        if output.status.success() {
+            return Ok(());
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.code() == Some(255) || is_connection_failure(&output) {
+            return Err(connection_error(&destination, &key, &stderr));
+        }
+        anyhow::bail!("sftp failed: {}", stderr.trim())
+    }
+
+    /// Temporary local file for one transfer.
+    fn staging_path() -> PathBuf {
+        std::env::temp_dir().join(format!("zeroclaw-sftp-{}", uuid::Uuid::new_v4().simple()))
+    }
+}
+
+/// Quote `value` for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quote a path for an sftp batch file.
+fn sftp_quote(path: &Path) -> Result<String> {
+    let path = path.to_string_lossy();
+    if path.contains(['\n', '\r']) {
+        anyhow::bail!("Path contains a line break: {path:?}");
+    }
+    Ok(format!(
+        "\"{}\"",
+        path.replace('\\', r"\\").replace('"', "\\\"")
+    ))
+}
+
+/// Whether a failed `ssh`/`sftp` run lost the connection rather than
+/// failing the operation itself.
+fn is_connection_failure(output: &Output) -> bool {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Retrying will not fix credentials or an untrusted host
+    if stderr.contains("Permission denied") || stderr.contains("Host key verification failed") {
+        return false;
+    }
+    if output.status.code() == Some(255) {
+        return true;
+    }
+    [
+        "Connection closed",
+        "Connection reset",
+        "Connection refused",
+        "Broken pipe",
+        "mux_client",
+    ]
+    .iter()
+    .any(|marker| stderr.contains(marker))
+}
+
+/// Turn ssh's stderr into an error a user can act on.
+fn connection_error(destination: &str, key: &Path, stderr: &str) -> anyhow::Error {
+    let stderr = stderr.trim();
+    if stderr.contains("Permission denied") {
+        anyhow::anyhow!(
+            "SSH authentication failed for {destination} with key {}: {stderr}",
+            key.display()
+        )
+    } else if stderr.contains("Host key verification failed") {
+        anyhow::anyhow!(
+            "Host key for {destination} is not trusted; connect once with `ssh` to add it to known_hosts: {stderr}"
+        )
+    } else {
+        anyhow::anyhow!("Could not connect to {destination} over SSH: {stderr}")
+    }
+}
+
+impl RuntimeAdapter for SshRuntime {
+    fn name(&self) -> &str {
+        "ssh"
+    }
+
+    fn has_shell_access(&self) -> bool {
+        true
+    }
+
+    fn has_filesystem_access(&self) -> bool {
+        true
+    }
+
+    /// Agent state (memory, logs) stays on the local machine.
+    fn storage_path(&self) -> PathBuf {
+        directories::UserDirs::new().map_or_else(
+            || PathBuf::from(".zeroclaw"),
+            |u| u.home_dir().join(".zeroclaw"),
+        )
+    }
+
+    fn supports_long_running(&self) -> bool {
+        true
+    }
+
+    /// Opens the master connection, so auth problems surface at startup.
+    fn check_available(&self) -> Result<()> {
+        let key = self.check_key()?;
+        let destination = self.destination()?;
+        let output = self
+            .ssh_command(&key)
+            .args(["-T", &destination, "true"])
+            .stdin(std::process::Stdio::null())
+            .output();
+        let output = match output {
+            Ok(output) => output,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                anyhow::bail!("runtime.kind = \"ssh\" but the `ssh` client was not found on PATH")
+            }
+            Err(e) => return Err(e).context("Failed to run `ssh`"),
+        };
+        if !output.status.success() {
+            return Err(connection_error(
+                &destination,
+                &key,
+                &String::from_utf8_lossy(&output.stderr),
+            ));
+        }
+        Ok(())
+    }
+
+    fn remote_files(&self) -> Option<&dyn RemoteFiles> {
+        Some(self)
+    }
+
+    fn build_shell_command(
+        &self,
+        command: &str,
+        workspace_dir: &Path,
+    ) -> Result<tokio::process::Command> {
+        let key = self.check_key()?;
+        let destination = self.destination()?;
+        let workspace = self.remote_workspace(workspace_dir);
+        let script = format!(
+            "cd {} && {command}",
+            shell_quote(&workspace.to_string_lossy())
+        );
+
+        let mut process = tokio::process::Command::from(self.ssh_command(&key));
+        process.arg("-T").arg(destination).arg(script);
+        Ok(process)
+    }
+}
+
+#[async_trait]
+impl RemoteFiles for SshRuntime {
+    async fn read_file(&self, workspace_dir: &Path, path: &str) -> Result<Vec<u8>> {
+        let remote = self.remote_workspace(workspace_dir).join(path);
+        let local = Self::staging_path();
+        let batch = format!("get {} {}\n", sftp_quote(&remote)?, sftp_quote(&local)?);
+        let result = self.sftp(&batch).await;
+        let contents = match result {
+            Ok(()) => tokio::fs::read(&local).await.map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        let _ = tokio::fs::remove_file(&local).await;
+        contents
+    }
+
+    async fn write_file(&self, workspace_dir: &Path, path: &str, contents: &[u8]) -> Result<()> {
+        let workspace = self.remote_workspace(workspace_dir);
+        let remote = workspace.join(path);
+        let local = Self::staging_path();
+
+        // `-` lets sftp carry on when a directory already exists
+        let mut batch = Vec::new();
+        let mut dir = workspace;
+        if let Some(parent) = Path::new(path).parent() {
+            for component in parent.components() {
+                dir.push(component);
+                batch.push(format!("-mkdir {}", sftp_quote(&dir)?));
+            }
+        }
+        batch.push(format!(
+            "put {} {}",
+            sftp_quote(&local)?,
+            sftp_quote(&remote)?
+        ));
+        batch.push(String::new());
+
+        tokio::fs::write(&local, contents).await?;
+        let result = self.sftp(&batch.join("\n")).await;
+        let _ = tokio::fs::remove_file(&local).await;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(key_path: &Path) -> SshRuntimeConfig {
+        SshRuntimeConfig {
+            host: "build.example.com".into(),
+            user: "agent".into(),
+            key_path: key_path.to_string_lossy().into_owned(),
+            ..SshRuntimeConfig::default()
+        }
+    }
+
+    /// A key file plus fake `ssh`/`sftp` programs standing in for the
+    /// transport. The fakes log their arguments and stdin to `log_dir`, and
+    /// act on the local filesystem as if it were the remote host.
+    #[cfg(unix)]
+    struct FakeTransport {
+        dir: TempDir,
+    }
+
+    #[cfg(unix)]
+    impl FakeTransport {
+        fn new() -> Self {
+            let dir = TempDir::new().unwrap();
+            std::fs::write(dir.path().join("id_test"), "key").unwrap();
+            std::fs::create_dir(dir.path().join("workspace")).unwrap();
+            Self { dir }
+        }
+
+        fn script(&self, name: &str, body: &str) -> OsString {
+            use std::os::unix::fs::PermissionsExt;
+            let path = self.dir.path().join(name);
+            std::fs::write(
+                &path,
+                format!("#!/bin/sh\nLOG='{}'\n{body}", self.dir.path().display()),
+            )
+            .unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            path.into_os_string()
+        }
+
+        /// Fake `ssh`: runs the remote command string with a local shell.
+        fn ssh(&self) -> OsString {
+            self.script(
+                "fake-ssh",
+                r#"printf '%s\n' "$@" > "$LOG/ssh.args"
+for last; do :; done
+exec sh -c "$last"
+"#,
+            )
+        }
+
+        /// Fake `sftp`: executes `get`/`put`/`mkdir` batch lines with `cp`.
+        fn sftp(&self) -> OsString {
+            self.script(
+                "fake-sftp",
+                r#"printf '%s\n' "$@" > "$LOG/sftp.args"
+cat > "$LOG/sftp.batch"
+while IFS= read -r line; do
+  case "$line" in
+    -mkdir*) eval "mkdir ${line#-mkdir }" 2>/dev/null ;;
+    get*) eval "cp ${line#get }" || exit 1 ;;
+    put*) eval "cp ${line#put }" || exit 1 ;;
+  esac
+done < "$LOG/sftp.batch"
+"#,
+            )
+        }
+
+        fn runtime(&self, ssh: OsString, sftp: OsString) -> SshRuntime {
+            let mut runtime = SshRuntime::new(config(&self.dir.path().join("id_test")));
+            runtime.ssh_program = ssh;
+            runtime.sftp_program = sftp;
+            runtime
+        }
+
+        fn workspace(&self) -> PathBuf {
+            self.dir.path().join("workspace")
+        }
+
+        fn log(&self, name: &str) -> String {
+            std::fs::read_to_string(self.dir.path().join(name)).unwrap()
+        }
+    }
+
+    #[test]
+    fn ssh_runtime_name_and_capabilities() {
+        let runtime = SshRuntime::new(SshRuntimeConfig::default());
+        assert_eq!(runtime.name(), "ssh");
+        assert!(runtime.has_shell_access());
+        assert!(runtime.remote_files().is_some());
+    }
+
+    #[test]
+    fn shell_quote_escapes_single_quotes() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+
+    #[test]
+    fn sftp_quote_escapes_and_rejects_newlines() {
+        assert_eq!(
+            sftp_quote(Path::new(r#"a "b"\c"#)).unwrap(),
+            r#""a \"b\"\\c""#
+        );
+        assert!(sftp_quote(Path::new("a\nrm b")).is_err());
+    }
+
+    #[test]
+    fn remote_workspace_defaults_to_local_path() {
+        let tmp = TempDir::new().unwrap();
+        let mut runtime = SshRuntime::new(config(tmp.path()));
+        let local = Path::new("/home/me/.zeroclaw/workspace");
+        assert_eq!(runtime.remote_workspace(local), local);
+
+        runtime.config.remote_workspace = Some("~/agent".into());
+        assert_eq!(runtime.remote_workspace(local), Path::new("agent"));
+        runtime.config.remote_workspace = Some("/srv/agent".into());
+        assert_eq!(runtime.remote_workspace(local), Path::new("/srv/agent"));
+    }
+
+    #[test]
+    fn missing_key_is_a_clear_error() {
+        let tmp = TempDir::new().unwrap();
+        let runtime = SshRuntime::new(config(&tmp.path().join("missing_key")));
+        let err = runtime.check_available().unwrap_err().to_string();
+        assert!(err.contains("SSH key not found"), "{err}");
+        assert!(runtime
+            .build_shell_command("ls", tmp.path())
+            .unwrap_err()
+            .to_string()
+            .contains("SSH key not found"));
+    }
+
+    #[test]
+    fn missing_host_is_a_clear_error() {
+        let tmp = TempDir::new().unwrap();
+        let key = tmp.path().join("id_test");
+        std::fs::write(&key, "key").unwrap();
+        let mut cfg = config(&key);
+        cfg.host = String::new();
+        let err = SshRuntime::new(cfg)
+            .build_shell_command("ls", tmp.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("runtime.ssh.host is not set"), "{err}");
+    }
+
+    #[test]
+    fn build_shell_command_marshals_options_and_script() {
+        let tmp = TempDir::new().unwrap();
+        let key = tmp.path().join("id_test");
+        std::fs::write(&key, "key").unwrap();
+        let mut cfg = config(&key);
+        cfg.port = 2222;
+        cfg.remote_workspace = Some("/srv/it's here".into());
+        let command = SshRuntime::new(cfg)
+            .build_shell_command("ls -la | head", tmp.path())
+            .unwrap();
+        let args: Vec<String> = command
+            .as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(command.as_std().get_program(), "ssh");
+        assert!(args.contains(&format!("IdentityFile={}", key.display())));
+        assert!(args.contains(&"BatchMode=yes".to_string()));
+        assert!(args.contains(&"Port=2222".to_string()));
+        assert!(args.contains(&"ControlMaster=auto".to_string()));
+        assert!(args.iter().any(|a| a.starts_with("ControlPersist=")));
+        let n = args.len();
+        assert_eq!(args[n - 2], "agent@build.example.com");
+        assert_eq!(args[n - 1], r"cd '/srv/it'\''s here' && ls -la | head");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn shell_command_output_is_captured() {
+        let fake = FakeTransport::new();
+        let runtime = fake.runtime(fake.ssh(), fake.sftp());
+        std::fs::write(fake.workspace().join("marker.txt"), "").unwrap();
+
+        let output = runtime
+            .build_shell_command("ls; echo oops >&2; exit 3", &fake.workspace())
+            .unwrap()
+            .output()
+            .await
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "marker.txt\n");
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "oops\n");
+        assert_eq!(output.status.code(), Some(3));
+        assert!(fake.log("ssh.args").contains("agent@build.example.com"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn files_round_trip_over_sftp() {
+        let fake = FakeTransport::new();
+        let runtime = fake.runtime(fake.ssh(), fake.sftp());
+        let workspace = fake.workspace();
+
+        runtime
+            .write_file(&workspace, "notes/my file.txt", b"hello")
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("notes/my file.txt")).unwrap(),
+            "hello"
+        );
+        let batch = fake.log("sftp.batch");
+        assert!(
+            batch.starts_with(&format!("-mkdir \"{}/notes\"\n", workspace.display())),
+            "{batch}"
+        );
+        assert!(fake.log("sftp.args").contains("\n-b\n-\n"));
+
+        let contents = runtime
+            .read_file(&workspace, "notes/my file.txt")
+            .await
+            .unwrap();
+        assert_eq!(contents, b"hello");
+        assert!(fake.log("sftp.batch").starts_with("get "));
+
+        let err = runtime.read_file(&workspace, "missing.txt").await;
+        assert!(err.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sftp_reconnects_once_after_dropped_connection() {
+        let fake = FakeTransport::new();
+        let flaky = fake.script(
+            "flaky-sftp",
+            r#"cat > /dev/null
+if [ ! -f "$LOG/dropped" ]; then
+  touch "$LOG/dropped"
+  echo "Connection closed" >&2
+  exit 255
+fi
+echo ok > "$LOG/reconnected"
+"#,
+        );
+        let ssh = fake.script("logging-ssh", "printf '%s\\n' \"$@\" > \"$LOG/ssh.args\"\n");
+        let runtime = fake.runtime(ssh, flaky);
+
+        runtime
+            .write_file(&fake.workspace(), "a.txt", b"x")
+            .await
+            .unwrap();
+        assert!(fake.dir.path().join("reconnected").exists());
+        assert!(fake.log("ssh.args").contains("-O\nexit\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn auth_failure_is_a_clear_error() {
+        let fake = FakeTransport::new();
+        let ssh = fake.script(
+            "denied-ssh",
+            "echo 'agent@build.example.com: Permission denied (publickey).' >&2\nexit 255\n",
+        );
+        let runtime = fake.runtime(ssh, fake.sftp());
+        let err = runtime.check_available().unwrap_err().to_string();
+        assert!(err.contains("SSH authentication failed"), "{err}");
+        assert!(err.contains("id_test"), "{err}");
+    }
+}
@@ -1,5 +1,24 @@
+use async_trait::async_trait;
 use std::path::{Path, PathBuf};
 
+/// File access for runtimes whose workspace is not on the local filesystem.
+/// Paths are relative to the workspace and already checked by the
+/// `SecurityPolicy`.
+#[async_trait]
+pub trait RemoteFiles: Send + Sync {
+    /// Read the whole file at `path` inside the workspace.
+    async fn read_file(&self, workspace_dir: &Path, path: &str) -> anyhow::Result<Vec<u8>>;
+
+    /// Create or replace the file at `path` inside the workspace, creating
+    /// missing parent directories.
+    async fn write_file(
+        &self,
+        workspace_dir: &Path,
+        path: &str,
+        contents: &[u8],
+    ) -> anyhow::Result<()>;
+}
+
 /// Runtime adapter — abstracts platform differences so the same agent
 /// code runs on native, Docker, Cloudflare Workers, Raspberry Pi, etc.
 pub trait RuntimeAdapter: Send + Sync {
@@ -23,6 +42,18 @@ pub trait RuntimeAdapter: Send + Sync {
         0
     }
 
+    /// Verify the runtime can actually execute commands. Called once at
+    /// startup so a missing dependency fails fast with a clear error.
+    fn check_available(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Remote file access, or `None` when file tools should use the local
+    /// filesystem directly.
+    fn remote_files(&self) -> Option<&dyn RemoteFiles> {
+        None
+    }
+
     /// Build a shell command process for this runtime.
     fn build_shell_command(
         &self,
@@ -0,0 +1,326 @@
+// Append-only audit log of tool executions.
+//
+// Each tool call becomes one JSON line with the timestamp, tool name,
+// arguments, and outcome. Every line carries the SHA-256 hash of the line
+// before it (`prev_hash`) and its own hash over that link plus its content,
+// so deleting, reordering or editing an entry breaks the chain from that
+// point on. `verify_chain` walks a log and reports the first broken link.
+//
+// Arguments whose key looks like a secret (`password`, `token`, ...) are
+// masked before they are written; the list comes from
+// `security.audit_redact_fields`.
+//
+// Auditing never fails a tool call: if the log cannot be written the error is
+// logged as a warning and the agent carries on.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// `prev_hash` of the first entry in a log.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Replacement for redacted argument values.
+const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments that mark an argument as secret unless configured otherwise.
+pub const DEFAULT_REDACT_FIELDS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "authorization",
+];
+
+/// Everything in an entry except its own hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuditRecord {
+    timestamp: String,
+    tool: String,
+    arguments: Value,
+    success: bool,
+    error: Option<String>,
+    prev_hash: String,
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuditEntry {
+    #[serde(flatten)]
+    record: AuditRecord,
+    hash: String,
+}
+
+impl AuditRecord {
+    fn hash(&self) -> Result<String> {
+        let body = serde_json::to_string(self)?;
+        Ok(hex::encode(Sha256::digest(body.as_bytes())))
+    }
+}
+
+/// Hash-chained JSON-lines log of every tool call.
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    redact_fields: Vec<String>,
+    /// Hash of the last entry written; the next entry links to it.
+    last_hash: Mutex<String>,
+}
+
+impl AuditLog {
+    /// Open (or prepare to create) the log at `path`, continuing the chain
+    /// from its last entry. `redact_fields` are matched case-insensitively
+    /// as substrings of argument keys.
+    pub fn open(path: PathBuf, redact_fields: Vec<String>) -> Self {
+        let last_hash = match last_entry_hash(&path) {
+            Ok(hash) => hash,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), "Audit log is unreadable, starting a new chain: {e:#}");
+                GENESIS_HASH.to_string()
+            }
+        };
+        Self {
+            path,
+            redact_fields: redact_fields
+                .into_iter()
+                .map(|field| field.to_ascii_lowercase())
+                .collect(),
+            last_hash: Mutex::new(last_hash),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry for one tool call, with secret fields and text
+    /// matching the redaction patterns masked. Failures are logged, never
+    /// returned.
+    pub fn record(&self, tool: &str, arguments: &Value, success: bool, error: Option<&str>) {
+        let redactor = super::redact::active();
+        let mut last_hash = self
+            .last_hash
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let record = AuditRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool: tool.to_string(),
+            arguments: redactor.redact_json(&redact_secrets(arguments, &self.redact_fields)),
+            success,
+            error: error.map(|e| redactor.redact(e).into_owned()),
+            prev_hash: last_hash.clone(),
+        };
+        match self.append(record) {
+            Ok(hash) => *last_hash = hash,
+            Err(e) => tracing::warn!(
+                path = %self.path.display(),
+                tool,
+                "Failed to write audit log entry: {e:#}"
+            ),
+        }
+    }
+
+    fn append(&self, record: AuditRecord) -> Result<String> {
+        let hash = record.hash()?;
+        let line = serde_json::to_string(&AuditEntry {
+            record,
+            hash: hash.clone(),
+        })?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{line}")?;
+        Ok(hash)
+    }
+}
+
+/// Copy of `value` with every field whose key contains one of `fields`
+/// (lowercase) masked, at any depth.
+pub fn redact_secrets(value: &Value, fields: &[impl AsRef<str>]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let key_lower = key.to_ascii_lowercase();
+                    let value = if fields
+                        .iter()
+                        .any(|field| key_lower.contains(field.as_ref()))
+                    {
+                        Value::from(REDACTED)
+                    } else {
+                        redact_secrets(value, fields)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| redact_secrets(item, fields))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Hash of the last entry in the log at `path`, or the genesis hash if the
+/// log does not exist yet.
+fn last_entry_hash(path: &Path) -> Result<String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(GENESIS_HASH.into()),
+        Err(e) => return Err(e.into()),
+    };
+    match content.lines().rev().find(|line| !line.trim().is_empty()) {
+        Some(line) => {
+            let entry: AuditEntry =
+                serde_json::from_str(line).context("last audit entry is malformed")?;
+            Ok(entry.hash)
+        }
+        None => Ok(GENESIS_HASH.into()),
+    }
+}
+
+/// Check that every entry in the log at `path` hashes correctly and links to
+/// the one before it. Returns the number of entries.
+pub fn verify_chain(path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read audit log {}", path.display()))?;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    let mut count = 0;
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line_no = index + 1;
+        let entry: AuditEntry = serde_json::from_str(line)
+            .with_context(|| format!("Malformed audit entry on line {line_no}"))?;
+        if entry.record.prev_hash != prev_hash {
+            bail!("Audit chain broken on line {line_no}: an earlier entry is missing or altered");
+        }
+        if entry.record.hash()? != entry.hash {
+            bail!("Audit entry on line {line_no} does not match its hash");
+        }
+        prev_hash = entry.hash;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn read_entries(path: &Path) -> Vec<AuditEntry> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn hash_chain_links_entries() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        let log = AuditLog::open(path.clone(), vec![]);
+        log.record("shell", &json!({"command": "ls"}), true, None);
+        log.record(
+            "file_read",
+            &json!({"path": "a.txt"}),
+            false,
+            Some("not found"),
+        );
+
+        // Reopening continues the chain from the last entry
+        let log = AuditLog::open(path.clone(), vec![]);
+        log.record("memory_store", &json!({"key": "k"}), true, None);
+
+        let entries = read_entries(&path);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].record.prev_hash, GENESIS_HASH);
+        assert_eq!(entries[1].record.prev_hash, entries[0].hash);
+        assert_eq!(entries[2].record.prev_hash, entries[1].hash);
+        assert_eq!(entries[1].record.error.as_deref(), Some("not found"));
+        assert_eq!(verify_chain(&path).unwrap(), 3);
+    }
+
+    #[test]
+    fn verify_chain_detects_deleted_and_edited_entries() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        let log = AuditLog::open(path.clone(), vec![]);
+        for i in 0..3 {
+            log.record(
+                "shell",
+                &json!({"command": format!("echo {i}")}),
+                true,
+                None,
+            );
+        }
+        let lines: Vec<String> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        let err = verify_chain(&path).unwrap_err().to_string();
+        assert!(err.contains("line 2"), "{err}");
+
+        let edited = lines[1].replace("echo 1", "echo 9");
+        fs::write(&path, format!("{}\n{edited}\n{}\n", lines[0], lines[2])).unwrap();
+        let err = verify_chain(&path).unwrap_err().to_string();
+        assert!(err.contains("does not match its hash"), "{err}");
+    }
+
+    #[test]
+    fn redacts_secret_looking_fields() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("audit.jsonl");
+        let log = AuditLog::open(path.clone(), vec!["password".into(), "token".into()]);
+        log.record(
+            "http_request",
+            &json!({
+                "url": "https://example.com",
+                "DB_Password": "hunter2",
+                "headers": {"X-Auth-Token": "abc", "Accept": "text/plain"},
+            }),
+            true,
+            None,
+        );
+
+        let args = &read_entries(&path)[0].record.arguments;
+        assert_eq!(args["DB_Password"], REDACTED);
+        assert_eq!(args["headers"]["X-Auth-Token"], REDACTED);
+        assert_eq!(args["headers"]["Accept"], "text/plain");
+        assert_eq!(args["url"], "https://example.com");
+        assert!(!fs::read_to_string(&path).unwrap().contains("hunter2"));
+    }
+
+    #[test]
+    fn unwritable_log_does_not_panic() {
+        let tmp = TempDir::new().unwrap();
+        // A directory where the file should be makes every append fail
+        let log = AuditLog::open(tmp.path().to_path_buf(), vec![]);
+        log.record("shell", &json!({"command": "ls"}), true, None);
+        assert_eq!(
+            *log.last_hash.lock().unwrap(),
+            GENESIS_HASH,
+            "a failed write must not advance the chain"
+        );
+    }
+}
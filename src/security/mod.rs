@@ -1,7 +1,11 @@
+pub mod audit;
+pub mod output_filter;
 pub mod pairing;
 pub mod policy;
+pub mod redact;
 pub mod secrets;
 
+pub use audit::AuditLog;
 #[allow(unused_imports)]
 pub use pairing::PairingGuard;
 pub use policy::{AutonomyLevel, SecurityPolicy};
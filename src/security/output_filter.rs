@@ -0,0 +1,233 @@
+use crate::config::{OutputFilterAction, SecurityConfig};
+use anyhow::{Context, Result};
+use regex_automata::meta::Regex;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// Put in place of filtered text in `redact` mode.
+pub const FILTERED: &str = "[FILTERED]";
+
+/// Streamed text held back (in bytes) so a filtered term split across
+/// chunks is still caught; longer matches may get through when split.
+const STREAM_HOLDBACK: usize = 256;
+
+/// A response withheld because it matched one of `security.output_filters`.
+#[derive(Debug)]
+pub struct OutputBlocked {
+    /// Index of the matching pattern in `security.output_filters`
+    pub index: usize,
+}
+
+impl fmt::Display for OutputBlocked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "response withheld: it matched security.output_filters[{}]",
+            self.index
+        )
+    }
+}
+
+impl std::error::Error for OutputBlocked {}
+
+/// Keeps configured terms (internal hostnames, profanity, a leaked prompt)
+/// out of what the agent shows the user, by masking them or by withholding
+/// the whole response.
+#[derive(Debug)]
+pub struct OutputFilter {
+    regex: Regex,
+    action: OutputFilterAction,
+}
+
+impl OutputFilter {
+    /// Filter for `patterns`; fails on an invalid pattern.
+    pub fn new(patterns: &[String], action: OutputFilterAction) -> Result<Self> {
+        for pattern in patterns {
+            Regex::new(pattern).with_context(|| format!("Invalid output filter '{pattern}'"))?;
+        }
+        Ok(Self {
+            regex: Regex::new_many(patterns).context("Invalid output filters")?,
+            action,
+        })
+    }
+
+    /// The filter configured in `[security]`, or `None` without any
+    /// `output_filters`.
+    pub fn from_config(config: &SecurityConfig) -> Result<Option<Self>> {
+        if config.output_filters.is_empty() {
+            return Ok(None);
+        }
+        Self::new(&config.output_filters, config.output_filter_action).map(Some)
+    }
+
+    /// `text` with every match replaced by `[FILTERED]`, or `OutputBlocked`
+    /// in `block` mode.
+    pub fn apply<'a>(&self, text: &'a str) -> Result<Cow<'a, str>, OutputBlocked> {
+        self.check_blocked(text)?;
+        let mut matches = self
+            .regex
+            .find_iter(text)
+            .filter(|found| !found.is_empty())
+            .peekable();
+        if matches.peek().is_none() {
+            return Ok(Cow::Borrowed(text));
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut last = 0;
+        for found in matches {
+            out.push_str(&text[last..found.start()]);
+            out.push_str(FILTERED);
+            last = found.end();
+        }
+        out.push_str(&text[last..]);
+        Ok(Cow::Owned(out))
+    }
+
+    /// A filter for text that arrives in chunks.
+    pub fn stream(&self) -> StreamFilter<'_> {
+        StreamFilter {
+            filter: self,
+            pending: String::new(),
+        }
+    }
+
+    fn check_blocked(&self, text: &str) -> Result<(), OutputBlocked> {
+        if self.action != OutputFilterAction::Block {
+            return Ok(());
+        }
+        match self.regex.find_iter(text).find(|found| !found.is_empty()) {
+            Some(found) => Err(OutputBlocked {
+                index: found.pattern().as_usize(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Applies an `OutputFilter` to a streamed response. The last few hundred
+/// bytes are held back until more text (or the end) arrives, so a term
+/// split across two chunks is filtered like any other.
+pub struct StreamFilter<'a> {
+    filter: &'a OutputFilter,
+    pending: String,
+}
+
+impl StreamFilter<'_> {
+    /// Add a chunk and return the text that is safe to print so far.
+    pub fn push(&mut self, chunk: &str) -> Result<String, OutputBlocked> {
+        self.pending.push_str(chunk);
+        let cut = self
+            .pending
+            .floor_char_boundary(self.pending.len().saturating_sub(STREAM_HOLDBACK));
+        self.release(cut)
+    }
+
+    /// Return whatever is still held back once the stream ends.
+    pub fn finish(&mut self) -> Result<String, OutputBlocked> {
+        self.release(self.pending.len())
+    }
+
+    /// Filter and hand out `pending[..cut]`, moving `cut` past a match that
+    /// straddles it.
+    fn release(&mut self, mut cut: usize) -> Result<String, OutputBlocked> {
+        self.filter.check_blocked(&self.pending)?;
+        let mut out = String::with_capacity(cut);
+        let mut last = 0;
+        for found in self.filter.regex.find_iter(&self.pending) {
+            if found.start() >= cut {
+                break;
+            }
+            if found.is_empty() {
+                continue;
+            }
+            out.push_str(&self.pending[last..found.start()]);
+            out.push_str(FILTERED);
+            last = found.end();
+            cut = cut.max(last);
+        }
+        out.push_str(&self.pending[last..cut]);
+        self.pending.drain(..cut);
+        Ok(out)
+    }
+}
+
+static ACTIVE: LazyLock<RwLock<Option<Arc<OutputFilter>>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Make `filter` the one applied to everything the agent shows the user.
+pub fn install(filter: OutputFilter) {
+    *ACTIVE
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Arc::new(filter));
+}
+
+/// The process-wide filter, if `security.output_filters` are configured.
+pub fn active() -> Option<Arc<OutputFilter>> {
+    ACTIVE
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+/// `text` as the process-wide filter lets it through; unchanged without one.
+pub fn screen(text: String) -> Result<String, OutputBlocked> {
+    let Some(filter) = active() else {
+        return Ok(text);
+    };
+    match filter.apply(&text)? {
+        Cow::Borrowed(_) => Ok(text),
+        Cow::Owned(filtered) => Ok(filtered),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(action: OutputFilterAction) -> OutputFilter {
+        OutputFilter::new(&[r"db-internal\.corp".into(), "(?i)darn".into()], action).unwrap()
+    }
+
+    #[test]
+    fn redact_mode_masks_filtered_terms() {
+        let filter = filter(OutputFilterAction::Redact);
+        assert_eq!(
+            filter
+                .apply("Connect to db-internal.corp. Darn, it's down.")
+                .unwrap(),
+            "Connect to [FILTERED]. [FILTERED], it's down."
+        );
+        assert!(matches!(filter.apply("All good."), Ok(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn block_mode_withholds_the_response() {
+        let filter = filter(OutputFilterAction::Block);
+        let blocked = filter.apply("Connect to db-internal.corp").unwrap_err();
+        assert_eq!(blocked.index, 0);
+        assert_eq!(
+            blocked.to_string(),
+            "response withheld: it matched security.output_filters[0]"
+        );
+        assert_eq!(filter.apply("All good.").unwrap(), "All good.");
+    }
+
+    #[test]
+    fn terms_split_across_stream_chunks_are_caught() {
+        let filter = filter(OutputFilterAction::Redact);
+        let mut stream = filter.stream();
+        let long_intro = "x".repeat(STREAM_HOLDBACK * 2);
+        let mut out = stream.push(&long_intro).unwrap();
+        out.push_str(&stream.push(" connect to db-int").unwrap());
+        assert!(!out.contains("db-int"), "printed too early: {out}");
+        out.push_str(&stream.push("ernal.corp now").unwrap());
+        out.push_str(&stream.finish().unwrap());
+        assert_eq!(out, format!("{long_intro} connect to [FILTERED] now"));
+
+        let filter = self::filter(OutputFilterAction::Block);
+        let mut stream = filter.stream();
+        let printed = stream.push("connect to db-int").unwrap();
+        assert!(printed.is_empty());
+        assert!(stream.push("ernal.corp now").is_err());
+    }
+}
@@ -1,6 +1,9 @@
+use super::AuditLog;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
 /// How much autonomy the agent has
@@ -85,11 +88,17 @@ pub struct SecurityPolicy {
     pub workspace_dir: PathBuf,
     pub workspace_only: bool,
     pub allowed_commands: Vec<String>,
+    pub denied_commands: Vec<String>,
     pub forbidden_paths: Vec<String>,
+    pub extra_allowed_dirs: Vec<PathBuf>,
+    pub allowed_hosts: Vec<String>,
+    pub denied_hosts: Vec<String>,
     pub max_actions_per_hour: u32,
     pub max_cost_per_day_cents: u32,
     pub require_approval_for_medium_risk: bool,
     pub block_high_risk_commands: bool,
+    /// Where every tool call is recorded, if auditing is enabled.
+    pub audit: Option<Arc<AuditLog>>,
     pub tracker: ActionTracker,
 }
 
@@ -113,6 +122,7 @@ impl Default for SecurityPolicy {
                 "head".into(),
                 "tail".into(),
             ],
+            denied_commands: Vec::new(),
             forbidden_paths: vec![
                 // System directories (blocked even when workspace_only=false)
                 "/etc".into(),
@@ -135,10 +145,14 @@ impl Default for SecurityPolicy {
                 "~/.aws".into(),
                 "~/.config".into(),
             ],
+            extra_allowed_dirs: Vec::new(),
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
             max_actions_per_hour: 20,
             max_cost_per_day_cents: 500,
             require_approval_for_medium_risk: true,
             block_high_risk_commands: true,
+            audit: None,
             tracker: ActionTracker::new(),
         }
     }
@@ -167,6 +181,86 @@ fn skip_env_assignments(s: &str) -> &str {
     }
 }
 
+/// Match a host against an `allowed_hosts`/`denied_hosts` pattern:
+/// `example.com` (exact), `*.example.com` (any subdomain), optionally
+/// followed by `:port`.
+fn host_matches(pattern: &str, host: &str, port: u16) -> bool {
+    let (pattern_host, pattern_port) = match pattern.rsplit_once(':') {
+        Some((h, p)) if !h.ends_with(':') => match p.parse::<u16>() {
+            Ok(p) => (h, Some(p)),
+            Err(_) => (pattern, None),
+        },
+        _ => (pattern, None),
+    };
+    if pattern_port.is_some_and(|p| p != port) {
+        return false;
+    }
+
+    let pattern_host = pattern_host.to_ascii_lowercase();
+    let host = host.to_ascii_lowercase();
+    match pattern_host.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|sub| sub.ends_with('.')),
+        None => pattern_host == host,
+    }
+}
+
+/// Loopback, private, link-local and other addresses that must not be
+/// reachable by default (SSRF against local services and cloud metadata).
+fn is_private_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || a == 0
+                // 100.64.0.0/10 carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_private_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                // fc00::/7 unique local, fe80::/10 link-local
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+/// Program names (basename, env assignments skipped) of each sub-command
+/// in a pipeline or `&&`/`||`/`;`/newline-separated list.
+fn command_programs(command: &str) -> Vec<String> {
+    let mut normalized = command.to_string();
+    for sep in ["&&", "||"] {
+        normalized = normalized.replace(sep, "\x00");
+    }
+    for sep in ['\n', ';', '|'] {
+        normalized = normalized.replace(sep, "\x00");
+    }
+
+    normalized
+        .split('\x00')
+        .filter_map(|segment| {
+            skip_env_assignments(segment.trim())
+                .split_whitespace()
+                .next()
+                .and_then(|word| word.rsplit('/').next())
+                .filter(|base| !base.is_empty())
+                .map(str::to_string)
+        })
+        .collect()
+}
+
 impl SecurityPolicy {
     /// Classify command risk. Any high-risk segment marks the whole command high.
     pub fn command_risk_level(&self, command: &str) -> CommandRiskLevel {
@@ -295,7 +389,11 @@ impl SecurityPolicy {
         approved: bool,
     ) -> Result<CommandRiskLevel, String> {
         if !self.is_command_allowed(command) {
-            return Err(format!("Command not allowed by security policy: {command}"));
+            let reason = self.check_command(command).err();
+            return Err(format!(
+                "Command not allowed by security policy: {}",
+                reason.as_deref().unwrap_or(command)
+            ));
         }
 
         let risk = self.command_risk_level(command);
@@ -325,6 +423,72 @@ impl SecurityPolicy {
         Ok(risk)
     }
 
+    /// Whether a tool call needs the user's go-ahead before it runs.
+    ///
+    /// Only supervised autonomy asks: full autonomy runs everything and
+    /// read-only is refused by the tools themselves. Shell commands ask by
+    /// risk (medium only with `require_approval_for_medium_risk`, high only
+    /// when not blocked outright), file writes always ask, and HTTP requests
+    /// ask unless they only read.
+    pub fn requires_confirmation(&self, tool_name: &str, args: &serde_json::Value) -> bool {
+        if self.autonomy != AutonomyLevel::Supervised {
+            return false;
+        }
+
+        match tool_name {
+            "shell" => {
+                let command = args
+                    .get("command")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default();
+                match self.command_risk_level(command) {
+                    CommandRiskLevel::High => !self.block_high_risk_commands,
+                    CommandRiskLevel::Medium => self.require_approval_for_medium_risk,
+                    CommandRiskLevel::Low => false,
+                }
+            }
+            "file_write" | "file_edit" | "file_append" | "make_directory" => true,
+            "http_request" => {
+                let method = args
+                    .get("method")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("GET");
+                !["GET", "HEAD", "OPTIONS"]
+                    .iter()
+                    .any(|safe| method.eq_ignore_ascii_case(safe))
+            }
+            _ => false,
+        }
+    }
+
+    /// Check every program in a command against the deny and allow lists.
+    ///
+    /// Matches whole program names, so `rm` in `denied_commands` does not
+    /// catch `format`. Each pipeline segment is checked on its own, and a
+    /// denied program is refused even if it is also allowlisted.
+    pub fn check_command(&self, command: &str) -> Result<(), String> {
+        let programs = command_programs(command);
+        if programs.is_empty() {
+            return Err("empty command".into());
+        }
+
+        if let Some(denied) = programs
+            .iter()
+            .find(|program| self.denied_commands.contains(program))
+        {
+            return Err(format!("'{denied}' is in denied_commands"));
+        }
+
+        if let Some(unlisted) = programs
+            .iter()
+            .find(|program| !self.allowed_commands.contains(program))
+        {
+            return Err(format!("'{unlisted}' is not in allowed_commands"));
+        }
+
+        Ok(())
+    }
+
     /// Check if a shell command is allowed.
     ///
     /// Validates the **entire** command string, not just the first word:
@@ -348,53 +512,8 @@ impl SecurityPolicy {
             return false;
         }
 
-        // Split on command separators and validate each sub-command.
-        // We collect segments by scanning for separator characters.
-        let mut normalized = command.to_string();
-        for sep in ["&&", "||"] {
-            normalized = normalized.replace(sep, "\x00");
-        }
-        for sep in ['\n', ';', '|'] {
-            normalized = normalized.replace(sep, "\x00");
-        }
-
-        for segment in normalized.split('\x00') {
-            let segment = segment.trim();
-            if segment.is_empty() {
-                continue;
-            }
-
-            // Strip leading env var assignments (e.g. FOO=bar cmd)
-            let cmd_part = skip_env_assignments(segment);
-
-            let base_cmd = cmd_part
-                .split_whitespace()
-                .next()
-                .unwrap_or("")
-                .rsplit('/')
-                .next()
-                .unwrap_or("");
-
-            if base_cmd.is_empty() {
-                continue;
-            }
-
-            if !self
-                .allowed_commands
-                .iter()
-                .any(|allowed| allowed == base_cmd)
-            {
-                return false;
-            }
-        }
-
-        // At least one command must be present
-        let has_cmd = normalized.split('\x00').any(|s| {
-            let s = skip_env_assignments(s.trim());
-            s.split_whitespace().next().is_some_and(|w| !w.is_empty())
-        });
-
-        has_cmd
+        // Every sub-command must pass the deny/allow lists
+        self.check_command(command).is_ok()
     }
 
     /// Check if a file path is allowed (no path traversal, within workspace)
@@ -429,13 +548,18 @@ impl SecurityPolicy {
             path.to_string()
         };
 
+        let expanded_path = Path::new(&expanded);
+        let extra_dir = self
+            .extra_allowed_dirs
+            .iter()
+            .find(|dir| expanded_path.starts_with(dir));
+
         // Block absolute paths when workspace_only is set
-        if self.workspace_only && Path::new(&expanded).is_absolute() {
+        if self.workspace_only && expanded_path.is_absolute() && extra_dir.is_none() {
             return false;
         }
 
         // Block forbidden paths using path-component-aware matching
-        let expanded_path = Path::new(&expanded);
         for forbidden in &self.forbidden_paths {
             let forbidden_expanded = if let Some(stripped) = forbidden.strip_prefix("~/") {
                 if let Some(home) = std::env::var("HOME").ok().map(PathBuf::from) {
@@ -447,7 +571,11 @@ impl SecurityPolicy {
                 forbidden.clone()
             };
             let forbidden_path = Path::new(&forbidden_expanded);
-            if expanded_path.starts_with(forbidden_path) {
+            // An extra allowed dir inside a forbidden one (e.g. a project
+            // under /home) is the more specific rule and wins
+            let carved_out = extra_dir
+                .is_some_and(|dir| dir.starts_with(forbidden_path) && dir != forbidden_path);
+            if expanded_path.starts_with(forbidden_path) && !carved_out {
                 return false;
             }
         }
@@ -455,17 +583,135 @@ impl SecurityPolicy {
         true
     }
 
-    /// Validate that a resolved path is still inside the workspace.
+    /// Validate that a resolved path is still inside the workspace (or one
+    /// of `extra_allowed_dirs`).
     /// Call this AFTER joining `workspace_dir` + relative path and canonicalizing.
     pub fn is_resolved_path_allowed(&self, resolved: &Path) -> bool {
-        // Must be under workspace_dir (prevents symlink escapes).
-        // Prefer canonical workspace root so `/a/../b` style config paths don't
+        // Must be under an allowed root (prevents symlink escapes).
+        // Prefer canonical roots so `/a/../b` style config paths don't
         // cause false positives or negatives.
-        let workspace_root = self
-            .workspace_dir
-            .canonicalize()
-            .unwrap_or_else(|_| self.workspace_dir.clone());
-        resolved.starts_with(workspace_root)
+        std::iter::once(&self.workspace_dir)
+            .chain(&self.extra_allowed_dirs)
+            .any(|root| {
+                let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+                resolved.starts_with(root)
+            })
+    }
+
+    /// Resolve a file tool path and confirm it stays inside the workspace
+    /// (or one of `extra_allowed_dirs`).
+    ///
+    /// Relative paths are joined onto `workspace_dir`. Symlinks are resolved,
+    /// so a link inside the workspace that points elsewhere is refused. A
+    /// path that does not exist yet is resolved through its nearest existing
+    /// ancestor, so files can be checked before they are created.
+    pub fn check_path(&self, requested: &Path) -> Result<PathBuf, String> {
+        if requested.to_string_lossy().contains('\0') {
+            return Err("Path contains a null byte".into());
+        }
+
+        let full = self.workspace_dir.join(requested);
+        let mut existing = full.as_path();
+        let mut missing = Vec::new();
+        let base = loop {
+            match existing.canonicalize() {
+                Ok(base) => break base,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    // `..` has no file name, so it can never be skipped over
+                    let (Some(parent), Some(name)) = (existing.parent(), existing.file_name())
+                    else {
+                        return Err(format!("Failed to resolve {}: {e}", full.display()));
+                    };
+                    missing.push(name);
+                    existing = parent;
+                }
+                Err(e) => return Err(format!("Failed to resolve {}: {e}", full.display())),
+            }
+        };
+        let resolved = missing
+            .into_iter()
+            .rev()
+            .fold(base, |path, name| path.join(name));
+
+        if !self.is_resolved_path_allowed(&resolved) {
+            return Err(format!(
+                "Resolved path escapes workspace: {}",
+                resolved.display()
+            ));
+        }
+        Ok(resolved)
+    }
+
+    /// Check a URL against the network egress policy.
+    ///
+    /// See [`SecurityPolicy::resolve_url`]; callers that go on to connect
+    /// should use that instead and pin the addresses it returns.
+    pub async fn check_url(&self, url: &str) -> Result<(), String> {
+        let url = Url::parse(url).map_err(|e| format!("Invalid URL: {e}"))?;
+        self.resolve_url(&url).await.map(|_| ())
+    }
+
+    /// Resolve a URL's host and check it against the egress policy.
+    ///
+    /// `denied_hosts` always wins. A non-empty `allowed_hosts` restricts
+    /// requests to matching hosts. Every resolved address is checked, so a
+    /// public name pointing at a private or metadata IP is refused unless
+    /// the host is explicitly allowlisted. Connect only to the returned
+    /// addresses so a second DNS answer cannot rebind the request.
+    pub async fn resolve_url(&self, url: &Url) -> Result<Vec<SocketAddr>, String> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| "URL has no host".to_string())?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| format!("URL has no port: {url}"))?;
+
+        if self
+            .denied_hosts
+            .iter()
+            .any(|pattern| host_matches(pattern, host, port))
+        {
+            return Err(format!("Host '{host}' is in denied_hosts"));
+        }
+        let allowlisted = self
+            .allowed_hosts
+            .iter()
+            .any(|pattern| host_matches(pattern, host, port));
+        if !self.allowed_hosts.is_empty() && !allowlisted {
+            return Err(format!("Host '{host}' is not in allowed_hosts"));
+        }
+
+        let lookup_host = host.trim_start_matches('[').trim_end_matches(']');
+        let addrs: Vec<SocketAddr> = tokio::net::lookup_host((lookup_host, port))
+            .await
+            .map_err(|e| format!("Failed to resolve host '{host}': {e}"))?
+            .collect();
+        if addrs.is_empty() {
+            return Err(format!("Host '{host}' did not resolve to any address"));
+        }
+
+        if !allowlisted {
+            if let Some(private) = addrs.iter().find(|addr| is_private_ip(addr.ip())) {
+                return Err(format!(
+                    "Blocked request to private address {} ({host}); add it to allowed_hosts to permit it",
+                    private.ip()
+                ));
+            }
+        }
+        Ok(addrs)
+    }
+
+    /// Record a finished tool call in the audit log, if one is configured.
+    pub fn audit_tool_call(
+        &self,
+        tool: &str,
+        arguments: &serde_json::Value,
+        success: bool,
+        error: Option<&str>,
+    ) {
+        if let Some(audit) = &self.audit {
+            audit.record(tool, arguments, success, error);
+        }
     }
 
     /// Check if autonomy level permits any action at all
@@ -488,18 +734,36 @@ impl SecurityPolicy {
     /// Build from config sections
     pub fn from_config(
         autonomy_config: &crate::config::AutonomyConfig,
+        security_config: &crate::config::SecurityConfig,
         workspace_dir: &Path,
     ) -> Self {
         Self {
             autonomy: autonomy_config.level,
             workspace_dir: workspace_dir.to_path_buf(),
             workspace_only: autonomy_config.workspace_only,
-            allowed_commands: autonomy_config.allowed_commands.clone(),
+            allowed_commands: security_config
+                .allowed_commands
+                .clone()
+                .unwrap_or_else(|| autonomy_config.allowed_commands.clone()),
+            denied_commands: security_config.denied_commands.clone(),
             forbidden_paths: autonomy_config.forbidden_paths.clone(),
+            extra_allowed_dirs: security_config
+                .extra_allowed_dirs
+                .iter()
+                .map(|dir| PathBuf::from(shellexpand::tilde(dir).as_ref()))
+                .collect(),
+            allowed_hosts: security_config.allowed_hosts.clone(),
+            denied_hosts: security_config.denied_hosts.clone(),
             max_actions_per_hour: autonomy_config.max_actions_per_hour,
             max_cost_per_day_cents: autonomy_config.max_cost_per_day_cents,
             require_approval_for_medium_risk: autonomy_config.require_approval_for_medium_risk,
             block_high_risk_commands: autonomy_config.block_high_risk_commands,
+            audit: security_config.audit_log.as_deref().map(|path| {
+                Arc::new(AuditLog::open(
+                    workspace_dir.join(shellexpand::tilde(path).as_ref()),
+                    security_config.audit_redact_fields.clone(),
+                ))
+            }),
             tracker: ActionTracker::new(),
         }
     }
@@ -644,6 +908,88 @@ mod tests {
         assert!(!p.is_command_allowed("echo hello"));
     }
 
+    // ── check_command ────────────────────────────────────────
+
+    #[test]
+    fn check_command_allows_listed_programs() {
+        let p = default_policy();
+        assert!(p.check_command("git status").is_ok());
+        assert!(p.check_command("FOO=bar /usr/bin/ls -la").is_ok());
+        let err = p.check_command("curl http://example.com").unwrap_err();
+        assert_eq!(err, "'curl' is not in allowed_commands");
+        assert_eq!(p.check_command("  ").unwrap_err(), "empty command");
+    }
+
+    #[test]
+    fn check_command_denies_listed_programs() {
+        let p = SecurityPolicy {
+            denied_commands: vec!["rm".into()],
+            ..SecurityPolicy::default()
+        };
+        assert_eq!(
+            p.check_command("rm -rf build").unwrap_err(),
+            "'rm' is in denied_commands"
+        );
+        assert!(!p.is_command_allowed("/bin/rm file"));
+    }
+
+    #[test]
+    fn check_command_deny_overrides_allow() {
+        let p = SecurityPolicy {
+            allowed_commands: vec!["git".into(), "rm".into()],
+            denied_commands: vec!["rm".into()],
+            ..SecurityPolicy::default()
+        };
+        assert!(p.check_command("git status").is_ok());
+        assert_eq!(
+            p.check_command("rm notes.txt").unwrap_err(),
+            "'rm' is in denied_commands"
+        );
+    }
+
+    #[test]
+    fn check_command_matches_program_names_not_substrings() {
+        let p = SecurityPolicy {
+            allowed_commands: vec!["format".into(), "echo".into()],
+            denied_commands: vec!["rm".into()],
+            ..SecurityPolicy::default()
+        };
+        assert!(p.check_command("format --check").is_ok());
+        assert!(p.check_command("echo rm").is_ok());
+    }
+
+    #[test]
+    fn check_command_checks_each_pipeline_segment() {
+        let p = SecurityPolicy {
+            denied_commands: vec!["rm".into()],
+            ..SecurityPolicy::default()
+        };
+        assert!(p.check_command("cat log.txt | grep error | wc -l").is_ok());
+        assert!(p.check_command("ls && rm -f a.txt").is_err());
+        assert!(p
+            .check_command("ls | xargs rm")
+            .is_err_and(|e| e.contains("xargs")));
+        assert!(p
+            .check_command("git pull; rm x")
+            .is_err_and(|e| e.contains("'rm'")));
+        assert!(p.check_command("echo ok || rm x").is_err());
+    }
+
+    #[test]
+    fn validate_command_reports_denied_program() {
+        let p = SecurityPolicy {
+            denied_commands: vec!["cat".into()],
+            ..SecurityPolicy::default()
+        };
+        let err = p
+            .validate_command_execution("cat secrets.txt", true)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            "Command not allowed by security policy: 'cat' is in denied_commands"
+        );
+    }
+
     #[test]
     fn command_risk_low_for_read_commands() {
         let p = default_policy();
@@ -709,6 +1055,57 @@ mod tests {
         assert!(result.unwrap_err().contains("high-risk"));
     }
 
+    // ── requires_confirmation ───────────────────────────────
+
+    #[test]
+    fn confirmation_follows_shell_command_risk() {
+        let p = SecurityPolicy {
+            block_high_risk_commands: false,
+            ..SecurityPolicy::default()
+        };
+        let shell = |command: &str| serde_json::json!({ "command": command });
+        assert!(!p.requires_confirmation("shell", &shell("ls -la")));
+        assert!(p.requires_confirmation("shell", &shell("git push")));
+        assert!(p.requires_confirmation("shell", &shell("rm -rf build")));
+
+        let relaxed = SecurityPolicy {
+            require_approval_for_medium_risk: false,
+            ..SecurityPolicy::default()
+        };
+        assert!(!relaxed.requires_confirmation("shell", &shell("git push")));
+        // Blocked outright, so there is nothing to confirm
+        assert!(!relaxed.requires_confirmation("shell", &shell("rm -rf build")));
+    }
+
+    #[test]
+    fn confirmation_for_writes_and_mutating_requests() {
+        let p = default_policy();
+        let args = serde_json::json!({ "path": "notes.md", "content": "x" });
+        assert!(p.requires_confirmation("file_write", &args));
+        assert!(p.requires_confirmation("file_edit", &args));
+        assert!(p.requires_confirmation("file_append", &args));
+        assert!(p.requires_confirmation("make_directory", &args));
+        assert!(!p.requires_confirmation("file_read", &args));
+        assert!(!p.requires_confirmation("memory_store", &args));
+
+        let request = |method: &str| serde_json::json!({ "method": method, "url": "https://x" });
+        assert!(!p.requires_confirmation("http_request", &request("get")));
+        assert!(p.requires_confirmation("http_request", &request("POST")));
+        assert!(p.requires_confirmation("http_request", &request("delete")));
+    }
+
+    #[test]
+    fn confirmation_only_in_supervised_mode() {
+        let args = serde_json::json!({ "path": "notes.md", "content": "x" });
+        for autonomy in [AutonomyLevel::Full, AutonomyLevel::ReadOnly] {
+            let p = SecurityPolicy {
+                autonomy,
+                ..SecurityPolicy::default()
+            };
+            assert!(!p.requires_confirmation("file_write", &args));
+        }
+    }
+
     // ── is_path_allowed ─────────────────────────────────────
 
     #[test]
@@ -741,6 +1138,9 @@ mod tests {
         let p = SecurityPolicy {
             workspace_only: false,
             forbidden_paths: vec![],
+            extra_allowed_dirs: vec![],
+            allowed_hosts: vec![],
+            denied_hosts: vec![],
             ..SecurityPolicy::default()
         };
         assert!(p.is_path_allowed("/tmp/file.txt"));
@@ -771,6 +1171,153 @@ mod tests {
         assert!(p.is_path_allowed(".env"));
     }
 
+    // ── check_path ──────────────────────────────────────────
+
+    fn workspace_policy(workspace: &Path) -> SecurityPolicy {
+        SecurityPolicy {
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }
+    }
+
+    #[test]
+    fn check_path_allows_workspace_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "hi").unwrap();
+        let p = workspace_policy(tmp.path());
+        let root = tmp.path().canonicalize().unwrap();
+
+        assert_eq!(
+            p.check_path(Path::new("notes.txt")).unwrap(),
+            root.join("notes.txt")
+        );
+        // Not created yet: resolved through the nearest existing ancestor
+        assert_eq!(
+            p.check_path(Path::new("new/dir/file.md")).unwrap(),
+            root.join("new/dir/file.md")
+        );
+    }
+
+    #[test]
+    fn check_path_denies_dotdot_escape() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("workspace")).unwrap();
+        let p = workspace_policy(&tmp.path().join("workspace"));
+
+        for path in ["../outside.txt", "../../etc/passwd", "new/../../escape.txt"] {
+            assert!(p.check_path(Path::new(path)).is_err(), "{path}");
+        }
+        assert!(p.check_path(Path::new("/etc/passwd")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn check_path_denies_symlink_escape() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        let outside = tmp.path().join("outside");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::create_dir(&outside).unwrap();
+        std::fs::write(outside.join("secret.txt"), "s").unwrap();
+        std::os::unix::fs::symlink(outside.join("secret.txt"), workspace.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(&outside, workspace.join("link_dir")).unwrap();
+        let p = workspace_policy(&workspace);
+
+        let err = p.check_path(Path::new("link.txt")).unwrap_err();
+        assert!(err.contains("escapes workspace"), "{err}");
+        assert!(p.check_path(Path::new("link_dir/new.txt")).is_err());
+    }
+
+    #[test]
+    fn check_path_allows_extra_dirs() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let workspace = tmp.path().join("workspace");
+        let shared = tmp.path().join("shared");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::create_dir(&shared).unwrap();
+        let p = SecurityPolicy {
+            extra_allowed_dirs: vec![shared.clone()],
+            ..workspace_policy(&workspace)
+        };
+
+        assert!(p.check_path(&shared.join("data.csv")).is_ok());
+        assert!(p.check_path(&tmp.path().join("other.txt")).is_err());
+    }
+
+    #[test]
+    fn extra_dirs_carve_out_of_forbidden_paths() {
+        let p = SecurityPolicy {
+            extra_allowed_dirs: vec![PathBuf::from("/home/user/project")],
+            ..SecurityPolicy::default()
+        };
+        assert!(p.is_path_allowed("/home/user/project/src/main.rs"));
+        assert!(!p.is_path_allowed("/home/user/.bashrc"));
+        assert!(!p.is_path_allowed("/etc/passwd"));
+    }
+
+    // ── check_url ───────────────────────────────────────────
+
+    #[tokio::test]
+    async fn check_url_allows_public_hosts() {
+        let p = default_policy();
+        assert!(p
+            .check_url("https://93.184.216.34/index.html")
+            .await
+            .is_ok());
+
+        let addrs = p
+            .resolve_url(&Url::parse("http://93.184.216.34:8080/").unwrap())
+            .await
+            .unwrap();
+        assert_eq!(addrs, vec!["93.184.216.34:8080".parse().unwrap()]);
+    }
+
+    #[tokio::test]
+    async fn check_url_denies_listed_hosts() {
+        let p = SecurityPolicy {
+            allowed_hosts: vec!["*.example.com".into(), "93.184.216.34".into()],
+            denied_hosts: vec!["admin.example.com".into(), "93.184.216.34:8080".into()],
+            ..SecurityPolicy::default()
+        };
+        let err = p.check_url("https://admin.example.com/").await.unwrap_err();
+        assert_eq!(err, "Host 'admin.example.com' is in denied_hosts");
+        assert!(p.check_url("http://93.184.216.34:8080/").await.is_err());
+        assert!(p.check_url("http://93.184.216.34/").await.is_ok());
+
+        let err = p.check_url("https://example.org/").await.unwrap_err();
+        assert!(err.contains("not in allowed_hosts"), "{err}");
+        // `*.` matches subdomains, not look-alike suffixes
+        assert!(p.check_url("https://evilexample.com/").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn check_url_blocks_private_and_metadata_addresses() {
+        let p = default_policy();
+        for url in [
+            "http://169.254.169.254/latest/meta-data/",
+            "http://127.0.0.1:8080/",
+            "http://10.0.0.5/",
+            "http://192.168.1.1/",
+            "http://[::1]/",
+            "http://[::ffff:169.254.169.254]/",
+            // Names are resolved, not just compared as strings
+            "http://localhost/",
+        ] {
+            let err = p.check_url(url).await.unwrap_err();
+            assert!(err.contains("private address"), "{url}: {err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn check_url_allowlist_opens_private_hosts() {
+        let p = SecurityPolicy {
+            allowed_hosts: vec!["127.0.0.1:8080".into()],
+            ..SecurityPolicy::default()
+        };
+        assert!(p.check_url("http://127.0.0.1:8080/health").await.is_ok());
+        assert!(p.check_url("http://127.0.0.1:9090/").await.is_err());
+    }
+
     // ── from_config ─────────────────────────────────────────
 
     #[test]
@@ -785,20 +1332,55 @@ mod tests {
             require_approval_for_medium_risk: false,
             block_high_risk_commands: false,
         };
+        let security_config = crate::config::SecurityConfig {
+            denied_commands: vec!["rm".into()],
+            extra_allowed_dirs: vec!["/srv/shared".into()],
+            allowed_hosts: vec!["api.github.com".into()],
+            denied_hosts: vec!["*.internal".into()],
+            audit_log: Some("logs/audit.jsonl".into()),
+            audit_redact_fields: vec!["password".into()],
+            ..crate::config::SecurityConfig::default()
+        };
         let workspace = PathBuf::from("/tmp/test-workspace");
-        let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
+        let policy = SecurityPolicy::from_config(&autonomy_config, &security_config, &workspace);
 
         assert_eq!(policy.autonomy, AutonomyLevel::Full);
         assert!(!policy.workspace_only);
         assert_eq!(policy.allowed_commands, vec!["docker"]);
+        assert_eq!(policy.denied_commands, vec!["rm"]);
         assert_eq!(policy.forbidden_paths, vec!["/secret"]);
+        assert_eq!(
+            policy.extra_allowed_dirs,
+            vec![PathBuf::from("/srv/shared")]
+        );
+        assert_eq!(policy.allowed_hosts, vec!["api.github.com"]);
+        assert_eq!(policy.denied_hosts, vec!["*.internal"]);
         assert_eq!(policy.max_actions_per_hour, 100);
         assert_eq!(policy.max_cost_per_day_cents, 1000);
         assert!(!policy.require_approval_for_medium_risk);
         assert!(!policy.block_high_risk_commands);
+        assert_eq!(
+            policy.audit.as_ref().map(|audit| audit.path()),
+            Some(Path::new("/tmp/test-workspace/logs/audit.jsonl"))
+        );
         assert_eq!(policy.workspace_dir, PathBuf::from("/tmp/test-workspace"));
     }
 
+    #[test]
+    fn security_allowed_commands_replace_autonomy_list() {
+        let security_config = crate::config::SecurityConfig {
+            allowed_commands: Some(vec!["git".into(), "ls".into()]),
+            ..crate::config::SecurityConfig::default()
+        };
+        let policy = SecurityPolicy::from_config(
+            &crate::config::AutonomyConfig::default(),
+            &security_config,
+            Path::new("/tmp/test"),
+        );
+        assert_eq!(policy.allowed_commands, vec!["git", "ls"]);
+        assert!(policy.check_command("cargo build").is_err());
+    }
+
     // ── Default policy ──────────────────────────────────────
 
     #[test]
@@ -1099,7 +1681,11 @@ mod tests {
             block_high_risk_commands: true,
         };
         let workspace = PathBuf::from("/tmp/test");
-        let policy = SecurityPolicy::from_config(&autonomy_config, &workspace);
+        let policy = SecurityPolicy::from_config(
+            &autonomy_config,
+            &crate::config::SecurityConfig::default(),
+            &workspace,
+        );
         assert_eq!(policy.tracker.count(), 0);
         assert!(!policy.is_rate_limited());
     }
@@ -1,3 +1,4 @@
+use crate::config::SkillsConfig;
 use anyhow::Result;
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,8 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime};
 
+pub mod watch;
+
 const OPEN_SKILLS_REPO_URL: &str = "https://github.com/besoeasy/open-skills";
 const OPEN_SKILLS_SYNC_MARKER: &str = ".zeroclaw-open-skills-sync";
 const OPEN_SKILLS_SYNC_INTERVAL_SECS: u64 = 60 * 60 * 24 * 7;
@@ -81,6 +84,49 @@ pub fn load_skills(workspace_dir: &Path) -> Vec<Skill> {
     skills
 }
 
+/// Load skills, keeping only those allowed by `[skills]` in config.
+pub fn load_configured_skills(workspace_dir: &Path, config: &SkillsConfig) -> Vec<Skill> {
+    filter_skills(load_skills(workspace_dir), config)
+}
+
+/// Apply `enabled`/`disabled` lists. An empty `enabled` list keeps everything;
+/// a skill listed in both is dropped. Names match either the skill's name or
+/// its directory (or, for single-file skills, file) name.
+pub fn filter_skills(skills: Vec<Skill>, config: &SkillsConfig) -> Vec<Skill> {
+    let listed = |names: &[String], skill: &Skill| names.iter().any(|n| skill_matches(skill, n));
+
+    for name in &config.enabled {
+        if !skills.iter().any(|skill| skill_matches(skill, name)) {
+            tracing::warn!("Enabled skill '{name}' was not found");
+        }
+    }
+
+    skills
+        .into_iter()
+        .filter(|skill| config.enabled.is_empty() || listed(&config.enabled, skill))
+        .filter(|skill| !listed(&config.disabled, skill))
+        .collect()
+}
+
+fn skill_matches(skill: &Skill, name: &str) -> bool {
+    let name = name.trim();
+    skill.name == name || skill_file_name(skill).is_some_and(|file| file == name)
+}
+
+/// Directory name for `<dir>/SKILL.{toml,md}`, file stem for single-file skills
+fn skill_file_name(skill: &Skill) -> Option<&str> {
+    let location = skill.location.as_deref()?;
+    let is_manifest = location
+        .file_name()
+        .is_some_and(|f| f == "SKILL.toml" || f == "SKILL.md");
+    let named = if is_manifest {
+        location.parent()?.file_name()
+    } else {
+        location.file_stem()
+    };
+    named?.to_str()
+}
+
 fn load_workspace_skills(workspace_dir: &Path) -> Vec<Skill> {
     let skills_dir = workspace_dir.join("skills");
     load_skills_from_directory(&skills_dir)
@@ -921,6 +967,88 @@ description = "Bare minimum"
         assert_eq!(skills.len(), 1);
         assert_eq!(skills[0].name, "from-toml"); // TOML takes priority
     }
+
+    fn skill_set(names: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for name in names {
+            let skill_dir = dir.path().join("skills").join(name);
+            fs::create_dir_all(&skill_dir).unwrap();
+            fs::write(skill_dir.join("SKILL.md"), format!("# {name}\n")).unwrap();
+        }
+        dir
+    }
+
+    fn names(skills: &[Skill]) -> Vec<String> {
+        let mut names: Vec<_> = skills.iter().map(|s| s.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn no_filter_loads_all_skills() {
+        let dir = skill_set(&["alpha", "beta"]);
+        let skills = load_configured_skills(dir.path(), &SkillsConfig::default());
+        assert_eq!(names(&skills), ["alpha", "beta"]);
+    }
+
+    #[test]
+    fn enabled_list_keeps_only_named_skills() {
+        let dir = skill_set(&["alpha", "beta", "gamma"]);
+        let config = SkillsConfig {
+            enabled: vec!["gamma".into(), "alpha".into(), "missing".into()],
+            disabled: Vec::new(),
+        };
+        let skills = load_configured_skills(dir.path(), &config);
+        assert_eq!(names(&skills), ["alpha", "gamma"]);
+    }
+
+    #[test]
+    fn disabled_list_drops_named_skills() {
+        let dir = skill_set(&["alpha", "beta", "gamma"]);
+        let config = SkillsConfig {
+            enabled: Vec::new(),
+            disabled: vec!["beta".into()],
+        };
+        let skills = load_configured_skills(dir.path(), &config);
+        assert_eq!(names(&skills), ["alpha", "gamma"]);
+    }
+
+    #[test]
+    fn disabled_wins_over_enabled() {
+        let dir = skill_set(&["alpha", "beta", "gamma"]);
+        let config = SkillsConfig {
+            enabled: vec!["alpha".into(), "beta".into()],
+            disabled: vec!["beta".into()],
+        };
+        let skills = load_configured_skills(dir.path(), &config);
+        assert_eq!(names(&skills), ["alpha"]);
+    }
+
+    #[test]
+    fn filter_matches_directory_name_of_toml_skill() {
+        let dir = tempfile::tempdir().unwrap();
+        let skill_dir = dir.path().join("skills").join("deploy");
+        fs::create_dir_all(&skill_dir).unwrap();
+        fs::write(
+            skill_dir.join("SKILL.toml"),
+            "[skill]\nname = \"Deploy Helper\"\ndescription = \"Ships builds\"\n",
+        )
+        .unwrap();
+
+        let by_dir = SkillsConfig {
+            enabled: vec!["deploy".into()],
+            disabled: Vec::new(),
+        };
+        assert_eq!(
+            names(&load_configured_skills(dir.path(), &by_dir)),
+            ["Deploy Helper"]
+        );
+        let by_name = SkillsConfig {
+            enabled: Vec::new(),
+            disabled: vec!["Deploy Helper".into()],
+        };
+        assert!(load_configured_skills(dir.path(), &by_name).is_empty());
+    }
 }
 
 #[cfg(test)]
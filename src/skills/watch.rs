@@ -0,0 +1,143 @@
+use super::skills_dir;
+use anyhow::{Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Quiet period after the last filesystem event before a reload fires, so an
+/// editor's save (often several writes and a rename) triggers one reload.
+pub const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Watches `workspace/skills` and calls `on_change` once per burst of edits.
+///
+/// Watching stops when the value is dropped: the OS watch is released and the
+/// debounce task is aborted, so no callback fires after that.
+pub struct SkillsWatcher {
+    _watcher: RecommendedWatcher,
+    task: JoinHandle<()>,
+}
+
+impl SkillsWatcher {
+    /// Start watching. The skills directory is created if it doesn't exist
+    /// yet so skills added later are noticed. Must be called inside a Tokio
+    /// runtime.
+    pub fn start<F>(workspace_dir: &Path, debounce: Duration, on_change: F) -> Result<Self>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let dir = skills_dir(workspace_dir);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create skills directory {}", dir.display()))?;
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+                Ok(event) if !event.kind.is_access() => {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Skills watcher error: {e}"),
+            })?;
+        watcher
+            .watch(&dir, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+        let task = tokio::spawn(async move {
+            while rx.recv().await.is_some() {
+                // Wait until the directory has been quiet for `debounce`
+                loop {
+                    match tokio::time::timeout(debounce, rx.recv()).await {
+                        Ok(Some(())) => {}
+                        Ok(None) => return,
+                        Err(_) => break,
+                    }
+                }
+                on_change();
+            }
+        });
+
+        Ok(Self {
+            _watcher: watcher,
+            task,
+        })
+    }
+}
+
+impl Drop for SkillsWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::build_system_prompt;
+    use crate::skills::load_skills;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn write_skill(workspace: &Path, name: &str, body: &str) {
+        let dir = skills_dir(workspace).join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("SKILL.md"), body).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reloads_after_skill_change_and_prompt_picks_it_up() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _watcher = SkillsWatcher::start(tmp.path(), Duration::from_millis(50), move || {
+            let _ = tx.send(());
+        })
+        .unwrap();
+
+        let before = build_system_prompt(tmp.path(), "model", &[], &load_skills(tmp.path()), None);
+        assert!(!before.contains("deploy-helper"));
+
+        write_skill(tmp.path(), "deploy-helper", "# Deploy helper\nShip it.");
+
+        tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("reload callback did not fire")
+            .unwrap();
+        let after = build_system_prompt(tmp.path(), "model", &[], &load_skills(tmp.path()), None);
+        assert!(after.contains("<name>deploy-helper</name>"), "{after}");
+    }
+
+    #[tokio::test]
+    async fn burst_of_writes_reloads_once() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reloads);
+        let _watcher = SkillsWatcher::start(tmp.path(), Duration::from_millis(200), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+
+        for i in 0..5 {
+            write_skill(tmp.path(), "draft", &format!("# Draft\nrevision {i}"));
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        assert_eq!(reloads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_watcher_stops_reloads() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reloads);
+        let watcher = SkillsWatcher::start(tmp.path(), Duration::from_millis(50), move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        drop(watcher);
+
+        write_skill(tmp.path(), "late", "# Late\nToo late.");
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(reloads.load(Ordering::SeqCst), 0);
+    }
+}
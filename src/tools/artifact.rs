@@ -0,0 +1,274 @@
+use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Name the agent loop looks for when collecting artifacts.
+pub const ARTIFACT_TOOL: &str = "artifact";
+
+const MAX_ARTIFACT_SIZE: u64 = 10 * 1024 * 1024;
+
+/// How `Artifact::content` is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactEncoding {
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "base64")]
+    Base64,
+}
+
+/// A file handed back to the user alongside the text answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub filename: String,
+    pub encoding: ArtifactEncoding,
+    pub content: String,
+}
+
+impl Artifact {
+    /// Text content is kept as is, anything else is base64-encoded.
+    pub fn from_bytes(filename: impl Into<String>, bytes: Vec<u8>) -> Self {
+        let (encoding, content) = match String::from_utf8(bytes) {
+            Ok(text) => (ArtifactEncoding::Utf8, text),
+            Err(e) => (
+                ArtifactEncoding::Base64,
+                BASE64_STANDARD.encode(e.into_bytes()),
+            ),
+        };
+        Self {
+            filename: filename.into(),
+            encoding,
+            content,
+        }
+    }
+
+    /// Block printed after the answer in the CLI:
+    /// `<artifact filename="…" encoding="…">…</artifact>`.
+    pub fn render(&self) -> String {
+        let encoding = match self.encoding {
+            ArtifactEncoding::Utf8 => "utf-8",
+            ArtifactEncoding::Base64 => "base64",
+        };
+        format!(
+            "<artifact filename=\"{}\" encoding=\"{encoding}\">\n{}\n</artifact>",
+            self.filename.replace('"', "&quot;"),
+            self.content.trim_end_matches('\n'),
+        )
+    }
+}
+
+/// Take the artifact out of a successful `artifact` call, leaving a short
+/// confirmation in its place so the content isn't sent back to the model.
+pub fn take_artifact(name: &str, result: &mut ToolResult) -> Option<Artifact> {
+    if name != ARTIFACT_TOOL || !result.success {
+        return None;
+    }
+    let artifact: Artifact = serde_json::from_str(&result.output).ok()?;
+    result.output = format!(
+        "Attached {} ({} bytes); it is delivered to the user with your answer.",
+        artifact.filename,
+        artifact.content.len()
+    );
+    Some(artifact)
+}
+
+/// Hand a file back to the user as an artifact, either from `content` or
+/// from a file in the workspace
+pub struct ArtifactTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+}
+
+impl ArtifactTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self::with_runtime(security, Arc::new(NativeRuntime::new()))
+    }
+
+    /// Go through `runtime`'s remote file access when it has one.
+    pub fn with_runtime(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        Self { security, runtime }
+    }
+
+    async fn read(&self, path: &str) -> Result<Vec<u8>, String> {
+        if !self.security.is_path_allowed(path) {
+            return Err(format!("Path not allowed by security policy: {path}"));
+        }
+        let bytes = if let Some(files) = self.runtime.remote_files() {
+            files
+                .read_file(&self.security.workspace_dir, path)
+                .await
+                .map_err(|e| format!("Failed to read file: {e:#}"))?
+        } else {
+            let resolved = tokio::fs::canonicalize(self.security.workspace_dir.join(path))
+                .await
+                .map_err(|e| format!("Failed to resolve file path: {e}"))?;
+            let resolved = self.security.check_path(&resolved)?;
+            let meta = tokio::fs::metadata(&resolved)
+                .await
+                .map_err(|e| format!("Failed to read file metadata: {e}"))?;
+            if meta.len() > MAX_ARTIFACT_SIZE {
+                return Err(format!(
+                    "File too large: {} bytes (limit: {MAX_ARTIFACT_SIZE} bytes)",
+                    meta.len()
+                ));
+            }
+            tokio::fs::read(&resolved)
+                .await
+                .map_err(|e| format!("Failed to read file: {e}"))?
+        };
+        if bytes.len() as u64 > MAX_ARTIFACT_SIZE {
+            return Err(format!(
+                "File too large: {} bytes (limit: {MAX_ARTIFACT_SIZE} bytes)",
+                bytes.len()
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+#[async_trait]
+impl Tool for ArtifactTool {
+    fn name(&self) -> &str {
+        ARTIFACT_TOOL
+    }
+
+    fn description(&self) -> &str {
+        "Return a file to the user alongside your answer. Pass `content` to \
+         create it, or `path` to send a file from the workspace."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "filename": {
+                    "type": "string",
+                    "description": "Name the user receives the file under; defaults to the file name of `path`"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text content of the file"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Relative path of a workspace file to send instead of `content`"
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let content = args.get("content").and_then(|v| v.as_str());
+        let path = args.get("path").and_then(|v| v.as_str());
+        let filename = args
+            .get("filename")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                path.and_then(|p| std::path::Path::new(p).file_name())
+                    .and_then(|name| name.to_str())
+            })
+            .ok_or_else(|| anyhow::anyhow!("Missing 'filename' parameter"))?;
+
+        let artifact = match (content, path) {
+            (Some(content), None) => Artifact {
+                filename: filename.to_string(),
+                encoding: ArtifactEncoding::Utf8,
+                content: content.to_string(),
+            },
+            (None, Some(path)) => match self.read(path).await {
+                Ok(bytes) => Artifact::from_bytes(filename, bytes),
+                Err(error) => {
+                    return Ok(ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(error),
+                    })
+                }
+            },
+            _ => anyhow::bail!("Pass exactly one of 'content' or 'path'"),
+        };
+
+        Ok(ToolResult {
+            success: true,
+            output: serde_json::to_string(&artifact)?,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use tempfile::TempDir;
+
+    fn tool(workspace: &std::path::Path) -> ArtifactTool {
+        ArtifactTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn artifact_from_content_is_taken_out_of_the_result() {
+        let tmp = TempDir::new().unwrap();
+        let mut result = tool(tmp.path())
+            .execute(json!({"filename": "report.csv", "content": "a,b\n1,2\n"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let artifact = take_artifact(ARTIFACT_TOOL, &mut result).unwrap();
+        assert_eq!(artifact.filename, "report.csv");
+        assert_eq!(artifact.content, "a,b\n1,2\n");
+        assert!(!result.output.contains("1,2"), "{}", result.output);
+        assert_eq!(
+            artifact.render(),
+            "<artifact filename=\"report.csv\" encoding=\"utf-8\">\na,b\n1,2\n</artifact>"
+        );
+        assert!(take_artifact("file_read", &mut result).is_none());
+    }
+
+    #[tokio::test]
+    async fn artifact_from_workspace_path() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.md"), "# Notes").unwrap();
+        std::fs::write(tmp.path().join("logo.bin"), [0xff, 0x00, 0x10]).unwrap();
+        let tool = tool(tmp.path());
+
+        let result = tool.execute(json!({"path": "notes.md"})).await.unwrap();
+        let artifact: Artifact = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(artifact.filename, "notes.md");
+        assert_eq!(artifact.encoding, ArtifactEncoding::Utf8);
+
+        let result = tool
+            .execute(json!({"path": "logo.bin", "filename": "logo.png"}))
+            .await
+            .unwrap();
+        let artifact: Artifact = serde_json::from_str(&result.output).unwrap();
+        assert_eq!(artifact.filename, "logo.png");
+        assert_eq!(artifact.encoding, ArtifactEncoding::Base64);
+        assert_eq!(artifact.content, "/wAQ");
+
+        let result = tool
+            .execute(json!({"path": "../../etc/passwd"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(tool
+            .execute(json!({"filename": "x", "content": "a", "path": "notes.md"}))
+            .await
+            .is_err());
+    }
+}
@@ -462,6 +462,10 @@ impl Tool for BrowserTool {
         })
     }
 
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: Value) -> anyhow::Result<ToolResult> {
         // Security checks
         if !self.security.can_act() {
@@ -0,0 +1,123 @@
+use super::context_set::SessionContext;
+use super::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+
+/// Let the agent read back a value kept with `context_set`
+pub struct ContextGetTool {
+    context: SessionContext,
+}
+
+impl ContextGetTool {
+    pub fn new(context: SessionContext) -> Self {
+        Self { context }
+    }
+}
+
+#[async_trait]
+impl Tool for ContextGetTool {
+    fn name(&self) -> &str {
+        "context_get"
+    }
+
+    fn description(&self) -> &str {
+        "Read a value kept earlier in this session with context_set. Omit the key to list every key that is set."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Name the value was stored under"
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let Some(key) = args.get("key").and_then(|v| v.as_str()) else {
+            let keys = self.context.keys();
+            let output = if keys.is_empty() {
+                "No context values are set.".into()
+            } else {
+                format!("Context keys: {}", keys.join(", "))
+            };
+            return Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+            });
+        };
+
+        Ok(match self.context.get(key) {
+            Some(value) => ToolResult {
+                success: true,
+                output: value,
+                error: None,
+            },
+            None => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("No context value for '{key}'")),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::ContextSetTool;
+
+    #[tokio::test]
+    async fn get_returns_value_set_earlier_in_session() {
+        let context = SessionContext::default();
+        let set = ContextSetTool::new(context.clone());
+        let get = ContextGetTool::new(context);
+
+        set.execute(json!({"key": "pr", "value": "42"}))
+            .await
+            .unwrap();
+        set.execute(json!({"key": "branch", "value": "fix-login"}))
+            .await
+            .unwrap();
+
+        let result = get.execute(json!({"key": "pr"})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "42");
+        let listing = get.execute(json!({})).await.unwrap();
+        assert_eq!(listing.output, "Context keys: branch, pr");
+    }
+
+    #[tokio::test]
+    async fn fresh_session_has_no_values() {
+        let context = SessionContext::default();
+        SessionContext::scope(
+            "earlier",
+            ContextSetTool::new(context.clone()).execute(json!({"key": "pr", "value": "42"})),
+        )
+        .await
+        .unwrap();
+
+        let get = ContextGetTool::new(context);
+        let result = SessionContext::scope("later", get.execute(json!({"key": "pr"})))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("pr"));
+        let listing = SessionContext::scope("later", get.execute(json!({})))
+            .await
+            .unwrap();
+        assert_eq!(listing.output, "No context values are set.");
+        let earlier = SessionContext::scope("earlier", get.execute(json!({"key": "pr"})))
+            .await
+            .unwrap();
+        assert_eq!(earlier.output, "42");
+    }
+}
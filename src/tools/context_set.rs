@@ -0,0 +1,172 @@
+use super::traits::{Tool, ToolResult};
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Instant;
+
+/// Sessions whose values are kept; the one set least recently is dropped
+/// beyond this.
+const MAX_SESSIONS: usize = 256;
+
+/// Each session's values and when they were last set
+type Sessions = HashMap<String, (Instant, HashMap<String, String>)>;
+
+tokio::task_local! {
+    /// Session the context tools act for while a turn runs
+    static SESSION: String;
+}
+
+/// Scratch values shared by `context_set` and `context_get`, kept apart per
+/// session. Unlike `Memory`, nothing here is persisted or recalled.
+///
+/// A registry serving many sessions (`AgentRunner`) runs each turn inside
+/// `SessionContext::scope`; outside any scope, as in a CLI run whose registry
+/// is its own session, one unnamed session is used.
+#[derive(Clone, Default)]
+pub struct SessionContext {
+    sessions: Arc<Mutex<Sessions>>,
+}
+
+impl SessionContext {
+    /// Run `turn` with the context tools acting for `session`.
+    pub async fn scope<F: Future>(session: &str, turn: F) -> F::Output {
+        SESSION.scope(session.to_string(), turn).await
+    }
+
+    pub fn set(&self, key: &str, value: &str) {
+        let mut sessions = self.lock();
+        let session = current_session();
+        if !sessions.contains_key(&session) && sessions.len() >= MAX_SESSIONS {
+            let oldest = sessions
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                sessions.remove(&oldest);
+            }
+        }
+        let (last_used, values) = sessions
+            .entry(session)
+            .or_insert_with(|| (Instant::now(), HashMap::new()));
+        *last_used = Instant::now();
+        values.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        self.lock()
+            .get(&current_session())
+            .and_then(|(_, values)| values.get(key).cloned())
+    }
+
+    /// The current session's keys, sorted
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .lock()
+            .get(&current_session())
+            .map(|(_, values)| values.keys().cloned().collect())
+            .unwrap_or_default();
+        keys.sort_unstable();
+        keys
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Sessions> {
+        self.sessions
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// The session set by `SessionContext::scope`, or the unnamed one
+pub(crate) fn current_session() -> String {
+    SESSION.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// Let the agent keep a value for later steps of the same session
+pub struct ContextSetTool {
+    context: SessionContext,
+}
+
+impl ContextSetTool {
+    pub fn new(context: SessionContext) -> Self {
+        Self { context }
+    }
+}
+
+#[async_trait]
+impl Tool for ContextSetTool {
+    fn name(&self) -> &str {
+        "context_set"
+    }
+
+    fn description(&self) -> &str {
+        "Keep a value (e.g. an ID or URL you just got) under a key for later steps of this session. Values are not saved to long-term memory and are gone when the session ends."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "Name to store the value under"
+                },
+                "value": {
+                    "type": "string",
+                    "description": "The value to keep; replaces any earlier value for the key"
+                }
+            },
+            "required": ["key", "value"]
+        })
+    }
+
+    fn concurrency_safe(&self) -> bool {
+        // A `context_get` later in the same response must see the value
+        false
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let key = args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'key' parameter"))?;
+        let value = args
+            .get("value")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'value' parameter"))?;
+
+        self.context.set(key, value);
+        Ok(ToolResult {
+            success: true,
+            output: format!("Set context: {key}"),
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn set_stores_and_replaces_value() {
+        let context = SessionContext::default();
+        let tool = ContextSetTool::new(context.clone());
+        let result = tool
+            .execute(json!({"key": "pr", "value": "41"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        tool.execute(json!({"key": "pr", "value": "42"}))
+            .await
+            .unwrap();
+        assert_eq!(context.get("pr").as_deref(), Some("42"));
+    }
+
+    #[tokio::test]
+    async fn set_missing_value() {
+        let tool = ContextSetTool::new(SessionContext::default());
+        assert!(tool.execute(json!({"key": "pr"})).await.is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+/// Append to a file in the workspace, creating it (and its parent
+/// directories) when missing
+pub struct FileAppendTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl FileAppendTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    /// Append `content` to `path`, returning whether the file was created.
+    async fn append(&self, path: &str, content: &str) -> Result<bool, String> {
+        let full_path = self.security.workspace_dir.join(path);
+        let existing = tokio::fs::symlink_metadata(&full_path).await.ok();
+        if let Some(meta) = &existing {
+            if meta.file_type().is_symlink() {
+                return Err(format!(
+                    "Refusing to write through symlink: {}",
+                    full_path.display()
+                ));
+            }
+            if meta.is_dir() {
+                return Err(format!("{path} is a directory, not a file"));
+            }
+        }
+
+        // Resolve BEFORE creating parent dirs so an escape never touches disk
+        let resolved = self.security.check_path(&full_path)?;
+        let parent = resolved
+            .parent()
+            .ok_or("Invalid path: missing parent directory")?;
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create parent directory: {e}"))?;
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&resolved)
+            .await
+            .map_err(|e| format!("Failed to open file: {e}"))?;
+        file.write_all(content.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to append to file: {e}"))?;
+        file.flush()
+            .await
+            .map_err(|e| format!("Failed to append to file: {e}"))?;
+        Ok(existing.is_none())
+    }
+}
+
+#[async_trait]
+impl Tool for FileAppendTool {
+    fn name(&self) -> &str {
+        "file_append"
+    }
+
+    fn description(&self) -> &str {
+        "Append content to the end of a file in the workspace, creating the file if it \
+         doesn't exist"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Relative path to the file within the workspace"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Content to add at the end of the file (include a trailing newline for line-based logs)"
+                }
+            },
+            "required": ["path", "content"]
+        })
+    }
+
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
+    fn idempotency_key_supported(&self, _args: &serde_json::Value) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+
+        let content = args
+            .get("content")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'content' parameter"))?;
+
+        if !self.security.is_path_allowed(path) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Path not allowed by security policy: {path}")),
+            });
+        }
+
+        Ok(match self.append(path, content).await {
+            Ok(created) => ToolResult {
+                success: true,
+                output: format!(
+                    "Appended {} bytes to {path}{}",
+                    content.len(),
+                    if created { " (new file)" } else { "" }
+                ),
+                error: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &std::path::Path) -> FileAppendTool {
+        FileAppendTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn file_append_creates_a_new_file() {
+        let tmp = TempDir::new().unwrap();
+        let result = tool(tmp.path())
+            .execute(json!({"path": "logs/run.log", "content": "started\n"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Appended 8 bytes to logs/run.log (new file)");
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("logs/run.log")).unwrap(),
+            "started\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_append_adds_to_an_existing_file() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("run.log"), "started\n").unwrap();
+        let tool = tool(tmp.path());
+
+        for line in ["step 1\n", "done\n"] {
+            let result = tool
+                .execute(json!({"path": "run.log", "content": line}))
+                .await
+                .unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert!(!result.output.contains("new file"));
+        }
+        assert_eq!(
+            std::fs::read_to_string(tmp.path().join("run.log")).unwrap(),
+            "started\nstep 1\ndone\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn file_append_refuses_directories_and_escapes() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::create_dir(tmp.path().join("logs")).unwrap();
+        let tool = tool(tmp.path());
+
+        let result = tool
+            .execute(json!({"path": "logs", "content": "x"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("logs is a directory, not a file")
+        );
+
+        let result = tool
+            .execute(json!({"path": "../outside.log", "content": "x"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not allowed"));
+    }
+}
@@ -0,0 +1,402 @@
+use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Matches listed back to the model when `old_string` is ambiguous.
+const MAX_LISTED_MATCHES: usize = 5;
+
+/// Longest line excerpt shown per match.
+const MAX_EXCERPT_CHARS: usize = 120;
+
+/// Replace an exact snippet inside a file with path sandboxing
+pub struct FileEditTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+}
+
+impl FileEditTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self::with_runtime(security, Arc::new(NativeRuntime::new()))
+    }
+
+    /// Go through `runtime`'s remote file access when it has one.
+    pub fn with_runtime(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        Self { security, runtime }
+    }
+
+    async fn edit_remote(
+        &self,
+        path: &str,
+        old: &str,
+        new: &str,
+        replace_all: bool,
+    ) -> Result<usize, String> {
+        let files = self
+            .runtime
+            .remote_files()
+            .ok_or("Runtime has no remote file access")?;
+        let workspace = &self.security.workspace_dir;
+        let bytes = files
+            .read_file(workspace, path)
+            .await
+            .map_err(|e| format!("Failed to read file: {e:#}"))?;
+        if bytes.len() as u64 > MAX_FILE_SIZE {
+            return Err(format!(
+                "File too large: {} bytes (limit: {MAX_FILE_SIZE} bytes)",
+                bytes.len()
+            ));
+        }
+        let content =
+            String::from_utf8(bytes).map_err(|_| format!("File is not valid UTF-8: {path}"))?;
+        let (edited, count) = apply_edit(&content, old, new, replace_all, path)?;
+        files
+            .write_file(workspace, path, edited.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write file: {e:#}"))?;
+        Ok(count)
+    }
+
+    async fn edit_local(
+        &self,
+        path: &str,
+        old: &str,
+        new: &str,
+        replace_all: bool,
+    ) -> Result<usize, String> {
+        let full_path = self.security.workspace_dir.join(path);
+
+        // Never edit through a symlink, same as file_write
+        let meta = tokio::fs::symlink_metadata(&full_path)
+            .await
+            .map_err(|e| format!("Failed to read file: {e}"))?;
+        if meta.file_type().is_symlink() {
+            return Err(format!(
+                "Refusing to edit through symlink: {}",
+                full_path.display()
+            ));
+        }
+        if meta.len() > MAX_FILE_SIZE {
+            return Err(format!(
+                "File too large: {} bytes (limit: {MAX_FILE_SIZE} bytes)",
+                meta.len()
+            ));
+        }
+
+        let resolved = self.security.check_path(&full_path)?;
+        let content = tokio::fs::read_to_string(&resolved)
+            .await
+            .map_err(|e| format!("Failed to read file: {e}"))?;
+        let (edited, count) = apply_edit(&content, old, new, replace_all, path)?;
+        tokio::fs::write(&resolved, edited)
+            .await
+            .map_err(|e| format!("Failed to write file: {e}"))?;
+        Ok(count)
+    }
+}
+
+/// Replace `old` with `new` in `content`, returning the edited text and how
+/// many occurrences were replaced. Without `replace_all`, `old` must occur
+/// exactly once; otherwise the error lists where it occurs.
+fn apply_edit(
+    content: &str,
+    old: &str,
+    new: &str,
+    replace_all: bool,
+    path: &str,
+) -> Result<(String, usize), String> {
+    if old.is_empty() {
+        return Err("old_string must not be empty".into());
+    }
+    if old == new {
+        return Err("old_string and new_string are identical; nothing to change".into());
+    }
+
+    let starts: Vec<usize> = content.match_indices(old).map(|(i, _)| i).collect();
+    match starts.len() {
+        0 => Err(format!(
+            "old_string not found in {path}. Check whitespace and indentation, \
+             or read the file again to copy the exact text."
+        )),
+        1 => Ok((content.replacen(old, new, 1), 1)),
+        n if replace_all => Ok((content.replace(old, new), n)),
+        n => Err(format!(
+            "old_string matches {n} places in {path}; add surrounding lines to \
+             make it unique, or set replace_all to change every occurrence:\n{}",
+            describe_matches(content, &starts)
+        )),
+    }
+}
+
+/// One line per match: its line number and (truncated) line text.
+fn describe_matches(content: &str, starts: &[usize]) -> String {
+    let mut lines: Vec<String> = starts
+        .iter()
+        .take(MAX_LISTED_MATCHES)
+        .map(|&start| {
+            let line_no = content[..start].matches('\n').count() + 1;
+            let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = content[start..]
+                .find('\n')
+                .map_or(content.len(), |i| start + i);
+            let line = &content[line_start..line_end];
+            let excerpt: String = line.chars().take(MAX_EXCERPT_CHARS).collect();
+            let ellipsis = if excerpt.len() < line.len() {
+                "…"
+            } else {
+                ""
+            };
+            format!("  line {line_no}: {}{ellipsis}", excerpt.trim_end())
+        })
+        .collect();
+    if starts.len() > MAX_LISTED_MATCHES {
+        lines.push(format!(
+            "  … and {} more",
+            starts.len() - MAX_LISTED_MATCHES
+        ));
+    }
+    lines.join("\n")
+}
+
+#[async_trait]
+impl Tool for FileEditTool {
+    fn name(&self) -> &str {
+        "file_edit"
+    }
+
+    fn description(&self) -> &str {
+        "Edit a file in the workspace by replacing an exact snippet of its text. \
+         old_string must match exactly once unless replace_all is true; include \
+         enough surrounding lines to make it unique."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Relative path to the file within the workspace"
+                },
+                "old_string": {
+                    "type": "string",
+                    "description": "Exact text to replace, including whitespace"
+                },
+                "new_string": {
+                    "type": "string",
+                    "description": "Text to put in its place"
+                },
+                "replace_all": {
+                    "type": "boolean",
+                    "description": "Replace every occurrence instead of requiring a unique match",
+                    "default": false
+                }
+            },
+            "required": ["path", "old_string", "new_string"]
+        })
+    }
+
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+        let old = args
+            .get("old_string")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'old_string' parameter"))?;
+        let new = args
+            .get("new_string")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'new_string' parameter"))?;
+        let replace_all = args
+            .get("replace_all")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        // Security check: validate path is within workspace
+        if !self.security.is_path_allowed(path) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Path not allowed by security policy: {path}")),
+            });
+        }
+
+        let result = if self.runtime.remote_files().is_some() {
+            self.edit_remote(path, old, new, replace_all).await
+        } else {
+            self.edit_local(path, old, new, replace_all).await
+        };
+
+        Ok(match result {
+            Ok(count) => ToolResult {
+                success: true,
+                output: format!(
+                    "Replaced {count} occurrence{} in {path}",
+                    if count == 1 { "" } else { "s" }
+                ),
+                error: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::{AutonomyLevel, SecurityPolicy};
+    use tempfile::TempDir;
+
+    fn tool_in(dir: &TempDir) -> FileEditTool {
+        FileEditTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: dir.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    const SOURCE: &str = "fn main() {\n    let x = 1;\n    println!(\"{x}\");\n    let x = 1;\n}\n";
+
+    #[test]
+    fn file_edit_schema_requires_path_and_strings() {
+        let tmp = TempDir::new().unwrap();
+        let schema = tool_in(&tmp).parameters_schema();
+        let required = schema["required"].as_array().unwrap();
+        for key in ["path", "old_string", "new_string"] {
+            assert!(required.contains(&json!(key)), "{key}");
+        }
+        assert!(schema["properties"]["replace_all"].is_object());
+    }
+
+    #[tokio::test]
+    async fn unique_match_is_replaced() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("main.rs"), SOURCE).unwrap();
+
+        let result = tool_in(&tmp)
+            .execute(json!({
+                "path": "main.rs",
+                "old_string": "println!(\"{x}\");",
+                "new_string": "println!(\"x = {x}\");"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Replaced 1 occurrence in main.rs");
+
+        let content = std::fs::read_to_string(tmp.path().join("main.rs")).unwrap();
+        assert_eq!(content, SOURCE.replace("{x}\")", "x = {x}\")"));
+    }
+
+    #[tokio::test]
+    async fn ambiguous_match_fails_with_locations() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("main.rs"), SOURCE).unwrap();
+
+        let result = tool_in(&tmp)
+            .execute(json!({
+                "path": "main.rs",
+                "old_string": "let x = 1;",
+                "new_string": "let x = 2;"
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(error.contains("matches 2 places"), "{error}");
+        assert!(error.contains("line 2:     let x = 1;"), "{error}");
+        assert!(error.contains("line 4:"), "{error}");
+        assert!(error.contains("replace_all"), "{error}");
+
+        let content = std::fs::read_to_string(tmp.path().join("main.rs")).unwrap();
+        assert_eq!(content, SOURCE, "file must be left untouched");
+    }
+
+    #[tokio::test]
+    async fn missing_snippet_fails() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("main.rs"), SOURCE).unwrap();
+
+        let result = tool_in(&tmp)
+            .execute(json!({
+                "path": "main.rs",
+                "old_string": "let y = 1;",
+                "new_string": "let y = 2;"
+            }))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not found in main.rs"));
+    }
+
+    #[tokio::test]
+    async fn replace_all_changes_every_occurrence() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("main.rs"), SOURCE).unwrap();
+
+        let result = tool_in(&tmp)
+            .execute(json!({
+                "path": "main.rs",
+                "old_string": "let x = 1;",
+                "new_string": "let x = 2;",
+                "replace_all": true
+            }))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Replaced 2 occurrences in main.rs");
+
+        let content = std::fs::read_to_string(tmp.path().join("main.rs")).unwrap();
+        assert_eq!(content.matches("let x = 2;").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn paths_outside_workspace_are_refused() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+        for path in ["../outside.txt", "/etc/passwd"] {
+            let result = tool
+                .execute(json!({"path": path, "old_string": "a", "new_string": "b"}))
+                .await
+                .unwrap();
+            assert!(!result.success, "{path}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlinked_file_is_refused() {
+        let tmp = TempDir::new().unwrap();
+        let outside = TempDir::new().unwrap();
+        std::fs::write(outside.path().join("target.txt"), "secret").unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("target.txt"),
+            tmp.path().join("link.txt"),
+        )
+        .unwrap();
+
+        let result = tool_in(&tmp)
+            .execute(json!({"path": "link.txt", "old_string": "secret", "new_string": "x"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            std::fs::read_to_string(outside.path().join("target.txt")).unwrap(),
+            "secret"
+        );
+    }
+}
@@ -1,17 +1,26 @@
 use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
 use std::sync::Arc;
 
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
 /// Read file contents with path sandboxing
 pub struct FileReadTool {
     security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
 }
 
 impl FileReadTool {
     pub fn new(security: Arc<SecurityPolicy>) -> Self {
-        Self { security }
+        Self::with_runtime(security, Arc::new(NativeRuntime::new()))
+    }
+
+    /// Go through `runtime`'s remote file access when it has one.
+    pub fn with_runtime(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        Self { security, runtime }
     }
 }
 
@@ -38,6 +47,10 @@ impl Tool for FileReadTool {
         })
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -53,6 +66,31 @@ impl Tool for FileReadTool {
             });
         }
 
+        if let Some(files) = self.runtime.remote_files() {
+            return Ok(
+                match files.read_file(&self.security.workspace_dir, path).await {
+                    Ok(bytes) if bytes.len() as u64 > MAX_FILE_SIZE => ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!(
+                            "File too large: {} bytes (limit: {MAX_FILE_SIZE} bytes)",
+                            bytes.len()
+                        )),
+                    },
+                    Ok(bytes) => ToolResult {
+                        success: true,
+                        output: String::from_utf8_lossy(&bytes).into_owned(),
+                        error: None,
+                    },
+                    Err(e) => ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Failed to read file: {e:#}")),
+                    },
+                },
+            );
+        }
+
         let full_path = self.security.workspace_dir.join(path);
 
         // Resolve path before reading to block symlink escapes.
@@ -67,19 +105,18 @@ impl Tool for FileReadTool {
             }
         };
 
-        if !self.security.is_resolved_path_allowed(&resolved_path) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved_path.display()
-                )),
-            });
-        }
+        let resolved_path = match self.security.check_path(&resolved_path) {
+            Ok(p) => p,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                });
+            }
+        };
 
         // Check file size AFTER canonicalization to prevent TOCTOU symlink bypass
-        const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
         match tokio::fs::metadata(&resolved_path).await {
             Ok(meta) => {
                 if meta.len() > MAX_FILE_SIZE {
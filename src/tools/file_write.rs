@@ -1,4 +1,5 @@
 use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
@@ -7,11 +8,17 @@ use std::sync::Arc;
 /// Write file contents with path sandboxing
 pub struct FileWriteTool {
     security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
 }
 
 impl FileWriteTool {
     pub fn new(security: Arc<SecurityPolicy>) -> Self {
-        Self { security }
+        Self::with_runtime(security, Arc::new(NativeRuntime::new()))
+    }
+
+    /// Go through `runtime`'s remote file access when it has one.
+    pub fn with_runtime(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        Self { security, runtime }
     }
 }
 
@@ -42,6 +49,10 @@ impl Tool for FileWriteTool {
         })
     }
 
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let path = args
             .get("path")
@@ -62,65 +73,64 @@ impl Tool for FileWriteTool {
             });
         }
 
-        let full_path = self.security.workspace_dir.join(path);
+        if let Some(files) = self.runtime.remote_files() {
+            return Ok(
+                match files
+                    .write_file(&self.security.workspace_dir, path, content.as_bytes())
+                    .await
+                {
+                    Ok(()) => ToolResult {
+                        success: true,
+                        output: format!("Written {} bytes to {path}", content.len()),
+                        error: None,
+                    },
+                    Err(e) => ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(format!("Failed to write file: {e:#}")),
+                    },
+                },
+            );
+        }
 
-        let Some(parent) = full_path.parent() else {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some("Invalid path: missing parent directory".into()),
-            });
-        };
+        let full_path = self.security.workspace_dir.join(path);
 
-        // Ensure parent directory exists
-        tokio::fs::create_dir_all(parent).await?;
+        // If the target already exists and is a symlink, refuse to follow it
+        if let Ok(meta) = tokio::fs::symlink_metadata(&full_path).await {
+            if meta.file_type().is_symlink() {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!(
+                        "Refusing to write through symlink: {}",
+                        full_path.display()
+                    )),
+                });
+            }
+        }
 
-        // Resolve parent AFTER creation to block symlink escapes.
-        let resolved_parent = match tokio::fs::canonicalize(parent).await {
+        // Resolve BEFORE creating parent dirs so an escape never touches disk
+        let resolved_target = match self.security.check_path(&full_path) {
             Ok(p) => p,
             Err(e) => {
                 return Ok(ToolResult {
                     success: false,
                     output: String::new(),
-                    error: Some(format!("Failed to resolve file path: {e}")),
+                    error: Some(e),
                 });
             }
         };
 
-        if !self.security.is_resolved_path_allowed(&resolved_parent) {
-            return Ok(ToolResult {
-                success: false,
-                output: String::new(),
-                error: Some(format!(
-                    "Resolved path escapes workspace: {}",
-                    resolved_parent.display()
-                )),
-            });
-        }
-
-        let Some(file_name) = full_path.file_name() else {
+        let Some(parent) = resolved_target.parent() else {
             return Ok(ToolResult {
                 success: false,
                 output: String::new(),
-                error: Some("Invalid path: missing file name".into()),
+                error: Some("Invalid path: missing parent directory".into()),
             });
         };
 
-        let resolved_target = resolved_parent.join(file_name);
-
-        // If the target already exists and is a symlink, refuse to follow it
-        if let Ok(meta) = tokio::fs::symlink_metadata(&resolved_target).await {
-            if meta.file_type().is_symlink() {
-                return Ok(ToolResult {
-                    success: false,
-                    output: String::new(),
-                    error: Some(format!(
-                        "Refusing to write through symlink: {}",
-                        resolved_target.display()
-                    )),
-                });
-            }
-        }
+        // Ensure parent directory exists
+        tokio::fs::create_dir_all(parent).await?;
 
         match tokio::fs::write(&resolved_target, content).await {
             Ok(()) => Ok(ToolResult {
@@ -324,4 +334,91 @@ mod tests {
 
         let _ = tokio::fs::remove_dir_all(&root).await;
     }
+
+    /// Remote runtime that records writes instead of performing them.
+    #[derive(Default)]
+    struct RecordingRemote {
+        writes: std::sync::Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl crate::runtime::traits::RemoteFiles for RecordingRemote {
+        async fn read_file(&self, _: &std::path::Path, _: &str) -> anyhow::Result<Vec<u8>> {
+            anyhow::bail!("not used")
+        }
+
+        async fn write_file(
+            &self,
+            _: &std::path::Path,
+            path: &str,
+            contents: &[u8],
+        ) -> anyhow::Result<()> {
+            self.writes
+                .lock()
+                .unwrap()
+                .push((path.to_string(), contents.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl RuntimeAdapter for RecordingRemote {
+        fn name(&self) -> &str {
+            "recording"
+        }
+
+        fn has_shell_access(&self) -> bool {
+            false
+        }
+
+        fn has_filesystem_access(&self) -> bool {
+            true
+        }
+
+        fn storage_path(&self) -> std::path::PathBuf {
+            std::env::temp_dir()
+        }
+
+        fn supports_long_running(&self) -> bool {
+            false
+        }
+
+        fn remote_files(&self) -> Option<&dyn crate::runtime::traits::RemoteFiles> {
+            Some(self)
+        }
+
+        fn build_shell_command(
+            &self,
+            _: &str,
+            _: &std::path::Path,
+        ) -> anyhow::Result<tokio::process::Command> {
+            anyhow::bail!("not used")
+        }
+    }
+
+    #[tokio::test]
+    async fn file_write_goes_through_remote_runtime_after_policy_check() {
+        let remote = Arc::new(RecordingRemote::default());
+        let tool = FileWriteTool::with_runtime(
+            test_security(std::env::temp_dir().join("zeroclaw_test_remote_write")),
+            remote.clone(),
+        );
+
+        let result = tool
+            .execute(json!({"path": "notes/a.txt", "content": "remote"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+
+        let blocked = tool
+            .execute(json!({"path": "../escape.txt", "content": "x"}))
+            .await
+            .unwrap();
+        assert!(!blocked.success);
+
+        let writes = remote.writes.lock().unwrap();
+        assert_eq!(
+            *writes,
+            vec![("notes/a.txt".to_string(), b"remote".to_vec())]
+        );
+    }
 }
@@ -0,0 +1,439 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+use serde_json::json;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum time for the whole request, including reading the body.
+const HTTP_TIMEOUT_SECS: u64 = 30;
+/// Maximum response body returned to the model (64KB).
+const MAX_BODY_BYTES: usize = 65_536;
+/// Error prefix for requests that never reached the server, which makes
+/// them safe to retry whatever the method.
+const CONNECT_FAILED: &str = "Connection failed";
+
+/// Call HTTP APIs directly instead of shelling out to `curl`
+pub struct HttpTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl HttpTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+fn build_request(args: &serde_json::Value) -> anyhow::Result<reqwest::Request> {
+    let method = args
+        .get("method")
+        .and_then(|v| v.as_str())
+        .unwrap_or("GET")
+        .to_ascii_uppercase();
+    let method = Method::from_bytes(method.as_bytes())
+        .map_err(|_| anyhow::anyhow!("Invalid HTTP method '{method}'"))?;
+
+    let url = args
+        .get("url")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| anyhow::anyhow!("Missing 'url' parameter"))?;
+    let url = Url::parse(url.trim()).map_err(|e| anyhow::anyhow!("Invalid URL: {e}"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        anyhow::bail!("Only http:// and https:// URLs are allowed");
+    }
+
+    let mut request = reqwest::Request::new(method, url);
+    if let Some(map) = args.get("headers").and_then(|v| v.as_object()) {
+        for (name, value) in map {
+            let value = value
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Header '{name}' must be a string"))?;
+            request.headers_mut().insert(
+                HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| anyhow::anyhow!("Invalid header name '{name}'"))?,
+                HeaderValue::from_str(value)
+                    .map_err(|_| anyhow::anyhow!("Invalid value for header '{name}'"))?,
+            );
+        }
+    }
+
+    if let Some(body) = args.get("body").and_then(|v| v.as_str()) {
+        *request.body_mut() = Some(body.to_string().into());
+    }
+    Ok(request)
+}
+
+/// Client that only connects to the addresses the egress policy checked,
+/// so a second DNS answer cannot rebind the request
+fn pinned_client(url: &Url, addrs: &[SocketAddr]) -> anyhow::Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_secs(HTTP_TIMEOUT_SECS))
+        // Redirects come back as responses so every hop is a visible request
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(url.host_str().unwrap_or_default(), addrs)
+        .build()?)
+}
+
+/// Render status, headers and the (size-capped) body as tool output
+async fn format_response(mut response: reqwest::Response) -> anyhow::Result<String> {
+    let mut output = format!("Status: {}\n", response.status());
+    for (name, value) in response.headers() {
+        let _ = writeln!(output, "{name}: {}", value.to_str().unwrap_or("<binary>"));
+    }
+    output.push('\n');
+
+    // Stream the body so an oversized response is never fully buffered
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if body.len() > MAX_BODY_BYTES {
+            truncated = true;
+            break;
+        }
+    }
+
+    let mut text = String::from_utf8_lossy(&body).into_owned();
+    if truncated {
+        text.truncate(text.floor_char_boundary(MAX_BODY_BYTES));
+        text.push_str("\n... [body truncated at 64KB]");
+    }
+    output.push_str(&text);
+    Ok(output)
+}
+
+#[async_trait]
+impl Tool for HttpTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Send an HTTP request and return the status, headers and body (truncated at 64KB)"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method (GET, POST, PUT, PATCH, DELETE, ...)",
+                    "default": "GET"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Absolute http:// or https:// URL"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Request headers as name → value strings",
+                    "additionalProperties": { "type": "string" }
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Request body, sent as-is"
+                }
+            },
+            "required": ["method", "url"]
+        })
+    }
+
+    fn concurrency_safe(&self) -> bool {
+        // POST/PUT/DELETE may have side effects later calls depend on
+        false
+    }
+
+    fn idempotency_key_supported(&self, args: &serde_json::Value) -> bool {
+        // A replayed POST would otherwise be sent twice; reads are repeated
+        let method = args.get("method").and_then(|v| v.as_str()).unwrap_or("GET");
+        ["POST", "PATCH", "DELETE"]
+            .iter()
+            .any(|m| method.eq_ignore_ascii_case(m))
+    }
+
+    fn is_retryable(&self, result: &ToolResult) -> bool {
+        // Anything that may have reached the server could have had effects
+        result
+            .error
+            .as_deref()
+            .is_some_and(|e| e.starts_with(CONNECT_FAILED))
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        if !self.security.can_act() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: autonomy is read-only".into()),
+            });
+        }
+
+        let request = match build_request(&args) {
+            Ok(request) => request,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                })
+            }
+        };
+
+        let client = match self.security.resolve_url(request.url()).await {
+            Ok(addrs) => pinned_client(request.url(), &addrs)?,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Request blocked: {e}")),
+                })
+            }
+        };
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+            });
+        }
+
+        let response = match client.execute(request).await {
+            Ok(response) => response,
+            Err(e) if e.is_timeout() => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Request timed out after {HTTP_TIMEOUT_SECS}s")),
+                })
+            }
+            Err(e) if e.is_connect() => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("{CONNECT_FAILED}: {e}")),
+                })
+            }
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Request failed: {e}")),
+                })
+            }
+        };
+
+        let status = response.status();
+        let failed = status.is_client_error() || status.is_server_error();
+        match format_response(response).await {
+            Ok(output) => Ok(ToolResult {
+                success: !failed,
+                output,
+                error: failed.then(|| format!("HTTP {status}")),
+            }),
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Failed to read response body: {e}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use axum::routing::{get, post};
+    use axum::Router;
+
+    fn tool(autonomy: AutonomyLevel) -> HttpTool {
+        HttpTool::new(Arc::new(SecurityPolicy {
+            autonomy,
+            // The mock server is on loopback, which is blocked unless listed
+            allowed_hosts: vec!["127.0.0.1".into()],
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    /// Serve a few canned routes on an ephemeral local port
+    async fn mock_server() -> String {
+        let app = Router::new()
+            .route(
+                "/hello",
+                get(|| async { ([("x-mock", "yes")], "hello from mock") }),
+            )
+            .route(
+                "/echo",
+                post(|headers: axum::http::HeaderMap, body: String| async move {
+                    let auth = headers
+                        .get("authorization")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("")
+                        .to_string();
+                    (axum::http::StatusCode::CREATED, format!("{auth}|{body}"))
+                }),
+            )
+            .route("/big", get(|| async { "x".repeat(MAX_BODY_BYTES * 2) }))
+            .route(
+                "/missing",
+                get(|| async { (axum::http::StatusCode::NOT_FOUND, "nope") }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn only_unsafe_methods_use_idempotency_keys() {
+        let tool = tool(AutonomyLevel::Supervised);
+        for method in ["POST", "patch", "DELETE"] {
+            assert!(tool.idempotency_key_supported(&json!({"method": method, "url": "x"})));
+        }
+        for method in ["GET", "HEAD", "PUT"] {
+            assert!(!tool.idempotency_key_supported(&json!({"method": method, "url": "x"})));
+        }
+        assert!(!tool.idempotency_key_supported(&json!({"url": "x"})));
+    }
+
+    #[test]
+    fn http_request_spec() {
+        let tool = tool(AutonomyLevel::Supervised);
+        assert_eq!(tool.name(), "http_request");
+        let schema = tool.parameters_schema();
+        assert!(schema["properties"]["headers"].is_object());
+        assert!(schema["required"]
+            .as_array()
+            .unwrap()
+            .contains(&json!("url")));
+        assert!(!tool.concurrency_safe());
+    }
+
+    #[tokio::test]
+    async fn get_returns_status_headers_and_body() {
+        let base = mock_server().await;
+        let result = tool(AutonomyLevel::Supervised)
+            .execute(json!({"method": "get", "url": format!("{base}/hello")}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.output.starts_with("Status: 200 OK\n"));
+        assert!(result.output.contains("x-mock: yes"));
+        assert!(result.output.ends_with("\n\nhello from mock"));
+    }
+
+    #[tokio::test]
+    async fn post_sends_headers_and_body() {
+        let base = mock_server().await;
+        let result = tool(AutonomyLevel::Supervised)
+            .execute(json!({
+                "method": "POST",
+                "url": format!("{base}/echo"),
+                "headers": {"Authorization": "Bearer t0k"},
+                "body": "{\"name\":\"zeroclaw\"}"
+            }))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(result.output.starts_with("Status: 201 Created\n"));
+        assert!(result
+            .output
+            .ends_with("Bearer t0k|{\"name\":\"zeroclaw\"}"));
+    }
+
+    #[tokio::test]
+    async fn large_body_is_truncated() {
+        let base = mock_server().await;
+        let result = tool(AutonomyLevel::Supervised)
+            .execute(json!({"method": "GET", "url": format!("{base}/big")}))
+            .await
+            .unwrap();
+        assert!(result.output.ends_with("[body truncated at 64KB]"));
+        assert!(result.output.len() < MAX_BODY_BYTES + 1024);
+    }
+
+    #[tokio::test]
+    async fn error_status_keeps_output() {
+        let base = mock_server().await;
+        let result = tool(AutonomyLevel::Supervised)
+            .execute(json!({"method": "GET", "url": format!("{base}/missing")}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("HTTP 404 Not Found"));
+        assert!(result.output.ends_with("nope"));
+    }
+
+    #[tokio::test]
+    async fn only_connection_failures_are_retryable() {
+        let tool = tool(AutonomyLevel::Supervised);
+        let missing = tool
+            .execute(json!({"method": "GET", "url": format!("{}/missing", mock_server().await)}))
+            .await
+            .unwrap();
+        assert!(!tool.is_retryable(&missing));
+
+        // Nothing listens on a port that was just released
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let refused = tool
+            .execute(json!({"method": "POST", "url": format!("http://{addr}/")}))
+            .await
+            .unwrap();
+        assert!(!refused.success);
+        assert!(tool.is_retryable(&refused), "{:?}", refused.error);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_arguments() {
+        let tool = tool(AutonomyLevel::Supervised);
+        for args in [
+            json!({"method": "GET"}),
+            json!({"method": "GET", "url": "ftp://example.com/file"}),
+            json!({"method": "GET", "url": "not a url"}),
+            json!({"method": "GE T", "url": "https://example.com"}),
+            json!({"method": "GET", "url": "https://example.com", "headers": {"X-N": 1}}),
+        ] {
+            let result = tool.execute(args.clone()).await.unwrap();
+            assert!(!result.success, "{args}");
+            assert!(result.error.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn blocks_hosts_outside_egress_policy() {
+        let open = HttpTool::new(Arc::new(SecurityPolicy::default()));
+        let result = open
+            .execute(json!({"method": "GET", "url": "http://169.254.169.254/latest/meta-data"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result
+            .error
+            .unwrap()
+            .contains("private address 169.254.169.254"));
+
+        let result = tool(AutonomyLevel::Supervised)
+            .execute(json!({"method": "GET", "url": "https://example.com"}))
+            .await
+            .unwrap();
+        assert!(result.error.unwrap().contains("not in allowed_hosts"));
+    }
+
+    #[tokio::test]
+    async fn blocks_readonly_mode() {
+        let result = tool(AutonomyLevel::ReadOnly)
+            .execute(json!({"method": "GET", "url": "https://example.com"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+}
@@ -0,0 +1,137 @@
+use super::traits::{Tool, ToolResult};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Create a directory (and any missing parents) inside the workspace
+pub struct MakeDirectoryTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl MakeDirectoryTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+
+    /// Create `path`, returning whether it already existed.
+    async fn create(&self, path: &str) -> Result<bool, String> {
+        let resolved = self
+            .security
+            .check_path(&self.security.workspace_dir.join(path))?;
+        match tokio::fs::metadata(&resolved).await {
+            Ok(meta) if meta.is_dir() => return Ok(true),
+            Ok(_) => return Err(format!("{path} already exists and is not a directory")),
+            Err(_) => {}
+        }
+        tokio::fs::create_dir_all(&resolved)
+            .await
+            .map_err(|e| format!("Failed to create directory: {e}"))?;
+        Ok(false)
+    }
+}
+
+#[async_trait]
+impl Tool for MakeDirectoryTool {
+    fn name(&self) -> &str {
+        "make_directory"
+    }
+
+    fn description(&self) -> &str {
+        "Create a directory in the workspace, including any missing parent directories; \
+         succeeds if it already exists"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "Relative path of the directory within the workspace"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let path = args
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'path' parameter"))?;
+
+        if !self.security.is_path_allowed(path) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("Path not allowed by security policy: {path}")),
+            });
+        }
+
+        Ok(match self.create(path).await {
+            Ok(existed) => ToolResult {
+                success: true,
+                output: if existed {
+                    format!("Directory {path} already exists")
+                } else {
+                    format!("Created directory {path}")
+                },
+                error: None,
+            },
+            Err(e) => ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(e),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool(workspace: &std::path::Path) -> MakeDirectoryTool {
+        MakeDirectoryTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: workspace.to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[tokio::test]
+    async fn make_directory_creates_parents() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool(tmp.path());
+
+        let result = tool
+            .execute(json!({"path": "reports/2026/q3"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Created directory reports/2026/q3");
+        assert!(tmp.path().join("reports/2026/q3").is_dir());
+
+        let result = tool.execute(json!({"path": "reports/2026"})).await.unwrap();
+        assert!(result.success);
+        assert_eq!(result.output, "Directory reports/2026 already exists");
+    }
+
+    #[tokio::test]
+    async fn make_directory_refuses_files_and_escapes() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), "x").unwrap();
+        let tool = tool(tmp.path());
+
+        let result = tool.execute(json!({"path": "notes.txt"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("not a directory"));
+
+        let result = tool.execute(json!({"path": "../escape"})).await.unwrap();
+        assert!(!result.success);
+        assert!(!tmp.path().parent().unwrap().join("escape").exists());
+    }
+}
@@ -0,0 +1,183 @@
+use super::traits::{Tool, ToolResult};
+use crate::memory::{Memory, MemoryCategory};
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Let the agent prune a memory entry, optionally only from one category
+pub struct MemoryDeleteTool {
+    memory: Arc<dyn Memory>,
+}
+
+impl MemoryDeleteTool {
+    pub fn new(memory: Arc<dyn Memory>) -> Self {
+        Self { memory }
+    }
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryDeleteTool {
+    fn name(&self) -> &str {
+        "memory_delete"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a memory by key (find keys with memory_list). Pass category to only delete it from that category. Fails if no such memory exists."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "key": {
+                    "type": "string",
+                    "description": "The key of the memory to delete"
+                },
+                "category": {
+                    "type": "string",
+                    "description": "Only delete if the memory is in this category: core, daily, conversation, or a custom name"
+                }
+            },
+            "required": ["key"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let key = args
+            .get("key")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'key' parameter"))?;
+        let category = args
+            .get("category")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .map(MemoryCategory::parse);
+
+        let entry = match self.memory.get(key).await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => return Ok(failure(format!("No memory found with key: {key}"))),
+            Err(e) => return Ok(failure(format!("Failed to look up memory: {e}"))),
+        };
+        if let Some(category) = category {
+            if entry.category != category {
+                return Ok(failure(format!(
+                    "Memory '{key}' is in category '{}', not '{category}'; nothing deleted",
+                    entry.category
+                )));
+            }
+        }
+
+        match self.memory.forget(key).await {
+            Ok(true) => Ok(ToolResult {
+                success: true,
+                output: format!("Deleted memory: {key} [{}]", entry.category),
+                error: None,
+            }),
+            Ok(false) => Ok(failure(format!("No memory found with key: {key}"))),
+            Err(e) => Ok(failure(format!("Failed to delete memory: {e}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use crate::tools::MemoryListTool;
+    use tempfile::TempDir;
+
+    fn test_mem() -> (TempDir, Arc<dyn Memory>) {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        (tmp, Arc::new(mem))
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let (_tmp, mem) = test_mem();
+        let tool = MemoryDeleteTool::new(mem);
+        assert_eq!(tool.name(), "memory_delete");
+        assert_eq!(tool.parameters_schema()["required"], json!(["key"]));
+    }
+
+    #[tokio::test]
+    async fn store_list_delete_then_recall() {
+        let (_tmp, mem) = test_mem();
+        mem.store("editor", "User edits in Helix", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("old_editor", "User edits in Vim", MemoryCategory::Core)
+            .await
+            .unwrap();
+
+        let list = MemoryListTool::new(mem.clone());
+        let listed = list.execute(json!({})).await.unwrap();
+        assert!(listed.output.contains("old_editor"), "{}", listed.output);
+
+        let result = MemoryDeleteTool::new(mem.clone())
+            .execute(json!({"key": "old_editor"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.output, "Deleted memory: old_editor [core]");
+
+        let listed = list.execute(json!({})).await.unwrap();
+        assert!(!listed.output.contains("old_editor"), "{}", listed.output);
+        let recalled = mem.recall("edits", 10).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].key, "editor");
+    }
+
+    #[tokio::test]
+    async fn delete_nonexistent_is_not_success() {
+        let (_tmp, mem) = test_mem();
+        let result = MemoryDeleteTool::new(mem)
+            .execute(json!({"key": "nope"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("No memory found with key: nope")
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_scoped_to_other_category_keeps_entry() {
+        let (_tmp, mem) = test_mem();
+        mem.store("standup", "10am", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let tool = MemoryDeleteTool::new(mem.clone());
+        let result = tool
+            .execute(json!({"key": "standup", "category": "core"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("in category 'daily'"));
+        assert!(mem.get("standup").await.unwrap().is_some());
+
+        let result = tool
+            .execute(json!({"key": "standup", "category": "daily"}))
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(mem.get("standup").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn delete_missing_key_param() {
+        let (_tmp, mem) = test_mem();
+        let result = MemoryDeleteTool::new(mem).execute(json!({})).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,212 @@
+use super::traits::{Tool, ToolResult};
+use crate::memory::{Memory, MemoryCategory};
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// Let the agent page through what it has stored, newest first
+pub struct MemoryListTool {
+    memory: Arc<dyn Memory>,
+}
+
+impl MemoryListTool {
+    pub fn new(memory: Arc<dyn Memory>) -> Self {
+        Self { memory }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryListTool {
+    fn name(&self) -> &str {
+        "memory_list"
+    }
+
+    fn description(&self) -> &str {
+        "List stored memories (key, category, timestamp), newest first. Use to review what you know and find stale entries to delete. Paginate with offset."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "category": {
+                    "type": "string",
+                    "description": "Only list this category: core, daily, conversation, or a custom name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Max entries to return (default: 20, max: 100)"
+                },
+                "offset": {
+                    "type": "integer",
+                    "description": "Entries to skip, for the next page (default: 0)"
+                }
+            }
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let category = args
+            .get("category")
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.trim().is_empty())
+            .map(MemoryCategory::parse);
+
+        #[allow(clippy::cast_possible_truncation)]
+        let limit = args
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(DEFAULT_LIMIT, |v| (v as usize).clamp(1, MAX_LIMIT));
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = args
+            .get("offset")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(0, |v| v as usize);
+
+        let entries = match self.memory.list(category.as_ref()).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to list memories: {e}")),
+                })
+            }
+        };
+
+        let total = entries.len();
+        if total == 0 {
+            return Ok(ToolResult {
+                success: true,
+                output: "No memories stored.".into(),
+                error: None,
+            });
+        }
+        if offset >= total {
+            return Ok(ToolResult {
+                success: true,
+                output: format!("No memories past offset {offset} ({total} in total)."),
+                error: None,
+            });
+        }
+
+        let page = &entries[offset..total.min(offset + limit)];
+        let mut output = format!(
+            "Memories {}-{} of {total}:\n",
+            offset + 1,
+            offset + page.len()
+        );
+        for entry in page {
+            let _ = writeln!(
+                output,
+                "- [{}] {} ({})",
+                entry.category, entry.key, entry.timestamp
+            );
+        }
+        if offset + page.len() < total {
+            let _ = write!(
+                output,
+                "More available: call again with offset={}",
+                offset + page.len()
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SqliteMemory;
+    use tempfile::TempDir;
+
+    fn test_mem() -> (TempDir, Arc<dyn Memory>) {
+        let tmp = TempDir::new().unwrap();
+        let mem = SqliteMemory::new(tmp.path()).unwrap();
+        (tmp, Arc::new(mem))
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let (_tmp, mem) = test_mem();
+        let tool = MemoryListTool::new(mem);
+        assert_eq!(tool.name(), "memory_list");
+        assert!(tool.is_read_only());
+        assert!(tool.parameters_schema()["properties"]["offset"].is_object());
+    }
+
+    #[tokio::test]
+    async fn list_empty() {
+        let (_tmp, mem) = test_mem();
+        let result = MemoryListTool::new(mem).execute(json!({})).await.unwrap();
+        assert!(result.success);
+        assert!(result.output.contains("No memories"));
+    }
+
+    #[tokio::test]
+    async fn list_filters_by_category() {
+        let (_tmp, mem) = test_mem();
+        mem.store("lang", "Rust", MemoryCategory::Core)
+            .await
+            .unwrap();
+        mem.store("standup", "10am", MemoryCategory::Daily)
+            .await
+            .unwrap();
+
+        let tool = MemoryListTool::new(mem);
+        let all = tool.execute(json!({})).await.unwrap();
+        assert!(all.output.contains("of 2"), "{}", all.output);
+        assert!(all.output.contains("- [core] lang ("));
+        assert!(all.output.contains("- [daily] standup ("));
+
+        let daily = tool.execute(json!({"category": "daily"})).await.unwrap();
+        assert!(daily.output.contains("standup"));
+        assert!(!daily.output.contains("lang"));
+    }
+
+    #[tokio::test]
+    async fn list_paginates() {
+        let (_tmp, mem) = test_mem();
+        for i in 0..5 {
+            mem.store(&format!("k{i}"), "v", MemoryCategory::Core)
+                .await
+                .unwrap();
+        }
+
+        let tool = MemoryListTool::new(mem);
+        let first = tool.execute(json!({"limit": 2})).await.unwrap();
+        assert!(
+            first.output.starts_with("Memories 1-2 of 5:"),
+            "{}",
+            first.output
+        );
+        assert!(first.output.contains("offset=2"));
+
+        let last = tool
+            .execute(json!({"limit": 2, "offset": 4}))
+            .await
+            .unwrap();
+        assert!(
+            last.output.starts_with("Memories 5-5 of 5:"),
+            "{}",
+            last.output
+        );
+        assert!(!last.output.contains("More available"));
+
+        let past = tool.execute(json!({"offset": 9})).await.unwrap();
+        assert!(past.success);
+        assert!(past.output.contains("No memories past offset 9"));
+    }
+}
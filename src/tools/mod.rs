@@ -1,26 +1,54 @@
+pub mod artifact;
 pub mod browser;
 pub mod browser_open;
 pub mod composio;
+pub mod context_get;
+pub mod context_set;
+pub mod file_append;
+pub mod file_edit;
 pub mod file_read;
 pub mod file_write;
+pub mod http_request;
+pub mod make_directory;
+pub mod memory_delete;
 pub mod memory_forget;
+pub mod memory_list;
 pub mod memory_recall;
 pub mod memory_store;
+pub mod schedule;
+pub mod schedule_cancel;
+pub mod schedule_list;
+pub mod search;
 pub mod shell;
 pub mod traits;
+pub mod web_search;
 
+pub use artifact::ArtifactTool;
 pub use browser::BrowserTool;
 pub use browser_open::BrowserOpenTool;
 pub use composio::ComposioTool;
+pub use context_get::ContextGetTool;
+pub use context_set::{ContextSetTool, SessionContext};
+pub use file_append::FileAppendTool;
+pub use file_edit::FileEditTool;
 pub use file_read::FileReadTool;
 pub use file_write::FileWriteTool;
+pub use http_request::HttpTool;
+pub use make_directory::MakeDirectoryTool;
+pub use memory_delete::MemoryDeleteTool;
 pub use memory_forget::MemoryForgetTool;
+pub use memory_list::MemoryListTool;
 pub use memory_recall::MemoryRecallTool;
 pub use memory_store::MemoryStoreTool;
+pub use schedule::ScheduleTool;
+pub use schedule_cancel::ScheduleCancelTool;
+pub use schedule_list::ScheduleListTool;
+pub use search::SearchTool;
 pub use shell::ShellTool;
 pub use traits::Tool;
 #[allow(unused_imports)]
-pub use traits::{ToolResult, ToolSpec};
+pub use traits::{ToolCall, ToolResult, ToolSpec};
+pub use web_search::WebSearchTool;
 
 use crate::memory::Memory;
 use crate::runtime::{NativeRuntime, RuntimeAdapter};
@@ -38,9 +66,12 @@ pub fn default_tools_with_runtime(
     runtime: Arc<dyn RuntimeAdapter>,
 ) -> Vec<Box<dyn Tool>> {
     vec![
-        Box::new(ShellTool::new(security.clone(), runtime)),
-        Box::new(FileReadTool::new(security.clone())),
-        Box::new(FileWriteTool::new(security)),
+        Box::new(ShellTool::new(security.clone(), runtime.clone())),
+        Box::new(FileReadTool::with_runtime(
+            security.clone(),
+            runtime.clone(),
+        )),
+        Box::new(FileWriteTool::with_runtime(security, runtime)),
     ]
 }
 
@@ -50,6 +81,7 @@ pub fn all_tools(
     memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
+    search_config: &crate::config::SearchConfig,
 ) -> Vec<Box<dyn Tool>> {
     all_tools_with_runtime(
         security,
@@ -57,6 +89,7 @@ pub fn all_tools(
         memory,
         composio_key,
         browser_config,
+        search_config,
     )
 }
 
@@ -67,16 +100,48 @@ pub fn all_tools_with_runtime(
     memory: Arc<dyn Memory>,
     composio_key: Option<&str>,
     browser_config: &crate::config::BrowserConfig,
+    search_config: &crate::config::SearchConfig,
 ) -> Vec<Box<dyn Tool>> {
+    let local_files = runtime.remote_files().is_none();
+    // Lives as long as this registry, i.e. one session or single run
+    let context = SessionContext::default();
     let mut tools: Vec<Box<dyn Tool>> = vec![
-        Box::new(ShellTool::new(security.clone(), runtime)),
-        Box::new(FileReadTool::new(security.clone())),
-        Box::new(FileWriteTool::new(security.clone())),
+        Box::new(ShellTool::new(security.clone(), runtime.clone())),
+        Box::new(FileReadTool::with_runtime(
+            security.clone(),
+            runtime.clone(),
+        )),
+        Box::new(FileWriteTool::with_runtime(
+            security.clone(),
+            runtime.clone(),
+        )),
+        Box::new(FileEditTool::with_runtime(
+            security.clone(),
+            runtime.clone(),
+        )),
+        Box::new(SearchTool::with_runtime(security.clone(), runtime.clone())),
+        Box::new(ArtifactTool::with_runtime(security.clone(), runtime)),
+        Box::new(HttpTool::new(security.clone())),
+        Box::new(WebSearchTool::new(security.clone(), search_config.clone())),
         Box::new(MemoryStoreTool::new(memory.clone())),
         Box::new(MemoryRecallTool::new(memory.clone())),
-        Box::new(MemoryForgetTool::new(memory)),
+        Box::new(MemoryForgetTool::new(memory.clone())),
+        Box::new(MemoryListTool::new(memory.clone())),
+        Box::new(MemoryDeleteTool::new(memory)),
+        Box::new(ScheduleTool::new(security.clone())),
+        Box::new(ScheduleListTool::new(security.clone())),
+        Box::new(ScheduleCancelTool::new(security.clone())),
+        Box::new(ContextSetTool::new(context.clone())),
+        Box::new(ContextGetTool::new(context)),
     ];
 
+    // These work on the local filesystem only; with remote file access the
+    // agent uses `shell` instead
+    if local_files {
+        tools.push(Box::new(FileAppendTool::new(security.clone())));
+        tools.push(Box::new(MakeDirectoryTool::new(security.clone())));
+    }
+
     if browser_config.enabled {
         // Add legacy browser_open tool for simple URL opening
         tools.push(Box::new(BrowserOpenTool::new(
@@ -103,7 +168,7 @@ pub fn all_tools_with_runtime(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{BrowserConfig, MemoryConfig};
+    use crate::config::{BrowserConfig, MemoryConfig, SearchConfig};
     use tempfile::TempDir;
 
     #[test]
@@ -130,9 +195,23 @@ mod tests {
             session_name: None,
         };
 
-        let tools = all_tools(&security, mem, None, &browser);
+        let tools = all_tools(&security, mem, None, &browser, &SearchConfig::default());
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(!names.contains(&"browser_open"));
+        assert!(names.contains(&"http_request"));
+        assert!(names.contains(&"web_search"));
+        assert!(names.contains(&"file_edit"));
+        assert!(names.contains(&"file_append"));
+        assert!(names.contains(&"make_directory"));
+        assert!(names.contains(&"search"));
+        assert!(names.contains(&"artifact"));
+        assert!(names.contains(&"memory_list"));
+        assert!(names.contains(&"memory_delete"));
+        assert!(names.contains(&"schedule"));
+        assert!(names.contains(&"schedule_list"));
+        assert!(names.contains(&"schedule_cancel"));
+        assert!(names.contains(&"context_set"));
+        assert!(names.contains(&"context_get"));
     }
 
     #[test]
@@ -152,7 +231,7 @@ mod tests {
             session_name: None,
         };
 
-        let tools = all_tools(&security, mem, None, &browser);
+        let tools = all_tools(&security, mem, None, &browser, &SearchConfig::default());
         let names: Vec<&str> = tools.iter().map(|t| t.name()).collect();
         assert!(names.contains(&"browser_open"));
     }
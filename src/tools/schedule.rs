@@ -0,0 +1,203 @@
+use super::traits::{Tool, ToolResult};
+use crate::cron::messages::{self, MessageSchedule};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+const DEFAULT_CHANNEL: &str = "cli";
+const DEFAULT_RECIPIENT: &str = "user";
+
+/// Let the agent send itself a message later, once or on a cron schedule.
+/// Messages live in the workspace and survive restarts.
+pub struct ScheduleTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ScheduleTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleTool {
+    fn name(&self) -> &str {
+        "schedule"
+    }
+
+    fn description(&self) -> &str {
+        "Schedule a message to yourself, delivered back into the conversation after a delay (e.g. \"30m\") or on a cron schedule (e.g. \"0 9 * * 1-5\"). Use for reminders and follow-ups. See pending ones with schedule_list."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "message": {
+                    "type": "string",
+                    "description": "What you want to be told when it fires"
+                },
+                "delay": {
+                    "type": "string",
+                    "description": "Fire once after this long: a number with s, m, h or d, combinable (e.g. 90s, 1h30m, 2d)"
+                },
+                "cron": {
+                    "type": "string",
+                    "description": "Fire repeatedly on this cron expression (UTC), e.g. '0 9 * * *'. Use instead of delay."
+                },
+                "channel": {
+                    "type": "string",
+                    "description": "Channel to deliver on, e.g. telegram (default: cli)"
+                },
+                "recipient": {
+                    "type": "string",
+                    "description": "Who to deliver to on that channel; use the sender of the current conversation (default: user)"
+                }
+            },
+            "required": ["message"]
+        })
+    }
+
+    fn idempotency_key_supported(&self, _args: &serde_json::Value) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let message = args
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'message' parameter"))?;
+        let text_arg = |name: &str| {
+            args.get(name)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+        };
+        let channel = text_arg("channel").unwrap_or(DEFAULT_CHANNEL);
+        let recipient = text_arg("recipient").unwrap_or(DEFAULT_RECIPIENT);
+
+        if !self.security.can_act() {
+            return Ok(failure("Action blocked: autonomy is read-only".into()));
+        }
+        let schedule = match MessageSchedule::parse(text_arg("delay"), text_arg("cron")) {
+            Ok(schedule) => schedule,
+            Err(e) => return Ok(failure(e.to_string())),
+        };
+        if !self.security.record_action() {
+            return Ok(failure("Action blocked: rate limit exceeded".into()));
+        }
+
+        match messages::add_message(
+            &self.security.workspace_dir,
+            schedule,
+            message,
+            channel,
+            recipient,
+        ) {
+            Ok(scheduled) => Ok(ToolResult {
+                success: true,
+                output: format!(
+                    "Scheduled {} ({}), next at {} on {channel}",
+                    scheduled.id,
+                    scheduled.schedule,
+                    scheduled.next_run.to_rfc3339()
+                ),
+                error: None,
+            }),
+            Err(e) => Ok(failure(format!("Failed to schedule message: {e}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    fn tool_in(tmp: &TempDir) -> ScheduleTool {
+        ScheduleTool::new(Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+        assert_eq!(tool.name(), "schedule");
+        assert!(!tool.is_read_only());
+        assert_eq!(tool.parameters_schema()["required"], json!(["message"]));
+    }
+
+    #[tokio::test]
+    async fn schedules_delay_and_cron() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+
+        let once = tool
+            .execute(json!({"message": "stretch", "delay": "45m"}))
+            .await
+            .unwrap();
+        assert!(once.success, "{:?}", once.error);
+        assert!(once.output.contains("once, after 45m"), "{}", once.output);
+        assert!(once.output.ends_with("on cli"));
+
+        let daily = tool
+            .execute(json!({
+                "message": "check the build",
+                "cron": "0 9 * * *",
+                "channel": "telegram",
+                "recipient": "alice"
+            }))
+            .await
+            .unwrap();
+        assert!(daily.success, "{:?}", daily.error);
+
+        let stored = messages::list_messages(tmp.path()).unwrap();
+        assert_eq!(stored.len(), 2);
+        let daily = stored.iter().find(|m| m.channel == "telegram").unwrap();
+        assert_eq!(daily.recipient, "alice");
+        assert_eq!(daily.schedule, MessageSchedule::Cron("0 9 * * *".into()));
+    }
+
+    #[tokio::test]
+    async fn bad_schedule_is_not_success() {
+        let tmp = TempDir::new().unwrap();
+        let result = tool_in(&tmp)
+            .execute(json!({"message": "hi", "delay": "soon"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("Invalid delay 'soon'"));
+        assert!(messages::list_messages(tmp.path()).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn blocked_in_read_only_mode() {
+        let tmp = TempDir::new().unwrap();
+        let tool = ScheduleTool::new(Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::ReadOnly,
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }));
+        let result = tool
+            .execute(json!({"message": "hi", "delay": "1m"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("read-only"));
+    }
+}
@@ -0,0 +1,121 @@
+use super::traits::{Tool, ToolResult};
+use crate::cron::messages;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Let the agent drop a message it scheduled for itself
+pub struct ScheduleCancelTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ScheduleCancelTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+fn failure(error: String) -> ToolResult {
+    ToolResult {
+        success: false,
+        output: String::new(),
+        error: Some(error),
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleCancelTool {
+    fn name(&self) -> &str {
+        "schedule_cancel"
+    }
+
+    fn description(&self) -> &str {
+        "Cancel a scheduled message by id (find ids with schedule_list). Recurring messages stop for good."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "string",
+                    "description": "The id of the scheduled message to cancel"
+                }
+            },
+            "required": ["id"]
+        })
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let id = args
+            .get("id")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .ok_or_else(|| anyhow::anyhow!("Missing 'id' parameter"))?;
+
+        if !self.security.can_act() {
+            return Ok(failure("Action blocked: autonomy is read-only".into()));
+        }
+
+        match messages::cancel_message(&self.security.workspace_dir, id) {
+            Ok(true) => Ok(ToolResult {
+                success: true,
+                output: format!("Cancelled scheduled message: {id}"),
+                error: None,
+            }),
+            Ok(false) => Ok(failure(format!("No scheduled message with id: {id}"))),
+            Err(e) => Ok(failure(format!("Failed to cancel scheduled message: {e}"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cron::messages::MessageSchedule;
+    use tempfile::TempDir;
+
+    fn tool_in(tmp: &TempDir) -> ScheduleCancelTool {
+        ScheduleCancelTool::new(Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+        assert_eq!(tool.name(), "schedule_cancel");
+        assert_eq!(tool.parameters_schema()["required"], json!(["id"]));
+    }
+
+    #[tokio::test]
+    async fn cancels_then_reports_missing() {
+        let tmp = TempDir::new().unwrap();
+        let scheduled = messages::add_message(
+            tmp.path(),
+            MessageSchedule::Cron("0 9 * * *".into()),
+            "standup",
+            "cli",
+            "user",
+        )
+        .unwrap();
+
+        let tool = tool_in(&tmp);
+        let result = tool.execute(json!({"id": scheduled.id})).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert!(messages::list_messages(tmp.path()).unwrap().is_empty());
+
+        let again = tool.execute(json!({"id": scheduled.id})).await.unwrap();
+        assert!(!again.success);
+        assert!(again.error.unwrap().starts_with("No scheduled message"));
+    }
+
+    #[tokio::test]
+    async fn cancel_missing_id_param() {
+        let tmp = TempDir::new().unwrap();
+        assert!(tool_in(&tmp).execute(json!({})).await.is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use super::traits::{Tool, ToolResult};
+use crate::cron::messages;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde_json::json;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// Let the agent see the messages it has scheduled for itself, soonest first
+pub struct ScheduleListTool {
+    security: Arc<SecurityPolicy>,
+}
+
+impl ScheduleListTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self { security }
+    }
+}
+
+#[async_trait]
+impl Tool for ScheduleListTool {
+    fn name(&self) -> &str {
+        "schedule_list"
+    }
+
+    fn description(&self) -> &str {
+        "List pending scheduled messages (id, schedule, next run, channel, text), soonest first. Use the id with schedule_cancel."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, _args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let scheduled = match messages::list_messages(&self.security.workspace_dir) {
+            Ok(scheduled) => scheduled,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(format!("Failed to list scheduled messages: {e}")),
+                })
+            }
+        };
+
+        if scheduled.is_empty() {
+            return Ok(ToolResult {
+                success: true,
+                output: "No scheduled messages.".into(),
+                error: None,
+            });
+        }
+
+        let mut output = format!("{} scheduled message(s):\n", scheduled.len());
+        for message in &scheduled {
+            let _ = writeln!(
+                output,
+                "- {} [{}] next {} on {}/{}: {}",
+                message.id,
+                message.schedule,
+                message.next_run.to_rfc3339(),
+                message.channel,
+                message.recipient,
+                message.message
+            );
+        }
+        Ok(ToolResult {
+            success: true,
+            output,
+            error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cron::messages::MessageSchedule;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn tool_in(tmp: &TempDir) -> ScheduleListTool {
+        ScheduleListTool::new(Arc::new(SecurityPolicy {
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        }))
+    }
+
+    #[test]
+    fn name_and_schema() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+        assert_eq!(tool.name(), "schedule_list");
+        assert!(tool.is_read_only());
+    }
+
+    #[tokio::test]
+    async fn lists_pending_messages() {
+        let tmp = TempDir::new().unwrap();
+        let tool = tool_in(&tmp);
+        let empty = tool.execute(json!({})).await.unwrap();
+        assert_eq!(empty.output, "No scheduled messages.");
+
+        let scheduled = messages::add_message(
+            tmp.path(),
+            MessageSchedule::Delay(Duration::from_secs(600)),
+            "stretch",
+            "cli",
+            "user",
+        )
+        .unwrap();
+        let listed = tool.execute(json!({})).await.unwrap();
+        assert!(listed.success);
+        assert!(listed.output.starts_with("1 scheduled message(s):"));
+        assert!(
+            listed
+                .output
+                .contains(&format!("- {} [once, after 10m]", scheduled.id)),
+            "{}",
+            listed.output
+        );
+        assert!(listed.output.contains("on cli/user: stretch"));
+    }
+}
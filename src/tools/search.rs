@@ -0,0 +1,430 @@
+use super::traits::{Tool, ToolResult};
+use crate::runtime::{NativeRuntime, RuntimeAdapter};
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use grep_regex::RegexMatcherBuilder;
+use grep_searcher::sinks::Lossy;
+use grep_searcher::{BinaryDetection, SearcherBuilder};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Most paths (glob) or matching lines (grep) returned per call.
+const MAX_RESULTS: usize = 200;
+
+/// Output is cut off past this many bytes.
+const MAX_OUTPUT_BYTES: usize = 64 * 1024;
+
+/// Matching lines longer than this are shortened.
+const MAX_LINE_CHARS: usize = 300;
+
+/// Find files by glob and search their contents by regex, honoring
+/// `.gitignore` and skipping hidden files, binaries and symlinks
+pub struct SearchTool {
+    security: Arc<SecurityPolicy>,
+    runtime: Arc<dyn RuntimeAdapter>,
+}
+
+impl SearchTool {
+    pub fn new(security: Arc<SecurityPolicy>) -> Self {
+        Self::with_runtime(security, Arc::new(NativeRuntime::new()))
+    }
+
+    /// Refuse to search when `runtime` keeps the workspace on another host.
+    pub fn with_runtime(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
+        Self { security, runtime }
+    }
+}
+
+/// What one call asks for, after validation.
+struct Query {
+    mode: Mode,
+    /// Directory the search starts from (already checked against the policy)
+    base: PathBuf,
+    /// Paths are reported relative to this
+    root: PathBuf,
+}
+
+enum Mode {
+    Glob(String),
+    Grep {
+        pattern: String,
+        include: Option<String>,
+        case_insensitive: bool,
+    },
+}
+
+/// Collected output, stopping once `MAX_RESULTS` or `MAX_OUTPUT_BYTES` is hit.
+#[derive(Default)]
+struct Results {
+    out: String,
+    count: usize,
+    truncated: bool,
+}
+
+impl Results {
+    /// Add one line; returns `false` once no more fit.
+    fn push(&mut self, line: &str) -> bool {
+        if self.count >= MAX_RESULTS || self.out.len() + line.len() + 1 > MAX_OUTPUT_BYTES {
+            self.truncated = true;
+            return false;
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+        self.count += 1;
+        true
+    }
+
+    fn finish(mut self, what: &str) -> String {
+        if self.count == 0 {
+            return format!("No {what} found");
+        }
+        if self.truncated {
+            let _ = write!(
+                self.out,
+                "... [truncated at {} {what}; narrow the pattern or path]",
+                self.count
+            );
+        }
+        self.out.trim_end().to_string()
+    }
+}
+
+fn walker(base: &Path, globs: Option<&str>) -> Result<ignore::Walk, String> {
+    let mut builder = WalkBuilder::new(base);
+    // Honor .gitignore even when the workspace isn't a git repository
+    builder.require_git(false).follow_links(false);
+    if let Some(glob) = globs {
+        let overrides = OverrideBuilder::new(base)
+            .add(glob)
+            .and_then(|b| b.build())
+            .map_err(|e| format!("Invalid glob '{glob}': {e}"))?;
+        builder.overrides(overrides);
+    }
+    Ok(builder.build())
+}
+
+/// Regular files the walk yields; directories, symlinks and unreadable
+/// entries are skipped.
+fn files(walk: ignore::Walk) -> impl Iterator<Item = PathBuf> {
+    walk.filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(ignore::DirEntry::into_path)
+}
+
+fn display(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+fn run_glob(query: &Query, pattern: &str) -> Result<String, String> {
+    let mut results = Results::default();
+    for path in files(walker(&query.base, Some(pattern))?) {
+        if !results.push(&display(&query.root, &path)) {
+            break;
+        }
+    }
+    Ok(results.finish("files"))
+}
+
+fn run_grep(
+    query: &Query,
+    pattern: &str,
+    include: Option<&str>,
+    case_insensitive: bool,
+) -> Result<String, String> {
+    let matcher = RegexMatcherBuilder::new()
+        .case_insensitive(case_insensitive)
+        .build(pattern)
+        .map_err(|e| format!("Invalid regex '{pattern}': {e}"))?;
+    let mut searcher = SearcherBuilder::new()
+        .binary_detection(BinaryDetection::quit(b'\x00'))
+        .line_number(true)
+        .build();
+
+    let mut results = Results::default();
+    for path in files(walker(&query.base, include)?) {
+        let shown = display(&query.root, &path);
+        let mut full = false;
+        // Unreadable files are skipped like binary ones
+        let _ = searcher.search_path(
+            &matcher,
+            &path,
+            Lossy(|line_number, line| {
+                let line = line.trim_end_matches(['\r', '\n']);
+                let mut text: String = line.chars().take(MAX_LINE_CHARS).collect();
+                if text.len() < line.len() {
+                    text.push('…');
+                }
+                full = !results.push(&format!("{shown}:{line_number}: {text}"));
+                Ok(!full)
+            }),
+        );
+        if full {
+            break;
+        }
+    }
+    Ok(results.finish("matches"))
+}
+
+#[async_trait]
+impl Tool for SearchTool {
+    fn name(&self) -> &str {
+        "search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the workspace. mode \"glob\" lists files matching a pattern like \
+         \"**/*.rs\"; mode \"grep\" finds lines matching a regex and returns \
+         path:line: text, optionally only in files matching include. Respects \
+         .gitignore and skips hidden and binary files."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "mode": {
+                    "type": "string",
+                    "enum": ["glob", "grep"],
+                    "description": "\"glob\" to find files by name, \"grep\" to search file contents"
+                },
+                "pattern": {
+                    "type": "string",
+                    "description": "Glob (glob mode) or regular expression (grep mode)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Directory to search, relative to the workspace (default: the workspace)"
+                },
+                "include": {
+                    "type": "string",
+                    "description": "grep mode: only search files matching this glob, e.g. \"*.rs\""
+                },
+                "case_insensitive": {
+                    "type": "boolean",
+                    "description": "grep mode: ignore case",
+                    "default": false
+                }
+            },
+            "required": ["mode", "pattern"]
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let mode = args
+            .get("mode")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'mode' parameter"))?;
+        let pattern = args
+            .get("pattern")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'pattern' parameter"))?
+            .to_string();
+        let path = args.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+
+        let refuse = |error: String| {
+            Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(error),
+            })
+        };
+
+        if self.runtime.remote_files().is_some() {
+            return refuse(format!(
+                "search is not available with the {} runtime; use shell instead",
+                self.runtime.name()
+            ));
+        }
+
+        let mode = match mode {
+            "glob" => Mode::Glob(pattern),
+            "grep" => Mode::Grep {
+                pattern,
+                include: args
+                    .get("include")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                case_insensitive: args
+                    .get("case_insensitive")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false),
+            },
+            other => return refuse(format!("Unknown mode '{other}' (expected glob or grep)")),
+        };
+
+        // Security check: validate path is within workspace
+        if !self.security.is_path_allowed(path) {
+            return refuse(format!("Path not allowed by security policy: {path}"));
+        }
+        let base = match self
+            .security
+            .check_path(&self.security.workspace_dir.join(path))
+        {
+            Ok(base) if base.is_dir() => base,
+            Ok(_) => return refuse(format!("Not a directory: {path}")),
+            Err(e) => return refuse(e),
+        };
+        let root = self
+            .security
+            .workspace_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.security.workspace_dir.clone());
+
+        let query = Query { mode, base, root };
+        let outcome = tokio::task::spawn_blocking(move || match &query.mode {
+            Mode::Glob(pattern) => run_glob(&query, pattern),
+            Mode::Grep {
+                pattern,
+                include,
+                case_insensitive,
+            } => run_grep(&query, pattern, include.as_deref(), *case_insensitive),
+        })
+        .await?;
+
+        match outcome {
+            Ok(output) => Ok(ToolResult {
+                success: true,
+                output,
+                error: None,
+            }),
+            Err(e) => refuse(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::security::AutonomyLevel;
+    use tempfile::TempDir;
+
+    /// src/{main.rs,lib.rs}, docs/guide.md, a gitignored target/ and a
+    /// hidden .cache/ that both contain matches.
+    fn tree() -> (TempDir, SearchTool) {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for dir in ["src", "docs", "target/debug", ".cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    run();\n}\n").unwrap();
+        std::fs::write(
+            root.join("src/lib.rs"),
+            "pub fn run() {}\n// TODO: handle Errors\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("docs/guide.md"), "Call run() to start.\n").unwrap();
+        std::fs::write(root.join("target/debug/build.rs"), "fn run() {}\n").unwrap();
+        std::fs::write(root.join(".cache/run.rs"), "fn run() {}\n").unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join("src/blob.bin"), b"run\0\x01\x02").unwrap();
+
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: root.to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        (tmp, SearchTool::new(security))
+    }
+
+    fn sorted_lines(output: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = output.lines().collect();
+        lines.sort_unstable();
+        lines
+    }
+
+    #[tokio::test]
+    async fn glob_lists_matching_files_and_skips_ignored() {
+        let (_tmp, tool) = tree();
+        let result = tool
+            .execute(json!({"mode": "glob", "pattern": "**/*.rs"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(sorted_lines(&result.output), ["src/lib.rs", "src/main.rs"]);
+
+        let docs = tool
+            .execute(json!({"mode": "glob", "pattern": "*.md", "path": "docs"}))
+            .await
+            .unwrap();
+        assert_eq!(docs.output, "docs/guide.md");
+    }
+
+    #[tokio::test]
+    async fn grep_returns_paths_and_line_numbers() {
+        let (_tmp, tool) = tree();
+        let result = tool
+            .execute(json!({"mode": "grep", "pattern": r"run\(\)"}))
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            sorted_lines(&result.output),
+            [
+                "docs/guide.md:1: Call run() to start.",
+                "src/lib.rs:1: pub fn run() {}",
+                "src/main.rs:2:     run();",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn grep_include_and_case_insensitive() {
+        let (_tmp, tool) = tree();
+        let result = tool
+            .execute(json!({
+                "mode": "grep",
+                "pattern": "todo: handle errors",
+                "include": "*.rs",
+                "case_insensitive": true
+            }))
+            .await
+            .unwrap();
+        assert_eq!(result.output, "src/lib.rs:2: // TODO: handle Errors");
+
+        let md_only = tool
+            .execute(json!({"mode": "grep", "pattern": "fn", "include": "*.md"}))
+            .await
+            .unwrap();
+        assert!(md_only.success);
+        assert_eq!(md_only.output, "No matches found");
+    }
+
+    #[tokio::test]
+    async fn results_are_capped() {
+        let (tmp, tool) = tree();
+        let many = "hit\n".repeat(MAX_RESULTS + 50);
+        std::fs::write(tmp.path().join("docs/many.txt"), many).unwrap();
+        let result = tool
+            .execute(json!({"mode": "grep", "pattern": "^hit$"}))
+            .await
+            .unwrap();
+        assert_eq!(result.output.lines().count(), MAX_RESULTS + 1);
+        assert!(result.output.ends_with("narrow the pattern or path]"));
+    }
+
+    #[tokio::test]
+    async fn bad_input_and_escapes_are_refused() {
+        let (_tmp, tool) = tree();
+        for args in [
+            json!({"mode": "grep", "pattern": "("}),
+            json!({"mode": "find", "pattern": "x"}),
+            json!({"mode": "glob", "pattern": "*", "path": "../"}),
+            json!({"mode": "glob", "pattern": "*", "path": "/etc"}),
+            json!({"mode": "glob", "pattern": "*", "path": "src/main.rs"}),
+        ] {
+            let result = tool.execute(args.clone()).await.unwrap();
+            assert!(!result.success, "{args}");
+        }
+    }
+}
@@ -1,10 +1,13 @@
+use super::context_set::current_session;
 use super::traits::{Tool, ToolResult};
 use crate::runtime::RuntimeAdapter;
 use crate::security::SecurityPolicy;
 use async_trait::async_trait;
 use serde_json::json;
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
 
 /// Maximum shell command execution time before kill.
 const SHELL_TIMEOUT_SECS: u64 = 60;
@@ -15,17 +18,190 @@ const MAX_OUTPUT_BYTES: usize = 1_048_576;
 const SAFE_ENV_VARS: &[&str] = &[
     "PATH", "HOME", "TERM", "LANG", "LC_ALL", "LC_CTYPE", "USER", "SHELL", "TMPDIR",
 ];
+/// Variables the `env` argument may not set: they change which programs
+/// run, which would sidestep the command allowlist.
+const BLOCKED_ENV_VARS: &[&str] = &["PATH", "IFS", "ENV", "BASH_ENV"];
+const BLOCKED_ENV_PREFIXES: &[&str] = &["LD_", "DYLD_"];
+/// Sessions whose working directory is kept; the one moved least recently
+/// goes back to the workspace root beyond this.
+const MAX_SESSION_DIRS: usize = 256;
 
 /// Shell command execution tool with sandboxing
+///
+/// The working directory persists across calls: a bare `cd <dir>` moves it,
+/// and later commands (and relative `cwd` arguments) resolve against it.
+/// Each session run through `SessionContext::scope` has its own; calls
+/// outside a scope share the unnamed session's.
 pub struct ShellTool {
     security: Arc<SecurityPolicy>,
     runtime: Arc<dyn RuntimeAdapter>,
+    /// Resolved working directory per session, with when it was set; a
+    /// session without one is at the workspace root
+    cwds: Mutex<HashMap<String, (Instant, PathBuf)>>,
 }
 
 impl ShellTool {
     pub fn new(security: Arc<SecurityPolicy>, runtime: Arc<dyn RuntimeAdapter>) -> Self {
-        Self { security, runtime }
+        Self {
+            security,
+            runtime,
+            cwds: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn current_dir(&self) -> Option<PathBuf> {
+        self.lock_cwds()
+            .get(&current_session())
+            .map(|(_, dir)| dir.clone())
+    }
+
+    fn set_current_dir(&self, dir: Option<PathBuf>) {
+        let mut cwds = self.lock_cwds();
+        let session = current_session();
+        let Some(dir) = dir else {
+            cwds.remove(&session);
+            return;
+        };
+        if !cwds.contains_key(&session) && cwds.len() >= MAX_SESSION_DIRS {
+            let oldest = cwds
+                .iter()
+                .min_by_key(|(_, (set_at, _))| *set_at)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                cwds.remove(&oldest);
+            }
+        }
+        cwds.insert(session, (Instant::now(), dir));
+    }
+
+    fn lock_cwds(&self) -> MutexGuard<'_, HashMap<String, (Instant, PathBuf)>> {
+        self.cwds
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Resolve `requested` against the current directory, keeping it inside
+    /// the paths the security policy allows.
+    fn resolve_dir(&self, requested: &str) -> Result<PathBuf, String> {
+        let base = self
+            .current_dir()
+            .unwrap_or_else(|| self.security.workspace_dir.clone());
+        let resolved = self.security.check_path(&base.join(requested))?;
+        if !resolved.is_dir() {
+            return Err(format!("Not a directory: {requested}"));
+        }
+        Ok(resolved)
+    }
+
+    /// Handle a bare `cd <dir>` by moving the persistent working directory.
+    fn change_dir(&self, target: &str) -> ToolResult {
+        let resolved = if target.is_empty() {
+            None
+        } else {
+            match self.resolve_dir(target) {
+                Ok(dir) => Some(dir),
+                Err(e) => {
+                    return ToolResult {
+                        success: false,
+                        output: String::new(),
+                        error: Some(e),
+                    }
+                }
+            }
+        };
+        let shown = resolved
+            .as_deref()
+            .map_or_else(|| ".".to_string(), |dir| self.display_dir(dir));
+        self.set_current_dir(resolved);
+        ToolResult {
+            success: true,
+            output: format!("Working directory: {shown}"),
+            error: None,
+        }
+    }
+
+    /// `dir` relative to the workspace when it is inside it, else absolute.
+    fn display_dir(&self, dir: &Path) -> String {
+        let root = self
+            .security
+            .workspace_dir
+            .canonicalize()
+            .unwrap_or_else(|_| self.security.workspace_dir.clone());
+        match dir.strip_prefix(&root) {
+            Ok(rel) if rel.as_os_str().is_empty() => ".".into(),
+            Ok(rel) => rel.display().to_string(),
+            Err(_) => dir.display().to_string(),
+        }
+    }
+
+    /// Resolve the call's working directory and extra environment. The
+    /// runtime starts in the workspace, so the command is prefixed with a
+    /// `cd` from there; containerized and remote runtimes follow it too.
+    fn prepare(
+        &self,
+        command: &str,
+        args: &serde_json::Value,
+    ) -> Result<(String, Vec<(String, String)>), String> {
+        let dir = match args.get("cwd").and_then(|v| v.as_str()) {
+            Some(cwd) => Some(self.resolve_dir(cwd)?),
+            None => self.current_dir(),
+        };
+        let env = parse_env(args)?;
+        let command = match dir.as_deref().map(|dir| self.display_dir(dir)) {
+            Some(dir) if dir != "." => format!("cd {} && {command}", shell_quote(&dir)),
+            _ => command.to_string(),
+        };
+        Ok((command, env))
+    }
+}
+
+/// The target of a command that is nothing but `cd <dir>` (or bare `cd`).
+fn bare_cd(command: &str) -> Option<&str> {
+    let rest = command.trim().strip_prefix("cd")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let target = rest.trim();
+    let plain =
+        !target.contains(|c: char| c.is_whitespace() || ";&|`$<>(){}*?'\"\\~!#".contains(c));
+    plain.then_some(target)
+}
+
+/// Parse the `env` argument, refusing variables that could change which
+/// binary a command runs.
+fn parse_env(args: &serde_json::Value) -> Result<Vec<(String, String)>, String> {
+    let Some(env) = args.get("env").filter(|v| !v.is_null()) else {
+        return Ok(Vec::new());
+    };
+    let env = env
+        .as_object()
+        .ok_or("'env' must be an object of string values")?;
+    let mut vars = Vec::with_capacity(env.len());
+    for (name, value) in env {
+        let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("Invalid environment variable name: {name:?}"));
+        }
+        let upper = name.to_ascii_uppercase();
+        if BLOCKED_ENV_VARS.contains(&upper.as_str())
+            || BLOCKED_ENV_PREFIXES.iter().any(|p| upper.starts_with(p))
+        {
+            return Err(format!(
+                "Refusing to set {name}: it can change which programs run"
+            ));
+        }
+        let value = value
+            .as_str()
+            .ok_or_else(|| format!("Environment variable {name} must be a string"))?;
+        vars.push((name.clone(), value.to_string()));
     }
+    Ok(vars)
+}
+
+/// Quote `s` as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
 }
 
 #[async_trait]
@@ -35,7 +211,7 @@ impl Tool for ShellTool {
     }
 
     fn description(&self) -> &str {
-        "Execute a shell command in the workspace directory"
+        "Execute a shell command. Starts in the workspace directory; a bare `cd <dir>` changes the working directory for later calls"
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -50,12 +226,25 @@ impl Tool for ShellTool {
                     "type": "boolean",
                     "description": "Set true to explicitly approve medium/high-risk commands in supervised mode",
                     "default": false
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run this command in, relative to the current working directory"
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra environment variables for this command"
                 }
             },
             "required": ["command"]
         })
     }
 
+    fn concurrency_safe(&self) -> bool {
+        false
+    }
+
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
         let command = args
             .get("command")
@@ -74,8 +263,12 @@ impl Tool for ShellTool {
             });
         }
 
-        match self.security.validate_command_execution(command, approved) {
-            Ok(_) => {}
+        if let Some(target) = bare_cd(command) {
+            return Ok(self.change_dir(target));
+        }
+
+        let (wrapped, env) = match self.prepare(command, &args) {
+            Ok(prepared) => prepared,
             Err(reason) => {
                 return Ok(ToolResult {
                     success: false,
@@ -83,6 +276,14 @@ impl Tool for ShellTool {
                     error: Some(reason),
                 });
             }
+        };
+
+        if let Err(reason) = self.security.validate_command_execution(command, approved) {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(reason),
+            });
         }
 
         if !self.security.record_action() {
@@ -98,7 +299,7 @@ impl Tool for ShellTool {
         // (CWE-200), then re-add only safe, functional variables.
         let mut cmd = match self
             .runtime
-            .build_shell_command(command, &self.security.workspace_dir)
+            .build_shell_command(&wrapped, &self.security.workspace_dir)
         {
             Ok(cmd) => cmd,
             Err(e) => {
@@ -110,12 +311,15 @@ impl Tool for ShellTool {
             }
         };
         cmd.env_clear();
+        // Kill the child if the call is cancelled (e.g. the agent's tool timeout)
+        cmd.kill_on_drop(true);
 
         for var in SAFE_ENV_VARS {
             if let Ok(val) = std::env::var(var) {
                 cmd.env(var, val);
             }
         }
+        cmd.envs(env);
 
         let result =
             tokio::time::timeout(Duration::from_secs(SHELL_TIMEOUT_SECS), cmd.output()).await;
@@ -365,4 +569,126 @@ mod tests {
 
         let _ = std::fs::remove_file(std::env::temp_dir().join("zeroclaw_shell_approval_test"));
     }
+
+    fn workspace_tool() -> (tempfile::TempDir, ShellTool) {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("app/src")).unwrap();
+        std::fs::write(tmp.path().join("app/notes.txt"), "from app").unwrap();
+        let security = Arc::new(SecurityPolicy {
+            autonomy: AutonomyLevel::Supervised,
+            workspace_dir: tmp.path().to_path_buf(),
+            ..SecurityPolicy::default()
+        });
+        (tmp, ShellTool::new(security, test_runtime()))
+    }
+
+    #[tokio::test]
+    async fn bare_cd_persists_for_later_calls() {
+        let (_tmp, tool) = workspace_tool();
+        let cd = tool.execute(json!({"command": "cd app"})).await.unwrap();
+        assert!(cd.success, "{:?}", cd.error);
+        assert_eq!(cd.output, "Working directory: app");
+
+        let cat = tool
+            .execute(json!({"command": "cat notes.txt"}))
+            .await
+            .unwrap();
+        assert!(cat.success, "{:?}", cat.error);
+        assert_eq!(cat.output, "from app");
+
+        // Relative cd moves on from the current directory; bare cd resets
+        tool.execute(json!({"command": "cd src"})).await.unwrap();
+        let pwd = tool.execute(json!({"command": "pwd"})).await.unwrap();
+        assert!(pwd.output.trim().ends_with("app/src"), "{}", pwd.output);
+        tool.execute(json!({"command": "cd"})).await.unwrap();
+        let ls = tool.execute(json!({"command": "ls"})).await.unwrap();
+        assert_eq!(ls.output.trim(), "app");
+    }
+
+    #[tokio::test]
+    async fn cwd_argument_resolves_against_current_dir() {
+        let (_tmp, tool) = workspace_tool();
+        tool.execute(json!({"command": "cd app"})).await.unwrap();
+        let pwd = tool
+            .execute(json!({"command": "pwd", "cwd": "src"}))
+            .await
+            .unwrap();
+        assert!(pwd.output.trim().ends_with("app/src"), "{}", pwd.output);
+
+        // A per-call cwd doesn't move the persistent one
+        let cat = tool
+            .execute(json!({"command": "cat notes.txt"}))
+            .await
+            .unwrap();
+        assert_eq!(cat.output, "from app");
+    }
+
+    #[tokio::test]
+    async fn sessions_keep_separate_working_directories() {
+        use crate::tools::SessionContext;
+
+        let (_tmp, tool) = workspace_tool();
+        SessionContext::scope("a", tool.execute(json!({"command": "cd app"})))
+            .await
+            .unwrap();
+
+        let cat = SessionContext::scope("a", tool.execute(json!({"command": "cat notes.txt"})))
+            .await
+            .unwrap();
+        assert_eq!(cat.output, "from app");
+        let ls = SessionContext::scope("b", tool.execute(json!({"command": "ls"})))
+            .await
+            .unwrap();
+        assert_eq!(ls.output.trim(), "app");
+        // Outside any scope the unnamed session is still at the root
+        let ls = tool.execute(json!({"command": "ls"})).await.unwrap();
+        assert_eq!(ls.output.trim(), "app");
+    }
+
+    #[tokio::test]
+    async fn cwd_outside_workspace_is_refused() {
+        let (_tmp, tool) = workspace_tool();
+        let cd = tool.execute(json!({"command": "cd .."})).await.unwrap();
+        assert!(!cd.success);
+        assert!(cd.error.unwrap().contains("escapes workspace"));
+
+        let run = tool
+            .execute(json!({"command": "ls", "cwd": "/"}))
+            .await
+            .unwrap();
+        assert!(!run.success);
+
+        let missing = tool.execute(json!({"command": "cd nope"})).await.unwrap();
+        assert!(!missing.success);
+        assert!(missing.error.unwrap().contains("Not a directory"));
+    }
+
+    #[tokio::test]
+    async fn env_argument_is_passed_and_dangerous_names_refused() {
+        let (_tmp, tool) = workspace_tool();
+        let echo = tool
+            .execute(json!({"command": "echo $GREETING", "env": {"GREETING": "hi there"}}))
+            .await
+            .unwrap();
+        assert!(echo.success, "{:?}", echo.error);
+        assert_eq!(echo.output.trim(), "hi there");
+
+        for name in ["PATH", "LD_PRELOAD", "bad-name"] {
+            let result = tool
+                .execute(json!({"command": "echo x", "env": {name: "v"}}))
+                .await
+                .unwrap();
+            assert!(!result.success, "{name}");
+        }
+    }
+
+    #[test]
+    fn bare_cd_only_matches_plain_cd() {
+        assert_eq!(bare_cd("cd src"), Some("src"));
+        assert_eq!(bare_cd("  cd  "), Some(""));
+        assert_eq!(bare_cd("cd src && ls"), None);
+        assert_eq!(bare_cd("cd $HOME"), None);
+        assert_eq!(bare_cd("cdk deploy"), None);
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+    }
 }
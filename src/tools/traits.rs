@@ -17,6 +17,13 @@ pub struct ToolSpec {
     pub parameters: serde_json::Value,
 }
 
+/// A tool invocation requested by the LLM
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolCall {
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
 /// Core tool trait — implement for any capability
 #[async_trait]
 pub trait Tool: Send + Sync {
@@ -32,6 +39,40 @@ pub trait Tool: Send + Sync {
     /// Execute the tool with given arguments
     async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult>;
 
+    /// Whether calls to this tool may run in parallel with other tool calls
+    /// from the same response. Tools with side effects that later calls may
+    /// depend on (shell commands, file writes) should return `false`.
+    fn concurrency_safe(&self) -> bool {
+        true
+    }
+
+    /// Whether the tool only reads state. Read-only tools still run in
+    /// dry-run mode; every other call is reported instead of executed.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// Whether a failed `result` is worth retrying as is, e.g. after a
+    /// dropped connection. Retries happen up to `[tools] max_retries` before
+    /// the model sees the failure, so only tools whose calls are safe to
+    /// repeat (read-only or idempotent) should return `true`.
+    fn is_retryable(&self, _result: &ToolResult) -> bool {
+        false
+    }
+
+    /// Whether a call with `args`, when its turn is run again, should return
+    /// the first result rather than act again. Side-effecting calls whose
+    /// effects add up (appending, sending, scheduling) should return `true`,
+    /// so a retried turn doesn't do the same thing twice.
+    fn idempotency_key_supported(&self, _args: &serde_json::Value) -> bool {
+        false
+    }
+
+    /// Per-tool execution timeout, overriding `[tools] timeout_secs`.
+    fn timeout(&self) -> Option<std::time::Duration> {
+        None
+    }
+
     /// Get the full spec for LLM registration
     fn spec(&self) -> ToolSpec {
         ToolSpec {
@@ -0,0 +1,463 @@
+use super::traits::{Tool, ToolResult};
+use crate::config::SearchConfig;
+use crate::security::SecurityPolicy;
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Maximum time for one search request.
+const SEARCH_TIMEOUT_SECS: u64 = 20;
+/// Results returned when the call doesn't ask for a count.
+const DEFAULT_COUNT: usize = 5;
+/// Upper bound on `count`, whatever the model asks for.
+const MAX_COUNT: usize = 20;
+/// Error prefix for a failed backend request; searches only read, so these
+/// are retried.
+const SEARCH_FAILED: &str = "Web search failed";
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+const SERPAPI_ENDPOINT: &str = "https://serpapi.com/search.json";
+const TAVILY_ENDPOINT: &str = "https://api.tavily.com/search";
+
+/// One web search hit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+/// A web search API — implement to add a provider
+#[async_trait]
+trait SearchBackend: Send + Sync {
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        count: usize,
+    ) -> anyhow::Result<Vec<SearchResult>>;
+}
+
+/// Build the backend named by `config.provider`, if it is known and has a key
+fn create_backend(config: &SearchConfig) -> Result<Box<dyn SearchBackend>, String> {
+    let provider = config
+        .provider
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .ok_or("Web search is not configured. Set [search] provider = \"brave\", \"serpapi\" or \"tavily\" in config.toml")?;
+    let api_key = config
+        .api_key
+        .clone()
+        .filter(|k| !k.trim().is_empty())
+        .ok_or_else(|| format!("Web search provider '{provider}' needs [search] api_key"))?;
+    let endpoint = |default: &str| {
+        config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| default.to_string())
+    };
+
+    match provider.to_ascii_lowercase().as_str() {
+        "brave" => Ok(Box::new(Brave {
+            api_key,
+            endpoint: endpoint(BRAVE_ENDPOINT),
+        })),
+        "serpapi" => Ok(Box::new(SerpApi {
+            api_key,
+            endpoint: endpoint(SERPAPI_ENDPOINT),
+        })),
+        "tavily" => Ok(Box::new(Tavily {
+            api_key,
+            endpoint: endpoint(TAVILY_ENDPOINT),
+        })),
+        other => Err(format!(
+            "Unknown web search provider '{other}' (expected brave, serpapi or tavily)"
+        )),
+    }
+}
+
+/// Pull `{title, url, snippet}` out of each object in `items`
+fn collect_results(items: Option<&Value>, url_key: &str, snippet_key: &str) -> Vec<SearchResult> {
+    let field = |item: &Value, key: &str| {
+        item.get(key)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    items
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .map(|item| SearchResult {
+            title: field(item, "title"),
+            url: field(item, url_key),
+            snippet: field(item, snippet_key),
+        })
+        .filter(|r| !r.url.is_empty())
+        .collect()
+}
+
+async fn read_json(response: reqwest::Response) -> anyhow::Result<Value> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        anyhow::bail!("search API returned {status}: {}", body.trim());
+    }
+    Ok(response.json().await?)
+}
+
+struct Brave {
+    api_key: String,
+    endpoint: String,
+}
+
+#[async_trait]
+impl SearchBackend for Brave {
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        count: usize,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        let response = client
+            .get(&self.endpoint)
+            .header("X-Subscription-Token", &self.api_key)
+            .header("Accept", "application/json")
+            .query(&[("q", query), ("count", &count.to_string())])
+            .send()
+            .await?;
+        let body = read_json(response).await?;
+        Ok(collect_results(
+            body.pointer("/web/results"),
+            "url",
+            "description",
+        ))
+    }
+}
+
+struct SerpApi {
+    api_key: String,
+    endpoint: String,
+}
+
+#[async_trait]
+impl SearchBackend for SerpApi {
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        count: usize,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        let response = client
+            .get(&self.endpoint)
+            .query(&[
+                ("engine", "google"),
+                ("q", query),
+                ("num", &count.to_string()),
+                ("api_key", &self.api_key),
+            ])
+            .send()
+            .await?;
+        let body = read_json(response).await?;
+        Ok(collect_results(
+            body.get("organic_results"),
+            "link",
+            "snippet",
+        ))
+    }
+}
+
+struct Tavily {
+    api_key: String,
+    endpoint: String,
+}
+
+#[async_trait]
+impl SearchBackend for Tavily {
+    async fn search(
+        &self,
+        client: &reqwest::Client,
+        query: &str,
+        count: usize,
+    ) -> anyhow::Result<Vec<SearchResult>> {
+        let response = client
+            .post(&self.endpoint)
+            .bearer_auth(&self.api_key)
+            .json(&json!({"query": query, "max_results": count}))
+            .send()
+            .await?;
+        let body = read_json(response).await?;
+        Ok(collect_results(body.get("results"), "url", "content"))
+    }
+}
+
+/// Search the web through the provider configured under `[search]`
+pub struct WebSearchTool {
+    security: Arc<SecurityPolicy>,
+    config: SearchConfig,
+    client: reqwest::Client,
+}
+
+impl WebSearchTool {
+    pub fn new(security: Arc<SecurityPolicy>, config: SearchConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(SEARCH_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        Self {
+            security,
+            config,
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for WebSearchTool {
+    fn name(&self) -> &str {
+        "web_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search the web and return matching pages as a JSON list of {title, url, snippet}"
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search query"
+                },
+                "count": {
+                    "type": "integer",
+                    "description": "Number of results to return (1-20)",
+                    "default": DEFAULT_COUNT
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn is_retryable(&self, result: &ToolResult) -> bool {
+        result
+            .error
+            .as_deref()
+            .is_some_and(|e| e.starts_with(SEARCH_FAILED))
+    }
+
+    async fn execute(&self, args: serde_json::Value) -> anyhow::Result<ToolResult> {
+        let query = args
+            .get("query")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("Missing 'query' parameter"))?;
+        let count = args
+            .get("count")
+            .and_then(serde_json::Value::as_u64)
+            .map_or(DEFAULT_COUNT, |c| {
+                usize::try_from(c).unwrap_or(MAX_COUNT).clamp(1, MAX_COUNT)
+            });
+
+        let backend = match create_backend(&self.config) {
+            Ok(backend) => backend,
+            Err(e) => {
+                return Ok(ToolResult {
+                    success: false,
+                    output: String::new(),
+                    error: Some(e),
+                })
+            }
+        };
+
+        if !self.security.record_action() {
+            return Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some("Action blocked: rate limit exceeded".into()),
+            });
+        }
+
+        match backend.search(&self.client, query, count).await {
+            Ok(mut results) => {
+                results.truncate(count);
+                Ok(ToolResult {
+                    success: true,
+                    output: serde_json::to_string_pretty(&results)?,
+                    error: None,
+                })
+            }
+            Err(e) => Ok(ToolResult {
+                success: false,
+                output: String::new(),
+                error: Some(format!("{SEARCH_FAILED}: {e}")),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::Query;
+    use axum::http::HeaderMap;
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+
+    /// Serve `router` on an ephemeral local port, returning its base URL
+    async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        format!("http://{addr}")
+    }
+
+    fn tool(provider: &str, base_url: Option<String>) -> WebSearchTool {
+        WebSearchTool::new(
+            Arc::new(SecurityPolicy::default()),
+            SearchConfig {
+                provider: Some(provider.into()),
+                api_key: Some("test-key".into()),
+                base_url,
+            },
+        )
+    }
+
+    async fn run(tool: &WebSearchTool, args: Value) -> Vec<Value> {
+        let result = tool.execute(args).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        serde_json::from_str(&result.output).unwrap()
+    }
+
+    #[tokio::test]
+    async fn brave_results_are_parsed() {
+        let router = Router::new().route(
+            "/",
+            get(
+                |headers: HeaderMap, Query(q): Query<HashMap<String, String>>| async move {
+                    assert_eq!(headers["x-subscription-token"], "test-key");
+                    assert_eq!(q["q"], "rust async");
+                    assert_eq!(q["count"], "2");
+                    Json(json!({"web": {"results": [
+                        {"title": "Tokio", "url": "https://tokio.rs", "description": "Async runtime"},
+                        {"title": "Async book", "url": "https://rust-lang.github.io/async-book", "description": "Guide"}
+                    ]}}))
+                },
+            ),
+        );
+        let base = serve(router).await;
+
+        let results = run(
+            &tool("brave", Some(base)),
+            json!({"query": "rust async", "count": 2}),
+        )
+        .await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0],
+            json!({"title": "Tokio", "url": "https://tokio.rs", "snippet": "Async runtime"})
+        );
+    }
+
+    #[tokio::test]
+    async fn serpapi_results_are_parsed() {
+        let router = Router::new().route(
+            "/",
+            get(|Query(q): Query<HashMap<String, String>>| async move {
+                assert_eq!(q["api_key"], "test-key");
+                assert_eq!(q["engine"], "google");
+                Json(json!({"organic_results": [
+                    {"title": "Serde", "link": "https://serde.rs", "snippet": "Serialization"},
+                    {"title": "No link"}
+                ]}))
+            }),
+        );
+        let base = serve(router).await;
+
+        let results = run(&tool("serpapi", Some(base)), json!({"query": "serde"})).await;
+        assert_eq!(
+            results,
+            vec![json!({"title": "Serde", "url": "https://serde.rs", "snippet": "Serialization"})]
+        );
+    }
+
+    #[tokio::test]
+    async fn tavily_results_are_parsed() {
+        let router = Router::new().route(
+            "/",
+            post(|headers: HeaderMap, Json(body): Json<Value>| async move {
+                assert_eq!(headers["authorization"], "Bearer test-key");
+                assert_eq!(body["query"], "axum");
+                assert_eq!(body["max_results"], 5);
+                Json(json!({"results": [
+                    {"title": "Axum", "url": "https://docs.rs/axum", "content": "Web framework"}
+                ]}))
+            }),
+        );
+        let base = serve(router).await;
+
+        let results = run(&tool("Tavily", Some(base)), json!({"query": "axum"})).await;
+        assert_eq!(results[0]["snippet"], "Web framework");
+    }
+
+    #[tokio::test]
+    async fn api_errors_become_tool_errors() {
+        let router = Router::new().route(
+            "/",
+            get(|| async { (axum::http::StatusCode::UNAUTHORIZED, "bad key") }),
+        );
+        let base = serve(router).await;
+
+        let result = tool("brave", Some(base))
+            .execute(json!({"query": "x"}))
+            .await
+            .unwrap();
+        assert!(!result.success);
+        let error = result.error.unwrap();
+        assert!(
+            error.contains("401") && error.contains("bad key"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn unset_or_unknown_provider_is_a_clear_error() {
+        let unset =
+            WebSearchTool::new(Arc::new(SecurityPolicy::default()), SearchConfig::default());
+        let result = unset.execute(json!({"query": "x"})).await.unwrap();
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("[search] provider"));
+
+        let result = tool("bing", None)
+            .execute(json!({"query": "x"}))
+            .await
+            .unwrap();
+        assert!(result
+            .error
+            .unwrap()
+            .contains("Unknown web search provider 'bing'"));
+    }
+
+    #[tokio::test]
+    async fn missing_api_key_is_a_clear_error() {
+        let tool = WebSearchTool::new(
+            Arc::new(SecurityPolicy::default()),
+            SearchConfig {
+                provider: Some("brave".into()),
+                ..SearchConfig::default()
+            },
+        );
+        let result = tool.execute(json!({"query": "x"})).await.unwrap();
+        assert!(result.error.unwrap().contains("needs [search] api_key"));
+    }
+}
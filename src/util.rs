@@ -33,13 +33,121 @@
 /// assert_eq!(truncate_with_ellipsis("", 10), "");
 /// ```
 pub fn truncate_with_ellipsis(s: &str, max_chars: usize) -> String {
-    match s.char_indices().nth(max_chars) {
-        Some((idx, _)) => {
-            let truncated = &s[..idx];
+    truncate_smart(s, max_chars, ContentKind::Text)
+}
+
+/// What `truncate_smart` is shortening, which decides where it cuts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// Prose: cut between words when that loses little, then append "..."
+    Text,
+    /// A JSON document: shrink it to a smaller valid document and note the
+    /// truncation after it
+    Json,
+    /// Source code or logs: cut at the end of a line, then "..." on its own
+    /// line
+    Code,
+}
+
+/// Truncate `s` to at most `max_chars` characters of content (the marker
+/// added after it isn't counted), never splitting a UTF-8 character.
+///
+/// `Text` backs up to the last word boundary if that keeps at least 80% of
+/// `max_chars`, `Code` backs up to the last line break if that keeps at
+/// least half. `Json` keeps the output parseable: long strings, arrays and
+/// objects inside it are cut short until it fits, and
+/// `[truncated JSON, N chars]` follows it. Input that isn't valid JSON is
+/// truncated as `Text`.
+///
+/// # Examples
+/// ```
+/// use zeroclaw::util::{truncate_smart, ContentKind};
+///
+/// assert_eq!(
+///     truncate_smart("The quick brown fox jumps", 22, ContentKind::Text),
+///     "The quick brown fox..."
+/// );
+/// assert_eq!(
+///     truncate_smart("let a = 1;\nlet b = 2;\n", 15, ContentKind::Code),
+///     "let a = 1;\n..."
+/// );
+/// ```
+pub fn truncate_smart(s: &str, max_chars: usize, kind: ContentKind) -> String {
+    let Some((cut, _)) = s.char_indices().nth(max_chars) else {
+        return s.to_string();
+    };
+    match kind {
+        ContentKind::Text => {
+            let at_boundary = s[..cut].ends_with(char::is_whitespace)
+                || s[cut..].starts_with(char::is_whitespace);
+            let end = match s[..cut].rfind(char::is_whitespace) {
+                Some(space) if !at_boundary && s[..space].chars().count() * 5 >= max_chars * 4 => {
+                    space
+                }
+                _ => cut,
+            };
             // Trim trailing whitespace for cleaner output
-            format!("{}...", truncated.trim_end())
+            format!("{}...", s[..end].trim_end())
+        }
+        ContentKind::Code => match s[..cut].rfind('\n') {
+            Some(line_end) if s[..line_end].chars().count() * 2 >= max_chars => {
+                format!("{}\n...", s[..line_end].trim_end())
+            }
+            _ => format!("{}...", &s[..cut]),
+        },
+        ContentKind::Json => truncate_json(s, max_chars)
+            .unwrap_or_else(|| truncate_smart(s, max_chars, ContentKind::Text)),
+    }
+}
+
+/// `s` shrunk to a valid JSON document of at most `max_chars` characters,
+/// followed by a note; `None` if `s` isn't JSON or can't be made to fit.
+fn truncate_json(s: &str, max_chars: usize) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(s).ok()?;
+    let render = |limit| shrink_json(&value, limit).to_string();
+    if render(0).chars().count() > max_chars {
+        return None;
+    }
+    // Largest limit that fits; the rendered size grows with it
+    let (mut fits, mut too_big) = (0, max_chars + 1);
+    while too_big - fits > 1 {
+        let mid = fits + (too_big - fits) / 2;
+        if render(mid).chars().count() <= max_chars {
+            fits = mid;
+        } else {
+            too_big = mid;
         }
-        None => s.to_string(),
+    }
+    Some(format!(
+        "{} [truncated JSON, {} chars]",
+        render(fits),
+        s.chars().count()
+    ))
+}
+
+/// Copy of `value` with strings cut to `limit` characters and arrays and
+/// objects to their first `limit` entries.
+fn shrink_json(value: &serde_json::Value, limit: usize) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(text) => match text.char_indices().nth(limit) {
+            Some((cut, _)) => Value::String(format!("{}...", &text[..cut])),
+            None => value.clone(),
+        },
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .take(limit)
+                .map(|item| shrink_json(item, limit))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .take(limit)
+                .map(|(key, item)| (key.clone(), shrink_json(item, limit)))
+                .collect(),
+        ),
+        other => other.clone(),
     }
 }
 
@@ -135,4 +243,65 @@ mod tests {
         // Edge case: max_chars = 0
         assert_eq!(truncate_with_ellipsis("hello", 0), "...");
     }
+
+    #[test]
+    fn test_truncate_smart_never_splits_a_character() {
+        let s = "naïve café 你好世界 🦀🦀 done";
+        for kind in [ContentKind::Text, ContentKind::Json, ContentKind::Code] {
+            for max in 0..=s.chars().count() {
+                let result = truncate_smart(s, max, kind);
+                let kept = result.trim_end_matches("...").trim_end();
+                assert!(s.starts_with(kept), "{kind:?} {max}: {result}");
+                assert!(kept.chars().count() <= max, "{kind:?} {max}: {result}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_truncate_smart_prefers_word_boundary() {
+        assert_eq!(
+            truncate_smart("The quick brown fox jumps", 22, ContentKind::Text),
+            "The quick brown fox..."
+        );
+        // Backing up to the last space would lose too much
+        assert_eq!(
+            truncate_smart("See https://example.com/a/long/path", 20, ContentKind::Text),
+            "See https://example...."
+        );
+        assert_eq!(
+            truncate_smart("Hello 世界 and more", 8, ContentKind::Text),
+            "Hello 世界..."
+        );
+    }
+
+    #[test]
+    fn test_truncate_smart_code_prefers_line_boundary() {
+        let code = "fn main() {\n    println!(\"hi\");\n}\n";
+        assert_eq!(
+            truncate_smart(code, 20, ContentKind::Code),
+            "fn main() {\n..."
+        );
+        assert_eq!(
+            truncate_smart("a\nvery_long_single_line", 10, ContentKind::Code),
+            "a\nvery_lon..."
+        );
+    }
+
+    #[test]
+    fn test_truncate_smart_json_stays_valid() {
+        let json = r#"{"name":"zeroclaw","tags":["a","b","c","d","e"],"note":"xxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"}"#;
+        let result = truncate_smart(json, 40, ContentKind::Json);
+        let (document, note) = result.rsplit_once(" [truncated JSON").unwrap();
+        assert!(document.chars().count() <= 40, "{result}");
+        let parsed: serde_json::Value = serde_json::from_str(document).unwrap();
+        assert!(parsed.is_object());
+        assert_eq!(note, ", 88 chars]");
+
+        assert_eq!(truncate_smart(json, 200, ContentKind::Json), json);
+        // Not JSON: truncated as text
+        assert_eq!(
+            truncate_smart("{not json at all", 9, ContentKind::Json),
+            "{not json..."
+        );
+    }
 }
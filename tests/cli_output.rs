@@ -0,0 +1,89 @@
+//! Output modes of `zeroclaw agent -m`, run against the offline mock provider
+//!
+//! Run with: cargo test --test cli_output
+
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+use zeroclaw::security::AutonomyLevel;
+use zeroclaw::Config;
+
+/// Write a config for `home` that scripts the mock provider with `responses`.
+fn write_config(home: &Path, responses: &[&str]) {
+    let dir = home.join(".zeroclaw");
+    std::fs::create_dir_all(dir.join("workspace")).unwrap();
+    let mut config = Config {
+        workspace_dir: dir.join("workspace"),
+        config_path: dir.join("config.toml"),
+        default_provider: Some("mock".into()),
+        default_model: Some("mock-model".into()),
+        ..Config::default()
+    };
+    config.autonomy.level = AutonomyLevel::Full;
+    config.memory.backend = "none".into();
+    config.mock.responses = responses.iter().map(ToString::to_string).collect();
+    config.save().unwrap();
+}
+
+fn run_agent(home: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_zeroclaw"))
+        .arg("agent")
+        .args(args)
+        .env("HOME", home)
+        .env_remove("ZEROCLAW_PROVIDER")
+        .env_remove("ZEROCLAW_MODEL")
+        .env_remove("ZEROCLAW_WORKSPACE")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+const RESPONSES: &[&str] = &[
+    r#"Saving it now. <tool_call>{"name": "file_write", "arguments": {"path": "note.txt", "content": "hello"}}</tool_call>"#,
+    "Saved the note.",
+];
+
+#[test]
+fn json_output_is_a_single_object() {
+    let home = TempDir::new().unwrap();
+    write_config(home.path(), RESPONSES);
+
+    let output = run_agent(home.path(), &["-m", "Save a note", "--json"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let report: serde_json::Value = serde_json::from_str(stdout.trim())
+        .unwrap_or_else(|e| panic!("stdout is not one JSON object ({e}): {stdout}"));
+
+    for key in ["response", "tool_calls", "usage", "duration", "artifacts"] {
+        assert!(report.get(key).is_some(), "missing {key}: {report}");
+    }
+    assert_eq!(report["response"], "Saved the note.");
+    let calls = report["tool_calls"].as_array().unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0]["name"], "file_write");
+    assert_eq!(calls[0]["success"], true);
+    assert!(report["duration"].as_f64().unwrap() >= 0.0);
+    assert_eq!(
+        std::fs::read_to_string(home.path().join(".zeroclaw/workspace/note.txt")).unwrap(),
+        "hello"
+    );
+}
+
+#[test]
+fn quiet_output_is_only_the_answer() {
+    let home = TempDir::new().unwrap();
+    write_config(home.path(), RESPONSES);
+
+    let output = run_agent(home.path(), &["-m", "Save a note", "--quiet"]);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "Saved the note.\n"
+    );
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("Saving it now."), "{stderr}");
+}
@@ -0,0 +1,158 @@
+//! Integration tests for the SQLite memory backend
+//!
+//! Run with: cargo test --test memory_sqlite
+
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use zeroclaw::memory::{sqlite::SqliteMemory, Memory, MemoryCategory};
+
+async fn seeded(dir: &std::path::Path) -> SqliteMemory {
+    let mem = SqliteMemory::new(dir).expect("SQLite init failed");
+    let entries = [
+        (
+            "lang",
+            "User prefers Rust for systems programming",
+            MemoryCategory::Core,
+        ),
+        (
+            "editor",
+            "User edits Rust code in Helix",
+            MemoryCategory::Core,
+        ),
+        (
+            "standup",
+            "Daily standup moved to 10am",
+            MemoryCategory::Daily,
+        ),
+        ("lunch", "Had ramen for lunch", MemoryCategory::Daily),
+        (
+            "chat_1",
+            "Asked about Rust async runtimes and Rust lifetimes",
+            MemoryCategory::Conversation,
+        ),
+        (
+            "recipe",
+            "Grandma's lasagna recipe",
+            MemoryCategory::Custom("recipes".into()),
+        ),
+    ];
+    for (key, content, category) in entries {
+        mem.store(key, content, category).await.unwrap();
+    }
+    mem
+}
+
+#[tokio::test]
+async fn recall_ranks_best_match_first_and_honors_limit() {
+    let tmp = TempDir::new().unwrap();
+    let mem = seeded(tmp.path()).await;
+
+    let results = mem.recall("Rust lifetimes", 10).await.unwrap();
+    assert!(!results.is_empty());
+    assert_eq!(results[0].key, "chat_1");
+    assert!(results.iter().all(|e| e.content.contains("Rust")));
+    let scores: Vec<f64> = results.iter().map(|e| e.score.unwrap()).collect();
+    assert!(
+        scores.windows(2).all(|w| w[0] >= w[1]),
+        "not ranked: {scores:?}"
+    );
+
+    let limited = mem.recall("Rust", 2).await.unwrap();
+    assert_eq!(limited.len(), 2);
+}
+
+#[tokio::test]
+async fn recall_spans_categories() {
+    let tmp = TempDir::new().unwrap();
+    let mem = seeded(tmp.path()).await;
+
+    let results = mem.recall("Rust", 10).await.unwrap();
+    let keys: Vec<&str> = results.iter().map(|e| e.key.as_str()).collect();
+    assert!(keys.contains(&"lang"));
+    assert!(keys.contains(&"chat_1"));
+    assert!(!keys.contains(&"lunch"));
+}
+
+#[tokio::test]
+async fn list_filters_by_category() {
+    let tmp = TempDir::new().unwrap();
+    let mem = seeded(tmp.path()).await;
+
+    let core = mem.list(Some(&MemoryCategory::Core)).await.unwrap();
+    assert_eq!(core.len(), 2);
+    assert!(core.iter().all(|e| e.category == MemoryCategory::Core));
+
+    let daily = mem.list(Some(&MemoryCategory::Daily)).await.unwrap();
+    assert_eq!(daily.len(), 2);
+
+    let custom = mem
+        .list(Some(&MemoryCategory::Custom("recipes".into())))
+        .await
+        .unwrap();
+    assert_eq!(custom.len(), 1);
+    assert_eq!(custom[0].key, "recipe");
+
+    assert_eq!(mem.list(None).await.unwrap().len(), 6);
+}
+
+#[tokio::test]
+async fn store_upserts_by_key() {
+    let tmp = TempDir::new().unwrap();
+    let mem = seeded(tmp.path()).await;
+
+    mem.store("lang", "User now prefers Zig", MemoryCategory::Core)
+        .await
+        .unwrap();
+
+    assert_eq!(mem.count().await.unwrap(), 6);
+    let entry = mem.get("lang").await.unwrap().unwrap();
+    assert_eq!(entry.content, "User now prefers Zig");
+    // The full-text index follows the update
+    let results = mem.recall("Zig", 5).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].key, "lang");
+}
+
+#[tokio::test]
+async fn entries_persist_in_workspace_dir() {
+    let tmp = TempDir::new().unwrap();
+    drop(seeded(tmp.path()).await);
+
+    assert!(tmp.path().join("memory").join("brain.db").exists());
+    let reopened = SqliteMemory::new(tmp.path()).unwrap();
+    assert_eq!(reopened.count().await.unwrap(), 6);
+    assert_eq!(reopened.recall("ramen", 5).await.unwrap()[0].key, "lunch");
+}
+
+#[tokio::test]
+async fn concurrent_stores_from_tasks() {
+    let tmp = TempDir::new().unwrap();
+    let mem = Arc::new(SqliteMemory::new(tmp.path()).unwrap());
+
+    let handles: Vec<_> = (0..16)
+        .map(|i| {
+            let mem = Arc::clone(&mem);
+            tokio::spawn(async move {
+                mem.store(
+                    &format!("task_{i}"),
+                    &format!("note from task {i}"),
+                    MemoryCategory::Conversation,
+                )
+                .await
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    assert_eq!(mem.count().await.unwrap(), 16);
+    assert_eq!(
+        mem.list(Some(&MemoryCategory::Conversation))
+            .await
+            .unwrap()
+            .len(),
+        16
+    );
+}
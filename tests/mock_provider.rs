@@ -0,0 +1,74 @@
+//! End-to-end agent loop driven by the offline mock provider
+//!
+//! Run with: cargo test --test mock_provider
+
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use zeroclaw::agent::AgentRunner;
+use zeroclaw::memory::{self, Memory};
+use zeroclaw::observability::NoopObserver;
+use zeroclaw::providers;
+use zeroclaw::security::AutonomyLevel;
+use zeroclaw::Config;
+
+fn mock_config(workspace: &TempDir, responses: &[&str]) -> Config {
+    let mut config = Config {
+        workspace_dir: workspace.path().to_path_buf(),
+        config_path: workspace.path().join("config.toml"),
+        default_provider: Some("mock".into()),
+        default_model: Some("mock-model".into()),
+        ..Config::default()
+    };
+    config.autonomy.level = AutonomyLevel::Full;
+    config.memory.backend = "none".into();
+    config.mock.responses = responses.iter().map(ToString::to_string).collect();
+    config
+}
+
+#[tokio::test]
+async fn mock_provider_drives_two_tool_calls_to_completion() {
+    let workspace = TempDir::new().unwrap();
+    let config = mock_config(
+        &workspace,
+        &[
+            // Iteration 1: tool call embedded in text
+            r#"Writing the note. <tool_call>{"name": "file_write", "arguments": {"path": "note.txt", "content": "hello from mock"}}</tool_call>"#,
+            // Iteration 2: native tool call
+            r#"{"text": "Reading it back.", "tool_calls": [{"name": "file_read", "arguments": {"path": "note.txt"}}]}"#,
+            // Iteration 3: script exhausted, so the provider echoes the tool
+            // results it was sent
+        ],
+    );
+
+    let provider = providers::create_routed_provider(
+        config.default_provider.as_deref().unwrap(),
+        None,
+        &config.reliability,
+        &config.model_routes,
+        config.default_model.as_deref().unwrap(),
+        &providers::ProviderOptions::from_config(&config),
+        Arc::new(NoopObserver),
+    )
+    .unwrap();
+    let mem: Arc<dyn Memory> =
+        Arc::from(memory::create_memory(&config.memory, &config.workspace_dir, None).unwrap());
+    let runner = AgentRunner::with_provider(&config, "mock", provider, mem).unwrap();
+
+    let reply = runner
+        .respond("test", &[], "Save a note and read it back")
+        .await
+        .unwrap()
+        .text;
+
+    assert_eq!(
+        std::fs::read_to_string(workspace.path().join("note.txt")).unwrap(),
+        "hello from mock"
+    );
+    assert!(reply.starts_with("[Tool Results]"), "{reply}");
+    assert!(
+        reply.contains(r#"<tool_result name="file_read">"#),
+        "{reply}"
+    );
+    assert!(reply.contains("hello from mock"), "{reply}");
+}
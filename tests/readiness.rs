@@ -0,0 +1,100 @@
+//! `/healthz` and `/readyz` probes, served over real HTTP
+//!
+//! Run with: cargo test --test readiness
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+use zeroclaw::health::Probes;
+use zeroclaw::memory::{MarkdownMemory, Memory};
+use zeroclaw::providers::{Provider, WarmupStatus};
+
+/// Provider whose warmup reports a fixed reachability.
+struct StubProvider {
+    reachable: bool,
+}
+
+#[async_trait]
+impl Provider for StubProvider {
+    async fn chat_with_system(
+        &self,
+        _system_prompt: Option<&str>,
+        _message: &str,
+        _model: &str,
+        _temperature: f64,
+    ) -> anyhow::Result<String> {
+        Ok("ok".into())
+    }
+
+    async fn warmup(&self) -> WarmupStatus {
+        WarmupStatus::check(async {
+            anyhow::ensure!(self.reachable, "credentials rejected");
+            Ok("credentials accepted".to_string())
+        })
+        .await
+    }
+}
+
+/// Serve `probes` on an ephemeral port and return its base URL.
+async fn serve(probes: Probes) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, probes.router()).await });
+    format!("http://{addr}")
+}
+
+async fn get(url: &str) -> (u16, serde_json::Value) {
+    let response = reqwest::get(url).await.unwrap();
+    let status = response.status().as_u16();
+    (status, response.json().await.unwrap())
+}
+
+#[tokio::test]
+async fn readyz_reports_each_subsystem() {
+    let workspace = TempDir::new().unwrap();
+    let memory: Arc<dyn Memory> = Arc::new(MarkdownMemory::new(workspace.path()));
+
+    let healthy = serve(Probes::new(
+        Arc::new(StubProvider { reachable: true }),
+        memory.clone(),
+    ))
+    .await;
+    let (status, body) = get(&format!("{healthy}/readyz")).await;
+    assert_eq!(status, 200, "{body}");
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["failed"], serde_json::json!([]));
+    assert_eq!(body["checks"][0]["detail"], "credentials accepted");
+
+    let unreachable = serve(Probes::new(
+        Arc::new(StubProvider { reachable: false }),
+        memory,
+    ))
+    .await;
+    let (status, body) = get(&format!("{unreachable}/readyz")).await;
+    assert_eq!(status, 503, "{body}");
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["failed"], serde_json::json!(["provider"]));
+    assert_eq!(body["checks"][0]["detail"], "credentials rejected");
+    assert_eq!(body["checks"][1]["ready"], true);
+
+    // Liveness doesn't depend on the subsystems
+    let (status, body) = get(&format!("{unreachable}/healthz")).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["status"], "ok");
+}
+
+#[tokio::test]
+async fn readyz_fails_when_memory_is_unreachable() {
+    let workspace = TempDir::new().unwrap();
+    let missing = workspace.path().join("gone");
+    let url = serve(Probes::new(
+        Arc::new(StubProvider { reachable: true }),
+        Arc::new(MarkdownMemory::new(&missing)),
+    ))
+    .await;
+
+    let (status, body) = get(&format!("{url}/readyz")).await;
+    assert_eq!(status, 503, "{body}");
+    assert_eq!(body["failed"], serde_json::json!(["memory"]));
+}